@@ -117,6 +117,31 @@ vcx_error_t vcx_agent_update_info(vcx_command_handle_t command_handle,
 ///                                                             "agent_did": string,
 ///                                                             "agent_vk": string,
 ///                                                         }
+///         "envelope_version": Option<"string"> - format of envelopes used for the connection: "v1" (DIDComm v1) or "v2" (DIDComm v2).
+///                                                default value is taken from `didcomm_envelope_version` config option (or "v1").
+///                                                "v2" is used only if the remote side publishes its keyAgreement keys as well.
+///         "return_route": Option<"string"> - `return_route` transport decorator added to every outbound message: "all", "thread" or "none" (default).
+///                                            replies the remote side returns in HTTP responses are handled as received messages
+///                                            by the next `update_state` call of the object waiting for them.
+///         "delivery_policy": Option<JSON object> - HTTP delivery policy for messages sent over the connection (same format as `http_delivery_policy` config option).
+///                                                  default value is taken from `http_delivery_policy` config option.
+///         "handshake_protocols": Option<["string"]> - handshake protocols to list in Out-of-Band Invitation in order of preference:
+///                                                     "connections" (Connections 1.0) or "didexchange" (DID Exchange 1.0).
+///                                                     default value is ["connections"]. Ignored for Invitations of Connections protocol.
+///         "peer_did": Option<"string"> - peer DID method used for the DIDDoc sent in connection request/response: "did:peer:2" or "did:peer:4".
+///                                        DIDDoc is bound to unqualified pairwise DID by default.
+///         "public_did_service": Option<bool> - Out-of-Band Invitation refers to the public DID as its only service instead of embedding
+///                                              the keys of the pairwise agent. Requires `use_public_did`. The invitee resolves endpoint
+///                                              and key of the DID from the Ledger. default value for `public_did_service`=false
+///         "multi_use": Option<bool> - invitation can be accepted by many invitees. Every received request is accepted by a new Connection
+///                                     object with its own pairwise keys, while this one stays in OfferSent state (see `vcx_connection_get_children`).
+///                                     Not supported for Out-of-Band invitations without handshake. default value for `multi_use`=false
+///         "invitation_ttl": Option<u64> - number of seconds the created invitation is valid for (`~timing.expires_time` decorator).
+///                                         requests received for the expired invitation are rejected with a problem report.
+///                                         Connection accepting the expired invitation fails. The invitation never expires by default.
+///         "handshake_timeout": Option<u64> - number of seconds the Connection can stay in every handshake state (OfferSent, RequestReceived).
+///                                            The Connection moves to the failed state (None) and sends a problem report to the other side
+///                                            on the next `vcx_connection_update_state` call after the timeout. No timeout by default.
 ///     }
 /// # Examples connection_options ->
 /// "{"connection_type":"SMS","phone":"123","use_public_did":true, "update_agent_info": Option<true>}"
@@ -333,6 +358,9 @@ vcx_error_t vcx_connection_accept_connection_invite(vcx_command_handle_t command
 ///                 "serviceEndpoint": string
 ///             }
 ///         ] - an item that is the equivalent of the service block of a DIDDoc that the receiver is to use in responding to the message.
+///               Items can also be DIDs resolving to such service block: did:peer:2, long form did:peer:4,
+///               or public did:sov / did:indy DIDs (verkey and `endpoint` ATTRIB are read from the Ledger).
+///               If the invitation has no services, its `public_did` is resolved instead.
 ///     }
 ///
 /// # Returns
@@ -545,6 +573,350 @@ vcx_error_t vcx_connection_send_discovery_features(vcx_u32_t command_handle,
                                                    const char* comment,
                                                    void (*cb)(vcx_command_handle_t, vcx_error_t));
 
+/// Rotate pairwise DID of the connection (Aries RFC 0794 DID Rotate).
+///
+/// Creates a new pairwise DID with keys and sends a `rotate` message with its DIDDoc signed by the current key.
+/// The current DID keeps receiving messages until the remote side acknowledges the rotation
+/// (the ack is received by `vcx_connection_update_state`), after that it is deleted.
+/// If the remote side rejects the rotation with a problem report, the connection goes back to the current DID.
+///
+/// Rotations started by the remote side are applied automatically by `vcx_connection_update_state`:
+/// the DIDDoc of the connection is replaced and the `ack` is sent back.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: connection to rotate DID for.
+///                    Note that connection must be in Accepted state and must not have pending rotation.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_rotate_did(vcx_u32_t command_handle,
+                                      vcx_connection_handle_t connection_handle,
+                                      void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+/// Send the current service endpoint and routing keys of the connection to the remote side.
+///
+/// Use it after the mediator or the Agency endpoint has changed: existing connections keep advertising
+/// the endpoint and routing keys of the DIDDoc sent during the handshake until the remote side receives the update.
+/// The connection takes the routing of the currently granted mediation (see `vcx_connection_request_mediation`)
+/// or the Agency endpoint and verkey of the current library configuration,
+/// and sends an `update` message with its DIDDoc signed by the pairwise key.
+/// The pairwise DID and keys stay the same.
+///
+/// Updates sent by the remote side are applied automatically by `vcx_connection_update_state`:
+/// the DIDDoc of the connection is replaced if the update is signed by the remote pairwise key and keeps its DID and recipient keys,
+/// then the `ack` is sent back. Other updates are rejected with a problem report.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+/// Service of one-time connections and connections using peer DIDs cannot be updated (rotate the DID instead).
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: connection to update service for.
+///                    Note that connection must be in Accepted state and must not have pending DID rotation.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_update_service(vcx_u32_t command_handle,
+                                          vcx_connection_handle_t connection_handle,
+                                          void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+/// Send the current service endpoint and routing keys to the remote side of every Aries connection in Accepted state.
+///
+/// Works as `vcx_connection_update_service` called for each connection.
+/// Failure to update one connection does not stop the update of the others.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides json with handles of updated connections and connections failed to be updated
+///
+/// # Example
+/// result -> {"updated": [2345, 3456], "failed": [4567]}
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_update_service_all(vcx_u32_t command_handle,
+                                              void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Get Connections spawned by the multi-use invitation.
+///
+/// Connection created with `multi_use` option (see `vcx_connection_connect`) stays in OfferSent state,
+/// every request received for its invitation by `vcx_connection_update_state` or `vcx_connection_update_state_with_message`
+/// is accepted by a new Connection object sharing the invitation but having its own pairwise keys.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle of the multi-use invitation.
+///
+/// cb: Callback that provides json array of handles of the spawned Connection objects
+///
+/// # Example
+/// children -> [2345, 3456]
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_get_children(vcx_u32_t command_handle,
+                                        vcx_connection_handle_t connection_handle,
+                                        void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Delete all Connections spawned by the multi-use invitation from the agency and release their handles.
+/// The invitation keeps accepting new requests until its Connection is deleted with `vcx_connection_delete_connection`.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle of the multi-use invitation.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_close_children(vcx_u32_t command_handle,
+                                          vcx_connection_handle_t connection_handle,
+                                          void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+/// Get message history records of the Connection.
+/// Records are stored only if `message_history` setting is set to `true` (see `vcx_init_with_config`).
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle to get message history for.
+///
+/// query: Optional<string> - filter of records (see `vcx_get_message_history`). `connection` field is ignored.
+///     Pass NULL or "{}" to get all records of the Connection.
+///
+/// cb: Callback that provides the list of records ordered by time (see `vcx_get_message_history`)
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_get_message_history(vcx_command_handle_t command_handle,
+                                               vcx_connection_handle_t connection_handle,
+                                               const char *query,
+                                               void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Store the Connection in the wallet or update its record if the Connection is already stored.
+///
+/// The record holds the serialized Connection (see `vcx_connection_serialize`) and tags describing the relationship.
+/// Tags set by the library are refreshed whenever the stored Connection changes its state or rotates its pairwise DID:
+///     "my_did": string - pairwise DID of the Connection
+///     "their_did": string - DID of the remote side (once it is known)
+///     "their_public_did": string - public DID of the inviter (if the invitation refers to it)
+///     "source_id": string - source id of the Connection
+///     "state": string - state of the Connection (see `vcx_connection_get_state`)
+///     "invitation_id": string - id of the invitation the Connection was created with
+///     "label": string - label of the invitation
+///     "goal_code": string - goal code of Out-of-Band invitation
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle to store.
+///
+/// tags: Optional<string> - JSON object of user-defined string tags (names of the library tags cannot be used).
+///     Tag names starting with `~` are stored unencrypted and can be used in WQL comparison operators.
+///     Pass NULL to keep the user-defined tags of already stored Connection.
+///
+/// cb: Callback that provides id of the wallet record (identifier of the Connection kept on DID rotation,
+///     the same for every `vcx_connection_store` call on the Connection)
+///
+/// # Example
+/// tags -> {"group": "partners", "~priority": "1"}
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_store(vcx_command_handle_t command_handle,
+                                 vcx_connection_handle_t connection_handle,
+                                 const char *tags,
+                                 void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Load the Connection stored in the wallet (see `vcx_connection_store`) and create a new Connection object.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// id: id of the wallet record returned by `vcx_connection_store` or `vcx_connection_search`.
+///
+/// cb: Callback that provides Connection handle
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_load(vcx_command_handle_t command_handle,
+                                const char *id,
+                                void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_connection_handle_t));
+
+/// Search Connections stored in the wallet (see `vcx_connection_store`).
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// query: Optional<string> - WQL query over record tags (see `vcx_connection_store`).
+///     Pass NULL or "{}" to get all stored Connections.
+///
+/// cb: Callback that provides the list of stored Connections
+///     [
+///         {
+///             "id": string, // id of the wallet record to use in `vcx_connection_load`
+///             "tags": {"tag_name": "tag_value", ...}, // library and user-defined tags
+///         }
+///     ]
+///
+/// # Example
+/// query -> {"their_did": "did:peer:2.Ez6LS...", "state": "4"}
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_search(vcx_command_handle_t command_handle,
+                                  const char *query,
+                                  void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Delete the Connection record from the wallet (see `vcx_connection_store`).
+/// Connection objects loaded from the record are not affected.
+/// NOTE: the record is deleted automatically when the Connection is deleted with `vcx_connection_delete_connection`.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// id: id of the wallet record returned by `vcx_connection_store` or `vcx_connection_search`.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_delete_stored(vcx_command_handle_t command_handle,
+                                         const char *id,
+                                         void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+/// Send mediation request (Aries RFC 0211 Coordinate Mediation) to the mediator over the specified connection.
+///
+/// Once the mediator grants the request (the grant is received by `vcx_connection_update_state`),
+/// all new connections register their recipient keys on the mediator and publish its endpoint and routing keys
+/// in their DIDDoc instead of the Agency ones.
+/// Connections created before keep using the routing they were created with until `vcx_connection_update_service` is called for them.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: connection with the mediator.
+///                    Note that connection must be in Accepted state.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_connection_request_mediation(vcx_u32_t command_handle,
+                                             vcx_connection_handle_t connection_handle,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+/// Get the state of the mediation requested with `vcx_connection_request_mediation`.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides mediation information
+///
+/// # Example
+/// info ->
+///     {
+///         "mediator_did": string, - DID of the mediator connection
+///         "state": string, - one of "requested", "granted", "denied"
+///         "endpoint": Optional<string>, - mediator endpoint (set if "granted")
+///         "routing_keys": Optional<array<string>>, - mediator routing keys (set if "granted")
+///         "recipient_keys": array<string>, - recipient keys registered on the mediator
+///         "live_delivery": bool - whether live delivery mode is enabled on the mediator
+///     }
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_get_mediation_info(vcx_command_handle_t command_handle,
+                                   void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Get the status of the message queue kept by the granted mediator (Message Pickup protocol).
+///
+/// Note that messages queued on the mediator are picked up automatically on connection state updates.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides status of the queue
+///
+/// # Example
+/// status ->
+///     {
+///         "@type": "https://didcomm.org/messagepickup/2.0/status",
+///         "@id": string,
+///         "recipient_key": Optional<string>, - recipient key the status is related to
+///         "message_count": int, - number of messages in the queue
+///         "longest_waited_seconds": Optional<int>, - age of the oldest message in the queue
+///         "newest_received_time": Optional<string>, - time of receipt of the newest message
+///         "oldest_received_time": Optional<string>, - time of receipt of the oldest message
+///         "total_bytes": Optional<int>, - size of all messages in the queue
+///         "live_delivery": Optional<bool> - whether live delivery mode is enabled
+///     }
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_get_pickup_status(vcx_command_handle_t command_handle,
+                                  void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Enable or disable live delivery mode on the granted mediator (Message Pickup protocol).
+/// In live mode the mediator sends messages to the endpoint of the connection with the mediator
+/// as soon as they are received instead of keeping them in the queue.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// live_delivery: whether live delivery mode should be enabled.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_set_pickup_live_delivery(vcx_command_handle_t command_handle,
+                                         vcx_bool_t live_delivery,
+                                         void (*cb)(vcx_command_handle_t, vcx_error_t));
+
 /// Send a message to reuse existing Connection instead of setting up a new one
 /// as response on received Out-of-Band Invitation.
 ///
@@ -594,6 +966,9 @@ vcx_error_t vcx_connection_send_discovery_features(vcx_u32_t command_handle,
 ///                 "serviceEndpoint": string
 ///             }
 ///         ] - an item that is the equivalent of the service block of a DIDDoc that the receiver is to use in responding to the message.
+///               Items can also be DIDs resolving to such service block: did:peer:2, long form did:peer:4,
+///               or public did:sov / did:indy DIDs (verkey and `endpoint` ATTRIB are read from the Ledger).
+///               If the invitation has no services, its `public_did` is resolved instead.
 ///     }
 ///
 /// #Returns
@@ -839,6 +1214,56 @@ vcx_error_t vcx_credential_create_with_offer(vcx_command_handle_t command_handle
                                           const char *offer,
                                           void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
 
+// Create a Credential object that starts the issuance by proposing a credential to an institution (Aries protocol only)
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Institution's personal identification for the credential, should be unique.
+//
+// proposal: proposed credential data
+// {
+//     "protocol_version": Optional<string> - version of Issue Credential protocol to use: "1.0" (default) or "2.0",
+//     "attributes": {"<attribute_name>": "<attribute_value>", ...} - proposed attribute values (credential preview),
+//     "schema_id": Optional<string>,
+//     "cred_def_id": Optional<string>,
+//     // filters below are supported by "2.0" protocol only
+//     "schema_issuer_did": Optional<string>,
+//     "schema_name": Optional<string>,
+//     "schema_version": Optional<string>,
+//     "issuer_did": Optional<string>
+// }
+//
+// comment: (Optional) comment sent with the proposal.
+//
+// cb: Callback that provides credential handle or error status
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credential_create_proposal(vcx_command_handle_t command_handle,
+                                        const char *source_id,
+                                        const char *proposal,
+                                        const char *comment,
+                                        void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_credential_handle_t));
+
+// Send a credential proposal to the connection, called after having created a Credential object with `vcx_credential_create_proposal`.
+//
+// #params
+// command_handle: command handle to map callback to user context
+//
+// credential_handle: credential handle that was provided during creation. Used to identify credential object
+//
+// connection_handle: Connection handle that identifies pairwise connection
+//
+// cb: Callback that provides error status of sending the proposal
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credential_send_proposal(vcx_command_handle_t command_handle,
+                                      vcx_credential_handle_t credential_handle,
+                                      vcx_connection_handle_t connection_handle,
+                                      void (*cb)(vcx_command_handle_t, vcx_error_t));
+
 /// Accept credential for the given offer.
 ///
 /// This function performs the following actions:
@@ -1168,7 +1593,15 @@ vcx_error_t vcx_credential_get_problem_report(vcx_command_handle_t command_handl
 ///     "schema_id": string,
 ///     "cred_def_id": string,
 ///     "rev_reg_id": Optional<string>,
-///     "cred_rev_id": Optional<string>
+///     "cred_rev_id": Optional<string>,
+///     "revoked": bool, // whether the issuer sent notification about revocation of the credential
+///     "revocation": Optional<{ // received revocation notification
+///         "cred_id": string,
+///         "rev_reg_id": Optional<string>,
+///         "cred_rev_id": Optional<string>,
+///         "comment": Optional<string>,
+///         "received": string // UTC time in RFC 3339 format
+///     }>
 /// }
 ///
 /// #Returns
@@ -1229,6 +1662,8 @@ vcx_error_t vcx_credentialdef_create(vcx_command_handle_t command_handle,
 ///     support_revocation: true|false - Optional, by default its false
 ///     tails_file: path to tails file - Optional if support_revocation is false
 ///     max_creds: size of tails file - Optional if support_revocation is false
+///     rotation_threshold: number of credentials left in the revocation registry at which the next registry is created and published
+///         - Optional, by default 10% of max_creds
 ///
 /// endorser: DID of the Endorser that will submit the transaction.
 ///
@@ -1301,6 +1736,22 @@ vcx_error_t vcx_credentialdef_get_cred_def_id(vcx_command_handle_t command_handl
                                            vcx_credential_handle_t cred_def_handle,
                                            void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+// Retrieves revocation registries of the credential definition
+//
+// #Params
+// cred_def_handle: CredDef handle that was provided during creation. Used to access credentialdef object
+//
+// cb: Callback that provides revocation registries json and provides error status
+//     [{"rev_reg_id": string, "rev_reg_def": string, "tails_file": string, "max_creds": number, "issued": number}]
+//     `issued` is the number of credentials created against the registry (credentials offered but not issued yet are not counted).
+//     The registry used for issuing is the first one with `issued` less than `max_creds`.
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_credentialdef_get_rev_regs(vcx_command_handle_t command_handle,
+                                           vcx_credentialdef_handle_t cred_def_handle,
+                                           void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 // Retrieve the txn associated with paying for the credential_def
 //
 // #param
@@ -1959,6 +2410,36 @@ vcx_error_t vcx_issuer_create_credential(vcx_command_handle_t command_handle,
                                       vcx_payment_handle_t price,
                                       void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_handle_t));
 
+// Create a Issuer Credential object answering the credential proposal received from the user (Aries protocol only).
+// Call `vcx_issuer_send_credential_offer` to reply with the credential offer on the thread of the proposal.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// source_id: Enterprise's personal identification for the user.
+//
+// cred_def_handle: handle of credential definition to issue the credential with
+//     It must satisfy the `cred_def_id`, `issuer_did` and schema filters of the proposal.
+//
+// proposal: received credential proposal message
+//
+// credential_data: (Optional) data attributes offered to person in the credential.
+//     The attribute values of the proposal are offered if not set.
+//
+// credential_name: Name of the credential - ex. Drivers Licence
+//
+// cb: Callback that provides credential handle and error status of request
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_create_credential_from_proposal(vcx_command_handle_t command_handle,
+                                                    const char *source_id,
+                                                    vcx_credentialdef_handle_t cred_def_handle,
+                                                    const char *proposal,
+                                                    const char *credential_data,
+                                                    const char *credential_name,
+                                                    void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_issuer_credential_handle_t));
+
 // Takes a json string representing an issuer credential object and recreates an object matching the json
 //
 // #Params
@@ -2091,6 +2572,122 @@ vcx_error_t vcx_issuer_send_credential(vcx_command_handle_t command_handle,
                                     vcx_connection_handle_t connection_handle,
                                     void (*cb)(vcx_command_handle_t, vcx_error_t));
 
+// Revoke Credential and publish the revocation to the Ledger.
+// Revocations of the same revocation registry made by `vcx_issuer_revoke_credential_local` before are published as well.
+// If the credential is revoked in the wallet but publishing fails, the error is returned and the revocation stays pending:
+// the credential is reported as revoked and calling this function again publishes the pending revocation.
+//
+// Supported for both proprietary and Aries credentials issued with a revocable credential definition.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+//
+// cb: Callback that provides error status of revoking the credential
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_revoke_credential(vcx_command_handle_t command_handle,
+                                      vcx_issuer_credential_handle_t credential_handle,
+                                      void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+// Revoke Credential in the wallet without publishing the revocation to the Ledger.
+// The revocation is added to the pending revocations of the revocation registry, which are published at once:
+//  - with the next call of `vcx_issuer_revoke_credential` for a credential of the same revocation registry
+//  - by `vcx_issuer_publish_pending_revocations`
+//  - in background once `revocation_publish_interval` seconds (config option) passed since the first pending revocation
+// The revocation is recorded in the wallet before the credential is revoked, so a revocation interrupted by a process stop is completed
+// on the next access to the pending revocations of the registry.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+//
+// cb: Callback that provides error status of revoking the credential
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_revoke_credential_local(vcx_command_handle_t command_handle,
+                                            vcx_issuer_credential_handle_t credential_handle,
+                                            void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+// Check whether the credential has been revoked by `vcx_issuer_revoke_credential` or `vcx_issuer_revoke_credential_local`.
+// A credential can be revoked only once, the following revocations return InvalidState error.
+//
+// NOTE: Supported for `aries` Issuer Credential objects only.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+//
+// cb: Callback that provides whether the credential has been revoked
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_credential_is_revoked(vcx_command_handle_t command_handle,
+                                             vcx_issuer_credential_handle_t credential_handle,
+                                             void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_bool_t));
+
+// Notify the holder about revocation of the credential (made by `vcx_issuer_revoke_credential` or `vcx_issuer_revoke_credential_local`).
+// Sends `RevocationNotification` message over the connection the credential was issued on.
+// The credential must be revoked.
+//
+// NOTE: Supported for `aries` Issuer Credential objects only.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+//
+// options: (Optional) notification options as JSON string
+//     {
+//         "version": Optional<string> - version of Revocation Notification protocol: "1.0" (default) or "2.0"
+//         "comment": Optional<string> - comment for the holder
+//     }
+//
+// cb: Callback that provides error status of sending the notification
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_send_revocation_notification(vcx_command_handle_t command_handle,
+                                                 vcx_issuer_credential_handle_t credential_handle,
+                                                 const char *options,
+                                                 void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+// Get revocations made by `vcx_issuer_revoke_credential_local` which are not published to the Ledger yet.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// cb: Callback that provides pending revocations of all revocation registries as JSON string
+//     [{"rev_reg_id": string, "cred_rev_ids": [string], "since": string (RFC 3339), "delta": Optional<string>,
+//       "unmerged": [string], "without_delta": [string], "tails_file": Optional<string>}]
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_get_pending_revocations(vcx_command_handle_t command_handle,
+                                            void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+// Publish pending revocations (made by `vcx_issuer_revoke_credential_local`) to the Ledger.
+// Revocations of a revocation registry are published as a single merged delta of the registry.
+//
+// #Params
+// command_handle: command handle to map callback to user context.
+//
+// rev_reg_id: (Optional) id of revocation registry to publish pending revocations of.
+//     Pending revocations of all revocation registries are published if not set.
+//
+// cb: Callback that provides ids of revocation registries which revocations were published as JSON array of strings
+//
+// #Returns
+// Error code as a u32
+vcx_error_t vcx_issuer_publish_pending_revocations(vcx_command_handle_t command_handle,
+                                                const char *rev_reg_id,
+                                                void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 // Send a credential offer to user showing what will be included in the actual credential
 //
 // #Params
@@ -2780,6 +3377,7 @@ const char *vcx_version();
 // command_handle: command handle to map callback to user context.
 //
 // type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+//     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 //
 // id: the id ("key") of the record.
 //
@@ -2815,6 +3413,7 @@ vcx_error_t vcx_wallet_add_record(vcx_command_handle_t command_handle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -2904,6 +3503,7 @@ vcx_error_t vcx_wallet_verify_with_address(vcx_command_handle_t command_handle,
 // command_handle: command handle to map callback to user context.
 //
 // type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+//     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 //
 // id: the id ("key") of the record.
 //
@@ -2925,6 +3525,7 @@ vcx_error_t vcx_wallet_delete_record(vcx_command_handle_t command_handle,
 // command_handle: command handle to map callback to user context.
 //
 // type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+//     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 //
 // id: the id ("key") of the record.
 //
@@ -2946,6 +3547,9 @@ vcx_error_t vcx_wallet_delete_record_tags(vcx_command_handle_t command_handle,
 // Note this endpoint is EXPERIMENTAL. Function signature and behaviour may change
 // in the future releases.
 //
+// Note the exported wallet contains all wallet records including private keys of the library
+// (e.g. DIDComm v2 key agreement keys), so the backup key must be kept secret.
+//
 // #Params:
 // command_handle: Handle for User's Reference only.
 // path: Path to export wallet to User's File System.
@@ -2966,6 +3570,7 @@ vcx_error_t vcx_wallet_export(vcx_command_handle_t command_handle,
 // command_handle: command handle to map callback to user context.
 //
 // type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+//     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 //
 // id: the id ("key") of the record.
 //
@@ -3022,6 +3627,7 @@ vcx_error_t vcx_wallet_import(vcx_command_handle_t command_handle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// query_json: MongoDB style query to wallet record tags:
 ///  {
@@ -3107,6 +3713,7 @@ vcx_error_t vcx_wallet_send_tokens(vcx_command_handle_t command_handle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -3129,6 +3736,7 @@ vcx_error_t vcx_wallet_update_record_tags(vcx_command_handle_t command_handle,
 // command_handle: command handle to map callback to user context.
 //
 // type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+//     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 //
 // id: the id ("key") of the record.
 //
@@ -3459,6 +4067,181 @@ vcx_error_t vcx_resolve_message_by_url(vcx_command_handle_t command_handle,
                                        const char *url,
                                        void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
 
+/// Start the embedded HTTP endpoint receiving packed Aries messages from the remote sides directly (without an Agency).
+/// Every received message is unpacked and used to update the state of the Connection, IssuerCredential, Credential,
+/// Proof or DisclosedProof object which uses the recipient key of the message.
+/// NOTE: the endpoint URL must be reachable by the remote sides and set as the service endpoint of your DIDDoc.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// address: local socket address to listen on (e.g. "0.0.0.0:8080")
+///
+/// cb: Callback that provides the address the endpoint is bound to
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_start_inbound_endpoint(vcx_command_handle_t command_handle,
+                                       const char *address,
+                                       void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Stop the embedded HTTP endpoint started by `vcx_start_inbound_endpoint`.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides result code
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_stop_inbound_endpoint(vcx_command_handle_t command_handle,
+                                      void (*cb)(vcx_command_handle_t, vcx_error_t));
+
+/// Get records of the messages which have already been handled by Connection, Credential, Proof and other objects.
+/// Messages having a record are skipped when they are received again.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// query: WQL query over record tags:
+///     {
+///         "connection": Optional<string> - identifier of the connection the message was received over
+///                                           (`connection_id` of the agent in the serialized Connection; kept on DID rotation)
+///         "thread_id": Optional<string> - thread id of the message
+///         "message_id": Optional<string> - id of the message
+///     }
+///     Use "{}" to get all records.
+///
+/// cb: Callback that provides the list of records
+///     [
+///         {
+///             "connection": string,
+///             "thread_id": string,
+///             "message_id": string,
+///             "type": string, // type of the message
+///             "processed_at": string, // UTC time the message was handled at (RFC 3339)
+///         }
+///     ]
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_get_processed_messages(vcx_command_handle_t command_handle,
+                                       const char *query,
+                                       void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Delete records of the handled messages (see `vcx_get_processed_messages`).
+/// The messages of deleted records will be handled again if they are received once more.
+/// NOTE: records of a connection are deleted automatically when the connection is deleted.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// query: WQL query over record tags (see `vcx_get_processed_messages`). Use "{}" to delete all records.
+///
+/// cb: Callback that provides the number of deleted records
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_purge_processed_messages(vcx_command_handle_t command_handle,
+                                         const char *query,
+                                         void (*cb)(vcx_command_handle_t, vcx_error_t, vcx_u32_t));
+
+/// Get message history records of all connections.
+/// Records are stored only if `message_history` setting is set to `true` (see `vcx_init_with_config`).
+/// Records contain metadata of sent and received messages only, the content of messages is not stored.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// query: Optional<string> - filter of records (all set fields must match):
+///     {
+///         "connection": Optional<string> - identifier of the connection the message was exchanged over
+///                                           (`connection_id` of the agent in the serialized Connection; kept on DID rotation)
+///         "thread_id": Optional<string> - thread id of the message
+///         "protocol": Optional<string> - protocol family of the message type (e.g. "issue-credential")
+///         "direction": Optional<string> - one of "sent", "received"
+///         "status": Optional<string> - one of "sent", "failed", "received", "processed"
+///         "from": Optional<int> - messages sent or received at or after this time (Unix timestamp in seconds)
+///         "to": Optional<int> - messages sent or received at or before this time (Unix timestamp in seconds)
+///         "limit": Optional<int> - maximum number of records to return
+///         "offset": Optional<int> - number of the first matching records (ordered by time) to skip
+///     }
+///     Pass NULL or "{}" to get all records.
+///
+/// cb: Callback that provides the list of records ordered by time
+///     [
+///         {
+///             "connection": string,
+///             "thread_id": string,
+///             "message_id": string,
+///             "type": string, // type of the message
+///             "protocol": string,
+///             "direction": string, // "sent" or "received"
+///             "status": string, // "sent", "failed" (for sent messages), "received", "processed" (for received messages)
+///             "timestamp": string, // UTC time the message was sent or received at (RFC 3339)
+///         }
+///     ]
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_get_message_history(vcx_command_handle_t command_handle,
+                                    const char *query,
+                                    void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Take a snapshot of all live objects: connections, issuer credentials, credentials, proofs, disclosed proofs,
+/// schemas, credential definitions and wallet backups.
+/// The snapshot can be persisted by the application and restored with `vcx_session_restore` after the process is restarted.
+/// NOTE: the snapshot contains the same sensitive data as the results of `serialize` functions of the objects.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides the snapshot
+///     {
+///         "version": "1.0",
+///         "connections": [{"handle": int, "data": <serialized connection>}],
+///         "issuer_credentials": [...],
+///         "credentials": [...],
+///         "proofs": [...],
+///         "disclosed_proofs": [...],
+///         "schemas": [...],
+///         "credential_defs": [...],
+///         "wallet_backups": [...]
+///     }
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_session_snapshot(vcx_command_handle_t command_handle,
+                                 void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
+/// Restore all objects of the snapshot taken by `vcx_session_snapshot`.
+/// Restored objects get new handles. Either all objects of the snapshot are restored or none of them.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// snapshot: snapshot returned by `vcx_session_snapshot`
+///
+/// cb: Callback that provides handles of the restored objects mapped from the handles they had in the snapshot
+///     {
+///         "connections": {"<old handle>": <new handle>},
+///         "issuer_credentials": {...},
+///         "credentials": {...},
+///         "proofs": {...},
+///         "disclosed_proofs": {...},
+///         "schemas": {...},
+///         "credential_defs": {...},
+///         "wallet_backups": {...}
+///     }
+///
+/// #Returns
+/// Error code as a u32
+vcx_error_t vcx_session_restore(vcx_command_handle_t command_handle,
+                                const char *snapshot,
+                                void (*cb)(vcx_command_handle_t, vcx_error_t, const char*));
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
@@ -0,0 +1,721 @@
+pub mod server;
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::agent::messages::{A2AMessageKinds, Bundled, MessageStatusCode, RemoteMessageType, bundle_from_u8};
+use crate::agent::messages::message_type::MessageTypes;
+use crate::agent::messages::get_message::{Message, MessagePayload, MessageByConnection};
+use crate::agent::messages::update_message::UIDsByConn;
+use crate::settings::protocol::ProtocolTypes;
+use crate::utils::libindy::crypto;
+use crate::utils::uuid::uuid;
+
+/*
+    Local stand-in for the Agency which keeps all its state in memory.
+
+    It implements the agency message set used by the library:
+        provisioning - CONNECT, SIGNUP, CREATE_AGENT
+        agent - CREATE_KEY, UPDATE_CONFIGS, UPDATE_COM_METHOD, GET_MSGS_BY_CONNS, UPDATE_MSG_STATUS_BY_CONNS
+        pairwise agent - SEND_REMOTE_MSG, GET_MSGS, UPDATE_CONN_STATUS
+    and routing of FWD messages to the agency entities and Aries `forward` messages to the pairwise agents.
+
+    All keys are created in the currently opened wallet. Messages passed in SEND_REMOTE_MSG are stored
+    on the sender's pairwise agent and delivered to the pairwise agent of the recipient found by the recipient keys
+    of the packed message. Both parties must be hosted by the local agency: there is no delivery to remote agencies.
+*/
+
+/// Seed of the Agency keys used by `local_agency` binary unless another one is passed.
+pub const DEFAULT_AGENCY_SEED: &str = "000000000000000000000LocalAgency";
+
+const FORWARD: &str = "FWD";
+const ARIES_FORWARD: &str = "forward";
+const CONNECTION_DELETED: &str = "CS-103";
+
+lazy_static! {
+    static ref LOCAL_AGENCY: Mutex<Option<LocalAgency>> = Mutex::new(None);
+}
+
+#[derive(Debug)]
+struct LocalAgency {
+    did: String,
+    verkey: String,
+    pairwise: HashMap<String, AgencyPairwise>,
+    agents: HashMap<String, Agent>,
+    connections: HashMap<String, AgentConnection>,
+}
+
+/// Pairwise relationship between the Agency and the provisioned user. Created by CONNECT.
+#[derive(Debug)]
+struct AgencyPairwise {
+    did: String,
+    user_vk: String,
+    signed_up: bool,
+    agent_vk: Option<String>,
+}
+
+/// Cloud agent of the user. Created by CREATE_AGENT.
+#[derive(Debug)]
+struct Agent {
+    did: String,
+    owner_vk: String,
+}
+
+/// Pairwise agent of the user's connection. Created by CREATE_KEY.
+#[derive(Debug)]
+struct AgentConnection {
+    did: String,
+    agent_vk: String,
+    for_did: String,
+    for_vk: String,
+    status_code: Option<String>,
+    messages: Vec<Message>,
+}
+
+/// Creates the Agency keys from `seed` in the opened wallet and drops the state left from the previous run.
+/// Returns the Agency DID and verkey.
+pub fn init(seed: Option<&str>) -> VcxResult<(String, String)> {
+    trace!("local_agency::init >>>");
+
+    let (did, verkey) = crypto::create_and_store_my_did(seed, None)?;
+
+    let mut agency = LOCAL_AGENCY.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot init local agency: lock is poisoned"))?;
+
+    *agency = Some(LocalAgency {
+        did: did.clone(),
+        verkey: verkey.clone(),
+        pairwise: HashMap::new(),
+        agents: HashMap::new(),
+        connections: HashMap::new(),
+    });
+
+    info!("Local agency has been initialized with DID: {}, verkey: {}", did, verkey);
+
+    Ok((did, verkey))
+}
+
+/// Returns the Agency DID and verkey.
+pub fn get_agency_info() -> VcxResult<(String, String)> {
+    with_agency(|agency| Ok((agency.did.clone(), agency.verkey.clone())))
+}
+
+/// Handles the message posted to the Agency endpoint and returns the encrypted response.
+/// Aries messages forwarded to the pairwise agents are stored and get an empty response.
+pub fn process(payload: &[u8]) -> VcxResult<Vec<u8>> {
+    trace!("local_agency::process >>> payload: {:?}", secret!(payload));
+
+    // packed messages are JSON objects, messages of protocol 1.0 are anoncrypted MessagePack bundles
+    match serde_json::from_slice::<Value>(payload) {
+        Ok(_) => process_packed(payload),
+        Err(_) => process_bundled(payload),
+    }
+}
+
+fn process_packed(payload: &[u8]) -> VcxResult<Vec<u8>> {
+    let mut payload = payload.to_vec();
+    // the response is encrypted for the sender of the outermost authcrypted message
+    let mut reply_keys: Option<(String, String)> = None;
+
+    loop {
+        let (message, recipient_vk, sender_vk) = unpack(&payload)?;
+
+        if reply_keys.is_none() {
+            reply_keys = sender_vk.clone().map(|sender_vk| (recipient_vk.clone(), sender_vk));
+        }
+
+        match message_name(&message)?.as_str() {
+            FORWARD => {
+                let did = get_str(&message, "@fwd")?;
+                with_agency(|agency| agency.verkey_by_did(did).map(|_| ()))?;
+
+                debug!("Local agency: forwarding message to {}", did);
+                payload = serde_json::to_vec(&message["@msg"])
+                    .map_err(|err| VcxError::from_msg(VcxErrorKind::SerializationError, format!("Cannot serialize forwarded message. Err: {:?}", err)))?;
+            }
+            ARIES_FORWARD => {
+                let to = get_str(&message, "to")?;
+
+                if !with_agency(|agency| Ok(agency.is_hosted_key(to)))? {
+                    with_agency(|agency| agency.store_forwarded_message(to, message["msg"].clone()))?;
+                    return Ok(Vec::new());
+                }
+
+                debug!("Local agency: forwarding Aries message to {}", to);
+                payload = serde_json::to_vec(&message["msg"])
+                    .map_err(|err| VcxError::from_msg(VcxErrorKind::SerializationError, format!("Cannot serialize forwarded message. Err: {:?}", err)))?;
+            }
+            _ => {
+                let sender_vk = sender_vk
+                    .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "Agency messages must be authcrypted"))?;
+
+                let response = handle(&recipient_vk, &sender_vk, &message, &ProtocolTypes::V2)?;
+
+                let (my_vk, their_vk) = reply_keys.unwrap_or((recipient_vk, sender_vk));
+
+                let receiver_keys = json!([their_vk]).to_string();
+                return crypto::pack_message(Some(my_vk.as_str()), &receiver_keys, response.to_string().as_bytes());
+            }
+        }
+    }
+}
+
+fn process_bundled(payload: &[u8]) -> VcxResult<Vec<u8>> {
+    let agency_vk = with_agency(|agency| Ok(agency.verkey.clone()))?;
+
+    let mut message = decode_bundled(&crypto::parse_anonymous_msg(&agency_vk, payload)?)?;
+    let mut reply_keys: Option<(String, String)> = None;
+    let mut keys: Option<(String, String)> = None;
+
+    while message_name(&message)? == FORWARD {
+        let did = get_str(&message, "@fwd")?;
+        let recipient_vk = with_agency(|agency| agency.verkey_by_did(did))?;
+
+        let payload: Vec<u8> = serde_json::from_value(message["@msg"].clone())
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Cannot get forwarded message. Err: {:?}", err)))?;
+
+        let (sender_vk, data) = crypto::parse_msg(&recipient_vk, &payload)?;
+
+        if reply_keys.is_none() {
+            reply_keys = Some((recipient_vk.clone(), sender_vk.clone()));
+        }
+
+        message = decode_bundled(&data)?;
+        keys = Some((recipient_vk, sender_vk));
+    }
+
+    let (recipient_vk, sender_vk) = keys
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "Agency messages must be forwarded to the agency entity"))?;
+
+    let response = handle(&recipient_vk, &sender_vk, &message, &ProtocolTypes::V1)?;
+
+    let (my_vk, their_vk) = reply_keys.unwrap_or((recipient_vk, sender_vk));
+
+    let response = rmp_serde::to_vec_named(&response)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidMessagePack, format!("Cannot encode response: {}", err)))?;
+    let response = Bundled::create(response).encode()?;
+
+    crypto::prep_msg(&my_vk, &their_vk, &response)
+}
+
+fn handle(recipient_vk: &str, sender_vk: &str, message: &Value, protocol: &ProtocolTypes) -> VcxResult<Value> {
+    let name = message_name(message)?;
+    debug!("Local agency: handling {} message", name);
+
+    let (kind, mut response) = with_agency(|agency| agency.handle(recipient_vk, sender_vk, &name, message))?;
+
+    response["@type"] = match protocol {
+        ProtocolTypes::V1 => json!(MessageTypes::build_v1(kind)),
+        ProtocolTypes::V2 |
+        ProtocolTypes::V3 |
+        ProtocolTypes::V4 => json!(MessageTypes::build_v2(kind)),
+    };
+
+    trace!("Local agency: response {:?}", secret!(response));
+    Ok(response)
+}
+
+impl LocalAgency {
+    fn handle(&mut self, recipient_vk: &str, sender_vk: &str, name: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        if recipient_vk == self.verkey {
+            return match name {
+                "CONNECT" => self.connect(sender_vk, message),
+                _ => Err(Self::not_supported(name))
+            };
+        }
+
+        if let Some(pairwise) = self.pairwise.get(recipient_vk) {
+            check_sender(&pairwise.user_vk, sender_vk)?;
+            return match name {
+                "SIGNUP" => self.sign_up(recipient_vk),
+                "CREATE_AGENT" => self.create_agent(recipient_vk),
+                _ => Err(Self::not_supported(name))
+            };
+        }
+
+        if let Some(agent) = self.agents.get(recipient_vk) {
+            check_sender(&agent.owner_vk, sender_vk)?;
+            return match name {
+                "CREATE_KEY" => self.create_key(recipient_vk, message),
+                "UPDATE_CONFIGS" => self.update_configs(message),
+                "UPDATE_COM_METHOD" => self.update_com_method(message),
+                "GET_MSGS_BY_CONNS" => self.get_messages_by_connections(recipient_vk, message),
+                "UPDATE_MSG_STATUS_BY_CONNS" => self.update_messages_status(recipient_vk, message),
+                _ => Err(Self::not_supported(name))
+            };
+        }
+
+        if let Some(connection) = self.connections.get(recipient_vk) {
+            check_sender(&connection.for_vk, sender_vk)?;
+            if connection.is_deleted() {
+                return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "Connection is deleted"));
+            }
+            return match name {
+                "GET_MSGS" => self.get_messages(recipient_vk, message),
+                "SEND_REMOTE_MSG" => self.send_remote_message(recipient_vk, message),
+                "UPDATE_CONN_STATUS" => self.update_connection_status(recipient_vk, message),
+                _ => Err(Self::not_supported(name))
+            };
+        }
+
+        Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Unknown message recipient: {}", recipient_vk)))
+    }
+
+    fn not_supported(name: &str) -> VcxError {
+        VcxError::from_msg(VcxErrorKind::ActionNotSupported, format!("Message {} is not supported by the recipient", name))
+    }
+
+    fn verkey_by_did(&self, did: &str) -> VcxResult<String> {
+        if did == self.did {
+            return Ok(self.verkey.clone());
+        }
+
+        self.pairwise.iter().find(|(_, pairwise)| pairwise.did == did).map(|(verkey, _)| verkey)
+            .or_else(|| self.agents.iter().find(|(_, agent)| agent.did == did).map(|(verkey, _)| verkey))
+            .or_else(|| self.connections.iter().find(|(_, connection)| connection.did == did).map(|(verkey, _)| verkey))
+            .cloned()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Unknown forward recipient: {}", did)))
+    }
+
+    fn is_hosted_key(&self, verkey: &str) -> bool {
+        verkey == self.verkey ||
+            self.pairwise.contains_key(verkey) ||
+            self.agents.contains_key(verkey) ||
+            self.connections.contains_key(verkey)
+    }
+
+    fn connect(&mut self, sender_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let from_vk = get_str(message, "fromDIDVerKey")?;
+        get_str(message, "fromDID")?;
+
+        if from_vk != sender_vk {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "fromDIDVerKey does not match the message sender"));
+        }
+
+        let existing = self.pairwise.iter()
+            .find(|(_, pairwise)| pairwise.user_vk == from_vk)
+            .map(|(verkey, pairwise)| (pairwise.did.clone(), verkey.clone()));
+
+        let (did, verkey) = match existing {
+            Some(keys) => keys,
+            None => {
+                let (did, verkey) = crypto::create_and_store_my_did(None, None)?;
+                self.pairwise.insert(verkey.clone(), AgencyPairwise {
+                    did: did.clone(),
+                    user_vk: from_vk.to_string(),
+                    signed_up: false,
+                    agent_vk: None,
+                });
+                (did, verkey)
+            }
+        };
+
+        Ok((A2AMessageKinds::Connected, json!({
+            "withPairwiseDID": did,
+            "withPairwiseDIDVerKey": verkey,
+        })))
+    }
+
+    fn sign_up(&mut self, pairwise_vk: &str) -> VcxResult<(A2AMessageKinds, Value)> {
+        self.pairwise_mut(pairwise_vk)?.signed_up = true;
+        Ok((A2AMessageKinds::SignedUp, json!({})))
+    }
+
+    fn create_agent(&mut self, pairwise_vk: &str) -> VcxResult<(A2AMessageKinds, Value)> {
+        let (signed_up, agent_vk, user_vk) = {
+            let pairwise = self.pairwise_mut(pairwise_vk)?;
+            (pairwise.signed_up, pairwise.agent_vk.clone(), pairwise.user_vk.clone())
+        };
+
+        if !signed_up {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "SIGNUP must be sent before CREATE_AGENT"));
+        }
+
+        let (did, verkey) = match agent_vk.and_then(|verkey| self.agents.get(&verkey).map(|agent| (agent.did.clone(), verkey))) {
+            Some(keys) => keys,
+            None => {
+                let (did, verkey) = crypto::create_and_store_my_did(None, None)?;
+                self.agents.insert(verkey.clone(), Agent {
+                    did: did.clone(),
+                    owner_vk: user_vk,
+                });
+                self.pairwise_mut(pairwise_vk)?.agent_vk = Some(verkey.clone());
+                (did, verkey)
+            }
+        };
+
+        Ok((A2AMessageKinds::AgentCreated, json!({
+            "withPairwiseDID": did,
+            "withPairwiseDIDVerKey": verkey,
+        })))
+    }
+
+    fn create_key(&mut self, agent_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let for_did = get_str(message, "forDID")?;
+        let for_vk = get_str(message, "forDIDVerKey")?;
+
+        if self.connections.values().any(|connection| connection.for_vk == for_vk) {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Pairwise key {} is already registered", for_vk)));
+        }
+
+        let (did, verkey) = crypto::create_and_store_my_did(None, None)?;
+
+        self.connections.insert(verkey.clone(), AgentConnection {
+            did: did.clone(),
+            agent_vk: agent_vk.to_string(),
+            for_did: for_did.to_string(),
+            for_vk: for_vk.to_string(),
+            status_code: None,
+            messages: Vec::new(),
+        });
+
+        Ok((A2AMessageKinds::KeyCreated, json!({
+            "withPairwiseDID": did,
+            "withPairwiseDIDVerKey": verkey,
+        })))
+    }
+
+    // agent configs and communication methods are accepted but not used: the local agency sends no push notifications
+    fn update_configs(&mut self, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        for config in message["configs"].as_array().map(Vec::as_slice).unwrap_or_default() {
+            get_str(config, "name")?;
+            get_str(config, "value")?;
+        }
+
+        Ok((A2AMessageKinds::ConfigsUpdated, json!({})))
+    }
+
+    fn update_com_method(&mut self, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let id = get_str(&message["comMethod"], "id")?;
+
+        Ok((A2AMessageKinds::ComMethodUpdated, json!({ "id": id })))
+    }
+
+    fn get_messages_by_connections(&mut self, agent_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let pairwise_dids = get_str_list(message, "pairwiseDIDs");
+
+        let messages: Vec<MessageByConnection> = self.connections.values()
+            .filter(|connection| connection.agent_vk == agent_vk)
+            .filter(|connection| pairwise_dids.as_ref().map(|dids| dids.contains(&connection.for_did)).unwrap_or(true))
+            .map(|connection| MessageByConnection {
+                pairwise_did: connection.for_did.clone(),
+                msgs: filter_messages(&connection.messages, message),
+            })
+            .collect();
+
+        Ok((A2AMessageKinds::MessagesByConnections, json!({ "msgsByConns": messages })))
+    }
+
+    fn update_messages_status(&mut self, agent_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let status_code: MessageStatusCode = serde_json::from_value(message["statusCode"].clone())
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Invalid statusCode. Err: {:?}", err)))?;
+
+        let uids_by_conns: Vec<UIDsByConn> = serde_json::from_value(message["uidsByConns"].clone())
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Invalid uidsByConns. Err: {:?}", err)))?;
+
+        let mut updated_uids_by_conns = Vec::new();
+
+        for uids_by_conn in uids_by_conns {
+            let connection = self.connections.values_mut()
+                .find(|connection| connection.agent_vk == agent_vk && connection.for_did == uids_by_conn.pairwise_did)
+                .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Unknown pairwise DID: {}", uids_by_conn.pairwise_did)))?;
+
+            let mut uids = Vec::new();
+            for message in connection.messages.iter_mut().filter(|message| uids_by_conn.uids.contains(&message.uid)) {
+                message.status_code = status_code.clone();
+                uids.push(message.uid.clone());
+            }
+
+            updated_uids_by_conns.push(UIDsByConn { pairwise_did: uids_by_conn.pairwise_did, uids });
+        }
+
+        Ok((A2AMessageKinds::MessageStatusUpdatedByConnections, json!({
+            "statusCode": status_code.to_string(),
+            "updatedUidsByConns": updated_uids_by_conns,
+        })))
+    }
+
+    fn get_messages(&mut self, connection_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let connection = self.connection_mut(connection_vk)?;
+
+        Ok((A2AMessageKinds::Messages, json!({ "msgs": filter_messages(&connection.messages, message) })))
+    }
+
+    fn send_remote_message(&mut self, connection_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let id = get_str(message, "@id")?.to_string();
+        let send_msg = message["sendMsg"].as_bool().unwrap_or(false);
+        let payload = message["@msg"].clone();
+
+        let msg_type: RemoteMessageType = serde_json::from_value(message["mtype"].clone())
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Invalid mtype. Err: {:?}", err)))?;
+
+        let recipient_vk = if send_msg { Some(self.remote_recipient(connection_vk, &payload)?) } else { None };
+
+        let connection = self.connection_mut(connection_vk)?;
+        let uid = uuid();
+        let sender_did = connection.for_did.clone();
+
+        if let Some(reply_to_msg_id) = message["replyToMsgId"].as_str() {
+            if let Some(answered) = connection.messages.iter_mut().find(|message| message.uid == reply_to_msg_id) {
+                answered.status_code = MessageStatusCode::Accepted;
+                answered.ref_msg_id = Some(uid.clone());
+            }
+        }
+
+        connection.messages.push(Message {
+            status_code: if send_msg { MessageStatusCode::Sent } else { MessageStatusCode::Created },
+            payload: Some(MessagePayload::V2(payload.clone())),
+            sender_did: sender_did.clone(),
+            uid,
+            msg_type: msg_type.clone(),
+            ref_msg_id: None,
+            delivery_details: Vec::new(),
+            decrypted_payload: None,
+        });
+
+        if let Some(recipient_vk) = recipient_vk {
+            let recipient = self.connection_mut(&recipient_vk)?;
+            let uid = uuid();
+            debug!("Local agency: delivering message {} from pairwise DID {} to pairwise DID {}", uid, sender_did, recipient.for_did);
+
+            recipient.messages.push(Message {
+                status_code: MessageStatusCode::Received,
+                payload: Some(MessagePayload::V2(payload)),
+                sender_did,
+                uid,
+                msg_type,
+                ref_msg_id: None,
+                delivery_details: Vec::new(),
+                decrypted_payload: None,
+            });
+        }
+
+        Ok((A2AMessageKinds::SendRemoteMessageResponse, json!({
+            "@id": id,
+            "sent": send_msg,
+        })))
+    }
+
+    /// Finds the pairwise agent of the remote party the packed message is encrypted for.
+    fn remote_recipient(&self, sender_vk: &str, payload: &Value) -> VcxResult<String> {
+        let recipient_keys = packed_recipient_keys(payload)?;
+
+        self.connections.iter()
+            .find(|(verkey, connection)|
+                verkey.as_str() != sender_vk && !connection.is_deleted() && recipient_keys.contains(&connection.for_vk))
+            .map(|(verkey, _)| verkey.clone())
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest,
+                                      format!("Message recipients {:?} are not hosted by the local agency", recipient_keys)))
+    }
+
+    fn update_connection_status(&mut self, connection_vk: &str, message: &Value) -> VcxResult<(A2AMessageKinds, Value)> {
+        let status_code = get_str(message, "statusCode")?.to_string();
+
+        self.connection_mut(connection_vk)?.status_code = Some(status_code.clone());
+
+        Ok((A2AMessageKinds::ConnectionStatusUpdated, json!({ "statusCode": status_code })))
+    }
+
+    fn store_forwarded_message(&mut self, to: &str, msg: Value) -> VcxResult<()> {
+        let connection = self.connections.values_mut()
+            .find(|connection| connection.for_vk == to && !connection.is_deleted())
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Unknown forward recipient: {}", to)))?;
+
+        let uid = uuid();
+        debug!("Local agency: storing message {} for pairwise DID {}", uid, connection.for_did);
+
+        connection.messages.push(Message {
+            status_code: MessageStatusCode::Received,
+            payload: Some(MessagePayload::V2(msg)),
+            sender_did: String::new(),
+            uid,
+            msg_type: RemoteMessageType::Other(String::from("aries")),
+            ref_msg_id: None,
+            delivery_details: Vec::new(),
+            decrypted_payload: None,
+        });
+
+        Ok(())
+    }
+
+    fn pairwise_mut(&mut self, verkey: &str) -> VcxResult<&mut AgencyPairwise> {
+        self.pairwise.get_mut(verkey)
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Unknown agency pairwise key: {}", verkey)))
+    }
+
+    fn connection_mut(&mut self, verkey: &str) -> VcxResult<&mut AgentConnection> {
+        self.connections.get_mut(verkey)
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Unknown pairwise agent key: {}", verkey)))
+    }
+}
+
+impl AgentConnection {
+    fn is_deleted(&self) -> bool {
+        self.status_code.as_ref().map(String::as_str) == Some(CONNECTION_DELETED)
+    }
+}
+
+fn with_agency<F, T>(closure: F) -> VcxResult<T> where F: FnOnce(&mut LocalAgency) -> VcxResult<T> {
+    let mut agency = LOCAL_AGENCY.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot get local agency: lock is poisoned"))?;
+
+    let agency = agency.as_mut()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "Local agency is not initialized"))?;
+
+    closure(agency)
+}
+
+fn check_sender(owner_vk: &str, sender_vk: &str) -> VcxResult<()> {
+    if owner_vk != sender_vk {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Sender {} is not allowed to use the recipient", sender_vk)));
+    }
+    Ok(())
+}
+
+fn unpack(payload: &[u8]) -> VcxResult<(Value, String, Option<String>)> {
+    let unpacked = crypto::unpack_message(payload)?;
+
+    let unpacked: Value = serde_json::from_slice(&unpacked)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Cannot deserialize unpacked message. Err: {:?}", err)))?;
+
+    let message: Value = serde_json::from_str(get_str(&unpacked, "message")?)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Cannot deserialize message. Err: {:?}", err)))?;
+
+    let recipient_vk = get_str(&unpacked, "recipient_verkey")?.to_string();
+    let sender_vk = unpacked["sender_verkey"].as_str().map(String::from);
+
+    Ok((message, recipient_vk, sender_vk))
+}
+
+/// Gets the recipient verkeys from the protected header of the packed message.
+fn packed_recipient_keys(message: &Value) -> VcxResult<Vec<String>> {
+    let protected = get_str(message, "protected")?;
+
+    let protected = base64::decode_config(protected.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Cannot decode protected header of packed message. Err: {:?}", err)))?;
+
+    let protected: Value = serde_json::from_slice(&protected)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Cannot deserialize protected header of packed message. Err: {:?}", err)))?;
+
+    let recipient_keys: Vec<String> = protected["recipients"].as_array()
+        .map(|recipients| recipients.iter().filter_map(|recipient| recipient["header"]["kid"].as_str()).map(String::from).collect())
+        .unwrap_or_default();
+
+    if recipient_keys.is_empty() {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "Packed message does not contain recipient keys"));
+    }
+
+    Ok(recipient_keys)
+}
+
+fn decode_bundled(data: &[u8]) -> VcxResult<Value> {
+    let bundle = bundle_from_u8(data)?;
+
+    let message = bundle.bundled.first()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, "Bundle does not contain any message"))?;
+
+    rmp_serde::from_slice(message)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidMessagePack, format!("Cannot decode message: {}", err)))
+}
+
+/// Gets the message name from either `{"name": .., "ver": ..}` or `<did>;spec/<family>/<version>/<name>` @type.
+fn message_name(message: &Value) -> VcxResult<String> {
+    let name = match message["@type"] {
+        Value::String(ref type_) => type_.rsplit('/').next(),
+        Value::Object(ref type_) => type_.get("name").and_then(Value::as_str),
+        _ => None
+    };
+
+    name.map(String::from)
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Cannot get @type of message: {:?}", secret!(message))))
+}
+
+fn get_str<'a>(value: &'a Value, field: &str) -> VcxResult<&'a str> {
+    value[field].as_str()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyRequest, format!("Field `{}` is missing or is not a string", field)))
+}
+
+fn get_str_list(value: &Value, field: &str) -> Option<Vec<String>> {
+    value[field].as_array()
+        .map(|values| values.iter().filter_map(Value::as_str).map(String::from).collect())
+}
+
+fn filter_messages(messages: &[Message], filter: &Value) -> Vec<Message> {
+    let uids = get_str_list(filter, "uids");
+    let status_codes = get_str_list(filter, "statusCodes");
+    let exclude_payload = filter["excludePayload"].as_str() == Some("Y");
+
+    messages.iter()
+        .filter(|message| uids.as_ref().map(|uids| uids.contains(&message.uid)).unwrap_or(true))
+        .filter(|message| status_codes.as_ref().map(|codes| codes.contains(&message.status_code.to_string())).unwrap_or(true))
+        .map(|message| {
+            let mut message = message.clone();
+            if exclude_payload {
+                message.payload = None;
+            }
+            message
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    fn _connection(agency: &mut LocalAgency, agent_vk: &str) -> (String, String) {
+        let (for_did, for_vk) = crypto::create_and_store_my_did(None, None).unwrap();
+        let (_, response) = agency.create_key(agent_vk, &json!({"forDID": for_did, "forDIDVerKey": for_vk})).unwrap();
+        (response["withPairwiseDIDVerKey"].as_str().unwrap().to_string(), for_vk)
+    }
+
+    #[test]
+    fn test_message_name() {
+        assert_eq!("CONNECT", message_name(&json!({"@type": {"name": "CONNECT", "ver": "1.0"}})).unwrap());
+        assert_eq!("FWD", message_name(&json!({"@type": "did:sov:123456789abcdefghi1234;spec/routing/1.0/FWD"})).unwrap());
+        assert_eq!("forward", message_name(&json!({"@type": "https://didcomm.org/routing/1.0/forward"})).unwrap());
+        assert_eq!(VcxErrorKind::InvalidAgencyRequest, message_name(&json!({"msg": "hello"})).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_send_remote_message_delivers_message_to_recipient() {
+        let _setup = SetupLibraryWallet::init();
+
+        let mut agency = LocalAgency {
+            did: String::new(),
+            verkey: String::new(),
+            pairwise: HashMap::new(),
+            agents: HashMap::new(),
+            connections: HashMap::new(),
+        };
+
+        let (sender_connection_vk, sender_vk) = _connection(&mut agency, "agent_1");
+        let (recipient_connection_vk, recipient_vk) = _connection(&mut agency, "agent_2");
+
+        let receiver_keys = json!([recipient_vk]).to_string();
+        let packed = crypto::pack_message(Some(&sender_vk), &receiver_keys, b"hello").unwrap();
+        let packed: Value = serde_json::from_slice(&packed).unwrap();
+        assert_eq!(vec![recipient_vk.clone()], packed_recipient_keys(&packed).unwrap());
+
+        let message = json!({"@id": "id", "mtype": "credOffer", "sendMsg": true, "@msg": packed});
+        agency.send_remote_message(&sender_connection_vk, &message).unwrap();
+
+        let sent = &agency.connections[&sender_connection_vk].messages;
+        assert_eq!(1, sent.len());
+        assert_eq!(MessageStatusCode::Sent, sent[0].status_code);
+
+        let received = &agency.connections[&recipient_connection_vk].messages;
+        assert_eq!(1, received.len());
+        assert_eq!(MessageStatusCode::Received, received[0].status_code);
+        assert_eq!(RemoteMessageType::CredOffer, received[0].msg_type);
+        assert_eq!(agency.connections[&sender_connection_vk].for_did, received[0].sender_did);
+
+        let receiver_keys = json!([sender_vk]).to_string();
+        let packed = crypto::pack_message(Some(&sender_vk), &receiver_keys, b"hello").unwrap();
+        let message = json!({"@id": "id", "mtype": "credOffer", "sendMsg": true, "@msg": serde_json::from_slice::<Value>(&packed).unwrap()});
+        assert_eq!(VcxErrorKind::InvalidAgencyRequest, agency.send_remote_message(&sender_connection_vk, &message).unwrap_err().kind());
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+
+use crate::error::prelude::*;
+use crate::error::agency_error::{AgencyError, AgencyErrorCode};
+use crate::agent::local_agency;
+use crate::aries::inbound::server::{HttpServer, HttpRequest, HttpResponse};
+
+lazy_static! {
+    static ref LOCAL_AGENCY_ENDPOINT: Mutex<Option<HttpServer>> = Mutex::new(None);
+}
+
+/// Starts serving the local agency on `address` (e.g. `127.0.0.1:8080`):
+///     GET  - returns the Agency DID and verkey (the same as `{agency_url}/agency` of the Agency)
+///     POST - handles the message (the same as `{agency_url}/agency/msg` of the Agency)
+/// The local agency must be initialized with `local_agency::init`.
+///
+/// Returns the address the endpoint is bound to.
+pub fn start(address: &str) -> VcxResult<String> {
+    trace!("local_agency::server::start >>> address: {:?}", address);
+
+    let mut endpoint = LOCAL_AGENCY_ENDPOINT.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot start local agency: lock is poisoned"))?;
+
+    if let Some(ref endpoint) = *endpoint {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidState, format!("Local agency is already running on {}", endpoint.address())));
+    }
+
+    let server = HttpServer::start("Local agency", address, handle_request)?;
+    let local_address = server.address().to_string();
+
+    *endpoint = Some(server);
+
+    Ok(local_address)
+}
+
+/// Stops serving the local agency if it is running.
+pub fn stop() -> VcxResult<()> {
+    trace!("local_agency::server::stop >>>");
+
+    let endpoint = LOCAL_AGENCY_ENDPOINT.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot stop local agency: lock is poisoned"))?
+        .take();
+
+    match endpoint {
+        Some(endpoint) => endpoint.stop(),
+        None => Ok(())
+    }
+}
+
+fn handle_request(request: VcxResult<HttpRequest>) -> HttpResponse {
+    let request = match request {
+        Ok(request) => request,
+        Err(err) => {
+            warn!("Local agency: cannot read request. Err: {}", err);
+            return error_response(&err);
+        }
+    };
+
+    let result = match request.method.as_str() {
+        "GET" => local_agency::get_agency_info()
+            .map(|(did, verkey)| json!({"DID": did, "verKey": verkey}).to_string().into_bytes()),
+        "POST" => local_agency::process(&request.body),
+        _ => return HttpResponse::empty(405, "Method Not Allowed")
+    };
+
+    match result {
+        Ok(body) => HttpResponse { code: 200, reason: "OK", content_type: Some("application/octet-stream"), body },
+        Err(err) => {
+            warn!("Local agency: cannot handle request. Err: {}", err);
+            error_response(&err)
+        }
+    }
+}
+
+// errors are reported in the Agency format so the library maps them the same way as for the Agency
+fn error_response(err: &VcxError) -> HttpResponse {
+    let error = AgencyError {
+        status_code: AgencyErrorCode::InvalidValue,
+        status_msg: err.to_string(),
+    };
+
+    HttpResponse {
+        code: 400,
+        reason: "Bad Request",
+        content_type: Some("application/json"),
+        body: json!(error).to_string().into_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    #[test]
+    fn test_local_agency_endpoint() {
+        let _setup = SetupLibraryWallet::init();
+
+        let (did, verkey) = local_agency::init(None).unwrap();
+        let address = start("127.0.0.1:0").unwrap();
+        assert_eq!(VcxErrorKind::InvalidState, start("127.0.0.1:0").unwrap_err().kind());
+
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream.write_all(b"GET /agency HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with(&json!({"DID": did, "verKey": verkey}).to_string()));
+
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream.write_all(b"POST /agency/msg HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 400"));
+        assert!(response.contains("GNR-115"));
+
+        stop().unwrap();
+    }
+}
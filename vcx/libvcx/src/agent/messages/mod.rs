@@ -858,10 +858,12 @@ pub enum A2AMessageKinds {
     MessageSent,
     GetMessages,
     GetMessagesByConnections,
+    MessagesByConnections,
     Messages,
     UpdateMessageStatusByConnections,
     MessageStatusUpdatedByConnections,
     UpdateConnectionStatus,
+    ConnectionStatusUpdated,
     UpdateConfigs,
     ConfigsUpdated,
     UpdateComMethod,
@@ -905,8 +907,10 @@ impl A2AMessageKinds {
             A2AMessageKinds::MessageSent => MessageFamilies::Pairwise,
             A2AMessageKinds::GetMessages => MessageFamilies::Pairwise,
             A2AMessageKinds::GetMessagesByConnections => MessageFamilies::Pairwise,
+            A2AMessageKinds::MessagesByConnections => MessageFamilies::Pairwise,
             A2AMessageKinds::Messages => MessageFamilies::Pairwise,
             A2AMessageKinds::UpdateConnectionStatus => MessageFamilies::Pairwise,
+            A2AMessageKinds::ConnectionStatusUpdated => MessageFamilies::Pairwise,
             A2AMessageKinds::ConnectionRequest => MessageFamilies::Connecting,
             A2AMessageKinds::ConnectionRequestAnswer => MessageFamilies::Connecting,
             A2AMessageKinds::ConnectionRequestRedirect => MessageFamilies::Connecting,
@@ -952,10 +956,12 @@ impl A2AMessageKinds {
             A2AMessageKinds::MessageSent => "MSGS_SENT".to_string(),
             A2AMessageKinds::GetMessages => "GET_MSGS".to_string(),
             A2AMessageKinds::GetMessagesByConnections => "GET_MSGS_BY_CONNS".to_string(),
+            A2AMessageKinds::MessagesByConnections => "MSGS_BY_CONNS".to_string(),
             A2AMessageKinds::UpdateMessageStatusByConnections => "UPDATE_MSG_STATUS_BY_CONNS".to_string(),
             A2AMessageKinds::MessageStatusUpdatedByConnections => "MSG_STATUS_UPDATED_BY_CONNS".to_string(),
             A2AMessageKinds::Messages => "MSGS".to_string(),
             A2AMessageKinds::UpdateConnectionStatus => "UPDATE_CONN_STATUS".to_string(),
+            A2AMessageKinds::ConnectionStatusUpdated => "CONN_STATUS_UPDATED".to_string(),
             A2AMessageKinds::ConnectionRequest => "CONN_REQUEST".to_string(),
             A2AMessageKinds::ConnectionRequestAnswer => "ACCEPT_CONN_REQ".to_string(),
             A2AMessageKinds::ConnectionRequestRedirect => "REDIRECT_CONN_REQ".to_string(),
@@ -1082,7 +1088,7 @@ fn parse_response_from_agency_v2(response: &[u8]) -> VcxResult<Vec<A2AMessage>>
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct Bundled<T> {
-    bundled: Vec<T>,
+    pub bundled: Vec<T>,
 }
 
 impl<T> Bundled<T> {
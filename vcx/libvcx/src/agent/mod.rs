@@ -1,3 +1,5 @@
 pub mod provisioning;
 pub mod messages;
-pub mod agent_info;
\ No newline at end of file
+pub mod agent_info;
+#[cfg(feature = "local_agency")]
+pub mod local_agency;
@@ -9,6 +9,8 @@ use crate::connection::*;
 use crate::error::prelude::*;
 use vdrtools_sys::CommandHandle;
 use crate::aries::messages::invite_action::invite::InviteActionData;
+use crate::aries::handlers::mediation::{self, pickup};
+use crate::aries::utils::message_history::HistoryQuery;
 
 /*
     Tha API represents a pairwise connection with another identity owner.
@@ -99,10 +101,20 @@ use crate::aries::messages::invite_action::invite::InviteActionData;
             VcxStateType::VcxStateOfferSent - received `ConnectionRequest` - VcxStateType::VcxStateRequestReceived
             VcxStateType::VcxStateOfferSent - received `ConnectionProblemReport` - VcxStateType::VcxStateNone
 
+            VcxStateType::VcxStateOfferSent - received `DidExchangeRequest` - VcxStateType::VcxStateRequestReceived
+            VcxStateType::VcxStateOfferSent - received `ConnectionRequest` or `DidExchangeRequest` (multi-use invitation) - VcxStateType::VcxStateOfferSent
+                                              (the request is accepted by a new Connection object in VcxStateType::VcxStateRequestReceived state)
+
             VcxStateType::VcxStateRequestReceived - received `Ack` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateRequestReceived - received `DidExchangeComplete` - VcxStateType::VcxStateAccepted
             VcxStateType::VcxStateRequestReceived - received `ConnectionProblemReport` - VcxStateType::VcxStateNone
 
             VcxStateType::VcxStateAccepted - received `Ping`, `PingResponse`, `Query`, `Disclose` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - `vcx_connection_rotate_did` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - received `DidRotate`, `DidRotateAck`, `DidRotateProblemReport` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - `vcx_connection_update_service` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - received `DidDocUpdate`, `DidDocUpdateAck`, `DidDocUpdateProblemReport` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - received `RevocationNotification` (stored credential is marked as revoked) - VcxStateType::VcxStateAccepted
 
             any state - `vcx_connection_delete_connection` - VcxStateType::VcxStateNone
 
@@ -116,9 +128,15 @@ use crate::aries::messages::invite_action::invite::InviteActionData;
             VcxStateType::VcxStateOfferSent - received `ConnectionProblemReport` - VcxStateType::VcxStateNone
 
             VcxStateType::VcxStateRequestReceived - received `ConnectionResponse` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateRequestReceived - received `DidExchangeResponse` - VcxStateType::VcxStateAccepted
             VcxStateType::VcxStateRequestReceived - received `ConnectionProblemReport` - VcxStateType::VcxStateNone
 
             VcxStateType::VcxStateAccepted - received `Ping`, `PingResponse`, `Query`, `Disclose` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - `vcx_connection_rotate_did` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - received `DidRotate`, `DidRotateAck`, `DidRotateProblemReport` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - `vcx_connection_update_service` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - received `DidDocUpdate`, `DidDocUpdateAck`, `DidDocUpdateProblemReport` - VcxStateType::VcxStateAccepted
+            VcxStateType::VcxStateAccepted - received `RevocationNotification` (stored credential is marked as revoked) - VcxStateType::VcxStateAccepted
 
             any state - `vcx_connection_delete_connection` - VcxStateType::VcxStateNone
 
@@ -133,12 +151,25 @@ use crate::aries::messages::invite_action::invite::InviteActionData;
         ConnectionRequest - https://github.com/hyperledger/aries-rfcs/tree/master/features/0160-connection-protocol#1-connection-request
         ConnectionResponse - https://github.com/hyperledger/aries-rfcs/tree/master/features/0160-connection-protocol#2-connection-response
         ConnectionProblemReport - https://github.com/hyperledger/aries-rfcs/tree/master/features/0160-connection-protocol#error-message-example
+        DidExchangeRequest - https://github.com/hyperledger/aries-rfcs/tree/main/features/0023-did-exchange#1-exchange-request
+        DidExchangeResponse - https://github.com/hyperledger/aries-rfcs/tree/main/features/0023-did-exchange#2-exchange-response
+        DidExchangeComplete - https://github.com/hyperledger/aries-rfcs/tree/main/features/0023-did-exchange#3-exchange-complete
         Ack - https://github.com/hyperledger/aries-rfcs/tree/master/features/0015-acks#explicit-acks
         Ping - https://github.com/hyperledger/aries-rfcs/tree/master/features/0048-trust-ping#messages
         PingResponse - https://github.com/hyperledger/aries-rfcs/tree/master/features/0048-trust-ping#messages
         Query - https://github.com/hyperledger/aries-rfcs/tree/master/features/0031-discover-features#query-message-type
         Disclose - https://github.com/hyperledger/aries-rfcs/tree/master/features/0031-discover-features#disclose-message-type
         Out-of-Band Invitation - https://github.com/hyperledger/aries-rfcs/tree/master/features/0434-outofband#message-type-httpsdidcommorgout-of-bandverinvitation
+        DidRotate - https://github.com/hyperledger/aries-rfcs/tree/main/features/0794-did-rotate#rotate
+        DidRotateAck - https://github.com/hyperledger/aries-rfcs/tree/main/features/0794-did-rotate#ack
+        DidRotateProblemReport - https://github.com/hyperledger/aries-rfcs/tree/main/features/0794-did-rotate#problem-report
+        RevocationNotification 1.0 - https://github.com/hyperledger/aries-rfcs/tree/main/features/0183-revocation-notification#revoke-message
+        RevocationNotification 2.0 - https://github.com/hyperledger/aries-rfcs/tree/main/features/0721-revocation-notification-v2#revoke-message
+
+    proprietary aries:
+        DidDocUpdate (`did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/did-doc-update/1.0/update`) - DIDDoc with new service endpoint and routing keys signed by the recipient key
+        DidDocUpdateAck (`did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/did-doc-update/1.0/ack`)
+        DidDocUpdateProblemReport (`did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/did-doc-update/1.0/problem-report`)
 */
 
 /// Delete a Connection object from the agency and release its handle.
@@ -416,6 +447,9 @@ pub extern fn vcx_connection_create_outofband(command_handle: CommandHandle,
 ///                 "serviceEndpoint": string
 ///             }
 ///         ] - an item that is the equivalent of the service block of a DIDDoc that the receiver is to use in responding to the message.
+///               Items can also be DIDs resolving to such service block: did:peer:2, long form did:peer:4,
+///               or public did:sov / did:indy DIDs (verkey and `endpoint` ATTRIB are read from the Ledger).
+///               If the invitation has no services, its `public_did` is resolved instead.
 ///     }
 ///
 /// # Returns
@@ -489,6 +523,14 @@ pub extern fn vcx_connection_create_with_outofband_invitation(command_handle: Co
 ///                                                             "agent_did": string,
 ///                                                             "agent_vk": string,
 ///                                                         }
+///         "envelope_version": Option<"string"> - format of envelopes used for the connection: "v1" (DIDComm v1) or "v2" (DIDComm v2).
+///                                                default value is taken from `didcomm_envelope_version` config option (or "v1").
+///                                                "v2" is used only if the remote side publishes its keyAgreement keys as well.
+///         "return_route": Option<"string"> - `return_route` transport decorator added to every outbound message: "all", "thread" or "none" (default).
+///                                            replies the remote side returns in HTTP responses are handled as received messages
+///                                            by the next `update_state` call of the object waiting for them.
+///         "delivery_policy": Option<JSON object> - HTTP delivery policy for messages sent over the connection (same format as `http_delivery_policy` config option).
+///                                                  default value is taken from `http_delivery_policy` config option.
 ///     }
 ///
 /// cb: Callback that provides connection handle and error status of request.
@@ -609,6 +651,31 @@ pub extern fn vcx_connection_accept_connection_invite(command_handle: CommandHan
 ///                                                             "agent_did": string,
 ///                                                             "agent_vk": string,
 ///                                                         }
+///         "envelope_version": Option<"string"> - format of envelopes used for the connection: "v1" (DIDComm v1) or "v2" (DIDComm v2).
+///                                                default value is taken from `didcomm_envelope_version` config option (or "v1").
+///                                                "v2" is used only if the remote side publishes its keyAgreement keys as well.
+///         "return_route": Option<"string"> - `return_route` transport decorator added to every outbound message: "all", "thread" or "none" (default).
+///                                            replies the remote side returns in HTTP responses are handled as received messages
+///                                            by the next `update_state` call of the object waiting for them.
+///         "delivery_policy": Option<JSON object> - HTTP delivery policy for messages sent over the connection (same format as `http_delivery_policy` config option).
+///                                                  default value is taken from `http_delivery_policy` config option.
+///         "handshake_protocols": Option<["string"]> - handshake protocols to list in Out-of-Band Invitation in order of preference:
+///                                                     "connections" (Connections 1.0) or "didexchange" (DID Exchange 1.0).
+///                                                     default value is ["connections"]. Ignored for Invitations of Connections protocol.
+///         "peer_did": Option<"string"> - peer DID method used for the DIDDoc sent in connection request/response: "did:peer:2" or "did:peer:4".
+///                                        DIDDoc is bound to unqualified pairwise DID by default.
+///         "public_did_service": Option<bool> - Out-of-Band Invitation refers to the public DID as its only service instead of embedding
+///                                              the keys of the pairwise agent. Requires `use_public_did`. The invitee resolves endpoint
+///                                              and key of the DID from the Ledger. default value for `public_did_service`=false
+///         "multi_use": Option<bool> - invitation can be accepted by many invitees. Every received request is accepted by a new Connection
+///                                     object with its own pairwise keys, while this one stays in OfferSent state (see `vcx_connection_get_children`).
+///                                     Not supported for Out-of-Band invitations without handshake. default value for `multi_use`=false
+///         "invitation_ttl": Option<u64> - number of seconds the created invitation is valid for (`~timing.expires_time` decorator).
+///                                         requests received for the expired invitation are rejected with a problem report.
+///                                         Connection accepting the expired invitation fails. The invitation never expires by default.
+///         "handshake_timeout": Option<u64> - number of seconds the Connection can stay in every handshake state (OfferSent, RequestReceived).
+///                                            The Connection moves to the failed state (None) and sends a problem report to the other side
+///                                            on the next `vcx_connection_update_state` call after the timeout. No timeout by default.
 ///     }
 /// # Examples connection_options ->
 /// "{"connection_type":"SMS","phone":"123","use_public_did":true, "update_agent_info": Option<true>}"
@@ -1022,12 +1089,18 @@ pub extern fn vcx_connection_invite_details(command_handle: CommandHandle,
 ///
 /// msg: actual message to send
 ///
-/// send_msg_options: (applicable for `proprietary` communication method only)
+/// send_msg_options:
+///     proprietary:
 ///     {
 ///         msg_type: String, // type of message to send. can be any string.
 ///         msg_title: String, // message title (user notification)
 ///         ref_msg_id: Option<String>, // If responding to a message, id of the message
 ///     }
+///     aries:
+///     {
+///         delivery_policy: Option<JSON object>, // HTTP delivery policy for this message (same format as `http_delivery_policy` config option).
+///                                               // `retry_unconfirmed`=true allows resending the message if the remote side could already receive it.
+///     }
 ///
 /// # Example:
 /// msg ->
@@ -1379,6 +1452,746 @@ pub extern fn vcx_connection_send_discovery_features(command_handle: u32,
     error::SUCCESS.code_num
 }
 
+/// Rotate pairwise DID of the connection (Aries RFC 0794 DID Rotate).
+///
+/// Creates a new pairwise DID with keys and sends a `rotate` message with its DIDDoc signed by the current key.
+/// The current DID keeps receiving messages until the remote side acknowledges the rotation
+/// (the ack is received by `vcx_connection_update_state`), after that it is deleted.
+/// If the remote side rejects the rotation with a problem report, the connection goes back to the current DID.
+///
+/// Rotations started by the remote side are applied automatically by `vcx_connection_update_state`:
+/// the DIDDoc of the connection is replaced and the `ack` is sent back.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: connection to rotate DID for.
+///                    Note that connection must be in Accepted state and must not have pending rotation.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_rotate_did(command_handle: u32,
+                                        connection_handle: Handle<Connections>,
+                                        cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    info!("vcx_connection_rotate_did >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_rotate_did(command_handle: {}, connection_handle: {})",
+           command_handle, connection_handle);
+
+    spawn(move || {
+        match connection_handle.rotate_did() {
+            Ok(()) => {
+                trace!("vcx_connection_rotate_did(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_connection_rotate_did(command_handle: {}, rc: {})",
+                      command_handle, e);
+
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Send the current service endpoint and routing keys of the connection to the remote side.
+///
+/// Use it after the mediator or the Agency endpoint has changed: existing connections keep advertising
+/// the endpoint and routing keys of the DIDDoc sent during the handshake until the remote side receives the update.
+/// The connection takes the routing of the currently granted mediation (see `vcx_connection_request_mediation`)
+/// or the Agency endpoint and verkey of the current library configuration,
+/// and sends an `update` message with its DIDDoc signed by the pairwise key.
+/// The pairwise DID and keys stay the same.
+///
+/// Updates sent by the remote side are applied automatically by `vcx_connection_update_state`:
+/// the DIDDoc of the connection is replaced if the update is signed by the remote pairwise key and keeps its DID and recipient keys,
+/// then the `ack` is sent back. Other updates are rejected with a problem report.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+/// Service of one-time connections and connections using peer DIDs cannot be updated (rotate the DID instead).
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: connection to update service for.
+///                    Note that connection must be in Accepted state and must not have pending DID rotation.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_update_service(command_handle: u32,
+                                            connection_handle: Handle<Connections>,
+                                            cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    info!("vcx_connection_update_service >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_update_service(command_handle: {}, connection_handle: {})",
+           command_handle, connection_handle);
+
+    spawn(move || {
+        match connection_handle.update_service() {
+            Ok(()) => {
+                trace!("vcx_connection_update_service(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_connection_update_service(command_handle: {}, rc: {})",
+                      command_handle, e);
+
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Send the current service endpoint and routing keys to the remote side of every Aries connection in Accepted state.
+///
+/// Works as `vcx_connection_update_service` called for each connection.
+/// Failure to update one connection does not stop the update of the others.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides json with handles of updated connections and connections failed to be updated
+///
+/// # Example
+/// result -> {"updated": [2345, 3456], "failed": [4567]}
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_update_service_all(command_handle: u32,
+                                                cb: Option<extern fn(xcommand_handle: u32, err: u32, result: *const c_char)>) -> u32 {
+    info!("vcx_connection_update_service_all >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_update_service_all(command_handle: {})", command_handle);
+
+    spawn(move || {
+        let result = json!(update_service_all()).to_string();
+
+        trace!("vcx_connection_update_service_all_cb(command_handle: {}, rc: {}, result: {})",
+               command_handle, error::SUCCESS.as_str(), result);
+
+        let result = CStringUtils::string_to_cstring(result);
+        cb(command_handle, error::SUCCESS.code_num, result.as_ptr());
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get Connections spawned by the multi-use invitation.
+///
+/// Connection created with `multi_use` option (see `vcx_connection_connect`) stays in OfferSent state,
+/// every request received for its invitation by `vcx_connection_update_state` or `vcx_connection_update_state_with_message`
+/// is accepted by a new Connection object sharing the invitation but having its own pairwise keys.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle of the multi-use invitation.
+///
+/// cb: Callback that provides json array of handles of the spawned Connection objects
+///
+/// # Example
+/// children -> [2345, 3456]
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_get_children(command_handle: u32,
+                                          connection_handle: Handle<Connections>,
+                                          cb: Option<extern fn(xcommand_handle: u32, err: u32, children: *const c_char)>) -> u32 {
+    info!("vcx_connection_get_children >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_get_children(command_handle: {}, connection_handle: {})",
+           command_handle, connection_handle);
+
+    spawn(move || {
+        match connection_handle.get_children() {
+            Ok(children) => {
+                let children = json!(children.into_iter().map(u32::from).collect::<Vec<u32>>()).to_string();
+                trace!("vcx_connection_get_children_cb(command_handle: {}, connection_handle: {}, rc: {}, children: {})",
+                       command_handle, connection_handle, error::SUCCESS.as_str(), children);
+                let children = CStringUtils::string_to_cstring(children);
+                cb(command_handle, error::SUCCESS.code_num, children.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_connection_get_children_cb(command_handle: {}, connection_handle: {}, rc: {}, children: {})",
+                      command_handle, connection_handle, x, "null");
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Delete all Connections spawned by the multi-use invitation from the agency and release their handles.
+/// The invitation keeps accepting new requests until its Connection is deleted with `vcx_connection_delete_connection`.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle of the multi-use invitation.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_close_children(command_handle: u32,
+                                            connection_handle: Handle<Connections>,
+                                            cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    info!("vcx_connection_close_children >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_close_children(command_handle: {}, connection_handle: {})",
+           command_handle, connection_handle);
+
+    spawn(move || {
+        match connection_handle.close_children() {
+            Ok(()) => {
+                trace!("vcx_connection_close_children(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_connection_close_children(command_handle: {}, rc: {})",
+                      command_handle, e);
+
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get message history records of the Connection.
+/// Records are stored only if `message_history` setting is set to `true` (see `vcx_init_with_config`).
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle to get message history for.
+///
+/// query: Optional<string> - filter of records (see `vcx_get_message_history`). `connection` field is ignored.
+///     Pass NULL or "{}" to get all records of the Connection.
+///
+/// cb: Callback that provides the list of records ordered by time (see `vcx_get_message_history`)
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_get_message_history(command_handle: CommandHandle,
+                                                 connection_handle: Handle<Connections>,
+                                                 query: *const c_char,
+                                                 cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, messages: *const c_char)>) -> u32 {
+    info!("vcx_connection_get_message_history >>>");
+
+    check_useful_opt_c_str!(query, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_get_message_history(command_handle: {}, connection_handle: {}, query: {:?})",
+           command_handle, connection_handle, secret!(query));
+
+    let query = match HistoryQuery::from_opt_str(query.as_deref()) {
+        Ok(query) => query,
+        Err(err) => return err.into(),
+    };
+
+    spawn(move || {
+        match connection_handle.get_message_history(query) {
+            Ok(messages) => {
+                let messages = json!(messages).to_string();
+                trace!("vcx_connection_get_message_history_cb(command_handle: {}, connection_handle: {}, rc: {}, messages: {:?})",
+                       command_handle, connection_handle, error::SUCCESS.as_str(), secret!(messages));
+                let messages = CStringUtils::string_to_cstring(messages);
+                cb(command_handle, error::SUCCESS.code_num, messages.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_connection_get_message_history_cb(command_handle: {}, connection_handle: {}, rc: {})",
+                      command_handle, connection_handle, x);
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Store the Connection in the wallet or update its record if the Connection is already stored.
+///
+/// The record holds the serialized Connection (see `vcx_connection_serialize`) and tags describing the relationship.
+/// Tags set by the library are refreshed whenever the stored Connection changes its state or rotates its pairwise DID:
+///     "my_did": string - pairwise DID of the Connection
+///     "their_did": string - DID of the remote side (once it is known)
+///     "their_public_did": string - public DID of the inviter (if the invitation refers to it)
+///     "source_id": string - source id of the Connection
+///     "state": string - state of the Connection (see `vcx_connection_get_state`)
+///     "invitation_id": string - id of the invitation the Connection was created with
+///     "label": string - label of the invitation
+///     "goal_code": string - goal code of Out-of-Band invitation
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: Connection handle to store.
+///
+/// tags: Optional<string> - JSON object of user-defined string tags (names of the library tags cannot be used).
+///     Tag names starting with `~` are stored unencrypted and can be used in WQL comparison operators.
+///     Pass NULL to keep the user-defined tags of already stored Connection.
+///
+/// cb: Callback that provides id of the wallet record (identifier of the Connection kept on DID rotation,
+///     the same for every `vcx_connection_store` call on the Connection)
+///
+/// # Example
+/// tags -> {"group": "partners", "~priority": "1"}
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_store(command_handle: CommandHandle,
+                                   connection_handle: Handle<Connections>,
+                                   tags: *const c_char,
+                                   cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, id: *const c_char)>) -> u32 {
+    info!("vcx_connection_store >>>");
+
+    check_useful_opt_c_str!(tags, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_store(command_handle: {}, connection_handle: {}, tags: {:?})",
+           command_handle, connection_handle, secret!(tags));
+
+    spawn(move || {
+        match connection_handle.store(tags) {
+            Ok(id) => {
+                trace!("vcx_connection_store_cb(command_handle: {}, connection_handle: {}, rc: {}, id: {})",
+                       command_handle, connection_handle, error::SUCCESS.as_str(), id);
+                let id = CStringUtils::string_to_cstring(id);
+                cb(command_handle, error::SUCCESS.code_num, id.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_connection_store_cb(command_handle: {}, connection_handle: {}, rc: {})",
+                      command_handle, connection_handle, x);
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Load the Connection stored in the wallet (see `vcx_connection_store`) and create a new Connection object.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// id: id of the wallet record returned by `vcx_connection_store` or `vcx_connection_search`.
+///
+/// cb: Callback that provides Connection handle
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_load(command_handle: CommandHandle,
+                                  id: *const c_char,
+                                  cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, connection_handle: Handle<Connections>)>) -> u32 {
+    info!("vcx_connection_load >>>");
+
+    check_useful_c_str!(id, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_load(command_handle: {}, id: {})",
+           command_handle, id);
+
+    spawn(move || {
+        let (rc, handle) = match load_stored_connection(&id) {
+            Ok(x) => {
+                trace!("vcx_connection_load_cb(command_handle: {}, rc: {}, handle: {})",
+                       command_handle, error::SUCCESS.as_str(), x);
+                (error::SUCCESS.code_num, x)
+            }
+            Err(x) => {
+                warn!("vcx_connection_load_cb(command_handle: {}, rc: {}, handle: {})",
+                      command_handle, x, 0);
+                (x.into(), Handle::dummy())
+            }
+        };
+
+        cb(command_handle, rc, handle);
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Search Connections stored in the wallet (see `vcx_connection_store`).
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// query: Optional<string> - WQL query over record tags (see `vcx_connection_store`).
+///     Pass NULL or "{}" to get all stored Connections.
+///
+/// cb: Callback that provides the list of stored Connections
+///     [
+///         {
+///             "id": string, // id of the wallet record to use in `vcx_connection_load`
+///             "tags": {"tag_name": "tag_value", ...}, // library and user-defined tags
+///         }
+///     ]
+///
+/// # Example
+/// query -> {"their_did": "did:peer:2.Ez6LS...", "state": "4"}
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_search(command_handle: CommandHandle,
+                                    query: *const c_char,
+                                    cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, connections: *const c_char)>) -> u32 {
+    info!("vcx_connection_search >>>");
+
+    check_useful_opt_c_str!(query, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_search(command_handle: {}, query: {:?})",
+           command_handle, secret!(query));
+
+    spawn(move || {
+        match search_stored_connections(query.as_deref()) {
+            Ok(connections) => {
+                let connections = json!(connections).to_string();
+                trace!("vcx_connection_search_cb(command_handle: {}, rc: {}, connections: {:?})",
+                       command_handle, error::SUCCESS.as_str(), secret!(connections));
+                let connections = CStringUtils::string_to_cstring(connections);
+                cb(command_handle, error::SUCCESS.code_num, connections.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_connection_search_cb(command_handle: {}, rc: {})",
+                      command_handle, x);
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Delete the Connection record from the wallet (see `vcx_connection_store`).
+/// Connection objects loaded from the record are not affected.
+/// NOTE: the record is deleted automatically when the Connection is deleted with `vcx_connection_delete_connection`.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// id: id of the wallet record returned by `vcx_connection_store` or `vcx_connection_search`.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_delete_stored(command_handle: CommandHandle,
+                                           id: *const c_char,
+                                           cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32)>) -> u32 {
+    info!("vcx_connection_delete_stored >>>");
+
+    check_useful_c_str!(id, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_delete_stored(command_handle: {}, id: {})",
+           command_handle, id);
+
+    spawn(move || {
+        match delete_stored_connection(&id) {
+            Ok(()) => {
+                trace!("vcx_connection_delete_stored_cb(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(x) => {
+                warn!("vcx_connection_delete_stored_cb(command_handle: {}, rc: {})",
+                      command_handle, x);
+                cb(command_handle, x.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Send mediation request (Aries RFC 0211 Coordinate Mediation) to the mediator over the specified connection.
+///
+/// Once the mediator grants the request (the grant is received by `vcx_connection_update_state`),
+/// all new connections register their recipient keys on the mediator and publish its endpoint and routing keys
+/// in their DIDDoc instead of the Agency ones.
+/// Connections created before keep using the routing they were created with until `vcx_connection_update_service` is called for them.
+///
+/// Note that this function is useful in case `aries` communication method is used.
+/// In other cases it returns ActionNotSupported error.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// connection_handle: connection with the mediator.
+///                    Note that connection must be in Accepted state.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_connection_request_mediation(command_handle: u32,
+                                               connection_handle: Handle<Connections>,
+                                               cb: Option<extern fn(xcommand_handle: u32, err: u32)>) -> u32 {
+    info!("vcx_connection_request_mediation >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_connection_request_mediation(command_handle: {}, connection_handle: {})",
+           command_handle, connection_handle);
+
+    spawn(move || {
+        match connection_handle.request_mediation() {
+            Ok(()) => {
+                trace!("vcx_connection_request_mediation(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_connection_request_mediation(command_handle: {}, rc: {})",
+                      command_handle, e);
+
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get the state of the mediation requested with `vcx_connection_request_mediation`.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides mediation information
+///
+/// # Example
+/// info ->
+///     {
+///         "mediator_did": string, - DID of the mediator connection
+///         "state": string, - one of "requested", "granted", "denied"
+///         "endpoint": Optional<string>, - mediator endpoint (set if "granted")
+///         "routing_keys": Optional<array<string>>, - mediator routing keys (set if "granted")
+///         "recipient_keys": array<string>, - recipient keys registered on the mediator
+///         "live_delivery": bool - whether live delivery mode is enabled on the mediator
+///     }
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_get_mediation_info(command_handle: CommandHandle,
+                                     cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, info: *const c_char)>) -> u32 {
+    info!("vcx_get_mediation_info >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_get_mediation_info(command_handle: {})", command_handle);
+
+    spawn(move || {
+        match mediation::get_info() {
+            Ok(info) => {
+                trace!("vcx_get_mediation_info(command_handle: {}, rc: {}, info: {})",
+                       command_handle, error::SUCCESS.as_str(), secret!(info));
+                let info = CStringUtils::string_to_cstring(info);
+                cb(command_handle, error::SUCCESS.code_num, info.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_get_mediation_info(command_handle: {}, rc: {}, info: {})",
+                      command_handle, x, "null");
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get the status of the message queue kept by the granted mediator (Message Pickup protocol).
+///
+/// Note that messages queued on the mediator are picked up automatically on connection state updates.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides status of the queue
+///
+/// # Example
+/// status ->
+///     {
+///         "@type": "https://didcomm.org/messagepickup/2.0/status",
+///         "@id": string,
+///         "recipient_key": Optional<string>, - recipient key the status is related to
+///         "message_count": int, - number of messages in the queue
+///         "longest_waited_seconds": Optional<int>, - age of the oldest message in the queue
+///         "newest_received_time": Optional<string>, - time of receipt of the newest message
+///         "oldest_received_time": Optional<string>, - time of receipt of the oldest message
+///         "total_bytes": Optional<int>, - size of all messages in the queue
+///         "live_delivery": Optional<bool> - whether live delivery mode is enabled
+///     }
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_get_pickup_status(command_handle: CommandHandle,
+                                    cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, status: *const c_char)>) -> u32 {
+    info!("vcx_get_pickup_status >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_get_pickup_status(command_handle: {})", command_handle);
+
+    spawn(move || {
+        match pickup::get_status() {
+            Ok(status) => {
+                trace!("vcx_get_pickup_status(command_handle: {}, rc: {}, status: {})",
+                       command_handle, error::SUCCESS.as_str(), secret!(status));
+                let status = CStringUtils::string_to_cstring(status);
+                cb(command_handle, error::SUCCESS.code_num, status.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_get_pickup_status(command_handle: {}, rc: {}, status: {})",
+                      command_handle, x, "null");
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Enable or disable live delivery mode on the granted mediator (Message Pickup protocol).
+/// In live mode the mediator sends messages to the endpoint of the connection with the mediator
+/// as soon as they are received instead of keeping them in the queue.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// live_delivery: whether live delivery mode should be enabled.
+///
+/// cb: Callback that provides success or failure of request
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_set_pickup_live_delivery(command_handle: CommandHandle,
+                                           live_delivery: bool,
+                                           cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32)>) -> u32 {
+    info!("vcx_set_pickup_live_delivery >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_set_pickup_live_delivery(command_handle: {}, live_delivery: {})",
+           command_handle, live_delivery);
+
+    spawn(move || {
+        match pickup::set_live_delivery(live_delivery) {
+            Ok(()) => {
+                trace!("vcx_set_pickup_live_delivery(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_set_pickup_live_delivery(command_handle: {}, rc: {})",
+                      command_handle, e);
+
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Send a message to reuse existing Connection instead of setting up a new one
 /// as response on received Out-of-Band Invitation.
 ///
@@ -1428,6 +2241,9 @@ pub extern fn vcx_connection_send_discovery_features(command_handle: u32,
 ///                 "serviceEndpoint": string
 ///             }
 ///         ] - an item that is the equivalent of the service block of a DIDDoc that the receiver is to use in responding to the message.
+///               Items can also be DIDs resolving to such service block: did:peer:2, long form did:peer:4,
+///               or public did:sov / did:indy DIDs (verkey and `endpoint` ATTRIB are read from the Ledger).
+///               If the invitation has no services, its `public_did` is resolved instead.
 ///     }
 ///
 /// #Returns
@@ -1998,6 +2814,25 @@ mod tests {
         assert!(invite_details.is_some());
     }
 
+    #[test]
+    fn test_vcx_connection_update_service() {
+        let _setup = SetupMocks::init();
+
+        let handle = build_test_connection();
+
+        let (h, cb, r) = return_types::return_u32();
+        vcx_connection_update_service(h, handle, Some(cb));
+        let rc = r.recv_medium().unwrap_err();
+        assert_eq!(rc, error::ACTION_NOT_SUPPORTED.code_num);
+
+        // proprietary connections are skipped
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_connection_update_service_all(h, Some(cb)), error::SUCCESS.code_num);
+        let result: ServiceUpdateResult = ::serde_json::from_str(&r.recv_medium().unwrap().unwrap()).unwrap();
+        assert!(!result.updated.contains(&u32::from(handle)));
+        assert!(!result.failed.contains(&u32::from(handle)));
+    }
+
     #[test]
     fn test_vcx_connection_redirect() {
         let _setup = SetupMocks::init();
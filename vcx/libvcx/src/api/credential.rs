@@ -242,6 +242,72 @@ pub extern fn vcx_credential_create_with_offer(command_handle: CommandHandle,
     error::SUCCESS.code_num
 }
 
+/// Create a Credential object that starts the issuance by proposing a credential to an institution (Aries protocol only)
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Institution's personal identification for the credential, should be unique.
+///
+/// proposal: proposed credential data
+/// {
+///     "protocol_version": Optional<string> - version of Issue Credential protocol to use: "1.0" (default) or "2.0",
+///     "attributes": {"<attribute_name>": "<attribute_value>", ...} - proposed attribute values (credential preview),
+///     "schema_id": Optional<string> - identifier of the schema the credential should be based on,
+///     "cred_def_id": Optional<string> - identifier of the credential definition the credential should be issued by,
+///     // filters below are supported by "2.0" protocol only
+///     "schema_issuer_did": Optional<string>,
+///     "schema_name": Optional<string>,
+///     "schema_version": Optional<string>,
+///     "issuer_did": Optional<string>
+/// }
+///
+/// # Example
+/// proposal -> {"attributes": {"name": "Alice", "age": "25"}, "cred_def_id": "V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1"}
+///
+/// comment: (Optional) comment sent with the proposal.
+///
+/// cb: Callback that provides credential handle or error status
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+#[allow(unused_variables, unused_mut)]
+pub extern fn vcx_credential_create_proposal(command_handle: CommandHandle,
+                                             source_id: *const c_char,
+                                             proposal: *const c_char,
+                                             comment: *const c_char,
+                                             cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, credential_handle: Handle<Credentials>)>) -> u32 {
+    info!("vcx_credential_create_proposal >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+    check_useful_c_str!(source_id, VcxErrorKind::InvalidOption);
+    check_useful_c_str!(proposal, VcxErrorKind::InvalidOption);
+    check_useful_opt_c_str!(comment, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_credential_create_proposal(command_handle: {}, source_id: {}, proposal: {}, comment: {:?})",
+           command_handle, source_id, secret!(&proposal), secret!(&comment));
+
+    spawn(move || {
+        match credential::credential_create_proposal(&source_id, &proposal, comment) {
+            Ok(x) => {
+                trace!("vcx_credential_create_proposal_cb(command_handle: {}, source_id: {}, rc: {}, handle: {})",
+                       command_handle, source_id, error::SUCCESS.as_str(), x);
+                cb(command_handle, error::SUCCESS.code_num, x)
+            }
+            Err(x) => {
+                warn!("vcx_credential_create_proposal_cb(command_handle: {}, source_id: {}, rc: {}, handle: {})",
+                      command_handle, source_id, x, 0);
+                cb(command_handle, x.into(), Handle::dummy());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Accept credential for the given offer.
 ///
 /// This function performs the following actions:
@@ -499,6 +565,52 @@ pub extern fn vcx_credential_create_with_msgid(command_handle: CommandHandle,
     error::SUCCESS.code_num
 }
 
+/// Send a credential proposal to the connection, called after having created a Credential object with `vcx_credential_create_proposal`.
+/// The issuer replies with a credential offer which is received by `vcx_credential_update_state`.
+///
+/// #params
+/// command_handle: command handle to map callback to user context
+///
+/// credential_handle: credential handle that was provided during creation. Used to identify credential object
+///
+/// connection_handle: Connection handle that identifies pairwise connection
+///
+/// cb: Callback that provides error status of sending the proposal
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_credential_send_proposal(command_handle: CommandHandle,
+                                           credential_handle: Handle<Credentials>,
+                                           connection_handle: Handle<Connections>,
+                                           cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32)>) -> u32 {
+    info!("vcx_credential_send_proposal >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_credential_send_proposal(command_handle: {}, credential_handle: {}, connection_handle: {})",
+           command_handle, credential_handle, connection_handle);
+
+    spawn(move || {
+        match credential_handle.send_proposal(connection_handle) {
+            Ok(()) => {
+                trace!("vcx_credential_send_proposal_cb(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_credential_send_proposal_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Approves the credential offer and submits a credential request. The result will be a credential stored in the prover's wallet.
 ///
 /// #params
@@ -1121,7 +1233,15 @@ pub extern fn vcx_credential_get_problem_report(command_handle: CommandHandle,
 ///     "schema_id": string,
 ///     "cred_def_id": string,
 ///     "rev_reg_id": Optional<string>,
-///     "cred_rev_id": Optional<string>
+///     "cred_rev_id": Optional<string>,
+///     "revoked": bool, // whether the issuer sent notification about revocation of the credential
+///     "revocation": Optional<{ // received revocation notification
+///         "cred_id": string,
+///         "rev_reg_id": Optional<string>,
+///         "cred_rev_id": Optional<string>,
+///         "comment": Optional<string>,
+///         "received": string // UTC time in RFC 3339 format
+///     }>
 /// }
 ///
 ///
@@ -1205,6 +1325,22 @@ mod tests {
         assert_eq!(err, error::INVALID_CREDENTIAL_OFFER.code_num);
     }
 
+    #[test]
+    fn test_vcx_credential_create_proposal() {
+        let _setup = SetupMocks::init();
+
+        let (h, cb, r) = return_types::return_u32_crdh();
+        let proposal = CString::new(json!({"attributes": {"name": "Alice"}}).to_string()).unwrap();
+        assert_eq!(error::SUCCESS.code_num, vcx_credential_create_proposal(h,
+                                                                           "test_create_proposal\0".as_ptr().cast(),
+                                                                           proposal.as_ptr(),
+                                                                           ptr::null(),
+                                                                           Some(cb)));
+        let handle = r.recv_medium().unwrap();
+
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state().unwrap());
+    }
+
     #[test]
     fn test_vcx_credential_serialize_and_deserialize() {
         let _setup = SetupMocks::init();
@@ -30,6 +30,8 @@ use vdrtools_sys::CommandHandle;
 ///     support_revocation: true|false - Optional, by default its false
 ///     tails_file: path to tails file - Optional if support_revocation is false
 ///     max_creds: size of tails file - Optional if support_revocation is false
+///     rotation_threshold: number of credentials left in the revocation registry at which the next registry is created and published
+///         - Optional, by default 10% of max_creds
 /// # Examples config ->  "{}" | "{"support_revocation":false}" | "{"support_revocation":true, "tails_file": "/tmp/tailsfile.txt", "max_creds": 1}"
 /// cb: Callback that provides CredentialDef handle and error status of request.
 ///
@@ -201,6 +203,8 @@ pub extern fn vcx_credentialdef_create_with_id(command_handle: CommandHandle,
 ///     support_revocation: true|false - Optional, by default its false
 ///     tails_file: path to tails file - Optional if support_revocation is false
 ///     max_creds: size of tails file - Optional if support_revocation is false
+///     rotation_threshold: number of credentials left in the revocation registry at which the next registry is created and published
+///         - Optional, by default 10% of max_creds
 ///
 /// endorser: DID of the Endorser that will submit the transaction.
 ///
@@ -414,6 +418,50 @@ pub extern fn vcx_credentialdef_get_cred_def_id(command_handle: CommandHandle,
     error::SUCCESS.code_num
 }
 
+/// Retrieves revocation registries of the credential definition
+///
+/// #Params
+/// cred_def_handle: CredDef handle that was provided during creation. Used to access credentialdef object
+///
+/// cb: Callback that provides revocation registries json and provides error status
+///     [{"rev_reg_id": string, "rev_reg_def": string, "tails_file": string, "max_creds": number, "issued": number}]
+///     `issued` is the number of credentials created against the registry (credentials offered but not issued yet are not counted).
+///     The registry used for issuing is the first one with `issued` less than `max_creds`.
+///     Empty list is returned if the credential definition does not support revocation or was created from id.
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_credentialdef_get_rev_regs(command_handle: CommandHandle,
+                                             cred_def_handle: Handle<CredentialDef>,
+                                             cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, rev_regs: *const c_char)>) -> u32 {
+    info!("vcx_credentialdef_get_rev_regs >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_credentialdef_get_rev_regs(command_handle: {}, cred_def_handle: {})", command_handle, cred_def_handle);
+
+    spawn(move || {
+        match cred_def_handle.get_rev_regs() {
+            Ok(x) => {
+                trace!("vcx_credentialdef_get_rev_regs(command_handle: {}, cred_def_handle: {}, rc: {}, rev_regs: {})",
+                       command_handle, cred_def_handle, error::SUCCESS.as_str(), secret!(x));
+                let msg = CStringUtils::string_to_cstring(x);
+                cb(command_handle, error::SUCCESS.code_num, msg.as_ptr());
+            },
+            Err(x) => {
+                warn!("vcx_credentialdef_get_rev_regs(command_handle: {}, cred_def_handle: {}, rc: {}, rev_regs: {})",
+                      command_handle, cred_def_handle, x, "");
+                cb(command_handle, x.into(), ptr::null_mut());
+            },
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Get the payment transaction information generated when paying the ledger fee
 ///
 /// #param
@@ -747,6 +795,30 @@ mod tests {
         r.recv_medium().unwrap();
     }
 
+    #[test]
+    fn test_vcx_creddef_get_rev_regs() {
+        let _setup = SetupMocks::init();
+
+        let (h, cb, r) = return_types::return_u32_cdh();
+        let details = CString::new(credential_def::tests::revocation_details(true).to_string()).unwrap();
+        assert_eq!(vcx_credentialdef_create(h,
+                                            TEST_SOURCE_ID,
+                                            TEST_CRED_DEF,
+                                            SCHEMA_ID_CSTR,
+                                            ISSUER_DID,
+                                            TAG,
+                                            details.as_ptr(),
+                                            0,
+                                            Some(cb)), error::SUCCESS.code_num);
+        let handle = r.recv_medium().unwrap();
+
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_credentialdef_get_rev_regs(h, handle, Some(cb)), error::SUCCESS.code_num);
+        let rev_regs: serde_json::Value = serde_json::from_str(&r.recv_medium().unwrap().unwrap()).unwrap();
+        assert_eq!(1, rev_regs.as_array().unwrap().len());
+        assert_eq!(0, rev_regs[0]["issued"]);
+    }
+
     #[test]
     fn test_vcx_prepare_cred_def_success() {
         let _setup = SetupMocks::init();
@@ -32,7 +32,7 @@ use crate::utils::object_cache::Handle;
         VcxStateType::VcxStateAccepted - once `vcx_issuer_send_credential` (send `CRED` message) is called.
 
     aries:
-        VcxStateType::VcxStateInitialized - once `vcx_issuer_create_credential` (create IssuerCredential object) is called.
+        VcxStateType::VcxStateInitialized - once `vcx_issuer_create_credential` or `vcx_issuer_create_credential_from_proposal` (create IssuerCredential object) is called.
 
         VcxStateType::VcxStateOfferSent - once `vcx_issuer_send_credential_offer` (send `CredentialOffer` message) is called.
 
@@ -56,6 +56,7 @@ use crate::utils::object_cache::Handle;
 
     aries: RFC - https://github.com/hyperledger/aries-rfcs/tree/7b6b93acbaf9611d3c892c4bada142fe2613de6e/features/0036-issue-credential
         VcxStateType::None - `vcx_issuer_create_credential` - VcxStateType::VcxStateInitialized
+        VcxStateType::None - `vcx_issuer_create_credential_from_proposal` - VcxStateType::VcxStateInitialized
 
         VcxStateType::VcxStateInitialized - `vcx_issuer_send_credential_offer` - VcxStateType::VcxStateOfferSent
 
@@ -65,6 +66,7 @@ use crate::utils::object_cache::Handle;
         VcxStateType::VcxStateRequestReceived - vcx_issuer_send_credential` - VcxStateType::VcxStateAccepted
 
         VcxStateType::VcxStateAccepted - received `Ack` - VcxStateType::VcxStateAccepted
+        VcxStateType::VcxStateAccepted - `vcx_issuer_send_revocation_notification` (send `RevocationNotification` message) - VcxStateType::VcxStateAccepted
 
     # Messages
 
@@ -80,6 +82,8 @@ use crate::utils::object_cache::Handle;
         Credential - https://github.com/hyperledger/aries-rfcs/tree/7b6b93acbaf9611d3c892c4bada142fe2613de6e/features/0036-issue-credential#issue-credential
         ProblemReport - https://github.com/hyperledger/aries-rfcs/tree/7b6b93acbaf9611d3c892c4bada142fe2613de6e/features/0035-report-problem#the-problem-report-message-type
         Ack - https://github.com/hyperledger/aries-rfcs/tree/master/features/0015-acks#explicit-acks
+        RevocationNotification 1.0 - https://github.com/hyperledger/aries-rfcs/tree/main/features/0183-revocation-notification#revoke-message
+        RevocationNotification 2.0 - https://github.com/hyperledger/aries-rfcs/tree/main/features/0721-revocation-notification-v2#revoke-message
 */
 
 /// Create a Issuer Credential object that provides a credential for an enterprise's user
@@ -171,6 +175,78 @@ pub extern fn vcx_issuer_create_credential(command_handle: CommandHandle,
     error::SUCCESS.code_num
 }
 
+/// Create a Issuer Credential object answering the credential proposal received from the user (Aries protocol only).
+/// Call `vcx_issuer_send_credential_offer` to reply with the credential offer on the thread of the proposal.
+/// Assumes a credential definition has been already written to the ledger.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// source_id: Enterprise's personal identification for the credential, should be unique.
+///
+/// cred_def_handle: handle of credential definition to issue the credential with
+///     It must satisfy the `cred_def_id`, `issuer_did` and schema filters of the proposal.
+///
+/// proposal: received credential proposal message (`propose-credential` of Issue Credential 1.0 or 2.0 protocol)
+///
+/// credential_data: (Optional) data attributes offered to person in the credential.
+///     The attribute values of the proposal are offered if not set.
+///
+/// credential_name: Name of the credential - ex. Drivers Licence
+///
+/// cb: Callback that provides credential handle and error status of request
+///
+/// #Returns
+/// Error code as a u32
+///
+/// # Example credential_data -> "{"state":"UT"}"
+#[no_mangle]
+#[allow(unused_variables, unused_mut)]
+pub extern fn vcx_issuer_create_credential_from_proposal(command_handle: CommandHandle,
+                                                         source_id: *const c_char,
+                                                         cred_def_handle: Handle<CredentialDef>,
+                                                         proposal: *const c_char,
+                                                         credential_data: *const c_char,
+                                                         credential_name: *const c_char,
+                                                         cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, credential_handle: Handle<IssuerCredentials>)>) -> u32 {
+    info!("vcx_issuer_create_credential_from_proposal >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+    check_useful_c_str!(source_id, VcxErrorKind::InvalidOption);
+    check_useful_c_str!(proposal, VcxErrorKind::InvalidOption);
+    check_useful_opt_c_str!(credential_data, VcxErrorKind::InvalidOption);
+    check_useful_c_str!(credential_name, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_issuer_create_credential_from_proposal(command_handle: {}, source_id: {}, cred_def_handle: {}, proposal: {}, credential_data: {:?}, credential_name: {})",
+           command_handle,
+           source_id,
+           cred_def_handle,
+           secret!(&proposal),
+           secret!(&credential_data),
+           secret!(credential_name));
+
+    spawn(move || {
+        let (rc, handle) = match issuer_credential::issuer_credential_create_from_proposal(cred_def_handle, source_id, &proposal, credential_data, credential_name) {
+            Ok(x) => {
+                trace!("vcx_issuer_create_credential_from_proposal_cb(command_handle: {}, rc: {}, handle: {})",
+                       command_handle, error::SUCCESS.as_str(), x);
+                (error::SUCCESS.code_num, x)
+            }
+            Err(x) => {
+                warn!("vcx_issuer_create_credential_from_proposal_cb(command_handle: {}, rc: {}, handle: {})",
+                      command_handle, x, 0);
+                (x.into(), Handle::dummy())
+            }
+        };
+
+        cb(command_handle, rc, handle);
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Send a credential offer to user showing what will be included in the actual credential
 ///
 /// #Params
@@ -698,7 +774,12 @@ pub extern fn vcx_issuer_credential_get_payment_txn(command_handle: CommandHandl
     error::SUCCESS.code_num
 }
 
-/// Revoke Credential
+/// Revoke Credential and publish the revocation to the Ledger.
+/// Revocations of the same revocation registry made by `vcx_issuer_revoke_credential_local` before are published as well.
+/// If the credential is revoked in the wallet but publishing fails, the error is returned and the revocation stays pending:
+/// the credential is reported as revoked and calling this function again publishes the pending revocation.
+///
+/// Supported for both proprietary and Aries credentials issued with a revocable credential definition.
 ///
 /// #Params
 /// command_handle: command handle to map callback to user context.
@@ -740,6 +821,254 @@ pub extern fn vcx_issuer_revoke_credential(command_handle: CommandHandle,
     error::SUCCESS.code_num
 }
 
+/// Revoke Credential in the wallet without publishing the revocation to the Ledger.
+/// The revocation is added to the pending revocations of the revocation registry, which are published at once:
+///  - with the next call of `vcx_issuer_revoke_credential` for a credential of the same revocation registry
+///  - by `vcx_issuer_publish_pending_revocations`
+///  - in background once `revocation_publish_interval` seconds (config option) passed since the first pending revocation
+/// The revocation is recorded in the wallet before the credential is revoked, so a revocation interrupted by a process stop is completed
+/// on the next access to the pending revocations of the registry.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+///
+/// cb: Callback that provides error status of revoking the credential
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_revoke_credential_local(command_handle: CommandHandle,
+                                                 credential_handle: Handle<IssuerCredentials>,
+                                                 cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32)>) -> u32 {
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    info!("vcx_issuer_revoke_credential_local(command_handle: {}, credential_handle: {})",
+          command_handle, credential_handle);
+
+    spawn(move || {
+        let err = match credential_handle.revoke_credential_local() {
+            Ok(()) => {
+                info!("vcx_issuer_revoke_credential_local_cb(command_handle: {}, credential_handle: {}, rc: {})",
+                      command_handle, credential_handle, error::SUCCESS.as_str());
+                error::SUCCESS.code_num
+            }
+            Err(x) => {
+                warn!("vcx_issuer_revoke_credential_local_cb(command_handle: {}, credential_handle: {}, rc: {})",
+                      command_handle, credential_handle, x);
+                x.into()
+            }
+        };
+
+        cb(command_handle, err);
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Check whether the credential has been revoked by `vcx_issuer_revoke_credential` or `vcx_issuer_revoke_credential_local`.
+/// A credential can be revoked only once, the following revocations return InvalidState error.
+///
+/// NOTE: Supported for `aries` Issuer Credential objects only.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+///
+/// cb: Callback that provides whether the credential has been revoked
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_credential_is_revoked(command_handle: CommandHandle,
+                                               credential_handle: Handle<IssuerCredentials>,
+                                               cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, revoked: bool)>) -> u32 {
+    info!("vcx_issuer_credential_is_revoked >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_issuer_credential_is_revoked(command_handle: {}, credential_handle: {})",
+           command_handle, credential_handle);
+
+    spawn(move || {
+        match credential_handle.is_revoked() {
+            Ok(revoked) => {
+                trace!("vcx_issuer_credential_is_revoked_cb(command_handle: {}, credential_handle: {}, rc: {}, revoked: {})",
+                       command_handle, credential_handle, error::SUCCESS.as_str(), revoked);
+                cb(command_handle, error::SUCCESS.code_num, revoked);
+            }
+            Err(x) => {
+                warn!("vcx_issuer_credential_is_revoked_cb(command_handle: {}, credential_handle: {}, rc: {}, revoked: {})",
+                      command_handle, credential_handle, x, false);
+                cb(command_handle, x.into(), false);
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Notify the holder about revocation of the credential (made by `vcx_issuer_revoke_credential` or `vcx_issuer_revoke_credential_local`).
+/// Sends `RevocationNotification` message over the connection the credential was issued on.
+/// The credential must be revoked.
+///
+/// NOTE: Supported for `aries` Issuer Credential objects only.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// credential_handle: Credential handle that was provided during creation. Used to identify credential object
+///
+/// options: (Optional) notification options as JSON string
+///     {
+///         "version": Optional<string> - version of Revocation Notification protocol: "1.0" (default) or "2.0"
+///                                         1.0 identifies the credential by the thread id of its issuance,
+///                                         2.0 identifies the credential by `<rev_reg_id>::<cred_rev_id>`
+///         "comment": Optional<string> - comment for the holder
+///     }
+///
+/// cb: Callback that provides error status of sending the notification
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_send_revocation_notification(command_handle: CommandHandle,
+                                                      credential_handle: Handle<IssuerCredentials>,
+                                                      options: *const c_char,
+                                                      cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32)>) -> u32 {
+    info!("vcx_issuer_send_revocation_notification >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+    check_useful_opt_c_str!(options, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_issuer_send_revocation_notification(command_handle: {}, credential_handle: {}, options: {:?})",
+           command_handle, credential_handle, options);
+
+    spawn(move || {
+        let err = match credential_handle.send_revocation_notification(options.as_deref()) {
+            Ok(()) => {
+                trace!("vcx_issuer_send_revocation_notification_cb(command_handle: {}, credential_handle: {}, rc: {})",
+                       command_handle, credential_handle, error::SUCCESS.as_str());
+                error::SUCCESS.code_num
+            }
+            Err(x) => {
+                warn!("vcx_issuer_send_revocation_notification_cb(command_handle: {}, credential_handle: {}, rc: {})",
+                      command_handle, credential_handle, x);
+                x.into()
+            }
+        };
+
+        cb(command_handle, err);
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get revocations made by `vcx_issuer_revoke_credential_local` which are not published to the Ledger yet.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides pending revocations of all revocation registries as JSON string
+///     [
+///         {
+///             "rev_reg_id": string - id of revocation registry,
+///             "cred_rev_ids": [string] - revocation ids of revoked credentials,
+///             "since": string - UTC time of the first pending revocation (RFC 3339),
+///             "delta": Optional<string> - merged delta of revocation registry to publish
+///             "unmerged": [string] - revocation ids of interrupted revocations, completed on the next access
+///             "without_delta": [string] - revocation ids of interrupted revocations which deltas are lost,
+///                 they are published with the next revocation of the registry
+///             "tails_file": Optional<string> - tails file of revocation registry
+///         }
+///     ]
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_get_pending_revocations(command_handle: CommandHandle,
+                                                 cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, pending: *const c_char)>) -> u32 {
+    info!("vcx_issuer_get_pending_revocations >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_issuer_get_pending_revocations(command_handle: {})", command_handle);
+
+    spawn(move || {
+        match issuer_credential::get_pending_revocations() {
+            Ok(pending) => {
+                trace!("vcx_issuer_get_pending_revocations_cb(command_handle: {}, rc: {}, pending: {})",
+                       command_handle, error::SUCCESS.as_str(), pending);
+                let pending = CStringUtils::string_to_cstring(pending);
+                cb(command_handle, error::SUCCESS.code_num, pending.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_issuer_get_pending_revocations_cb(command_handle: {}, rc: {})",
+                      command_handle, x);
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Publish pending revocations (made by `vcx_issuer_revoke_credential_local`) to the Ledger.
+/// Revocations of a revocation registry are published as a single merged delta of the registry.
+///
+/// #Params
+/// command_handle: command handle to map callback to user context.
+///
+/// rev_reg_id: (Optional) id of revocation registry to publish pending revocations of.
+///     Pending revocations of all revocation registries are published if not set.
+///
+/// cb: Callback that provides ids of revocation registries which revocations were published as JSON array of strings
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_issuer_publish_pending_revocations(command_handle: CommandHandle,
+                                                     rev_reg_id: *const c_char,
+                                                     cb: Option<extern fn(xcommand_handle: CommandHandle, err: u32, published: *const c_char)>) -> u32 {
+    info!("vcx_issuer_publish_pending_revocations >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+    check_useful_opt_c_str!(rev_reg_id, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_issuer_publish_pending_revocations(command_handle: {}, rev_reg_id: {:?})",
+           command_handle, rev_reg_id);
+
+    spawn(move || {
+        match issuer_credential::publish_pending_revocations(rev_reg_id.as_deref()) {
+            Ok(published) => {
+                trace!("vcx_issuer_publish_pending_revocations_cb(command_handle: {}, rc: {}, published: {})",
+                       command_handle, error::SUCCESS.as_str(), published);
+                let published = CStringUtils::string_to_cstring(published);
+                cb(command_handle, error::SUCCESS.code_num, published.as_ptr());
+            }
+            Err(x) => {
+                warn!("vcx_issuer_publish_pending_revocations_cb(command_handle: {}, rc: {})",
+                      command_handle, x);
+                cb(command_handle, x.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 /// Get Problem Report message for Issuer Credential object in Failed or Rejected state.
 ///
 /// #Params
@@ -792,7 +1121,7 @@ pub mod tests {
     use std::ptr;
     use crate::settings;
     use crate::utils::{
-        constants::CREDENTIAL_REQ_RESPONSE_STR,
+        constants::{CREDENTIAL_REQ_RESPONSE_STR, CRED_DEF_ID, SCHEMA_ID},
         get_temp_dir_path
     };
     use crate::api::{return_types, VcxStateType};
@@ -908,6 +1237,27 @@ pub mod tests {
         let _ = r.recv_medium().is_err();
     }
 
+    #[test]
+    fn test_vcx_issuer_create_credential_from_proposal() {
+        let _setup = SetupAriesMocks::init();
+
+        let proposal = crate::aries::messages::issuance::credential_proposal::tests::_credential_proposal_for_cred_def(CRED_DEF_ID, SCHEMA_ID);
+        let proposal = CString::new(json!(proposal).to_string()).unwrap();
+
+        let (h, cb, r) = return_types::return_u32_ih();
+        assert_eq!(vcx_issuer_create_credential_from_proposal(h,
+                                                              DEFAULT_CREDENTIAL_NAME_CSTR,
+                                                              crate::credential_def::tests::create_cred_def_fake(),
+                                                              proposal.as_ptr(),
+                                                              ptr::null(),
+                                                              DEFAULT_CREDENTIAL_NAME_CSTR,
+                                                              Some(cb)),
+                   error::SUCCESS.code_num);
+        let handle = r.recv_short().unwrap();
+
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state().unwrap());
+    }
+
     #[test]
     fn test_vcx_issuer_credential_serialize_deserialize() {
         let _setup = SetupMocks::init();
@@ -1054,6 +1404,73 @@ pub mod tests {
         r.recv_medium().unwrap();
     }
 
+    #[test]
+    fn test_vcx_issuer_revoke_credential_local() {
+        let _setup = SetupMocks::init();
+
+        let handle = issuer_credential::from_string(&issuer_credential_state_accepted()).unwrap();
+
+        let (h, cb, r) = return_types::return_u32();
+        assert_eq!(vcx_issuer_revoke_credential_local(h,
+                                                      handle,
+                                                      Some(cb)),
+                   error::SUCCESS.code_num);
+        r.recv_medium().unwrap();
+    }
+
+    #[test]
+    fn test_vcx_issuer_credential_is_revoked() {
+        let _setup = SetupMocks::init();
+
+        let handle = issuer_credential::from_string(&issuer_credential_state_accepted()).unwrap();
+
+        // proprietary credential
+        let (h, cb, r) = return_types::return_u32_bool();
+        assert_eq!(vcx_issuer_credential_is_revoked(h, handle, Some(cb)), error::SUCCESS.code_num);
+        assert_eq!(error::ACTION_NOT_SUPPORTED.code_num, r.recv_medium().unwrap_err());
+    }
+
+    #[test]
+    fn test_vcx_issuer_send_revocation_notification() {
+        let _setup = SetupMocks::init();
+
+        let handle = issuer_credential::from_string(&issuer_credential_state_accepted()).unwrap();
+
+        // proprietary credential
+        let (h, cb, r) = return_types::return_u32();
+        assert_eq!(vcx_issuer_send_revocation_notification(h,
+                                                           handle,
+                                                           ptr::null(),
+                                                           Some(cb)),
+                   error::SUCCESS.code_num);
+        assert_eq!(error::ACTION_NOT_SUPPORTED.code_num, r.recv_medium().unwrap_err());
+
+        let (h, cb, r) = return_types::return_u32();
+        assert_eq!(vcx_issuer_send_revocation_notification(h,
+                                                           handle,
+                                                           "invalid\0".as_ptr().cast(),
+                                                           Some(cb)),
+                   error::SUCCESS.code_num);
+        assert_eq!(error::INVALID_JSON.code_num, r.recv_medium().unwrap_err());
+    }
+
+    #[test]
+    fn test_vcx_issuer_pending_revocations() {
+        let _setup = SetupMocks::init();
+
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_issuer_get_pending_revocations(h, Some(cb)), error::SUCCESS.code_num);
+        assert_eq!("[]", r.recv_medium().unwrap().unwrap());
+
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_issuer_publish_pending_revocations(h, "rev_reg_id\0".as_ptr().cast(), Some(cb)), error::SUCCESS.code_num);
+        assert_eq!(r#"["rev_reg_id"]"#, r.recv_medium().unwrap().unwrap());
+
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_issuer_publish_pending_revocations(h, ptr::null(), Some(cb)), error::SUCCESS.code_num);
+        assert_eq!("[]", r.recv_medium().unwrap().unwrap());
+    }
+
     #[test]
     fn test_vcx_issuer_credential_release() {
         let _setup = SetupMocks::init();
@@ -19,7 +19,11 @@ use crate::agent::messages::update_agent::ComMethod;
 use crate::aries::messages::message_with_attachment::extract_attached_message;
 use crate::aries::messages::message_with_thread::extract_thread_id;
 use crate::aries::utils::resolve_message_by_url;
+use crate::aries::inbound;
+use crate::aries::utils::processed_messages;
+use crate::aries::utils::message_history::{self, HistoryQuery};
 use crate::utils::libindy::anoncreds::holder::Holder;
+use crate::session;
 
 /// Provision an agent in the agency, populate configuration and wallet for this agent.
 ///
@@ -1082,6 +1086,409 @@ pub extern fn vcx_extract_thread_id(command_handle: CommandHandle,
     error::SUCCESS.code_num
 }
 
+/// Start the embedded HTTP endpoint receiving packed Aries messages from the remote sides directly (without an Agency).
+/// Every received message is unpacked and used to update the state of the Connection, IssuerCredential, Credential,
+/// Proof or DisclosedProof object which uses the recipient key of the message.
+/// NOTE: the endpoint URL must be reachable by the remote sides and set as the service endpoint of your DIDDoc.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// address: local socket address to listen on (e.g. "0.0.0.0:8080")
+///
+/// cb: Callback that provides the address the endpoint is bound to
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_start_inbound_endpoint(command_handle: CommandHandle,
+                                         address: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                              err: u32,
+                                                              address: *const c_char)>) -> u32 {
+    info!("vcx_start_inbound_endpoint >>>");
+
+    check_useful_c_str!(address, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_start_inbound_endpoint(command_handle: {}, address: {:?})",
+           command_handle, address);
+
+    spawn(move || {
+        match inbound::server::start(&address) {
+            Ok(address) => {
+                trace!("vcx_start_inbound_endpoint_cb(command_handle: {}, rc: {}, address: {:?})",
+                       command_handle, error::SUCCESS.as_str(), address);
+
+                let address = CStringUtils::string_to_cstring(address);
+                cb(command_handle, error::SUCCESS.code_num, address.as_ptr());
+            }
+            Err(e) => {
+                warn!("vcx_start_inbound_endpoint_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Stop the embedded HTTP endpoint started by `vcx_start_inbound_endpoint`.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides result code
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_stop_inbound_endpoint(command_handle: CommandHandle,
+                                        cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                             err: u32)>) -> u32 {
+    info!("vcx_stop_inbound_endpoint >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_stop_inbound_endpoint(command_handle: {})", command_handle);
+
+    spawn(move || {
+        match inbound::server::stop() {
+            Ok(()) => {
+                trace!("vcx_stop_inbound_endpoint_cb(command_handle: {}, rc: {})",
+                       command_handle, error::SUCCESS.as_str());
+
+                cb(command_handle, error::SUCCESS.code_num);
+            }
+            Err(e) => {
+                warn!("vcx_stop_inbound_endpoint_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get records of the messages which have already been handled by Connection, Credential, Proof and other objects.
+/// Messages having a record are skipped when they are received again.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// query: WQL query over record tags:
+///     {
+///         "connection": Optional<string> - identifier of the connection the message was received over
+///                                           (`connection_id` of the agent in the serialized Connection; kept on DID rotation)
+///         "thread_id": Optional<string> - thread id of the message
+///         "message_id": Optional<string> - id of the message
+///     }
+///     Use "{}" to get all records.
+///
+/// cb: Callback that provides the list of records
+///     [
+///         {
+///             "connection": string,
+///             "thread_id": string,
+///             "message_id": string,
+///             "type": string, // type of the message
+///             "processed_at": string, // UTC time the message was handled at (RFC 3339)
+///         }
+///     ]
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_get_processed_messages(command_handle: CommandHandle,
+                                         query: *const c_char,
+                                         cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                              err: u32,
+                                                              messages: *const c_char)>) -> u32 {
+    info!("vcx_get_processed_messages >>>");
+
+    check_useful_c_str!(query, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_get_processed_messages(command_handle: {}, query: {:?})",
+           command_handle, secret!(query));
+
+    spawn(move || {
+        match processed_messages::query(&query) {
+            Ok(messages) => {
+                let messages = json!(messages).to_string();
+
+                trace!("vcx_get_processed_messages_cb(command_handle: {}, rc: {}, messages: {:?})",
+                       command_handle, error::SUCCESS.as_str(), secret!(messages));
+
+                let messages = CStringUtils::string_to_cstring(messages);
+                cb(command_handle, error::SUCCESS.code_num, messages.as_ptr());
+            }
+            Err(e) => {
+                warn!("vcx_get_processed_messages_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Delete records of the handled messages (see `vcx_get_processed_messages`).
+/// The messages of deleted records will be handled again if they are received once more.
+/// NOTE: records of a connection are deleted automatically when the connection is deleted.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// query: WQL query over record tags (see `vcx_get_processed_messages`). Use "{}" to delete all records.
+///
+/// cb: Callback that provides the number of deleted records
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_purge_processed_messages(command_handle: CommandHandle,
+                                           query: *const c_char,
+                                           cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                                err: u32,
+                                                                count: u32)>) -> u32 {
+    info!("vcx_purge_processed_messages >>>");
+
+    check_useful_c_str!(query, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_purge_processed_messages(command_handle: {}, query: {:?})",
+           command_handle, secret!(query));
+
+    spawn(move || {
+        match processed_messages::purge(&query) {
+            Ok(count) => {
+                trace!("vcx_purge_processed_messages_cb(command_handle: {}, rc: {}, count: {})",
+                       command_handle, error::SUCCESS.as_str(), count);
+
+                cb(command_handle, error::SUCCESS.code_num, count as u32);
+            }
+            Err(e) => {
+                warn!("vcx_purge_processed_messages_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into(), 0);
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Get message history records of all connections.
+/// Records are stored only if `message_history` setting is set to `true` (see `vcx_init_with_config`).
+/// Records contain metadata of sent and received messages only, the content of messages is not stored.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// query: Optional<string> - filter of records (all set fields must match):
+///     {
+///         "connection": Optional<string> - identifier of the connection the message was exchanged over
+///                                           (`connection_id` of the agent in the serialized Connection; kept on DID rotation)
+///         "thread_id": Optional<string> - thread id of the message
+///         "protocol": Optional<string> - protocol family of the message type (e.g. "issue-credential")
+///         "direction": Optional<string> - one of "sent", "received"
+///         "status": Optional<string> - one of "sent", "failed", "received", "processed"
+///         "from": Optional<int> - messages sent or received at or after this time (Unix timestamp in seconds)
+///         "to": Optional<int> - messages sent or received at or before this time (Unix timestamp in seconds)
+///         "limit": Optional<int> - maximum number of records to return
+///         "offset": Optional<int> - number of the first matching records (ordered by time) to skip
+///     }
+///     Pass NULL or "{}" to get all records.
+///
+/// cb: Callback that provides the list of records ordered by time
+///     [
+///         {
+///             "connection": string,
+///             "thread_id": string,
+///             "message_id": string,
+///             "type": string, // type of the message
+///             "protocol": string,
+///             "direction": string, // "sent" or "received"
+///             "status": string, // "sent", "failed" (for sent messages), "received", "processed" (for received messages)
+///             "timestamp": string, // UTC time the message was sent or received at (RFC 3339)
+///         }
+///     ]
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_get_message_history(command_handle: CommandHandle,
+                                      query: *const c_char,
+                                      cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                           err: u32,
+                                                           messages: *const c_char)>) -> u32 {
+    info!("vcx_get_message_history >>>");
+
+    check_useful_opt_c_str!(query, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_get_message_history(command_handle: {}, query: {:?})",
+           command_handle, secret!(query));
+
+    let query = match HistoryQuery::from_opt_str(query.as_deref()) {
+        Ok(query) => query,
+        Err(err) => return err.into(),
+    };
+
+    spawn(move || {
+        match message_history::query(&query) {
+            Ok(messages) => {
+                let messages = json!(messages).to_string();
+
+                trace!("vcx_get_message_history_cb(command_handle: {}, rc: {}, messages: {:?})",
+                       command_handle, error::SUCCESS.as_str(), secret!(messages));
+
+                let messages = CStringUtils::string_to_cstring(messages);
+                cb(command_handle, error::SUCCESS.code_num, messages.as_ptr());
+            }
+            Err(e) => {
+                warn!("vcx_get_message_history_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Take a snapshot of all live objects: connections, issuer credentials, credentials, proofs, disclosed proofs,
+/// schemas, credential definitions and wallet backups.
+/// The snapshot can be persisted by the application and restored with `vcx_session_restore` after the process is restarted.
+/// NOTE: the snapshot contains the same sensitive data as the results of `serialize` functions of the objects.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+///
+/// cb: Callback that provides the snapshot
+///     {
+///         "version": "1.0",
+///         "connections": [{"handle": int, "data": <serialized connection>}],
+///         "issuer_credentials": [...],
+///         "credentials": [...],
+///         "proofs": [...],
+///         "disclosed_proofs": [...],
+///         "schemas": [...],
+///         "credential_defs": [...],
+///         "wallet_backups": [...]
+///     }
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_session_snapshot(command_handle: CommandHandle,
+                                   cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                        err: u32,
+                                                        snapshot: *const c_char)>) -> u32 {
+    info!("vcx_session_snapshot >>>");
+
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_session_snapshot(command_handle: {})", command_handle);
+
+    spawn(move || {
+        match session::snapshot() {
+            Ok(snapshot) => {
+                trace!("vcx_session_snapshot_cb(command_handle: {}, rc: {}, snapshot: {:?})",
+                       command_handle, error::SUCCESS.as_str(), secret!(snapshot));
+
+                let snapshot = CStringUtils::string_to_cstring(snapshot);
+                cb(command_handle, error::SUCCESS.code_num, snapshot.as_ptr());
+            }
+            Err(e) => {
+                warn!("vcx_session_snapshot_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
+/// Restore all objects of the snapshot taken by `vcx_session_snapshot`.
+/// Restored objects get new handles. Either all objects of the snapshot are restored or none of them.
+///
+/// #params
+///
+/// command_handle: command handle to map callback to user context.
+/// snapshot: snapshot returned by `vcx_session_snapshot`
+///
+/// cb: Callback that provides handles of the restored objects mapped from the handles they had in the snapshot
+///     {
+///         "connections": {"<old handle>": <new handle>},
+///         "issuer_credentials": {...},
+///         "credentials": {...},
+///         "proofs": {...},
+///         "disclosed_proofs": {...},
+///         "schemas": {...},
+///         "credential_defs": {...},
+///         "wallet_backups": {...}
+///     }
+///
+/// #Returns
+/// Error code as a u32
+#[no_mangle]
+pub extern fn vcx_session_restore(command_handle: CommandHandle,
+                                  snapshot: *const c_char,
+                                  cb: Option<extern fn(xcommand_handle: CommandHandle,
+                                                       err: u32,
+                                                       handles: *const c_char)>) -> u32 {
+    info!("vcx_session_restore >>>");
+
+    check_useful_c_str!(snapshot, VcxErrorKind::InvalidOption);
+    check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
+
+    trace!("vcx_session_restore(command_handle: {}, snapshot: {:?})",
+           command_handle, secret!(snapshot));
+
+    spawn(move || {
+        match session::restore(&snapshot) {
+            Ok(handles) => {
+                let handles = json!(handles).to_string();
+
+                trace!("vcx_session_restore_cb(command_handle: {}, rc: {}, handles: {})",
+                       command_handle, error::SUCCESS.as_str(), handles);
+
+                let handles = CStringUtils::string_to_cstring(handles);
+                cb(command_handle, error::SUCCESS.code_num, handles.as_ptr());
+            }
+            Err(e) => {
+                warn!("vcx_session_restore_cb(command_handle: {}, rc: {})",
+                      command_handle, e);
+                cb(command_handle, e.into(), ptr::null_mut());
+            }
+        };
+
+        Ok(())
+    });
+
+    error::SUCCESS.code_num
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1285,5 +1692,22 @@ mod tests {
         );
         r.recv_medium().unwrap_err();
     }
+
+    #[test]
+    fn test_session_snapshot_and_restore() {
+        let _setup = SetupMocks::init();
+
+        let connection_handle = crate::connection::create_connection("session").unwrap();
+
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_session_snapshot(h, Some(cb)), error::SUCCESS.code_num);
+        let snapshot = r.recv_medium().unwrap().unwrap();
+
+        let (h, cb, r) = return_types::return_u32_str();
+        assert_eq!(vcx_session_restore(h, CString::new(snapshot).unwrap().into_raw(), Some(cb)), error::SUCCESS.code_num);
+        let handles: serde_json::Value = serde_json::from_str(&r.recv_medium().unwrap().unwrap()).unwrap();
+
+        assert!(handles["connections"][u32::from(connection_handle).to_string()].is_u64());
+    }
 }
 
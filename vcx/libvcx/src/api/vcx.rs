@@ -302,6 +302,7 @@ pub extern fn vcx_shutdown(delete: bool) -> u32 {
     crate::proof::release_all();
     crate::disclosed_proof::release_all();
     crate::credential::release_all();
+    crate::aries::resolver::clear_cache();
 
     if delete {
         let wallet_name = settings::get_config_value(settings::CONFIG_WALLET_NAME)
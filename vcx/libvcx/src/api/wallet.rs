@@ -9,6 +9,9 @@ use std::{thread, ptr};
 use std::ptr::null;
 use crate::error::prelude::*;
 use crate::indy::{CommandHandle, SearchHandle};
+use crate::aries::utils::didcomm_v2::KEY_AGREEMENT_RECORD_TYPE;
+
+const PRIVATE_RECORD_TYPES: &[&str] = &[KEY_AGREEMENT_RECORD_TYPE];
 
 const EMPTY_CSTR: *const c_char = "\0".as_ptr().cast();
 
@@ -221,6 +224,7 @@ pub extern fn vcx_wallet_verify_with_address(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -251,6 +255,7 @@ pub extern fn vcx_wallet_add_record(command_handle: CommandHandle,
     info!("vcx_wallet_add_record >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_str!(value, VcxErrorKind::InvalidOption);
     check_useful_c_str!(tags_json, VcxErrorKind::InvalidOption);
@@ -288,6 +293,7 @@ pub extern fn vcx_wallet_add_record(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -307,6 +313,7 @@ pub extern fn vcx_wallet_update_record_value(command_handle: CommandHandle,
     info!("vcx_wallet_update_record_value >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_str!(value, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
@@ -344,6 +351,7 @@ pub extern fn vcx_wallet_update_record_value(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -362,6 +370,7 @@ pub extern fn vcx_wallet_update_record_tags(command_handle: CommandHandle,
     info!("vcx_wallet_update_record_tags >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_str!(tags, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
@@ -399,6 +408,7 @@ pub extern fn vcx_wallet_update_record_tags(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -417,6 +427,7 @@ pub extern fn vcx_wallet_add_record_tags(command_handle: CommandHandle,
     info!("vcx_wallet_add_record_tags >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_str!(tags, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
@@ -454,6 +465,7 @@ pub extern fn vcx_wallet_add_record_tags(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -473,6 +485,7 @@ pub extern fn vcx_wallet_delete_record_tags(command_handle: CommandHandle,
     info!("vcx_wallet_delete_record_tags >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_str!(tags, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
@@ -508,6 +521,7 @@ pub extern fn vcx_wallet_delete_record_tags(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -526,6 +540,7 @@ pub extern fn vcx_wallet_get_record(command_handle: CommandHandle,
     info!("vcx_wallet_get_record >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_str!(options_json, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
@@ -564,6 +579,7 @@ pub extern fn vcx_wallet_get_record(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// id: the id ("key") of the record.
 ///
@@ -581,6 +597,7 @@ pub extern fn vcx_wallet_delete_record(command_handle: CommandHandle,
     info!("vcx_wallet_delete_record >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(id, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
 
@@ -673,6 +690,7 @@ pub extern fn vcx_wallet_send_tokens(command_handle: CommandHandle,
 /// command_handle: command handle to map callback to user context.
 ///
 /// type_: type of record. (e.g. 'data', 'string', 'foobar', 'image')
+///     Records of 'didcomm_v2_key_agreement' type keep private keys of the library and are not accessible.
 ///
 /// query_json: MongoDB style query to wallet record tags:
 ///  {
@@ -704,6 +722,7 @@ pub  extern fn vcx_wallet_open_search(command_handle: CommandHandle,
     info!("vcx_wallet_open_search >>>");
 
     check_useful_c_str!(type_, VcxErrorKind::InvalidOption);
+    if let Err(err) = _check_public_record_type(&type_) { return err.into(); }
     check_useful_c_str!(query_json, VcxErrorKind::InvalidOption);
     check_useful_c_str!(options_json, VcxErrorKind::InvalidOption);
     check_useful_c_callback!(cb, VcxErrorKind::InvalidOption);
@@ -833,6 +852,9 @@ pub extern fn vcx_wallet_close_search(command_handle: CommandHandle,
 /// Note this endpoint is EXPERIMENTAL. Function signature and behaviour may change
 /// in the future releases.
 ///
+/// Note the exported wallet contains all wallet records including private keys of the library
+/// (e.g. DIDComm v2 key agreement keys), so the backup key must be kept secret.
+///
 /// #Params:
 /// command_handle: Handle for User's Reference only.
 /// path: Path to export wallet to User's File System.
@@ -954,6 +976,14 @@ pub  extern fn vcx_wallet_validate_payment_address(command_handle: i32,
     error::SUCCESS.code_num
 }
 
+// Records keeping private keys of the library are not accessible through the public record API
+fn _check_public_record_type(type_: &str) -> VcxResult<()> {
+    if PRIVATE_RECORD_TYPES.contains(&type_) {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidOption, format!("Records of type \"{}\" are not accessible", type_)));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::*;
@@ -1040,6 +1070,27 @@ pub mod tests {
         assert_eq!(r.recv_medium().err(), Some(error::WALLET_RECORD_NOT_FOUND.code_num));
     }
 
+    #[test]
+    fn test_private_records_are_not_accessible() {
+        let _setup = SetupLibraryWallet::init();
+
+        let xtype = CString::new(KEY_AGREEMENT_RECORD_TYPE).unwrap();
+
+        let (h, cb, _r) = return_types::return_u32();
+        assert_eq!(vcx_wallet_add_record(h, xtype.as_ptr(), ID, VALUE, EMPTY_TAGS, Some(cb)),
+                   error::INVALID_OPTION.code_num);
+
+        let (h, cb, _r) = return_types::return_u32_str();
+        assert_eq!(vcx_wallet_get_record(h, xtype.as_ptr(), ID, OPTIONS, Some(cb)),
+                   error::INVALID_OPTION.code_num);
+
+        let (h, cb, _r) = return_types::return_u32();
+        assert_eq!(vcx_wallet_delete_record(h, xtype.as_ptr(), ID, Some(cb)),
+                   error::INVALID_OPTION.code_num);
+
+        _check_public_record_type("record_type").unwrap();
+    }
+
     #[test]
     fn test_get_record_value_success() {
         let _setup = SetupLibraryWallet::init();
@@ -5,17 +5,26 @@ use crate::agent::messages::get_message::{Message, get_connection_messages};
 use crate::agent::messages::update_connection::send_delete_connection_message;
 
 use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::messages::connection::did_peer::{self, PeerDidNumalgo};
 use crate::aries::messages::a2a::A2AMessage;
-use crate::aries::utils::encryption_envelope::EncryptionEnvelope;
+use crate::aries::messages::transport::{Transport, ReturnRoute, TRANSPORT_DECORATOR};
+use crate::aries::inbound::replies;
+use crate::aries::handlers::mediation::{self, MediatorRouting};
+use crate::aries::handlers::mediation::pickup;
+use crate::aries::utils::encryption_envelope::{EncryptionEnvelope, EnvelopeVersion};
+use crate::aries::utils::didcomm_v2;
+use crate::aries::utils::processed_messages;
+use crate::aries::utils::message_history;
 
 use std::collections::HashMap;
 
 use crate::connection::create_agent_keys;
-use crate::utils::httpclient;
 use crate::utils::libindy::crypto::create_and_store_my_did;
+use crate::utils::uuid::uuid;
 use crate::settings;
 use crate::error::prelude::*;
 use crate::settings::protocol::ProtocolTypes;
+use crate::settings::delivery::DeliveryPolicy;
 use serde::Serialize;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -24,6 +33,30 @@ pub struct AgentInfo {
     pub pw_vk: String,
     pub agent_did: String,
     pub agent_vk: String,
+    /// X25519 key agreement key used for DIDComm v2 envelopes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_agreement_key: Option<String>,
+    /// `return_route` requested on every outbound message. Synchronous replies are handled as received messages.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_route: Option<ReturnRoute>,
+    /// Mediator routing used instead of the Agency one if the agent was created while the mediation was granted.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mediator: Option<MediatorRouting>,
+    /// Peer DID method used for the DIDDoc shared with the remote side instead of the unqualified pairwise DID.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer_did: Option<PeerDidNumalgo>,
+    /// HTTP delivery policy used for messages sent over the connection instead of the one set in the library config.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delivery_policy: Option<DeliveryPolicy>,
+    /// Stable identifier of the connection served by the agent. It is kept when the pairwise DID is rotated.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub connection_id: String,
 }
 
 impl Default for AgentInfo {
@@ -33,6 +66,12 @@ impl Default for AgentInfo {
             pw_vk: String::new(),
             agent_did: String::new(),
             agent_vk: String::new(),
+            key_agreement_key: None,
+            return_route: None,
+            mediator: None,
+            peer_did: None,
+            delivery_policy: None,
+            connection_id: String::new(),
         }
     }
 }
@@ -45,22 +84,126 @@ impl AgentInfo {
         let method_name = settings::get_config_value(settings::CONFIG_DID_METHOD).ok();
         let (pw_did, pw_vk) = create_and_store_my_did(None, method_name.as_ref().map(String::as_str))?;
 
-        /*
-            Create User Pairwise Agent in old way.
-            Send Messages corresponding to V2 Protocol to avoid code changes on Agency side.
-        */
-        let (agent_did, agent_vk) = create_agent_keys("", &pw_did, &pw_vk)?;
+        let connection_id = uuid();
 
-        let agent = AgentInfo { pw_did, pw_vk, agent_did, agent_vk };
+        let agent = match mediation::add_recipient_key(&pw_vk)? {
+            Some(mediator) => {
+                debug!("Agent: using mediator for pairwise agent");
+                AgentInfo { pw_did, pw_vk, agent_did: String::new(), agent_vk: String::new(), key_agreement_key: None, return_route: None, mediator: Some(mediator), peer_did: None, delivery_policy: None, connection_id }
+            }
+            None => {
+                /*
+                    Create User Pairwise Agent in old way.
+                    Send Messages corresponding to V2 Protocol to avoid code changes on Agency side.
+                */
+                let (agent_did, agent_vk) = create_agent_keys("", &pw_did, &pw_vk)?;
+                AgentInfo { pw_did, pw_vk, agent_did, agent_vk, key_agreement_key: None, return_route: None, mediator: None, peer_did: None, delivery_policy: None, connection_id }
+            }
+        };
 
         trace!("Agent::create_agent <<< pairwise_agent: {:?}", secret!(agent));
         Ok(agent)
     }
 
+    /// Switches the pairwise agent to DIDComm v2 envelopes by generating X25519 key agreement key for it.
+    pub fn enable_didcomm_v2(mut self) -> VcxResult<AgentInfo> {
+        trace!("Agent::enable_didcomm_v2 >>>");
+        debug!("Agent: enabling DIDComm v2 envelopes");
+
+        if self.key_agreement_key.is_none() {
+            self.key_agreement_key = Some(didcomm_v2::create_key_agreement_key(&self.pw_vk)?);
+        }
+
+        Ok(self)
+    }
+
+    pub fn set_envelope_version(self, version: EnvelopeVersion) -> VcxResult<AgentInfo> {
+        match version {
+            EnvelopeVersion::V1 => Ok(self),
+            EnvelopeVersion::V2 => self.enable_didcomm_v2(),
+        }
+    }
+
+    pub fn envelope_version(&self) -> EnvelopeVersion {
+        match self.key_agreement_key {
+            Some(_) => EnvelopeVersion::V2,
+            None => EnvelopeVersion::V1,
+        }
+    }
+
+    /// Sets `return_route` mode for outbound messages if it is passed.
+    pub fn set_return_route(mut self, return_route: Option<ReturnRoute>) -> AgentInfo {
+        match return_route {
+            Some(ReturnRoute::None) => self.return_route = None,
+            Some(return_route) => self.return_route = Some(return_route),
+            None => {}
+        }
+        self
+    }
+
+    /// Sets peer DID method for the DIDDoc if it is passed.
+    pub fn set_peer_did(mut self, peer_did: Option<PeerDidNumalgo>) -> AgentInfo {
+        if peer_did.is_some() {
+            self.peer_did = peer_did;
+        }
+        self
+    }
+
+    /// Sets HTTP delivery policy for outbound messages if it is passed.
+    pub fn set_delivery_policy(mut self, delivery_policy: Option<DeliveryPolicy>) -> AgentInfo {
+        if delivery_policy.is_some() {
+            self.delivery_policy = delivery_policy;
+        }
+        self
+    }
+
+    /// Makes the agent serve the same connection as the agent it replaces (e.g. on DID rotation).
+    pub fn set_connection_id(mut self, connection_id: &str) -> AgentInfo {
+        self.connection_id = connection_id.to_string();
+        self
+    }
+
+    /// Returns the stable identifier of the connection served by the agent.
+    /// Agents created before the identifier was introduced are identified by their pairwise verkey.
+    pub fn connection_id(&self) -> &str {
+        match self.connection_id.is_empty() {
+            true => &self.pw_vk,
+            false => &self.connection_id,
+        }
+    }
+
+    /// Refreshes the mediator routing of the pairwise agent with the routing of the currently granted mediation.
+    /// Agents routed through the Agency always use the Agency endpoint and verkey of the current configuration.
+    pub fn refresh_routing(mut self) -> VcxResult<AgentInfo> {
+        trace!("Agent::refresh_routing >>>");
+
+        if self.mediator.is_none() {
+            return Ok(self);
+        }
+
+        debug!("Agent: refreshing mediator routing");
+
+        match mediation::recipient_key_routing(&self.pw_vk)? {
+            Some(mediator) => self.mediator = Some(mediator),
+            None => warn!("Agent: there is no granted mediation, pairwise agent keeps its previous mediator routing"),
+        }
+
+        trace!("Agent::refresh_routing <<< mediator: {:?}", self.mediator);
+        Ok(self)
+    }
+
+    pub fn key_agreement_keys(&self) -> Vec<String> {
+        self.key_agreement_key.iter().cloned().collect()
+    }
+
     pub fn agency_endpoint(&self) -> VcxResult<String> {
         trace!("Agent::agency_endpoint >>>");
         debug!("Agent: Getting Agency endpoint");
 
+        if let Some(ref mediator) = self.mediator {
+            return Ok(mediator.endpoint.clone());
+        }
+
         settings::get_config_value(settings::CONFIG_AGENCY_ENDPOINT)
             .map(|str| format!("{}/agency/msg", str))
     }
@@ -69,6 +212,10 @@ impl AgentInfo {
         trace!("Agent::routing_keys >>>");
         debug!("Agent: Getting routing keys");
 
+        if let Some(ref mediator) = self.mediator {
+            return Ok(mediator.routing_keys.clone());
+        }
+
         let agency_vk = settings::get_config_value(settings::CONFIG_AGENCY_VERKEY)?;
         Ok(vec![self.agent_vk.to_string(), agency_vk])
     }
@@ -80,10 +227,43 @@ impl AgentInfo {
         vec![self.pw_vk.to_string()]
     }
 
+    /// DIDDoc of the pairwise agent shared with the remote side during DID Exchange.
+    pub fn did_doc(&self) -> VcxResult<DidDoc> {
+        trace!("Agent::did_doc >>>");
+        debug!("Agent: Building DIDDoc");
+
+        let mut did_doc = DidDoc::create();
+        did_doc.set_id(self.pw_did.to_string());
+        did_doc.set_service_endpoint(self.agency_endpoint()?);
+        did_doc.set_keys(self.recipient_keys(), self.routing_keys()?);
+        did_doc.set_key_agreement_keys(self.key_agreement_keys());
+
+        match self.peer_did {
+            Some(numalgo) => did_peer::generate(&did_doc, numalgo),
+            None => Ok(did_doc)
+        }
+    }
+
     pub fn update_message_status(&self, uid: String, pw_did: Option<String>) -> VcxResult<()> {
         trace!("Agent::update_message_status_as_reviewed >>> uid: {:?}", uid);
         debug!("Agent: Updating message {:?} status on reviewed", uid);
 
+        if replies::remove(&self.pw_vk, &uid)? {
+            // picked up messages stay in the mediator queue until they are acknowledged
+            if self.mediator.is_some() {
+                if let Err(err) = pickup::acknowledge(vec![uid]) {
+                    warn!("Agent: Unable to acknowledge message received from the mediator. Err: {:?}", err);
+                }
+            }
+            trace!("Agent::update_message_status_as_reviewed <<< synchronous reply removed");
+            return Ok(());
+        }
+
+        if self.mediator.is_some() {
+            trace!("Agent::update_message_status_as_reviewed <<< messages are not stored on the Agency");
+            return Ok(());
+        }
+
         let messages_to_update = vec![UIDsByConn {
             pairwise_did: pw_did.unwrap_or(self.pw_did.clone()),
             uids: vec![uid],
@@ -95,18 +275,64 @@ impl AgentInfo {
         Ok(())
     }
 
+    /// Handles the message received by the agent with `handler` unless it has already been handled.
+    /// The message is recorded in the processed message ledger before it is handled, so the message delivered again
+    /// (or handled concurrently) is skipped, and the record is dropped if `handler` fails.
+    /// The message status is updated on the Agency afterwards. The status update failure does not fail the handling:
+    /// the message is handled already, so the update is retried when the message is received again.
+    ///
+    /// Returns `None` if the message has already been handled.
+    pub fn handle_message_once<F, T>(&self, uid: String, pw_did: Option<String>, message: &A2AMessage, handler: F) -> VcxResult<Option<T>>
+        where F: FnOnce() -> VcxResult<T> {
+        trace!("Agent::handle_message_once >>> uid: {:?}", uid);
+        debug!("Agent: Handling message {:?}", uid);
+
+        if !processed_messages::mark_processed(self.connection_id(), message)? {
+            debug!("Agent: Message {:?} has already been processed", uid);
+            if let Err(err) = self.update_message_status(uid, pw_did) {
+                warn!("Agent: Unable to update status of processed message. Err: {:?}", err);
+            }
+            return Ok(None);
+        }
+
+        let result = match handler() {
+            Ok(result) => result,
+            Err(err) => {
+                if let Err(err) = processed_messages::unmark_processed(self.connection_id(), message) {
+                    error!("Agent: Unable to drop processed message record of failed message. Err: {:?}", err);
+                }
+                return Err(err);
+            }
+        };
+
+        message_history::record_processed(self.connection_id(), message);
+
+        if let Err(err) = self.update_message_status(uid, pw_did) {
+            warn!("Agent: Unable to update status of processed message. Err: {:?}", err);
+        }
+
+        trace!("Agent::handle_message_once <<<");
+        Ok(Some(result))
+    }
+
     pub fn get_messages(&self) -> VcxResult<HashMap<String, A2AMessage>> {
         trace!("Agent::get_messages >>>");
         debug!("Agent: Getting all received agent from the agent");
 
-        let messages = get_connection_messages(&self.pw_did,
-                                               &self.pw_vk,
-                                               &self.agent_did,
-                                               &self.agent_vk,
-                                               None,
-                                               Some(vec![MessageStatusCode::Received]),
-                                               &Some(ProtocolTypes::V2))?;
-
+        // messages for mediated agents are not delivered to the Agency but picked up from the mediator
+        let messages = match self.mediator {
+            Some(_) => {
+                pickup::fetch(&self.pw_vk)?;
+                Vec::new()
+            }
+            None => get_connection_messages(&self.pw_did,
+                                            &self.pw_vk,
+                                            &self.agent_did,
+                                            &self.agent_vk,
+                                            None,
+                                            Some(vec![MessageStatusCode::Received]),
+                                            &Some(ProtocolTypes::V2))?
+        };
 
         let mut a2a_messages: HashMap<String, A2AMessage> = HashMap::new();
         for message in messages {
@@ -123,6 +349,24 @@ impl AgentInfo {
             }
         }
 
+        a2a_messages.extend(replies::get(&self.pw_vk)?);
+
+        // messages left after the failed status update or delivered again must not be handled twice
+        let processed_uids = processed_messages::filter_processed(self.connection_id(), &a2a_messages)?;
+
+        for uid in processed_uids {
+            debug!("Agent: Message {:?} has already been processed", uid);
+            a2a_messages.remove(&uid);
+
+            if let Err(err) = self.update_message_status(uid, None) {
+                warn!("Agent: Unable to update status of already processed message. Err: {:?}", err);
+            }
+        }
+
+        for message in a2a_messages.values() {
+            message_history::record_received(self.connection_id(), message);
+        }
+
         trace!("Agent::get_messages <<< a2a_messages: {:?}", secret!(a2a_messages));
         Ok(a2a_messages)
     }
@@ -131,6 +375,21 @@ impl AgentInfo {
         trace!("Agent::get_message_by_id >>> msg_id: {:?}", msg_id);
         debug!("Agent: Getting message by id {}", msg_id);
 
+        if let Some(message) = replies::get_by_id(&self.pw_vk, msg_id)? {
+            trace!("Agent::get_message_by_id <<< synchronous reply: {:?}", secret!(message));
+            return Ok(message);
+        }
+
+        if self.mediator.is_some() {
+            pickup::fetch(&self.pw_vk)?;
+
+            let message = replies::get_by_id(&self.pw_vk, msg_id)?
+                .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Message not found for id: {:?}", msg_id)))?;
+
+            trace!("Agent::get_message_by_id <<< picked up message: {:?}", secret!(message));
+            return Ok(message);
+        }
+
         let mut messages = get_connection_messages(&self.pw_did,
                                                    &self.pw_vk,
                                                    &self.agent_did,
@@ -175,13 +434,79 @@ impl AgentInfo {
         trace!("Agent::send_message >>> message: {:?}, did_doc: {:?}", secret!(message), secret!(did_doc));
         debug!("Agent: Sending message on the remote endpoint");
 
-        let pw_key = if self.pw_vk.is_empty() { None} else {Some(self.pw_vk.clone())};
-        let envelope = EncryptionEnvelope::create(&message, pw_key.as_ref().map(String::as_str), &did_doc)?;
-        httpclient::post_message(&envelope.0, &did_doc.get_endpoint())?;
+        let reply = self.pack_and_send(message, did_doc)?;
+
+        if let (Some(_), Some(reply)) = (self.return_route, reply) {
+            if let Err(err) = self.receive_reply(reply) {
+                warn!("Agent: Unable to handle synchronous reply. Err: {:?}", err);
+            }
+        }
+
         trace!("Agent::send_message <<<");
         Ok(())
     }
 
+    /// Sends the message requesting `return_route: all` and returns the reply received in the HTTP response.
+    pub fn send_message_and_wait_reply<T: Serialize + Debug>(&self, message: &T, did_doc: &DidDoc) -> VcxResult<Option<A2AMessage>> {
+        trace!("Agent::send_message_and_wait_reply >>> message: {:?}, did_doc: {:?}", secret!(message), secret!(did_doc));
+        debug!("Agent: Sending message on the remote endpoint and wait for reply");
+
+        let agent = self.clone().set_return_route(Some(ReturnRoute::All));
+
+        let reply = match agent.pack_and_send(message, did_doc)? {
+            Some(reply) => Some(EncryptionEnvelope::open(reply)?),
+            None => None
+        };
+
+        trace!("Agent::send_message_and_wait_reply <<< reply: {:?}", secret!(reply));
+        Ok(reply)
+    }
+
+    fn pack_and_send<T: Serialize + Debug>(&self, message: &T, did_doc: &DidDoc) -> VcxResult<Option<Vec<u8>>> {
+        let message = self.decorate_message(message);
+
+        // DIDComm v2 envelopes are used only if the remote side published its key agreement keys as well
+        let (envelope, version) = match self.key_agreement_key {
+            Some(ref key_agreement_key) if !did_doc.key_agreement_keys().is_empty() =>
+                (EncryptionEnvelope::create_v2(&message, Some(key_agreement_key.as_str()), &did_doc)?, EnvelopeVersion::V2),
+            _ => {
+                let pw_key = if self.pw_vk.is_empty() { None} else {Some(self.pw_vk.clone())};
+                (EncryptionEnvelope::create(&message, pw_key.as_ref().map(String::as_str), &did_doc)?, EnvelopeVersion::V1)
+            }
+        };
+
+        let result = envelope.send(&did_doc, version, self.delivery_policy.as_ref());
+        message_history::record_sent(self.connection_id(), &message, result.is_ok());
+        result
+    }
+
+    fn decorate_message<T: Serialize>(&self, message: &T) -> ::serde_json::Value {
+        let mut message = json!(message);
+
+        if let (Some(return_route), Some(message)) = (self.return_route, message.as_object_mut()) {
+            message.entry(TRANSPORT_DECORATOR).or_insert(json!(Transport::new(return_route)));
+        }
+
+        message
+    }
+
+    fn receive_reply(&self, reply: Vec<u8>) -> VcxResult<()> {
+        trace!("Agent::receive_reply >>>");
+        debug!("Agent: Received synchronous reply");
+
+        let (message, recipient_vk) = EncryptionEnvelope::open_with_recipient_key(reply)?;
+
+        if recipient_vk != self.pw_vk {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse,
+                                          format!("Synchronous reply is addressed to unexpected recipient {:?}", recipient_vk)));
+        }
+
+        replies::add(&recipient_vk, message)?;
+
+        trace!("Agent::receive_reply <<<");
+        Ok(())
+    }
+
     pub fn send_message_and_wait_result<T: Serialize + Debug>(message: &T, did_doc: &DidDoc) -> VcxResult<A2AMessage> {
         trace!("Agent::send_message_and_wait_result >>> message: {:?}, did_doc: {:?}",
                secret!(message), secret!(did_doc));
@@ -189,7 +514,8 @@ impl AgentInfo {
 
         let (_, sender_vk) = create_and_store_my_did(None, None)?;
         let envelope = EncryptionEnvelope::create(&message, Some(&sender_vk), &did_doc)?;
-        let response = httpclient::post_message(&envelope.0, &did_doc.get_endpoint())?;
+        let response = envelope.send(&did_doc, EnvelopeVersion::V1, None)?
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Remote endpoint did not return a synchronous reply"))?;
         let message = EncryptionEnvelope::open(response)?;
 
         trace!("Agent::send_message_and_wait_result <<< message: {:?}", secret!(message));
@@ -201,17 +527,104 @@ impl AgentInfo {
         debug!("Agent: Sending message on the remote anonymous endpoint");
 
         let envelope = EncryptionEnvelope::create(&message, None, &did_dod)?;
-        httpclient::post_message(&envelope.0, &did_dod.get_endpoint())?;
+        envelope.send(&did_dod, EnvelopeVersion::V1, None)?;
         trace!("Agent::send_message_anonymously <<<");
         Ok(())
     }
 
+    /// Deletes the agent of the deleted connection together with the records kept for the connection.
     pub fn delete(&self) -> VcxResult<()> {
         trace!("Agent::delete >>>");
         debug!("Agent: deleting");
 
-        send_delete_connection_message(&self.pw_did, &self.pw_vk, &self.agent_did, &self.agent_vk)?;
+        self.retire()?;
+
+        if let Err(err) = processed_messages::purge_connection(self.connection_id()) {
+            warn!("Agent: Unable to purge processed messages of deleted connection. Err: {:?}", err);
+        }
+
         trace!("Agent::delete <<<");
         Ok(())
     }
+
+    /// Deletes the agent replaced by another one serving the same connection (e.g. after DID rotation).
+    /// Records kept for the connection are left to the agent replacing this one.
+    pub fn retire(&self) -> VcxResult<()> {
+        trace!("Agent::retire >>>");
+        debug!("Agent: retiring");
+
+        match self.mediator {
+            Some(_) => mediation::remove_recipient_key(&self.pw_vk)?,
+            None => send_delete_connection_message(&self.pw_did, &self.pw_vk, &self.agent_did, &self.agent_vk)?
+        }
+
+        if let Err(err) = replies::purge(&self.pw_vk) {
+            warn!("Agent: Unable to purge pending replies of deleted agent. Err: {:?}", err);
+        }
+
+        trace!("Agent::retire <<<");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::{SetupAriesMocks, SetupLibraryWallet};
+    use crate::utils::httpclient;
+    use std::sync::Arc;
+    use crate::aries::messages::ack::tests::_ack;
+
+    #[test]
+    fn test_decorate_message_adds_transport_decorator() {
+        let _setup = SetupAriesMocks::init();
+
+        let message = A2AMessage::Ack(_ack());
+
+        let agent_info = AgentInfo::default();
+        assert_eq!(json!(message), agent_info.decorate_message(&message));
+
+        let agent_info = AgentInfo::default().set_return_route(Some(ReturnRoute::All));
+        let decorated = agent_info.decorate_message(&message);
+        assert_eq!(json!({"return_route": "all"}), decorated[TRANSPORT_DECORATOR]);
+
+        let agent_info = agent_info.set_return_route(Some(ReturnRoute::None));
+        assert_eq!(None, agent_info.return_route);
+    }
+
+    #[test]
+    fn test_connection_id() {
+        let _setup = SetupAriesMocks::init();
+
+        let agent_info = AgentInfo { pw_vk: String::from("pw_vk"), ..AgentInfo::default() };
+        assert_eq!("pw_vk", agent_info.connection_id());
+
+        let agent_info = agent_info.set_connection_id("connection_id");
+        assert_eq!("connection_id", agent_info.connection_id());
+    }
+
+    #[test]
+    fn test_handle_message_once_keeps_result_if_status_update_fails() {
+        let _setup = SetupLibraryWallet::init();
+
+        let transport = |_: &[u8], _: &str| -> VcxResult<Option<Vec<u8>>> {
+            Err(VcxError::from_msg(VcxErrorKind::PostMessageFailed, "Agency is not available"))
+        };
+        httpclient::register_transport("failing", Arc::new(transport)).unwrap();
+        settings::set_config_value(settings::CONFIG_AGENCY_ENDPOINT, "failing://agency");
+
+        let agent_info = AgentInfo { pw_vk: String::from("pw_vk"), ..AgentInfo::default() };
+        let message = A2AMessage::Ack(_ack());
+
+        assert!(agent_info.update_message_status(String::from("uid"), None).is_err());
+
+        let result = agent_info.handle_message_once(String::from("uid"), None, &message, || Ok("advanced")).unwrap();
+        assert_eq!(Some("advanced"), result);
+
+        // the message is handled only once
+        let result = agent_info.handle_message_once(String::from("uid"), None, &message, || Ok("advanced")).unwrap();
+        assert_eq!(None, result);
+
+        httpclient::unregister_transport("failing").unwrap();
+    }
 }
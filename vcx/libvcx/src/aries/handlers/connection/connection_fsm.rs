@@ -29,8 +29,22 @@ use crate::aries::messages::questionanswer::question::{Question, QuestionRespons
 use crate::aries::messages::questionanswer::answer::Answer;
 use crate::aries::messages::committedanswer::question::{Question as CommittedQuestion, QuestionResponse as CommittedQuestionResponse};
 use crate::aries::messages::committedanswer::answer::Answer as CommittedAnswer;
-use crate::aries::handlers::connection::types::{CompletedConnection, OutofbandMeta, Invitations};
+use crate::aries::handlers::connection::types::{CompletedConnection, OutofbandMeta, Invitations, HandshakeRequest, HandshakeResponse};
+use crate::aries::messages::didexchange::request::Request as DidExchangeRequest;
+use crate::aries::messages::didexchange::response::Response as DidExchangeResponse;
+use crate::aries::messages::didexchange::complete::Complete;
+use crate::aries::messages::outofband::HandshakeProtocol;
 use crate::aries::messages::invite_action::invite::Invite as InviteForAction;
+use crate::aries::messages::mediation::mediate_grant::MediateGrant;
+use crate::aries::messages::mediation::mediate_deny::MediateDeny;
+use crate::aries::messages::mediation::keylist_update_response::KeylistUpdateResponse;
+use crate::aries::messages::pickup::delivery::Delivery;
+use crate::aries::messages::did_rotate::rotate::Rotate;
+use crate::aries::messages::did_doc_update::update::Update as DidDocUpdate;
+use crate::aries::messages::error::ProblemReport as CommonProblemReport;
+use crate::aries::messages::revocation_notification::revoke::RevocationNotification;
+use crate::aries::utils::revoked_credentials;
+use crate::aries::handlers::mediation::{self, pickup};
 use crate::aries::handlers::connection::states::*;
 
 use std::collections::HashMap;
@@ -39,6 +53,8 @@ use crate::error::prelude::*;
 use crate::aries::messages::thread::Thread;
 use crate::settings;
 use crate::connection::ConnectionOptions;
+use crate::aries::resolver;
+use crate::utils::libindy::crypto;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +64,9 @@ pub struct DidExchangeSM {
     pub state: ActorDidExchangeState,
 }
 
+const INVITATION_EXPIRED: &str = "Invitation has expired";
+const HANDSHAKE_TIMED_OUT: &str = "Connection handshake has timed out";
+
 
 impl InitializedState {
     fn prepare_invitation(self, source_id: &str, agent_info: &AgentInfo, options: &ConnectionOptions) -> VcxResult<ActorDidExchangeState> {
@@ -70,32 +89,57 @@ impl InitializedState {
                     .set_service_endpoint(agent_info.agency_endpoint()?)
                     .set_opt_public_did(public_did)
                     .set_recipient_keys(agent_info.recipient_keys())
-                    .set_routing_keys(agent_info.routing_keys()?);
+                    .set_routing_keys(agent_info.routing_keys()?)
+                    .set_opt_expiration(options.invitation_ttl);
+
+                let state = InvitedState::from((self, invite))
+                    .set_multi_use(options.multi_use())
+                    .set_timeout(options.handshake_timeout);
 
-                ActorDidExchangeState::Inviter(DidExchangeState::Invited((self, invite).into()))
+                ActorDidExchangeState::Inviter(DidExchangeState::Invited(state))
             }
             Some(outofband_meta) => {
+                let handshake_protocols = if outofband_meta.handshake { options.handshake_protocols() } else { vec![] };
+
+                // public DID invitation refers to the service published on the Ledger instead of embedding the keys
+                let service = match (options.public_did_service, public_did.as_ref()) {
+                    (Some(true), Some(public_did)) => Service::did_reference(&resolver::qualify_did(public_did)),
+                    (Some(true), None) => {
+                        return Err(VcxError::from_msg(VcxErrorKind::InvalidOption,
+                                                      "`public_did_service` option requires `use_public_did` to be set"));
+                    }
+                    _ => {
+                        Service::create()
+                            .set_id(SERVICE_ID.to_string())
+                            .set_type(OUTOFBAND_SERVICE_TYPE.to_string())
+                            .set_service_endpoint(agent_info.agency_endpoint()?)
+                            .set_recipient_keys(agent_info.recipient_keys())
+                            .set_routing_keys(agent_info.routing_keys()?)
+                    }
+                };
+
                 let invite: OutofbandInvitation = OutofbandInvitation::V10(
                     OutofbandInvitationV10::create()
                         .set_label(label)
                         .set_opt_profile_url(profile_url)
                         .set_opt_goal_code(outofband_meta.goal_code)
                         .set_opt_goal(outofband_meta.goal)
-                        .set_handshake(outofband_meta.handshake)
+                        .set_handshake_protocols(&handshake_protocols)
                         .set_opt_public_did(public_did)
-                        .set_service(
-                            Service::create()
-                                .set_id(SERVICE_ID.to_string())
-                                .set_type(OUTOFBAND_SERVICE_TYPE.to_string())
-                                .set_service_endpoint(agent_info.agency_endpoint()?)
-                                .set_recipient_keys(agent_info.recipient_keys())
-                                .set_routing_keys(agent_info.routing_keys()?)
-                        )
+                        .set_service(service)
                         .set_opt_request_attach(outofband_meta.request_attach)?
+                        .set_opt_expiration(options.invitation_ttl)
                 );
 
                 if outofband_meta.handshake {
-                    ActorDidExchangeState::Inviter(DidExchangeState::Invited((self, invite).into()))
+                    let state = InvitedState::from((self, invite))
+                        .set_multi_use(options.multi_use())
+                        .set_timeout(options.handshake_timeout);
+
+                    ActorDidExchangeState::Inviter(DidExchangeState::Invited(state))
+                } else if options.multi_use() {
+                    return Err(VcxError::from_msg(VcxErrorKind::InvalidOption,
+                                                  "`multi_use` option requires Out-of-Band invitation with handshake"));
                 } else {
                     ActorDidExchangeState::Inviter(DidExchangeState::Completed((self, invite).into()))
                 }
@@ -117,27 +161,91 @@ impl InvitedState {
         let prev_agent_info = agent_info.clone();
 
         // provision a new pairwise agent
-        let new_agent_info: AgentInfo = AgentInfo::create_agent()?;
+        let new_agent_info: AgentInfo = AgentInfo::create_agent()?
+            .set_envelope_version(prev_agent_info.envelope_version())?
+            .set_return_route(prev_agent_info.return_route)
+            .set_peer_did(prev_agent_info.peer_did);
 
         let thread = Thread::new()
             .set_thid(request.id.to_string())
             .update_received_order(&request.connection.did_doc.id);
 
         let response = Response::create()
-            .set_did(new_agent_info.pw_did.to_string())
-            .set_service_endpoint(new_agent_info.agency_endpoint()?)
-            .set_keys(new_agent_info.recipient_keys(), new_agent_info.routing_keys()?)
+            .set_did_doc(new_agent_info.did_doc()?)
             .ask_for_ack();
 
         let signed_response = response
             .set_thread(thread.clone())
-            .encode(&prev_agent_info.pw_vk)?;
+            .encode(&self.response_signing_key(&prev_agent_info, false)?)?;
 
         new_agent_info.send_message(&signed_response, &request.connection.did_doc)?;
 
         trace!("InvitedState:handle_connection_request <<<");
         Ok((signed_response, prev_agent_info, new_agent_info, thread))
     }
+
+    fn handle_didexchange_request(&self, request: &DidExchangeRequest,
+                                  agent_info: &AgentInfo) -> VcxResult<(DidDoc, DidExchangeResponse, AgentInfo, AgentInfo, Thread)> {
+        trace!("InvitedState:handle_didexchange_request >>> request: {:?}, agent_info: {:?}", secret!(request), secret!(agent_info));
+        debug!("handling received DID Exchange request message");
+
+        let did_doc = request.did_doc()?;
+        did_doc.validate()?;
+
+        // request must be bound either to the invitation or to the public DID of the inviter
+        let pthid = request.thread.pthid.as_ref()
+            .ok_or(VcxError::from_msg(VcxErrorKind::MessageIsOutOfThread, "Cannot handle DID Exchange Request: parent thread id is not set"))?;
+
+        let bound_to_public_did = self.is_public_did(pthid);
+
+        if pthid != &self.invitation.id() && !bound_to_public_did {
+            return Err(VcxError::from_msg(VcxErrorKind::MessageIsOutOfThread,
+                                          format!("Cannot handle DID Exchange Request: parent thread id does not match. Expected: {:?}, Received: {:?}", self.invitation.id(), pthid)));
+        }
+
+        let prev_agent_info = agent_info.clone();
+
+        // provision a new pairwise agent
+        let new_agent_info: AgentInfo = AgentInfo::create_agent()?
+            .set_envelope_version(prev_agent_info.envelope_version())?
+            .set_return_route(prev_agent_info.return_route)
+            .set_peer_did(prev_agent_info.peer_did);
+
+        let thread = Thread::new()
+            .set_thid(request.thread_id())
+            .set_opt_pthid(request.thread.pthid.clone())
+            .update_received_order(&did_doc.id);
+
+        let response = DidExchangeResponse::create()
+            .set_thread(thread.clone())
+            .set_did_doc(&new_agent_info.did_doc()?)
+            .sign(&self.response_signing_key(&prev_agent_info, bound_to_public_did)?)?;
+
+        new_agent_info.send_message(&response, &did_doc)?;
+
+        trace!("InvitedState:handle_didexchange_request <<<");
+        Ok((did_doc, response, prev_agent_info, new_agent_info, thread))
+    }
+
+    // whether the DID is the public DID of the invitation (qualified or not)
+    fn is_public_did(&self, did: &str) -> bool {
+        match self.invitation.public_did() {
+            Some(public_did) if !public_did.is_empty() => resolver::qualify_did(did) == resolver::qualify_did(public_did),
+            _ => false,
+        }
+    }
+
+    // responses to public DID invitations and to requests bound to the public DID are signed by the key of the public DID (RFC 0023)
+    fn response_signing_key(&self, agent_info: &AgentInfo, bound_to_public_did: bool) -> VcxResult<String> {
+        if !bound_to_public_did && !self.invitation.is_public_did_invitation() {
+            return Ok(agent_info.pw_vk.clone());
+        }
+
+        let public_did = self.invitation.public_did()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "Cannot sign Response: public DID is not set in the Invitation"))?;
+
+        crypto::get_local_verkey(resolver::unqualify_did(public_did))
+    }
 }
 
 impl RequestedState {
@@ -151,9 +259,9 @@ impl RequestedState {
 
         let response: Response = response.decode(&remote_vk)?;
 
-        if !response.from_thread(self.request.id.value()) {
+        if !response.from_thread(self.request.id()) {
             return Err(VcxError::from_msg(VcxErrorKind::MessageIsOutOfThread,
-                                          format!("Cannot handle Connection Response: thread id does not match. Expected: {:?}, Received: {:?}", response.thread, self.request.id.to_string())));
+                                          format!("Cannot handle Connection Response: thread id does not match. Expected: {:?}, Received: {:?}", response.thread, self.request.id())));
         }
 
         let thread = response.thread.clone()
@@ -174,6 +282,73 @@ impl RequestedState {
         trace!("RequestedState:handle_connection_response <<<");
         Ok((response, thread))
     }
+
+    fn handle_didexchange_response(&self, response: &DidExchangeResponse, agent_info: &AgentInfo) -> VcxResult<(DidDoc, Thread)> {
+        trace!("RequestedState:handle_didexchange_response >>> response: {:?}, agent_info: {:?}", secret!(response), secret!(agent_info));
+        debug!("handling received DID Exchange response message");
+
+        if !response.from_thread(self.request.id()) {
+            return Err(VcxError::from_msg(VcxErrorKind::MessageIsOutOfThread,
+                                          format!("Cannot handle DID Exchange Response: thread id does not match. Expected: {:?}, Received: {:?}", self.request.id(), response.thread)));
+        }
+
+        let (signer, did_doc) = response.decode()?;
+
+        self.check_response_signer(&signer)?;
+
+        did_doc.validate()?;
+
+        let thread = response.thread.clone()
+            .set_opt_pthid(self.thread.pthid.clone())
+            .increment_sender_order()
+            .update_received_order(&did_doc.id);
+
+        let complete = Complete::create()
+            .set_thread(thread.clone());
+
+        agent_info.send_message(&complete, &did_doc)?;
+
+        trace!("RequestedState:handle_didexchange_response <<<");
+        Ok((did_doc, thread))
+    }
+
+    fn send_problem_report(&self, agent_info: &AgentInfo, err: String) -> VcxResult<(ProblemReport, Thread)> {
+        trace!("RequestedState:send_problem_report >>> err: {:?}, agent_info: {:?}", secret!(err), secret!(agent_info));
+        debug!("sending connection problem report message");
+
+        let thread = self.thread.clone()
+            .increment_sender_order();
+
+        let problem_report = ProblemReport::create()
+            .set_explain(err)
+            .set_thread(thread.clone());
+
+        let problem_report = match self.request {
+            HandshakeRequest::Connections(_) => problem_report,
+            HandshakeRequest::DidExchange(_) => problem_report.for_didexchange(),
+        };
+
+        agent_info.send_message(&problem_report, &self.did_doc).ok();
+
+        trace!("RequestedState:send_problem_report <<<");
+        Ok((problem_report, thread))
+    }
+
+    // DIDDoc attachment must be signed either by the invitation key or by the key of the public DID
+    fn check_response_signer(&self, signer: &str) -> VcxResult<()> {
+        if self.did_doc.recipient_keys().iter().any(|key| key == signer) {
+            return Ok(());
+        }
+
+        if let Some(public_did) = self.invitation.as_ref().and_then(|invitation| invitation.public_did()) {
+            if resolver::resolve(public_did)?.recipient_keys().iter().any(|key| key == signer) {
+                return Ok(());
+            }
+        }
+
+        Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                               "Cannot handle DID Exchange Response: DIDDoc attachment is not signed by the key used in the Invitation"))
+    }
 }
 
 impl RespondedState {
@@ -186,12 +361,34 @@ impl RespondedState {
         let thread = self.thread.clone()
             .update_received_order(&self.did_doc.id);
 
-        self.prev_agent_info.delete()?;
+        self.release_prev_agent()?;
 
         trace!("RespondedState:handle_ack <<<");
         Ok(thread)
     }
 
+    fn handle_complete(&self, complete: &Complete) -> VcxResult<Thread> {
+        trace!("RespondedState:handle_complete >>> complete: {:?}", secret!(complete));
+        debug!("handling received DID Exchange complete message");
+
+        if let Some(thid) = self.thread.thid.as_ref() {
+            if !complete.from_thread(thid) {
+                return Err(VcxError::from_msg(VcxErrorKind::MessageIsOutOfThread,
+                                              format!("Cannot handle DID Exchange Complete: thread id does not match. Expected: {:?}, Received: {:?}", thid, complete.thread)));
+            }
+        }
+
+        self.thread.check_message_order(&self.did_doc.id, &complete.thread)?;
+
+        let thread = self.thread.clone()
+            .update_received_order(&self.did_doc.id);
+
+        self.release_prev_agent()?;
+
+        trace!("RespondedState:handle_complete <<<");
+        Ok(thread)
+    }
+
     fn handle_ping(&self, ping: &Ping, agent_info: &AgentInfo) -> VcxResult<Thread> {
         trace!("RespondedState:handle_ping >>> ping: {:?}, agent_info: {:?}", secret!(ping), secret!(agent_info));
         debug!("sending connection ping message");
@@ -203,7 +400,7 @@ impl RespondedState {
         let thread = self.thread.clone()
             .update_received_order(&self.did_doc.id);
 
-        self.prev_agent_info.delete()?;
+        self.release_prev_agent()?;
 
         trace!("RespondedState:handle_ping <<<");
         Ok(thread)
@@ -218,12 +415,18 @@ impl RespondedState {
         let thread = self.thread.clone()
             .update_received_order(&self.did_doc.id);
 
-        self.prev_agent_info.delete()?;
+        self.release_prev_agent()?;
 
         trace!("RespondedState:handle_ping_response <<<");
         Ok(thread)
     }
 
+    fn release_prev_agent(&self) -> VcxResult<()> {
+        if self.shared_prev_agent { return Ok(()); }
+
+        self.prev_agent_info.delete()
+    }
+
     fn send_problem_report(&self, agent_info: &AgentInfo, err: String) -> VcxResult<(ProblemReport, Thread)> {
         trace!("RespondedState:send_problem_report >>> err: {:?}, agent_info: {:?}", secret!(err), secret!(agent_info));
         debug!("sending connection problem report message");
@@ -236,6 +439,11 @@ impl RespondedState {
             .set_explain(err)
             .set_thread(thread.clone());
 
+        let problem_report = match self.response {
+            HandshakeResponse::Connections(_) => problem_report,
+            HandshakeResponse::DidExchange(_) => problem_report.for_didexchange(),
+        };
+
         agent_info.send_message(&problem_report, &self.did_doc).ok();
 
         trace!("RespondedState:send_problem_report <<<");
@@ -244,6 +452,36 @@ impl RespondedState {
 }
 
 impl CompleteState {
+    /// Handles DID Rotate messages and service updates, which may replace the pairwise agent, and passes everything else to `handle_message`.
+    fn step(self, message: DidExchangeMessages, agent_info: AgentInfo) -> VcxResult<(DidExchangeState, AgentInfo)> {
+        match message {
+            DidExchangeMessages::RotateDid => {
+                let (state, agent_info) = self.handle_rotate_did(agent_info)?;
+                Ok((DidExchangeState::Completed(state), agent_info))
+            }
+            DidExchangeMessages::DidRotateReceived(rotate) => {
+                let state = self.handle_did_rotate(&rotate, &agent_info)?;
+                Ok((DidExchangeState::Completed(state), agent_info))
+            }
+            DidExchangeMessages::DidRotateAckReceived(ack) => {
+                let state = self.handle_did_rotate_ack(&ack)?;
+                Ok((DidExchangeState::Completed(state), agent_info))
+            }
+            DidExchangeMessages::DidRotateProblemReportReceived(problem_report) => {
+                let (state, agent_info) = self.handle_did_rotate_problem_report(&problem_report, agent_info)?;
+                Ok((DidExchangeState::Completed(state), agent_info))
+            }
+            DidExchangeMessages::UpdateService => {
+                let agent_info = self.handle_update_service(agent_info)?;
+                Ok((DidExchangeState::Completed(self), agent_info))
+            }
+            message => {
+                let state = self.handle_message(message, &agent_info)?;
+                Ok((state, agent_info))
+            }
+        }
+    }
+
     fn handle_message(self, message: DidExchangeMessages, agent_info: &AgentInfo) -> VcxResult<DidExchangeState> {
         trace!("CompleteState:handle_message >>> message: {:?}, agent_info: {:?}", secret!(message), secret!(agent_info));
         debug!("handling message in completed state");
@@ -313,6 +551,41 @@ impl CompleteState {
                 self.handle_invite_action(&invite)?;
                 DidExchangeState::Completed(self)
             }
+            DidExchangeMessages::RequestMediation => {
+                self.handle_request_mediation(agent_info)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::MediateGrantReceived(grant) => {
+                self.handle_mediate_grant(&grant, agent_info)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::MediateDenyReceived(deny) => {
+                self.handle_mediate_deny(&deny, agent_info)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::KeylistUpdateResponseReceived(response) => {
+                self.handle_keylist_update_response(&response, agent_info)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::PickupDeliveryReceived(delivery) => {
+                self.handle_pickup_delivery(&delivery)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::DidDocUpdateReceived(update) => {
+                DidExchangeState::Completed(self.handle_did_doc_update(&update, agent_info)?)
+            }
+            DidExchangeMessages::DidDocUpdateAckReceived(ack) => {
+                self.handle_did_doc_update_ack(&ack)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::DidDocUpdateProblemReportReceived(problem_report) => {
+                self.handle_did_doc_update_problem_report(&problem_report)?;
+                DidExchangeState::Completed(self)
+            }
+            DidExchangeMessages::RevocationNotificationReceived(notification) => {
+                self.handle_revocation_notification(&notification, agent_info)?;
+                DidExchangeState::Completed(self)
+            }
             message_ => {
                 warn!("DidExchangeSM: Unexpected action to update state {:?}", message_);
                 DidExchangeState::Completed(self)
@@ -548,6 +821,307 @@ impl CompleteState {
         Ok(())
     }
 
+    fn handle_request_mediation(&self, agent_info: &AgentInfo) -> VcxResult<()> {
+        trace!("CompleteState:handle_request_mediation >>> agent_info: {:?}", secret!(agent_info));
+        debug!("requesting mediation over connection");
+
+        if self.without_handshake() {
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Mediation cannot be requested over one-time connection"));
+        }
+
+        mediation::request_mediation(CompletedConnection { agent: agent_info.clone(), data: self.clone() })?;
+
+        trace!("CompleteState:handle_request_mediation <<<");
+        Ok(())
+    }
+
+    fn handle_mediate_grant(&self, grant: &MediateGrant, agent_info: &AgentInfo) -> VcxResult<()> {
+        trace!("CompleteState:handle_mediate_grant >>> grant: {:?}", secret!(grant));
+        debug!("handling received mediate grant message");
+        // unexpected mediation replies must not block the connection
+        if let Err(err) = mediation::handle_mediate_grant(agent_info.connection_id(), grant) {
+            warn!("Unable to handle mediate grant message. Err: {:?}", err);
+        }
+        Ok(())
+    }
+
+    fn handle_mediate_deny(&self, deny: &MediateDeny, agent_info: &AgentInfo) -> VcxResult<()> {
+        trace!("CompleteState:handle_mediate_deny >>> deny: {:?}", secret!(deny));
+        debug!("handling received mediate deny message");
+        if let Err(err) = mediation::handle_mediate_deny(agent_info.connection_id(), deny) {
+            warn!("Unable to handle mediate deny message. Err: {:?}", err);
+        }
+        Ok(())
+    }
+
+    fn handle_keylist_update_response(&self, response: &KeylistUpdateResponse, agent_info: &AgentInfo) -> VcxResult<()> {
+        trace!("CompleteState:handle_keylist_update_response >>> response: {:?}", secret!(response));
+        debug!("handling received keylist update response message");
+        if let Err(err) = mediation::handle_keylist_update_response(agent_info.connection_id(), response) {
+            warn!("Unable to handle keylist update response message. Err: {:?}", err);
+        }
+        Ok(())
+    }
+
+    fn handle_pickup_delivery(&self, delivery: &Delivery) -> VcxResult<()> {
+        trace!("CompleteState:handle_pickup_delivery >>> delivery: {:?}", secret!(delivery));
+        debug!("handling received pickup delivery message");
+        if let Err(err) = pickup::handle_delivery(delivery) {
+            warn!("Unable to handle pickup delivery message. Err: {:?}", err);
+        }
+        Ok(())
+    }
+
+    fn handle_rotate_did(mut self, agent_info: AgentInfo) -> VcxResult<(CompleteState, AgentInfo)> {
+        trace!("CompleteState:handle_rotate_did >>> agent_info: {:?}", secret!(agent_info));
+        debug!("rotating pairwise DID of connection");
+
+        if self.without_handshake() {
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "DID cannot be rotated for one-time connection"));
+        }
+
+        if let Some(did_rotation) = self.did_rotation.as_ref() {
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported,
+                                          format!("Cannot rotate DID: previous rotation {:?} is not acknowledged yet", did_rotation.rotate_id)));
+        }
+
+        let new_agent_info = AgentInfo::create_agent()?
+            .set_envelope_version(agent_info.envelope_version())?
+            .set_return_route(agent_info.return_route)
+            .set_peer_did(agent_info.peer_did)
+            .set_connection_id(agent_info.connection_id());
+
+        let rotate = Rotate::create()
+            .set_did_doc(&new_agent_info.did_doc()?)
+            .sign(&agent_info.pw_vk)?;
+
+        // the old agent is still known by the remote side, so it sends the rotation
+        if let Err(err) = self.send_message(&rotate, &agent_info) {
+            new_agent_info.retire().ok();
+            return Err(err);
+        }
+
+        self.did_rotation = Some(DidRotation {
+            rotate_id: rotate.id.to_string(),
+            prev_agent_info: agent_info,
+        });
+
+        trace!("CompleteState:handle_rotate_did <<<");
+        Ok((self, new_agent_info))
+    }
+
+    fn handle_did_rotate(mut self, rotate: &Rotate, agent_info: &AgentInfo) -> VcxResult<CompleteState> {
+        trace!("CompleteState:handle_did_rotate >>> rotate: {:?}, agent_info: {:?}", secret!(rotate), secret!(agent_info));
+        debug!("handling received DID rotate message");
+
+        let thread_id = rotate.id.to_string();
+
+        let did_doc = match self.check_did_rotate(rotate) {
+            Ok(did_doc) => did_doc,
+            Err(err) => {
+                warn!("Unable to accept rotated DID of remote side. Err: {:?}", err);
+
+                let problem_report = CommonProblemReport::create()
+                    .set_message_type(&rotate.type_)
+                    .set_comment(err.to_string())
+                    .set_thread_id(&thread_id);
+
+                self.send_message(&problem_report, agent_info)?;
+                return Ok(self);
+            }
+        };
+
+        let ack = Ack::create()
+            .set_message_type(&rotate.type_)
+            .set_thread_id(&thread_id);
+
+        agent_info.send_message(&ack, &did_doc)?;
+
+        // keep thread continuity: the remote side goes on with the same order under its new DID
+        if let Some(order) = self.thread.received_orders.remove(&self.did_doc.id) {
+            self.thread.received_orders.insert(did_doc.id.clone(), order);
+        }
+        self.did_doc = did_doc;
+
+        trace!("CompleteState:handle_did_rotate <<<");
+        Ok(self)
+    }
+
+    // `rotate` message must be signed by the key of the DID being rotated
+    fn check_did_rotate(&self, rotate: &Rotate) -> VcxResult<DidDoc> {
+        let (signer, did_doc) = rotate.decode()?;
+
+        if !self.did_doc.recipient_keys().iter().any(|key| key == &signer) {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                          "Cannot handle DID Rotate message: DIDDoc attachment is not signed by the key of the rotated DID"));
+        }
+
+        did_doc.validate()?;
+
+        Ok(did_doc)
+    }
+
+    fn handle_did_rotate_ack(mut self, ack: &Ack) -> VcxResult<CompleteState> {
+        trace!("CompleteState:handle_did_rotate_ack >>> ack: {:?}", secret!(ack));
+        debug!("handling received DID rotate ack message");
+
+        match self.did_rotation.take() {
+            Some(did_rotation) if ack.from_thread(&did_rotation.rotate_id) => {
+                // protocols started before the rotation may still get messages addressed to the old agent
+                self.retired_agents.push(did_rotation.prev_agent_info);
+            }
+            did_rotation => {
+                warn!("DidExchangeSM: DID rotate ack does not match any pending rotation: {:?}", ack.thread);
+                self.did_rotation = did_rotation;
+            }
+        }
+
+        trace!("CompleteState:handle_did_rotate_ack <<<");
+        Ok(self)
+    }
+
+    fn handle_did_rotate_problem_report(mut self, problem_report: &CommonProblemReport, agent_info: AgentInfo) -> VcxResult<(CompleteState, AgentInfo)> {
+        trace!("CompleteState:handle_did_rotate_problem_report >>> problem_report: {:?}", secret!(problem_report));
+        debug!("handling received DID rotate problem report message");
+
+        match self.did_rotation.take() {
+            Some(did_rotation) if problem_report.from_thread(&did_rotation.rotate_id) => {
+                warn!("DID rotation was rejected by remote side: {:?}", problem_report.comment);
+
+                if let Err(err) = agent_info.retire() {
+                    warn!("Unable to delete pairwise agent of rejected DID. Err: {:?}", err);
+                }
+
+                trace!("CompleteState:handle_did_rotate_problem_report <<<");
+                Ok((self, did_rotation.prev_agent_info))
+            }
+            did_rotation => {
+                warn!("DidExchangeSM: DID rotate problem report does not match any pending rotation: {:?}", problem_report.thread);
+                self.did_rotation = did_rotation;
+
+                trace!("CompleteState:handle_did_rotate_problem_report <<<");
+                Ok((self, agent_info))
+            }
+        }
+    }
+
+    fn handle_update_service(&self, agent_info: AgentInfo) -> VcxResult<AgentInfo> {
+        trace!("CompleteState:handle_update_service >>> agent_info: {:?}", secret!(agent_info));
+        debug!("updating service endpoint and routing keys of connection");
+
+        if self.without_handshake() {
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Service cannot be updated for one-time connection"));
+        }
+
+        if let Some(did_rotation) = self.did_rotation.as_ref() {
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported,
+                                          format!("Cannot update service: DID rotation {:?} is not acknowledged yet", did_rotation.rotate_id)));
+        }
+
+        // peer DID changes together with the service encoded into it
+        if agent_info.peer_did.is_some() {
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported,
+                                          "Service of peer DID cannot be updated without changing the DID, rotate the DID instead"));
+        }
+
+        let agent_info = agent_info.refresh_routing()?;
+
+        let update = DidDocUpdate::create()
+            .set_did_doc(&agent_info.did_doc()?)
+            .sign(&agent_info.pw_vk)?;
+
+        self.send_message(&update, &agent_info)?;
+
+        trace!("CompleteState:handle_update_service <<<");
+        Ok(agent_info)
+    }
+
+    fn handle_did_doc_update(mut self, update: &DidDocUpdate, agent_info: &AgentInfo) -> VcxResult<CompleteState> {
+        trace!("CompleteState:handle_did_doc_update >>> update: {:?}, agent_info: {:?}", secret!(update), secret!(agent_info));
+        debug!("handling received DIDDoc update message");
+
+        let thread_id = update.id.to_string();
+
+        let did_doc = match self.check_did_doc_update(update) {
+            Ok(did_doc) => did_doc,
+            Err(err) => {
+                warn!("Unable to accept updated DIDDoc of remote side. Err: {:?}", err);
+
+                let problem_report = CommonProblemReport::create()
+                    .set_message_type(&update.type_)
+                    .set_comment(err.to_string())
+                    .set_thread_id(&thread_id);
+
+                self.send_message(&problem_report, agent_info)?;
+                return Ok(self);
+            }
+        };
+
+        let ack = Ack::create()
+            .set_message_type(&update.type_)
+            .set_thread_id(&thread_id);
+
+        agent_info.send_message(&ack, &did_doc)?;
+
+        self.did_doc = did_doc;
+
+        trace!("CompleteState:handle_did_doc_update <<<");
+        Ok(self)
+    }
+
+    // only service endpoint and routing keys can be changed, and only by the owner of the DID
+    fn check_did_doc_update(&self, update: &DidDocUpdate) -> VcxResult<DidDoc> {
+        let (signer, did_doc) = update.decode()?;
+
+        if did_doc.id != self.did_doc.id {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                          format!("Updated DIDDoc does not belong to the remote DID. Expected: {:?}, Received: {:?}", self.did_doc.id, did_doc.id)));
+        }
+
+        let recipient_keys = self.did_doc.recipient_keys();
+
+        if !recipient_keys.iter().any(|key| key == &signer) {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                          "Cannot handle DIDDoc update message: DIDDoc attachment is not signed by the key of the remote DID"));
+        }
+
+        if did_doc.recipient_keys() != recipient_keys {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                          "Cannot handle DIDDoc update message: recipient keys cannot be changed without DID rotation"));
+        }
+
+        did_doc.validate()?;
+
+        Ok(did_doc)
+    }
+
+    fn handle_did_doc_update_ack(&self, ack: &Ack) -> VcxResult<()> {
+        trace!("CompleteState:handle_did_doc_update_ack >>> ack: {:?}", secret!(ack));
+        debug!("DIDDoc update was accepted by remote side: {:?}", ack.thread);
+        Ok(())
+    }
+
+    fn handle_did_doc_update_problem_report(&self, problem_report: &CommonProblemReport) -> VcxResult<()> {
+        trace!("CompleteState:handle_did_doc_update_problem_report >>> problem_report: {:?}", secret!(problem_report));
+        warn!("DIDDoc update was rejected by remote side: {:?}", problem_report.comment);
+        Ok(())
+    }
+
+    fn handle_revocation_notification(&self, notification: &RevocationNotification, agent_info: &AgentInfo) -> VcxResult<()> {
+        trace!("CompleteState:handle_revocation_notification >>> notification: {:?}", secret!(notification));
+        debug!("handling received revocation notification");
+
+        // the notification is consumed anyway: failing here would leave it pending forever
+        match revoked_credentials::handle_notification(notification, agent_info.connection_id()) {
+            Ok(Some(cred_id)) => debug!("credential {} marked as revoked", cred_id),
+            Ok(None) => warn!("Revocation notification refers to unknown credential or credential issued over another connection: {:?}", notification),
+            Err(err) => warn!("Cannot handle revocation notification: {}", err),
+        }
+
+        trace!("CompleteState:handle_revocation_notification <<<");
+        Ok(())
+    }
+
     pub fn send_message<T: Serialize + Debug>(&self, message: &T, agent_info: &AgentInfo) -> VcxResult<()> {
         self.warn_if_onetime_connection();
         agent_info.send_message(message, &self.did_doc)
@@ -642,6 +1216,10 @@ impl DidExchangeSM {
                             debug!("DidExchangeSM: Inviter received ConnectionRequest message");
                             return Some((uid, request));
                         }
+                        request @ A2AMessage::DidExchangeRequest(_) => {
+                            debug!("DidExchangeSM: Inviter received DID Exchange Request message");
+                            return Some((uid, request));
+                        }
                         problem_report @ A2AMessage::ConnectionProblemReport(_) => {
                             debug!("DidExchangeSM: Inviter received ProblemReport message");
                             return Some((uid, problem_report));
@@ -657,6 +1235,10 @@ impl DidExchangeSM {
                             debug!("DidExchangeSM: Invitee received ConnectionResponse message");
                             return Some((uid, response));
                         }
+                        response @ A2AMessage::DidExchangeResponse(_) => {
+                            debug!("DidExchangeSM: Invitee received DID Exchange Response message");
+                            return Some((uid, response));
+                        }
                         problem_report @ A2AMessage::ConnectionProblemReport(_) => {
                             debug!("DidExchangeSM: Invitee received ProblemReport message");
                             return Some((uid, problem_report));
@@ -672,6 +1254,10 @@ impl DidExchangeSM {
                             debug!("DidExchangeSM: Ack message received");
                             return Some((uid, ack));
                         }
+                        complete @ A2AMessage::DidExchangeComplete(_) => {
+                            debug!("DidExchangeSM: DID Exchange Complete message received");
+                            return Some((uid, complete));
+                        }
                         ping @ A2AMessage::Ping(_) => {
                             debug!("DidExchangeSM: Ping message received");
                             return Some((uid, ping));
@@ -732,6 +1318,50 @@ impl DidExchangeSM {
                             debug!("DidExchangeSM: HandshakeReuseAccepted message received");
                             return Some((uid, reuse_accepted));
                         }
+                        grant @ A2AMessage::MediateGrant(_) => {
+                            debug!("DidExchangeSM: MediateGrant message received");
+                            return Some((uid, grant));
+                        }
+                        deny @ A2AMessage::MediateDeny(_) => {
+                            debug!("DidExchangeSM: MediateDeny message received");
+                            return Some((uid, deny));
+                        }
+                        response @ A2AMessage::KeylistUpdateResponse(_) => {
+                            debug!("DidExchangeSM: KeylistUpdateResponse message received");
+                            return Some((uid, response));
+                        }
+                        delivery @ A2AMessage::PickupDelivery(_) => {
+                            debug!("DidExchangeSM: PickupDelivery message received");
+                            return Some((uid, delivery));
+                        }
+                        rotate @ A2AMessage::DidRotate(_) => {
+                            debug!("DidExchangeSM: DidRotate message received");
+                            return Some((uid, rotate));
+                        }
+                        ack @ A2AMessage::DidRotateAck(_) => {
+                            debug!("DidExchangeSM: DidRotateAck message received");
+                            return Some((uid, ack));
+                        }
+                        problem_report @ A2AMessage::DidRotateProblemReport(_) => {
+                            debug!("DidExchangeSM: DidRotateProblemReport message received");
+                            return Some((uid, problem_report));
+                        }
+                        update @ A2AMessage::DidDocUpdate(_) => {
+                            debug!("DidExchangeSM: DidDocUpdate message received");
+                            return Some((uid, update));
+                        }
+                        ack @ A2AMessage::DidDocUpdateAck(_) => {
+                            debug!("DidExchangeSM: DidDocUpdateAck message received");
+                            return Some((uid, ack));
+                        }
+                        problem_report @ A2AMessage::DidDocUpdateProblemReport(_) => {
+                            debug!("DidExchangeSM: DidDocUpdateProblemReport message received");
+                            return Some((uid, problem_report));
+                        }
+                        notification @ A2AMessage::RevocationNotification(_) => {
+                            debug!("DidExchangeSM: RevocationNotification message received");
+                            return Some((uid, notification));
+                        }
                         message @ _ => {
                             warn!("DidExchangeSM: Unexpected message received in Completed state: {:?}", message);
                         }
@@ -761,7 +1391,10 @@ impl DidExchangeSM {
                                 agent_info = match options.pairwise_agent_info.as_ref() {
                                     Some(pairwise_agent_info) => pairwise_agent_info.clone(),
                                     None => AgentInfo::create_agent()?
-                                };
+                                }.set_envelope_version(options.envelope_version()?)?
+                                    .set_return_route(options.return_route)
+                                    .set_peer_did(options.peer_did)
+                                    .set_delivery_policy(options.delivery_policy.clone());
                                 state.prepare_invitation(&source_id, &agent_info, &options)?
                             }
                             message_ => {
@@ -772,8 +1405,37 @@ impl DidExchangeSM {
                     }
                     DidExchangeState::Invited(state) => {
                         match message {
-                            DidExchangeMessages::ExchangeRequestReceived(request) => {
-                                match state.handle_connection_request(&request, &agent_info) {
+                            DidExchangeMessages::ExchangeRequestReceived(request) if state.invitation.is_expired() => {
+                                let thread = Thread::new()
+                                    .set_thid(request.id.to_string())
+                                    .update_received_order(&request.connection.did_doc.id);
+
+                                let problem_report = ProblemReport::create()
+                                    .set_problem_code(ProblemCode::RequestNotAccepted)
+                                    .set_explain(INVITATION_EXPIRED.to_string())
+                                    .set_thread(thread.clone());
+
+                                agent_info.send_message(&problem_report, &request.connection.did_doc).ok();
+                                ActorDidExchangeState::Inviter(DidExchangeState::Failed((state, problem_report, thread).into()))
+                            }
+                            DidExchangeMessages::DidExchangeRequestReceived(request) if state.invitation.is_expired() => {
+                                let thread = Thread::new()
+                                    .set_thid(request.thread_id())
+                                    .set_opt_pthid(request.thread.pthid.clone());
+
+                                let problem_report = ProblemReport::create()
+                                    .set_problem_code(ProblemCode::RequestNotAccepted)
+                                    .set_explain(INVITATION_EXPIRED.to_string())
+                                    .set_thread(thread.clone())
+                                    .for_didexchange();
+
+                                if let Ok(did_doc) = request.did_doc() {
+                                    agent_info.send_message(&problem_report, &did_doc).ok();
+                                }
+                                ActorDidExchangeState::Inviter(DidExchangeState::Failed((state, problem_report, thread).into()))
+                            }
+                            DidExchangeMessages::ExchangeRequestReceived(request) => {
+                                match state.handle_connection_request(&request, &agent_info) {
                                     Ok((response, prev_agent_info, new_agent_info, thread)) => {
                                         agent_info = new_agent_info;
                                         ActorDidExchangeState::Inviter(DidExchangeState::Responded((state, request, response, prev_agent_info, thread).into()))
@@ -793,6 +1455,31 @@ impl DidExchangeSM {
                                     }
                                 }
                             }
+                            DidExchangeMessages::DidExchangeRequestReceived(request) => {
+                                match state.handle_didexchange_request(&request, &agent_info) {
+                                    Ok((did_doc, response, prev_agent_info, new_agent_info, thread)) => {
+                                        agent_info = new_agent_info;
+                                        ActorDidExchangeState::Inviter(DidExchangeState::Responded((state, did_doc, response, prev_agent_info, thread).into()))
+                                    }
+                                    Err(err) => {
+                                        if let Ok(did_doc) = request.did_doc() {
+                                            let thread = Thread::new()
+                                                .set_thid(request.thread_id())
+                                                .set_opt_pthid(request.thread.pthid.clone())
+                                                .update_received_order(&did_doc.id);
+
+                                            let problem_report = ProblemReport::create()
+                                                .set_problem_code(ProblemCode::RequestProcessingError)
+                                                .set_explain(err.to_string())
+                                                .set_thread(thread)
+                                                .for_didexchange();
+
+                                            agent_info.send_message(&problem_report, &did_doc).ok();
+                                        }
+                                        return Err(err);
+                                    }
+                                }
+                            }
                             DidExchangeMessages::ProblemReportReceived(problem_report) => {
                                 let thread = Thread::new()
                                     .set_thid(problem_report.id.to_string());
@@ -821,6 +1508,17 @@ impl DidExchangeSM {
                                     }
                                 }
                             }
+                            DidExchangeMessages::DidExchangeCompleteReceived(complete) => {
+                                match state.handle_complete(&complete) {
+                                    Ok(thread) => {
+                                        ActorDidExchangeState::Inviter(DidExchangeState::Completed((state, complete, thread).into()))
+                                    }
+                                    Err(err) => {
+                                        state.send_problem_report(&agent_info, err.to_string())?;
+                                        return Err(err);
+                                    }
+                                }
+                            }
                             DidExchangeMessages::PingReceived(ping) => {
                                 match state.handle_ping(&ping, &agent_info) {
                                     Ok(thread) => {
@@ -873,7 +1571,9 @@ impl DidExchangeSM {
                         ActorDidExchangeState::Inviter(DidExchangeState::Failed(state))
                     }
                     DidExchangeState::Completed(state) => {
-                        ActorDidExchangeState::Inviter(state.handle_message(message, &agent_info)?)
+                        let (state, agent_info_) = state.step(message, agent_info)?;
+                        agent_info = agent_info_;
+                        ActorDidExchangeState::Inviter(state)
                     }
                 }
             }
@@ -899,27 +1599,50 @@ impl DidExchangeSM {
                     }
                     DidExchangeState::Invited(state) => {
                         match message {
+                            DidExchangeMessages::Connect(_) if state.invitation.is_expired() => {
+                                return Err(VcxError::from_msg(VcxErrorKind::InvalidInviteDetail, INVITATION_EXPIRED));
+                            }
                             DidExchangeMessages::Connect(options) => {
+                                let state = state.set_timeout(options.handshake_timeout);
+
                                 agent_info = match options.pairwise_agent_info.as_ref() {
                                     Some(pairwise_agent_info) => pairwise_agent_info.clone(),
                                     None => AgentInfo::create_agent()?
-                                };
+                                }.set_envelope_version(options.envelope_version()?)?
+                                    .set_return_route(options.return_route)
+                                    .set_peer_did(options.peer_did)
+                                    .set_delivery_policy(options.delivery_policy.clone());
 
                                 let label = settings::get_config_value(settings::CONFIG_INSTITUTION_NAME).unwrap_or(source_id.to_string());
 
-                                let request = Request::create()
-                                    .set_label(label)
-                                    .set_pthid(&state.invitation.id())
-                                    .set_did(agent_info.pw_did.to_string())
-                                    .set_service_endpoint(agent_info.agency_endpoint()?)
-                                    .set_keys(agent_info.recipient_keys(), agent_info.routing_keys()?);
+                                match state.invitation.handshake_protocol() {
+                                    HandshakeProtocol::Connections => {
+                                        let request = Request::create()
+                                            .set_label(label)
+                                            .set_pthid(&state.invitation.id())
+                                            .set_did_doc(agent_info.did_doc()?);
 
-                                let thread = Thread::new()
-                                    .set_thid(request.id.to_string())
-                                    .set_opt_pthid(state.invitation.pthid());
+                                        let thread = Thread::new()
+                                            .set_thid(request.id.to_string())
+                                            .set_opt_pthid(state.invitation.pthid());
+
+                                        agent_info.send_message(&request, &DidDoc::from(state.invitation.clone()))?;
+                                        ActorDidExchangeState::Invitee(DidExchangeState::Requested((state, request, thread).into()))
+                                    }
+                                    HandshakeProtocol::DidExchange => {
+                                        let request = DidExchangeRequest::create()
+                                            .set_label(label)
+                                            .set_pthid(&state.invitation.id())
+                                            .set_did_doc(&agent_info.did_doc()?);
 
-                                agent_info.send_message(&request, &DidDoc::from(state.invitation.clone()))?;
-                                ActorDidExchangeState::Invitee(DidExchangeState::Requested((state, request, thread).into()))
+                                        let thread = Thread::new()
+                                            .set_thid(request.id.to_string())
+                                            .set_opt_pthid(state.invitation.pthid());
+
+                                        agent_info.send_message(&request, &DidDoc::from(state.invitation.clone()))?;
+                                        ActorDidExchangeState::Invitee(DidExchangeState::Requested((state, request, thread).into()))
+                                    }
+                                }
                             }
                             DidExchangeMessages::ProblemReportReceived(problem_report) => {
                                 let thread = problem_report.thread.clone();
@@ -953,6 +1676,27 @@ impl DidExchangeSM {
                                     }
                                 }
                             }
+                            DidExchangeMessages::DidExchangeResponseReceived(response) => {
+                                match state.handle_didexchange_response(&response, &agent_info) {
+                                    Ok((did_doc, thread)) => {
+                                        ActorDidExchangeState::Invitee(DidExchangeState::Completed((state, did_doc, thread).into()))
+                                    }
+                                    Err(err) => {
+                                        let thread = state.thread.clone()
+                                            .increment_sender_order()
+                                            .update_received_order(&state.did_doc.id);
+
+                                        let problem_report = ProblemReport::create()
+                                            .set_problem_code(ProblemCode::ResponseProcessingError)
+                                            .set_explain(err.to_string())
+                                            .set_thread(thread)
+                                            .for_didexchange();
+
+                                        agent_info.send_message(&problem_report, &state.did_doc).ok();
+                                        return Err(err);
+                                    }
+                                }
+                            }
                             DidExchangeMessages::ProblemReportReceived(problem_report) => {
                                 let thread = state.thread.clone()
                                     .update_received_order(&state.did_doc.id);
@@ -977,11 +1721,16 @@ impl DidExchangeSM {
                                 agent_info = match options.pairwise_agent_info.as_ref() {
                                     Some(pairwise_agent_info) => pairwise_agent_info.clone(),
                                     None => AgentInfo::create_agent()?
-                                };
+                                }.set_envelope_version(options.envelope_version()?)?
+                                    .set_return_route(options.return_route)
+                                    .set_peer_did(options.peer_did)
+                                    .set_delivery_policy(options.delivery_policy.clone());
                             }
                             ActorDidExchangeState::Invitee(DidExchangeState::Completed(state))
                         } else {
-                            ActorDidExchangeState::Invitee(state.handle_message(message, &agent_info)?)
+                            let (state, agent_info_) = state.step(message, agent_info)?;
+                            agent_info = agent_info_;
+                            ActorDidExchangeState::Invitee(state)
                         }
                     }
                 }
@@ -1050,6 +1799,98 @@ impl DidExchangeSM {
         }
     }
 
+    pub fn is_multi_use(&self) -> bool {
+        match self.state {
+            ActorDidExchangeState::Inviter(DidExchangeState::Invited(ref state)) => state.multi_use,
+            _ => false
+        }
+    }
+
+    /// Whether the Connection is the inviter side of multi-use invitation which has expired.
+    pub fn is_expired_multi_use(&self) -> bool {
+        match self.state {
+            ActorDidExchangeState::Inviter(DidExchangeState::Invited(ref state)) => state.multi_use && state.invitation.is_expired(),
+            _ => false
+        }
+    }
+
+    /// Whether the Connection stays in the current handshake state longer than `handshake_timeout` option allows.
+    /// Multi-use invitations are limited by `invitation_ttl` option only.
+    pub fn is_timed_out(&self) -> bool {
+        let timeout = match self.state {
+            ActorDidExchangeState::Inviter(ref state) | ActorDidExchangeState::Invitee(ref state) =>
+                match state {
+                    DidExchangeState::Invited(ref state) if !state.multi_use => state.timeout.as_ref(),
+                    DidExchangeState::Requested(ref state) => state.timeout.as_ref(),
+                    DidExchangeState::Responded(ref state) => state.timeout.as_ref(),
+                    _ => None
+                }
+        };
+
+        timeout.map_or(false, HandshakeTimeout::is_expired)
+    }
+
+    /// Moves the timed out Connection to `Failed` state.
+    /// The problem report is sent to the other side if its DIDDoc is already known.
+    pub fn check_timeout(self) -> VcxResult<DidExchangeSM> {
+        if !self.is_timed_out() {
+            return Ok(self);
+        }
+
+        warn!("DidExchangeSM: {}: handshake has timed out in state {}", self.source_id, self.state());
+
+        let DidExchangeSM { source_id, agent_info, state } = self;
+
+        let fail = |state: DidExchangeState| -> VcxResult<DidExchangeState> {
+            let state: FailedState = match state {
+                DidExchangeState::Invited(state) => {
+                    let problem_report = ProblemReport::create()
+                        .set_explain(HANDSHAKE_TIMED_OUT.to_string());
+                    (state, problem_report, Thread::new()).into()
+                }
+                DidExchangeState::Requested(state) => {
+                    let (problem_report, thread) = state.send_problem_report(&agent_info, HANDSHAKE_TIMED_OUT.to_string())?;
+                    (state, problem_report, thread).into()
+                }
+                DidExchangeState::Responded(state) => {
+                    let (problem_report, thread) = state.send_problem_report(&agent_info, HANDSHAKE_TIMED_OUT.to_string())?;
+                    (state, problem_report, thread).into()
+                }
+                state => return Ok(state)
+            };
+            Ok(DidExchangeState::Failed(state))
+        };
+
+        let state = match state {
+            ActorDidExchangeState::Inviter(state) => ActorDidExchangeState::Inviter(fail(state)?),
+            ActorDidExchangeState::Invitee(state) => ActorDidExchangeState::Invitee(fail(state)?),
+        };
+
+        Ok(DidExchangeSM { source_id, agent_info, state })
+    }
+
+    /// Moves the inviter side of expired multi-use invitation to `Failed` state, so it stops accepting requests.
+    pub fn check_invitation_expiry(self) -> DidExchangeSM {
+        if !self.is_expired_multi_use() {
+            return self;
+        }
+
+        warn!("DidExchangeSM: {}: multi-use invitation has expired", self.source_id);
+
+        let DidExchangeSM { source_id, agent_info, state } = self;
+
+        let state = match state {
+            ActorDidExchangeState::Inviter(DidExchangeState::Invited(state)) => {
+                let problem_report = ProblemReport::create()
+                    .set_explain(INVITATION_EXPIRED.to_string());
+                ActorDidExchangeState::Inviter(DidExchangeState::Failed((state, problem_report, Thread::new()).into()))
+            }
+            state => state
+        };
+
+        DidExchangeSM { source_id, agent_info, state }
+    }
+
     pub fn get_protocols(&self) -> Vec<ProtocolDescriptor> {
         ProtocolRegistry::init().protocols()
     }
@@ -1082,11 +1923,38 @@ impl DidExchangeSM {
 
     pub fn prev_agent_info(&self) -> Option<&AgentInfo> {
         match self.state {
-            ActorDidExchangeState::Inviter(DidExchangeState::Responded(ref state)) => Some(&state.prev_agent_info),
+            ActorDidExchangeState::Inviter(DidExchangeState::Responded(ref state)) if !state.shared_prev_agent => Some(&state.prev_agent_info),
+            ActorDidExchangeState::Inviter(DidExchangeState::Completed(ref state)) |
+            ActorDidExchangeState::Invitee(DidExchangeState::Completed(ref state)) =>
+                state.did_rotation.as_ref().map(|did_rotation| &did_rotation.prev_agent_info),
             _ => None
         }
     }
 
+    /// Pairwise agents of rotated DIDs which are not deleted yet.
+    pub fn retired_agents(&self) -> &[AgentInfo] {
+        match self.state {
+            ActorDidExchangeState::Inviter(DidExchangeState::Completed(ref state)) |
+            ActorDidExchangeState::Invitee(DidExchangeState::Completed(ref state)) => &state.retired_agents,
+            _ => &[]
+        }
+    }
+
+    /// Deletes pairwise agents of rotated DIDs.
+    pub fn release_retired_agents(&mut self) {
+        match self.state {
+            ActorDidExchangeState::Inviter(DidExchangeState::Completed(ref mut state)) |
+            ActorDidExchangeState::Invitee(DidExchangeState::Completed(ref mut state)) => {
+                for agent_info in state.retired_agents.drain(..) {
+                    if let Err(err) = agent_info.retire() {
+                        warn!("Unable to delete pairwise agent of rotated DID. Err: {:?}", err);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     pub fn thread(&self) -> Option<&Thread> {
         match self.state {
             ActorDidExchangeState::Inviter(DidExchangeState::Invited(_)) |
@@ -1128,6 +1996,16 @@ pub mod test {
     use crate::aries::messages::discovery::query::tests::_query;
     use crate::aries::messages::discovery::disclose::tests::_disclose;
     use crate::aries::messages::outofband::v10::invitation::tests::{_invitation as _outofband_invitation, _invitation_no_handshake as _outofband_invitation_no_handshake};
+    use crate::aries::messages::didexchange::request::tests::_didexchange_request;
+    use crate::aries::messages::didexchange::complete::tests::_complete;
+    use crate::aries::messages::connection::did_doc::tests::{_did_doc, _id, _key_1, _key_2, _key_3, _recipient_keys};
+    use crate::aries::handlers::connection::types::HandshakeRequest;
+    use crate::aries::messages::a2a::MessageId;
+    use crate::aries::messages::did_rotate::rotate::tests::_rotate;
+    use crate::aries::messages::did_doc_update::update::tests::_update;
+    use crate::aries::messages::revocation_notification::v10::tests::_revoke;
+    use crate::aries::messages::connection::did_peer::{self, PeerDidNumalgo};
+    use crate::aries::messages::timing::Timing;
 
     pub fn _ack() -> Ack {
         let mut ack = t_ack();
@@ -1257,6 +2135,33 @@ pub mod test {
                 }
             }
 
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_handshake_protocols_from_initialized_outofband_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = DidExchangeSM::new(Actor::Inviter, &source_id(), Some(_outofband_meta()));
+
+                let options = ConnectionOptions {
+                    handshake_protocols: Some(vec![HandshakeProtocol::DidExchange, HandshakeProtocol::Connections]),
+                    ..ConnectionOptions::default()
+                };
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::Connect(options)).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Invited(state)) => {
+                        match state.invitation {
+                            Invitations::OutofbandInvitation(invitation) => {
+                                assert_eq!(&vec![HandshakeProtocol::DidExchange.id(), HandshakeProtocol::Connections.id()], invitation.handshake_protocols());
+                                Ok(())
+                            }
+                            Invitations::ConnectionInvitation(other) => Err(format!("Invitation expected to be in `Out-of-Band` protocol format, but: {:?}", other))
+                        }
+                    }
+                    other => Err(format!("State expected to be Invited, but: {:?}", other))
+                }
+            }
+
             #[test]
             fn test_did_exchange_handle_connect_message_from_initialized_outofband_state_with_no_handshake() -> Result<(), String> {
                 let _setup = AgencyModeSetup::init();
@@ -1278,6 +2183,163 @@ pub mod test {
                 }
             }
 
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_public_did_service_from_initialized_outofband_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let public_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+
+                let options = ConnectionOptions {
+                    use_public_did: Some(true),
+                    public_did_service: Some(true),
+                    ..ConnectionOptions::default()
+                };
+
+                let did_exchange_sm = DidExchangeSM::new(Actor::Inviter, &source_id(), Some(_outofband_meta()))
+                    .step(DidExchangeMessages::Connect(options)).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Invited(state)) => {
+                        assert!(state.invitation.is_public_did_invitation());
+
+                        let invitation_json = json!(state.invitation);
+                        assert_eq!(json!([format!("did:sov:{}", public_did)]), invitation_json["OutofbandInvitation"]["service"]);
+                        assert_eq!(json!(public_did), invitation_json["OutofbandInvitation"]["public_did"]);
+                        Ok(())
+                    }
+                    other => Err(format!("State expected to be Invited, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_public_did_service_requires_public_did() {
+                let _setup = AgencyModeSetup::init();
+
+                let options = ConnectionOptions {
+                    public_did_service: Some(true),
+                    ..ConnectionOptions::default()
+                };
+
+                DidExchangeSM::new(Actor::Inviter, &source_id(), Some(_outofband_meta()))
+                    .step(DidExchangeMessages::Connect(options)).unwrap_err();
+            }
+
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_multi_use_from_initialized_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let options = ConnectionOptions {
+                    multi_use: Some(true),
+                    ..ConnectionOptions::default()
+                };
+
+                let did_exchange_sm = inviter_sm().step(DidExchangeMessages::Connect(options.clone())).unwrap();
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Invited(InvitedState { multi_use: true, .. })), did_exchange_sm.state);
+                assert!(did_exchange_sm.is_multi_use());
+
+                let did_exchange_sm = DidExchangeSM::new(Actor::Inviter, &source_id(), Some(_outofband_meta()))
+                    .step(DidExchangeMessages::Connect(options)).unwrap();
+                assert!(did_exchange_sm.is_multi_use());
+
+                assert!(!inviter_sm().to_inviter_invited_state().is_multi_use());
+            }
+
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_multi_use_requires_handshake() {
+                let _setup = AgencyModeSetup::init();
+
+                let outofband_meta = OutofbandMeta {
+                    handshake: false,
+                    .._outofband_meta()
+                };
+
+                let options = ConnectionOptions {
+                    multi_use: Some(true),
+                    ..ConnectionOptions::default()
+                };
+
+                DidExchangeSM::new(Actor::Inviter, &source_id(), Some(outofband_meta))
+                    .step(DidExchangeMessages::Connect(options)).unwrap_err();
+            }
+
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_invitation_ttl_and_handshake_timeout() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let options = ConnectionOptions {
+                    invitation_ttl: Some(60),
+                    handshake_timeout: Some(60),
+                    ..ConnectionOptions::default()
+                };
+
+                let did_exchange_sm = inviter_sm().step(DidExchangeMessages::Connect(options)).unwrap();
+                assert!(!did_exchange_sm.is_timed_out());
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Invited(state)) => {
+                        assert_eq!(Some(60), state.timeout.map(|timeout| timeout.timeout));
+                        match state.invitation {
+                            Invitations::ConnectionInvitation(invitation) => {
+                                assert!(invitation.timing.is_some());
+                                assert!(!invitation.is_expired());
+                                Ok(())
+                            }
+                            Invitations::OutofbandInvitation(other) => Err(format!("Invitation expected to be in `Connections` protocol format, but: {:?}", other))
+                        }
+                    }
+                    other => Err(format!("State expected to be Invited, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_exchange_request_message_for_expired_invitation() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_invited_state();
+
+                if let ActorDidExchangeState::Inviter(DidExchangeState::Invited(ref mut state)) = did_exchange_sm.state {
+                    if let Invitations::ConnectionInvitation(ref mut invitation) = state.invitation {
+                        invitation.timing = Some(Timing { expires_time: Some(String::from("2018-12-13T17:29:06+00:00")) });
+                    }
+                }
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::ExchangeRequestReceived(_request())).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Failed(state)) => {
+                        assert_eq!(Some(ProblemCode::RequestNotAccepted), state.error.unwrap().problem_code);
+                        Ok(())
+                    }
+                    other => Err(format!("State expected to be Failed, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_check_timeout_from_responded_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_responded_state();
+
+                // no timeout is set by default
+                did_exchange_sm = did_exchange_sm.check_timeout().unwrap();
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Responded(_)), did_exchange_sm.state);
+
+                if let ActorDidExchangeState::Inviter(DidExchangeState::Responded(ref mut state)) = did_exchange_sm.state {
+                    state.timeout = Some(HandshakeTimeout { timeout: 60, expires_at: 0 });
+                }
+                assert!(did_exchange_sm.is_timed_out());
+
+                did_exchange_sm = did_exchange_sm.check_timeout().unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Failed(state)) => {
+                        assert_eq!(Some(HANDSHAKE_TIMED_OUT.to_string()), state.error.unwrap().explain);
+                        Ok(())
+                    }
+                    other => Err(format!("State expected to be Failed, but: {:?}", other))
+                }
+            }
+
             #[test]
             fn test_did_exchange_handle_other_messages_from_initialized_state() {
                 let _setup = AgencyModeSetup::init();
@@ -1323,6 +2385,109 @@ pub mod test {
                 did_exchange_sm.step(DidExchangeMessages::ExchangeRequestReceived(request)).unwrap_err();
             }
 
+            #[test]
+            fn test_did_exchange_handle_didexchange_request_message_from_invited_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_invited_state();
+                let invitation_id = did_exchange_sm.get_invitation().unwrap().id();
+                let request = _didexchange_request().set_pthid(&invitation_id);
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidExchangeRequestReceived(request.clone())).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Responded(state)) => {
+                        assert_eq!(request.thread_id(), state.thread.thid.clone().unwrap());
+                        assert_eq!(invitation_id, state.thread.pthid.clone().unwrap());
+                        assert_eq!(0, state.thread.received_orders.get(&request.did).cloned().unwrap());
+                        assert_eq!(_did_doc(), state.did_doc);
+                        match state.response {
+                            HandshakeResponse::DidExchange(response) => {
+                                let (signer, _) = response.decode().unwrap();
+                                assert_eq!(state.prev_agent_info.pw_vk, signer);
+                                Ok(())
+                            }
+                            other => Err(format!("Response expected to be DID Exchange, but: {:?}", other))
+                        }
+                    }
+                    other => Err(format!("State expected to be Responded, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_didexchange_request_bound_to_public_did_from_invited_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let public_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
+
+                let options = ConnectionOptions {
+                    use_public_did: Some(true),
+                    ..ConnectionOptions::default()
+                };
+
+                let did_exchange_sm = DidExchangeSM::new(Actor::Inviter, &source_id(), Some(_outofband_meta()))
+                    .step(DidExchangeMessages::Connect(options)).unwrap();
+
+                // parent thread only ends with the public DID
+                let request = _didexchange_request().set_pthid(&format!("did:sov:X{}", public_did));
+                did_exchange_sm.clone().step(DidExchangeMessages::DidExchangeRequestReceived(request)).unwrap_err();
+
+                let request = _didexchange_request().set_pthid(&format!("did:sov:{}", public_did));
+                let did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidExchangeRequestReceived(request)).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Responded(state)) => {
+                        match state.response {
+                            HandshakeResponse::DidExchange(response) => {
+                                let (signer, _) = response.decode().unwrap();
+                                assert_eq!(crypto::get_local_verkey(&public_did).unwrap(), signer);
+                                Ok(())
+                            }
+                            other => Err(format!("Response expected to be DID Exchange, but: {:?}", other))
+                        }
+                    }
+                    other => Err(format!("State expected to be Responded, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_invalid_didexchange_request_message_from_invited_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let did_exchange_sm = inviter_sm().to_inviter_invited_state();
+
+                // parent thread does not point to the invitation
+                did_exchange_sm.clone().step(DidExchangeMessages::DidExchangeRequestReceived(_didexchange_request())).unwrap_err();
+
+                // parent thread is not set
+                let mut request = _didexchange_request();
+                request.thread.pthid = None;
+                did_exchange_sm.clone().step(DidExchangeMessages::DidExchangeRequestReceived(request)).unwrap_err();
+
+                // DIDDoc is not attached
+                let mut request = _didexchange_request();
+                request.did_doc_attach = None;
+                did_exchange_sm.step(DidExchangeMessages::DidExchangeRequestReceived(request)).unwrap_err();
+            }
+
+            #[test]
+            fn test_did_exchange_handle_didexchange_complete_message_from_responded_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_invited_state();
+                let invitation_id = did_exchange_sm.get_invitation().unwrap().id();
+                let request = _didexchange_request().set_pthid(&invitation_id);
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidExchangeRequestReceived(request.clone())).unwrap();
+
+                let mut complete = _complete().set_thread_id(&request.thread_id());
+                complete.thread.sender_order = 1;
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidExchangeCompleteReceived(complete)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+            }
+
             #[test]
             fn test_did_exchange_handle_problem_report_message_from_invited_state() -> Result<(), String> {
                 let _setup = AgencyModeSetup::init();
@@ -1448,6 +2613,177 @@ pub mod test {
                 did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::ProblemReportReceived(_problem_report())).unwrap();
                 assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
             }
+
+            fn _rotate_id(did_exchange_sm: &DidExchangeSM) -> String {
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Inviter(DidExchangeState::Completed(ref state)) =>
+                        state.did_rotation.as_ref().unwrap().rotate_id.clone(),
+                    ref other => panic!("State expected to be Completed, but: {:?}", other)
+                }
+            }
+
+            fn _rotated_did_doc() -> DidDoc {
+                let mut did_doc = _did_doc();
+                did_doc.set_id(String::from("CnEDk9HrMnmiHXEV1WFgbV"));
+                did_doc
+            }
+
+            #[test]
+            fn test_did_exchange_handle_rotate_did_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+                let agent_info = did_exchange_sm.agent_info().clone();
+                assert!(did_exchange_sm.prev_agent_info().is_none());
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::RotateDid).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_ne!(agent_info.pw_did, did_exchange_sm.agent_info().pw_did);
+                assert_eq!(agent_info.connection_id(), did_exchange_sm.agent_info().connection_id());
+                assert_eq!(Some(&agent_info), did_exchange_sm.prev_agent_info());
+
+                // only one rotation at a time
+                did_exchange_sm.clone().step(DidExchangeMessages::RotateDid).unwrap_err();
+
+                // Ack of other thread is ignored
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidRotateAckReceived(_ack())).unwrap();
+                assert_eq!(Some(&agent_info), did_exchange_sm.prev_agent_info());
+
+                let ack = Ack::create().set_thread_id(&_rotate_id(&did_exchange_sm));
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidRotateAckReceived(ack)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_ne!(agent_info.pw_did, did_exchange_sm.agent_info().pw_did);
+                assert!(did_exchange_sm.prev_agent_info().is_none());
+
+                // the old agent is kept until it is released
+                assert_eq!(vec![agent_info], did_exchange_sm.retired_agents().to_vec());
+                did_exchange_sm.release_retired_agents();
+                assert!(did_exchange_sm.retired_agents().is_empty());
+            }
+
+            #[test]
+            fn test_did_exchange_handle_did_rotate_problem_report_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+                let agent_info = did_exchange_sm.agent_info().clone();
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::RotateDid).unwrap();
+                assert_ne!(&agent_info, did_exchange_sm.agent_info());
+
+                let problem_report = CommonProblemReport::create().set_thread_id(&_rotate_id(&did_exchange_sm));
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidRotateProblemReportReceived(problem_report)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_eq!(&agent_info, did_exchange_sm.agent_info());
+                assert!(did_exchange_sm.prev_agent_info().is_none());
+            }
+
+            #[test]
+            fn test_did_exchange_handle_did_rotate_message_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+                let agent_info = did_exchange_sm.agent_info().clone();
+
+                let rotate = Rotate::create().set_did_doc(&_rotated_did_doc()).sign(&_key_1()).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidRotateReceived(rotate)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_eq!(Some(_rotated_did_doc()), did_exchange_sm.did_doc());
+                assert_eq!(&agent_info, did_exchange_sm.agent_info());
+            }
+
+            #[test]
+            fn test_did_exchange_handle_did_rotate_message_signed_by_other_key_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+
+                let rotate = Rotate::create().set_did_doc(&_rotated_did_doc()).sign(&_key_2()).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidRotateReceived(rotate)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_eq!(Some(_did_doc()), did_exchange_sm.did_doc());
+            }
+
+            fn _updated_did_doc(recipient_keys: Vec<String>) -> DidDoc {
+                let mut did_doc = DidDoc::create();
+                did_doc.set_id(_id());
+                did_doc.set_service_endpoint(String::from("https://new-agency.example.com/agency/msg"));
+                did_doc.set_keys(recipient_keys, vec![_key_3()]);
+                did_doc
+            }
+
+            #[test]
+            fn test_did_exchange_handle_update_service_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+                let agent_info = did_exchange_sm.agent_info().clone();
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::UpdateService).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_eq!(&agent_info, did_exchange_sm.agent_info());
+                assert_eq!(Some(_did_doc()), did_exchange_sm.did_doc());
+
+                // not allowed while DID rotation is pending
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::RotateDid).unwrap();
+                did_exchange_sm.step(DidExchangeMessages::UpdateService).unwrap_err();
+            }
+
+            #[test]
+            fn test_did_exchange_handle_did_doc_update_message_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+                let agent_info = did_exchange_sm.agent_info().clone();
+
+                let update = DidDocUpdate::create().set_did_doc(&_updated_did_doc(_recipient_keys())).sign(&_key_1()).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidDocUpdateReceived(update)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_eq!(Some(_updated_did_doc(_recipient_keys())), did_exchange_sm.did_doc());
+                assert_eq!(&agent_info, did_exchange_sm.agent_info());
+            }
+
+            #[test]
+            fn test_did_exchange_handle_invalid_did_doc_update_message_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+
+                // signed by other key
+                let update = DidDocUpdate::create().set_did_doc(&_updated_did_doc(_recipient_keys())).sign(&_key_2()).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidDocUpdateReceived(update)).unwrap();
+                assert_eq!(Some(_did_doc()), did_exchange_sm.did_doc());
+
+                // other DID
+                let update = DidDocUpdate::create().set_did_doc(&_rotated_did_doc()).sign(&_key_1()).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidDocUpdateReceived(update)).unwrap();
+                assert_eq!(Some(_did_doc()), did_exchange_sm.did_doc());
+
+                // changed recipient keys
+                let update = DidDocUpdate::create().set_did_doc(&_updated_did_doc(vec![_key_2()])).sign(&_key_1()).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidDocUpdateReceived(update)).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+                assert_eq!(Some(_did_doc()), did_exchange_sm.did_doc());
+            }
+
+            #[test]
+            fn test_did_exchange_handle_revocation_notification_message_from_completed_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = inviter_sm().to_inviter_completed_state();
+
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::RevocationNotificationReceived(RevocationNotification::V1(_revoke()))).unwrap();
+
+                assert_match!(ActorDidExchangeState::Inviter(DidExchangeState::Completed(_)), did_exchange_sm.state);
+            }
         }
 
         mod find_message_to_handle {
@@ -1494,6 +2830,18 @@ pub mod test {
                     assert_match!(A2AMessage::ConnectionRequest(_), message);
                 }
 
+                // DID Exchange Request
+                {
+                    let messages = map!(
+                        "key_1".to_string() => A2AMessage::Ping(_ping()),
+                        "key_2".to_string() => A2AMessage::DidExchangeRequest(_didexchange_request())
+                    );
+
+                    let (uid, message) = connection.find_message_to_handle(messages).unwrap();
+                    assert_eq!("key_2", uid);
+                    assert_match!(A2AMessage::DidExchangeRequest(_), message);
+                }
+
                 // Connection Problem Report
                 {
                     let messages = map!(
@@ -1660,6 +3008,43 @@ pub mod test {
                     assert_eq!("key_3", uid);
                     assert_match!(A2AMessage::Answer(_), message);
                 }
+
+                // DID Rotate
+                {
+                    let messages = map!(
+                        "key_1".to_string() => A2AMessage::ConnectionRequest(_request()),
+                        "key_2".to_string() => A2AMessage::ConnectionResponse(_signed_response()),
+                        "key_3".to_string() => A2AMessage::DidRotate(_rotate())
+                    );
+
+                    let (uid, message) = connection.find_message_to_handle(messages).unwrap();
+                    assert_eq!("key_3", uid);
+                    assert_match!(A2AMessage::DidRotate(_), message);
+                }
+
+                // DIDDoc update
+                {
+                    let messages = map!(
+                        "key_1".to_string() => A2AMessage::ConnectionRequest(_request()),
+                        "key_2".to_string() => A2AMessage::DidDocUpdate(_update())
+                    );
+
+                    let (uid, message) = connection.find_message_to_handle(messages).unwrap();
+                    assert_eq!("key_2", uid);
+                    assert_match!(A2AMessage::DidDocUpdate(_), message);
+                }
+
+                // Revocation notification
+                {
+                    let messages = map!(
+                        "key_1".to_string() => A2AMessage::ConnectionRequest(_request()),
+                        "key_2".to_string() => A2AMessage::RevocationNotification(RevocationNotification::V1(_revoke()))
+                    );
+
+                    let (uid, message) = connection.find_message_to_handle(messages).unwrap();
+                    assert_eq!("key_2", uid);
+                    assert_match!(A2AMessage::RevocationNotification(_), message);
+                }
             }
         }
 
@@ -1711,6 +3096,20 @@ pub mod test {
             }
         }
 
+        fn _didexchange_invitation() -> OutofbandInvitation {
+            OutofbandInvitation::V10(
+                _outofband_invitation()
+                    .set_handshake_protocols(&[HandshakeProtocol::DidExchange])
+            )
+        }
+
+        fn _didexchange_response(key: &str) -> DidExchangeResponse {
+            DidExchangeResponse::create()
+                .set_thread_id(&_request().id.0)
+                .set_did_doc(&_did_doc())
+                .sign(key).unwrap()
+        }
+
         fn _response(key: &str) -> SignedResponse {
             Response::default()
                 .set_service_endpoint(_service_endpoint())
@@ -1833,6 +3232,19 @@ pub mod test {
                 }
             }
 
+            #[test]
+            fn test_did_exchange_handle_connect_message_for_expired_invitation() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut invitation = _invitation();
+                invitation.timing = Some(Timing { expires_time: Some(String::from("2018-12-13T17:29:06+00:00")) });
+
+                let did_exchange_sm = invitee_sm().step(DidExchangeMessages::InvitationReceived(invitation)).unwrap();
+
+                let err = did_exchange_sm.step(DidExchangeMessages::Connect(ConnectionOptions::default())).unwrap_err();
+                assert_eq!(VcxErrorKind::InvalidInviteDetail, err.kind());
+            }
+
             #[test]
             fn test_did_exchange_handle_problem_report_message_from_invited_state() -> Result<(), String> {
                 let _setup = AgencyModeSetup::init();
@@ -1876,6 +3288,89 @@ pub mod test {
                 assert_match!(ActorDidExchangeState::Invitee(DidExchangeState::Completed(_)), did_exchange_sm.state);
             }
 
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_didexchange_invitation_from_invited_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = invitee_sm();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::OutofbandInvitationReceived(_didexchange_invitation())).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::Connect(ConnectionOptions::default())).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Invitee(DidExchangeState::Requested(state)) => {
+                        match state.request {
+                            HandshakeRequest::DidExchange(request) => {
+                                assert_eq!(did_exchange_sm.agent_info.pw_did, request.did);
+                                assert_eq!(did_exchange_sm.agent_info.recipient_keys(), request.did_doc().unwrap().recipient_keys());
+                                assert_eq!(state.thread.pthid, request.thread.pthid);
+                                Ok(())
+                            }
+                            other => Err(format!("Request expected to be DID Exchange, but: {:?}", other))
+                        }
+                    }
+                    other => Err(format!("State expected to be Requested, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_connect_message_with_peer_did_from_invited_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let options = ConnectionOptions {
+                    peer_did: Some(PeerDidNumalgo::Numalgo2),
+                    ..ConnectionOptions::default()
+                };
+
+                let mut did_exchange_sm = invitee_sm();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::OutofbandInvitationReceived(_didexchange_invitation())).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::Connect(options)).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Invitee(DidExchangeState::Requested(state)) => {
+                        match state.request {
+                            HandshakeRequest::DidExchange(request) => {
+                                assert!(request.did.starts_with("did:peer:2"));
+                                assert_eq!(did_exchange_sm.agent_info.recipient_keys(), request.did_doc().unwrap().recipient_keys());
+                                assert_eq!(did_peer::resolve(&request.did).unwrap(), request.did_doc().unwrap());
+                                Ok(())
+                            }
+                            other => Err(format!("Request expected to be DID Exchange, but: {:?}", other))
+                        }
+                    }
+                    other => Err(format!("State expected to be Requested, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_didexchange_response_message_from_requested_state() -> Result<(), String> {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = invitee_sm();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::OutofbandInvitationReceived(_didexchange_invitation())).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::Connect(ConnectionOptions::default())).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::DidExchangeResponseReceived(_didexchange_response(&_key_1()))).unwrap();
+
+                match did_exchange_sm.state {
+                    ActorDidExchangeState::Invitee(DidExchangeState::Completed(state)) => {
+                        assert_eq!(_did_doc(), state.did_doc);
+                        assert_eq!(Some(MessageId::id().0), state.thread.thid);
+                        Ok(())
+                    }
+                    other => Err(format!("State expected to be Completed, but: {:?}", other))
+                }
+            }
+
+            #[test]
+            fn test_did_exchange_handle_didexchange_response_signed_by_other_key_from_requested_state() {
+                let _setup = AgencyModeSetup::init();
+
+                let mut did_exchange_sm = invitee_sm();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::OutofbandInvitationReceived(_didexchange_invitation())).unwrap();
+                did_exchange_sm = did_exchange_sm.step(DidExchangeMessages::Connect(ConnectionOptions::default())).unwrap();
+
+                did_exchange_sm.step(DidExchangeMessages::DidExchangeResponseReceived(_didexchange_response(&_key_2()))).unwrap_err();
+            }
+
             #[test]
             fn test_did_exchange_handle_invalid_response_message_from_requested_state() {
                 let _setup = AgencyModeSetup::init();
@@ -15,8 +15,19 @@ use crate::aries::messages::questionanswer::answer::Answer;
 use crate::aries::messages::committedanswer::question::{Question as CommittedQuestion, QuestionResponse as CommittedQuestionResponse};
 use crate::aries::messages::committedanswer::answer::Answer as CommitedAnswer;
 use crate::aries::messages::invite_action::invite::Invite as InviteForAction;
+use crate::aries::messages::mediation::mediate_grant::MediateGrant;
+use crate::aries::messages::mediation::mediate_deny::MediateDeny;
+use crate::aries::messages::mediation::keylist_update_response::KeylistUpdateResponse;
+use crate::aries::messages::pickup::delivery::Delivery;
 use crate::connection::ConnectionOptions;
 use crate::aries::messages::connection::response::SignedResponse;
+use crate::aries::messages::didexchange::request::Request as DidExchangeRequest;
+use crate::aries::messages::didexchange::response::Response as DidExchangeResponse;
+use crate::aries::messages::didexchange::complete::Complete;
+use crate::aries::messages::did_rotate::rotate::Rotate;
+use crate::aries::messages::did_doc_update::update::Update as DidDocUpdate;
+use crate::aries::messages::error::ProblemReport as CommonProblemReport;
+use crate::aries::messages::revocation_notification::revoke::RevocationNotification;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum DidExchangeMessages {
@@ -25,6 +36,9 @@ pub enum DidExchangeMessages {
     ExchangeRequestReceived(Request),
     ExchangeResponseReceived(SignedResponse),
     AckReceived(Ack),
+    DidExchangeRequestReceived(DidExchangeRequest),
+    DidExchangeResponseReceived(DidExchangeResponse),
+    DidExchangeCompleteReceived(Complete),
     ProblemReportReceived(ProblemReport),
     SendPing(Option<String>),
     PingReceived(Ping),
@@ -44,6 +58,20 @@ pub enum DidExchangeMessages {
     CommittedAnswerReceived(CommitedAnswer),
     SendInviteAction(InviteForAction),
     InviteActionReceived(InviteForAction),
+    RequestMediation,
+    MediateGrantReceived(MediateGrant),
+    MediateDenyReceived(MediateDeny),
+    KeylistUpdateResponseReceived(KeylistUpdateResponse),
+    PickupDeliveryReceived(Delivery),
+    RotateDid,
+    DidRotateReceived(Rotate),
+    DidRotateAckReceived(Ack),
+    DidRotateProblemReportReceived(CommonProblemReport),
+    UpdateService,
+    DidDocUpdateReceived(DidDocUpdate),
+    DidDocUpdateAckReceived(Ack),
+    DidDocUpdateProblemReportReceived(CommonProblemReport),
+    RevocationNotificationReceived(RevocationNotification),
     Unknown
 }
 
@@ -59,6 +87,15 @@ impl From<A2AMessage> for DidExchangeMessages {
             A2AMessage::ConnectionResponse(request) => {
                 DidExchangeMessages::ExchangeResponseReceived(request)
             }
+            A2AMessage::DidExchangeRequest(request) => {
+                DidExchangeMessages::DidExchangeRequestReceived(request)
+            }
+            A2AMessage::DidExchangeResponse(response) => {
+                DidExchangeMessages::DidExchangeResponseReceived(response)
+            }
+            A2AMessage::DidExchangeComplete(complete) => {
+                DidExchangeMessages::DidExchangeCompleteReceived(complete)
+            }
             A2AMessage::Ping(ping) => {
                 DidExchangeMessages::PingReceived(ping)
             }
@@ -98,6 +135,39 @@ impl From<A2AMessage> for DidExchangeMessages {
             A2AMessage::InviteForAction(invite) => {
                 DidExchangeMessages::InviteActionReceived(invite)
             }
+            A2AMessage::MediateGrant(grant) => {
+                DidExchangeMessages::MediateGrantReceived(grant)
+            }
+            A2AMessage::MediateDeny(deny) => {
+                DidExchangeMessages::MediateDenyReceived(deny)
+            }
+            A2AMessage::KeylistUpdateResponse(response) => {
+                DidExchangeMessages::KeylistUpdateResponseReceived(response)
+            }
+            A2AMessage::PickupDelivery(delivery) => {
+                DidExchangeMessages::PickupDeliveryReceived(delivery)
+            }
+            A2AMessage::DidRotate(rotate) => {
+                DidExchangeMessages::DidRotateReceived(rotate)
+            }
+            A2AMessage::DidRotateAck(ack) => {
+                DidExchangeMessages::DidRotateAckReceived(ack)
+            }
+            A2AMessage::DidRotateProblemReport(problem_report) => {
+                DidExchangeMessages::DidRotateProblemReportReceived(problem_report)
+            }
+            A2AMessage::DidDocUpdate(update) => {
+                DidExchangeMessages::DidDocUpdateReceived(update)
+            }
+            A2AMessage::DidDocUpdateAck(ack) => {
+                DidExchangeMessages::DidDocUpdateAckReceived(ack)
+            }
+            A2AMessage::DidDocUpdateProblemReport(problem_report) => {
+                DidExchangeMessages::DidDocUpdateProblemReportReceived(problem_report)
+            }
+            A2AMessage::RevocationNotification(notification) => {
+                DidExchangeMessages::RevocationNotificationReceived(notification)
+            }
             _ => {
                 DidExchangeMessages::Unknown
             }
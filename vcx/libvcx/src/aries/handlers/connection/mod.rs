@@ -17,12 +17,13 @@ use crate::aries::messages::connection::invite::Invitation;
 use crate::aries::messages::connection::did_doc::DidDoc;
 use crate::aries::messages::basic_message::message::BasicMessage;
 use crate::aries::handlers::connection::types::{SideConnectionInfo, PairwiseConnectionInfo, CompletedConnection, OutofbandMeta, Invitations};
+use crate::aries::utils::connection_storage;
 use crate::aries::messages::outofband::invitation::Invitation as OutofbandInvitation;
 use crate::aries::messages::questionanswer::question::{Question, QuestionResponse};
 use crate::aries::messages::committedanswer::question::{Question as CommittedQuestion, QuestionResponse as CommittedQuestionResponse};
 use crate::aries::messages::invite_action::invite::InviteActionData;
 use crate::aries::messages::invite_action::invite::Invite as InviteForAction;
-use crate::connection::{ConnectionOptions, Connections};
+use crate::connection::{ConnectionOptions, Connections, GenericMessageOptions};
 use crate::aries::messages::connection::problem_report::ProblemReport;
 use crate::agent::messages::connection_upgrade::{ConnectionUpgradeInfo, ConnectionUpgradeDirections};
 use crate::connection::Connection as ConnectionV1;
@@ -79,6 +80,9 @@ impl Connection {
 
         invitation.validate()?;
 
+        // resolve services given as DIDs (or public DID) into endpoints and keys
+        invitation.resolve_services()?;
+
         // normalize service keys in case invitation is using did:key format
         invitation.normalize_service_keys()?;
 
@@ -101,6 +105,10 @@ impl Connection {
         self.connection_sm.remote_did()
     }
 
+    pub fn remote_did_doc(&self) -> Option<DidDoc> {
+        self.connection_sm.did_doc()
+    }
+
     pub fn remote_vk(&self) -> VcxResult<String> {
         self.connection_sm.remote_vk()
     }
@@ -167,22 +175,23 @@ impl Connection {
         }
 
         let messages = self.get_messages()?;
-        let pw_did = self.agent_info().pw_did.clone();
+        let agent_info = self.agent_info().clone();
+        let pw_did = agent_info.pw_did.clone();
 
         if let Some((uid, message)) = self.connection_sm.find_message_to_handle(messages) {
-            self.handle_message(message.into())?;
-            self.agent_info().update_message_status(uid, Some(pw_did))?;
+            agent_info.handle_message_once(uid, Some(pw_did), &message, || self.handle_message(message.clone().into()))?;
         } else {
             if let Some(prev_agent_info) = self.connection_sm.prev_agent_info().cloned() {
                 let messages = prev_agent_info.get_messages()?;
 
                 if let Some((uid, message)) = self.connection_sm.find_message_to_handle(messages) {
-                    self.handle_message(message.into())?;
-                    prev_agent_info.update_message_status(uid, Some(pw_did))?;
+                    prev_agent_info.handle_message_once(uid, Some(pw_did), &message, || self.handle_message(message.clone().into()))?;
                 }
             }
         };
 
+        self.check_timeout()?;
+
         let state = self.state();
 
         trace!("Connection::update_state <<< state: {:?}", state);
@@ -205,6 +214,7 @@ impl Connection {
                                               format!("Cannot updated Connection state with agent: Message deserialization failed with: {:?}", err)))?;
 
         self.handle_message(message.into())?;
+        self.check_timeout()?;
 
         let state = self.state();
 
@@ -213,6 +223,210 @@ impl Connection {
         Ok(state)
     }
 
+    /// Returns the pairwise agent of this Connection using the key `recipient_vk`.
+    pub fn agent_for_recipient_key(&self, recipient_vk: &str) -> Option<&AgentInfo> {
+        Some(self.agent_info())
+            .into_iter()
+            .chain(self.connection_sm.prev_agent_info())
+            .chain(self.connection_sm.retired_agents())
+            .find(|agent_info| agent_info.pw_vk == recipient_vk)
+    }
+
+    /// Pairwise agents of rotated DIDs kept for the protocols started before the rotation.
+    pub fn retired_agents(&self) -> &[AgentInfo] {
+        self.connection_sm.retired_agents()
+    }
+
+    /// Deletes pairwise agents of rotated DIDs once no protocol started before the rotation is in progress.
+    pub fn release_retired_agents(&mut self) {
+        self.connection_sm.release_retired_agents()
+    }
+
+    /// Whether a message received through the inbound endpoint is addressed to this Connection and can be handled in its current state.
+    /// Multi-use invitations accept requests addressed to the invitation key only.
+    pub fn accepts_inbound_message(&self, recipient_vk: &str, message: &A2AMessage) -> bool {
+        let is_recipient = self.agent_info().pw_vk == recipient_vk ||
+            (!self.is_multi_use() && self.agent_for_recipient_key(recipient_vk).is_some());
+
+        is_recipient && self.connection_sm.find_message_to_handle(map!(String::new() => message.clone())).is_some()
+    }
+
+    /// Handles a message received through the inbound endpoint.
+    /// Returns `false` if the message was not addressed to this Connection or cannot be handled in its current state.
+    pub fn handle_inbound_message(&mut self, recipient_vk: &str, message: &A2AMessage) -> VcxResult<bool> {
+        trace!("Connection::handle_inbound_message >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+        let is_recipient = self.agent_for_recipient_key(recipient_vk).is_some();
+
+        if !is_recipient {
+            return Ok(false);
+        }
+
+        match self.connection_sm.find_message_to_handle(map!(String::new() => message.clone())) {
+            Some((_, message)) => {
+                debug!("Connection {}: Handling inbound message", self.source_id());
+                self.handle_message(message.into())?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
+    pub fn is_multi_use(&self) -> bool {
+        self.connection_sm.is_multi_use()
+    }
+
+    /// Fails the Connection staying in a handshake state longer than `handshake_timeout` option allows.
+    fn check_timeout(&mut self) -> VcxResult<()> {
+        if self.connection_sm.is_timed_out() {
+            debug!("Connection {}: Handshake has timed out", self.source_id());
+            self.connection_sm = self.connection_sm.clone().check_timeout()?;
+        }
+        Ok(())
+    }
+
+    /// Fails the multi-use invitation which has expired (see `invitation_ttl` option).
+    fn check_invitation_expiry(&mut self) {
+        if self.connection_sm.is_expired_multi_use() {
+            debug!("Connection {}: Multi-use invitation has expired", self.source_id());
+            self.connection_sm = self.connection_sm.clone().check_invitation_expiry();
+        }
+    }
+
+    /// Whether the Connection was spawned by the multi-use invitation with the given id.
+    pub fn is_spawned_by(&self, invitation_id: &str) -> bool {
+        match self.state_object() {
+            ActorDidExchangeState::Inviter(_) if !self.is_multi_use() =>
+                self.get_invitation().map_or(false, |invitation| invitation.id() == invitation_id),
+            _ => false
+        }
+    }
+
+    /// Tags describing the Connection in the wallet storage (see `connection_storage`).
+    pub fn storage_tags(&self) -> HashMap<String, String> {
+        let mut tags = HashMap::new();
+
+        tags.insert(connection_storage::TAG_MY_DID.to_string(), self.agent_info().pw_did.clone());
+        tags.insert(connection_storage::TAG_SOURCE_ID.to_string(), self.source_id());
+        tags.insert(connection_storage::TAG_STATE.to_string(), self.state().to_string());
+
+        if let Ok(their_did) = self.remote_did() {
+            tags.insert(connection_storage::TAG_THEIR_DID.to_string(), their_did);
+        }
+
+        if let Some(invitation) = self.get_invitation() {
+            tags.insert(connection_storage::TAG_INVITATION_ID.to_string(), invitation.id());
+
+            if let Some(label) = invitation.name() {
+                tags.insert(connection_storage::TAG_LABEL.to_string(), label.to_string());
+            }
+            if let Some(goal_code) = invitation.goal_code() {
+                tags.insert(connection_storage::TAG_GOAL_CODE.to_string(), goal_code.to_string());
+            }
+            if let Some(public_did) = invitation.public_did() {
+                tags.insert(connection_storage::TAG_THEIR_PUBLIC_DID.to_string(), public_did.to_string());
+            }
+        }
+
+        tags
+    }
+
+    /// Handles requests received for multi-use invitation.
+    /// Every request is accepted by a new Connection sharing the invitation, while this Connection stays in `Invited` state.
+    /// Once the invitation has expired, this Connection rejects the requests itself and moves to `Failed` state.
+    /// Returns the Connections spawned for the handled requests.
+    pub fn accept_requests(&mut self, message: Option<&str>) -> VcxResult<Vec<Connection>> {
+        trace!("Connection::accept_requests >>> message: {:?}", secret!(message));
+        debug!("Connection {}: Accepting requests for multi-use invitation", self.source_id());
+
+        if let Some(message_) = message {
+            let message: A2AMessage = ::serde_json::from_str(&message_)
+                .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson,
+                                                  format!("Cannot accept request for multi-use invitation: Message deserialization failed with: {:?}", err)))?;
+
+            return self.spawn_child(message.into()).map(|child| child.into_iter().collect());
+        }
+
+        let agent_info = self.agent_info().clone();
+        let pw_did = agent_info.pw_did.clone();
+
+        let mut children = Vec::new();
+
+        for (uid, message) in self.get_messages()? {
+            // the invitation has expired on one of the previous requests
+            if !self.is_multi_use() {
+                break;
+            }
+
+            if let Some((uid, message)) = self.connection_sm.find_message_to_handle(map!(uid => message)) {
+                // a broken request must not block the requests of other invitees
+                let child = agent_info.handle_message_once(uid, Some(pw_did.clone()), &message, || {
+                    match self.spawn_child(message.clone().into()) {
+                        Ok(child) => Ok(child),
+                        Err(err) => {
+                            warn!("Connection {}: Cannot accept request for multi-use invitation: {}", self.source_id(), err);
+                            Ok(None)
+                        }
+                    }
+                })?;
+                children.extend(child.and_then(|child| child));
+            }
+        }
+
+        self.check_invitation_expiry();
+
+        trace!("Connection::accept_requests <<< children: {}", children.len());
+        Ok(children)
+    }
+
+    /// Handles a request received through the inbound endpoint for multi-use invitation.
+    /// Returns the Connection spawned for the request or `None` if the message is not addressed to the invitation.
+    pub fn accept_inbound_request(&mut self, recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<Connection>> {
+        trace!("Connection::accept_inbound_request >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+        if self.agent_info().pw_vk != recipient_vk {
+            return Ok(None);
+        }
+
+        match self.connection_sm.find_message_to_handle(map!(String::new() => message.clone())) {
+            Some((_, message)) => self.spawn_child(message.into()),
+            None => Ok(None)
+        }
+    }
+
+    /// Accepts the request with a copy of this Connection which gets its own pairwise keys.
+    /// Requests received after the invitation has expired are rejected by this Connection, which moves to `Failed` state.
+    /// Other messages (problem reports) are ignored so that a single invitee cannot fail the invitation.
+    fn spawn_child(&mut self, message: DidExchangeMessages) -> VcxResult<Option<Connection>> {
+        match message {
+            message @ DidExchangeMessages::ExchangeRequestReceived(_) |
+            message @ DidExchangeMessages::DidExchangeRequestReceived(_) => {
+                // expiry is checked when the request is handled as it could be received long before
+                if self.connection_sm.is_expired_multi_use() {
+                    self.step(message)?;
+                    return Err(VcxError::from_msg(VcxErrorKind::InvalidInviteDetail,
+                                                  "Request for multi-use invitation is rejected: Invitation has expired"));
+                }
+
+                let mut child = self.clone();
+                child.step(message)?;
+
+                // the request cannot be processed
+                if let Some(problem_report) = child.connection_sm.problem_report() {
+                    return Err(VcxError::from_msg(VcxErrorKind::InvalidInviteDetail,
+                                                  format!("Request for multi-use invitation is rejected: {}", problem_report.explain.clone().unwrap_or_default())));
+                }
+
+                debug!("Connection {}: Spawned Connection for request received on multi-use invitation", self.source_id());
+                Ok(Some(child))
+            }
+            message => {
+                warn!("Connection {}: Ignoring message received for multi-use invitation: {:?}", self.source_id(), secret!(message));
+                Ok(None)
+            }
+        }
+    }
+
     pub fn get_messages(&self) -> VcxResult<HashMap<String, A2AMessage>> {
         trace!("Connection: get_messages >>>");
         debug!("Connection {}: Getting agent", self.source_id());
@@ -267,16 +481,25 @@ impl Connection {
         }
     }
 
-    pub fn send_generic_message(&self, message: &str, _message_options: &str) -> VcxResult<String> {
-        trace!("Connection::send_generic_message >>> message: {:?}", secret!(message));
+    pub fn send_generic_message(&self, message: &str, message_options: &str) -> VcxResult<String> {
+        trace!("Connection::send_generic_message >>> message: {:?}, message_options: {:?}", secret!(message), message_options);
         debug!("Connection {}: Sending generic message", self.source_id());
 
-        let message = Connection::parse_generic_message(message, _message_options);
+        let options = GenericMessageOptions::from_str(message_options)?;
+
+        let message = Connection::parse_generic_message(message, message_options);
         let message = match message {
             A2AMessage::Generic(message_) => message_,
             message => json!(message)
         };
-        self.send_message(&message).map(|_| String::new())
+
+        let did_doc = self.connection_sm.did_doc()
+            .ok_or(VcxError::from_msg(VcxErrorKind::NotReady, "Cannot send message: Remote Connection DIDDoc is not set"))?;
+
+        self.agent_info().clone()
+            .set_delivery_policy(options.delivery_policy)
+            .send_message(&message, &did_doc)
+            .map(|_| String::new())
     }
 
     pub fn send_ping(&mut self, comment: Option<String>) -> VcxResult<()> {
@@ -286,6 +509,30 @@ impl Connection {
         self.handle_message(DidExchangeMessages::SendPing(comment))
     }
 
+    pub fn request_mediation(&mut self) -> VcxResult<()> {
+        trace!("Connection::request_mediation >>>");
+        debug!("Connection {}: Requesting mediation", self.source_id());
+
+        self.handle_message(DidExchangeMessages::RequestMediation)
+    }
+
+    pub fn rotate_did(&mut self) -> VcxResult<()> {
+        trace!("Connection::rotate_did >>>");
+        debug!("Connection {}: Rotating pairwise DID", self.source_id());
+
+        self.handle_message(DidExchangeMessages::RotateDid)
+    }
+
+    /// Sends the current service endpoint and routing keys of the pairwise agent to the remote side.
+    pub fn update_service(&mut self) -> VcxResult<()> {
+        trace!("Connection::update_service >>>");
+        debug!("Connection {}: Updating service", self.source_id());
+
+        self.get_completed_connection()?;
+
+        self.handle_message(DidExchangeMessages::UpdateService)
+    }
+
     pub fn delete(&self) -> VcxResult<()> {
         trace!("Connection: delete >>> {:?}", self.connection_sm.source_id());
         self.agent_info().delete()
@@ -303,10 +550,12 @@ impl Connection {
         self.handle_message(DidExchangeMessages::DiscoverFeatures((query, comment)))
     }
 
-    pub fn send_reuse(&mut self, invitation: OutofbandInvitation) -> VcxResult<()> {
+    pub fn send_reuse(&mut self, mut invitation: OutofbandInvitation) -> VcxResult<()> {
         trace!("Connection::send_reuse >>> invitation: {:?}", secret!(invitation));
         debug!("Connection {}: Sending reuse message", self.source_id());
 
+        invitation.resolve_services()?;
+
         self.handle_message(DidExchangeMessages::SendHandshakeReuse(invitation))
     }
 
@@ -443,7 +692,17 @@ pub mod tests {
     use crate::aries::messages::connection::request::tests::_request;
     use crate::connection::Connections;
     use crate::utils::object_cache::Handle;
+    use crate::utils::devsetup::SetupAriesMocks;
+    use crate::aries::messages::outofband::invitation::Invitation as OutofbandInvitation;
+    use crate::aries::messages::connection::problem_report::tests::_problem_report;
+    use crate::aries::test::setup::AgencyModeSetup;
+    use crate::api::VcxStateType;
+    use crate::connection::ConnectionOptions;
     use crate::settings;
+    use crate::error::VcxErrorKind;
+    use crate::aries::handlers::connection::states::{ActorDidExchangeState, DidExchangeState};
+    use crate::aries::handlers::connection::types::Invitations;
+    use crate::aries::messages::timing::Timing;
 
     #[test]
     fn test_parse_generic_message_plain_string_should_be_parsed_as_basic_msg() -> Result<(), String> {
@@ -496,6 +755,152 @@ pub mod tests {
         connection_handle
     }
 
+    #[test]
+    fn test_create_with_outofband_invite_resolves_did_services() {
+        let _setup = SetupAriesMocks::init();
+
+        let invitation: OutofbandInvitation = ::serde_json::from_value(json!({
+            "@id": "testid",
+            "@type": "https://didcomm.org/out-of-band/1.0/invitation",
+            "handshake_protocols": ["https://didcomm.org/connections/1.0"],
+            "service": ["did:sov:V4SGRU86Z58d6TV7PBUe6f"]
+        })).unwrap();
+        assert!(invitation.is_public_did_invitation());
+
+        let connection = Connection::create_with_outofband_invite("source_id", invitation).unwrap();
+        assert_eq!(settings::DEFAULT_VERKEY, connection.remote_vk().unwrap());
+        assert_eq!(settings::DEFAULT_URL, connection.connection_sm.remote_endpoint().unwrap());
+    }
+
+    #[test]
+    fn test_create_with_outofband_invite_resolves_public_did() {
+        let _setup = SetupAriesMocks::init();
+
+        let invitation: OutofbandInvitation = ::serde_json::from_value(json!({
+            "@id": "testid",
+            "@type": "https://didcomm.org/out-of-band/1.1/invitation",
+            "handshake_protocols": ["https://didcomm.org/didexchange/1.0"],
+            "services": [],
+            "public_did": "V4SGRU86Z58d6TV7PBUe6f"
+        })).unwrap();
+        assert!(invitation.is_public_did_invitation());
+
+        let connection = Connection::create_with_outofband_invite("source_id", invitation).unwrap();
+        assert_eq!(settings::DEFAULT_VERKEY, connection.remote_vk().unwrap());
+        assert_eq!(settings::DEFAULT_URL, connection.connection_sm.remote_endpoint().unwrap());
+    }
+
+    #[test]
+    fn test_multi_use_invitation_spawns_connection_for_every_request() {
+        let _setup = AgencyModeSetup::init();
+
+        let mut connection = Connection::create("source_id");
+        connection.connect(ConnectionOptions { multi_use: Some(true), ..ConnectionOptions::default() }).unwrap();
+        assert!(connection.is_multi_use());
+
+        let invitation_id = connection.get_invitation().unwrap().id();
+        let request = json!(A2AMessage::ConnectionRequest(_request())).to_string();
+
+        let first = connection.accept_requests(Some(&request)).unwrap().pop().unwrap();
+        let second = connection.accept_requests(Some(&request)).unwrap().pop().unwrap();
+
+        // invitation keeps waiting for requests
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, connection.state());
+        assert!(!connection.is_spawned_by(&invitation_id));
+
+        for child in [&first, &second].iter() {
+            assert_eq!(VcxStateType::VcxStateRequestReceived as u32, child.state());
+            assert!(child.is_spawned_by(&invitation_id));
+            assert_ne!(connection.agent_info().pw_vk, child.agent_info().pw_vk);
+        }
+        assert_ne!(first.agent_info().pw_vk, second.agent_info().pw_vk);
+
+        // problem report of a single invitee does not fail the invitation
+        let problem_report = json!(A2AMessage::ConnectionProblemReport(_problem_report())).to_string();
+        assert!(connection.accept_requests(Some(&problem_report)).unwrap().is_empty());
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, connection.state());
+    }
+
+    #[test]
+    fn test_connections_spawned_by_multi_use_invitation_complete_sequentially() {
+        let _setup = AgencyModeSetup::init();
+
+        let mut connection = Connection::create("source_id");
+        connection.connect(ConnectionOptions { multi_use: Some(true), ..ConnectionOptions::default() }).unwrap();
+        let invitation_vk = connection.agent_info().pw_vk.clone();
+
+        let request = json!(A2AMessage::ConnectionRequest(_request())).to_string();
+        let ack = json!(A2AMessage::Ack(crate::aries::messages::ack::tests::_ack())).to_string();
+
+        let mut first = connection.accept_requests(Some(&request)).unwrap().pop().unwrap();
+        let mut second = connection.accept_requests(Some(&request)).unwrap().pop().unwrap();
+
+        // the agent of the invitation stays with the invitation
+        for child in [&first, &second].iter() {
+            assert!(child.connection_sm.prev_agent_info().is_none());
+            assert!(child.agent_for_recipient_key(&invitation_vk).is_none());
+        }
+
+        first.update_state(Some(&ack)).unwrap();
+        assert_eq!(VcxStateType::VcxStateAccepted as u32, first.state());
+
+        // completion of the first Connection does not affect the invitation and the second Connection
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, connection.state());
+        assert_eq!(invitation_vk, connection.agent_info().pw_vk);
+        assert_eq!(VcxStateType::VcxStateRequestReceived as u32, second.state());
+
+        second.update_state(Some(&ack)).unwrap();
+        assert_eq!(VcxStateType::VcxStateAccepted as u32, second.state());
+
+        // the invitation keeps accepting requests
+        let third = connection.accept_requests(Some(&request)).unwrap().pop().unwrap();
+        assert_eq!(VcxStateType::VcxStateRequestReceived as u32, third.state());
+    }
+
+    #[test]
+    fn test_multi_use_invitation_rejects_requests_once_expired() {
+        let _setup = AgencyModeSetup::init();
+
+        let mut connection = Connection::create("source_id");
+        connection.connect(ConnectionOptions { multi_use: Some(true), ..ConnectionOptions::default() }).unwrap();
+
+        if let ActorDidExchangeState::Inviter(DidExchangeState::Invited(ref mut state)) = connection.connection_sm.state {
+            if let Invitations::ConnectionInvitation(ref mut invitation) = state.invitation {
+                invitation.timing = Some(Timing { expires_time: Some(String::from("2018-12-13T17:29:06+00:00")) });
+            }
+        }
+
+        let request = json!(A2AMessage::ConnectionRequest(_request())).to_string();
+
+        let err = connection.accept_requests(Some(&request)).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidInviteDetail, err.kind());
+
+        // the invitation itself is failed instead of a spawned Connection
+        assert_eq!(VcxStateType::VcxStateNone as u32, connection.state());
+        assert!(!connection.is_multi_use());
+    }
+
+    #[test]
+    fn test_multi_use_invitation_fails_once_expired() {
+        let _setup = AgencyModeSetup::init();
+
+        let mut connection = Connection::create("source_id");
+        connection.connect(ConnectionOptions { multi_use: Some(true), ..ConnectionOptions::default() }).unwrap();
+
+        connection.check_invitation_expiry();
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, connection.state());
+
+        if let ActorDidExchangeState::Inviter(DidExchangeState::Invited(ref mut state)) = connection.connection_sm.state {
+            if let Invitations::ConnectionInvitation(ref mut invitation) = state.invitation {
+                invitation.timing = Some(Timing { expires_time: Some(String::from("2018-12-13T17:29:06+00:00")) });
+            }
+        }
+
+        connection.check_invitation_expiry();
+        assert_eq!(VcxStateType::VcxStateNone as u32, connection.state());
+        assert!(!connection.is_multi_use());
+    }
+
     fn _setup() {
         settings::set_config_value(settings::CONFIG_PROTOCOL_TYPE, "3.0");
     }
@@ -1,4 +1,5 @@
 use core::fmt::Debug;
+use chrono::Utc;
 use crate::api::VcxStateType;
 
 use crate::aries::handlers::connection::agent::AgentInfo;
@@ -12,10 +13,13 @@ use crate::aries::messages::ack::Ack;
 use crate::aries::messages::connection::did_doc::DidDoc;
 use crate::aries::messages::discovery::disclose::ProtocolDescriptor;
 use crate::aries::messages::outofband::invitation::Invitation as OutofbandInvitation;
+use crate::aries::messages::didexchange::request::Request as DidExchangeRequest;
+use crate::aries::messages::didexchange::response::Response as DidExchangeResponse;
+use crate::aries::messages::didexchange::complete::Complete;
 
 use crate::aries::messages::thread::Thread;
 use serde::Serialize;
-use crate::aries::handlers::connection::types::{OutofbandMeta, Invitations};
+use crate::aries::handlers::connection::types::{OutofbandMeta, Invitations, HandshakeRequest, HandshakeResponse};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActorDidExchangeState {
@@ -68,27 +72,43 @@ pub struct InitializedState {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvitedState {
     pub invitation: Invitations,
+    /// Inviter side of multi-use invitation: every received request is accepted by a new Connection
+    #[serde(default)]
+    pub multi_use: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<HandshakeTimeout>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequestedState {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invitation: Option<Invitations>,
-    pub request: Request,
+    pub request: HandshakeRequest,
     pub did_doc: DidDoc,
     #[serde(default)]
     pub thread: Thread,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<HandshakeTimeout>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RespondedState {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invitation: Option<Invitations>,
-    pub response: SignedResponse,
+    pub response: HandshakeResponse,
     pub did_doc: DidDoc,
     pub prev_agent_info: AgentInfo,
+    /// The previous agent is the one of multi-use invitation which keeps accepting requests,
+    /// so it is neither polled nor deleted by this Connection.
+    #[serde(default)]
+    pub shared_prev_agent: bool,
     #[serde(default)]
     pub thread: Thread,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<HandshakeTimeout>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -99,6 +119,62 @@ pub struct CompleteState {
     pub protocols: Option<Vec<ProtocolDescriptor>>,
     #[serde(default)]
     pub thread: Thread,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_rotation: Option<DidRotation>,
+    /// Pairwise agents of our rotated DIDs.
+    /// They are kept until the protocols started over the Connection before the rotation are finished.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub retired_agents: Vec<AgentInfo>,
+}
+
+/// Pending rotation of our pairwise DID.
+/// Previous agent is kept until the other side acknowledges the new DID.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DidRotation {
+    pub rotate_id: String,
+    pub prev_agent_info: AgentInfo,
+}
+
+/// Limit of the time the Connection can stay in every handshake state (`Invited`, `Requested`, `Responded`).
+/// The Connection fails once the limit is exceeded (see `handshake_timeout` Connection option).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct HandshakeTimeout {
+    /// seconds the Connection can stay in a handshake state
+    pub timeout: u64,
+    /// Unix timestamp the current state times out at
+    pub expires_at: i64,
+}
+
+impl HandshakeTimeout {
+    pub fn new(timeout: u64) -> HandshakeTimeout {
+        HandshakeTimeout {
+            timeout,
+            expires_at: Utc::now().timestamp() + timeout as i64,
+        }
+    }
+
+    /// Starts counting the timeout for the next handshake state.
+    pub fn restart(self) -> HandshakeTimeout {
+        HandshakeTimeout::new(self.timeout)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Utc::now().timestamp() > self.expires_at
+    }
+}
+
+impl InvitedState {
+    pub fn set_multi_use(mut self, multi_use: bool) -> InvitedState {
+        self.multi_use = multi_use;
+        self
+    }
+
+    pub fn set_timeout(mut self, timeout: Option<u64>) -> InvitedState {
+        self.timeout = timeout.map(HandshakeTimeout::new);
+        self
+    }
 }
 
 impl CompleteState {
@@ -123,14 +199,14 @@ pub struct FailedState {
 impl From<(InitializedState, Invitation)> for InvitedState {
     fn from((_state, invitation): (InitializedState, Invitation)) -> InvitedState {
         trace!("DidExchangeStateSM: transit state from InitializedState to InvitedState with ConnectionInvitation");
-        InvitedState { invitation: Invitations::ConnectionInvitation(invitation) }
+        InvitedState { invitation: Invitations::ConnectionInvitation(invitation), multi_use: false, timeout: None }
     }
 }
 
 impl From<(InitializedState, OutofbandInvitation)> for InvitedState {
     fn from((_state, invitation): (InitializedState, OutofbandInvitation)) -> InvitedState {
         trace!("DidExchangeStateSM: transit state from InitializedState to InvitedState with OutofbandInvitation");
-        InvitedState { invitation: Invitations::OutofbandInvitation(invitation) }
+        InvitedState { invitation: Invitations::OutofbandInvitation(invitation), multi_use: false, timeout: None }
     }
 }
 
@@ -145,6 +221,8 @@ impl From<(InitializedState, OutofbandInvitation)> for CompleteState {
             protocols: None,
             thread,
             invitation: Some(Invitations::OutofbandInvitation(invitation)),
+            did_rotation: None,
+            retired_agents: Vec::new(),
         }
     }
 }
@@ -167,9 +245,24 @@ impl From<(InvitedState, Request, Thread)> for RequestedState {
         trace!("Thread: {:?}", thread);
         RequestedState {
             invitation: Some(state.invitation.clone()),
-            request,
+            request: HandshakeRequest::Connections(request),
             did_doc: DidDoc::from(state.invitation),
             thread,
+            timeout: state.timeout.map(HandshakeTimeout::restart),
+        }
+    }
+}
+
+impl From<(InvitedState, DidExchangeRequest, Thread)> for RequestedState {
+    fn from((state, request, thread): (InvitedState, DidExchangeRequest, Thread)) -> RequestedState {
+        trace!("DidExchangeStateSM: transit state from InvitedState to RequestedState with DID Exchange Request");
+        trace!("Thread: {:?}", thread);
+        RequestedState {
+            invitation: Some(state.invitation.clone()),
+            request: HandshakeRequest::DidExchange(request),
+            did_doc: DidDoc::from(state.invitation),
+            thread,
+            timeout: state.timeout.map(HandshakeTimeout::restart),
         }
     }
 }
@@ -180,10 +273,28 @@ impl From<(InvitedState, Request, SignedResponse, AgentInfo, Thread)> for Respon
         trace!("Thread: {:?}", thread);
         RespondedState {
             invitation: Some(state.invitation),
-            response,
+            response: HandshakeResponse::Connections(response),
             did_doc: request.connection.did_doc,
             prev_agent_info,
+            shared_prev_agent: state.multi_use,
             thread,
+            timeout: state.timeout.map(HandshakeTimeout::restart),
+        }
+    }
+}
+
+impl From<(InvitedState, DidDoc, DidExchangeResponse, AgentInfo, Thread)> for RespondedState {
+    fn from((state, did_doc, response, prev_agent_info, thread): (InvitedState, DidDoc, DidExchangeResponse, AgentInfo, Thread)) -> RespondedState {
+        trace!("DidExchangeStateSM: transit state from InvitedState to RespondedState with DID Exchange Response");
+        trace!("Thread: {:?}", thread);
+        RespondedState {
+            invitation: Some(state.invitation),
+            response: HandshakeResponse::DidExchange(response),
+            did_doc,
+            prev_agent_info,
+            shared_prev_agent: state.multi_use,
+            thread,
+            timeout: state.timeout.map(HandshakeTimeout::restart),
         }
     }
 }
@@ -196,9 +307,10 @@ impl From<(RespondedState, Ping, Thread)> for RespondedState {
             invitation: state.invitation,
             response: state.response,
             did_doc: state.did_doc,
-            prev_agent_info:
-            state.prev_agent_info,
+            prev_agent_info: state.prev_agent_info,
+            shared_prev_agent: state.shared_prev_agent,
             thread,
+            timeout: state.timeout,
         }
     }
 }
@@ -229,6 +341,23 @@ impl From<(RequestedState, Response, Thread)> for CompleteState {
                 received_orders: thread.received_orders
             },
             invitation: state.invitation,
+            did_rotation: None,
+            retired_agents: Vec::new(),
+        }
+    }
+}
+
+impl From<(RequestedState, DidDoc, Thread)> for CompleteState {
+    fn from((state, did_doc, thread): (RequestedState, DidDoc, Thread)) -> CompleteState {
+        trace!("DidExchangeStateSM: transit state from RequestedState to CompleteState with DID Exchange Response");
+        trace!("Thread: {:?}", thread);
+        CompleteState {
+            did_doc,
+            protocols: None,
+            thread,
+            invitation: state.invitation,
+            did_rotation: None,
+            retired_agents: Vec::new(),
         }
     }
 }
@@ -254,6 +383,23 @@ impl From<(RespondedState, Ack, Thread)> for CompleteState {
             protocols: None,
             thread,
             invitation: state.invitation,
+            did_rotation: None,
+            retired_agents: Vec::new(),
+        }
+    }
+}
+
+impl From<(RespondedState, Complete, Thread)> for CompleteState {
+    fn from((state, _complete, thread): (RespondedState, Complete, Thread)) -> CompleteState {
+        trace!("DidExchangeStateSM: transit state from RespondedState to CompleteState with DID Exchange Complete");
+        trace!("Thread: {:?}", thread);
+        CompleteState {
+            did_doc: state.did_doc,
+            protocols: None,
+            thread,
+            invitation: state.invitation,
+            did_rotation: None,
+            retired_agents: Vec::new(),
         }
     }
 }
@@ -267,6 +413,8 @@ impl From<(RespondedState, Ping, Thread)> for CompleteState {
             protocols: None,
             thread,
             invitation: state.invitation,
+            did_rotation: None,
+            retired_agents: Vec::new(),
         }
     }
 }
@@ -280,6 +428,8 @@ impl From<(RespondedState, PingResponse, Thread)> for CompleteState {
             protocols: None,
             thread,
             invitation: state.invitation,
+            did_rotation: None,
+            retired_agents: Vec::new(),
         }
     }
 }
@@ -292,6 +442,8 @@ impl From<(CompleteState, Vec<ProtocolDescriptor>)> for CompleteState {
             protocols: Some(protocols),
             thread: state.thread,
             invitation: state.invitation,
+            did_rotation: state.did_rotation,
+            retired_agents: state.retired_agents,
         }
     }
 }
\ No newline at end of file
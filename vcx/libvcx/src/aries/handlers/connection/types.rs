@@ -2,9 +2,15 @@ use crate::aries::messages::discovery::disclose::ProtocolDescriptor;
 use crate::aries::handlers::connection::agent::AgentInfo;
 use crate::aries::handlers::connection::states::CompleteState;
 use crate::aries::messages::connection::invite::Invitation;
+use crate::aries::messages::connection::request::Request;
+use crate::aries::messages::connection::response::SignedResponse;
+use crate::aries::messages::didexchange::request::Request as DidExchangeRequest;
+use crate::aries::messages::didexchange::response::Response as DidExchangeResponse;
+use crate::aries::messages::outofband::HandshakeProtocol;
 use crate::aries::messages::outofband::invitation::Invitation as OutofbandInvitation;
 use crate::aries::messages::connection::did_doc::DidDoc;
 use crate::error::VcxResult;
+use crate::connection;
 use crate::aries::messages::connection::service::Service;
 
 /*
@@ -45,6 +51,44 @@ impl CompletedConnection {
         self.data.without_handshake()
     }
 
+    /// Pairwise agents the messages of the protocols started over the Connection can be addressed to:
+    /// the current one and the ones of rotated DIDs which are not deleted yet.
+    pub fn agents(&self) -> Vec<&AgentInfo> {
+        Some(&self.agent)
+            .into_iter()
+            .chain(self.data.did_rotation.as_ref().map(|did_rotation| &did_rotation.prev_agent_info))
+            .chain(self.data.retired_agents.iter())
+            .collect()
+    }
+
+    /// Whether a message sent to `recipient_vk` belongs to the Connection.
+    /// The key of the Connection could be rotated after this copy was taken, so the loaded Connection is checked as well.
+    pub fn is_addressed_to(&self, recipient_vk: &str) -> bool {
+        if self.agents().iter().any(|agent_info| agent_info.pw_vk == recipient_vk) {
+            return true;
+        }
+
+        match connection::connection_id_by_recipient_key(recipient_vk) {
+            Ok(Some(connection_id)) => connection_id == self.agent.connection_id(),
+            _ => false
+        }
+    }
+
+    /// Replaces this copy with the current state of the Connection if it is loaded,
+    /// so protocols started over the Connection go on after the DID rotation or DIDDoc update.
+    pub fn refresh(&mut self) {
+        match connection::completed_connection_by_id(self.agent.connection_id()) {
+            Ok(Some(connection)) => {
+                if connection != *self {
+                    debug!("Connection {} has been changed since the protocol started", self.agent.connection_id());
+                    *self = connection;
+                }
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Unable to refresh the Connection state. Err: {:?}", err)
+        }
+    }
+
     pub fn service(&self) -> VcxResult<Option<Service>> {
         if self.without_handshake() && !self.agent.pw_did.is_empty() {
             Ok(Some(Service::create()
@@ -144,10 +188,69 @@ impl Invitations {
         }
     }
 
+    /// Whether the invitation refers to the public DID of the inviter instead of embedding its keys.
+    pub fn is_public_did_invitation(&self) -> bool {
+        match self {
+            Invitations::ConnectionInvitation(_)=> false,
+            Invitations::OutofbandInvitation(invitation_)=> invitation_.is_public_did_invitation(),
+        }
+    }
+
+    /// Handshake protocol to use for accepting the invitation.
+    /// Out-of-Band Invitation: the first supported protocol, `connections` for others.
+    pub fn handshake_protocol(&self) -> HandshakeProtocol {
+        match self {
+            Invitations::ConnectionInvitation(_)=> HandshakeProtocol::Connections,
+            Invitations::OutofbandInvitation(invitation_)=>
+                invitation_.handshake_protocol().unwrap_or(HandshakeProtocol::Connections),
+        }
+    }
+
+    /// Whether `~timing.expires_time` of the invitation has passed.
+    pub fn is_expired(&self) -> bool {
+        match self {
+            Invitations::ConnectionInvitation(invitation_)=> invitation_.is_expired(),
+            Invitations::OutofbandInvitation(invitation_)=> invitation_.is_expired(),
+        }
+    }
+
+    pub fn goal_code(&self) -> Option<&str>{
+        match self {
+            Invitations::ConnectionInvitation(_)=> None,
+            Invitations::OutofbandInvitation(invitation_)=> invitation_.goal_code(),
+        }
+    }
+
     pub fn name(&self) -> Option<&str>{
         match self {
             Invitations::ConnectionInvitation(invitation_)=> Some(invitation_.label.as_str()),
             Invitations::OutofbandInvitation(invitation_)=> invitation_.label(),
         }
     }
-}
\ No newline at end of file
+}
+
+/*
+    Connection can be established with either `connections` or `didexchange` protocols
+*/
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HandshakeRequest {
+    Connections(Request),
+    DidExchange(DidExchangeRequest),
+}
+
+impl HandshakeRequest {
+    pub fn id(&self) -> &str {
+        match self {
+            HandshakeRequest::Connections(request) => request.id.value(),
+            HandshakeRequest::DidExchange(request) => request.id.value(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HandshakeResponse {
+    Connections(SignedResponse),
+    DidExchange(DidExchangeResponse),
+}
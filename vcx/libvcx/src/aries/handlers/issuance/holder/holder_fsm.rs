@@ -19,12 +19,14 @@ use crate::aries::messages::{
     issuance::{
         credential::Credential,
         credential_offer::CredentialOffer,
+        credential_proposal::CredentialProposal,
         credential_request::CredentialRequest,
         credential_ack::CredentialAck,
         v10::credential_request::CredentialRequest as CredentialRequestV1,
         v20::credential_request::CredentialRequest as CredentialRequestV2,
     },
 };
+use crate::aries::utils::revoked_credentials;
 use crate::error::prelude::*;
 use crate::utils::object_cache::Handle;
 use crate::connection::Connections;
@@ -48,12 +50,21 @@ impl HolderSM {
         }
     }
 
+    pub fn new_proposal(proposal: CredentialProposal, source_id: String) -> Self {
+        HolderSM {
+            state: HolderState::ProposalPrepared(ProposalPreparedState::new(proposal)),
+            source_id,
+        }
+    }
+
     pub fn get_source_id(&self) -> &String {
         &self.source_id
     }
 
     pub fn state(&self) -> u32 {
         match self.state {
+            HolderState::ProposalPrepared(_) => VcxStateType::VcxStateInitialized as u32,
+            HolderState::ProposalSent(_) => VcxStateType::VcxStateOfferSent as u32,
             HolderState::OfferReceived(_) => VcxStateType::VcxStateRequestReceived as u32,
             HolderState::RequestSent(_) => VcxStateType::VcxStateOfferSent as u32,
             HolderState::Finished(ref status) => {
@@ -66,37 +77,63 @@ impl HolderSM {
         }
     }
 
-    pub fn update_state(self) -> VcxResult<Self> {
+    pub fn update_state(mut self) -> VcxResult<Self> {
         trace!("Holder::update_state >>> ");
 
         if self.is_terminal_state() { return Ok(self); }
 
-        let agent = match self.get_agent_info() {
-            Some(agent_info) => agent_info.clone(),
+        self.refresh_connection();
+
+        let connection = match self.get_connection() {
+            Some(connection) => connection.clone(),
             None => {
                 warn!("Could not update Holder state: no information about Connection.");
                 return Ok(self);
             }
         };
 
-        let messages = agent.get_messages()?;
+        // messages sent before the DID rotation are delivered to the agent of the rotated DID
+        for agent in connection.agents() {
+            let messages = agent.get_messages()?;
 
-        match self.find_message_to_handle(messages) {
-            Some((uid, msg)) => {
-                let state = self.handle_message(msg.into())?;
-                agent.update_message_status(uid, None)?;
-                Ok(state)
+            if let Some((uid, msg)) = self.find_message_to_handle(messages) {
+                let state = agent.handle_message_once(uid, None, &msg, || self.clone().handle_message(msg.clone().into()))?;
+                return Ok(state.unwrap_or(self));
             }
-            None => Ok(self)
         }
+
+        Ok(self)
     }
 
-    fn find_message_to_handle(&self, messages: HashMap<String, A2AMessage>) -> Option<(String, A2AMessage)> {
+    pub fn find_message_to_handle(&self, messages: HashMap<String, A2AMessage>) -> Option<(String, A2AMessage)> {
         trace!("Holder::find_message_to_handle >>> agent: {:?}", secret!(messages));
         debug!("Holder: Finding message to update state");
 
         for (uid, message) in messages {
             match self.state {
+                HolderState::ProposalPrepared(_) => {
+                    // do not process agent
+                }
+                HolderState::ProposalSent(ref state) => {
+                    match message {
+                        A2AMessage::CredentialOffer(offer) => {
+                            if offer.thread().map_or(false, |thread| thread.is_reply(state.thread.thid.as_deref().unwrap_or_default())) {
+                                debug!("Holder: CredentialOffer message received");
+                                return Some((uid, A2AMessage::CredentialOffer(offer)));
+                            }
+                        }
+                        A2AMessage::CommonProblemReport(problem_report) |
+                        A2AMessage::CredentialReject(problem_report) => {
+                            if problem_report.from_thread(state.thread.thid.as_deref().unwrap_or_default()) {
+                                debug!("Holder: CredentialReject message received");
+                                return Some((uid, A2AMessage::CommonProblemReport(problem_report)));
+                            }
+                        }
+                        message => {
+                            warn!("Holder: Unexpected message received in ProposalSent state: {:?}", message);
+                        }
+                    }
+                }
                 HolderState::OfferReceived(_) => {
                     // do not process agent
                 }
@@ -133,12 +170,39 @@ impl HolderSM {
         HolderSM { state, source_id }
     }
 
-    pub fn handle_message(self, cim: HolderMessages) -> VcxResult<HolderSM> {
+    pub fn handle_message(mut self, cim: HolderMessages) -> VcxResult<HolderSM> {
         trace!("Holder::handle_message >>> cim: {:?}", secret!(cim));
         debug!("Holder: Updating state");
 
+        self.refresh_connection();
+
         let HolderSM { state, source_id } = self;
         let state = match state {
+            HolderState::ProposalPrepared(state_data) => match cim {
+                HolderMessages::CredentialProposalSend(connection_handle) => {
+                    state_data.send_credential_proposal(connection_handle)?
+                }
+                _ => {
+                    warn!("In this state Credential Issuance can accept only CredentialProposalSend");
+                    HolderState::ProposalPrepared(state_data)
+                }
+            },
+            HolderState::ProposalSent(state_data) => match cim {
+                HolderMessages::CredentialOffer(offer) => {
+                    let thread = state_data.thread.clone();
+                    // we do not update received order here, because it will be updated before sending the credential request.
+                    HolderState::OfferReceived((state_data, offer, thread).into())
+                }
+                HolderMessages::ProblemReport(problem_report) => {
+                    let thread = problem_report.thread.clone()
+                        .update_received_order(&state_data.connection.data.did_doc.id);
+                    HolderState::Finished((state_data, problem_report, thread, Reason::Reject).into())
+                }
+                _ => {
+                    warn!("In this state Credential Issuance can accept only Credential Offer and Problem Report");
+                    HolderState::ProposalSent(state_data)
+                }
+            },
             HolderState::OfferReceived(state_data) => match cim {
                 HolderMessages::CredentialRequestSend(connection_handle) => {
                     state_data.send_credential_request(connection_handle)?
@@ -185,8 +249,22 @@ impl HolderSM {
         }
     }
 
+    /// Whether a message received from the remote side can move the Holder to another state.
+    pub fn has_transitions(&self) -> bool {
+        match self.state {
+            HolderState::ProposalPrepared(_) => false,
+            HolderState::ProposalSent(_) => true,
+            HolderState::OfferReceived(_) => false,
+            HolderState::RequestSent(_) => true,
+            HolderState::Finished(_) => false,
+        }
+    }
+
     pub fn get_credential_offer(&self) -> VcxResult<CredentialOffer> {
         match self.state {
+            HolderState::ProposalPrepared(_) |
+            HolderState::ProposalSent(_) => Err(VcxError::from_msg(VcxErrorKind::NotReady,
+                                                                   format!("Holder object {} in state {} not ready to get Credential Offer message", self.source_id, self.state()))),
             HolderState::OfferReceived(ref state) => Ok(state.offer.clone()),
             HolderState::RequestSent(ref state) => state.offer.clone().ok_or(
                 VcxError::from_msg(VcxErrorKind::InvalidState, format!("Invalid {} Holder object state: `offer` not found", self.source_id))),
@@ -223,16 +301,46 @@ impl HolderSM {
         }
     }
 
+    pub fn get_connection(&self) -> Option<&CompletedConnection> {
+        match self.state {
+            HolderState::ProposalSent(ref state) => Some(&state.connection),
+            HolderState::RequestSent(ref state) => Some(&state.connection),
+            _ => None,
+        }
+    }
+
+    /// Takes the current state of the Connection the protocol was started over (see `CompletedConnection::refresh`).
+    pub fn refresh_connection(&mut self) {
+        match self.state {
+            HolderState::ProposalSent(ref mut state) => state.connection.refresh(),
+            HolderState::RequestSent(ref mut state) => state.connection.refresh(),
+            _ => {}
+        }
+    }
+
     pub fn get_agent_info(&self) -> Option<&AgentInfo> {
         match self.state {
+            HolderState::ProposalSent(ref state) => Some(&state.connection.agent),
             HolderState::RequestSent(ref state) => Some(&state.connection.agent),
+            HolderState::ProposalPrepared(_) => None,
             HolderState::OfferReceived(_) => None,
             HolderState::Finished(_) => None,
         }
     }
 
+    pub fn get_credential_proposal(&self) -> VcxResult<CredentialProposal> {
+        match self.state {
+            HolderState::ProposalPrepared(ref state) => Ok(state.proposal.clone()),
+            HolderState::ProposalSent(ref state) => Ok(state.proposal.clone()),
+            _ => Err(VcxError::from_msg(VcxErrorKind::NotReady,
+                                        format!("Holder object {} in state {} does not have Credential Proposal message", self.source_id, self.state())))
+        }
+    }
+
     pub fn problem_report(&self) -> Option<&ProblemReport> {
         match self.state {
+            HolderState::ProposalPrepared(_) |
+            HolderState::ProposalSent(_) |
             HolderState::OfferReceived(_) |
             HolderState::RequestSent(_) => None,
             HolderState::Finished(ref status) => {
@@ -247,6 +355,8 @@ impl HolderSM {
 
     pub fn get_info(&self) -> VcxResult<CredentialInfo> {
         match self.state {
+            HolderState::ProposalPrepared(_) |
+            HolderState::ProposalSent(_) |
             HolderState::OfferReceived(_) |
             HolderState::RequestSent(_) => {
                 Err(VcxError::from_msg(VcxErrorKind::NotReady,
@@ -341,10 +451,17 @@ impl RequestSentState {
             .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState,
                                       format!("Invalid Holder object state: `offer` not found")))?;
 
-        _store_credential(&credential_offer,
-                          &credential,
-                          &self.req_meta,
-                          &self.cred_def_json)
+        let cred_id = _store_credential(&credential_offer,
+                                        &credential,
+                                        &self.req_meta,
+                                        &self.cred_def_json)?;
+
+        // revocation notifications are accepted over the connection the credential was issued over only
+        if let Err(err) = revoked_credentials::store_issuer_connection(&cred_id, self.connection.agent.connection_id()) {
+            warn!("Cannot store connection the credential {} was issued over. Err: {}", cred_id, err);
+        }
+
+        Ok(cred_id)
     }
 }
 
@@ -478,10 +595,25 @@ impl OfferReceivedState {
     }
 }
 
+impl ProposalPreparedState {
+    fn send_credential_proposal(self, connection_handle: Handle<Connections>) -> VcxResult<HolderState> {
+        trace!("Holder::ProposalPreparedState::send_credential_proposal >>> proposal: {:?}", secret!(self.proposal));
+
+        let connection = connection_handle.get_completed_connection()?;
+
+        let thread = self.thread.clone()
+            .set_opt_pthid(connection.data.thread.pthid.clone());
+
+        connection.data.send_message(&self.proposal, &connection.agent)?;
+        Ok(HolderState::ProposalSent((self, connection, thread).into()))
+    }
+}
+
 impl FinishedHolderState {
     fn delete_credential(&self, cred_id: &str) -> VcxResult<()> {
         trace!("Holder::_delete_credential >>> cred_id: {}", cred_id);
-        IndyHolder::delete_credential(cred_id)
+        IndyHolder::delete_credential(cred_id)?;
+        revoked_credentials::delete_issuer_connection(cred_id)
     }
 }
 
@@ -509,6 +641,14 @@ mod test {
         HolderSM::new(_credential_offer(), source_id())
     }
 
+    fn _holder_sm_from_proposal() -> HolderSM {
+        HolderSM::new_proposal(_credential_proposal(), source_id())
+    }
+
+    fn _credential_offer_for_proposal() -> CredentialOffer {
+        _credential_offer().set_thread_id(&_credential_proposal().thread().unwrap().thid.clone().unwrap())
+    }
+
     impl HolderSM {
         fn to_request_sent_state(mut self) -> HolderSM {
             self = self.handle_message(HolderMessages::CredentialRequestSend(mock_connection())).unwrap();
@@ -858,4 +998,104 @@ mod test {
             assert_eq!(VcxStateType::VcxStateAccepted as u32, _holder_sm().to_finished_state().state());
         }
     }
+
+    mod proposal {
+        use super::*;
+
+        #[test]
+        fn test_holder_new_proposal() {
+            let _setup = SetupAriesMocks::init();
+
+            let holder_sm = _holder_sm_from_proposal();
+
+            assert_match!(HolderState::ProposalPrepared(_), holder_sm.state);
+            assert_eq!(VcxStateType::VcxStateInitialized as u32, holder_sm.state());
+            assert_eq!(_credential_proposal(), holder_sm.get_credential_proposal().unwrap());
+            holder_sm.get_credential_offer().unwrap_err();
+        }
+
+        #[test]
+        fn test_holder_handle_credential_proposal_send_message_from_proposal_prepared_state() {
+            let _setup = SetupAriesMocks::init();
+
+            let mut holder_sm = _holder_sm_from_proposal();
+
+            holder_sm = holder_sm.handle_message(HolderMessages::Credential(_credential())).unwrap();
+            assert_match!(HolderState::ProposalPrepared(_), holder_sm.state);
+
+            holder_sm = holder_sm.handle_message(HolderMessages::CredentialProposalSend(mock_connection())).unwrap();
+            assert_match!(HolderState::ProposalSent(_), holder_sm.state);
+            assert_eq!(VcxStateType::VcxStateOfferSent as u32, holder_sm.state());
+        }
+
+        #[test]
+        fn test_holder_handle_credential_offer_message_from_proposal_sent_state() {
+            let _setup = SetupAriesMocks::init();
+
+            let mut holder_sm = _holder_sm_from_proposal();
+            holder_sm = holder_sm.handle_message(HolderMessages::CredentialProposalSend(mock_connection())).unwrap();
+            holder_sm = holder_sm.handle_message(HolderMessages::CredentialOffer(_credential_offer_for_proposal())).unwrap();
+
+            assert_match!(HolderState::OfferReceived(_), holder_sm.state);
+            assert_eq!(_credential_offer_for_proposal(), holder_sm.get_credential_offer().unwrap());
+
+            holder_sm = holder_sm.handle_message(HolderMessages::CredentialRequestSend(mock_connection())).unwrap();
+            assert_match!(HolderState::RequestSent(_), holder_sm.state);
+        }
+
+        #[test]
+        fn test_holder_handle_problem_report_from_proposal_sent_state() {
+            let _setup = SetupAriesMocks::init();
+
+            let mut holder_sm = _holder_sm_from_proposal();
+            holder_sm = holder_sm.handle_message(HolderMessages::CredentialProposalSend(mock_connection())).unwrap();
+            holder_sm = holder_sm.handle_message(HolderMessages::ProblemReport(_problem_report())).unwrap();
+
+            assert_match!(HolderState::Finished(_), holder_sm.state);
+            assert_eq!(VcxStateType::VcxStateRejected as u32, holder_sm.state());
+        }
+
+        #[test]
+        fn test_holder_find_message_to_handle_from_proposal_sent_state() {
+            let _setup = SetupAriesMocks::init();
+
+            let holder = _holder_sm_from_proposal();
+            assert!(holder.find_message_to_handle(map!("key_1".to_string() => A2AMessage::CredentialOffer(_credential_offer_for_proposal()))).is_none());
+
+            let holder = holder.handle_message(HolderMessages::CredentialProposalSend(mock_connection())).unwrap();
+
+            // Credential Offer
+            {
+                let messages = map!(
+                    "key_1".to_string() => A2AMessage::CredentialOffer(_credential_offer_for_proposal()),
+                    "key_2".to_string() => A2AMessage::Credential(_credential())
+                );
+
+                let (uid, message) = holder.find_message_to_handle(messages).unwrap();
+                assert_eq!("key_1", uid);
+                assert_match!(A2AMessage::CredentialOffer(_), message);
+            }
+
+            // Problem Report
+            {
+                let messages = map!(
+                    "key_1".to_string() => A2AMessage::CredentialReject(_problem_report())
+                );
+
+                let (uid, message) = holder.find_message_to_handle(messages).unwrap();
+                assert_eq!("key_1", uid);
+                assert_match!(A2AMessage::CommonProblemReport(_), message);
+            }
+
+            // No agent for different Thread ID
+            {
+                let messages = map!(
+                    "key_1".to_string() => A2AMessage::CredentialOffer(_credential_offer().set_thread_id("other")),
+                    "key_2".to_string() => A2AMessage::CommonProblemReport(_problem_report().set_thread_id("other"))
+                );
+
+                assert!(holder.find_message_to_handle(messages).is_none());
+            }
+        }
+    }
 }
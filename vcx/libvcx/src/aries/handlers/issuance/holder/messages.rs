@@ -14,6 +14,7 @@ use crate::utils::object_cache::Handle;
 #[derive(Debug, Clone)]
 pub enum HolderMessages {
     CredentialProposal(CredentialProposal),
+    CredentialProposalSend(Handle<Connections>),
     CredentialOffer(CredentialOffer),
     CredentialRequestSend(Handle<Connections>),
     Credential(Credential),
@@ -12,6 +12,7 @@ use crate::aries::messages::{
     issuance::{
         credential::Credential,
         credential_offer::CredentialOffer,
+        credential_proposal::CredentialProposal,
     },
     proof_presentation::{
         presentation_preview::PresentationPreview,
@@ -20,6 +21,7 @@ use crate::aries::messages::{
     },
     error::ProblemReport,
 };
+use crate::aries::utils::revoked_credentials;
 use crate::connection::Connections;
 use crate::utils::object_cache::Handle;
 use crate::utils::libindy::{
@@ -35,15 +37,32 @@ pub struct Holder {
 }
 
 impl Holder {
-    pub fn create(credential_offer: CredentialOffer, source_id: &str) -> VcxResult<Holder> {
+    pub fn create(mut credential_offer: CredentialOffer, source_id: &str) -> VcxResult<Holder> {
         trace!("Holder::holder_create_credential >>> credential_offer: {:?}, source_id: {:?}", credential_offer, source_id);
         debug!("Holder {}: Creating credential Holder state object", source_id);
 
+        credential_offer.resolve_services()?;
+
         let holder_sm = HolderSM::new(credential_offer, source_id.to_string());
 
         Ok(Holder { holder_sm })
     }
 
+    pub fn create_proposal(source_id: &str, proposal: CredentialProposal) -> VcxResult<Holder> {
+        trace!("Holder::create_proposal >>> source_id: {:?}, proposal: {:?}", source_id, secret!(proposal));
+        debug!("Holder {}: Creating credential Holder state object from proposal", source_id);
+
+        let holder_sm = HolderSM::new_proposal(proposal, source_id.to_string());
+
+        Ok(Holder { holder_sm })
+    }
+
+    pub fn send_proposal(&mut self, connection_handle: Handle<Connections>) -> VcxResult<()> {
+        trace!("Holder::send_proposal >>>");
+        debug!("Holder {}: Sending credential proposal", self.get_source_id());
+        self.step(HolderMessages::CredentialProposalSend(connection_handle))
+    }
+
     pub fn send_request(&mut self, connection_handle: Handle<Connections>) -> VcxResult<()> {
         trace!("Holder::send_request >>>");
         debug!("Holder {}: Sending credential request", self.get_source_id());
@@ -93,6 +112,12 @@ impl Holder {
         self.holder_sm.get_credential_offer()
     }
 
+    pub fn get_credential_proposal(&self) -> VcxResult<CredentialProposal> {
+        trace!("Holder::get_credential_proposal >>>");
+        debug!("Holder {}: Getting credential proposal", self.get_source_id());
+        self.holder_sm.get_credential_proposal()
+    }
+
     pub fn get_credential(&self) -> VcxResult<(String, Credential)> {
         trace!("Holder::get_credential >>>");
         debug!("Holder {}: Getting credential", self.get_source_id());
@@ -136,7 +161,40 @@ impl Holder {
         debug!("Holder {}: Getting credential info", self.get_source_id());
 
         let info: CredentialInfo = self.holder_sm.get_info()?;
-        Ok(json!(&info).to_string())
+        revoked_credentials::credential_info_json(&info)
+    }
+
+    /// Whether the Holder still expects messages over the connection identified by `connection_id`.
+    pub fn is_in_progress_over(&self, connection_id: &str) -> bool {
+        self.holder_sm.has_transitions() &&
+            self.holder_sm.get_agent_info().map_or(false, |agent_info| agent_info.connection_id() == connection_id)
+    }
+
+    /// Whether a message received through the inbound endpoint belongs to this Holder and can be handled in its current state.
+    pub fn accepts_inbound_message(&self, recipient_vk: &str, message: &A2AMessage) -> bool {
+        self.holder_sm.has_transitions() &&
+            self.holder_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) &&
+            self.holder_sm.find_message_to_handle(map!(String::new() => message.clone())).is_some()
+    }
+
+    /// Handles a message received through the inbound endpoint.
+    /// Returns `false` if the message does not belong to this Holder.
+    pub fn handle_inbound_message(&mut self, recipient_vk: &str, message: &A2AMessage) -> VcxResult<bool> {
+        trace!("Holder::handle_inbound_message >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+        if !self.holder_sm.has_transitions() ||
+            !self.holder_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) {
+            return Ok(false);
+        }
+
+        match self.holder_sm.find_message_to_handle(map!(String::new() => message.clone())) {
+            Some((_, message)) => {
+                debug!("Holder {}: Handling inbound message", self.get_source_id());
+                self.step(message.into())?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
     }
 
     pub fn step(&mut self, message: HolderMessages) -> VcxResult<()> {
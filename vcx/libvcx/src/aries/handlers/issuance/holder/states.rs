@@ -1,4 +1,5 @@
 use crate::aries::messages::{
+    issuance::credential_proposal::CredentialProposal,
     issuance::credential_offer::CredentialOffer,
     issuance::credential::Credential,
     status::Status,
@@ -7,8 +8,15 @@ use crate::aries::messages::{
 use crate::aries::handlers::connection::types::CompletedConnection;
 use crate::aries::messages::thread::Thread;
 
+// Possible Transitions:
+// ProposalPrepared -> ProposalSent
+// ProposalSent -> OfferReceived, Finished
+// OfferReceived -> RequestSent, Finished
+// RequestSent -> Finished
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum HolderState {
+    ProposalPrepared(ProposalPreparedState),
+    ProposalSent(ProposalSentState),
     OfferReceived(OfferReceivedState),
     RequestSent(RequestSentState),
     Finished(FinishedHolderState),
@@ -43,6 +51,33 @@ impl OfferReceivedState {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProposalPreparedState {
+    pub proposal: CredentialProposal,
+    #[serde(default)]
+    pub thread: Thread,
+}
+
+impl ProposalPreparedState {
+    pub fn new(proposal: CredentialProposal) -> Self {
+        let thid = proposal.thread().and_then(|thread| thread.thid.clone()).unwrap_or_else(|| proposal.id());
+        let thread = Thread::new().set_thid(thid);
+        trace!("Thread: {:?}", thread);
+        ProposalPreparedState {
+            proposal,
+            thread,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProposalSentState {
+    pub proposal: CredentialProposal,
+    pub connection: CompletedConnection,
+    #[serde(default)]
+    pub thread: Thread,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FinishedHolderState {
     pub offer: Option<CredentialOffer>,
@@ -53,6 +88,43 @@ pub struct FinishedHolderState {
     pub thread: Thread,
 }
 
+impl From<(ProposalPreparedState, CompletedConnection, Thread)> for ProposalSentState {
+    fn from((state, connection, thread): (ProposalPreparedState, CompletedConnection, Thread)) -> Self {
+        trace!("HolderSM: transit state from ProposalPreparedState to ProposalSentState");
+        trace!("Thread: {:?}", thread);
+        ProposalSentState {
+            proposal: state.proposal,
+            connection,
+            thread,
+        }
+    }
+}
+
+impl From<(ProposalSentState, CredentialOffer, Thread)> for OfferReceivedState {
+    fn from((_state, offer, thread): (ProposalSentState, CredentialOffer, Thread)) -> Self {
+        trace!("HolderSM: transit state from ProposalSentState to OfferReceivedState");
+        trace!("Thread: {:?}", thread);
+        OfferReceivedState {
+            offer,
+            thread,
+        }
+    }
+}
+
+impl From<(ProposalSentState, ProblemReport, Thread, Reason)> for FinishedHolderState {
+    fn from((_state, problem_report, thread, reason): (ProposalSentState, ProblemReport, Thread, Reason)) -> Self {
+        trace!("HolderSM: transit state from ProposalSentState to FinishedHolderState with ProblemReport: {:?}", problem_report);
+        trace!("Thread: {:?}", thread);
+        FinishedHolderState {
+            offer: None,
+            cred_id: None,
+            credential: None,
+            status: reason.to_status(problem_report),
+            thread,
+        }
+    }
+}
+
 impl From<(OfferReceivedState, String, String, CompletedConnection, Thread)> for RequestSentState {
     fn from((state, req_meta, cred_def_json, connection, thread): (OfferReceivedState, String, String, CompletedConnection, Thread)) -> Self {
         trace!("HolderSM: transit state from OfferReceivedState to RequestSentState");
@@ -6,23 +6,30 @@ use crate::aries::handlers::{
         issuer::messages::IssuerMessages,
         issuer::states::*,
     },
-    connection::agent::AgentInfo,
+    connection::{
+        agent::AgentInfo,
+        types::CompletedConnection,
+    },
 };
 use crate::aries::messages::{
     a2a::A2AMessage,
     issuance::{
         credential_offer::CredentialOffer,
+        credential_proposal::CredentialProposal,
         credential::Credential,
         v10::credential_offer::CredentialOffer as CredentialOfferV1,
+        v20::credential_offer::CredentialOffer as CredentialOfferV2,
         v10::credential::Credential as CredentialV1,
         v20::credential::Credential as CredentialV2,
     },
     error::{ProblemReport, ProblemReportCodes},
     mime_type::MimeType,
     status::Status,
+    revocation_notification::revoke::RevocationNotification,
 };
 use crate::aries::messages::thread::Thread;
 use crate::issuer_credential::encode_attributes;
+use crate::credential_def;
 use crate::utils::libindy::anoncreds::issuer::Issuer as IndyIssuer;
 use crate::error::{VcxResult, VcxError, VcxErrorKind};
 use crate::connection::Connections;
@@ -49,6 +56,23 @@ impl IssuerSM {
         }
     }
 
+    /// Issuer answering the credential proposal received from the holder.
+    /// The credential definition must satisfy the `cred_def_id`, `issuer_did` and schema filters of the proposal.
+    pub fn new_from_proposal(proposal: CredentialProposal, cred_def_id: &str, schema_id: Option<&str>, credential_data: &str,
+                             rev_reg_id: Option<String>, tails_file: Option<String>, source_id: &str, credential_name: &str) -> VcxResult<Self> {
+        proposal.check_cred_def(cred_def_id, schema_id)?;
+
+        Ok(IssuerSM {
+            state: IssuerState::ProposalReceived(ProposalReceivedState::new(proposal,
+                                                                            cred_def_id,
+                                                                            credential_data,
+                                                                            rev_reg_id,
+                                                                            tails_file,
+                                                                            Some(credential_name.to_string()))),
+            source_id: source_id.to_string(),
+        })
+    }
+
     pub fn get_source_id(&self) -> &String {
         &self.source_id
     }
@@ -60,38 +84,42 @@ impl IssuerSM {
         }
     }
 
-    pub fn update_state(self) -> VcxResult<Self> {
-        trace!("Issuer::update_state >>> ", );
+    pub fn update_state(mut self) -> VcxResult<Self> {
+        trace!("Issuer::update_state >>> ");
 
         if self.is_terminal_state() { return Ok(self); }
 
-        let agent = match self.get_agent_info() {
-            Some(agent_info) => agent_info.clone(),
+        self.refresh_connection();
+
+        let connection = match self.get_connection() {
+            Some(connection) => connection.clone(),
             None => {
                 warn!("Could not update Issuer state: no information about Connection.");
                 return Ok(self);
             }
         };
 
-        let messages = agent.get_messages()?;
+        // messages sent before the DID rotation are delivered to the agent of the rotated DID
+        for agent in connection.agents() {
+            let messages = agent.get_messages()?;
 
-        match self.find_message_to_handle(messages) {
-            Some((uid, msg)) => {
-                let state = self.handle_message(msg.into())?;
-                agent.update_message_status(uid, None)?;
-                Ok(state)
+            if let Some((uid, msg)) = self.find_message_to_handle(messages) {
+                let state = agent.handle_message_once(uid, None, &msg, || self.clone().handle_message(msg.clone().into()))?;
+                return Ok(state.unwrap_or(self));
             }
-            None => Ok(self)
         }
+
+        Ok(self)
     }
 
-    fn find_message_to_handle(&self, messages: HashMap<String, A2AMessage>) -> Option<(String, A2AMessage)> {
+    pub fn find_message_to_handle(&self, messages: HashMap<String, A2AMessage>) -> Option<(String, A2AMessage)> {
         trace!("Issuer::find_message_to_handle >>> agent: {:?}", secret!(messages));
         debug!("Issuer: Finding message to update state");
 
         for (uid, message) in messages {
             match self.state {
-                IssuerState::Initial(_) => {
+                IssuerState::Initial(_) |
+                IssuerState::ProposalReceived(_) => {
                     // do not process agent
                 }
                 IssuerState::OfferSent(ref state) => {
@@ -155,6 +183,7 @@ impl IssuerSM {
     pub fn state(&self) -> u32 {
         match self.state {
             IssuerState::Initial(_) => VcxStateType::VcxStateInitialized as u32,
+            IssuerState::ProposalReceived(_) => VcxStateType::VcxStateInitialized as u32,
             IssuerState::OfferSent(_) => VcxStateType::VcxStateOfferSent as u32,
             IssuerState::RequestReceived(_) => VcxStateType::VcxStateRequestReceived as u32,
             IssuerState::CredentialSent(_) => VcxStateType::VcxStateAccepted as u32,
@@ -168,10 +197,12 @@ impl IssuerSM {
         }
     }
 
-    pub fn handle_message(self, cim: IssuerMessages) -> VcxResult<IssuerSM> {
+    pub fn handle_message(mut self, cim: IssuerMessages) -> VcxResult<IssuerSM> {
         trace!("Issuer::handle_message >>> cim: {:?}", secret!(cim));
         debug!("Issuer: Updating state");
 
+        self.refresh_connection();
+
         let IssuerSM { state, source_id } = self;
         let state = match state {
             IssuerState::Initial(state_data) => match cim {
@@ -183,6 +214,15 @@ impl IssuerSM {
                     IssuerState::Initial(state_data)
                 }
             }
+            IssuerState::ProposalReceived(state_data) => match cim {
+                IssuerMessages::CredentialInit(connection_handle) => {
+                    state_data.send_credential_offer(connection_handle)?
+                }
+                _ => {
+                    warn!("In this state Credential Issuance can only send Offer in reply to Proposal");
+                    IssuerState::ProposalReceived(state_data)
+                }
+            }
             IssuerState::OfferSent(state_data) => match cim {
                 IssuerMessages::CredentialRequest(request) => {
                     let thread = state_data.thread.clone();
@@ -245,12 +285,44 @@ impl IssuerSM {
         }
     }
 
+    /// Whether a message received from the remote side can move the Issuer to another state.
+    pub fn has_transitions(&self) -> bool {
+        match self.state {
+            IssuerState::Initial(_) => false,
+            IssuerState::ProposalReceived(_) => false,
+            IssuerState::OfferSent(_) => true,
+            IssuerState::RequestReceived(_) => false,
+            IssuerState::CredentialSent(_) => true,
+            IssuerState::Finished(_) => false,
+        }
+    }
+
+    pub fn get_connection(&self) -> Option<&CompletedConnection> {
+        match self.state {
+            IssuerState::OfferSent(ref state) => Some(&state.connection),
+            IssuerState::RequestReceived(ref state) => Some(&state.connection),
+            IssuerState::CredentialSent(ref state) => Some(&state.connection),
+            _ => None,
+        }
+    }
+
+    /// Takes the current state of the Connection the protocol was started over (see `CompletedConnection::refresh`).
+    pub fn refresh_connection(&mut self) {
+        match self.state {
+            IssuerState::OfferSent(ref mut state) => state.connection.refresh(),
+            IssuerState::RequestReceived(ref mut state) => state.connection.refresh(),
+            IssuerState::CredentialSent(ref mut state) => state.connection.refresh(),
+            _ => {}
+        }
+    }
+
     pub fn get_agent_info(&self) -> Option<&AgentInfo> {
         match self.state {
             IssuerState::OfferSent(ref state) => Some(&state.connection.agent),
             IssuerState::RequestReceived(ref state) => Some(&state.connection.agent),
             IssuerState::CredentialSent(ref state) => Some(&state.connection.agent),
             IssuerState::Initial(_) => None,
+            IssuerState::ProposalReceived(_) => None,
             IssuerState::Finished(_) => None,
         }
     }
@@ -258,6 +330,7 @@ impl IssuerSM {
     pub fn get_credential_offer(&self) -> Option<&CredentialOffer> {
         match self.state {
             IssuerState::Initial(_) => None,
+            IssuerState::ProposalReceived(_) => None,
             IssuerState::OfferSent(ref state) => Some(&state.offer),
             IssuerState::RequestReceived(ref state) => Some(&state.offer),
             IssuerState::CredentialSent(ref state) => Some(&state.offer),
@@ -265,9 +338,114 @@ impl IssuerSM {
         }
     }
 
+    pub fn get_revocation_info(&self) -> Option<&RevocationInfo> {
+        match self.state {
+            IssuerState::Initial(_) |
+            IssuerState::ProposalReceived(_) |
+            IssuerState::OfferSent(_) |
+            IssuerState::RequestReceived(_) => None,
+            IssuerState::CredentialSent(ref state) => state.revocation_info.as_ref(),
+            IssuerState::Finished(ref state) => state.revocation_info.as_ref(),
+        }
+    }
+
+    /// Whether the issued credential has been revoked.
+    pub fn is_revoked(&self) -> bool {
+        match self.state {
+            IssuerState::CredentialSent(ref state) => state.revoked,
+            IssuerState::Finished(ref state) => state.revoked,
+            _ => false
+        }
+    }
+
+    /// Revokes the issued credential.
+    /// If `publish` is `false` the revocation is only recorded in the wallet and published to the Ledger later.
+    /// The credential is revoked once the wallet revokes it: if publishing fails, the revocation stays pending
+    /// and is published by the next call with `publish` or with other pending revocations of the registry.
+    pub fn revoke_credential(&mut self, publish: bool) -> VcxResult<()> {
+        trace!("Issuer::revoke_credential >>> publish: {}", publish);
+
+        let revocation_info = self.get_revocation_info().cloned()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails,
+                                      format!("Issuer {}: Credential is not revocable or has not been issued yet", self.source_id)))?;
+
+        if self.is_revoked() {
+            if !publish || !IndyIssuer::is_revocation_pending(&revocation_info.rev_reg_id, &revocation_info.cred_rev_id)? {
+                return Err(VcxError::from_msg(VcxErrorKind::InvalidState,
+                                              format!("Issuer {}: Credential is already revoked", self.source_id)));
+            }
+            debug!("Issuer {}: Publishing pending revocation of the credential", self.source_id);
+        } else {
+            IndyIssuer::revoke_credential_local(&revocation_info.tails_file, &revocation_info.rev_reg_id, &revocation_info.cred_rev_id)?;
+
+            match self.state {
+                IssuerState::CredentialSent(ref mut state) => state.revoked = true,
+                IssuerState::Finished(ref mut state) => state.revoked = true,
+                _ => {}
+            }
+        }
+
+        if publish {
+            IndyIssuer::publish_pending_revocations(&revocation_info.rev_reg_id)
+                .map_err(|err| VcxError::from_msg(err.kind(),
+                                                  format!("Issuer {}: Credential is revoked, but the revocation is not published to the Ledger and stays pending. Err: {}",
+                                                          self.source_id, err)))?;
+        }
+
+        trace!("Issuer::revoke_credential <<<");
+        Ok(())
+    }
+
+    /// Notifies the holder about revocation of the issued credential over the connection the credential was issued over.
+    /// `version` is the version of Revocation Notification protocol: `1.0` (default) or `2.0`.
+    pub fn send_revocation_notification(&self, version: Option<&str>, comment: Option<String>) -> VcxResult<()> {
+        trace!("Issuer::send_revocation_notification >>> version: {:?}, comment: {:?}", version, secret!(comment));
+
+        let revocation_info = self.get_revocation_info()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails,
+                                      format!("Issuer {}: Credential is not revocable or has not been issued yet", self.source_id)))?;
+
+        if !self.is_revoked() {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidState,
+                                          format!("Issuer {}: Credential has not been revoked", self.source_id)));
+        }
+
+        let (thread, connection) = match self.state {
+            IssuerState::CredentialSent(ref state) => (Some(&state.thread), Some(&state.connection)),
+            IssuerState::Finished(ref state) => (Some(&state.thread), state.connection.as_ref()),
+            _ => (None, None),
+        };
+
+        let thread_id = thread.and_then(|thread| thread.thid.clone())
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState,
+                                      format!("Issuer {}: Thread of the credential issuance not found", self.source_id)))?;
+
+        let mut connection = connection.cloned()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState,
+                                      format!("Issuer {}: Connection the credential was issued over not found", self.source_id)))?;
+
+        // the connection could be updated (DID rotation, DIDDoc update) since the credential was issued
+        connection.refresh();
+
+        let notification = RevocationNotification::create(version, &thread_id, &revocation_info.rev_reg_id, &revocation_info.cred_rev_id, comment)?;
+
+        connection.data.send_message(&notification, &connection.agent)?;
+
+        trace!("Issuer::send_revocation_notification <<<");
+        Ok(())
+    }
+
+    pub fn get_credential_proposal(&self) -> Option<&CredentialProposal> {
+        match self.state {
+            IssuerState::ProposalReceived(ref state) => Some(&state.proposal),
+            _ => None,
+        }
+    }
+
     pub fn problem_report(&self) -> Option<&ProblemReport> {
         match self.state {
             IssuerState::Initial(_) |
+            IssuerState::ProposalReceived(_) |
             IssuerState::OfferSent(_) |
             IssuerState::RequestReceived(_) |
             IssuerState::CredentialSent(_) => None,
@@ -324,6 +502,42 @@ impl InitialState {
     }
 }
 
+impl ProposalReceivedState {
+    fn send_credential_offer(self, connection_handle: Handle<Connections>) -> VcxResult<IssuerState> {
+        let connection = connection_handle.get_completed_connection()?;
+
+        // offer continues the thread started by the proposal
+        let thread = self.thread.clone()
+            .update_received_order(&connection.data.did_doc.id)
+            .set_opt_pthid(connection.data.thread.pthid.clone());
+
+        let cred_offer = IndyIssuer::create_credential_offer(&self.cred_def_id)?;
+
+        // reply with the same protocol version as the proposal has
+        let cred_offer_msg = match self.proposal {
+            CredentialProposal::V1(_) =>
+                CredentialOffer::V1(
+                    CredentialOfferV1::create()
+                        .set_comment(self.credential_name.clone())
+                        .set_offers_attach(&cred_offer)?
+                ),
+            CredentialProposal::V2(_) =>
+                CredentialOffer::V2(
+                    CredentialOfferV2::create()
+                        .set_comment(self.credential_name.clone())
+                        .set_indy_offers_attach(&cred_offer)?
+                ),
+        };
+
+        let cred_offer_msg = cred_offer_msg
+            .append_credential_preview(&self.credential_json)?
+            .set_thread(thread.clone());
+
+        connection.data.send_message(&cred_offer_msg, &connection.agent)?;
+        Ok(IssuerState::OfferSent((self, cred_offer_msg, connection, thread).into()))
+    }
+}
+
 impl OfferSentState {
     fn handle_received_credential_proposal(self) -> VcxResult<IssuerState> {
         let thread = self.thread.clone()
@@ -342,7 +556,7 @@ impl OfferSentState {
 }
 
 impl RequestReceivedState {
-    fn send_credential(self, connection_handle: Handle<Connections>) -> VcxResult<IssuerState> {
+    fn send_credential(mut self, connection_handle: Handle<Connections>) -> VcxResult<IssuerState> {
         let connection = connection_handle.get_completed_connection()?;
 
         let thread = self.request.thread().clone()
@@ -350,9 +564,10 @@ impl RequestReceivedState {
             .update_received_order(&self.connection.data.did_doc.id);
 
         match self.create_credential(&thread) {
-            Ok(credential_msg) => {
+            Ok((credential_msg, cred_rev_id)) => {
                 connection.data.send_message(&credential_msg, &connection.agent)?;
-                Ok(IssuerState::Finished((self, thread).into()))
+                let revocation_info = self.revocation_info(cred_rev_id);
+                Ok(IssuerState::Finished((self, revocation_info, thread).into()))
             }
             Err(err) => {
                 let problem_report = ProblemReport::create()
@@ -367,7 +582,9 @@ impl RequestReceivedState {
         }
     }
 
-    fn create_credential(&self, thread: &Thread) -> VcxResult<Credential> {
+    // returns the credential message and `cred_rev_id` of the credential
+    // the revocation registry is selected right before the credential is created, `rev_reg_id` and `tails_file` are updated with it
+    fn create_credential(&mut self, thread: &Thread) -> VcxResult<(Credential, Option<String>)> {
         trace!("Issuer::RequestReceivedState::create_credential >>>");
 
         self.thread.check_message_order(&self.connection.data.did_doc.id, self.request.thread())?;
@@ -376,12 +593,24 @@ impl RequestReceivedState {
 
         let cred_data = encode_attributes(&self.cred_data)?;
         let (_, cred_offer_attachment) = self.offer.offer_attach().content()?;
-
-        let (credential, _, _) = IndyIssuer::create_credential(&cred_offer_attachment,
-                                                               &request,
-                                                               &cred_data,
-                                                               self.rev_reg_id.as_deref(),
-                                                               self.tails_file.as_deref())?;
+        let cred_def_id = ::serde_json::from_str::<::serde_json::Value>(&cred_offer_attachment).ok()
+            .and_then(|offer| offer["cred_def_id"].as_str().map(String::from))
+            .unwrap_or_default();
+
+        let (credential, cred_rev_id, rev_reg_id, tails_file) = credential_def::issue_credential(
+            &cred_def_id,
+            (self.rev_reg_id.clone(), None, self.tails_file.clone()),
+            |rev_reg_id, _, tails_file| {
+                let (credential, cred_rev_id, _) = IndyIssuer::create_credential(&cred_offer_attachment,
+                                                                                 &request,
+                                                                                 &cred_data,
+                                                                                 rev_reg_id,
+                                                                                 tails_file)?;
+                Ok((credential, cred_rev_id, rev_reg_id.map(String::from), tails_file.map(String::from)))
+            })?;
+
+        self.rev_reg_id = rev_reg_id;
+        self.tails_file = tails_file;
 
         let credential = match self.request {
             CredentialRequest::V1(_) =>
@@ -398,8 +627,8 @@ impl RequestReceivedState {
                 )
         };
 
-        trace!("Issuer::RequestReceivedState::create_credential <<<");
-        Ok(credential)
+        trace!("Issuer::RequestReceivedState::create_credential <<< cred_rev_id: {:?}", cred_rev_id);
+        Ok((credential, cred_rev_id))
     }
 }
 
@@ -774,4 +1003,179 @@ pub mod test {
             assert_eq!(VcxStateType::VcxStateAccepted as u32, _issuer_sm().to_finished_state().state());
         }
     }
+
+    mod revoke_credential {
+        use super::*;
+
+        #[test]
+        fn test_issuer_revoke_credential() {
+            let _setup = SetupAriesMocks::init();
+
+            // credential definition does not support revocation
+            let mut issuer_sm = _issuer_sm().to_finished_state();
+            assert_eq!(None, issuer_sm.get_revocation_info());
+            assert_eq!(VcxErrorKind::InvalidRevocationDetails, issuer_sm.revoke_credential(true).unwrap_err().kind());
+
+            let revocation_info = RevocationInfo {
+                cred_rev_id: String::from("1"),
+                rev_reg_id: String::from("rev_reg_id"),
+                tails_file: String::from("tails_file"),
+            };
+
+            let mut issuer_sm = _issuer_sm().to_finished_state();
+            if let IssuerState::Finished(ref mut state) = issuer_sm.state {
+                state.revocation_info = Some(revocation_info.clone());
+            }
+            assert_eq!(Some(&revocation_info), issuer_sm.get_revocation_info());
+            assert!(!issuer_sm.is_revoked());
+
+            issuer_sm.revoke_credential(true).unwrap();
+            assert!(issuer_sm.is_revoked());
+
+            // the credential is revoked only once
+            assert_eq!(VcxErrorKind::InvalidState, issuer_sm.revoke_credential(true).unwrap_err().kind());
+            assert_eq!(VcxErrorKind::InvalidState, issuer_sm.revoke_credential(false).unwrap_err().kind());
+
+            // the flag is kept in the serialized state
+            let issuer_sm: IssuerSM = ::serde_json::from_str(&json!(issuer_sm).to_string()).unwrap();
+            assert!(issuer_sm.is_revoked());
+        }
+
+        #[test]
+        fn test_issuer_revoke_credential_local() {
+            let _setup = SetupAriesMocks::init();
+
+            let mut issuer_sm = _issuer_sm().to_finished_state();
+            if let IssuerState::Finished(ref mut state) = issuer_sm.state {
+                state.revocation_info = Some(RevocationInfo {
+                    cred_rev_id: String::from("1"),
+                    rev_reg_id: String::from("rev_reg_id"),
+                    tails_file: String::from("tails_file"),
+                });
+            }
+
+            issuer_sm.revoke_credential(false).unwrap();
+            assert!(issuer_sm.is_revoked());
+            assert_eq!(VcxErrorKind::InvalidState, issuer_sm.revoke_credential(true).unwrap_err().kind());
+        }
+
+        #[test]
+        fn test_issuer_send_revocation_notification() {
+            let _setup = SetupAriesMocks::init();
+
+            // credential definition does not support revocation
+            let issuer_sm = _issuer_sm().to_finished_state();
+            assert_eq!(VcxErrorKind::InvalidRevocationDetails,
+                       issuer_sm.send_revocation_notification(None, None).unwrap_err().kind());
+
+            let mut issuer_sm = _issuer_sm().to_finished_state();
+            if let IssuerState::Finished(ref mut state) = issuer_sm.state {
+                state.revocation_info = Some(RevocationInfo {
+                    cred_rev_id: String::from("1"),
+                    rev_reg_id: String::from("rev_reg_id"),
+                    tails_file: String::from("tails_file"),
+                });
+            }
+
+            // credential is not revoked
+            assert_eq!(VcxErrorKind::InvalidState,
+                       issuer_sm.send_revocation_notification(None, None).unwrap_err().kind());
+
+            issuer_sm.revoke_credential(false).unwrap();
+
+            issuer_sm.send_revocation_notification(None, None).unwrap();
+            issuer_sm.send_revocation_notification(Some("2.0"), Some(String::from("Expired"))).unwrap();
+            assert_eq!(VcxErrorKind::InvalidOption,
+                       issuer_sm.send_revocation_notification(Some("3.0"), None).unwrap_err().kind());
+
+            // connection is not known to Finished state of the previous version
+            if let IssuerState::Finished(ref mut state) = issuer_sm.state {
+                state.connection = None;
+            }
+            assert_eq!(VcxErrorKind::InvalidState,
+                       issuer_sm.send_revocation_notification(None, None).unwrap_err().kind());
+        }
+    }
+
+    mod proposal {
+        use super::*;
+        use crate::aries::messages::issuance::v20::credential_proposal::tests::_credential_proposal as _credential_proposal_v2;
+
+        fn _issuer_sm_from_proposal(proposal: CredentialProposal) -> IssuerSM {
+            IssuerSM::new_from_proposal(proposal, "cred_def_id:id", Some("schema:id"), &json!({"name": "alice"}).to_string(), None, None, &source_id(), "test").unwrap()
+        }
+
+        fn _proposal_thid(proposal: &CredentialProposal) -> Option<String> {
+            proposal.thread().and_then(|thread| thread.thid.clone())
+        }
+
+        #[test]
+        fn test_issuer_new_from_proposal() {
+            let _setup = SetupAriesMocks::init();
+
+            let issuer_sm = _issuer_sm_from_proposal(_credential_proposal());
+
+            assert_match!(IssuerState::ProposalReceived(_), issuer_sm.state);
+            assert_eq!(VcxStateType::VcxStateInitialized as u32, issuer_sm.state());
+            assert_eq!(Some(&_credential_proposal()), issuer_sm.get_credential_proposal());
+            assert!(issuer_sm.get_credential_offer().is_none());
+            assert!(issuer_sm.get_agent_info().is_none());
+        }
+
+        #[test]
+        fn test_issuer_new_from_proposal_for_other_cred_def() {
+            let _setup = SetupAriesMocks::init();
+
+            let err = IssuerSM::new_from_proposal(_credential_proposal(), "other:id", Some("schema:id"), "{}", None, None, &source_id(), "test").unwrap_err();
+            assert_eq!(VcxErrorKind::InvalidCredDefHandle, err.kind());
+        }
+
+        #[test]
+        fn test_issuer_send_offer_for_proposal() {
+            let _setup = SetupAriesMocks::init();
+
+            let proposal = _credential_proposal();
+
+            let mut issuer_sm = _issuer_sm_from_proposal(proposal.clone());
+            issuer_sm = issuer_sm.handle_message(IssuerMessages::CredentialInit(mock_connection())).unwrap();
+
+            assert_match!(IssuerState::OfferSent(_), issuer_sm.state);
+            let offer = issuer_sm.get_credential_offer().unwrap();
+            assert_match!(CredentialOffer::V1(_), offer);
+            assert_eq!(_proposal_thid(&proposal), offer.thread().unwrap().thid);
+
+            issuer_sm = issuer_sm.handle_message(IssuerMessages::CredentialRequest(_credential_request())).unwrap();
+            assert_match!(IssuerState::RequestReceived(_), issuer_sm.state);
+        }
+
+        #[test]
+        fn test_issuer_send_offer_for_proposal_v2() {
+            let _setup = SetupAriesMocks::init();
+
+            let proposal = CredentialProposal::V2(_credential_proposal_v2());
+
+            let mut issuer_sm = _issuer_sm_from_proposal(proposal.clone());
+            issuer_sm = issuer_sm.handle_message(IssuerMessages::CredentialInit(mock_connection())).unwrap();
+
+            let offer = issuer_sm.get_credential_offer().unwrap();
+            assert_match!(CredentialOffer::V2(_), offer);
+            assert_eq!(_proposal_thid(&proposal), offer.thread().unwrap().thid);
+        }
+
+        #[test]
+        fn test_issuer_handle_other_messages_from_proposal_received_state() {
+            let _setup = SetupAriesMocks::init();
+
+            let mut issuer_sm = _issuer_sm_from_proposal(_credential_proposal());
+
+            issuer_sm = issuer_sm.handle_message(IssuerMessages::CredentialRequest(_credential_request())).unwrap();
+            assert_match!(IssuerState::ProposalReceived(_), issuer_sm.state);
+
+            let messages = map!(
+                "key_1".to_string() => A2AMessage::CredentialRequest(_credential_request()),
+                "key_2".to_string() => A2AMessage::CredentialProposal(_credential_proposal())
+            );
+            assert!(issuer_sm.find_message_to_handle(messages).is_none());
+        }
+    }
 }
@@ -10,6 +10,7 @@ use crate::error::prelude::*;
 use crate::aries::messages::{
     a2a::A2AMessage,
     issuance::credential_offer::CredentialOffer,
+    issuance::credential_proposal::CredentialProposal,
     error::ProblemReport,
 };
 
@@ -31,8 +32,8 @@ impl Issuer {
         debug!("Issuer {}: Creating credential Issuer state object", source_id);
 
         let cred_def_id = cred_def_handle.get_cred_def_id()?;
-        let rev_reg_id = cred_def_handle.get_rev_reg_id()?;
-        let tails_file = cred_def_handle.get_tails_file()?;
+        // selected again right before the credential is created
+        let (rev_reg_id, _, tails_file) = cred_def_handle.select_rev_reg()?;
         let issuer_sm = IssuerSM::new(&cred_def_id, credential_data, rev_reg_id, tails_file, source_id, credential_name);
         Ok(Issuer { issuer_sm })
     }
@@ -46,6 +47,29 @@ impl Issuer {
         Ok(Issuer { issuer_sm })
     }
 
+    /// Creates Issuer answering the credential proposal received from the holder.
+    /// Proposed attribute values are offered if `credential_data` is not set.
+    pub fn create_from_proposal(cred_def_handle: Handle<CredentialDef>, proposal: CredentialProposal, credential_data: Option<&str>,
+                                source_id: &str, credential_name: &str) -> VcxResult<Issuer> {
+        trace!("Issuer::create_from_proposal >>> cred_def_handle: {:?}, proposal: {:?}, credential_data: {:?}, source_id: {:?}",
+               cred_def_handle, secret!(proposal), secret!(credential_data), source_id);
+        debug!("Issuer {}: Creating credential Issuer state object from proposal", source_id);
+
+        let cred_def_id = cred_def_handle.get_cred_def_id()?;
+        let schema_id = cred_def_handle.get_schema_id()?;
+        // selected again right before the credential is created
+        let (rev_reg_id, _, tails_file) = cred_def_handle.select_rev_reg()?;
+
+        let credential_data = match credential_data {
+            Some(credential_data) => credential_data.to_string(),
+            None => json!(proposal.credential_values()).to_string()
+        };
+
+        let issuer_sm = IssuerSM::new_from_proposal(proposal, &cred_def_id, schema_id.as_deref(), &credential_data,
+                                                    rev_reg_id, tails_file, source_id, credential_name)?;
+        Ok(Issuer { issuer_sm })
+    }
+
     pub fn send_credential_offer(&mut self, connection_handle: Handle<Connections>) -> VcxResult<()> {
         debug!("Issuer {}: Sending credential offer", self.get_source_id()?);
         self.step(IssuerMessages::CredentialInit(connection_handle))
@@ -69,6 +93,30 @@ impl Issuer {
             .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, format!("Invalid {} Issuer object state: `offer` not found", self.get_source_id()?)))
     }
 
+    pub fn get_credential_proposal(&self) -> VcxResult<&CredentialProposal> {
+        self.issuer_sm.get_credential_proposal()
+            .ok_or(VcxError::from_msg(VcxErrorKind::NotReady, format!("{} Issuer object is not created from credential proposal", self.get_source_id()?)))
+    }
+
+    /// Revokes the issued credential.
+    /// If `publish` is `false` the revocation is only recorded in the wallet.
+    /// The credential can be revoked only once.
+    pub fn revoke_credential(&mut self, publish: bool) -> VcxResult<()> {
+        debug!("Issuer {}: Revoking credential", self.get_source_id()?);
+        self.issuer_sm.revoke_credential(publish)
+    }
+
+    /// Whether the issued credential has been revoked.
+    pub fn is_revoked(&self) -> bool {
+        self.issuer_sm.is_revoked()
+    }
+
+    /// Notifies the holder about revocation of the issued credential over the connection the credential was issued over.
+    pub fn send_revocation_notification(&self, version: Option<&str>, comment: Option<String>) -> VcxResult<()> {
+        debug!("Issuer {}: Sending revocation notification", self.get_source_id()?);
+        self.issuer_sm.send_revocation_notification(version, comment)
+    }
+
     pub fn get_problem_report_message(&self) -> VcxResult<String> {
         trace!("Issuer::get_problem_report_message >>>");
         debug!("Issuer {}: Getting problem report message", self.issuer_sm.get_source_id());
@@ -100,6 +148,39 @@ impl Issuer {
         Ok(state)
     }
 
+    /// Whether the Issuer still expects messages over the connection identified by `connection_id`.
+    pub fn is_in_progress_over(&self, connection_id: &str) -> bool {
+        self.issuer_sm.has_transitions() &&
+            self.issuer_sm.get_agent_info().map_or(false, |agent_info| agent_info.connection_id() == connection_id)
+    }
+
+    /// Whether a message received through the inbound endpoint belongs to this Issuer and can be handled in its current state.
+    pub fn accepts_inbound_message(&self, recipient_vk: &str, message: &A2AMessage) -> bool {
+        self.issuer_sm.has_transitions() &&
+            self.issuer_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) &&
+            self.issuer_sm.find_message_to_handle(map!(String::new() => message.clone())).is_some()
+    }
+
+    /// Handles a message received through the inbound endpoint.
+    /// Returns `false` if the message does not belong to this Issuer.
+    pub fn handle_inbound_message(&mut self, recipient_vk: &str, message: &A2AMessage) -> VcxResult<bool> {
+        trace!("Issuer::handle_inbound_message >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+        if !self.issuer_sm.has_transitions() ||
+            !self.issuer_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) {
+            return Ok(false);
+        }
+
+        match self.issuer_sm.find_message_to_handle(map!(String::new() => message.clone())) {
+            Some((_, message)) => {
+                debug!("Issuer {}: Handling inbound message", self.get_source_id()?);
+                self.step(message.into())?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
     pub fn step(&mut self, message: IssuerMessages) -> VcxResult<()> {
         self.issuer_sm = self.issuer_sm.clone().handle_message(message)?;
         Ok(())
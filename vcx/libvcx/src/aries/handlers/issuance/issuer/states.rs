@@ -2,6 +2,7 @@ use crate::aries::messages::{
     issuance::{
         credential_request::CredentialRequest,
         credential_offer::CredentialOffer,
+        credential_proposal::CredentialProposal,
     },
     status::Status,
 };
@@ -11,12 +12,14 @@ use crate::aries::messages::thread::Thread;
 // Possible Transitions:
 // Initial -> OfferSent
 // Initial -> Finished
+// ProposalReceived -> OfferSent
 // OfferSent -> CredentialSent
 // OfferSent -> Finished
 // CredentialSent -> Finished
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum IssuerState {
     Initial(InitialState),
+    ProposalReceived(ProposalReceivedState),
     OfferSent(OfferSentState),
     RequestReceived(RequestReceivedState),
     CredentialSent(CredentialSentState),
@@ -45,6 +48,38 @@ pub struct InitialState {
     pub credential_name: Option<String>,
 }
 
+impl ProposalReceivedState {
+    pub fn new(proposal: CredentialProposal, cred_def_id: &str, credential_json: &str, rev_reg_id: Option<String>,
+               tails_file: Option<String>, credential_name: Option<String>) -> Self {
+        let thid = proposal.thread()
+            .and_then(|thread| thread.thid.clone())
+            .unwrap_or_else(|| proposal.id());
+
+        ProposalReceivedState {
+            proposal,
+            cred_def_id: cred_def_id.to_string(),
+            credential_json: credential_json.to_string(),
+            rev_reg_id,
+            tails_file,
+            credential_name,
+            thread: Thread::new().set_thid(thid),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProposalReceivedState {
+    pub proposal: CredentialProposal,
+    pub cred_def_id: String,
+    pub credential_json: String,
+    pub rev_reg_id: Option<String>,
+    pub tails_file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub credential_name: Option<String>,
+    #[serde(default)]
+    pub thread: Thread,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OfferSentState {
     pub offer: CredentialOffer,
@@ -73,6 +108,12 @@ pub struct CredentialSentState {
     pub offer: CredentialOffer,
     pub connection: CompletedConnection,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_info: Option<RevocationInfo>,
+    /// whether the issued credential has been revoked
+    #[serde(default)]
+    pub revoked: bool,
+    #[serde(default)]
     pub thread: Thread,
 }
 
@@ -82,7 +123,36 @@ pub struct FinishedState {
     pub cred_id: Option<String>,
     pub status: Status,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revocation_info: Option<RevocationInfo>,
+    /// whether the issued credential has been revoked
+    #[serde(default)]
+    pub revoked: bool,
+    #[serde(default)]
     pub thread: Thread,
+    /// connection the credential was issued over, used to notify the holder about its revocation
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<CompletedConnection>,
+}
+
+/// Data needed to revoke the issued credential.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RevocationInfo {
+    pub cred_rev_id: String,
+    pub rev_reg_id: String,
+    pub tails_file: String,
+}
+
+impl RequestReceivedState {
+    /// Revocation info of the credential issued with `cred_rev_id` (if the credential definition supports revocation).
+    pub fn revocation_info(&self, cred_rev_id: Option<String>) -> Option<RevocationInfo> {
+        match (cred_rev_id, &self.rev_reg_id, &self.tails_file) {
+            (Some(cred_rev_id), Some(rev_reg_id), Some(tails_file)) =>
+                Some(RevocationInfo { cred_rev_id, rev_reg_id: rev_reg_id.clone(), tails_file: tails_file.clone() }),
+            _ => None
+        }
+    }
 }
 
 impl From<(InitialState, CredentialOffer, CompletedConnection, Thread)> for OfferSentState {
@@ -100,6 +170,21 @@ impl From<(InitialState, CredentialOffer, CompletedConnection, Thread)> for Offe
     }
 }
 
+impl From<(ProposalReceivedState, CredentialOffer, CompletedConnection, Thread)> for OfferSentState {
+    fn from((state, offer, connection, thread): (ProposalReceivedState, CredentialOffer, CompletedConnection, Thread)) -> Self {
+        trace!("IssuerSM: transit state from ProposalReceivedState to OfferSentState");
+        trace!("Thread: {:?}", thread);
+        OfferSentState {
+            offer,
+            cred_data: state.credential_json,
+            rev_reg_id: state.rev_reg_id,
+            tails_file: state.tails_file,
+            connection,
+            thread,
+        }
+    }
+}
+
 impl From<(OfferSentState, CredentialRequest, Thread)> for RequestReceivedState {
     fn from((state, request, thread): (OfferSentState, CredentialRequest, Thread)) -> Self {
         trace!("IssuerSM: transit state from OfferSentState to RequestReceivedState");
@@ -116,13 +201,15 @@ impl From<(OfferSentState, CredentialRequest, Thread)> for RequestReceivedState
     }
 }
 
-impl From<(RequestReceivedState, Thread)> for CredentialSentState {
-    fn from((state, thread): (RequestReceivedState, Thread)) -> Self {
+impl From<(RequestReceivedState, Option<RevocationInfo>, Thread)> for CredentialSentState {
+    fn from((state, revocation_info, thread): (RequestReceivedState, Option<RevocationInfo>, Thread)) -> Self {
         trace!("IssuerSM: transit state from RequestReceivedState to CredentialSentState");
         trace!("Thread: {:?}", thread);
         CredentialSentState {
             offer: state.offer,
             connection: state.connection,
+            revocation_info,
+            revoked: false,
             thread,
         }
     }
@@ -136,20 +223,26 @@ impl From<(OfferSentState, Status, Thread)> for FinishedState {
             cred_id: None,
             offer: Some(state.offer),
             status,
+            revocation_info: None,
+            revoked: false,
             thread,
+            connection: None,
         }
     }
 }
 
-impl From<(RequestReceivedState, Thread)> for FinishedState {
-    fn from((state, thread): (RequestReceivedState, Thread)) -> Self {
+impl From<(RequestReceivedState, Option<RevocationInfo>, Thread)> for FinishedState {
+    fn from((state, revocation_info, thread): (RequestReceivedState, Option<RevocationInfo>, Thread)) -> Self {
         trace!("IssuerSM: transit state from RequestReceivedState to FinishedState");
         trace!("Thread: {:?}", thread);
         FinishedState {
             cred_id: None,
             offer: Some(state.offer),
             status: Status::Success,
+            revocation_info,
+            revoked: false,
             thread,
+            connection: Some(state.connection),
         }
     }
 }
@@ -162,7 +255,10 @@ impl From<(RequestReceivedState, Status, Thread)> for FinishedState {
             cred_id: None,
             offer: Some(state.offer),
             status,
+            revocation_info: None,
+            revoked: false,
             thread,
+            connection: None,
         }
     }
 }
@@ -175,7 +271,10 @@ impl From<(CredentialSentState, Thread)> for FinishedState {
             cred_id: None,
             offer: Some(state.offer),
             status: Status::Success,
+            revocation_info: state.revocation_info,
+            revoked: state.revoked,
             thread,
+            connection: Some(state.connection),
         }
     }
 }
@@ -188,7 +287,10 @@ impl From<(CredentialSentState, Status, Thread)> for FinishedState {
             cred_id: None,
             offer: Some(state.offer),
             status,
+            revocation_info: state.revocation_info,
+            revoked: state.revoked,
             thread,
+            connection: Some(state.connection),
         }
     }
 }
\ No newline at end of file
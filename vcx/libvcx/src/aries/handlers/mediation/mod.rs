@@ -0,0 +1,425 @@
+pub mod pickup;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::connection;
+use crate::utils::libindy::wallet::{self, WalletRecord};
+use crate::aries::handlers::connection::types::CompletedConnection;
+use crate::aries::messages::connection::did_doc::Service;
+use crate::aries::messages::mediation::mediate_request::MediateRequest;
+use crate::aries::messages::mediation::mediate_grant::MediateGrant;
+use crate::aries::messages::mediation::mediate_deny::MediateDeny;
+use crate::aries::messages::mediation::keylist_update::{KeylistUpdate, KeylistUpdateAction};
+use crate::aries::messages::mediation::keylist_update_response::KeylistUpdateResponse;
+
+/*
+    Recipient side of Coordinate Mediation protocol (Aries RFC 0211).
+
+    Mediation is requested over an established pairwise connection with the mediator and is stored in the wallet.
+    The record refers to the connection by its id, messages are sent over the current state of the connection
+    (it can be changed by the DID rotation or DIDDoc update after the mediation was requested).
+    While the mediation is granted, every new pairwise agent registers its recipient key on the mediator
+    and publishes the mediator endpoint and routing keys in its DIDDoc instead of the Agency ones.
+*/
+
+const MEDIATION_RECORD_TYPE: &str = "vcx_mediation";
+const MEDIATION_RECORD_ID: &str = "default";
+
+/// Endpoint and routing keys granted by the mediator.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MediatorRouting {
+    pub endpoint: String,
+    pub routing_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum MediationState {
+    Requested { request_id: String },
+    Granted(MediatorRouting),
+    Denied,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Mediation {
+    /// Id of the connection with the mediator (see `AgentInfo::connection_id`).
+    #[serde(default)]
+    pub connection_id: String,
+    /// Last known state of the connection with the mediator. Used only while the connection is not loaded.
+    pub connection: CompletedConnection,
+    pub state: MediationState,
+    #[serde(default)]
+    pub recipient_keys: Vec<String>,
+    /// Whether the mediator was asked to deliver messages as soon as they are received (Message Pickup live mode).
+    #[serde(default)]
+    pub live_delivery: bool,
+}
+
+/// Object returned by `vcx_get_mediation_info`
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MediationInfo {
+    pub mediator_did: String,
+    #[serde(flatten)]
+    pub state: MediationState,
+    pub recipient_keys: Vec<String>,
+    pub live_delivery: bool,
+}
+
+impl Mediation {
+    fn new(connection: CompletedConnection, request_id: String) -> Mediation {
+        Mediation {
+            connection_id: connection.agent.connection_id().to_string(),
+            connection,
+            state: MediationState::Requested { request_id },
+            recipient_keys: Vec::new(),
+            live_delivery: false,
+        }
+    }
+
+    fn load() -> VcxResult<Option<Mediation>> {
+        if settings::indy_mocks_enabled() { return Ok(None); }
+
+        let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false});
+
+        let record = match wallet::get_record(MEDIATION_RECORD_TYPE, MEDIATION_RECORD_ID, &options.to_string()) {
+            Ok(record) => record,
+            Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+            Err(err) => return Err(err)
+        };
+
+        let mut mediation: Mediation = WalletRecord::from_str(&record)?.value
+            .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Mediation record does not contain value"))
+            .and_then(|value| ::serde_json::from_str(&value)
+                .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize Mediation record: {}", err))))?;
+
+        // records stored before keep the connection only
+        if mediation.connection_id.is_empty() {
+            mediation.connection_id = mediation.connection.agent.connection_id().to_string();
+        }
+
+        Ok(Some(mediation))
+    }
+
+    fn store(&self) -> VcxResult<()> {
+        let value = json!(self).to_string();
+
+        wallet::update_record_value(MEDIATION_RECORD_TYPE, MEDIATION_RECORD_ID, &value)
+            .or_else(|_| wallet::add_record(MEDIATION_RECORD_TYPE, MEDIATION_RECORD_ID, &value, None))
+    }
+
+    fn load_for_connection(connection_id: &str) -> VcxResult<Mediation> {
+        Mediation::load()?
+            .filter(|mediation| mediation.connection_id == connection_id)
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "Mediation has not been requested over this connection"))
+    }
+
+    /// Returns the current state of the connection with the mediator.
+    fn connection(&mut self) -> CompletedConnection {
+        match connection::completed_connection_by_id(&self.connection_id) {
+            Ok(Some(connection)) => self.connection = connection,
+            Ok(None) => debug!("Connection with the mediator is not available, its last known state is used"),
+            Err(err) => warn!("Unable to get the connection with the mediator, its last known state is used. Err: {:?}", err)
+        }
+        self.connection.clone()
+    }
+
+    fn routing(&self) -> Option<&MediatorRouting> {
+        match self.state {
+            MediationState::Granted(ref routing) => Some(routing),
+            MediationState::Requested { .. } | MediationState::Denied => None
+        }
+    }
+
+    fn expects_reply(&self, thread_id: Option<&String>) -> VcxResult<()> {
+        match self.state {
+            // some mediators do not thread their replies
+            MediationState::Requested { ref request_id } if thread_id.map(|thid| thid == request_id).unwrap_or(true) => Ok(()),
+            MediationState::Requested { .. } =>
+                Err(VcxError::from_msg(VcxErrorKind::InvalidState, "Mediation reply does not relate to the sent mediation request")),
+            MediationState::Granted(_) | MediationState::Denied =>
+                Err(VcxError::from_msg(VcxErrorKind::InvalidState, "Mediation request has already been answered"))
+        }
+    }
+
+    fn grant(mut self, grant: &MediateGrant) -> VcxResult<Mediation> {
+        self.expects_reply(grant.thread.thid.as_ref())?;
+
+        let mut routing_keys = grant.routing_keys.clone();
+        Service::transform_did_keys_to_naked_keys(&mut routing_keys)?;
+
+        self.state = MediationState::Granted(MediatorRouting { endpoint: grant.endpoint.clone(), routing_keys });
+        Ok(self)
+    }
+
+    fn deny(mut self, deny: &MediateDeny) -> VcxResult<Mediation> {
+        self.expects_reply(deny.thread.thid.as_ref())?;
+
+        self.state = MediationState::Denied;
+        Ok(self)
+    }
+
+    fn update_keys(mut self, add: &[&str], remove: &[&str]) -> VcxResult<Mediation> {
+        let update = add.iter().fold(KeylistUpdate::create(), |update, key| update.add_key(key));
+        let update = remove.iter().fold(update, |update, key| update.remove_key(key));
+
+        let connection = self.connection();
+        connection.agent.send_message(&update, &connection.data.did_doc)?;
+
+        self.recipient_keys.extend(add.iter().map(|key| key.to_string()));
+        self.recipient_keys.retain(|key| !remove.contains(&key.as_str()));
+        Ok(self)
+    }
+
+    fn apply_update_response(mut self, response: &KeylistUpdateResponse) -> Mediation {
+        for updated in response.updated.iter().filter(|updated| !updated.is_applied()) {
+            warn!("Mediator has not applied {:?} of recipient key {:?}: {:?}", updated.action, updated.recipient_key, updated.result);
+
+            if updated.action == KeylistUpdateAction::Add {
+                self.recipient_keys.retain(|key| key != &updated.recipient_key);
+            }
+        }
+        self
+    }
+
+    fn info(&self) -> MediationInfo {
+        MediationInfo {
+            mediator_did: self.connection.data.did_doc.id.clone(),
+            state: self.state.clone(),
+            recipient_keys: self.recipient_keys.clone(),
+            live_delivery: self.live_delivery,
+        }
+    }
+}
+
+/// Sends `mediate-request` over the connection with the mediator.
+/// Replaces the previous mediation. Pairwise agents created before keep using the routing they were created with until their service is updated.
+pub fn request_mediation(connection: CompletedConnection) -> VcxResult<()> {
+    trace!("mediation::request_mediation >>> connection: {:?}", secret!(connection));
+    debug!("Requesting mediation");
+
+    if let Some(mediation) = Mediation::load()? {
+        warn!("Previous mediation with {:?} is replaced", mediation.connection.data.did_doc.id);
+    }
+
+    let request = MediateRequest::create();
+    connection.agent.send_message(&request, &connection.data.did_doc)?;
+
+    Mediation::new(connection, request.id.to_string()).store()?;
+
+    trace!("mediation::request_mediation <<<");
+    Ok(())
+}
+
+pub fn handle_mediate_grant(connection_id: &str, grant: &MediateGrant) -> VcxResult<()> {
+    trace!("mediation::handle_mediate_grant >>> connection_id: {:?}, grant: {:?}", connection_id, secret!(grant));
+    debug!("Handling received mediate-grant message");
+
+    Mediation::load_for_connection(connection_id)?
+        .grant(grant)?
+        .store()?;
+
+    trace!("mediation::handle_mediate_grant <<<");
+    Ok(())
+}
+
+pub fn handle_mediate_deny(connection_id: &str, deny: &MediateDeny) -> VcxResult<()> {
+    trace!("mediation::handle_mediate_deny >>> connection_id: {:?}, deny: {:?}", connection_id, secret!(deny));
+    debug!("Handling received mediate-deny message");
+
+    Mediation::load_for_connection(connection_id)?
+        .deny(deny)?
+        .store()?;
+
+    trace!("mediation::handle_mediate_deny <<<");
+    Ok(())
+}
+
+pub fn handle_keylist_update_response(connection_id: &str, response: &KeylistUpdateResponse) -> VcxResult<()> {
+    trace!("mediation::handle_keylist_update_response >>> connection_id: {:?}, response: {:?}", connection_id, secret!(response));
+    debug!("Handling received keylist-update-response message");
+
+    Mediation::load_for_connection(connection_id)?
+        .apply_update_response(response)
+        .store()?;
+
+    trace!("mediation::handle_keylist_update_response <<<");
+    Ok(())
+}
+
+/// Registers the recipient key on the granted mediator.
+/// Returns the mediator routing to publish for the key or `None` if there is no granted mediation.
+pub fn add_recipient_key(recipient_key: &str) -> VcxResult<Option<MediatorRouting>> {
+    trace!("mediation::add_recipient_key >>> recipient_key: {:?}", recipient_key);
+
+    let mediation = match Mediation::load()? {
+        Some(ref mediation) if mediation.routing().is_some() => mediation.clone(),
+        _ => return Ok(None)
+    };
+
+    debug!("Registering recipient key on the mediator");
+
+    let mediation = mediation.update_keys(&[recipient_key], &[])?;
+    mediation.store()?;
+
+    let routing = mediation.routing().cloned();
+
+    trace!("mediation::add_recipient_key <<< routing: {:?}", routing);
+    Ok(routing)
+}
+
+/// Returns the mediator routing to publish for the recipient key, registering the key on the granted mediator if it is not registered there yet.
+/// Returns `None` if there is no granted mediation.
+pub fn recipient_key_routing(recipient_key: &str) -> VcxResult<Option<MediatorRouting>> {
+    trace!("mediation::recipient_key_routing >>> recipient_key: {:?}", recipient_key);
+
+    match Mediation::load()? {
+        Some(ref mediation) if mediation.recipient_keys.iter().any(|key| key == recipient_key) => Ok(mediation.routing().cloned()),
+        _ => add_recipient_key(recipient_key)
+    }
+}
+
+/// Removes the recipient key from the mediator key list.
+pub fn remove_recipient_key(recipient_key: &str) -> VcxResult<()> {
+    trace!("mediation::remove_recipient_key >>> recipient_key: {:?}", recipient_key);
+
+    match Mediation::load()? {
+        Some(mediation) if mediation.recipient_keys.iter().any(|key| key == recipient_key) => {
+            debug!("Removing recipient key from the mediator");
+            mediation.update_keys(&[], &[recipient_key])?.store()?;
+        }
+        _ => {
+            warn!("Recipient key {:?} is not registered on the current mediator", recipient_key);
+        }
+    }
+
+    trace!("mediation::remove_recipient_key <<<");
+    Ok(())
+}
+
+/// Returns the state of the current mediation as JSON string.
+pub fn get_info() -> VcxResult<String> {
+    trace!("mediation::get_info >>>");
+
+    let mut mediation = Mediation::load()?
+        .ok_or(VcxError::from_msg(VcxErrorKind::NotReady, "Mediation has not been requested"))?;
+    mediation.connection();
+
+    let info = json!(mediation.info()).to_string();
+
+    trace!("mediation::get_info <<< info: {:?}", info);
+    Ok(info)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::utils::devsetup::{SetupAriesMocks, SetupLibraryWallet};
+    use crate::aries::handlers::connection::agent::AgentInfo;
+    use crate::aries::handlers::connection::states::CompleteState;
+    use crate::aries::messages::thread::Thread;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::aries::messages::mediation::keylist_update_response::{KeylistUpdated, KeylistUpdateResult};
+
+    fn _mediation() -> Mediation {
+        let connection = CompletedConnection {
+            agent: AgentInfo { connection_id: String::from("connection_id"), ..AgentInfo::default() },
+            data: CompleteState {
+                invitation: None,
+                did_doc: _did_doc(),
+                protocols: None,
+                thread: Thread::default(),
+                did_rotation: None,
+                retired_agents: Vec::new(),
+            },
+        };
+        Mediation::new(connection, String::from("request_id"))
+    }
+
+    fn _granted_routing() -> MediatorRouting {
+        MediatorRouting { endpoint: _service_endpoint(), routing_keys: vec![_key_1()] }
+    }
+
+    #[test]
+    fn test_mediation_grant_works() {
+        let _setup = SetupAriesMocks::init();
+
+        let grant = MediateGrant::create()
+            .set_endpoint(_service_endpoint())
+            .set_routing_keys(vec![_did_key_1()])
+            .set_thread_id("request_id");
+
+        let mediation = _mediation().grant(&grant).unwrap();
+        assert_eq!(MediationState::Granted(_granted_routing()), mediation.state);
+        assert_eq!(Some(&_granted_routing()), mediation.routing());
+
+        // grant can be received only once
+        assert_eq!(VcxErrorKind::InvalidState, mediation.grant(&grant).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_mediation_reply_must_relate_to_request() {
+        let _setup = SetupAriesMocks::init();
+
+        let grant = MediateGrant::create().set_thread_id("other_request_id");
+        assert_eq!(VcxErrorKind::InvalidState, _mediation().grant(&grant).unwrap_err().kind());
+
+        let deny = MediateDeny::create();
+        let mediation = _mediation().deny(&deny).unwrap();
+        assert_eq!(MediationState::Denied, mediation.state);
+        assert_eq!(None, mediation.routing());
+    }
+
+    #[test]
+    fn test_mediation_keylist_update_response_drops_rejected_keys() {
+        let _setup = SetupAriesMocks::init();
+
+        let mut mediation = _mediation();
+        mediation.recipient_keys = vec![_key_1(), _key_2()];
+
+        let response = KeylistUpdateResponse::create()
+            .set_updated(vec![
+                KeylistUpdated { recipient_key: _key_1(), action: KeylistUpdateAction::Add, result: KeylistUpdateResult::Success },
+                KeylistUpdated { recipient_key: _key_2(), action: KeylistUpdateAction::Add, result: KeylistUpdateResult::ServerError },
+            ]);
+
+        let mediation = mediation.apply_update_response(&response);
+        assert_eq!(vec![_key_1()], mediation.recipient_keys);
+    }
+
+    #[test]
+    fn test_mediation_info_serialization() {
+        let _setup = SetupAriesMocks::init();
+
+        let mut mediation = _mediation();
+        mediation.state = MediationState::Granted(_granted_routing());
+
+        let expected = json!({
+            "mediator_did": _did_doc().id,
+            "state": "granted",
+            "endpoint": _service_endpoint(),
+            "routing_keys": [_key_1()],
+            "recipient_keys": [],
+            "live_delivery": false
+        });
+        assert_eq!(expected, json!(mediation.info()));
+    }
+
+    #[test]
+    fn test_mediation_refers_to_connection_by_id() {
+        let _setup = SetupLibraryWallet::init();
+
+        let mediation = _mediation();
+        assert_eq!("connection_id", mediation.connection_id);
+
+        // records stored before keep the connection only
+        let mut record = json!(mediation);
+        record.as_object_mut().unwrap().remove("connection_id");
+        wallet::add_record(MEDIATION_RECORD_TYPE, MEDIATION_RECORD_ID, &record.to_string(), None).unwrap();
+
+        assert_eq!(mediation, Mediation::load_for_connection("connection_id").unwrap());
+        assert_eq!(VcxErrorKind::InvalidState, Mediation::load_for_connection("other_connection_id").unwrap_err().kind());
+
+        // the connection is not loaded: its last known state is used
+        assert_eq!(mediation.connection, mediation.clone().connection());
+    }
+}
@@ -0,0 +1,158 @@
+use crate::error::prelude::*;
+use crate::aries::handlers::mediation::Mediation;
+use crate::aries::inbound::replies;
+use crate::aries::messages::a2a::A2AMessage;
+use crate::aries::messages::pickup::status_request::StatusRequest;
+use crate::aries::messages::pickup::delivery_request::DeliveryRequest;
+use crate::aries::messages::pickup::delivery::Delivery;
+use crate::aries::messages::pickup::messages_received::MessagesReceived;
+use crate::aries::messages::pickup::live_delivery_change::LiveDeliveryChange;
+use crate::aries::utils::encryption_envelope::EncryptionEnvelope;
+
+/*
+    Recipient side of Message Pickup protocol 2.0 (Aries RFC 0685).
+
+    Messages queued on the granted mediator are requested with `delivery-request` over the connection with the mediator
+    and passed to the same queue as synchronous replies, so state machines consume them the same way as the messages
+    downloaded from the Agency. Messages are removed from the mediator queue with `messages-received` once they are handled.
+*/
+
+const DELIVERY_LIMIT: u32 = 100;
+
+fn granted_mediation() -> VcxResult<Mediation> {
+    Mediation::load()?
+        .filter(|mediation| mediation.routing().is_some())
+        .ok_or(VcxError::from_msg(VcxErrorKind::NotReady, "Mediation has not been granted"))
+}
+
+fn store_delivered_messages(delivery: &Delivery) -> VcxResult<()> {
+    for delivered_message in delivery.attachments.iter() {
+        let message = delivered_message.packed_message()
+            .and_then(EncryptionEnvelope::open_with_recipient_key);
+
+        match message {
+            Ok((message, recipient_vk)) => replies::insert(&recipient_vk, &delivered_message.id, message)?,
+            Err(err) => warn!("Unable to open message {:?} delivered by the mediator. Err: {:?}", delivered_message.id, err)
+        }
+    }
+    Ok(())
+}
+
+/// Requests the messages queued on the mediator for the recipient key.
+pub fn fetch(recipient_key: &str) -> VcxResult<()> {
+    trace!("pickup::fetch >>> recipient_key: {:?}", recipient_key);
+
+    let mut mediation = granted_mediation()?;
+
+    debug!("Requesting messages from the mediator");
+
+    let request = DeliveryRequest::create()
+        .set_limit(DELIVERY_LIMIT)
+        .set_recipient_key(Some(recipient_key.to_string()));
+
+    let connection = mediation.connection();
+    match connection.agent.send_message_and_wait_reply(&request, &connection.data.did_doc)? {
+        Some(A2AMessage::PickupDelivery(delivery)) => store_delivered_messages(&delivery)?,
+        // mediator replies with status if there are no queued messages
+        Some(A2AMessage::PickupStatus(_)) => {}
+        Some(message) => warn!("Unexpected reply on delivery request: {:?}", secret!(message)),
+        None => debug!("Mediator did not reply on delivery request, messages can be delivered asynchronously")
+    }
+
+    trace!("pickup::fetch <<<");
+    Ok(())
+}
+
+/// Removes the handled messages from the mediator queue.
+pub fn acknowledge(message_ids: Vec<String>) -> VcxResult<()> {
+    trace!("pickup::acknowledge >>> message_ids: {:?}", message_ids);
+
+    let mut mediation = granted_mediation()?;
+
+    debug!("Acknowledging messages received from the mediator");
+
+    let messages_received = MessagesReceived::create()
+        .set_message_id_list(message_ids);
+
+    let connection = mediation.connection();
+    let status = connection.agent.send_message_and_wait_reply(&messages_received, &connection.data.did_doc)?;
+
+    trace!("pickup::acknowledge <<< status: {:?}", secret!(status));
+    Ok(())
+}
+
+/// Handles `delivery` message sent by the mediator asynchronously or in live mode.
+pub fn handle_delivery(delivery: &Delivery) -> VcxResult<()> {
+    trace!("pickup::handle_delivery >>> delivery: {:?}", secret!(delivery));
+    debug!("Handling messages delivered by the mediator");
+
+    store_delivered_messages(delivery)?;
+
+    trace!("pickup::handle_delivery <<<");
+    Ok(())
+}
+
+/// Returns the status of the mediator queue as JSON string.
+pub fn get_status() -> VcxResult<String> {
+    trace!("pickup::get_status >>>");
+
+    let mut mediation = granted_mediation()?;
+
+    debug!("Requesting status of the mediator queue");
+
+    let connection = mediation.connection();
+    let status = match connection.agent.send_message_and_wait_reply(&StatusRequest::create(), &connection.data.did_doc)? {
+        Some(A2AMessage::PickupStatus(status)) => status,
+        message =>
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse,
+                                          format!("Mediator did not return status of the queue. Reply: {:?}", message)))
+    };
+
+    let status = json!(status).to_string();
+
+    trace!("pickup::get_status <<< status: {:?}", status);
+    Ok(status)
+}
+
+/// Switches live delivery mode on the mediator.
+/// In live mode the mediator sends messages on the connection endpoint as soon as they are received.
+pub fn set_live_delivery(live_delivery: bool) -> VcxResult<()> {
+    trace!("pickup::set_live_delivery >>> live_delivery: {:?}", live_delivery);
+
+    let mut mediation = granted_mediation()?;
+
+    debug!("Changing live delivery mode on the mediator");
+
+    let live_delivery_change = LiveDeliveryChange::create()
+        .set_live_delivery(live_delivery);
+
+    let connection = mediation.connection();
+    match connection.agent.send_message_and_wait_reply(&live_delivery_change, &connection.data.did_doc)? {
+        Some(A2AMessage::CommonProblemReport(problem_report)) =>
+            return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported,
+                                          format!("Mediator rejected live delivery change: {:?}", problem_report.description))),
+        Some(A2AMessage::PickupDelivery(delivery)) => store_delivered_messages(&delivery)?,
+        _ => {}
+    }
+
+    mediation.live_delivery = live_delivery;
+    mediation.store()?;
+
+    trace!("pickup::set_live_delivery <<<");
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupAriesMocks;
+
+    #[test]
+    fn test_pickup_requires_granted_mediation() {
+        let _setup = SetupAriesMocks::init();
+
+        assert_eq!(VcxErrorKind::NotReady, fetch("RecipientVerkey").unwrap_err().kind());
+        assert_eq!(VcxErrorKind::NotReady, get_status().unwrap_err().kind());
+        assert_eq!(VcxErrorKind::NotReady, set_live_delivery(true).unwrap_err().kind());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod connection;
 pub mod issuance;
-pub mod proof_presentation;
\ No newline at end of file
+pub mod proof_presentation;
+pub mod mediation;
\ No newline at end of file
@@ -28,9 +28,12 @@ pub struct Prover {
 }
 
 impl Prover {
-    pub fn create(source_id: &str, presentation_request: PresentationRequest) -> VcxResult<Prover> {
+    pub fn create(source_id: &str, mut presentation_request: PresentationRequest) -> VcxResult<Prover> {
         trace!("Prover::create >>> source_id: {}, presentation_request: {:?}", source_id, secret!(presentation_request));
         debug!("Prover {}: Creating Prover state object", source_id);
+
+        presentation_request.resolve_services()?;
+
         Ok(Prover {
             prover_sm: ProverSM::new(presentation_request, source_id.to_string()),
         })
@@ -95,19 +98,25 @@ impl Prover {
             return self.update_state_with_message(message_);
         }
 
-        let agent_info = match self.prover_sm.get_agent_info() {
-            Some(agent_info) => agent_info.clone(),
+        self.prover_sm.refresh_connection();
+
+        let connection = match self.prover_sm.get_connection() {
+            Some(connection) => connection.clone(),
             None => {
                 warn!("Could not update Prover state: no information about Connection.");
                 return Ok(());
             }
         };
 
-        let messages = agent_info.get_messages()?;
-        if let Some((uid, message)) = self.prover_sm.find_message_to_handle(messages) {
-            self.handle_message(message.into())?;
-            agent_info.update_message_status(uid, None)?;
-        };
+        // messages sent before the DID rotation are delivered to the agent of the rotated DID
+        for agent_info in connection.agents() {
+            let messages = agent_info.get_messages()?;
+
+            if let Some((uid, message)) = self.prover_sm.find_message_to_handle(messages) {
+                agent_info.handle_message_once(uid, None, &message, || self.handle_message(message.clone().into()))?;
+                break;
+            }
+        }
 
         Ok(())
     }
@@ -125,6 +134,39 @@ impl Prover {
         Ok(())
     }
 
+    /// Whether the Prover still expects messages over the connection identified by `connection_id`.
+    pub fn is_in_progress_over(&self, connection_id: &str) -> bool {
+        self.prover_sm.has_transitions() &&
+            self.prover_sm.get_agent_info().map_or(false, |agent_info| agent_info.connection_id() == connection_id)
+    }
+
+    /// Whether a message received through the inbound endpoint belongs to this Prover and can be handled in its current state.
+    pub fn accepts_inbound_message(&self, recipient_vk: &str, message: &A2AMessage) -> bool {
+        self.prover_sm.has_transitions() &&
+            self.prover_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) &&
+            self.prover_sm.find_message_to_handle(map!(String::new() => message.clone())).is_some()
+    }
+
+    /// Handles a message received through the inbound endpoint.
+    /// Returns `false` if the message does not belong to this Prover.
+    pub fn handle_inbound_message(&mut self, recipient_vk: &str, message: &A2AMessage) -> VcxResult<bool> {
+        trace!("Prover::handle_inbound_message >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+        if !self.prover_sm.has_transitions() ||
+            !self.prover_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) {
+            return Ok(false);
+        }
+
+        match self.prover_sm.find_message_to_handle(map!(String::new() => message.clone())) {
+            Some((_, message)) => {
+                debug!("Prover {}: Handling inbound message", self.get_source_id());
+                self.handle_message(message.into())?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
     pub fn handle_message(&mut self, message: ProverMessages) -> VcxResult<()> {
         trace!("Prover::handle_message >>> message: {:?}", secret!(message));
         self.step(message)
@@ -157,10 +157,12 @@ impl ProverSM {
         None
     }
 
-    pub fn step(self, message: ProverMessages) -> VcxResult<ProverSM> {
+    pub fn step(mut self, message: ProverMessages) -> VcxResult<ProverSM> {
         trace!("ProverSM::step >>> message: {:?}", secret!(message));
         debug!("Prover: Updating state");
 
+        self.refresh_connection();
+
         let ProverSM { source_id, state } = self;
 
         let state = match state {
@@ -312,6 +314,23 @@ impl ProverSM {
         }
     }
 
+    pub fn get_connection(&self) -> Option<&CompletedConnection> {
+        match self.state {
+            ProverState::PresentationSent(ref state) => Some(&state.connection),
+            ProverState::ProposalSent(ref state) => Some(&state.connection),
+            _ => None,
+        }
+    }
+
+    /// Takes the current state of the Connection the protocol was started over (see `CompletedConnection::refresh`).
+    pub fn refresh_connection(&mut self) {
+        match self.state {
+            ProverState::PresentationSent(ref mut state) => state.connection.refresh(),
+            ProverState::ProposalSent(ref mut state) => state.connection.refresh(),
+            _ => {}
+        }
+    }
+
     pub fn get_agent_info(&self) -> Option<&AgentInfo> {
         match self.state {
             ProverState::RequestReceived(_) => None,
@@ -77,20 +77,25 @@ impl Verifier {
             return self.update_state_with_message(message_);
         }
 
-        let agent_info = match self.verifier_sm.get_agent_info() {
-            Some(agent_info) => agent_info.clone(),
+        self.verifier_sm.refresh_connection();
+
+        let connection = match self.verifier_sm.get_connection() {
+            Some(connection) => connection.clone(),
             None => {
                 warn!("Could not update Verifier state: no information about Connection.");
                 return Ok(self.state());
             }
         };
 
-        let messages = agent_info.get_messages()?;
+        // messages sent before the DID rotation are delivered to the agent of the rotated DID
+        for agent_info in connection.agents() {
+            let messages = agent_info.get_messages()?;
 
-        if let Some((uid, message)) = self.verifier_sm.find_message_to_handle(messages) {
-            self.handle_message(message.into())?;
-            agent_info.update_message_status(uid, None)?;
-        };
+            if let Some((uid, message)) = self.verifier_sm.find_message_to_handle(messages) {
+                agent_info.handle_message_once(uid, None, &message, || self.handle_message(message.clone().into()))?;
+                break;
+            }
+        }
 
         let state = self.state();
 
@@ -114,6 +119,39 @@ impl Verifier {
         Ok(state)
     }
 
+    /// Whether the Verifier still expects messages over the connection identified by `connection_id`.
+    pub fn is_in_progress_over(&self, connection_id: &str) -> bool {
+        self.verifier_sm.has_transitions() &&
+            self.verifier_sm.get_agent_info().map_or(false, |agent_info| agent_info.connection_id() == connection_id)
+    }
+
+    /// Whether a message received through the inbound endpoint belongs to this Verifier and can be handled in its current state.
+    pub fn accepts_inbound_message(&self, recipient_vk: &str, message: &A2AMessage) -> bool {
+        self.verifier_sm.has_transitions() &&
+            self.verifier_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) &&
+            self.verifier_sm.find_message_to_handle(map!(String::new() => message.clone())).is_some()
+    }
+
+    /// Handles a message received through the inbound endpoint.
+    /// Returns `false` if the message does not belong to this Verifier.
+    pub fn handle_inbound_message(&mut self, recipient_vk: &str, message: &A2AMessage) -> VcxResult<bool> {
+        trace!("Verifier::handle_inbound_message >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+        if !self.verifier_sm.has_transitions() ||
+            !self.verifier_sm.get_connection().map_or(false, |connection| connection.is_addressed_to(recipient_vk)) {
+            return Ok(false);
+        }
+
+        match self.verifier_sm.find_message_to_handle(map!(String::new() => message.clone())) {
+            Some((_, message)) => {
+                debug!("Verifier {}: Handling inbound message", self.get_source_id());
+                self.handle_message(message.into())?;
+                Ok(true)
+            }
+            None => Ok(false)
+        }
+    }
+
     pub fn handle_message(&mut self, message: VerifierMessages) -> VcxResult<()> {
         trace!("Verifier::handle_message >>> message: {:?}", message);
         self.step(message)
@@ -148,10 +148,12 @@ impl VerifierSM {
         None
     }
 
-    pub fn step(self, message: VerifierMessages) -> VcxResult<VerifierSM> {
+    pub fn step(mut self, message: VerifierMessages) -> VcxResult<VerifierSM> {
         trace!("VerifierSM::step >>> message: {:?}", secret!(message));
         debug!("verifier updating state");
 
+        self.refresh_connection();
+
         let VerifierSM { source_id, state } = self;
 
         let state = match state {
@@ -265,6 +267,25 @@ impl VerifierSM {
         }
     }
 
+    pub fn get_connection(&self) -> Option<&CompletedConnection> {
+        match self.state {
+            VerifierState::PresentationRequestSent(ref state) => Some(&state.connection),
+            VerifierState::PresentationRequestPrepared(ref state) => state.connection.as_ref(),
+            VerifierState::PresentationProposalReceived(ref state) => state.connection.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Takes the current state of the Connection the protocol was started over (see `CompletedConnection::refresh`).
+    pub fn refresh_connection(&mut self) {
+        match self.state {
+            VerifierState::PresentationRequestSent(ref mut state) => state.connection.refresh(),
+            VerifierState::PresentationRequestPrepared(ref mut state) => if let Some(connection) = state.connection.as_mut() { connection.refresh() },
+            VerifierState::PresentationProposalReceived(ref mut state) => if let Some(connection) = state.connection.as_mut() { connection.refresh() },
+            _ => {}
+        }
+    }
+
     pub fn get_agent_info(&self) -> Option<&AgentInfo> {
         match self.state {
             VerifierState::Initiated(_) => None,
@@ -0,0 +1,118 @@
+pub mod server;
+pub mod replies;
+
+use crate::error::prelude::*;
+use crate::aries::messages::a2a::A2AMessage;
+use crate::aries::inbound::replies;
+use crate::aries::utils::processed_messages;
+use crate::aries::utils::message_history;
+use crate::utils::object_cache::Handle;
+use crate::connection::{self, Connections};
+use crate::issuer_credential::{self, IssuerCredentials};
+use crate::credential::{self, Credentials};
+use crate::proof::{self, Proofs};
+use crate::disclosed_proof::{self, DisclosedProofs};
+
+/// Object which consumed an inbound message.
+#[derive(Debug, PartialEq)]
+pub enum InboundRoute {
+    Connection(Handle<Connections>),
+    IssuerCredential(Handle<IssuerCredentials>),
+    Credential(Handle<Credentials>),
+    Proof(Handle<Proofs>),
+    DisclosedProof(Handle<DisclosedProofs>),
+}
+
+/// Finds the object waiting for `message` among the objects using the pairwise key `recipient_vk`
+/// and updates its state with the message.
+/// Returns `None` if no object can handle the message in its current state or the message has already been processed.
+/// The message no object can handle (e.g. the first message of a new protocol) is stored as pending for `recipient_vk`,
+/// so it is returned together with the messages downloaded for the pairwise agent.
+pub fn route_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<InboundRoute>> {
+    trace!("route_message >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+    debug!("Routing inbound message");
+
+    // messages for objects without a known connection are recorded by the recipient key
+    let connection_id = connection::connection_id_by_recipient_key(recipient_vk)?
+        .unwrap_or_else(|| recipient_vk.to_string());
+
+    // the message is recorded before it is routed, so the message delivered concurrently is not handled twice
+    if !processed_messages::mark_processed(&connection_id, message)? {
+        warn!("Inbound message for recipient {:?} has already been processed", recipient_vk);
+        return Ok(None);
+    }
+
+    message_history::record_received(&connection_id, message);
+
+    let route = _route_message(recipient_vk, message);
+
+    match route {
+        Ok(Some(_)) => message_history::record_processed(&connection_id, message),
+        Ok(None) => {
+            processed_messages::unmark_processed(&connection_id, message)?;
+            _store_unrouted(recipient_vk, message)?;
+        }
+        Err(_) => processed_messages::unmark_processed(&connection_id, message)?
+    }
+
+    route
+}
+
+// the message id is used as the uid of the pending message, so the message delivered again is stored once
+fn _store_unrouted(recipient_vk: &str, message: &A2AMessage) -> VcxResult<()> {
+    match json!(message)["@id"].as_str() {
+        Some(id) if !id.is_empty() => replies::insert(recipient_vk, id, message.clone()),
+        _ => replies::add(recipient_vk, message.clone()).map(|_| ())
+    }
+}
+
+fn _route_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<InboundRoute>> {
+    if let Some(handle) = connection::handle_inbound_message(recipient_vk, message)? {
+        return Ok(Some(InboundRoute::Connection(handle)));
+    }
+    if let Some(handle) = issuer_credential::handle_inbound_message(recipient_vk, message)? {
+        return Ok(Some(InboundRoute::IssuerCredential(handle)));
+    }
+    if let Some(handle) = credential::handle_inbound_message(recipient_vk, message)? {
+        return Ok(Some(InboundRoute::Credential(handle)));
+    }
+    if let Some(handle) = proof::handle_inbound_message(recipient_vk, message)? {
+        return Ok(Some(InboundRoute::Proof(handle)));
+    }
+    if let Some(handle) = disclosed_proof::handle_inbound_message(recipient_vk, message)? {
+        return Ok(Some(InboundRoute::DisclosedProof(handle)));
+    }
+
+    warn!("No object found to handle inbound message for recipient: {:?}", recipient_vk);
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::{SetupAriesMocks, SetupLibraryWallet};
+    use crate::aries::messages::ack::tests::_ack;
+
+    #[test]
+    fn test_route_message_returns_none_for_unknown_recipient() {
+        let _setup = SetupAriesMocks::init();
+
+        let message = A2AMessage::Ack(_ack());
+        assert_eq!(None, route_message("UnknownRecipientVerkey", &message).unwrap());
+    }
+
+    #[test]
+    fn test_route_message_stores_unrouted_message() {
+        let _setup = SetupLibraryWallet::init();
+
+        let recipient_vk = "UnroutedRecipientVerkey";
+        let message = A2AMessage::Ack(_ack());
+
+        assert_eq!(None, route_message(recipient_vk, &message).unwrap());
+        assert_eq!(vec![message.clone()], replies::get(recipient_vk).unwrap().into_iter().map(|(_, message)| message).collect::<Vec<A2AMessage>>());
+
+        // the message delivered again is stored once
+        assert_eq!(None, route_message(recipient_vk, &message).unwrap());
+        assert_eq!(1, replies::get(recipient_vk).unwrap().len());
+    }
+}
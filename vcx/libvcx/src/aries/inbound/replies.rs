@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::aries::messages::a2a::A2AMessage;
+use crate::utils::libindy::wallet::{self, WalletRecord};
+use crate::utils::uuid::uuid;
+
+/*
+    Synchronous replies received in HTTP responses on outbound messages which requested `return_route`
+    and messages picked up from the mediator.
+
+    Replies are stored in the wallet per pairwise verkey they are encrypted for until the state machine owning the
+    pairwise agent consumes them together with the messages downloaded from the Agency, so they survive the restart.
+    Replies nobody consumed within `REPLY_TTL_SECS` are deleted on the next lookup.
+*/
+
+const PENDING_REPLY_RECORD_TYPE: &str = "vcx_pending_reply";
+const REPLY_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct PendingReply {
+    uid: String,
+    recipient_vk: String,
+    message: A2AMessage,
+    /// UTC time the reply was received (unix timestamp)
+    received: i64,
+}
+
+impl PendingReply {
+    fn record_id(recipient_vk: &str, uid: &str) -> String {
+        format!("{}:{}", recipient_vk, uid)
+    }
+
+    fn is_expired(&self) -> bool {
+        Utc::now().timestamp() - self.received > REPLY_TTL_SECS
+    }
+}
+
+/// Stores the synchronous reply addressed to `recipient_vk`. Returns the uid assigned to the reply.
+pub fn add(recipient_vk: &str, message: A2AMessage) -> VcxResult<String> {
+    trace!("replies::add >>> recipient_vk: {:?}, message: {:?}", recipient_vk, secret!(message));
+
+    let uid = uuid();
+    insert(recipient_vk, &uid, message)?;
+    Ok(uid)
+}
+
+/// Stores the message addressed to `recipient_vk` under the given uid.
+/// The message is ignored if there is a pending one with the same uid already.
+pub fn insert(recipient_vk: &str, uid: &str, message: A2AMessage) -> VcxResult<()> {
+    trace!("replies::insert >>> recipient_vk: {:?}, uid: {:?}, message: {:?}", recipient_vk, uid, secret!(message));
+
+    if settings::indy_mocks_enabled() { return Ok(()); }
+
+    let reply = PendingReply {
+        uid: uid.to_string(),
+        recipient_vk: recipient_vk.to_string(),
+        message,
+        received: Utc::now().timestamp(),
+    };
+
+    let tags = json!({"recipient_vk": recipient_vk}).to_string();
+
+    match wallet::add_record(PENDING_REPLY_RECORD_TYPE, &PendingReply::record_id(recipient_vk, uid), &json!(reply).to_string(), Some(&tags)) {
+        Ok(()) => {
+            debug!("Message {} has been stored for recipient {:?}", uid, recipient_vk);
+            Ok(())
+        }
+        Err(ref err) if err.kind() == VcxErrorKind::DuplicationWalletRecord => {
+            debug!("Message {} is already pending for recipient {:?}", uid, recipient_vk);
+            Ok(())
+        }
+        Err(err) => Err(err)
+    }
+}
+
+/// Returns the pending replies addressed to `recipient_vk` keyed by their uids.
+pub fn get(recipient_vk: &str) -> VcxResult<HashMap<String, A2AMessage>> {
+    trace!("replies::get >>> recipient_vk: {:?}", recipient_vk);
+
+    let mut replies = HashMap::new();
+
+    for reply in _query(recipient_vk)? {
+        if reply.is_expired() {
+            warn!("Reply {} for recipient {:?} has not been consumed in time and is deleted", reply.uid, recipient_vk);
+            remove(recipient_vk, &reply.uid)?;
+            continue;
+        }
+        replies.insert(reply.uid, reply.message);
+    }
+
+    Ok(replies)
+}
+
+pub fn get_by_id(recipient_vk: &str, uid: &str) -> VcxResult<Option<A2AMessage>> {
+    trace!("replies::get_by_id >>> recipient_vk: {:?}, uid: {:?}", recipient_vk, uid);
+
+    if settings::indy_mocks_enabled() { return Ok(None); }
+
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    let record = match wallet::get_record(PENDING_REPLY_RECORD_TYPE, &PendingReply::record_id(recipient_vk, uid), &options) {
+        Ok(record) => record,
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    let value = WalletRecord::from_str(&record)?.value
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Pending reply record does not contain value"))?;
+
+    _parse(&value).map(|reply| Some(reply.message))
+}
+
+/// Removes the reply after it has been handled. Returns `false` if there was no such reply.
+pub fn remove(recipient_vk: &str, uid: &str) -> VcxResult<bool> {
+    trace!("replies::remove >>> recipient_vk: {:?}, uid: {:?}", recipient_vk, uid);
+
+    if settings::indy_mocks_enabled() { return Ok(false); }
+
+    match wallet::delete_record(PENDING_REPLY_RECORD_TYPE, &PendingReply::record_id(recipient_vk, uid)) {
+        Ok(()) => Ok(true),
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
+/// Removes all pending replies addressed to `recipient_vk` (e.g. when the pairwise agent is deleted).
+/// Returns the number of deleted replies.
+pub fn purge(recipient_vk: &str) -> VcxResult<usize> {
+    trace!("replies::purge >>> recipient_vk: {:?}", recipient_vk);
+
+    let replies = _query(recipient_vk)?;
+
+    for reply in replies.iter() {
+        remove(recipient_vk, &reply.uid)?;
+    }
+
+    Ok(replies.len())
+}
+
+fn _query(recipient_vk: &str) -> VcxResult<Vec<PendingReply>> {
+    if settings::indy_mocks_enabled() { return Ok(Vec::new()); }
+
+    let query = json!({"recipient_vk": recipient_vk}).to_string();
+    let options = json!({"retrieveRecords": true, "retrieveTotalCount": false, "retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    wallet::search_records(PENDING_REPLY_RECORD_TYPE, &query, &options)?
+        .iter()
+        .map(|record| {
+            let value = record["value"].as_str()
+                .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Pending reply record does not contain value"))?;
+            _parse(value)
+        })
+        .collect()
+}
+
+fn _parse(value: &str) -> VcxResult<PendingReply> {
+    ::serde_json::from_str(value)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize pending reply record: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+    use crate::aries::messages::ack::tests::_ack;
+
+    #[test]
+    fn test_synchronous_replies() {
+        let _setup = SetupLibraryWallet::init();
+
+        let recipient_vk = "RepliesTestRecipientVerkey";
+        assert!(get(recipient_vk).unwrap().is_empty());
+
+        let uid = add(recipient_vk, A2AMessage::Ack(_ack())).unwrap();
+        assert_eq!(map!(uid.clone() => A2AMessage::Ack(_ack())), get(recipient_vk).unwrap());
+        assert_eq!(Some(A2AMessage::Ack(_ack())), get_by_id(recipient_vk, &uid).unwrap());
+        assert_eq!(None, get_by_id("OtherRecipientVerkey", &uid).unwrap());
+        assert!(get("OtherRecipientVerkey").unwrap().is_empty());
+
+        assert!(remove(recipient_vk, &uid).unwrap());
+        assert!(!remove(recipient_vk, &uid).unwrap());
+        assert!(get(recipient_vk).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_insert_ignores_duplicated_messages() {
+        let _setup = SetupLibraryWallet::init();
+
+        let recipient_vk = "InsertTestRecipientVerkey";
+
+        insert(recipient_vk, "message_1", A2AMessage::Ack(_ack())).unwrap();
+        insert(recipient_vk, "message_1", A2AMessage::Ack(_ack())).unwrap();
+        assert_eq!(1, get(recipient_vk).unwrap().len());
+
+        assert!(remove(recipient_vk, "message_1").unwrap());
+        assert!(get(recipient_vk).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expired_replies_are_deleted() {
+        let _setup = SetupLibraryWallet::init();
+
+        let recipient_vk = "ExpiredTestRecipientVerkey";
+
+        let reply = PendingReply {
+            uid: "message_1".to_string(),
+            recipient_vk: recipient_vk.to_string(),
+            message: A2AMessage::Ack(_ack()),
+            received: Utc::now().timestamp() - REPLY_TTL_SECS - 1,
+        };
+        wallet::add_record(PENDING_REPLY_RECORD_TYPE,
+                           &PendingReply::record_id(recipient_vk, &reply.uid),
+                           &json!(reply).to_string(),
+                           Some(&json!({"recipient_vk": recipient_vk}).to_string())).unwrap();
+        add(recipient_vk, A2AMessage::Ack(_ack())).unwrap();
+
+        assert_eq!(1, get(recipient_vk).unwrap().len());
+        assert_eq!(None, get_by_id(recipient_vk, "message_1").unwrap());
+
+        assert_eq!(1, purge(recipient_vk).unwrap());
+        assert!(get(recipient_vk).unwrap().is_empty());
+    }
+}
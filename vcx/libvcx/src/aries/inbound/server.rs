@@ -0,0 +1,376 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::prelude::*;
+use crate::aries::inbound::route_message;
+use crate::aries::utils::encryption_envelope::EncryptionEnvelope;
+
+pub static MAX_INBOUND_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+// connections are served by a fixed number of workers, the ones exceeding the queue are rejected with 503
+const WORKERS_COUNT: usize = 8;
+const MAX_QUEUED_CONNECTIONS: usize = 64;
+
+lazy_static! {
+    static ref INBOUND_ENDPOINT: Mutex<Option<HttpServer>> = Mutex::new(None);
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct HttpRequest {
+    pub(crate) method: String,
+    pub(crate) body: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct HttpResponse {
+    pub(crate) code: u16,
+    pub(crate) reason: &'static str,
+    pub(crate) content_type: Option<&'static str>,
+    pub(crate) body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub(crate) fn empty(code: u16, reason: &'static str) -> HttpResponse {
+        HttpResponse { code, reason, content_type: None, body: Vec::new() }
+    }
+}
+
+/// Handles the request read from the connection or the error occurred on reading it.
+pub(crate) type RequestHandler = fn(VcxResult<HttpRequest>) -> HttpResponse;
+
+/// Minimal HTTP/1.1 server accepting connections on the listener thread and serving them by a fixed worker pool.
+/// Shared by the inbound endpoint and the local agency.
+pub(crate) struct HttpServer {
+    name: &'static str,
+    address: String,
+    shutdown: Arc<AtomicBool>,
+    worker: thread::JoinHandle<()>,
+}
+
+impl HttpServer {
+    pub(crate) fn start(name: &'static str, address: &str, handler: RequestHandler) -> VcxResult<HttpServer> {
+        let listener = TcpListener::bind(address)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::IOError, format!("Cannot bind {} to {}. Err: {:?}", name, address, err)))?;
+        listener.set_nonblocking(true)?;
+        let local_address = listener.local_addr()?.to_string();
+
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let shutdown = shutdown.clone();
+            thread::spawn(move || listen(name, listener, shutdown, handler))
+        };
+
+        info!("{} is listening on {}", name, local_address);
+
+        Ok(HttpServer { name, address: local_address, shutdown, worker })
+    }
+
+    pub(crate) fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub(crate) fn stop(self) -> VcxResult<()> {
+        self.shutdown.store(true, Ordering::SeqCst);
+        self.worker.join()
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, format!("{} worker panicked", self.name)))?;
+        info!("{} on {} has been stopped", self.name, self.address);
+        Ok(())
+    }
+}
+
+/// Starts the embedded HTTP endpoint accepting packed DIDComm envelopes on `address` (e.g. `0.0.0.0:8080`).
+/// Every received message is passed to the Connection, IssuerCredential, Credential, Proof or DisclosedProof
+/// object which uses the recipient key of the envelope.
+///
+/// Returns the address the endpoint is bound to.
+pub fn start(address: &str) -> VcxResult<String> {
+    trace!("inbound::server::start >>> address: {:?}", address);
+
+    let mut endpoint = INBOUND_ENDPOINT.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot start inbound endpoint: lock is poisoned"))?;
+
+    if let Some(ref endpoint) = *endpoint {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidState, format!("Inbound endpoint is already running on {}", endpoint.address())));
+    }
+
+    let server = HttpServer::start("Inbound endpoint", address, handle_request)?;
+    let local_address = server.address().to_string();
+
+    *endpoint = Some(server);
+
+    Ok(local_address)
+}
+
+/// Stops the embedded HTTP endpoint if it is running.
+pub fn stop() -> VcxResult<()> {
+    trace!("inbound::server::stop >>>");
+
+    let endpoint = INBOUND_ENDPOINT.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot stop inbound endpoint: lock is poisoned"))?
+        .take();
+
+    match endpoint {
+        Some(endpoint) => endpoint.stop(),
+        None => Ok(())
+    }
+}
+
+pub fn is_running() -> bool {
+    INBOUND_ENDPOINT.lock()
+        .map(|endpoint| endpoint.is_some())
+        .unwrap_or(false)
+}
+
+fn listen(name: &'static str, listener: TcpListener, shutdown: Arc<AtomicBool>, handler: RequestHandler) {
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(MAX_QUEUED_CONNECTIONS);
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    let workers: Vec<thread::JoinHandle<()>> = (0..WORKERS_COUNT)
+        .map(|_| {
+            let receiver = receiver.clone();
+            thread::spawn(move || serve(name, receiver, handler))
+        })
+        .collect();
+
+    while !shutdown.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(TrySendError::Full(mut stream)) = sender.try_send(stream) {
+                    warn!("{}: too many pending connections, rejecting the connection", name);
+                    let _ = stream.set_nonblocking(false);
+                    write_response(name, &mut stream, HttpResponse::empty(503, "Service Unavailable"));
+                }
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(err) => {
+                error!("{}: cannot accept connection. Err: {:?}", name, err);
+                thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+        }
+    }
+
+    // workers finish the queued connections and exit once the channel is closed
+    drop(sender);
+    for worker in workers {
+        if worker.join().is_err() {
+            error!("{}: worker panicked", name);
+        }
+    }
+}
+
+fn serve(name: &'static str, receiver: Arc<Mutex<Receiver<TcpStream>>>, handler: RequestHandler) {
+    loop {
+        let stream = match receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(_) => return
+        };
+
+        match stream {
+            Ok(stream) => handle_connection(name, stream, handler),
+            Err(_) => return
+        }
+    }
+}
+
+fn handle_connection(name: &'static str, mut stream: TcpStream, handler: RequestHandler) {
+    // accepted sockets inherit non-blocking mode from the listener on some platforms
+    let prepared = stream.set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)));
+
+    let request = prepared.map_err(VcxError::from).and_then(|_| read_request(&mut stream));
+
+    write_response(name, &mut stream, handler(request));
+}
+
+fn write_response(name: &str, stream: &mut TcpStream, response: HttpResponse) {
+    let content_type = response.content_type
+        .map(|content_type| format!("Content-Type: {}\r\n", content_type))
+        .unwrap_or_default();
+
+    let head = format!("HTTP/1.1 {} {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                       response.code, response.reason, content_type, response.body.len());
+
+    if let Err(err) = stream.write_all(head.as_bytes())
+        .and_then(|_| stream.write_all(&response.body))
+        .and_then(|_| stream.flush()) {
+        warn!("{}: cannot write response. Err: {:?}", name, err);
+    }
+}
+
+fn handle_request(request: VcxResult<HttpRequest>) -> HttpResponse {
+    let (code, reason) = match request {
+        Ok(request) => process_request(request),
+        Err(err) => {
+            warn!("Inbound endpoint: cannot read request. Err: {}", err);
+            (400, "Bad Request")
+        }
+    };
+
+    HttpResponse::empty(code, reason)
+}
+
+fn process_request(request: HttpRequest) -> (u16, &'static str) {
+    if request.method != "POST" {
+        return (405, "Method Not Allowed");
+    }
+
+    let (message, recipient_vk) = match EncryptionEnvelope::open_with_recipient_key(request.body) {
+        Ok(opened) => opened,
+        Err(err) => {
+            warn!("Inbound endpoint: cannot open envelope. Err: {}", err);
+            return (400, "Bad Request");
+        }
+    };
+
+    match route_message(&recipient_vk, &message) {
+        Ok(Some(route)) => {
+            debug!("Inbound endpoint: message has been handled by {:?}", route);
+            (202, "Accepted")
+        }
+        // the message no object waits for is stored as pending before it is accepted
+        Ok(None) => (202, "Accepted"),
+        Err(err) => {
+            error!("Inbound endpoint: cannot handle message. Err: {}", err);
+            (500, "Internal Server Error")
+        }
+    }
+}
+
+pub(crate) fn read_request<R: Read>(reader: R) -> VcxResult<HttpRequest> {
+    let mut reader = BufReader::new(reader);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let method = request_line.split_whitespace().next()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidOption, "Invalid HTTP request line"))?
+        .to_uppercase();
+
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            break;
+        }
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        let mut parts = header.splitn(2, ':');
+        let name = parts.next().unwrap_or_default().trim().to_lowercase();
+        let value = parts.next().unwrap_or_default().trim();
+
+        if name == "content-length" {
+            let length = value.parse::<usize>()
+                .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidOption, format!("Invalid Content-Length header. Err: {:?}", err)))?;
+            content_length = Some(length);
+        }
+    }
+
+    let body = match content_length {
+        Some(length) if length > MAX_INBOUND_MESSAGE_SIZE => {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidOption, format!("Message size {} exceeds the limit {}", length, MAX_INBOUND_MESSAGE_SIZE)));
+        }
+        Some(length) => {
+            let mut body = vec![0u8; length];
+            reader.read_exact(&mut body)?;
+            body
+        }
+        None if method == "POST" => {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidOption, "Content-Length header is required"));
+        }
+        None => Vec::new()
+    };
+
+    Ok(HttpRequest { method, body })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupAriesMocks;
+
+    #[test]
+    fn test_read_request() {
+        let _setup = SetupAriesMocks::init();
+
+        let request = "POST /didcomm HTTP/1.1\r\nHost: localhost\r\ncontent-length: 5\r\nContent-Type: application/didcomm-envelope-enc\r\n\r\nhello";
+        assert_eq!(HttpRequest { method: "POST".to_string(), body: b"hello".to_vec() },
+                   read_request(request.as_bytes()).unwrap());
+
+        let request = "GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(HttpRequest { method: "GET".to_string(), body: vec![] },
+                   read_request(request.as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_read_request_fails_for_invalid_request() {
+        let _setup = SetupAriesMocks::init();
+
+        let request = "POST / HTTP/1.1\r\nHost: localhost\r\n\r\nhello";
+        assert_eq!(VcxErrorKind::InvalidOption, read_request(request.as_bytes()).unwrap_err().kind());
+
+        let request = "POST / HTTP/1.1\r\nContent-Length: five\r\n\r\nhello";
+        assert_eq!(VcxErrorKind::InvalidOption, read_request(request.as_bytes()).unwrap_err().kind());
+
+        let request = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_INBOUND_MESSAGE_SIZE + 1);
+        assert_eq!(VcxErrorKind::InvalidOption, read_request(request.as_bytes()).unwrap_err().kind());
+
+        let request = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello";
+        assert_eq!(VcxErrorKind::IOError, read_request(request.as_bytes()).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_inbound_endpoint_start_stop() {
+        let _setup = SetupAriesMocks::init();
+
+        let address = start("127.0.0.1:0").unwrap();
+        assert!(is_running());
+        assert_eq!(VcxErrorKind::InvalidState, start("127.0.0.1:0").unwrap_err().kind());
+
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 405"));
+
+        stop().unwrap();
+        assert!(!is_running());
+    }
+
+    #[test]
+    fn test_inbound_endpoint_serves_more_connections_than_workers() {
+        let _setup = SetupAriesMocks::init();
+
+        let address = start("127.0.0.1:0").unwrap();
+
+        let clients: Vec<thread::JoinHandle<String>> = (0..WORKERS_COUNT * 2)
+            .map(|_| {
+                let address = address.clone();
+                thread::spawn(move || {
+                    let mut stream = TcpStream::connect(&address).unwrap();
+                    stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+                    let mut response = String::new();
+                    stream.read_to_string(&mut response).unwrap();
+                    response
+                })
+            })
+            .collect();
+
+        for client in clients {
+            assert!(client.join().unwrap().starts_with("HTTP/1.1 405"));
+        }
+
+        stop().unwrap();
+    }
+}
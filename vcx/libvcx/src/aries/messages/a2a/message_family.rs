@@ -4,6 +4,9 @@ use crate::aries::messages::a2a::protocol_registry::Actors;
 pub enum MessageTypeFamilies {
     Routing,
     Connections,
+    DidExchange,
+    DidRotate,
+    DidDocUpdate,
     Notification,
     Signature,
     CredentialIssuance,
@@ -16,6 +19,9 @@ pub enum MessageTypeFamilies {
     QuestionAnswer,
     Committedanswer,
     InviteAction,
+    CoordinateMediation,
+    MessagePickup,
+    RevocationNotification,
     Unknown(String)
 }
 
@@ -27,6 +33,9 @@ impl MessageTypeFamilies {
         match self {
             MessageTypeFamilies::Routing => "1.0",
             MessageTypeFamilies::Connections => "1.0",
+            MessageTypeFamilies::DidExchange => "1.0",
+            MessageTypeFamilies::DidRotate => "1.0",
+            MessageTypeFamilies::DidDocUpdate => "1.0",
             MessageTypeFamilies::Notification => "1.0",
             MessageTypeFamilies::Signature => "1.0",
             MessageTypeFamilies::CredentialIssuance => "1.0",
@@ -39,6 +48,9 @@ impl MessageTypeFamilies {
             MessageTypeFamilies::QuestionAnswer => "1.0",
             MessageTypeFamilies::Committedanswer => "1.0",
             MessageTypeFamilies::InviteAction => "0.9",
+            MessageTypeFamilies::CoordinateMediation => "1.0",
+            MessageTypeFamilies::MessagePickup => "2.0",
+            MessageTypeFamilies::RevocationNotification => "2.0",
             MessageTypeFamilies::Unknown(_) => "1.0"
         }
     }
@@ -57,9 +69,15 @@ impl MessageTypeFamilies {
             MessageTypeFamilies::Basicmessage |
             MessageTypeFamilies::QuestionAnswer |
             MessageTypeFamilies::Committedanswer |
+            MessageTypeFamilies::DidDocUpdate |
             MessageTypeFamilies::Unknown(_) => format!("{}/{}/{}", Self::DID, self.to_string(), self.version().to_string()),
+            MessageTypeFamilies::DidExchange |
+            MessageTypeFamilies::DidRotate |
             MessageTypeFamilies::Outofband |
-            MessageTypeFamilies::InviteAction => format!("{}/{}/{}", Self::ENDPOINT, self.to_string(), self.version().to_string()),
+            MessageTypeFamilies::InviteAction |
+            MessageTypeFamilies::CoordinateMediation |
+            MessageTypeFamilies::MessagePickup |
+            MessageTypeFamilies::RevocationNotification => format!("{}/{}/{}", Self::ENDPOINT, self.to_string(), self.version().to_string()),
         }
     }
 
@@ -67,6 +85,9 @@ impl MessageTypeFamilies {
         match self {
             MessageTypeFamilies::Routing => None,
             MessageTypeFamilies::Connections => Some((Some(Actors::Inviter), Some(Actors::Invitee))),
+            MessageTypeFamilies::DidExchange => Some((Some(Actors::Inviter), Some(Actors::Invitee))),
+            MessageTypeFamilies::DidRotate => None,
+            MessageTypeFamilies::DidDocUpdate => None,
             MessageTypeFamilies::Notification => None,
             MessageTypeFamilies::Signature => None,
             MessageTypeFamilies::CredentialIssuance => Some((Some(Actors::Issuer), Some(Actors::Holder))),
@@ -79,6 +100,9 @@ impl MessageTypeFamilies {
             MessageTypeFamilies::QuestionAnswer => Some((None, Some(Actors::Receiver))),
             MessageTypeFamilies::Committedanswer => Some((None, Some(Actors::Receiver))),
             MessageTypeFamilies::InviteAction => Some((Some(Actors::Inviter), Some(Actors::Invitee))),
+            MessageTypeFamilies::CoordinateMediation => None,
+            MessageTypeFamilies::MessagePickup => None,
+            MessageTypeFamilies::RevocationNotification => Some((Some(Actors::Issuer), Some(Actors::Holder))),
             MessageTypeFamilies::Unknown(_) => None
         }
     }
@@ -88,7 +112,10 @@ impl From<String> for MessageTypeFamilies {
     fn from(family: String) -> Self {
         match family.as_str() {
             "routing" => MessageTypeFamilies::Routing,
-            "didexchange" => MessageTypeFamilies::Connections,
+            "connections" => MessageTypeFamilies::Connections,
+            "didexchange" => MessageTypeFamilies::DidExchange,
+            "did-rotate" => MessageTypeFamilies::DidRotate,
+            "did-doc-update" => MessageTypeFamilies::DidDocUpdate,
             "signature" => MessageTypeFamilies::Signature,
             "notification" => MessageTypeFamilies::Notification,
             "issue-credential" => MessageTypeFamilies::CredentialIssuance,
@@ -101,6 +128,9 @@ impl From<String> for MessageTypeFamilies {
             "questionanswer" => MessageTypeFamilies::QuestionAnswer,
             "committedanswer" => MessageTypeFamilies::Committedanswer,
             "invite-action" => MessageTypeFamilies::InviteAction,
+            "coordinate-mediation" => MessageTypeFamilies::CoordinateMediation,
+            "messagepickup" => MessageTypeFamilies::MessagePickup,
+            "revocation_notification" => MessageTypeFamilies::RevocationNotification,
             _ => MessageTypeFamilies::Unknown(family)
         }
     }
@@ -110,7 +140,10 @@ impl ToString for MessageTypeFamilies {
     fn to_string(&self) -> String {
         match self {
             MessageTypeFamilies::Routing => "routing".to_string(),
-            MessageTypeFamilies::Connections => "didexchange".to_string(),
+            MessageTypeFamilies::Connections => "connections".to_string(),
+            MessageTypeFamilies::DidExchange => "didexchange".to_string(),
+            MessageTypeFamilies::DidRotate => "did-rotate".to_string(),
+            MessageTypeFamilies::DidDocUpdate => "did-doc-update".to_string(),
             MessageTypeFamilies::Notification => "notification".to_string(),
             MessageTypeFamilies::Signature => "signature".to_string(),
             MessageTypeFamilies::CredentialIssuance => "issue-credential".to_string(),
@@ -123,6 +156,9 @@ impl ToString for MessageTypeFamilies {
             MessageTypeFamilies::QuestionAnswer => "questionanswer".to_string(),
             MessageTypeFamilies::Committedanswer => "committedanswer".to_string(),
             MessageTypeFamilies::InviteAction => "invite-action".to_string(),
+            MessageTypeFamilies::CoordinateMediation => "coordinate-mediation".to_string(),
+            MessageTypeFamilies::MessagePickup => "messagepickup".to_string(),
+            MessageTypeFamilies::RevocationNotification => "revocation_notification".to_string(),
             MessageTypeFamilies::Unknown(family) => family.to_string()
         }
     }
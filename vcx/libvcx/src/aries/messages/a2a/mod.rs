@@ -12,6 +12,11 @@ use crate::aries::messages::connection::invite::Invitation;
 use crate::aries::messages::connection::request::Request;
 use crate::aries::messages::connection::response::SignedResponse;
 use crate::aries::messages::connection::problem_report::ProblemReport as ConnectionProblemReport;
+use crate::aries::messages::didexchange::request::Request as DidExchangeRequest;
+use crate::aries::messages::didexchange::response::Response as DidExchangeResponse;
+use crate::aries::messages::didexchange::complete::Complete as DidExchangeComplete;
+use crate::aries::messages::did_rotate::rotate::Rotate as DidRotate;
+use crate::aries::messages::did_doc_update::update::Update as DidDocUpdate;
 use crate::aries::messages::trust_ping::ping::Ping;
 use crate::aries::messages::trust_ping::ping_response::PingResponse;
 use crate::aries::messages::forward::Forward;
@@ -43,18 +48,46 @@ use crate::aries::messages::committedanswer::answer::Answer as CommitedAnswer;
 
 use crate::aries::messages::invite_action::invite::Invite as InviteForAction;
 
+use crate::aries::messages::mediation::mediate_request::MediateRequest;
+use crate::aries::messages::mediation::mediate_grant::MediateGrant;
+use crate::aries::messages::mediation::mediate_deny::MediateDeny;
+use crate::aries::messages::mediation::keylist_update::KeylistUpdate;
+use crate::aries::messages::mediation::keylist_update_response::KeylistUpdateResponse;
+use crate::aries::messages::pickup::status_request::StatusRequest;
+use crate::aries::messages::pickup::status::Status;
+use crate::aries::messages::pickup::delivery_request::DeliveryRequest;
+use crate::aries::messages::pickup::delivery::Delivery;
+use crate::aries::messages::pickup::messages_received::MessagesReceived;
+use crate::aries::messages::pickup::live_delivery_change::LiveDeliveryChange;
+use crate::aries::messages::revocation_notification::revoke::RevocationNotification;
+
 #[derive(Debug, PartialEq, Clone, Serialize)]
 #[serde(untagged)]
 pub enum A2AMessage {
     /// routing
     Forward(Forward),
 
-    /// DID Exchange
+    /// connections
     ConnectionInvitation(Invitation),
     ConnectionRequest(Request),
     ConnectionResponse(SignedResponse),
     ConnectionProblemReport(ConnectionProblemReport),
 
+    /// DID Exchange
+    DidExchangeRequest(DidExchangeRequest),
+    DidExchangeResponse(DidExchangeResponse),
+    DidExchangeComplete(DidExchangeComplete),
+
+    /// DID Rotate
+    DidRotate(DidRotate),
+    DidRotateAck(Ack),
+    DidRotateProblemReport(CommonProblemReport),
+
+    /// DIDDoc update
+    DidDocUpdate(DidDocUpdate),
+    DidDocUpdateAck(Ack),
+    DidDocUpdateProblemReport(CommonProblemReport),
+
     /// trust ping
     Ping(Ping),
     PingResponse(PingResponse),
@@ -103,6 +136,24 @@ pub enum A2AMessage {
     InviteForActionAck(Ack),
     InviteForActionReject(CommonProblemReport),
 
+    /// coordinate-mediation
+    MediateRequest(MediateRequest),
+    MediateGrant(MediateGrant),
+    MediateDeny(MediateDeny),
+    KeylistUpdate(KeylistUpdate),
+    KeylistUpdateResponse(KeylistUpdateResponse),
+
+    /// messagepickup
+    PickupStatusRequest(StatusRequest),
+    PickupStatus(Status),
+    PickupDeliveryRequest(DeliveryRequest),
+    PickupDelivery(Delivery),
+    PickupMessagesReceived(MessagesReceived),
+    PickupLiveDeliveryChange(LiveDeliveryChange),
+
+    /// revocation_notification
+    RevocationNotification(RevocationNotification),
+
     /// Any Raw Message
     Generic(Value),
 }
@@ -139,6 +190,56 @@ impl<'de> Deserialize<'de> for A2AMessage {
                     .map(|msg| A2AMessage::ConnectionResponse(msg))
                     .map_err(de::Error::custom)
             }
+            (MessageTypeFamilies::DidExchange, A2AMessage::CONNECTION_REQUEST) => {
+                DidExchangeRequest::deserialize(value)
+                    .map(|msg| A2AMessage::DidExchangeRequest(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidExchange, A2AMessage::CONNECTION_RESPONSE) => {
+                DidExchangeResponse::deserialize(value)
+                    .map(|msg| A2AMessage::DidExchangeResponse(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidExchange, A2AMessage::DIDEXCHANGE_COMPLETE) => {
+                DidExchangeComplete::deserialize(value)
+                    .map(|msg| A2AMessage::DidExchangeComplete(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidExchange, A2AMessage::CONNECTION_PROBLEM_REPORT) => {
+                ConnectionProblemReport::deserialize(value)
+                    .map(|msg| A2AMessage::ConnectionProblemReport(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidRotate, A2AMessage::DID_ROTATE) => {
+                DidRotate::deserialize(value)
+                    .map(|msg| A2AMessage::DidRotate(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidRotate, A2AMessage::ACK) => {
+                Ack::deserialize(value)
+                    .map(|msg| A2AMessage::DidRotateAck(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidRotate, A2AMessage::PROBLEM_REPORT) => {
+                CommonProblemReport::deserialize(value)
+                    .map(|msg| A2AMessage::DidRotateProblemReport(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidDocUpdate, A2AMessage::DID_DOC_UPDATE) => {
+                DidDocUpdate::deserialize(value)
+                    .map(|msg| A2AMessage::DidDocUpdate(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidDocUpdate, A2AMessage::ACK) => {
+                Ack::deserialize(value)
+                    .map(|msg| A2AMessage::DidDocUpdateAck(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::DidDocUpdate, A2AMessage::PROBLEM_REPORT) => {
+                CommonProblemReport::deserialize(value)
+                    .map(|msg| A2AMessage::DidDocUpdateProblemReport(msg))
+                    .map_err(de::Error::custom)
+            }
             (MessageTypeFamilies::TrustPing, A2AMessage::PING) => {
                 Ping::deserialize(value)
                     .map(|msg| A2AMessage::Ping(msg))
@@ -284,6 +385,66 @@ impl<'de> Deserialize<'de> for A2AMessage {
                     .map(|msg| A2AMessage::InviteForActionReject(msg))
                     .map_err(de::Error::custom)
             }
+            (MessageTypeFamilies::CoordinateMediation, A2AMessage::MEDIATE_REQUEST) => {
+                MediateRequest::deserialize(value)
+                    .map(|msg| A2AMessage::MediateRequest(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::CoordinateMediation, A2AMessage::MEDIATE_GRANT) => {
+                MediateGrant::deserialize(value)
+                    .map(|msg| A2AMessage::MediateGrant(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::CoordinateMediation, A2AMessage::MEDIATE_DENY) => {
+                MediateDeny::deserialize(value)
+                    .map(|msg| A2AMessage::MediateDeny(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::CoordinateMediation, A2AMessage::KEYLIST_UPDATE) => {
+                KeylistUpdate::deserialize(value)
+                    .map(|msg| A2AMessage::KeylistUpdate(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::CoordinateMediation, A2AMessage::KEYLIST_UPDATE_RESPONSE) => {
+                KeylistUpdateResponse::deserialize(value)
+                    .map(|msg| A2AMessage::KeylistUpdateResponse(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::MessagePickup, A2AMessage::PICKUP_STATUS_REQUEST) => {
+                StatusRequest::deserialize(value)
+                    .map(|msg| A2AMessage::PickupStatusRequest(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::MessagePickup, A2AMessage::PICKUP_STATUS) => {
+                Status::deserialize(value)
+                    .map(|msg| A2AMessage::PickupStatus(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::MessagePickup, A2AMessage::PICKUP_DELIVERY_REQUEST) => {
+                DeliveryRequest::deserialize(value)
+                    .map(|msg| A2AMessage::PickupDeliveryRequest(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::MessagePickup, A2AMessage::PICKUP_DELIVERY) => {
+                Delivery::deserialize(value)
+                    .map(|msg| A2AMessage::PickupDelivery(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::MessagePickup, A2AMessage::PICKUP_MESSAGES_RECEIVED) => {
+                MessagesReceived::deserialize(value)
+                    .map(|msg| A2AMessage::PickupMessagesReceived(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::MessagePickup, A2AMessage::PICKUP_LIVE_DELIVERY_CHANGE) => {
+                LiveDeliveryChange::deserialize(value)
+                    .map(|msg| A2AMessage::PickupLiveDeliveryChange(msg))
+                    .map_err(de::Error::custom)
+            }
+            (MessageTypeFamilies::RevocationNotification, A2AMessage::REVOKE) => {
+                RevocationNotification::deserialize(value)
+                    .map(|msg| A2AMessage::RevocationNotification(msg))
+                    .map_err(de::Error::custom)
+            }
             (_, _) => {
                 warn!("Unexpected @type field: {}", value["@type"]);
                 Ok(A2AMessage::Generic(value))
@@ -335,6 +496,9 @@ impl A2AMessage {
     pub const CONNECTION_REQUEST: &'static str = "request";
     pub const CONNECTION_RESPONSE: &'static str = "response";
     pub const CONNECTION_PROBLEM_REPORT: &'static str = "problem_report";
+    pub const DIDEXCHANGE_COMPLETE: &'static str = "complete";
+    pub const DID_ROTATE: &'static str = "rotate";
+    pub const DID_DOC_UPDATE: &'static str = "update";
     pub const PING: &'static str = "ping";
     pub const PING_RESPONSE: &'static str = "ping_response";
     pub const ACK: &'static str = "ack";
@@ -355,4 +519,16 @@ impl A2AMessage {
     pub const QUESTION: &'static str = "question";
     pub const ANSWER: &'static str = "answer";
     pub const INVITE_FOR_ACTION: &'static str = "invite";
+    pub const MEDIATE_REQUEST: &'static str = "mediate-request";
+    pub const MEDIATE_GRANT: &'static str = "mediate-grant";
+    pub const MEDIATE_DENY: &'static str = "mediate-deny";
+    pub const KEYLIST_UPDATE: &'static str = "keylist-update";
+    pub const KEYLIST_UPDATE_RESPONSE: &'static str = "keylist-update-response";
+    pub const PICKUP_STATUS_REQUEST: &'static str = "status-request";
+    pub const PICKUP_STATUS: &'static str = "status";
+    pub const PICKUP_DELIVERY_REQUEST: &'static str = "delivery-request";
+    pub const PICKUP_DELIVERY: &'static str = "delivery";
+    pub const PICKUP_MESSAGES_RECEIVED: &'static str = "messages-received";
+    pub const PICKUP_LIVE_DELIVERY_CHANGE: &'static str = "live-delivery-change";
+    pub const REVOKE: &'static str = "revoke";
 }
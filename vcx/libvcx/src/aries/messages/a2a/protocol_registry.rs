@@ -32,6 +32,9 @@ impl ProtocolRegistry {
                 family @ MessageTypeFamilies::ReportProblem |
                 family @ MessageTypeFamilies::Notification |
                 family @ MessageTypeFamilies::Connections |
+                family @ MessageTypeFamilies::DidExchange |
+                family @ MessageTypeFamilies::DidRotate |
+                family @ MessageTypeFamilies::DidDocUpdate |
                 family @ MessageTypeFamilies::CredentialIssuance |
                 family @ MessageTypeFamilies::PresentProof |
                 family @ MessageTypeFamilies::TrustPing |
@@ -40,8 +43,12 @@ impl ProtocolRegistry {
                 family @ MessageTypeFamilies::QuestionAnswer |
                 family @ MessageTypeFamilies::Committedanswer |
                 family @ MessageTypeFamilies::InviteAction |
+                family @ MessageTypeFamilies::RevocationNotification |
                 family @ MessageTypeFamilies::DiscoveryFeatures => registry.add_protocol(&actors, family),
                 MessageTypeFamilies::Signature => {}
+                // only the recipient side of mediation is supported, so there is nothing to disclose
+                MessageTypeFamilies::CoordinateMediation |
+                MessageTypeFamilies::MessagePickup => {}
                 MessageTypeFamilies::Unknown(_) => {}
             }
         }
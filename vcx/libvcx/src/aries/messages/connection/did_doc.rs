@@ -9,6 +9,8 @@ use rust_base58::{FromBase58, ToBase58};
 pub const CONTEXT: &str = "https://w3id.org/did/v1";
 pub const KEY_TYPE: &str = "Ed25519VerificationKey2018";
 pub const KEY_AUTHENTICATION_TYPE: &str = "Ed25519SignatureAuthentication2018";
+pub const KEY_AGREEMENT_TYPE: &str = "X25519KeyAgreementKey2019";
+pub const KEY_AGREEMENT_ID_PREFIX: &str = "key-agreement-";
 pub const SERVICE_SUFFIX: &str = "indy";
 pub const SERVICE_TYPE: &str = "IndyAgent";
 pub const SERVICE_ID: &str = "#inline";
@@ -26,6 +28,10 @@ pub struct DidDoc {
     pub public_key: Vec<Ed25519PublicKey>, // TODO: A DID document MAY include a publicKey property??? (https://w3c.github.io/did-core/#public-keys)
     #[serde(default)]
     pub authentication: Vec<Authentication>,
+    #[serde(default)]
+    #[serde(rename = "keyAgreement")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub key_agreement: Vec<X25519PublicKey>, // keys used for DIDComm v2 envelopes
     pub service: Vec<Service>,
 }
 
@@ -39,6 +45,16 @@ pub struct Ed25519PublicKey {
     pub public_key_base_58: String,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct X25519PublicKey {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub controller: String,
+    #[serde(rename = "publicKeyBase58")]
+    pub public_key_base_58: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Authentication {
     #[serde(rename = "type")]
@@ -71,6 +87,7 @@ impl Default for DidDoc {
             id: String::new(),
             public_key: vec![],
             authentication: vec![],
+            key_agreement: vec![],
             service: vec![Service::default()],
         }
     }
@@ -151,6 +168,31 @@ impl DidDoc {
             });
     }
 
+    pub fn set_key_agreement_keys(&mut self, keys: Vec<String>) {
+        self.key_agreement = keys
+            .into_iter()
+            .enumerate()
+            .map(|(index, key)| {
+                let key_id = format!("{}{}", KEY_AGREEMENT_ID_PREFIX, index + 1);
+
+                X25519PublicKey {
+                    id: DidDoc::_build_key_reference(&self.id, &key_id),
+                    type_: String::from(KEY_AGREEMENT_TYPE),
+                    controller: self.id.clone(),
+                    public_key_base_58: key,
+                }
+            })
+            .collect();
+    }
+
+    pub fn key_agreement_keys(&self) -> Vec<String> {
+        self.key_agreement
+            .iter()
+            .filter(|key| key.type_ == KEY_AGREEMENT_TYPE)
+            .map(|key| key.public_key_base_58.clone())
+            .collect()
+    }
+
     pub fn validate(&self) -> VcxResult<()> {
         trace!("DidDoc::validate >>> {:?}", secret!(self));
 
@@ -265,7 +307,7 @@ impl DidDoc {
         }
     }
 
-    fn key_for_reference(&self, key_reference: &str) -> String {
+    pub fn key_for_reference(&self, key_reference: &str) -> String {
         let id = DidDoc::_parse_key_reference(key_reference);
 
         self.public_key.iter().find(|key_| key_.id == id.to_string() || key_.public_key_base_58 == id.to_string() || key_.id == key_reference)
@@ -331,6 +373,31 @@ impl Service {
         self
     }
 
+    /// Service given by the DID it has to be resolved from (e.g. Out-of-Band invitation service referencing a public DID).
+    pub fn did_reference(did: &str) -> Self {
+        Service {
+            id: did.to_string(),
+            type_: String::new(),
+            ..Service::default()
+        }
+    }
+
+    pub fn is_did_reference(&self) -> bool {
+        self.id.starts_with("did:") && self.service_endpoint.is_empty() && self.recipient_keys.is_empty()
+    }
+
+    /// Service of the resolved DIDDoc with key references replaced by keys.
+    pub fn from_did_doc(did_doc: &DidDoc) -> Self {
+        let (recipient_keys, routing_keys) = did_doc.resolve_keys();
+
+        Service::create()
+            .set_id(did_doc.id.clone())
+            .set_type(did_doc.service.get(0).map(|service| service.type_.clone()).unwrap_or_default())
+            .set_service_endpoint(did_doc.get_endpoint())
+            .set_recipient_keys(recipient_keys)
+            .set_routing_keys(routing_keys)
+    }
+
     // extract key from did:key as per method spec: https://w3c-ccg.github.io/did-method-key/
     fn extract_key_from_did_key(key: &str) -> VcxResult<String> {
         debug!("Extracting public key from key reference: {}", key);
@@ -405,8 +472,8 @@ impl From<Service> for Invitation {
 impl From<OutofbandInvitation> for DidDoc {
     fn from(invite: OutofbandInvitation) -> DidDoc {
         match invite {
-            OutofbandInvitation::V10(mut invitation) => DidDoc::from(invitation.service.swap_remove(0)),
-            OutofbandInvitation::V11(mut invitation) => DidDoc::from(invitation.services.swap_remove(0)),
+            OutofbandInvitation::V10(invitation) => invitation.service.into_iter().next().map(DidDoc::from).unwrap_or_default(),
+            OutofbandInvitation::V11(invitation) => invitation.services.into_iter().next().map(DidDoc::from).unwrap_or_default(),
         }
     }
 }
@@ -429,6 +496,10 @@ pub mod tests {
         String::from("3LYuxJBJkngDbvJj4zjx13DBUdZ2P96eNybwd2n9L9AU")
     }
 
+    pub fn _key_agreement_key() -> String {
+        String::from("7QHA5JA7SWe8iRK5JBVjyGWV6sue4yCQdYjG7YgHyACN")
+    }
+
     pub fn _did_key_1() -> String { String::from("did:key:z6MkukGVb3mRvTu1msArDKY9UwxeZFGjmwnCKtdQttr4Fk6i")}
 
     pub fn _did_key_2() -> String { String::from("did:key:z6Mkw7FfEGiwh6YQbCLTNbJWAYR8boGNMt7PCjh35GLNxmMo")}
@@ -505,6 +576,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_reference_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_reference_1()],
@@ -524,6 +596,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_reference_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_1()],
@@ -543,6 +616,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_reference_1() }
             ],
+            key_agreement: vec![],
             service: vec![_service_did_formatted()],
         }
     }
@@ -557,6 +631,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_reference_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_reference_1()],
@@ -578,6 +653,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_reference_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_1()],
@@ -599,6 +675,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_1()],
@@ -618,6 +695,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_1()],
@@ -637,6 +715,7 @@ pub mod tests {
             authentication: vec![
                 Authentication { type_: KEY_AUTHENTICATION_TYPE.to_string(), public_key: _key_reference_1() }
             ],
+            key_agreement: vec![],
             service: vec![Service {
                 service_endpoint: _service_endpoint(),
                 recipient_keys: vec![_key_1()],
@@ -656,6 +735,24 @@ pub mod tests {
         assert_eq!(_did_doc(), did_doc);
     }
 
+    #[test]
+    fn test_did_doc_key_agreement_keys_works() {
+        let mut did_doc: DidDoc = DidDoc::default();
+        did_doc.set_id(_id());
+        did_doc.set_service_endpoint(_service_endpoint());
+        did_doc.set_keys(_recipient_keys(), _routing_keys());
+        assert!(did_doc.key_agreement_keys().is_empty());
+        assert!(!json!(did_doc).to_string().contains("keyAgreement"));
+
+        did_doc.set_key_agreement_keys(vec![_key_agreement_key()]);
+        assert_eq!(vec![_key_agreement_key()], did_doc.key_agreement_keys());
+        assert_eq!(format!("{}#key-agreement-1", _id()), did_doc.key_agreement[0].id);
+
+        let did_doc: DidDoc = ::serde_json::from_str(&json!(did_doc).to_string()).unwrap();
+        assert_eq!(vec![_key_agreement_key()], did_doc.key_agreement_keys());
+        did_doc.validate().unwrap();
+    }
+
     #[test]
     fn test_did_doc_validate_works() {
         _did_doc().validate().unwrap();
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rust_base58::{FromBase58, ToBase58};
+use sha2::{Sha256, Digest};
+
+use crate::error::prelude::*;
+use crate::aries::messages::connection::did_doc::{DidDoc, Service, SERVICE_ID};
+use crate::aries::messages::didexchange::did_doc_attachment::did_key;
+
+/*
+    Peer DIDs of pairwise connections:
+    https://identity.foundation/peer-did-method-spec/
+
+    did:peer:2 - keys and service are encoded into the DID itself:
+        did:peer:2.V{Ed25519 key}.E{X25519 key}.S{base64url service}
+    did:peer:4 - DIDDoc without `id` is encoded into the long form DID, prefixed with the hash of the encoded document:
+        did:peer:4{hash}:{encoded document}
+        The short form `did:peer:4{hash}` is resolved with the long form resolved before.
+
+    Resolved DIDDocs have the same layout as the ones built from pairwise agents,
+    so keys of peer DIDs are used for message packing the same way as keys of unqualified DIDs.
+*/
+
+pub const PEER_DID_PREFIX: &str = "did:peer:";
+const PEER_DID_2_PREFIX: &str = "did:peer:2";
+const PEER_DID_4_PREFIX: &str = "did:peer:4";
+
+const PURPOSE_VERIFICATION: char = 'V';
+const PURPOSE_ENCRYPTION: char = 'E';
+const PURPOSE_SERVICE: char = 'S';
+
+const MULTIBASE_BASE58_BTC: char = 'z';
+const ED25519_MULTICODEC_PREFIX: [u8; 2] = [0xed, 0x01];
+const X25519_MULTICODEC_PREFIX: [u8; 2] = [0xec, 0x01];
+const JSON_MULTICODEC_PREFIX: [u8; 2] = [0x02, 0x00];
+const SHA256_MULTIHASH_PREFIX: [u8; 2] = [0x12, 0x20];
+
+const SERVICE_TYPE_DIDCOMM_MESSAGING: &str = "DIDCommMessaging";
+const SERVICE_TYPE_DIDCOMM_MESSAGING_ABBREVIATION: &str = "dm";
+const SERVICE_ACCEPT_DIDCOMM_V1: &str = "didcomm/aip2;env=rfc19";
+
+lazy_static! {
+    // long forms of the resolved did:peer:4 DIDs keyed by their short forms
+    static ref PEER_DID_4_LONG_FORMS: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+/// Peer DID method numeric algorithm used to generate pairwise DIDs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PeerDidNumalgo {
+    /// Keys and service are encoded into the DID.
+    #[serde(rename = "did:peer:2")]
+    Numalgo2,
+    /// Hash of the DIDDoc and the DIDDoc itself are encoded into the DID (long form).
+    #[serde(rename = "did:peer:4")]
+    Numalgo4,
+}
+
+/// Abbreviated service element of did:peer:2
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct PeerDidService {
+    #[serde(rename = "t")]
+    type_: String,
+    #[serde(rename = "s")]
+    endpoint: PeerDidServiceEndpoint,
+    #[serde(rename = "r")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    routing_keys: Vec<String>,
+    #[serde(rename = "a")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    accept: Vec<String>,
+}
+
+// DIDComm v2 services put routing keys into the endpoint object
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+enum PeerDidServiceEndpoint {
+    Uri(String),
+    Object {
+        uri: String,
+        #[serde(rename = "r")]
+        #[serde(default)]
+        routing_keys: Vec<String>,
+        #[serde(rename = "a")]
+        #[serde(default)]
+        accept: Vec<String>,
+    },
+}
+
+pub fn is_peer_did(did: &str) -> bool {
+    did.starts_with(PEER_DID_PREFIX)
+}
+
+/// Generates peer DID for the DIDDoc of a pairwise agent and returns the DIDDoc resolved for this DID.
+pub fn generate(did_doc: &DidDoc, numalgo: PeerDidNumalgo) -> VcxResult<DidDoc> {
+    trace!("did_peer::generate >>> did_doc: {:?}, numalgo: {:?}", secret!(did_doc), numalgo);
+
+    let did = match numalgo {
+        PeerDidNumalgo::Numalgo2 => _generate_numalgo_2(did_doc)?,
+        PeerDidNumalgo::Numalgo4 => _generate_numalgo_4(did_doc)?,
+    };
+
+    let did_doc = resolve(&did)?;
+
+    trace!("did_peer::generate <<< did: {:?}", did);
+    Ok(did_doc)
+}
+
+/// Resolves DIDDoc of did:peer:2 or did:peer:4 DID.
+/// Short form of did:peer:4 is resolved only if its long form has been resolved before.
+pub fn resolve(did: &str) -> VcxResult<DidDoc> {
+    trace!("did_peer::resolve >>> did: {:?}", did);
+
+    let did_doc = if did.starts_with(PEER_DID_2_PREFIX) {
+        _resolve_numalgo_2(did)?
+    } else if did.starts_with(PEER_DID_4_PREFIX) {
+        _resolve_numalgo_4(did)?
+    } else {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("Unsupported peer DID: {}. Only did:peer:2 and did:peer:4 can be resolved", did)));
+    };
+
+    trace!("did_peer::resolve <<< did_doc: {:?}", secret!(did_doc));
+    Ok(did_doc)
+}
+
+fn _generate_numalgo_2(did_doc: &DidDoc) -> VcxResult<String> {
+    let mut did = PEER_DID_2_PREFIX.to_string();
+
+    for key in did_doc.recipient_keys() {
+        did.push('.');
+        did.push(PURPOSE_VERIFICATION);
+        did.push_str(&_encode_key(&key, &ED25519_MULTICODEC_PREFIX)?);
+    }
+
+    for key in did_doc.key_agreement_keys() {
+        did.push('.');
+        did.push(PURPOSE_ENCRYPTION);
+        did.push_str(&_encode_key(&key, &X25519_MULTICODEC_PREFIX)?);
+    }
+
+    let routing_keys = did_doc.routing_keys()
+        .iter()
+        .map(|key| did_key(key))
+        .collect::<VcxResult<Vec<String>>>()?;
+
+    let service = PeerDidService {
+        type_: SERVICE_TYPE_DIDCOMM_MESSAGING_ABBREVIATION.to_string(),
+        endpoint: PeerDidServiceEndpoint::Uri(did_doc.get_endpoint()),
+        routing_keys,
+        accept: vec![SERVICE_ACCEPT_DIDCOMM_V1.to_string()],
+    };
+
+    did.push('.');
+    did.push(PURPOSE_SERVICE);
+    did.push_str(&base64::encode_config(&json!(service).to_string(), base64::URL_SAFE_NO_PAD));
+
+    Ok(did)
+}
+
+fn _resolve_numalgo_2(did: &str) -> VcxResult<DidDoc> {
+    let mut recipient_keys: Vec<String> = Vec::new();
+    let mut key_agreement_keys: Vec<String> = Vec::new();
+    let mut service: Option<PeerDidService> = None;
+
+    for element in did[PEER_DID_2_PREFIX.len()..].split('.').filter(|element| !element.is_empty()) {
+        let mut chars = element.chars();
+        let purpose = chars.next();
+        let value = chars.as_str();
+
+        match purpose {
+            Some(PURPOSE_VERIFICATION) => recipient_keys.push(_decode_key(value, &ED25519_MULTICODEC_PREFIX)?),
+            Some(PURPOSE_ENCRYPTION) => key_agreement_keys.push(_decode_key(value, &X25519_MULTICODEC_PREFIX)?),
+            Some(PURPOSE_SERVICE) if service.is_none() => service = Some(_decode_service(value)?),
+            _ => warn!("did:peer:2 element is not supported and will be skipped: {}", element)
+        }
+    }
+
+    let service = service
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Cannot resolve peer DID: {}. Service is not encoded", did)))?;
+
+    let (endpoint, mut routing_keys) = match service.endpoint {
+        PeerDidServiceEndpoint::Uri(uri) => (uri, service.routing_keys),
+        PeerDidServiceEndpoint::Object { uri, routing_keys, .. } => (uri, routing_keys),
+    };
+    Service::transform_did_keys_to_naked_keys(&mut routing_keys)?;
+
+    let mut did_doc = DidDoc::create();
+    did_doc.set_id(did.to_string());
+    did_doc.set_service_endpoint(endpoint);
+    did_doc.set_keys(recipient_keys, routing_keys);
+    did_doc.set_key_agreement_keys(key_agreement_keys);
+
+    if let Some(did_doc_service) = did_doc.service.get_mut(0) {
+        did_doc_service.id = format!("{}{}", did, SERVICE_ID);
+        did_doc_service.type_ = match service.type_.as_str() {
+            SERVICE_TYPE_DIDCOMM_MESSAGING_ABBREVIATION => SERVICE_TYPE_DIDCOMM_MESSAGING.to_string(),
+            type_ => type_.to_string()
+        };
+    }
+
+    Ok(did_doc)
+}
+
+fn _generate_numalgo_4(did_doc: &DidDoc) -> VcxResult<String> {
+    // input document has no `id`, so all references are relative
+    let mut input_doc = DidDoc::create();
+    input_doc.set_service_endpoint(did_doc.get_endpoint());
+    input_doc.set_keys(did_doc.recipient_keys(), did_doc.routing_keys());
+    input_doc.set_key_agreement_keys(did_doc.key_agreement_keys());
+
+    if let Some(service) = input_doc.service.get_mut(0) {
+        service.id = SERVICE_ID.to_string();
+    }
+
+    let mut input_doc = json!(input_doc);
+    if let Some(input_doc) = input_doc.as_object_mut() {
+        input_doc.remove("id");
+    }
+
+    let encoded_doc = _encode_multibase(&JSON_MULTICODEC_PREFIX, input_doc.to_string().as_bytes());
+    let hash = _hash(&encoded_doc);
+
+    Ok(format!("{}{}:{}", PEER_DID_4_PREFIX, hash, encoded_doc))
+}
+
+fn _resolve_numalgo_4(did: &str) -> VcxResult<DidDoc> {
+    let mut parts = did[PEER_DID_4_PREFIX.len()..].splitn(2, ':');
+
+    let (hash, encoded_doc) = match (parts.next(), parts.next()) {
+        (Some(hash), Some(encoded_doc)) => (hash, encoded_doc),
+        _ => {
+            let long_form = _long_form(did)?
+                .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                          format!("Cannot resolve peer DID: {}. Short form of did:peer:4 can be resolved only with long form received before", did)))?;
+            return _resolve_numalgo_4(&long_form);
+        }
+    };
+
+    if _hash(encoded_doc) != hash {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("Cannot resolve peer DID: {}. Hash does not match encoded DIDDoc", did)));
+    }
+
+    _remember_long_form(&format!("{}{}", PEER_DID_4_PREFIX, hash), did)?;
+
+    let input_doc = _decode_multibase(encoded_doc, &JSON_MULTICODEC_PREFIX)?;
+
+    let mut did_doc: DidDoc = ::serde_json::from_slice(&input_doc)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Cannot resolve peer DID: {}. Invalid encoded DIDDoc: {}", did, err)))?;
+
+    let absolute = |reference: &mut String| {
+        if reference.starts_with('#') {
+            *reference = format!("{}{}", did, reference);
+        }
+    };
+
+    did_doc.id = did.to_string();
+
+    for key in did_doc.public_key.iter_mut() {
+        absolute(&mut key.id);
+        if key.controller.is_empty() {
+            key.controller = did.to_string();
+        }
+    }
+
+    for key in did_doc.key_agreement.iter_mut() {
+        absolute(&mut key.id);
+        if key.controller.is_empty() {
+            key.controller = did.to_string();
+        }
+    }
+
+    for authentication in did_doc.authentication.iter_mut() {
+        absolute(&mut authentication.public_key);
+    }
+
+    for service in did_doc.service.iter_mut() {
+        absolute(&mut service.id);
+        service.recipient_keys.iter_mut().for_each(absolute);
+        service.routing_keys.iter_mut().for_each(absolute);
+    }
+
+    Ok(did_doc)
+}
+
+fn _long_form(short_form: &str) -> VcxResult<Option<String>> {
+    let long_forms = PEER_DID_4_LONG_FORMS.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot resolve peer DID: lock is poisoned"))?;
+
+    Ok(long_forms.get(short_form).cloned())
+}
+
+fn _remember_long_form(short_form: &str, long_form: &str) -> VcxResult<()> {
+    let mut long_forms = PEER_DID_4_LONG_FORMS.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot resolve peer DID: lock is poisoned"))?;
+
+    if !long_forms.contains_key(short_form) {
+        long_forms.insert(short_form.to_string(), long_form.to_string());
+    }
+
+    Ok(())
+}
+
+fn _decode_service(value: &str) -> VcxResult<PeerDidService> {
+    let service = base64::decode_config(value, base64::URL_SAFE_NO_PAD)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Cannot decode did:peer:2 service: {}", err)))?;
+
+    ::serde_json::from_slice(&service)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Cannot parse did:peer:2 service: {}", err)))
+}
+
+fn _encode_key(key: &str, multicodec_prefix: &[u8]) -> VcxResult<String> {
+    let key = key.from_base58()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidVerkey, format!("Key is not base58 encoded: {}", key)))?;
+
+    Ok(_encode_multibase(multicodec_prefix, &key))
+}
+
+fn _decode_key(value: &str, multicodec_prefix: &[u8]) -> VcxResult<String> {
+    _decode_multibase(value, multicodec_prefix)
+        .map(|key| key.to_base58())
+}
+
+fn _encode_multibase(multicodec_prefix: &[u8], data: &[u8]) -> String {
+    let mut value = multicodec_prefix.to_vec();
+    value.extend_from_slice(data);
+    format!("{}{}", MULTIBASE_BASE58_BTC, value.to_base58())
+}
+
+fn _decode_multibase(value: &str, multicodec_prefix: &[u8]) -> VcxResult<Vec<u8>> {
+    if !value.starts_with(MULTIBASE_BASE58_BTC) {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Unsupported multibase encoding of peer DID element: {}", value)));
+    }
+
+    let decoded = value[1..].from_base58()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Peer DID element is not base58 encoded: {}", value)))?;
+
+    if !decoded.starts_with(multicodec_prefix) {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("Unexpected multicodec of peer DID element. Expected: {:?}, Element: {}", multicodec_prefix, value)));
+    }
+
+    Ok(decoded[multicodec_prefix.len()..].to_vec())
+}
+
+fn _hash(encoded_doc: &str) -> String {
+    _encode_multibase(&SHA256_MULTIHASH_PREFIX, &Sha256::digest(encoded_doc.as_bytes()))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+
+    fn _agent_did_doc() -> DidDoc {
+        let mut did_doc = _did_doc();
+        did_doc.set_key_agreement_keys(vec![_key_agreement_key()]);
+        did_doc
+    }
+
+    #[test]
+    fn test_peer_did_numalgo_2_works() {
+        let did_doc = generate(&_agent_did_doc(), PeerDidNumalgo::Numalgo2).unwrap();
+
+        assert!(did_doc.id.starts_with("did:peer:2.Vz6Mk"));
+        assert!(is_peer_did(&did_doc.id));
+        did_doc.validate().unwrap();
+
+        assert_eq!(_recipient_keys(), did_doc.recipient_keys());
+        assert_eq!(_routing_keys(), did_doc.routing_keys());
+        assert_eq!(vec![_key_agreement_key()], did_doc.key_agreement_keys());
+        assert_eq!(_service_endpoint(), did_doc.get_endpoint());
+        assert_eq!(SERVICE_TYPE_DIDCOMM_MESSAGING, did_doc.service[0].type_);
+    }
+
+    #[test]
+    fn test_peer_did_numalgo_2_resolve_works_for_didcomm_v2_service() {
+        let service = base64::encode_config(r#"{"t":"dm","s":{"uri":"http://localhost:8080","a":["didcomm/v2"]}}"#, base64::URL_SAFE_NO_PAD);
+        let did = format!("did:peer:2.Vz6MkqRYqQiSgvZQdnBytw86Qbs2ZWUkGv22od935YF4s8M7V.S{}", service);
+
+        let did_doc = resolve(&did).unwrap();
+        assert_eq!(did, did_doc.id);
+        assert_eq!(_service_endpoint(), did_doc.get_endpoint());
+        assert_eq!(1, did_doc.recipient_keys().len());
+        assert!(did_doc.routing_keys().is_empty());
+    }
+
+    #[test]
+    fn test_peer_did_numalgo_2_resolve_fails_for_invalid_did() {
+        resolve("did:peer:2.Vz6MkqRYqQiSgvZQdnBytw86Qbs2ZWUkGv22od935YF4s8M7V").unwrap_err();
+        resolve("did:peer:2.Vz6LSbysY2xFMRpGMhb7tFTLMpeuPRaqaWM1yECx2AtzE3KCc.Sabc").unwrap_err();
+        resolve("did:peer:1zQmZMygzYqNwU6Uhmewx5Xepf2VLp5S4HLSwwgf2aiKZuwa").unwrap_err();
+    }
+
+    #[test]
+    fn test_peer_did_numalgo_4_works() {
+        let did_doc = generate(&_agent_did_doc(), PeerDidNumalgo::Numalgo4).unwrap();
+
+        assert!(did_doc.id.starts_with("did:peer:4z"));
+        did_doc.validate().unwrap();
+
+        assert_eq!(_recipient_keys(), did_doc.recipient_keys());
+        assert_eq!(_routing_keys(), did_doc.routing_keys());
+        assert_eq!(vec![_key_agreement_key()], did_doc.key_agreement_keys());
+        assert_eq!(_service_endpoint(), did_doc.get_endpoint());
+        assert!(did_doc.public_key.iter().all(|key| key.id.starts_with(&did_doc.id)));
+
+        assert_eq!(did_doc, resolve(&did_doc.id).unwrap());
+
+        // short form is resolved with the long form resolved before
+        let short_form = did_doc.id.split(':').take(3).collect::<Vec<&str>>().join(":");
+        assert_eq!(did_doc, resolve(&short_form).unwrap());
+
+        // short form of DID which has not been seen yet
+        let mut other_did_doc = _agent_did_doc();
+        other_did_doc.set_service_endpoint("http://other.example".to_string());
+        let other_did = _generate_numalgo_4(&other_did_doc).unwrap();
+        let other_short_form = other_did.split(':').take(3).collect::<Vec<&str>>().join(":");
+        resolve(&other_short_form).unwrap_err();
+
+        // tampered document
+        let tampered = format!("{}a", did_doc.id);
+        resolve(&tampered).unwrap_err();
+    }
+}
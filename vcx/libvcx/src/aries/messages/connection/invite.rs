@@ -1,3 +1,4 @@
+use crate::aries::messages::timing::Timing;
 use crate::aries::messages::a2a::{A2AMessage, MessageId};
 use crate::aries::messages::a2a::message_type::{
     MessageType,
@@ -26,6 +27,9 @@ pub struct Invitation {
     pub profile_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub public_did: Option<String>,
+    #[serde(rename = "~timing")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Timing>,
 }
 
 impl Invitation {
@@ -71,6 +75,16 @@ impl Invitation {
         self.public_did = public_did;
         self
     }
+
+    /// Sets `~timing.expires_time` decorator, the invitation is not accepted after it expires.
+    pub fn set_opt_expiration(mut self, ttl: Option<u64>) -> Invitation {
+        self.timing = ttl.map(Timing::expiring_in);
+        self
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.timing.as_ref().map_or(false, Timing::is_expired)
+    }
 }
 
 impl Default for Invitation {
@@ -84,6 +98,7 @@ impl Default for Invitation {
             service_endpoint: Default::default(),
             profile_url: Default::default(),
             public_did: Default::default(),
+            timing: Default::default(),
         }
     }
 }
@@ -55,6 +55,17 @@ impl ProblemReport {
         self.explain = Some(explain);
         self
     }
+
+    /// Turns the report into the Problem Report of DID Exchange protocol.
+    pub fn for_didexchange(mut self) -> ProblemReport {
+        self.type_ = MessageType {
+            prefix: MessageTypePrefix::Endpoint,
+            family: MessageTypeFamilies::DidExchange,
+            version: MessageTypeVersion::V10,
+            type_: A2AMessage::CONNECTION_PROBLEM_REPORT.to_string()
+        };
+        self
+    }
 }
 impl Default for ProblemReport {
     fn default() -> ProblemReport {
@@ -116,4 +127,17 @@ pub mod tests {
         let expected = r#"{"@id":"testid","@type":"did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/1.0/problem-report","explain":"test explanation","problem-code":"response_processing_error","~thread":{"received_orders":{},"sender_order":0,"thid":"test_id"}}"#;
         assert_eq!(expected, json!(report).to_string());
     }
+
+    #[test]
+    fn test_didexchange_problem_report_works() {
+        let report = _problem_report().for_didexchange();
+
+        let report_json = json!(report).to_string();
+        assert!(report_json.contains(r#""@type":"https://didcomm.org/didexchange/1.0/problem_report""#));
+
+        match ::serde_json::from_str::<A2AMessage>(&report_json).unwrap() {
+            A2AMessage::ConnectionProblemReport(report_) => assert_eq!(report, report_),
+            message => panic!("unexpected message: {:?}", message)
+        }
+    }
 }
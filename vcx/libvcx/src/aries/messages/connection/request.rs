@@ -53,6 +53,17 @@ impl Request {
         self.connection.did_doc.set_keys(recipient_keys, routing_keys);
         self
     }
+
+    pub fn set_key_agreement_keys(mut self, keys: Vec<String>) -> Request {
+        self.connection.did_doc.set_key_agreement_keys(keys);
+        self
+    }
+
+    pub fn set_did_doc(mut self, did_doc: DidDoc) -> Request {
+        self.connection.did = did_doc.id.clone();
+        self.connection.did_doc = did_doc;
+        self
+    }
 }
 
 impl Default for Request {
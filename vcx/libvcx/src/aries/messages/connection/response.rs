@@ -81,6 +81,17 @@ impl Response {
         self
     }
 
+    pub fn set_key_agreement_keys(mut self, keys: Vec<String>) -> Response {
+        self.connection.did_doc.set_key_agreement_keys(keys);
+        self
+    }
+
+    pub fn set_did_doc(mut self, did_doc: DidDoc) -> Response {
+        self.connection.did = did_doc.id.clone();
+        self.connection.did_doc = did_doc;
+        self
+    }
+
     pub fn encode(&self, key: &str) -> VcxResult<SignedResponse> {
         trace!("Response::encode >>> {:?}", secret!(self));
 
@@ -1,4 +1,6 @@
+use crate::error::VcxResult;
 use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::resolver;
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
@@ -28,6 +30,17 @@ impl Service {
         self.recipient_keys = recipient_keys;
         self
     }
+
+    /// Replaces keys given as DIDs with the keys resolved by the registered DID resolvers.
+    pub fn resolve_keys(&mut self) -> VcxResult<()> {
+        resolver::resolve_keys(&mut self.recipient_keys)?;
+
+        if let Some(routing_keys) = self.routing_keys.as_mut() {
+            resolver::resolve_keys(routing_keys)?;
+        }
+
+        Ok(())
+    }
 }
 
 impl Into<DidDoc> for Service {
@@ -42,7 +55,8 @@ impl Into<DidDoc> for Service {
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::aries::messages::connection::did_doc::tests::{_service_endpoint, _recipient_keys, _routing_keys};
+    use crate::aries::messages::connection::did_doc::tests::{_service_endpoint, _recipient_keys, _routing_keys, _key_1, _key_2};
+    use crate::aries::messages::didexchange::did_doc_attachment::did_key;
 
     pub fn _service() -> Service {
         Service {
@@ -52,6 +66,18 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_service_resolve_keys_works() {
+        let mut service = _service()
+            .set_recipient_keys(vec![did_key(&_key_1()).unwrap()])
+            .set_routing_keys(vec![did_key(&_key_2()).unwrap()]);
+
+        service.resolve_keys().unwrap();
+
+        assert_eq!(vec![_key_1()], service.recipient_keys);
+        assert_eq!(Some(vec![_key_2()]), service.routing_keys);
+    }
+
     #[test]
     fn test_service_build_works() {
         let service: Service = Service::default()
@@ -0,0 +1,118 @@
+use crate::error::prelude::*;
+use crate::aries::messages::a2a::{A2AMessage, MessageId};
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::messages::didexchange::did_doc_attachment::DidDocAttachment;
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+/*
+    `update` message of proprietary DIDDoc Update protocol.
+
+    Announces new service endpoint and routing keys of the pairwise DID without changing the DID itself.
+    Updated DIDDoc is attached and signed by the recipient key of the DID, the remote side replies with `ack` or `problem-report`.
+*/
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Update {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(rename = "did_doc~attach")]
+    pub did_doc_attach: DidDocAttachment,
+}
+
+impl Update {
+    pub fn create() -> Update {
+        Update::default()
+    }
+
+    pub fn set_did_doc(mut self, did_doc: &DidDoc) -> Update {
+        self.did_doc_attach = DidDocAttachment::create(did_doc);
+        self
+    }
+
+    /// Signs DIDDoc attachment with the recipient `key` of the DID.
+    pub fn sign(mut self, key: &str) -> VcxResult<Update> {
+        trace!("Update::sign >>> key: {:?}", secret!(key));
+
+        self.did_doc_attach = self.did_doc_attach.sign(key)?;
+
+        trace!("Update::sign <<<");
+        Ok(self)
+    }
+
+    /// Verifies signature of DIDDoc attachment.
+    /// Returns the verkey of the signer and the updated DIDDoc.
+    pub fn decode(&self) -> VcxResult<(String, DidDoc)> {
+        trace!("Update::decode >>> {:?}", secret!(self));
+
+        let signer = self.did_doc_attach.verify()?;
+        let did_doc = self.did_doc_attach.did_doc()?;
+
+        trace!("Update::decode <<< signer: {:?}", secret!(signer));
+        Ok((signer, did_doc))
+    }
+}
+
+impl Default for Update {
+    fn default() -> Update {
+        Update {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::DID,
+                family: MessageTypeFamilies::DidDocUpdate,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::DID_DOC_UPDATE.to_string()
+            },
+            did_doc_attach: DidDocAttachment::create(&DidDoc::default()),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::utils::libindy::tests::test_setup;
+
+    pub fn _update() -> Update {
+        Update {
+            id: MessageId::id(),
+            ..Update::default()
+        }
+            .set_did_doc(&_did_doc())
+    }
+
+    #[test]
+    fn test_update_build_works() {
+        let update = _update();
+
+        let update_json = json!(update);
+        assert_eq!("did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/did-doc-update/1.0/update", update_json["@type"]);
+
+        let update_: Update = ::serde_json::from_value(update_json).unwrap();
+        assert_eq!(update, update_);
+
+        match ::serde_json::from_value::<A2AMessage>(json!(update)).unwrap() {
+            A2AMessage::DidDocUpdate(update_) => assert_eq!(update, update_),
+            message => panic!("Unexpected message: {:?}", message)
+        }
+    }
+
+    #[test]
+    fn test_update_sign_works() {
+        let setup = test_setup::key();
+
+        _update().decode().unwrap_err();
+
+        let update = _update().sign(&setup.key).unwrap();
+        let (signer, did_doc) = update.decode().unwrap();
+        assert_eq!(setup.key, signer);
+        assert_eq!(_did_doc(), did_doc);
+    }
+}
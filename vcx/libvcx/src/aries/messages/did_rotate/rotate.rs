@@ -0,0 +1,135 @@
+use crate::error::prelude::*;
+use crate::aries::messages::a2a::{A2AMessage, MessageId};
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::messages::didexchange::did_doc_attachment::DidDocAttachment;
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+/*
+    `rotate` message of DID Rotate protocol:
+    https://github.com/hyperledger/aries-rfcs/tree/main/features/0794-did-rotate
+
+    Pairwise DIDs are not resolvable, so DIDDoc of `to_did` is attached and signed by the key being rotated.
+*/
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Rotate {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub to_did: String,
+    #[serde(rename = "did_doc~attach")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub did_doc_attach: Option<DidDocAttachment>,
+}
+
+impl Rotate {
+    pub fn create() -> Rotate {
+        Rotate::default()
+    }
+
+    pub fn set_did_doc(mut self, did_doc: &DidDoc) -> Rotate {
+        self.to_did = did_doc.id.clone();
+        self.did_doc_attach = Some(DidDocAttachment::create(did_doc));
+        self
+    }
+
+    /// Signs DIDDoc attachment with the `key` being rotated.
+    pub fn sign(mut self, key: &str) -> VcxResult<Rotate> {
+        trace!("Rotate::sign >>> key: {:?}", secret!(key));
+
+        let did_doc_attach = self.did_doc_attach.take()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, "Cannot sign DID Rotate message: DIDDoc attachment is not set"))?;
+
+        self.did_doc_attach = Some(did_doc_attach.sign(key)?);
+
+        trace!("Rotate::sign <<<");
+        Ok(self)
+    }
+
+    /// Verifies signature of DIDDoc attachment.
+    /// Returns the verkey of the signer and DIDDoc of `to_did`.
+    pub fn decode(&self) -> VcxResult<(String, DidDoc)> {
+        trace!("Rotate::decode >>> {:?}", secret!(self));
+
+        let did_doc_attach = self.did_doc_attach.as_ref()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("DID Rotate message does not contain DIDDoc attachment for DID: {}", self.to_did)))?;
+
+        let signer = did_doc_attach.verify()?;
+        let did_doc = did_doc_attach.did_doc()?;
+
+        if did_doc.id != self.to_did {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                          format!("DIDDoc attachment does not belong to rotated DID. Expected: {:?}, Received: {:?}", self.to_did, did_doc.id)));
+        }
+
+        trace!("Rotate::decode <<< signer: {:?}", secret!(signer));
+        Ok((signer, did_doc))
+    }
+}
+
+impl Default for Rotate {
+    fn default() -> Rotate {
+        Rotate {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::DidRotate,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::DID_ROTATE.to_string()
+            },
+            to_did: Default::default(),
+            did_doc_attach: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::utils::libindy::tests::test_setup;
+
+    pub fn _rotate() -> Rotate {
+        Rotate {
+            id: MessageId::id(),
+            ..Rotate::default()
+        }
+            .set_did_doc(&_did_doc())
+    }
+
+    #[test]
+    fn test_rotate_build_works() {
+        let rotate = _rotate();
+        assert_eq!(_id(), rotate.to_did);
+
+        let rotate_json = json!(rotate);
+        assert_eq!("https://didcomm.org/did-rotate/1.0/rotate", rotate_json["@type"]);
+
+        let rotate_: Rotate = ::serde_json::from_value(rotate_json).unwrap();
+        assert_eq!(rotate, rotate_);
+    }
+
+    #[test]
+    fn test_rotate_sign_works() {
+        let setup = test_setup::key();
+
+        _rotate().decode().unwrap_err();
+
+        let rotate = _rotate().sign(&setup.key).unwrap();
+        let (signer, did_doc) = rotate.decode().unwrap();
+        assert_eq!(setup.key, signer);
+        assert_eq!(_did_doc(), did_doc);
+
+        let mut other_rotate = rotate.clone();
+        other_rotate.to_did = String::from("other");
+        other_rotate.decode().unwrap_err();
+    }
+}
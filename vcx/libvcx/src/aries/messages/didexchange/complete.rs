@@ -0,0 +1,62 @@
+use crate::aries::messages::a2a::{A2AMessage, MessageId};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+use crate::aries::messages::thread::Thread;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Complete {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(rename = "~thread")]
+    pub thread: Thread,
+}
+
+impl Complete {
+    pub fn create() -> Complete {
+        Complete::default()
+    }
+}
+
+impl Default for Complete {
+    fn default() -> Complete {
+        Complete {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::DidExchange,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::DIDEXCHANGE_COMPLETE.to_string()
+            },
+            thread: Default::default(),
+        }
+    }
+}
+
+threadlike!(Complete);
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::response::tests::_thread_id;
+
+    pub fn _complete() -> Complete {
+        Complete {
+            id: MessageId::id(),
+            ..Complete::default()
+        }
+            .set_thread_id(&_thread_id())
+            .set_pthid("invitation_id")
+    }
+
+    #[test]
+    fn test_complete_build_works() {
+        let expected = r#"{"@id":"testid","@type":"https://didcomm.org/didexchange/1.0/complete","~thread":{"pthid":"invitation_id","received_orders":{},"sender_order":0,"thid":"test_id"}}"#;
+        assert_eq!(expected, json!(_complete()).to_string());
+    }
+}
@@ -0,0 +1,226 @@
+use rust_base58::{FromBase58, ToBase58};
+
+use crate::error::prelude::*;
+use crate::utils::libindy::crypto;
+use crate::aries::messages::connection::did_doc::{DidDoc, Service};
+
+/*
+    `did_doc~attach` decorator of DID Exchange messages.
+
+    DIDDoc is attached as base64 encoded JSON.
+    Attachment of the Response is signed with detached JWS by the key used in the Invitation:
+    https://github.com/hyperledger/aries-rfcs/tree/main/features/0023-did-exchange#signed-attachments
+*/
+
+const MIME_TYPE: &str = "application/json";
+const JWS_ALGORITHM: &str = "EdDSA";
+const JWK_KEY_TYPE: &str = "OKP";
+const JWK_CURVE: &str = "Ed25519";
+const ED25519_MULTICODEC_PREFIX: [u8; 2] = [0xed, 0x01];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DidDocAttachment {
+    #[serde(rename = "@id")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(rename = "mime-type")]
+    #[serde(default = "default_mime_type")]
+    pub mime_type: String,
+    pub data: DidDocAttachmentData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DidDocAttachmentData {
+    pub base64: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub jws: Option<Jws>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Jws {
+    pub header: JwsHeader,
+    pub protected: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JwsHeader {
+    pub kid: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct JwsProtectedHeader {
+    alg: String,
+    kid: String,
+    jwk: Jwk,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Jwk {
+    kty: String,
+    crv: String,
+    x: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+impl DidDocAttachment {
+    pub fn create(did_doc: &DidDoc) -> DidDocAttachment {
+        DidDocAttachment {
+            id: Some(crate::utils::uuid::uuid()),
+            mime_type: default_mime_type(),
+            data: DidDocAttachmentData {
+                base64: base64::encode(&json!(did_doc).to_string()),
+                jws: None,
+            },
+        }
+    }
+
+    pub fn did_doc(&self) -> VcxResult<DidDoc> {
+        let did_doc = _decode(&self.data.base64)?;
+
+        ::serde_json::from_slice(&did_doc)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Cannot deserialize DIDDoc attachment: {}", err)))
+    }
+
+    /// Signs the attachment with `verkey` (Ed25519 key stored in the wallet).
+    pub fn sign(mut self, verkey: &str) -> VcxResult<DidDocAttachment> {
+        trace!("DidDocAttachment::sign >>> verkey: {:?}", secret!(verkey));
+
+        let kid = did_key(verkey)?;
+
+        let protected = JwsProtectedHeader {
+            alg: JWS_ALGORITHM.to_string(),
+            kid: kid.clone(),
+            jwk: Jwk {
+                kty: JWK_KEY_TYPE.to_string(),
+                crv: JWK_CURVE.to_string(),
+                x: base64::encode_config(&_decode_verkey(verkey)?, base64::URL_SAFE_NO_PAD),
+                kid: Some(kid.clone()),
+            },
+        };
+        let protected = base64::encode_config(&json!(protected).to_string(), base64::URL_SAFE_NO_PAD);
+
+        let signature = crypto::sign(verkey, self._signing_input(&protected)?.as_bytes())?;
+
+        self.data.jws = Some(Jws {
+            header: JwsHeader { kid },
+            protected,
+            signature: base64::encode_config(&signature, base64::URL_SAFE_NO_PAD),
+        });
+
+        trace!("DidDocAttachment::sign <<<");
+        Ok(self)
+    }
+
+    /// Verifies the attachment signature.
+    /// Returns the verkey of the signer.
+    pub fn verify(&self) -> VcxResult<String> {
+        trace!("DidDocAttachment::verify >>>");
+
+        let jws = self.data.jws.as_ref()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, "DIDDoc attachment is not signed"))?;
+
+        let protected: JwsProtectedHeader = ::serde_json::from_slice(&_decode(&jws.protected)?)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Cannot deserialize JWS protected header: {}", err)))?;
+
+        if protected.alg != JWS_ALGORITHM {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Unsupported JWS algorithm: {}", protected.alg)));
+        }
+
+        let signer = _decode(&protected.jwk.x)?.to_base58();
+
+        let signature = _decode(&jws.signature)?;
+
+        if !crypto::verify(&signer, self._signing_input(&jws.protected)?.as_bytes(), &signature)? {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, "DIDDoc attachment signature is invalid"));
+        }
+
+        trace!("DidDocAttachment::verify <<< signer: {:?}", secret!(signer));
+        Ok(signer)
+    }
+
+    fn _signing_input(&self, protected: &str) -> VcxResult<String> {
+        let payload = base64::encode_config(&_decode(&self.data.base64)?, base64::URL_SAFE_NO_PAD);
+        Ok(format!("{}.{}", protected, payload))
+    }
+}
+
+fn default_mime_type() -> String {
+    MIME_TYPE.to_string()
+}
+
+/// Builds `did:key` key identifier for Ed25519 verkey in base58.
+pub fn did_key(verkey: &str) -> VcxResult<String> {
+    let mut multicodec = ED25519_MULTICODEC_PREFIX.to_vec();
+    multicodec.extend(_decode_verkey(verkey)?);
+    let multibase = format!("z{}", multicodec.to_base58());
+
+    Ok(format!("did:key:{}#{}", multibase, multibase))
+}
+
+/// Resolves Ed25519 verkey (base58) from `did:key` key identifier.
+pub fn resolve_did_key(kid: &str) -> VcxResult<String> {
+    let mut keys = vec![kid.to_string()];
+    Service::transform_did_keys_to_naked_keys(&mut keys)?;
+    Ok(keys.remove(0))
+}
+
+fn _decode_verkey(verkey: &str) -> VcxResult<Vec<u8>> {
+    verkey.from_base58()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidVerkey, format!("Verkey is not base58 encoded: {}", verkey)))
+}
+
+// attachments produced by other agents may use either standard or url safe alphabet, with or without padding
+fn _decode(data: &str) -> VcxResult<Vec<u8>> {
+    let data = data
+        .trim_end_matches('=')
+        .replace('+', "-")
+        .replace('/', "_");
+
+    base64::decode_config(&data, base64::URL_SAFE_NO_PAD)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAttachmentEncoding, format!("Cannot decode base64 attachment data: {}", err)))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::utils::libindy::tests::test_setup;
+
+    pub fn _did_doc_attachment() -> DidDocAttachment {
+        DidDocAttachment::create(&_did_doc())
+    }
+
+    #[test]
+    fn test_did_doc_attachment_works() {
+        let attachment = _did_doc_attachment();
+        assert_eq!(_did_doc(), attachment.did_doc().unwrap());
+        attachment.verify().unwrap_err();
+    }
+
+    #[test]
+    fn test_did_doc_attachment_sign_works() {
+        let setup = test_setup::key();
+
+        let attachment = _did_doc_attachment().sign(&setup.key).unwrap();
+        assert_eq!(setup.key, attachment.verify().unwrap());
+        assert_eq!(_did_doc(), attachment.did_doc().unwrap());
+
+        let mut other_did_doc = _did_doc();
+        other_did_doc.set_id(String::from("other"));
+
+        let mut tampered = attachment.clone();
+        tampered.data.base64 = DidDocAttachment::create(&other_did_doc).data.base64;
+        tampered.verify().unwrap_err();
+    }
+
+    #[test]
+    fn test_did_key_works() {
+        assert_eq!(_key_1(), resolve_did_key(&did_key(&_key_1()).unwrap()).unwrap());
+        assert!(did_key(&_key_1()).unwrap().starts_with("did:key:z6Mk"));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod did_doc_attachment;
+pub mod request;
+pub mod response;
+pub mod complete;
@@ -0,0 +1,144 @@
+use crate::error::prelude::*;
+use crate::aries::messages::a2a::{A2AMessage, MessageId};
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::resolver;
+use crate::aries::messages::didexchange::did_doc_attachment::DidDocAttachment;
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+use crate::aries::messages::thread::Thread;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Request {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(rename = "~thread")]
+    #[serde(default)]
+    pub thread: Thread,
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub goal_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub goal: Option<String>,
+    pub did: String,
+    #[serde(rename = "did_doc~attach")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub did_doc_attach: Option<DidDocAttachment>,
+}
+
+impl Request {
+    pub fn create() -> Request {
+        Request::default()
+    }
+
+    pub fn set_label(mut self, label: String) -> Request {
+        self.label = label;
+        self
+    }
+
+    pub fn set_did_doc(mut self, did_doc: &DidDoc) -> Request {
+        self.did = did_doc.id.clone();
+        self.did_doc_attach = Some(DidDocAttachment::create(did_doc));
+        self
+    }
+
+    /// Id of the thread the Request starts: `~thread.thid` if it is set, otherwise `@id` of the Request.
+    pub fn thread_id(&self) -> String {
+        self.thread.thid.clone().unwrap_or(self.id.to_string())
+    }
+
+    /// DIDDoc of the requester.
+    /// DIDs sent without DIDDoc attachment (peer or public ones) are resolved.
+    pub fn did_doc(&self) -> VcxResult<DidDoc> {
+        match self.did_doc_attach.as_ref() {
+            Some(did_doc_attach) => did_doc_attach.did_doc(),
+            None if self.did.is_empty() => Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                                                  "DID Exchange Request contains neither DIDDoc attachment nor DID")),
+            None => resolver::resolve(&self.did)
+        }
+    }
+}
+
+impl Default for Request {
+    fn default() -> Request {
+        Request {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::DidExchange,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::CONNECTION_REQUEST.to_string()
+            },
+            thread: Default::default(),
+            label: Default::default(),
+            goal_code: Default::default(),
+            goal: Default::default(),
+            did: Default::default(),
+            did_doc_attach: Default::default(),
+        }
+    }
+}
+
+threadlike!(Request);
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_peer;
+    use crate::aries::messages::connection::did_doc::tests::*;
+
+    fn _pthid() -> String {
+        String::from("invitation_id")
+    }
+
+    pub fn _didexchange_request() -> Request {
+        Request {
+            id: MessageId::id(),
+            label: _label(),
+            ..Request::default()
+        }
+            .set_pthid(&_pthid())
+            .set_did_doc(&_did_doc())
+    }
+
+    #[test]
+    fn test_didexchange_request_build_works() {
+        let request = _didexchange_request();
+
+        assert_eq!(_id(), request.did);
+        assert_eq!(_did_doc(), request.did_doc().unwrap());
+        assert_eq!(MessageId::id().to_string(), request.thread_id());
+
+        let request_json = json!(request);
+        assert_eq!("https://didcomm.org/didexchange/1.0/request", request_json["@type"]);
+        assert_eq!(_pthid(), request_json["~thread"]["pthid"]);
+        assert_eq!("application/json", request_json["did_doc~attach"]["mime-type"]);
+
+        let request_: Request = ::serde_json::from_value(request_json).unwrap();
+        assert_eq!(request, request_);
+    }
+
+    #[test]
+    fn test_didexchange_request_without_did_doc() {
+        let request = Request::create().set_label(_label());
+        request.did_doc().unwrap_err();
+    }
+
+    #[test]
+    fn test_didexchange_request_with_peer_did_without_did_doc() {
+        let did_doc = did_peer::generate(&_did_doc(), did_peer::PeerDidNumalgo::Numalgo2).unwrap();
+
+        let mut request = Request::create().set_label(_label());
+        request.did = did_doc.id.clone();
+
+        assert_eq!(did_doc, request.did_doc().unwrap());
+    }
+}
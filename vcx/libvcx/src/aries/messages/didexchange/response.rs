@@ -0,0 +1,129 @@
+use crate::error::prelude::*;
+use crate::aries::messages::a2a::{A2AMessage, MessageId};
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::messages::didexchange::did_doc_attachment::DidDocAttachment;
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+use crate::aries::messages::thread::Thread;
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Response {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(rename = "~thread")]
+    pub thread: Thread,
+    pub did: String,
+    #[serde(rename = "did_doc~attach")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub did_doc_attach: Option<DidDocAttachment>,
+}
+
+impl Response {
+    pub fn create() -> Response {
+        Response::default()
+    }
+
+    pub fn set_did_doc(mut self, did_doc: &DidDoc) -> Response {
+        self.did = did_doc.id.clone();
+        self.did_doc_attach = Some(DidDocAttachment::create(did_doc));
+        self
+    }
+
+    /// Signs DIDDoc attachment with the `key` used in the Invitation.
+    pub fn sign(mut self, key: &str) -> VcxResult<Response> {
+        trace!("Response::sign >>> key: {:?}", secret!(key));
+
+        let did_doc_attach = self.did_doc_attach.take()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, "Cannot sign DID Exchange Response: DIDDoc attachment is not set"))?;
+
+        self.did_doc_attach = Some(did_doc_attach.sign(key)?);
+
+        trace!("Response::sign <<<");
+        Ok(self)
+    }
+
+    /// Verifies signature of DIDDoc attachment.
+    /// Returns the verkey of the signer and DIDDoc of the responder.
+    pub fn decode(&self) -> VcxResult<(String, DidDoc)> {
+        trace!("Response::decode >>> {:?}", secret!(self));
+
+        let did_doc_attach = self.did_doc_attach.as_ref()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("DID Exchange Response does not contain DIDDoc attachment for DID: {}", self.did)))?;
+
+        let signer = did_doc_attach.verify()?;
+        let did_doc = did_doc_attach.did_doc()?;
+
+        trace!("Response::decode <<< signer: {:?}", secret!(signer));
+        Ok((signer, did_doc))
+    }
+}
+
+impl Default for Response {
+    fn default() -> Response {
+        Response {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::DidExchange,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::CONNECTION_RESPONSE.to_string()
+            },
+            thread: Default::default(),
+            did: Default::default(),
+            did_doc_attach: Default::default(),
+        }
+    }
+}
+
+threadlike!(Response);
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::aries::messages::connection::response::tests::{_thread, _thread_id};
+    use crate::utils::libindy::tests::test_setup;
+
+    pub fn _didexchange_response() -> Response {
+        Response {
+            id: MessageId::id(),
+            ..Response::default()
+        }
+            .set_thread(_thread())
+            .set_did_doc(&_did_doc())
+    }
+
+    #[test]
+    fn test_didexchange_response_build_works() {
+        let response = _didexchange_response();
+
+        assert_eq!(_id(), response.did);
+        assert!(response.from_thread(&_thread_id()));
+
+        let response_json = json!(response);
+        assert_eq!("https://didcomm.org/didexchange/1.0/response", response_json["@type"]);
+
+        let response_: Response = ::serde_json::from_value(response_json).unwrap();
+        assert_eq!(response, response_);
+    }
+
+    #[test]
+    fn test_didexchange_response_sign_works() {
+        let setup = test_setup::key();
+
+        _didexchange_response().decode().unwrap_err();
+
+        let response = _didexchange_response().sign(&setup.key).unwrap();
+        let (signer, did_doc) = response.decode().unwrap();
+        assert_eq!(setup.key, signer);
+        assert_eq!(_did_doc(), did_doc);
+    }
+}
@@ -37,6 +37,13 @@ impl CredentialOffer {
         }
     }
 
+    pub fn set_thread(self, thread: Thread) -> Self {
+        match self {
+            CredentialOffer::V1(offer) => CredentialOffer::V1(offer.set_thread(thread)),
+            CredentialOffer::V2(offer) => CredentialOffer::V2(offer.set_thread(thread))
+        }
+    }
+
     pub fn set_thread_id(self, thid: &str) -> Self {
         match self {
             CredentialOffer::V1(offer) => CredentialOffer::V1(offer.set_thread_id(thid)),
@@ -86,6 +93,19 @@ impl CredentialOffer {
         }
     }
 
+    /// Resolves keys of `~service` decorator given as DIDs.
+    pub fn resolve_services(&mut self) -> VcxResult<()> {
+        let service = match self {
+            CredentialOffer::V1(credential_offer) => credential_offer.service.as_mut(),
+            CredentialOffer::V2(credential_offer) => credential_offer.service.as_mut()
+        };
+
+        match service {
+            Some(service) => service.resolve_keys(),
+            None => Ok(())
+        }
+    }
+
     pub fn offer_attach(&self) -> &Attachments {
         match self {
             CredentialOffer::V1(credential_offer) => &credential_offer.offers_attach,
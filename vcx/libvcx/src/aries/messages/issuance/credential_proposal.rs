@@ -1,9 +1,13 @@
 use serde::{de, Deserialize, Deserializer};
+use serde_json::{Map, Value};
 
 use crate::aries::messages::issuance::v10::credential_proposal::CredentialProposal as CredentialProposalV1;
 use crate::aries::messages::issuance::v20::credential_proposal::CredentialProposal as CredentialProposalV2;
+use crate::aries::messages::issuance::credential_preview::CredentialPreviewData;
 use crate::aries::messages::a2a::message_type::{MessageType, MessageTypeVersion};
+use crate::aries::messages::mime_type::MimeType;
 use crate::aries::messages::thread::Thread;
+use crate::error::prelude::*;
 
 #[derive(Debug, Serialize, PartialEq, Clone)]
 #[serde(untagged)]
@@ -12,7 +16,215 @@ pub enum CredentialProposal {
     V2(CredentialProposalV2),
 }
 
+/// Credential proposal data provided by the holder.
+#[derive(Debug, Deserialize, PartialEq, Clone, Default)]
+pub struct CredentialProposalData {
+    /// Version of Issue Credential protocol: `1.0` (default) or `2.0`.
+    #[serde(default)]
+    pub protocol_version: Option<String>,
+    /// Proposed attribute values (credential preview).
+    #[serde(default)]
+    pub attributes: Map<String, Value>,
+    #[serde(default)]
+    pub schema_id: Option<String>,
+    #[serde(default)]
+    pub cred_def_id: Option<String>,
+    // filters below are supported by `2.0` protocol only
+    #[serde(default)]
+    pub schema_issuer_did: Option<String>,
+    #[serde(default)]
+    pub schema_name: Option<String>,
+    #[serde(default)]
+    pub schema_version: Option<String>,
+    #[serde(default)]
+    pub issuer_did: Option<String>,
+}
+
+// `filters~attach` content of Issue Credential 2.0 proposal
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Default)]
+struct IndyCredentialFilter {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_issuer_did: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_version: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema_id: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    issuer_did: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cred_def_id: Option<String>,
+}
+
+impl IndyCredentialFilter {
+    fn has_schema_filters(&self) -> bool {
+        self.schema_issuer_did.is_some() || self.schema_name.is_some() || self.schema_version.is_some() || self.schema_id.is_some()
+    }
+}
+
 impl CredentialProposal {
+    pub fn create_from_data(data: &CredentialProposalData, comment: Option<String>) -> VcxResult<CredentialProposal> {
+        trace!("CredentialProposal::create_from_data >>> data: {:?}, comment: {:?}", secret!(data), comment);
+
+        let proposal = match data.protocol_version.as_deref() {
+            None | Some("1.0") => {
+                let mut proposal = CredentialProposalV1::create()
+                    .set_comment(comment)
+                    .set_schema_id(data.schema_id.clone().unwrap_or_default())
+                    .set_cred_def_id(data.cred_def_id.clone().unwrap_or_default());
+
+                for (name, value) in data.attributes.iter() {
+                    proposal = proposal.add_credential_preview_data(name, value, MimeType::Plain)?;
+                }
+
+                CredentialProposal::V1(proposal)
+            }
+            Some("2.0") => {
+                let filter = IndyCredentialFilter {
+                    schema_issuer_did: data.schema_issuer_did.clone(),
+                    schema_name: data.schema_name.clone(),
+                    schema_version: data.schema_version.clone(),
+                    schema_id: data.schema_id.clone(),
+                    issuer_did: data.issuer_did.clone(),
+                    cred_def_id: data.cred_def_id.clone(),
+                };
+
+                let mut proposal = CredentialProposalV2::create()
+                    .set_comment(comment)
+                    .set_indy_filters_attach(&json!(filter).to_string())?;
+
+                for (name, value) in data.attributes.iter() {
+                    proposal = proposal.add_credential_preview_data(name, value, MimeType::Plain)?;
+                }
+
+                CredentialProposal::V2(proposal)
+            }
+            Some(version) => {
+                return Err(VcxError::from_msg(VcxErrorKind::InvalidOption,
+                                              format!("Unsupported Issue Credential protocol version: {:?}. Expected: \"1.0\" or \"2.0\"", version)));
+            }
+        };
+
+        trace!("CredentialProposal::create_from_data <<< proposal: {:?}", secret!(proposal));
+        Ok(proposal)
+    }
+
+    pub fn type_(&self) -> &MessageType {
+        match self {
+            CredentialProposal::V1(credential_proposal) => &credential_proposal.type_,
+            CredentialProposal::V2(credential_proposal) => &credential_proposal.type_,
+        }
+    }
+
+    pub fn comment(&self) -> Option<String> {
+        match self {
+            CredentialProposal::V1(credential_proposal) => credential_proposal.comment.clone(),
+            CredentialProposal::V2(credential_proposal) => credential_proposal.comment.clone(),
+        }
+    }
+
+    pub fn credential_preview(&self) -> &CredentialPreviewData {
+        match self {
+            CredentialProposal::V1(credential_proposal) => &credential_proposal.credential_proposal,
+            CredentialProposal::V2(credential_proposal) => &credential_proposal.credential_preview,
+        }
+    }
+
+    /// Proposed attribute values as JSON object: {"name": "value"}.
+    pub fn credential_values(&self) -> Map<String, Value> {
+        self.credential_preview().attributes.iter()
+            .map(|attribute| (attribute.name.clone(), attribute.value.clone()))
+            .collect()
+    }
+
+    /// Checks that the credential definition satisfies the filters of the proposal.
+    /// `schema_id` - the schema of the credential definition (`None` if not known).
+    pub fn check_cred_def(&self, cred_def_id: &str, schema_id: Option<&str>) -> VcxResult<()> {
+        trace!("CredentialProposal::check_cred_def >>> cred_def_id: {:?}, schema_id: {:?}", cred_def_id, schema_id);
+
+        let filters = self.filters()?;
+
+        let mismatch = |filter: &str, expected: &str| -> VcxResult<()> {
+            Err(VcxError::from_msg(VcxErrorKind::InvalidCredDefHandle,
+                                   format!("Credential Definition {} does not match the proposal: `{}` filter {:?} is expected", cred_def_id, filter, expected)))
+        };
+
+        if let Some(ref expected) = filters.cred_def_id {
+            if expected != cred_def_id { return mismatch("cred_def_id", expected); }
+        }
+
+        if let Some(ref expected) = filters.issuer_did {
+            // cred def id format: <issuer_did>:3:CL:<schema_seq_no>:<tag>
+            if expected != cred_def_id.split(":3:").next().unwrap_or(cred_def_id) { return mismatch("issuer_did", expected); }
+        }
+
+        if !filters.has_schema_filters() {
+            return Ok(());
+        }
+
+        let schema_id = schema_id
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidCredDefHandle,
+                                      format!("Cannot check the schema filters of the proposal: schema of Credential Definition {} is unknown", cred_def_id)))?;
+
+        if let Some(ref expected) = filters.schema_id {
+            if expected != schema_id { return mismatch("schema_id", expected); }
+        }
+
+        if filters.schema_issuer_did.is_none() && filters.schema_name.is_none() && filters.schema_version.is_none() {
+            return Ok(());
+        }
+
+        // schema id format: <issuer_did>:2:<name>:<version>
+        let (schema_issuer_did, name, version) = match schema_id.rsplitn(4, ':').collect::<Vec<&str>>().as_slice() {
+            [version, name, _, did] => (*did, *name, *version),
+            _ => return Err(VcxError::from_msg(VcxErrorKind::InvalidSchema, format!("Invalid Schema ID: {}", schema_id)))
+        };
+
+        let checks = [
+            ("schema_issuer_did", &filters.schema_issuer_did, schema_issuer_did),
+            ("schema_name", &filters.schema_name, name),
+            ("schema_version", &filters.schema_version, version),
+        ];
+
+        for (filter, expected, actual) in checks.iter() {
+            if let Some(expected) = expected {
+                if expected.as_str() != *actual { return mismatch(filter, expected); }
+            }
+        }
+
+        Ok(())
+    }
+
+    // filters of `1.0` proposal are `schema_id` and `cred_def_id` fields (empty if not set)
+    fn filters(&self) -> VcxResult<IndyCredentialFilter> {
+        match self {
+            CredentialProposal::V1(credential_proposal) => {
+                let not_empty = |value: &str| if value.is_empty() { None } else { Some(value.to_string()) };
+                Ok(IndyCredentialFilter {
+                    schema_id: not_empty(&credential_proposal.schema_id),
+                    cred_def_id: not_empty(&credential_proposal.cred_def_id),
+                    ..IndyCredentialFilter::default()
+                })
+            }
+            CredentialProposal::V2(credential_proposal) => {
+                if credential_proposal.filters_attach.get().is_none() {
+                    return Ok(IndyCredentialFilter::default());
+                }
+                let (_, filters) = credential_proposal.filters_attach_content()?;
+                ::serde_json::from_str(&filters)
+                    .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson,
+                                                      format!("Cannot parse filters of the Credential Proposal. Err: {:?}", err)))
+            }
+        }
+    }
+
     pub fn set_thread_id(self, thid: &str) -> Self {
         match self {
             CredentialProposal::V1(credential_proposal) => CredentialProposal::V1(credential_proposal.set_thread_id(thid)),
@@ -53,4 +265,81 @@ pub mod tests {
     pub fn _credential_proposal() -> CredentialProposal {
         CredentialProposal::V1(_credential_proposal_v1())
     }
-}
\ No newline at end of file
+
+    pub fn _credential_proposal_for_cred_def(cred_def_id: &str, schema_id: &str) -> CredentialProposal {
+        CredentialProposal::V1(_credential_proposal_v1()
+            .set_cred_def_id(cred_def_id.to_string())
+            .set_schema_id(schema_id.to_string()))
+    }
+
+    #[test]
+    fn test_credential_proposal_create_from_data_works() {
+        let data = CredentialProposalData {
+            attributes: json!({"name": "Alice", "age": "25"}).as_object().cloned().unwrap(),
+            schema_id: Some(String::from("schema:id")),
+            cred_def_id: Some(String::from("cred_def_id:id")),
+            ..CredentialProposalData::default()
+        };
+
+        let proposal = CredentialProposal::create_from_data(&data, Some(String::from("comment"))).unwrap();
+        match proposal {
+            CredentialProposal::V1(ref proposal) => {
+                assert_eq!("schema:id", proposal.schema_id);
+                assert_eq!("cred_def_id:id", proposal.cred_def_id);
+            }
+            _ => panic!("Unexpected proposal version")
+        }
+        assert_eq!(Some(String::from("comment")), proposal.comment());
+        assert_eq!(data.attributes, proposal.credential_values());
+
+        let data = CredentialProposalData { protocol_version: Some(String::from("2.0")), schema_name: Some(String::from("gvt")), ..data };
+
+        let proposal = CredentialProposal::create_from_data(&data, None).unwrap();
+        match proposal {
+            CredentialProposal::V2(ref proposal) => {
+                let (_, filters) = proposal.filters_attach_content().unwrap();
+                let filters: Value = ::serde_json::from_str(&filters).unwrap();
+                assert_eq!(json!({"schema_name": "gvt", "schema_id": "schema:id", "cred_def_id": "cred_def_id:id"}), filters);
+            }
+            _ => panic!("Unexpected proposal version")
+        }
+        assert_eq!(data.attributes, proposal.credential_values());
+
+        let data = CredentialProposalData { protocol_version: Some(String::from("3.0")), ..data };
+        assert_eq!(VcxErrorKind::InvalidOption, CredentialProposal::create_from_data(&data, None).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_credential_proposal_check_cred_def_works() {
+        let schema_id = "V4SGRU86Z58d6TV7PBUe6f:2:gvt:1.0";
+        let cred_def_id = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag";
+
+        let data = CredentialProposalData {
+            schema_id: Some(schema_id.to_string()),
+            cred_def_id: Some(cred_def_id.to_string()),
+            ..CredentialProposalData::default()
+        };
+
+        let proposal = CredentialProposal::create_from_data(&data, None).unwrap();
+        proposal.check_cred_def(cred_def_id, Some(schema_id)).unwrap();
+        assert_eq!(VcxErrorKind::InvalidCredDefHandle, proposal.check_cred_def("NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:other", Some(schema_id)).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidCredDefHandle, proposal.check_cred_def(cred_def_id, Some("V4SGRU86Z58d6TV7PBUe6f:2:gvt:2.0")).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidCredDefHandle, proposal.check_cred_def(cred_def_id, None).unwrap_err().kind());
+
+        let proposal = CredentialProposal::create_from_data(&CredentialProposalData::default(), None).unwrap();
+        proposal.check_cred_def(cred_def_id, None).unwrap();
+
+        let data = CredentialProposalData {
+            protocol_version: Some(String::from("2.0")),
+            schema_issuer_did: Some(String::from("V4SGRU86Z58d6TV7PBUe6f")),
+            schema_name: Some(String::from("gvt")),
+            issuer_did: Some(String::from("NcYxiDXkpYi6ov5FcYDi1e")),
+            ..CredentialProposalData::default()
+        };
+
+        let proposal = CredentialProposal::create_from_data(&data, None).unwrap();
+        proposal.check_cred_def(cred_def_id, Some(schema_id)).unwrap();
+        assert_eq!(VcxErrorKind::InvalidCredDefHandle, proposal.check_cred_def("V4SGRU86Z58d6TV7PBUe6f:3:CL:1:tag", Some(schema_id)).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidCredDefHandle, proposal.check_cred_def(cred_def_id, Some("V4SGRU86Z58d6TV7PBUe6f:2:other:1.0")).unwrap_err().kind());
+    }
+}
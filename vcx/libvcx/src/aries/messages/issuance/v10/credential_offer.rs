@@ -65,6 +65,11 @@ impl CredentialOffer {
         Ok(self)
     }
 
+    pub fn set_thread(mut self, thread: Thread) -> Self {
+        self.thread = Some(thread);
+        self
+    }
+
     pub fn set_thread_id(mut self, id: &str) -> Self {
         self.thread = Some(Thread::new().set_thid(id.to_string()));
         self
@@ -10,6 +10,7 @@ use crate::error::VcxResult;
 use crate::aries::messages::thread::Thread;
 use crate::aries::messages::attachment_format::{AttachmentFormats, AttachmentFormatTypes, AttachmentFormat};
 use crate::aries::messages::attachment::{Attachments, AttachmentId};
+use crate::aries::messages::mime_type::MimeType;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct CredentialProposal {
@@ -40,6 +41,11 @@ impl CredentialProposal {
         self
     }
 
+    pub fn add_credential_preview_data(mut self, name: &str, value: &serde_json::Value, mime_type: MimeType) -> VcxResult<CredentialProposal> {
+        self.credential_preview = self.credential_preview.add_value(name, value, mime_type)?;
+        Ok(self)
+    }
+
     pub fn set_indy_filters_attach(self, filters: &str) -> VcxResult<CredentialProposal> {
         self.set_filters_attach(filters, AttachmentFormatTypes::IndyCredential)
     }
@@ -87,7 +93,6 @@ impl Default for CredentialProposal {
 pub mod tests {
     use super::*;
     use crate::aries::messages::issuance::credential_offer::tests::{thread, thread_id, _value};
-    use crate::aries::messages::mime_type::MimeType;
 
     fn _attachment() -> ::serde_json::Value {
         json!({"credential offer": {}})
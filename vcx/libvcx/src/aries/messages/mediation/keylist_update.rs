@@ -0,0 +1,96 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdate {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub updates: Vec<KeylistUpdateItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdateItem {
+    pub recipient_key: String,
+    pub action: KeylistUpdateAction,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeylistUpdateAction {
+    Add,
+    Remove,
+}
+
+impl KeylistUpdate {
+    pub fn create() -> KeylistUpdate {
+        KeylistUpdate::default()
+    }
+
+    pub fn add_key(mut self, recipient_key: &str) -> KeylistUpdate {
+        self.updates.push(KeylistUpdateItem { recipient_key: recipient_key.to_string(), action: KeylistUpdateAction::Add });
+        self
+    }
+
+    pub fn remove_key(mut self, recipient_key: &str) -> KeylistUpdate {
+        self.updates.push(KeylistUpdateItem { recipient_key: recipient_key.to_string(), action: KeylistUpdateAction::Remove });
+        self
+    }
+}
+
+impl Default for KeylistUpdate {
+    fn default() -> KeylistUpdate {
+        KeylistUpdate {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::CoordinateMediation,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::KEYLIST_UPDATE.to_string()
+            },
+            updates: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+
+    pub fn _keylist_update() -> KeylistUpdate {
+        KeylistUpdate {
+            id: MessageId::id(),
+            updates: vec![
+                KeylistUpdateItem { recipient_key: _key_1(), action: KeylistUpdateAction::Add },
+                KeylistUpdateItem { recipient_key: _key_2(), action: KeylistUpdateAction::Remove },
+            ],
+            ..KeylistUpdate::default()
+        }
+    }
+
+    #[test]
+    fn test_keylist_update_build_works() {
+        let keylist_update = KeylistUpdate::create()
+            .add_key(&_key_1())
+            .remove_key(&_key_2());
+
+        assert_eq!(_keylist_update(), keylist_update);
+
+        let expected = json!({
+            "@id": "testid",
+            "@type": "https://didcomm.org/coordinate-mediation/1.0/keylist-update",
+            "updates": [
+                {"recipient_key": _key_1(), "action": "add"},
+                {"recipient_key": _key_2(), "action": "remove"}
+            ]
+        });
+        assert_eq!(expected, json!(keylist_update));
+    }
+}
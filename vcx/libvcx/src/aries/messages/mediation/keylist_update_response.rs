@@ -0,0 +1,101 @@
+use crate::aries::messages::thread::Thread;
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+use crate::aries::messages::mediation::keylist_update::KeylistUpdateAction;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdateResponse {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(default)]
+    pub updated: Vec<KeylistUpdated>,
+    #[serde(rename = "~thread")]
+    #[serde(default)]
+    pub thread: Thread,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeylistUpdated {
+    pub recipient_key: String,
+    pub action: KeylistUpdateAction,
+    pub result: KeylistUpdateResult,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeylistUpdateResult {
+    ClientError,
+    ServerError,
+    NoChange,
+    Success,
+}
+
+impl KeylistUpdated {
+    pub fn is_applied(&self) -> bool {
+        match self.result {
+            KeylistUpdateResult::Success | KeylistUpdateResult::NoChange => true,
+            KeylistUpdateResult::ClientError | KeylistUpdateResult::ServerError => false,
+        }
+    }
+}
+
+impl KeylistUpdateResponse {
+    pub fn create() -> KeylistUpdateResponse {
+        KeylistUpdateResponse::default()
+    }
+
+    pub fn set_updated(mut self, updated: Vec<KeylistUpdated>) -> KeylistUpdateResponse {
+        self.updated = updated;
+        self
+    }
+}
+
+threadlike!(KeylistUpdateResponse);
+
+impl Default for KeylistUpdateResponse {
+    fn default() -> KeylistUpdateResponse {
+        KeylistUpdateResponse {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::CoordinateMediation,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::KEYLIST_UPDATE_RESPONSE.to_string()
+            },
+            updated: Vec::new(),
+            thread: Thread::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+
+    #[test]
+    fn test_keylist_update_response_deserialization_works() {
+        let response = json!({
+            "@id": "testid",
+            "@type": "https://didcomm.org/coordinate-mediation/1.0/keylist-update-response",
+            "updated": [
+                {"recipient_key": _key_1(), "action": "add", "result": "success"},
+                {"recipient_key": _key_2(), "action": "remove", "result": "client_error"}
+            ],
+            "~thread": {"thid": "test_id"}
+        });
+
+        let response: KeylistUpdateResponse = ::serde_json::from_value(response).unwrap();
+        assert_eq!(2, response.updated.len());
+        assert!(response.updated[0].is_applied());
+        assert!(!response.updated[1].is_applied());
+        assert!(response.from_thread("test_id"));
+    }
+}
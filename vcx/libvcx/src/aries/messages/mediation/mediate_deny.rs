@@ -0,0 +1,67 @@
+use crate::aries::messages::thread::Thread;
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediateDeny {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(rename = "~thread")]
+    #[serde(default)]
+    pub thread: Thread,
+}
+
+impl MediateDeny {
+    pub fn create() -> MediateDeny {
+        MediateDeny::default()
+    }
+}
+
+threadlike!(MediateDeny);
+
+impl Default for MediateDeny {
+    fn default() -> MediateDeny {
+        MediateDeny {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::CoordinateMediation,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::MEDIATE_DENY.to_string()
+            },
+            thread: Thread::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::response::tests::*;
+
+    pub fn _mediate_deny() -> MediateDeny {
+        MediateDeny {
+            id: MessageId::id(),
+            thread: _thread(),
+            ..MediateDeny::default()
+        }
+    }
+
+    #[test]
+    fn test_mediate_deny_build_works() {
+        let mediate_deny = MediateDeny::create()
+            .set_thread(_thread());
+
+        assert_eq!(_mediate_deny(), mediate_deny);
+
+        let expected = r#"{"@id":"testid","@type":"https://didcomm.org/coordinate-mediation/1.0/mediate-deny","~thread":{"received_orders":{},"sender_order":0,"thid":"test_id"}}"#;
+        assert_eq!(expected, json!(mediate_deny).to_string());
+    }
+}
@@ -0,0 +1,93 @@
+use crate::aries::messages::thread::Thread;
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediateGrant {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub endpoint: String,
+    #[serde(default)]
+    pub routing_keys: Vec<String>,
+    #[serde(rename = "~thread")]
+    #[serde(default)]
+    pub thread: Thread,
+}
+
+impl MediateGrant {
+    pub fn create() -> MediateGrant {
+        MediateGrant::default()
+    }
+
+    pub fn set_endpoint(mut self, endpoint: String) -> MediateGrant {
+        self.endpoint = endpoint;
+        self
+    }
+
+    pub fn set_routing_keys(mut self, routing_keys: Vec<String>) -> MediateGrant {
+        self.routing_keys = routing_keys;
+        self
+    }
+}
+
+threadlike!(MediateGrant);
+
+impl Default for MediateGrant {
+    fn default() -> MediateGrant {
+        MediateGrant {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::CoordinateMediation,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::MEDIATE_GRANT.to_string()
+            },
+            endpoint: String::new(),
+            routing_keys: Vec::new(),
+            thread: Thread::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::response::tests::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+
+    pub fn _mediate_grant() -> MediateGrant {
+        MediateGrant {
+            id: MessageId::id(),
+            endpoint: _service_endpoint(),
+            routing_keys: _routing_keys(),
+            thread: _thread(),
+            ..MediateGrant::default()
+        }
+    }
+
+    #[test]
+    fn test_mediate_grant_build_works() {
+        let mediate_grant = MediateGrant::create()
+            .set_endpoint(_service_endpoint())
+            .set_routing_keys(_routing_keys())
+            .set_thread(_thread());
+
+        assert_eq!(_mediate_grant(), mediate_grant);
+    }
+
+    #[test]
+    fn test_mediate_grant_deserialization_works_for_missed_optional_fields() {
+        let mediate_grant = r#"{"@id":"testid","@type":"https://didcomm.org/coordinate-mediation/1.0/mediate-grant","endpoint":"http://localhost:8080"}"#;
+
+        let mediate_grant: MediateGrant = ::serde_json::from_str(mediate_grant).unwrap();
+        assert_eq!("http://localhost:8080", mediate_grant.endpoint);
+        assert!(mediate_grant.routing_keys.is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MediateRequest {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+}
+
+impl MediateRequest {
+    pub fn create() -> MediateRequest {
+        MediateRequest::default()
+    }
+}
+
+impl Default for MediateRequest {
+    fn default() -> MediateRequest {
+        MediateRequest {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::CoordinateMediation,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::MEDIATE_REQUEST.to_string()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub fn _mediate_request() -> MediateRequest {
+        MediateRequest {
+            id: MessageId::id(),
+            ..MediateRequest::default()
+        }
+    }
+
+    #[test]
+    fn test_mediate_request_build_works() {
+        let mediate_request = MediateRequest::create();
+
+        assert_eq!(_mediate_request(), mediate_request);
+
+        let expected = r#"{"@id":"testid","@type":"https://didcomm.org/coordinate-mediation/1.0/mediate-request"}"#;
+        assert_eq!(expected, json!(mediate_request).to_string());
+    }
+}
@@ -33,6 +33,9 @@ pub mod ack;
 #[macro_use]
 pub mod transport;
 pub mod connection;
+pub mod didexchange;
+pub mod did_rotate;
+pub mod did_doc_update;
 pub mod error;
 pub mod forward;
 pub mod attachment;
@@ -45,10 +48,13 @@ pub mod discovery;
 pub mod trust_ping;
 pub mod basic_message;
 pub mod localization;
+pub mod timing;
 pub mod outofband;
 pub mod questionanswer;
 pub mod committedanswer;
 pub mod invite_action;
 pub mod message_with_attachment;
 pub mod message_with_thread;
-pub mod alias;
\ No newline at end of file
+pub mod alias;
+pub mod mediation;
+pub mod pickup;
\ No newline at end of file
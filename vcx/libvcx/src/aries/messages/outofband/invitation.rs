@@ -5,6 +5,8 @@ use crate::aries::messages::outofband::v11::invitation::Invitation as Invitation
 use crate::aries::messages::a2a::MessageId;
 use crate::aries::messages::connection::did_doc::Service;
 use crate::aries::messages::attachment::Attachments;
+use crate::aries::messages::outofband::HandshakeProtocol;
+use crate::aries::resolver;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(untagged)]
@@ -28,6 +30,20 @@ impl Invitation {
         }
     }
 
+    pub fn is_expired(&self) -> bool {
+        match self {
+            Invitation::V10(invitation) => invitation.is_expired(),
+            Invitation::V11(invitation) => invitation.is_expired(),
+        }
+    }
+
+    pub fn goal_code(&self) -> Option<&str> {
+        match self {
+            Invitation::V10(invitation) => invitation.goal_code.as_deref(),
+            Invitation::V11(invitation) => invitation.goal_code.as_deref(),
+        }
+    }
+
     pub fn public_did(&self) -> Option<&str> {
         match self {
             Invitation::V10(invitation) => invitation.public_did.as_deref(),
@@ -49,6 +65,14 @@ impl Invitation {
         }
     }
 
+    /// The most preferred of `handshake_protocols` supported by the library.
+    pub fn handshake_protocol(&self) -> Option<HandshakeProtocol> {
+        self.handshake_protocols()
+            .iter()
+            .filter_map(|protocol| HandshakeProtocol::from_id(protocol))
+            .next()
+    }
+
     pub fn services(&self) -> &Vec<Service> {
         match self {
             Invitation::V10(invitation) => &invitation.service,
@@ -56,6 +80,43 @@ impl Invitation {
         }
     }
 
+    fn services_mut(&mut self) -> &mut Vec<Service> {
+        match self {
+            Invitation::V10(invitation) => &mut invitation.service,
+            Invitation::V11(invitation) => &mut invitation.services,
+        }
+    }
+
+    /// Whether the invitation refers to the public DID of the inviter instead of embedding its keys.
+    pub fn is_public_did_invitation(&self) -> bool {
+        match self.services().is_empty() {
+            true => self.public_did().is_some(),
+            false => self.services().iter().all(Service::is_did_reference),
+        }
+    }
+
+    /// Resolves services given as DIDs.
+    /// `public_did` is resolved into the service if the invitation does not contain any.
+    pub fn resolve_services(&mut self) -> VcxResult<()> {
+        trace!("Invitation::resolve_services >>>");
+
+        let public_did = self.public_did().map(String::from);
+        let services = self.services_mut();
+
+        for service in services.iter_mut() {
+            if service.is_did_reference() {
+                *service = resolver::resolve_service(&service.id)?;
+            }
+        }
+
+        if let (true, Some(public_did)) = (services.is_empty(), public_did) {
+            services.push(resolver::resolve_service(&public_did)?);
+        }
+
+        trace!("Invitation::resolve_services <<< services: {:?}", secret!(services));
+        Ok(())
+    }
+
     pub fn requests_attach(&self) -> &Attachments {
         match self {
             Invitation::V10(invitation) => &invitation.request_attach,
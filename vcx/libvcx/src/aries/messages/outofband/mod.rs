@@ -4,4 +4,134 @@ pub mod invitation;
 pub mod handshake_reuse;
 pub mod handshake_reuse_accepted;
 
+use serde::{de, Deserialize, Deserializer, Serializer};
+use serde::ser::SerializeSeq;
+use serde_json::Value;
+
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+use crate::aries::messages::connection::did_doc::Service;
+use crate::aries::messages::connection::did_peer::is_peer_did;
+use crate::aries::resolver;
+
 pub const SUPPORTED_HANDSHAKE_PROTOCOL: &str = "connections/1.0";
+pub const DIDEXCHANGE_HANDSHAKE_PROTOCOL: &str = "didexchange/1.0";
+
+/// Protocols which can be listed in `handshake_protocols` of Out-of-Band Invitation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HandshakeProtocol {
+    Connections,
+    DidExchange,
+}
+
+impl HandshakeProtocol {
+    pub fn id(&self) -> String {
+        match self {
+            // Out-of-Band RFC contains that format of handshake protocol for Connections protocol.
+            // But it differs from format in Connection RFC where we use DID's
+            HandshakeProtocol::Connections => format!("{}/{}", MessageTypeFamilies::ENDPOINT, SUPPORTED_HANDSHAKE_PROTOCOL),
+            HandshakeProtocol::DidExchange => MessageTypeFamilies::DidExchange.id(),
+        }
+    }
+
+    /// Parses entry of `handshake_protocols` given in either `https://didcomm.org/didexchange/1.0`
+    /// or `did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/didexchange/1.0/` form.
+    pub fn from_id(id: &str) -> Option<HandshakeProtocol> {
+        let id = id.trim_end_matches('/');
+
+        if id.ends_with(SUPPORTED_HANDSHAKE_PROTOCOL) {
+            Some(HandshakeProtocol::Connections)
+        } else if id.ends_with(DIDEXCHANGE_HANDSHAKE_PROTOCOL) {
+            Some(HandshakeProtocol::DidExchange)
+        } else {
+            None
+        }
+    }
+}
+
+/// Deserializes Out-of-Band services which can be either inline objects or DIDs.
+/// Peer DIDs are resolved in place, other DIDs are kept as references resolved on accepting the invitation.
+pub fn deserialize_services<'de, D>(deserializer: D) -> Result<Vec<Service>, D::Error> where D: Deserializer<'de> {
+    let services: Vec<Value> = Deserialize::deserialize(deserializer)?;
+
+    services
+        .into_iter()
+        .map(|service| match service {
+            Value::String(did) if is_peer_did(&did) => resolver::resolve_service(&did).map_err(de::Error::custom),
+            Value::String(did) if did.starts_with(resolver::DID_PREFIX) => Ok(Service::did_reference(&did)),
+            Value::String(did) => Err(de::Error::custom(format!("Out-of-Band service must be either an object or a DID, found: {}", did))),
+            service => Service::deserialize(service).map_err(de::Error::custom),
+        })
+        .collect()
+}
+
+/// Serializes Out-of-Band services referencing DIDs as DID strings.
+pub fn serialize_services<S>(services: &[Service], serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+    let mut seq = serializer.serialize_seq(Some(services.len()))?;
+    for service in services {
+        if service.is_did_reference() {
+            seq.serialize_element(&service.id)?;
+        } else {
+            seq.serialize_element(service)?;
+        }
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::aries::messages::connection::did_peer::{self, PeerDidNumalgo};
+
+    #[derive(Serialize, Deserialize)]
+    struct Services {
+        #[serde(deserialize_with = "deserialize_services")]
+        #[serde(serialize_with = "serialize_services")]
+        services: Vec<Service>
+    }
+
+    #[test]
+    fn test_deserialize_services_works() {
+        let peer_did = did_peer::generate(&_did_doc(), PeerDidNumalgo::Numalgo2).unwrap().id;
+
+        let services: Services = ::serde_json::from_value(json!({
+            "services": [peer_did, _service(), "did:sov:V4SGRU86Z58d6TV7PBUe6f"]
+        })).unwrap();
+
+        assert_eq!(3, services.services.len());
+        assert_eq!(peer_did, services.services[0].id);
+        assert_eq!(_recipient_keys(), services.services[0].recipient_keys);
+        assert_eq!(_routing_keys(), services.services[0].routing_keys);
+        assert_eq!(_service(), services.services[1]);
+        assert_eq!(Service::did_reference("did:sov:V4SGRU86Z58d6TV7PBUe6f"), services.services[2]);
+        assert!(services.services[2].is_did_reference());
+
+        ::serde_json::from_value::<Services>(json!({"services": [_id()]})).unwrap_err();
+    }
+
+    #[test]
+    fn test_serialize_services_works() {
+        let services = Services {
+            services: vec![_service(), Service::did_reference("did:sov:V4SGRU86Z58d6TV7PBUe6f")]
+        };
+
+        let services_json = json!(services);
+        assert_eq!(json!(_service()), services_json["services"][0]);
+        assert_eq!(json!("did:sov:V4SGRU86Z58d6TV7PBUe6f"), services_json["services"][1]);
+
+        let services_: Services = ::serde_json::from_value(services_json).unwrap();
+        assert_eq!(services.services, services_.services);
+    }
+
+    #[test]
+    fn test_handshake_protocol_from_id_works() {
+        assert_eq!(Some(HandshakeProtocol::Connections), HandshakeProtocol::from_id("https://didcomm.org/connections/1.0"));
+        assert_eq!(Some(HandshakeProtocol::Connections), HandshakeProtocol::from_id("did:sov:BzCbsNYhMrjHiqZDTUASHg;spec/connections/1.0/"));
+        assert_eq!(Some(HandshakeProtocol::DidExchange), HandshakeProtocol::from_id("https://didcomm.org/didexchange/1.0"));
+        assert_eq!(None, HandshakeProtocol::from_id("https://didcomm.org/didexchange/2.0"));
+
+        assert_eq!(Some(HandshakeProtocol::DidExchange), HandshakeProtocol::from_id(&HandshakeProtocol::DidExchange.id()));
+        assert_eq!(Some(HandshakeProtocol::Connections), HandshakeProtocol::from_id(&HandshakeProtocol::Connections.id()));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::aries::messages::timing::Timing;
 use crate::aries::messages::a2a::{A2AMessage, MessageId};
 use crate::aries::messages::attachment::{Attachments, AttachmentId};
 use crate::aries::messages::connection::did_doc::Service;
@@ -9,7 +10,7 @@ use crate::aries::messages::a2a::message_type::{
     MessageTypeVersion,
 };
 use crate::aries::utils::normalize_service_keys;
-use crate::aries::messages::outofband::SUPPORTED_HANDSHAKE_PROTOCOL;
+use crate::aries::messages::outofband::{SUPPORTED_HANDSHAKE_PROTOCOL, HandshakeProtocol, deserialize_services, serialize_services};
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Invitation {
@@ -29,12 +30,17 @@ pub struct Invitation {
     #[serde(default)]
     #[serde(rename = "request~attach")]
     pub request_attach: Attachments,
+    #[serde(deserialize_with = "deserialize_services")]
+    #[serde(serialize_with = "serialize_services")]
     pub service: Vec<Service>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "profileUrl")]
     pub profile_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub public_did: Option<String>,
+    #[serde(rename = "~timing")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Timing>,
 }
 
 impl Invitation {
@@ -82,6 +88,11 @@ impl Invitation {
         self
     }
 
+    pub fn set_handshake_protocols(mut self, handshake_protocols: &[HandshakeProtocol]) -> Invitation {
+        self.handshake_protocols = handshake_protocols.iter().map(HandshakeProtocol::id).collect();
+        self
+    }
+
     pub fn set_service(mut self, service: Service) -> Invitation {
         self.service = vec![service];
         self
@@ -109,6 +120,16 @@ impl Invitation {
         self
     }
 
+    /// Sets `~timing.expires_time` decorator, the invitation is not accepted after it expires.
+    pub fn set_opt_expiration(mut self, ttl: Option<u64>) -> Invitation {
+        self.timing = ttl.map(Timing::expiring_in);
+        self
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.timing.as_ref().map_or(false, Timing::is_expired)
+    }
+
     pub fn validate(&self) -> VcxResult<()> {
         Ok(())
     }
@@ -131,6 +152,7 @@ impl Default for Invitation {
             service: Default::default(),
             profile_url: Default::default(),
             public_did: Default::default(),
+            timing: Default::default(),
         }
     }
 }
@@ -230,6 +252,19 @@ pub mod tests {
         assert_eq!(expected, json!(invitation).to_string());
     }
 
+    #[test]
+    fn test_outofband_invitation_handshake_protocols_works() {
+        use crate::aries::messages::outofband::invitation::Invitation as OutofbandInvitation;
+
+        let invitation = Invitation::create()
+            .set_handshake_protocols(&[HandshakeProtocol::DidExchange, HandshakeProtocol::Connections]);
+        assert_eq!(vec!["https://didcomm.org/didexchange/1.0", "https://didcomm.org/connections/1.0"], invitation.handshake_protocols);
+
+        assert_eq!(Some(HandshakeProtocol::DidExchange), OutofbandInvitation::V10(invitation).handshake_protocol());
+        assert_eq!(Some(HandshakeProtocol::Connections), OutofbandInvitation::V10(_invitation()).handshake_protocol());
+        assert_eq!(None, OutofbandInvitation::V10(_invitation_no_handshake()).handshake_protocol());
+    }
+
     #[test]
     fn test_outofband_invitation_validate_works() {
         _invitation().validate().unwrap();
@@ -1,3 +1,4 @@
+use crate::aries::messages::timing::Timing;
 use crate::aries::messages::a2a::{A2AMessage, MessageId};
 use crate::aries::messages::attachment::{Attachments, AttachmentId};
 use crate::aries::messages::connection::did_doc::Service;
@@ -9,7 +10,7 @@ use crate::aries::messages::a2a::message_type::{
     MessageTypeVersion,
 };
 use crate::aries::utils::normalize_service_keys;
-use crate::aries::messages::outofband::SUPPORTED_HANDSHAKE_PROTOCOL;
+use crate::aries::messages::outofband::{SUPPORTED_HANDSHAKE_PROTOCOL, HandshakeProtocol, deserialize_services, serialize_services};
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub struct Invitation {
@@ -32,12 +33,17 @@ pub struct Invitation {
     #[serde(default)]
     #[serde(rename = "requests~attach")]
     pub request_attach: Attachments,
+    #[serde(deserialize_with = "deserialize_services")]
+    #[serde(serialize_with = "serialize_services")]
     pub services: Vec<Service>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(rename = "profileUrl")]
     pub profile_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub public_did: Option<String>,
+    #[serde(rename = "~timing")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Timing>,
 }
 
 impl Invitation {
@@ -85,6 +91,11 @@ impl Invitation {
         self
     }
 
+    pub fn set_handshake_protocols(mut self, handshake_protocols: &[HandshakeProtocol]) -> Invitation {
+        self.handshake_protocols = handshake_protocols.iter().map(HandshakeProtocol::id).collect();
+        self
+    }
+
     pub fn set_service(mut self, service: Service) -> Invitation {
         self.services = vec![service];
         self
@@ -112,6 +123,16 @@ impl Invitation {
         self
     }
 
+    /// Sets `~timing.expires_time` decorator, the invitation is not accepted after it expires.
+    pub fn set_opt_expiration(mut self, ttl: Option<u64>) -> Invitation {
+        self.timing = ttl.map(Timing::expiring_in);
+        self
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.timing.as_ref().map_or(false, Timing::is_expired)
+    }
+
     pub fn validate(&self) -> VcxResult<()> {
         Ok(())
     }
@@ -135,6 +156,7 @@ impl Default for Invitation {
             services: Default::default(),
             profile_url: Default::default(),
             public_did: Default::default(),
+            timing: Default::default(),
         }
     }
 }
@@ -305,4 +327,20 @@ pub mod tests {
         invitation.normalize_service_keys().unwrap();
         assert_eq!(_service(), invitation.services[0])
     }
+
+    #[test]
+    pub fn test_invitation_with_peer_did_service_works() {
+        use crate::aries::messages::connection::did_doc::tests::{_did_doc, _recipient_keys, _service_endpoint};
+        use crate::aries::messages::connection::did_peer::{self, PeerDidNumalgo};
+
+        let peer_did = did_peer::generate(&_did_doc(), PeerDidNumalgo::Numalgo2).unwrap().id;
+
+        let mut invitation = json!(_invitation());
+        invitation["services"] = json!([peer_did]);
+
+        let invitation: Invitation = serde_json::from_value(invitation).unwrap();
+        assert_eq!(peer_did, invitation.services[0].id);
+        assert_eq!(_recipient_keys(), invitation.services[0].recipient_keys);
+        assert_eq!(_service_endpoint(), invitation.services[0].service_endpoint);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,144 @@
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::aries::messages::thread::Thread;
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Delivery {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+    #[serde(rename = "~attach")]
+    #[serde(default)]
+    pub attachments: Vec<DeliveredMessage>,
+    #[serde(rename = "~thread")]
+    #[serde(default)]
+    pub thread: Thread,
+}
+
+/// Packed message queued on the mediator. The id is used to acknowledge the message with `messages-received`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliveredMessage {
+    #[serde(rename = "@id")]
+    pub id: String,
+    pub data: DeliveredMessageData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DeliveredMessageData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base64: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json: Option<Value>,
+}
+
+impl DeliveredMessage {
+    pub fn new(id: &str, packed_message: &[u8]) -> DeliveredMessage {
+        DeliveredMessage {
+            id: id.to_string(),
+            data: DeliveredMessageData { base64: Some(base64::encode_config(packed_message, base64::URL_SAFE)), json: None },
+        }
+    }
+
+    /// Returns the packed message as it was received by the mediator.
+    pub fn packed_message(&self) -> VcxResult<Vec<u8>> {
+        match (&self.data.base64, &self.data.json) {
+            (Some(ref data), _) =>
+                base64::decode_config(data, base64::URL_SAFE)
+                    .or_else(|_| base64::decode(data))
+                    .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAttachmentEncoding, format!("Cannot decode delivered message {}: {}", self.id, err))),
+            (None, Some(ref json)) => Ok(json.to_string().into_bytes()),
+            (None, None) => Err(VcxError::from_msg(VcxErrorKind::InvalidAttachmentEncoding, format!("Delivered message {} does not contain data", self.id)))
+        }
+    }
+}
+
+impl Delivery {
+    pub fn create() -> Delivery {
+        Delivery::default()
+    }
+
+    pub fn add_message(mut self, message: DeliveredMessage) -> Delivery {
+        self.attachments.push(message);
+        self
+    }
+}
+
+threadlike!(Delivery);
+
+impl Default for Delivery {
+    fn default() -> Delivery {
+        Delivery {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::MessagePickup,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::PICKUP_DELIVERY.to_string()
+            },
+            recipient_key: None,
+            attachments: Vec::new(),
+            thread: Thread::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::response::tests::*;
+
+    fn _packed_message() -> Vec<u8> {
+        br#"{"protected":"eyJlbmMiOiJ4Y2hhY2hhMjBwb2x5MTMwNV9pZXRmIn0","iv":"iv","ciphertext":"ciphertext","tag":"tag"}"#.to_vec()
+    }
+
+    pub fn _delivery() -> Delivery {
+        Delivery {
+            id: MessageId::id(),
+            attachments: vec![DeliveredMessage::new("message_1", &_packed_message())],
+            thread: _thread(),
+            ..Delivery::default()
+        }
+    }
+
+    #[test]
+    fn test_delivery_build_works() {
+        let delivery = Delivery::create()
+            .add_message(DeliveredMessage::new("message_1", &_packed_message()))
+            .set_thread(_thread());
+
+        assert_eq!(_delivery(), delivery);
+        assert_eq!(_packed_message(), delivery.attachments[0].packed_message().unwrap());
+    }
+
+    #[test]
+    fn test_delivered_message_data_forms() {
+        let packed_message: Value = ::serde_json::from_slice(&_packed_message()).unwrap();
+
+        let delivery = json!({
+            "@id": "testid",
+            "@type": "https://didcomm.org/messagepickup/2.0/delivery",
+            "~attach": [
+                {"@id": "message_1", "data": {"json": packed_message}},
+                {"@id": "message_2", "data": {"base64": base64::encode(&_packed_message())}},
+                {"@id": "message_3", "data": {}}
+            ]
+        });
+        let delivery: Delivery = ::serde_json::from_value(delivery).unwrap();
+
+        let message_1: Value = ::serde_json::from_slice(&delivery.attachments[0].packed_message().unwrap()).unwrap();
+        assert_eq!(packed_message, message_1);
+        assert_eq!(_packed_message(), delivery.attachments[1].packed_message().unwrap());
+        assert_eq!(VcxErrorKind::InvalidAttachmentEncoding, delivery.attachments[2].packed_message().unwrap_err().kind());
+    }
+}
@@ -0,0 +1,71 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeliveryRequest {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub limit: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+}
+
+impl DeliveryRequest {
+    pub fn create() -> DeliveryRequest {
+        DeliveryRequest::default()
+    }
+
+    pub fn set_limit(mut self, limit: u32) -> DeliveryRequest {
+        self.limit = limit;
+        self
+    }
+
+    pub fn set_recipient_key(mut self, recipient_key: Option<String>) -> DeliveryRequest {
+        self.recipient_key = recipient_key;
+        self
+    }
+}
+
+impl Default for DeliveryRequest {
+    fn default() -> DeliveryRequest {
+        DeliveryRequest {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::MessagePickup,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::PICKUP_DELIVERY_REQUEST.to_string()
+            },
+            limit: 10,
+            recipient_key: None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::did_doc::tests::*;
+
+    #[test]
+    fn test_delivery_request_build_works() {
+        let delivery_request = DeliveryRequest::create()
+            .set_limit(5)
+            .set_recipient_key(Some(_key_1()));
+
+        let expected = json!({
+            "@id": delivery_request.id,
+            "@type": "https://didcomm.org/messagepickup/2.0/delivery-request",
+            "limit": 5,
+            "recipient_key": _key_1()
+        });
+        assert_eq!(expected, json!(delivery_request));
+    }
+}
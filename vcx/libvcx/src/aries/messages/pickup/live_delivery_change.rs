@@ -0,0 +1,56 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LiveDeliveryChange {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub live_delivery: bool,
+}
+
+impl LiveDeliveryChange {
+    pub fn create() -> LiveDeliveryChange {
+        LiveDeliveryChange::default()
+    }
+
+    pub fn set_live_delivery(mut self, live_delivery: bool) -> LiveDeliveryChange {
+        self.live_delivery = live_delivery;
+        self
+    }
+}
+
+impl Default for LiveDeliveryChange {
+    fn default() -> LiveDeliveryChange {
+        LiveDeliveryChange {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::MessagePickup,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::PICKUP_LIVE_DELIVERY_CHANGE.to_string()
+            },
+            live_delivery: false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_delivery_change_serialization_works() {
+        let live_delivery_change = LiveDeliveryChange { id: MessageId::id(), ..LiveDeliveryChange::create() }
+            .set_live_delivery(true);
+
+        let expected = r#"{"@id":"testid","@type":"https://didcomm.org/messagepickup/2.0/live-delivery-change","live_delivery":true}"#;
+        assert_eq!(expected, json!(live_delivery_change).to_string());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MessagesReceived {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub message_id_list: Vec<String>,
+}
+
+impl MessagesReceived {
+    pub fn create() -> MessagesReceived {
+        MessagesReceived::default()
+    }
+
+    pub fn set_message_id_list(mut self, message_id_list: Vec<String>) -> MessagesReceived {
+        self.message_id_list = message_id_list;
+        self
+    }
+}
+
+impl Default for MessagesReceived {
+    fn default() -> MessagesReceived {
+        MessagesReceived {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::MessagePickup,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::PICKUP_MESSAGES_RECEIVED.to_string()
+            },
+            message_id_list: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_received_serialization_works() {
+        let messages_received = MessagesReceived { id: MessageId::id(), ..MessagesReceived::create() }
+            .set_message_id_list(vec![String::from("message_1"), String::from("message_2")]);
+
+        let expected = r#"{"@id":"testid","@type":"https://didcomm.org/messagepickup/2.0/messages-received","message_id_list":["message_1","message_2"]}"#;
+        assert_eq!(expected, json!(messages_received).to_string());
+    }
+}
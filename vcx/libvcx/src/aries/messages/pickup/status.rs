@@ -0,0 +1,102 @@
+use crate::aries::messages::thread::Thread;
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Status {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+    pub message_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longest_waited_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub newest_received_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oldest_received_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub live_delivery: Option<bool>,
+    #[serde(rename = "~thread")]
+    #[serde(default)]
+    pub thread: Thread,
+}
+
+impl Status {
+    pub fn create() -> Status {
+        Status::default()
+    }
+
+    pub fn set_message_count(mut self, message_count: u32) -> Status {
+        self.message_count = message_count;
+        self
+    }
+}
+
+threadlike!(Status);
+
+impl Default for Status {
+    fn default() -> Status {
+        Status {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::MessagePickup,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::PICKUP_STATUS.to_string()
+            },
+            recipient_key: None,
+            message_count: 0,
+            longest_waited_seconds: None,
+            newest_received_time: None,
+            oldest_received_time: None,
+            total_bytes: None,
+            live_delivery: None,
+            thread: Thread::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::connection::response::tests::*;
+
+    pub fn _status() -> Status {
+        Status {
+            id: MessageId::id(),
+            message_count: 2,
+            thread: _thread(),
+            ..Status::default()
+        }
+    }
+
+    #[test]
+    fn test_status_build_works() {
+        let status = Status::create()
+            .set_message_count(2)
+            .set_thread(_thread());
+
+        assert_eq!(_status(), status);
+    }
+
+    #[test]
+    fn test_status_deserialization_works() {
+        let status = r#"{"@id":"testid","@type":"https://didcomm.org/messagepickup/2.0/status","message_count":2,"total_bytes":512,"live_delivery":false}"#;
+
+        let status: Status = ::serde_json::from_str(status).unwrap();
+        assert_eq!(2, status.message_count);
+        assert_eq!(Some(512), status.total_bytes);
+        assert_eq!(Some(false), status.live_delivery);
+        assert_eq!(None, status.recipient_key);
+    }
+}
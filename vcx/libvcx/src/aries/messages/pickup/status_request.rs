@@ -0,0 +1,56 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StatusRequest {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient_key: Option<String>,
+}
+
+impl StatusRequest {
+    pub fn create() -> StatusRequest {
+        StatusRequest::default()
+    }
+
+    pub fn set_recipient_key(mut self, recipient_key: Option<String>) -> StatusRequest {
+        self.recipient_key = recipient_key;
+        self
+    }
+}
+
+impl Default for StatusRequest {
+    fn default() -> StatusRequest {
+        StatusRequest {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::MessagePickup,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::PICKUP_STATUS_REQUEST.to_string()
+            },
+            recipient_key: None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_request_serialization_works() {
+        let status_request = StatusRequest { id: MessageId::id(), ..StatusRequest::create() };
+
+        let expected = r#"{"@id":"testid","@type":"https://didcomm.org/messagepickup/2.0/status-request"}"#;
+        assert_eq!(expected, json!(status_request).to_string());
+    }
+}
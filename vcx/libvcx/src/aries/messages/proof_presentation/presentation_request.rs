@@ -77,6 +77,19 @@ impl PresentationRequest {
         }
     }
 
+    /// Resolves keys of `~service` decorator given as DIDs.
+    pub fn resolve_services(&mut self) -> VcxResult<()> {
+        let service = match self {
+            PresentationRequest::V1(presentation_request) => presentation_request.service.as_mut(),
+            PresentationRequest::V2(presentation_request) => presentation_request.service.as_mut(),
+        };
+
+        match service {
+            Some(service) => service.resolve_keys(),
+            None => Ok(())
+        }
+    }
+
     pub fn request_presentations_attach(&self) -> &Attachments {
         match self {
             PresentationRequest::V1(presentation_request) => &presentation_request.request_presentations_attach,
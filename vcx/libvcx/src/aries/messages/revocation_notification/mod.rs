@@ -0,0 +1,3 @@
+pub mod v10;
+pub mod v20;
+pub mod revoke;
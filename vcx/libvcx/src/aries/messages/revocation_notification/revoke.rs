@@ -0,0 +1,151 @@
+use serde::{de, Deserialize, Deserializer};
+
+use crate::aries::messages::revocation_notification::v10::Revoke as RevokeV1;
+use crate::aries::messages::revocation_notification::v20::{Revoke as RevokeV2, INDY_ANONCREDS_FORMAT};
+use crate::aries::messages::a2a::message_type::{MessageType, MessageTypeVersion};
+use crate::error::prelude::*;
+
+/*
+    Revocation Notification protocol: the issuer tells the holder that the credential it issued has been revoked.
+
+    1.0 (Aries RFC 0183) identifies the credential by the thread id of its issuance.
+    Some agents put `indy::<rev_reg_id>::<cred_rev_id>` there instead, this form is accepted as well.
+    2.0 (Aries RFC 0721) identifies the credential by `<rev_reg_id>::<cred_rev_id>`.
+*/
+
+const INDY_THREAD_ID_PREFIX: &str = "indy::";
+
+#[derive(Debug, Serialize, PartialEq, Clone)]
+#[serde(untagged)]
+pub enum RevocationNotification {
+    V1(RevokeV1),
+    V2(RevokeV2),
+}
+
+/// Identifier of the revoked credential.
+#[derive(Debug, PartialEq, Clone)]
+pub enum RevokedCredential {
+    /// thread id of the credential issuance
+    Thread(String),
+    Revocation { rev_reg_id: String, cred_rev_id: String },
+}
+
+impl RevocationNotification {
+    /// Creates notification about revocation of the credential issued on `thread_id` thread.
+    /// `version` is the version of Revocation Notification protocol: `1.0` (default) or `2.0`.
+    pub fn create(version: Option<&str>, thread_id: &str, rev_reg_id: &str, cred_rev_id: &str, comment: Option<String>) -> VcxResult<RevocationNotification> {
+        match version {
+            None | Some("1.0") => {
+                Ok(RevocationNotification::V1(
+                    RevokeV1::create()
+                        .set_thread_id(thread_id)
+                        .set_comment(comment)
+                ))
+            }
+            Some("2.0") => {
+                Ok(RevocationNotification::V2(
+                    RevokeV2::create()
+                        .set_credential_id(rev_reg_id, cred_rev_id)
+                        .set_comment(comment)
+                ))
+            }
+            Some(version) => {
+                Err(VcxError::from_msg(VcxErrorKind::InvalidOption,
+                                       format!("Unsupported Revocation Notification protocol version: {:?}. Expected: \"1.0\" or \"2.0\"", version)))
+            }
+        }
+    }
+
+    pub fn type_(&self) -> &MessageType {
+        match self {
+            RevocationNotification::V1(revoke) => &revoke.type_,
+            RevocationNotification::V2(revoke) => &revoke.type_,
+        }
+    }
+
+    pub fn comment(&self) -> Option<&String> {
+        match self {
+            RevocationNotification::V1(revoke) => revoke.comment.as_ref(),
+            RevocationNotification::V2(revoke) => revoke.comment.as_ref(),
+        }
+    }
+
+    pub fn revoked_credential(&self) -> VcxResult<RevokedCredential> {
+        match self {
+            RevocationNotification::V1(revoke) => {
+                match revoke.thread_id.strip_prefix(INDY_THREAD_ID_PREFIX) {
+                    Some(credential_id) => _parse_credential_id(credential_id),
+                    None => Ok(RevokedCredential::Thread(revoke.thread_id.clone()))
+                }
+            }
+            RevocationNotification::V2(revoke) => {
+                if revoke.revocation_format != INDY_ANONCREDS_FORMAT {
+                    return Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported,
+                                                  format!("Unsupported revocation format: {:?}. Expected: {:?}", revoke.revocation_format, INDY_ANONCREDS_FORMAT)));
+                }
+                _parse_credential_id(&revoke.credential_id)
+            }
+        }
+    }
+}
+
+fn _parse_credential_id(credential_id: &str) -> VcxResult<RevokedCredential> {
+    match credential_id.split_once("::") {
+        Some((rev_reg_id, cred_rev_id)) if !rev_reg_id.is_empty() && !cred_rev_id.is_empty() => {
+            Ok(RevokedCredential::Revocation { rev_reg_id: rev_reg_id.to_string(), cred_rev_id: cred_rev_id.to_string() })
+        }
+        _ => {
+            Err(VcxError::from_msg(VcxErrorKind::InvalidJson,
+                                   format!("Invalid id of revoked credential: {:?}. Expected: `<rev_reg_id>::<cred_rev_id>`", credential_id)))
+        }
+    }
+}
+
+deserialize_v1_v2_message!(RevocationNotification, RevokeV1, RevokeV2);
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::aries::messages::a2a::A2AMessage;
+    use crate::aries::messages::revocation_notification::v10::tests::{_revoke as _revoke_v1, THREAD_ID};
+    use crate::aries::messages::revocation_notification::v20::tests::{_revoke as _revoke_v2, REV_REG_ID, CRED_REV_ID};
+
+    fn _revocation() -> RevokedCredential {
+        RevokedCredential::Revocation { rev_reg_id: REV_REG_ID.to_string(), cred_rev_id: CRED_REV_ID.to_string() }
+    }
+
+    #[test]
+    fn test_revocation_notification_create_works() {
+        let notification = RevocationNotification::create(None, THREAD_ID, REV_REG_ID, CRED_REV_ID, None).unwrap();
+        assert_eq!(RevokedCredential::Thread(THREAD_ID.to_string()), notification.revoked_credential().unwrap());
+
+        let notification = RevocationNotification::create(Some("2.0"), THREAD_ID, REV_REG_ID, CRED_REV_ID, Some(String::from("comment"))).unwrap();
+        assert_eq!(_revocation(), notification.revoked_credential().unwrap());
+        assert_eq!(Some(&String::from("comment")), notification.comment());
+
+        let err = RevocationNotification::create(Some("3.0"), THREAD_ID, REV_REG_ID, CRED_REV_ID, None).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidOption, err.kind());
+    }
+
+    #[test]
+    fn test_revocation_notification_revoked_credential() {
+        let revoke = _revoke_v1().set_thread_id(&format!("indy::{}::{}", REV_REG_ID, CRED_REV_ID));
+        assert_eq!(_revocation(), RevocationNotification::V1(revoke).revoked_credential().unwrap());
+
+        let revoke = RevokeV2 { credential_id: REV_REG_ID.to_string(), .._revoke_v2() };
+        assert_eq!(VcxErrorKind::InvalidJson, RevocationNotification::V2(revoke).revoked_credential().unwrap_err().kind());
+
+        let revoke = RevokeV2 { revocation_format: String::from("anoncreds"), .._revoke_v2() };
+        assert_eq!(VcxErrorKind::ActionNotSupported, RevocationNotification::V2(revoke).revoked_credential().unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_revocation_notification_deserialization() {
+        for notification in [RevocationNotification::V1(_revoke_v1()), RevocationNotification::V2(_revoke_v2())].iter() {
+            match ::serde_json::from_value::<A2AMessage>(json!(notification)).unwrap() {
+                A2AMessage::RevocationNotification(notification_) => assert_eq!(notification, &notification_),
+                message => panic!("Unexpected message: {:?}", message)
+            }
+        }
+    }
+}
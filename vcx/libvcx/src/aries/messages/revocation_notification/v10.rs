@@ -0,0 +1,80 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+/// `revoke` message of Revocation Notification 1.0 protocol (Aries RFC 0183).
+/// The revoked credential is identified by the thread of its issuance.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Revoke {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub thread_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Revoke {
+    pub fn create() -> Self {
+        Revoke::default()
+    }
+
+    pub fn set_thread_id(mut self, thread_id: &str) -> Self {
+        self.thread_id = thread_id.to_string();
+        self
+    }
+
+    pub fn set_comment(mut self, comment: Option<String>) -> Self {
+        self.comment = comment;
+        self
+    }
+}
+
+impl Default for Revoke {
+    fn default() -> Revoke {
+        Revoke {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::RevocationNotification,
+                version: MessageTypeVersion::V10,
+                type_: A2AMessage::REVOKE.to_string(),
+            },
+            thread_id: String::new(),
+            comment: None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub const THREAD_ID: &str = "ed5b7b75-8b4e-4d3c-a6f5-9bc0d7a3a8f1";
+
+    pub fn _revoke() -> Revoke {
+        Revoke {
+            id: MessageId::id(),
+            ..Revoke::default()
+        }
+            .set_thread_id(THREAD_ID)
+            .set_comment(Some(String::from("comment")))
+    }
+
+    #[test]
+    fn test_revoke_v1_build_works() {
+        let revoke = _revoke();
+
+        let revoke_json = json!(revoke);
+        assert_eq!("https://didcomm.org/revocation_notification/1.0/revoke", revoke_json["@type"]);
+        assert_eq!(THREAD_ID, revoke_json["thread_id"]);
+
+        let revoke_: Revoke = ::serde_json::from_value(revoke_json).unwrap();
+        assert_eq!(revoke, revoke_);
+    }
+}
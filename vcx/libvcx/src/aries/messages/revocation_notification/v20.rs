@@ -0,0 +1,88 @@
+use crate::aries::messages::a2a::{MessageId, A2AMessage};
+use crate::aries::messages::a2a::message_type::{
+    MessageType,
+    MessageTypePrefix,
+    MessageTypeVersion,
+};
+use crate::aries::messages::a2a::message_family::MessageTypeFamilies;
+
+pub const INDY_ANONCREDS_FORMAT: &str = "indy-anoncreds";
+
+/// `revoke` message of Revocation Notification 2.0 protocol (Aries RFC 0721).
+/// The revoked credential is identified by `credential_id` in `revocation_format`,
+/// which is `<rev_reg_id>::<cred_rev_id>` for `indy-anoncreds` format.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Revoke {
+    #[serde(rename = "@id")]
+    pub id: MessageId,
+    #[serde(rename = "@type")]
+    pub type_: MessageType,
+    pub revocation_format: String,
+    pub credential_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Revoke {
+    pub fn create() -> Self {
+        Revoke::default()
+    }
+
+    pub fn set_credential_id(mut self, rev_reg_id: &str, cred_rev_id: &str) -> Self {
+        self.revocation_format = INDY_ANONCREDS_FORMAT.to_string();
+        self.credential_id = format!("{}::{}", rev_reg_id, cred_rev_id);
+        self
+    }
+
+    pub fn set_comment(mut self, comment: Option<String>) -> Self {
+        self.comment = comment;
+        self
+    }
+}
+
+impl Default for Revoke {
+    fn default() -> Revoke {
+        Revoke {
+            id: MessageId::default(),
+            type_: MessageType {
+                prefix: MessageTypePrefix::Endpoint,
+                family: MessageTypeFamilies::RevocationNotification,
+                version: MessageTypeVersion::V20,
+                type_: A2AMessage::REVOKE.to_string(),
+            },
+            revocation_format: INDY_ANONCREDS_FORMAT.to_string(),
+            credential_id: String::new(),
+            comment: None,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub const REV_REG_ID: &str = "V4SGRU86Z58d6TV7PBUe6f:4:V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1:CL_ACCUM:tag1";
+    pub const CRED_REV_ID: &str = "7";
+
+    pub fn _revoke() -> Revoke {
+        Revoke {
+            id: MessageId::id(),
+            ..Revoke::default()
+        }
+            .set_credential_id(REV_REG_ID, CRED_REV_ID)
+            .set_comment(Some(String::from("comment")))
+    }
+
+    #[test]
+    fn test_revoke_v2_build_works() {
+        let revoke = _revoke();
+
+        let revoke_json = json!(revoke);
+        assert_eq!("https://didcomm.org/revocation_notification/2.0/revoke", revoke_json["@type"]);
+        assert_eq!("indy-anoncreds", revoke_json["revocation_format"]);
+        assert_eq!(format!("{}::{}", REV_REG_ID, CRED_REV_ID), revoke_json["credential_id"]);
+
+        let revoke_: Revoke = ::serde_json::from_value(revoke_json).unwrap();
+        assert_eq!(revoke, revoke_);
+    }
+}
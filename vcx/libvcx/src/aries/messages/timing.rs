@@ -0,0 +1,48 @@
+use chrono::{DateTime, Duration, Utc};
+
+/// `~timing` decorator (Aries RFC 0032 Message Timing).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Timing {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_time: Option<String>,
+}
+
+impl Timing {
+    /// Timing of the message expiring in `ttl` seconds.
+    pub fn expiring_in(ttl: u64) -> Timing {
+        Timing {
+            expires_time: Some((Utc::now() + Duration::seconds(ttl as i64)).to_rfc3339())
+        }
+    }
+
+    /// Whether `expires_time` has passed. Messages with `expires_time` in unknown format are considered as not expired.
+    pub fn is_expired(&self) -> bool {
+        self.expires_time.as_ref()
+            .and_then(|expires_time| parse_time(expires_time))
+            .map_or(false, |expires_time| expires_time < Utc::now())
+    }
+}
+
+// RFC 0032 examples use `+0000` offsets which are not valid RFC 3339
+fn parse_time(time: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(time)
+        .or_else(|_| DateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%z"))
+        .map(|time| time.with_timezone(&Utc))
+        .ok()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_is_expired() {
+        assert!(!Timing::default().is_expired());
+        assert!(!Timing::expiring_in(60).is_expired());
+
+        assert!(Timing { expires_time: Some(String::from("2018-12-13T17:29:06+0000")) }.is_expired());
+        assert!(Timing { expires_time: Some(String::from("2018-12-13T17:29:06+00:00")) }.is_expired());
+        assert!(!Timing { expires_time: Some(String::from("3018-12-13T17:29:06Z")) }.is_expired());
+        assert!(!Timing { expires_time: Some(String::from("tomorrow")) }.is_expired());
+    }
+}
@@ -1,16 +1,32 @@
+pub const TRANSPORT_DECORATOR: &str = "~transport";
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ReturnRoute {
+    None,
+    All,
+    Thread,
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub struct Transport {
-    return_route: String,
+    pub return_route: ReturnRoute,
 }
 
 impl Default for Transport {
     fn default() -> Transport {
         Transport {
-            return_route: String::from("thread")
+            return_route: ReturnRoute::Thread
         }
     }
 }
 
+impl Transport {
+    pub fn new(return_route: ReturnRoute) -> Transport {
+        Transport { return_route }
+    }
+}
+
 #[macro_export]
 macro_rules! return_route (($type:ident) => (
     impl $type {
@@ -20,4 +36,18 @@ macro_rules! return_route (($type:ident) => (
         }
 
     }
-));
\ No newline at end of file
+));
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_serialization() {
+        assert_eq!(json!({"return_route": "thread"}), json!(Transport::default()));
+        assert_eq!(json!({"return_route": "all"}), json!(Transport::new(ReturnRoute::All)));
+
+        let transport: Transport = ::serde_json::from_str(r#"{"return_route": "none"}"#).unwrap();
+        assert_eq!(ReturnRoute::None, transport.return_route);
+    }
+}
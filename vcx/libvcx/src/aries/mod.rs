@@ -2,6 +2,8 @@
 pub mod utils;
 pub mod handlers;
 pub mod messages;
+pub mod inbound;
+pub mod resolver;
 
 pub const SERIALIZE_VERSION: &'static str = "2.0";
 
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::resolver::DidResolver;
+
+pub static DEFAULT_CACHE_TTL_SECS: u64 = 300;
+
+/// Remembers DIDDocs resolved by the wrapped resolver for `did_resolver_cache_ttl` seconds.
+/// Caching is disabled if TTL is set to 0.
+pub struct CachingResolver<R: DidResolver> {
+    resolver: R,
+    cache: Mutex<HashMap<String, (Instant, DidDoc)>>,
+}
+
+impl<R: DidResolver> CachingResolver<R> {
+    pub fn new(resolver: R) -> CachingResolver<R> {
+        CachingResolver {
+            resolver,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn ttl() -> Duration {
+        let ttl = settings::get_opt_config_value(settings::CONFIG_DID_RESOLVER_CACHE_TTL)
+            .and_then(|ttl| ttl.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CACHE_TTL_SECS);
+
+        Duration::from_secs(ttl)
+    }
+
+    fn cached(&self, did: &str, ttl: Duration) -> Option<DidDoc> {
+        self.cache.lock().ok()?
+            .get(did)
+            .filter(|(resolved_at, _)| resolved_at.elapsed() < ttl)
+            .map(|(_, did_doc)| did_doc.clone())
+    }
+}
+
+impl<R: DidResolver> DidResolver for CachingResolver<R> {
+    fn methods(&self) -> Vec<&'static str> {
+        self.resolver.methods()
+    }
+
+    fn resolve(&self, did: &str) -> VcxResult<DidDoc> {
+        let ttl = CachingResolver::<R>::ttl();
+
+        if ttl.as_secs() == 0 {
+            return self.resolver.resolve(did);
+        }
+
+        if let Some(did_doc) = self.cached(did, ttl) {
+            trace!("CachingResolver::resolve <<< cached DIDDoc is used for DID: {:?}", did);
+            return Ok(did_doc);
+        }
+
+        let did_doc = self.resolver.resolve(did)?;
+
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.retain(|_, (resolved_at, _)| resolved_at.elapsed() < ttl);
+            cache.insert(did.to_string(), (Instant::now(), did_doc.clone()));
+        }
+
+        Ok(did_doc)
+    }
+
+    fn clear_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::utils::devsetup::SetupDefaults;
+    use crate::aries::messages::connection::did_doc::tests::_did_doc;
+
+    #[derive(Default)]
+    struct CountingResolver {
+        calls: AtomicUsize,
+    }
+
+    impl DidResolver for CountingResolver {
+        fn methods(&self) -> Vec<&'static str> { vec!["example"] }
+
+        fn resolve(&self, _did: &str) -> VcxResult<DidDoc> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(_did_doc())
+        }
+    }
+
+    #[test]
+    fn test_caching_resolver_works() {
+        let _setup = SetupDefaults::init();
+
+        let resolver = CachingResolver::new(CountingResolver::default());
+
+        assert_eq!(_did_doc(), resolver.resolve("did:example:1").unwrap());
+        assert_eq!(_did_doc(), resolver.resolve("did:example:1").unwrap());
+        assert_eq!(1, resolver.resolver.calls.load(Ordering::SeqCst));
+
+        resolver.resolve("did:example:2").unwrap();
+        assert_eq!(2, resolver.resolver.calls.load(Ordering::SeqCst));
+
+        resolver.clear_cache();
+        resolver.resolve("did:example:1").unwrap();
+        assert_eq!(3, resolver.resolver.calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_caching_resolver_works_for_disabled_cache() {
+        let _setup = SetupDefaults::init();
+        settings::set_config_value(settings::CONFIG_DID_RESOLVER_CACHE_TTL, "0");
+
+        let resolver = CachingResolver::new(CountingResolver::default());
+
+        resolver.resolve("did:example:1").unwrap();
+        resolver.resolve("did:example:1").unwrap();
+        assert_eq!(2, resolver.resolver.calls.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,23 @@
+use crate::error::prelude::*;
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::messages::didexchange::did_doc_attachment::resolve_did_key;
+use crate::aries::resolver::{DidResolver, KEY_METHOD};
+
+/// Resolves did:key DIDs into DIDDocs containing the single encoded Ed25519 key.
+/// Such DIDDocs have no service endpoint.
+pub struct KeyResolver {}
+
+impl DidResolver for KeyResolver {
+    fn methods(&self) -> Vec<&'static str> {
+        vec![KEY_METHOD]
+    }
+
+    fn resolve(&self, did: &str) -> VcxResult<DidDoc> {
+        let verkey = resolve_did_key(did)?;
+
+        let mut did_doc = DidDoc::create();
+        did_doc.set_id(did.to_string());
+        did_doc.set_keys(vec![verkey], vec![]);
+        Ok(did_doc)
+    }
+}
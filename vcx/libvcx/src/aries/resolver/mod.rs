@@ -0,0 +1,280 @@
+pub mod sov;
+pub mod key;
+pub mod peer;
+pub mod cache;
+
+use std::sync::RwLock;
+
+use crate::error::prelude::*;
+use crate::aries::messages::connection::did_doc::{DidDoc, Service};
+use crate::aries::resolver::sov::SovResolver;
+use crate::aries::resolver::key::KeyResolver;
+use crate::aries::resolver::peer::PeerResolver;
+use crate::aries::resolver::cache::CachingResolver;
+use crate::utils::validation::validate_did;
+
+/*
+    Resolution of DIDs used by Out-of-Band invitations (`public_did`, `services` given as DIDs) and connection handshakes:
+        did:sov, did:indy and unqualified DIDs - NYM (verkey) and `endpoint` ATTRIB written on the Ledger
+        did:key - the key encoded into the DID
+        did:peer - did:peer:2 and did:peer:4 DIDs
+
+    DIDDocs read from the Ledger are cached for `did_resolver_cache_ttl` seconds.
+*/
+
+pub const DID_PREFIX: &str = "did:";
+pub const SOV_METHOD: &str = "sov";
+pub const INDY_METHOD: &str = "indy";
+pub const KEY_METHOD: &str = "key";
+pub const PEER_METHOD: &str = "peer";
+
+/// Resolves DIDDocs for DIDs of particular methods.
+pub trait DidResolver: Send + Sync {
+    /// DID methods handled by the resolver.
+    fn methods(&self) -> Vec<&'static str>;
+
+    fn resolve(&self, did: &str) -> VcxResult<DidDoc>;
+
+    /// Drops DIDDocs remembered by the resolver (if any).
+    fn clear_cache(&self) {}
+}
+
+/// Resolvers of DID methods. Resolvers registered later take precedence over the ones registered before for the same DID methods.
+pub struct DidResolverRegistry {
+    resolvers: Vec<Box<dyn DidResolver>>,
+}
+
+impl Default for DidResolverRegistry {
+    fn default() -> DidResolverRegistry {
+        DidResolverRegistry {
+            resolvers: vec![
+                Box::new(CachingResolver::new(SovResolver {})),
+                Box::new(KeyResolver {}),
+                Box::new(PeerResolver {}),
+            ]
+        }
+    }
+}
+
+impl DidResolverRegistry {
+    pub fn register(&mut self, resolver: Box<dyn DidResolver>) {
+        self.resolvers.insert(0, resolver);
+    }
+
+    pub fn resolve(&self, did: &str) -> VcxResult<DidDoc> {
+        let method = did_method(did)?;
+
+        let resolver = self.resolvers.iter()
+            .find(|resolver| resolver.methods().contains(&method))
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDid, format!("Cannot resolve DID: {}. DID method `{}` is not supported", did, method)))?;
+
+        resolver.resolve(did)
+    }
+
+    pub fn clear_cache(&self) {
+        self.resolvers.iter().for_each(|resolver| resolver.clear_cache());
+    }
+}
+
+lazy_static! {
+    static ref RESOLVERS: RwLock<DidResolverRegistry> = RwLock::new(DidResolverRegistry::default());
+}
+
+/// Registers a resolver taking precedence over the ones registered before for the same DID methods.
+pub fn register_resolver(resolver: Box<dyn DidResolver>) -> VcxResult<()> {
+    RESOLVERS.write()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidState, "Cannot register DID resolver"))?
+        .register(resolver);
+    Ok(())
+}
+
+/// Method of the DID. Unqualified DIDs are considered as `sov` ones.
+pub fn did_method(did: &str) -> VcxResult<&str> {
+    if !did.starts_with(DID_PREFIX) {
+        validate_did(did)?;
+        return Ok(SOV_METHOD);
+    }
+
+    did.split(':').nth(1)
+        .filter(|method| !method.is_empty())
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDid, format!("Invalid DID: {}. DID method is not specified", did)))
+}
+
+/// Qualifies the DID as `did:sov` one if it is not qualified yet.
+pub fn qualify_did(did: &str) -> String {
+    match did.starts_with(DID_PREFIX) {
+        true => did.to_string(),
+        false => format!("{}{}:{}", DID_PREFIX, SOV_METHOD, did),
+    }
+}
+
+/// Drops the method (and namespace) prefix of `did:sov` and `did:indy` DIDs.
+pub fn unqualify_did(did: &str) -> &str {
+    match did.starts_with(DID_PREFIX) {
+        true => did.rsplit(':').next().unwrap_or(did),
+        false => did,
+    }
+}
+
+/// Resolves DIDDoc of the DID with the resolver registered for its method.
+pub fn resolve(did: &str) -> VcxResult<DidDoc> {
+    trace!("resolver::resolve >>> did: {:?}", did);
+    debug!("resolving DIDDoc for DID {}", did);
+
+    let did_doc = RESOLVERS.read()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidState, "Cannot access DID resolvers"))?
+        .resolve(did)?;
+
+    trace!("resolver::resolve <<< did_doc: {:?}", secret!(did_doc));
+    Ok(did_doc)
+}
+
+/// Resolves the DID into the DIDComm service which can be used as Out-of-Band invitation service.
+pub fn resolve_service(did: &str) -> VcxResult<Service> {
+    let did_doc = resolve(did)?;
+
+    if did_doc.get_endpoint().is_empty() {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("Cannot resolve service for DID: {}. DIDDoc does not contain service endpoint", did)));
+    }
+
+    Ok(Service::from_did_doc(&did_doc))
+}
+
+/// Replaces keys given as DIDs (e.g. `did:key` or DID URL referencing the key) with the keys of the resolved DIDDocs.
+pub fn resolve_keys(keys: &mut [String]) -> VcxResult<()> {
+    for key in keys.iter_mut() {
+        if !key.starts_with(DID_PREFIX) { continue; }
+
+        let did = key.split('#').next().unwrap_or_default().to_string();
+
+        *key = resolve(&did)?.recipient_keys().into_iter().next()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc,
+                                      format!("Cannot resolve key: {}. DIDDoc does not contain keys", key)))?;
+    }
+    Ok(())
+}
+
+/// Drops DIDDocs cached by the registered resolvers.
+pub fn clear_cache() {
+    if let Ok(resolvers) = RESOLVERS.read() {
+        resolvers.clear_cache();
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::settings;
+    use crate::utils::devsetup::SetupMocks;
+    use crate::aries::messages::connection::did_doc::tests::*;
+    use crate::aries::messages::connection::did_peer::{self, PeerDidNumalgo};
+    use crate::aries::messages::didexchange::did_doc_attachment::did_key;
+
+    struct ExampleResolver {}
+
+    impl DidResolver for ExampleResolver {
+        fn methods(&self) -> Vec<&'static str> { vec!["example"] }
+
+        fn resolve(&self, did: &str) -> VcxResult<DidDoc> {
+            let mut did_doc = _did_doc();
+            did_doc.set_id(did.to_string());
+            Ok(did_doc)
+        }
+    }
+
+    #[test]
+    fn test_did_method_works() {
+        assert_eq!(SOV_METHOD, did_method("V4SGRU86Z58d6TV7PBUe6f").unwrap());
+        assert_eq!(SOV_METHOD, did_method("did:sov:V4SGRU86Z58d6TV7PBUe6f").unwrap());
+        assert_eq!(INDY_METHOD, did_method("did:indy:sovrin:V4SGRU86Z58d6TV7PBUe6f").unwrap());
+        assert_eq!(PEER_METHOD, did_method("did:peer:2.Vz6Mk").unwrap());
+
+        did_method("did::V4SGRU86Z58d6TV7PBUe6f").unwrap_err();
+        did_method("invalid").unwrap_err();
+    }
+
+    #[test]
+    fn test_qualify_did_works() {
+        assert_eq!("did:sov:V4SGRU86Z58d6TV7PBUe6f", qualify_did("V4SGRU86Z58d6TV7PBUe6f"));
+        assert_eq!("did:indy:sovrin:V4SGRU86Z58d6TV7PBUe6f", qualify_did("did:indy:sovrin:V4SGRU86Z58d6TV7PBUe6f"));
+    }
+
+    #[test]
+    fn test_unqualify_did_works() {
+        assert_eq!("V4SGRU86Z58d6TV7PBUe6f", unqualify_did("V4SGRU86Z58d6TV7PBUe6f"));
+        assert_eq!("V4SGRU86Z58d6TV7PBUe6f", unqualify_did("did:sov:V4SGRU86Z58d6TV7PBUe6f"));
+        assert_eq!("V4SGRU86Z58d6TV7PBUe6f", unqualify_did("did:indy:sovrin:V4SGRU86Z58d6TV7PBUe6f"));
+    }
+
+    #[test]
+    fn test_resolve_works_for_sov_did() {
+        let _setup = SetupMocks::init();
+
+        let did_doc = resolve("did:sov:V4SGRU86Z58d6TV7PBUe6f").unwrap();
+        assert_eq!("did:sov:V4SGRU86Z58d6TV7PBUe6f", did_doc.id);
+        assert_eq!(vec![settings::DEFAULT_VERKEY.to_string()], did_doc.recipient_keys());
+        assert_eq!(settings::DEFAULT_URL, did_doc.get_endpoint());
+
+        let service = resolve_service("V4SGRU86Z58d6TV7PBUe6f").unwrap();
+        assert_eq!("V4SGRU86Z58d6TV7PBUe6f", service.id);
+        assert_eq!(vec![settings::DEFAULT_VERKEY.to_string()], service.recipient_keys);
+        assert_eq!(settings::DEFAULT_URL, service.service_endpoint);
+    }
+
+    #[test]
+    fn test_resolve_works_for_did_key() {
+        let did = did_key(&_key_1()).unwrap().split('#').next().unwrap().to_string();
+
+        let did_doc = resolve(&did).unwrap();
+        assert_eq!(did, did_doc.id);
+        assert_eq!(vec![_key_1()], did_doc.recipient_keys());
+
+        // did:key does not provide service endpoint
+        resolve_service(&did).unwrap_err();
+    }
+
+    #[test]
+    fn test_resolve_works_for_peer_did() {
+        let did_doc = did_peer::generate(&_did_doc(), PeerDidNumalgo::Numalgo2).unwrap();
+
+        assert_eq!(did_doc, resolve(&did_doc.id).unwrap());
+
+        let service = resolve_service(&did_doc.id).unwrap();
+        assert_eq!(did_doc.id, service.id);
+        assert_eq!(_recipient_keys(), service.recipient_keys);
+        assert_eq!(_routing_keys(), service.routing_keys);
+        assert_eq!(_service_endpoint(), service.service_endpoint);
+    }
+
+    #[test]
+    fn test_resolve_fails_for_unsupported_method() {
+        resolve("did:web:example.com").unwrap_err();
+    }
+
+    #[test]
+    fn test_register_resolver_works() {
+        let mut registry = DidResolverRegistry::default();
+        registry.resolve("did:example:123").unwrap_err();
+
+        registry.register(Box::new(ExampleResolver {}));
+
+        let did_doc = registry.resolve("did:example:123").unwrap();
+        assert_eq!("did:example:123", did_doc.id);
+        assert_eq!(_recipient_keys(), did_doc.recipient_keys());
+
+        // registered resolvers are not shared
+        resolve("did:example:123").unwrap_err();
+    }
+
+    #[test]
+    fn test_resolve_keys_works() {
+        let did_key = did_key(&_key_1()).unwrap();
+
+        let mut keys = vec![did_key.clone(), did_key.split('#').next().unwrap().to_string(), _key_2()];
+        resolve_keys(&mut keys).unwrap();
+        assert_eq!(vec![_key_1(), _key_1(), _key_2()], keys);
+
+        resolve_keys(&mut vec!["did:web:example.com".to_string()]).unwrap_err();
+    }
+}
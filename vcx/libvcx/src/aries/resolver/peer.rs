@@ -0,0 +1,17 @@
+use crate::error::prelude::*;
+use crate::aries::messages::connection::did_doc::DidDoc;
+use crate::aries::messages::connection::did_peer;
+use crate::aries::resolver::{DidResolver, PEER_METHOD};
+
+/// Resolves did:peer:2 and long form did:peer:4 DIDs. DIDDocs are decoded from the DIDs themselves.
+pub struct PeerResolver {}
+
+impl DidResolver for PeerResolver {
+    fn methods(&self) -> Vec<&'static str> {
+        vec![PEER_METHOD]
+    }
+
+    fn resolve(&self, did: &str) -> VcxResult<DidDoc> {
+        did_peer::resolve(did)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::prelude::*;
+use crate::aries::messages::connection::did_doc::{DidDoc, Service, OUTOFBAND_SERVICE_TYPE};
+use crate::aries::resolver::{DidResolver, DID_PREFIX, SOV_METHOD, INDY_METHOD};
+use crate::utils::libindy::ledger;
+
+/// Resolves public DIDs written on the Ledger: verkey is taken from NYM and service from `endpoint` ATTRIB.
+/// `did:sov:<id>` and unqualified `<id>` DIDs are looked up on the default Ledger,
+/// `did:indy:<namespace>:<id>` DIDs - on the Ledger registered for `<namespace>` (DIDs of not configured namespaces are rejected).
+pub struct SovResolver {}
+
+impl DidResolver for SovResolver {
+    fn methods(&self) -> Vec<&'static str> {
+        vec![SOV_METHOD, INDY_METHOD]
+    }
+
+    fn resolve(&self, did: &str) -> VcxResult<DidDoc> {
+        trace!("SovResolver::resolve >>> did: {:?}", did);
+
+        let (namespace, unqualified_did) = ledger_did(did)?;
+
+        let verkey = ledger::utils::get_verkey(namespace, unqualified_did)?;
+        let mut endpoint = ledger::utils::get_endpoint(namespace, unqualified_did)?;
+
+        Service::transform_did_keys_to_naked_keys(&mut endpoint.routing_keys)?;
+
+        let mut did_doc = DidDoc::create();
+        did_doc.set_id(did.to_string());
+        did_doc.service = vec![
+            Service::create()
+                .set_id(format!("{};{}", did, OUTOFBAND_SERVICE_TYPE))
+                .set_type(OUTOFBAND_SERVICE_TYPE.to_string())
+                .set_service_endpoint(endpoint.endpoint)
+        ];
+        did_doc.set_keys(vec![verkey], endpoint.routing_keys);
+
+        trace!("SovResolver::resolve <<< did_doc: {:?}", secret!(did_doc));
+        Ok(did_doc)
+    }
+}
+
+/// Splits the DID into the Ledger namespace (`None` for the default Ledger) and the DID written on the Ledger.
+fn ledger_did(did: &str) -> VcxResult<(Option<&str>, &str)> {
+    let indy_prefix = format!("{}{}:", DID_PREFIX, INDY_METHOD);
+
+    if !did.starts_with(&indy_prefix) {
+        return Ok((None, did.rsplit(':').next().unwrap_or(did)));
+    }
+
+    // namespace may contain sub-namespace: did:indy:sovrin:staging:<id>
+    match did[indy_prefix.len()..].rsplitn(2, ':').collect::<Vec<&str>>().as_slice() {
+        [id, namespace] if !id.is_empty() && !namespace.is_empty() => Ok((Some(*namespace), *id)),
+        _ => Err(VcxError::from_msg(VcxErrorKind::InvalidDid,
+                                    format!("Invalid DID: {}. `did:indy` DID must contain Ledger namespace", did)))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_did_works() {
+        assert_eq!((None, "V4SGRU86Z58d6TV7PBUe6f"), ledger_did("V4SGRU86Z58d6TV7PBUe6f").unwrap());
+        assert_eq!((None, "V4SGRU86Z58d6TV7PBUe6f"), ledger_did("did:sov:V4SGRU86Z58d6TV7PBUe6f").unwrap());
+        assert_eq!((Some("sovrin"), "V4SGRU86Z58d6TV7PBUe6f"), ledger_did("did:indy:sovrin:V4SGRU86Z58d6TV7PBUe6f").unwrap());
+        assert_eq!((Some("sovrin:staging"), "V4SGRU86Z58d6TV7PBUe6f"), ledger_did("did:indy:sovrin:staging:V4SGRU86Z58d6TV7PBUe6f").unwrap());
+
+        ledger_did("did:indy:V4SGRU86Z58d6TV7PBUe6f").unwrap_err();
+        ledger_did("did:indy:sovrin:").unwrap_err();
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::utils::libindy::wallet;
+
+/*
+    Storage of Connections in the wallet.
+
+    A record holds the serialized Connection (the same JSON as `vcx_connection_serialize` returns) and tags
+    describing the relationship. Tags set by the library (see `TAG_*` constants) are refreshed whenever the
+    stored Connection changes its state or rotates its pairwise DID, user-defined tags are kept as they are.
+
+    Record id is the identifier of the Connection (`connection_id` of its pairwise agent, generated UUID),
+    which the Connection keeps on DID rotation, so the record follows it without looking up by `my_did` tag.
+*/
+
+const CONNECTION_RECORD_TYPE: &str = "vcx_connection";
+
+pub const TAG_MY_DID: &str = "my_did";
+pub const TAG_THEIR_DID: &str = "their_did";
+pub const TAG_THEIR_PUBLIC_DID: &str = "their_public_did";
+pub const TAG_SOURCE_ID: &str = "source_id";
+pub const TAG_STATE: &str = "state";
+pub const TAG_INVITATION_ID: &str = "invitation_id";
+pub const TAG_LABEL: &str = "label";
+pub const TAG_GOAL_CODE: &str = "goal_code";
+
+const SYSTEM_TAGS: [&str; 8] = [TAG_MY_DID, TAG_THEIR_DID, TAG_THEIR_PUBLIC_DID, TAG_SOURCE_ID, TAG_STATE, TAG_INVITATION_ID, TAG_LABEL, TAG_GOAL_CODE];
+
+/// Stored Connection record returned by search.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredConnection {
+    pub id: String,
+    pub tags: HashMap<String, String>,
+}
+
+/// Parses user-defined tags given as JSON object of strings.
+/// Names of the tags set by the library are not allowed.
+pub fn parse_user_tags(tags: Option<&str>) -> VcxResult<Option<HashMap<String, String>>> {
+    let tags: HashMap<String, String> = match tags.map(str::trim) {
+        Some(tags) if !tags.is_empty() => {
+            ::serde_json::from_str(tags)
+                .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot parse Connection tags: {}", err)))?
+        }
+        _ => return Ok(None)
+    };
+
+    if let Some(name) = tags.keys().find(|name| _is_system_tag(name)) {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidOption,
+                                      format!("Connection tag `{}` is set by the library and cannot be used as user-defined tag", name)));
+    }
+
+    Ok(Some(tags))
+}
+
+/// Stores the Connection under `id` record id.
+/// Updates the record if the Connection is already stored. `user_tags` replace the stored user-defined tags if set.
+/// Returns id of the record.
+pub fn store(id: &str, connection: &str, tags: HashMap<String, String>, user_tags: Option<HashMap<String, String>>) -> VcxResult<String> {
+    trace!("connection_storage::store >>> id: {}, tags: {:?}, user_tags: {:?}", id, secret!(tags), secret!(user_tags));
+
+    if _exists(id)? {
+        _update(id, connection, tags, user_tags)?;
+        return Ok(id.to_string());
+    }
+
+    let mut tags = tags;
+    tags.extend(user_tags.unwrap_or_default());
+
+    wallet::add_record(CONNECTION_RECORD_TYPE, id, connection, Some(&json!(tags).to_string()))?;
+
+    trace!("connection_storage::store <<< id: {}", id);
+    Ok(id.to_string())
+}
+
+/// Updates the record of the stored Connection.
+/// Returns `false` if the Connection is not stored.
+pub fn refresh(id: &str, connection: &str, tags: HashMap<String, String>) -> VcxResult<bool> {
+    trace!("connection_storage::refresh >>> id: {}, tags: {:?}", id, secret!(tags));
+
+    if !_exists(id)? {
+        return Ok(false);
+    }
+
+    _update(id, connection, tags, None)?;
+    Ok(true)
+}
+
+/// Returns serialized Connection of the record.
+pub fn get(id: &str) -> VcxResult<String> {
+    trace!("connection_storage::get >>> id: {}", id);
+
+    let record = _get_record(id)?;
+
+    record["value"].as_str()
+        .map(String::from)
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Connection record does not contain value"))
+}
+
+/// Returns records matching WQL `query` over the record tags.
+pub fn search(query: Option<&str>) -> VcxResult<Vec<StoredConnection>> {
+    trace!("connection_storage::search >>> query: {:?}", secret!(query));
+
+    let query = match query.map(str::trim) {
+        Some(query) if !query.is_empty() => query,
+        _ => "{}"
+    };
+
+    let records = _search(query)?;
+
+    let connections = records.iter()
+        .map(|record| {
+            let id = record["id"].as_str()
+                .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Connection record does not contain id"))?;
+            Ok(StoredConnection { id: id.to_string(), tags: _parse_tags(record)? })
+        })
+        .collect::<VcxResult<Vec<StoredConnection>>>()?;
+
+    trace!("connection_storage::search <<< connections: {:?}", secret!(connections));
+    Ok(connections)
+}
+
+/// Deletes the record.
+pub fn delete(id: &str) -> VcxResult<()> {
+    trace!("connection_storage::delete >>> id: {}", id);
+
+    wallet::delete_record(CONNECTION_RECORD_TYPE, id)
+}
+
+/// Deletes the record if the Connection is stored.
+pub fn delete_if_stored(id: &str) -> VcxResult<()> {
+    match delete(id) {
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => Ok(()),
+        result => result
+    }
+}
+
+fn _is_system_tag(name: &str) -> bool {
+    SYSTEM_TAGS.contains(&name)
+}
+
+fn _exists(id: &str) -> VcxResult<bool> {
+    if settings::indy_mocks_enabled() { return Ok(false); }
+
+    match _get_record(id) {
+        Ok(_) => Ok(true),
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
+fn _update(id: &str, connection: &str, tags: HashMap<String, String>, user_tags: Option<HashMap<String, String>>) -> VcxResult<()> {
+    let user_tags = match user_tags {
+        Some(user_tags) => user_tags,
+        None => {
+            _parse_tags(&_get_record(id)?)?
+                .into_iter()
+                .filter(|(name, _)| !_is_system_tag(name))
+                .collect()
+        }
+    };
+
+    let mut tags = tags;
+    tags.extend(user_tags);
+
+    wallet::update_record_value(CONNECTION_RECORD_TYPE, id, connection)?;
+    wallet::update_record_tags(CONNECTION_RECORD_TYPE, id, &json!(tags).to_string())
+}
+
+fn _get_record(id: &str) -> VcxResult<Value> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": true}).to_string();
+
+    let record = wallet::get_record(CONNECTION_RECORD_TYPE, id, &options)?;
+
+    ::serde_json::from_str(&record)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize Connection record: {}", err)))
+}
+
+fn _parse_tags(record: &Value) -> VcxResult<HashMap<String, String>> {
+    match record["tags"] {
+        Value::Null => Ok(HashMap::new()),
+        ref tags => ::serde_json::from_value(tags.clone())
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize Connection record tags: {}", err)))
+    }
+}
+
+fn _search(query: &str) -> VcxResult<Vec<Value>> {
+    if settings::indy_mocks_enabled() { return Ok(Vec::new()); }
+
+    let options = json!({"retrieveRecords": true, "retrieveTotalCount": false, "retrieveType": false, "retrieveValue": false, "retrieveTags": true}).to_string();
+    wallet::search_records(CONNECTION_RECORD_TYPE, query, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    const CONNECTION_ID: &str = "0f6b7c9e-8a51-4a8b-9a2e-3b1f5d2c7e41";
+    const PW_DID: &str = "VsKV7grR1BUE29mG2Fm2kX";
+    const ROTATED_PW_DID: &str = "KC6NKcpXcpVnpjL8uKH3tV";
+
+    fn _tags(pw_did: &str, state: u32) -> HashMap<String, String> {
+        map!(
+            TAG_MY_DID.to_string() => pw_did.to_string(),
+            TAG_STATE.to_string() => state.to_string(),
+            TAG_THEIR_DID.to_string() => "2ZHFFhzA2XtTD6hJqzL7ux".to_string()
+        )
+    }
+
+    #[test]
+    fn test_parse_user_tags() {
+        assert_eq!(None, parse_user_tags(None).unwrap());
+        assert_eq!(None, parse_user_tags(Some("")).unwrap());
+        assert_eq!(Some(map!("group".to_string() => "partners".to_string())), parse_user_tags(Some(r#"{"group":"partners"}"#)).unwrap());
+
+        assert_eq!(VcxErrorKind::InvalidOption, parse_user_tags(Some(r#"{"state":"4"}"#)).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidJson, parse_user_tags(Some(r#"{"group":1}"#)).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_connection_storage_works() {
+        let _setup = SetupLibraryWallet::init();
+
+        let user_tags = map!("group".to_string() => "partners".to_string());
+
+        let id = store(CONNECTION_ID, "connection_v1", _tags(PW_DID, 2), Some(user_tags)).unwrap();
+        assert_eq!(CONNECTION_ID, id);
+        assert_eq!("connection_v1", get(&id).unwrap());
+
+        // user-defined tags are kept on update
+        assert_eq!(id, store(CONNECTION_ID, "connection_v2", _tags(PW_DID, 3), None).unwrap());
+
+        let found = search(Some(&json!({"group": "partners"}).to_string())).unwrap();
+        assert_eq!(1, found.len());
+        assert_eq!(id, found[0].id);
+        assert_eq!("3", found[0].tags[TAG_STATE]);
+
+        // record follows rotated pairwise DID
+        assert!(refresh(CONNECTION_ID, "connection_v3", _tags(ROTATED_PW_DID, 4)).unwrap());
+        assert!(!refresh("unknown", "connection_v3", _tags("unknown", 4)).unwrap());
+
+        let found = search(Some(&json!({TAG_MY_DID: ROTATED_PW_DID}).to_string())).unwrap();
+        assert_eq!(1, found.len());
+        assert_eq!(id, found[0].id);
+        assert_eq!("partners", found[0].tags["group"]);
+        assert_eq!("connection_v3", get(&id).unwrap());
+
+        assert!(search(Some(&json!({TAG_MY_DID: PW_DID}).to_string())).unwrap().is_empty());
+        assert_eq!(1, search(None).unwrap().len());
+
+        delete_if_stored(&id).unwrap();
+        assert!(search(None).unwrap().is_empty());
+        assert_eq!(VcxErrorKind::WalletRecordNotFound, get(&id).unwrap_err().kind());
+
+        delete_if_stored(&id).unwrap();
+        assert_eq!(VcxErrorKind::WalletRecordNotFound, delete(&id).unwrap_err().kind());
+    }
+}
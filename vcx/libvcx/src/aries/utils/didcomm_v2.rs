@@ -0,0 +1,733 @@
+use std::fmt::Debug;
+
+use openssl::aes::{AesKey, wrap_key, unwrap_key};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::derive::Deriver;
+use openssl::hash::MessageDigest;
+use openssl::memcmp;
+use openssl::pkey::{PKey, Private, Public};
+use openssl::rand::rand_bytes;
+use openssl::sha::sha256;
+use openssl::sign::Signer;
+use openssl::symm::{self, Cipher};
+use rust_base58::{FromBase58, ToBase58};
+
+use crate::error::prelude::*;
+use crate::utils::libindy::wallet::{self, WalletRecord};
+use crate::utils::uuid::uuid;
+
+/*
+    DIDComm v2 encrypted messages (https://identity.foundation/didcomm-messaging/spec/#didcomm-encrypted-messages)
+    JWE in General JSON serialization:
+        - anoncrypt: ECDH-ES+A256KW key agreement
+        - authcrypt: ECDH-1PU+A256KW key agreement
+        - content encryption: A256CBC-HS512
+        - key agreement keys: X25519
+
+    Key agreement private keys are stored as wallet records of `KEY_AGREEMENT_RECORD_TYPE` type.
+    The records are not accessible through the public wallet record API (`vcx_wallet_*_record*`, `vcx_wallet_open_search`),
+    but they are included into the wallet export (encrypted with the export key like the rest of the wallet).
+*/
+
+pub const ENCRYPTED_MESSAGE_TYPE: &str = "application/didcomm-encrypted+json";
+pub const PLAIN_MESSAGE_TYPE: &str = "application/didcomm-plain+json";
+pub const ARIES_MESSAGE_CONTENT_TYPE: &str = "application/json";
+pub const FORWARD_MESSAGE_TYPE: &str = "https://didcomm.org/routing/2.0/forward";
+
+const ALG_ANONCRYPT: &str = "ECDH-ES+A256KW";
+const ALG_AUTHCRYPT: &str = "ECDH-1PU+A256KW";
+const ENC: &str = "A256CBC-HS512";
+
+pub const KEY_AGREEMENT_RECORD_TYPE: &str = "didcomm_v2_key_agreement";
+
+const X25519_MULTICODEC_PREFIX: [u8; 2] = [0xec, 0x01];
+const ED25519_MULTICODEC_PREFIX: [u8; 2] = [0xed, 0x01];
+const X25519_PKCS8_PREFIX: [u8; 16] = [0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x04, 0x22, 0x04, 0x20];
+const X25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x6e, 0x03, 0x21, 0x00];
+const KEY_SIZE: usize = 32;
+const CEK_SIZE: usize = 64;
+const IV_SIZE: usize = 16;
+const TAG_SIZE: usize = 32;
+
+// 2^255 - 19
+const CURVE25519_PRIME: &str = "57896044618658097711785492504343953926634992332820282019728792003956564819949";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Jwe {
+    pub protected: String,
+    pub recipients: Vec<JweRecipient>,
+    pub iv: String,
+    pub ciphertext: String,
+    pub tag: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JweRecipient {
+    pub header: JweRecipientHeader,
+    pub encrypted_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JweRecipientHeader {
+    pub kid: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct ProtectedHeader {
+    typ: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cty: Option<String>,
+    alg: String,
+    enc: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skid: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    apu: Option<String>,
+    apv: String,
+    epk: EphemeralPublicKey,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct EphemeralPublicKey {
+    kty: String,
+    crv: String,
+    x: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct KeyAgreementRecord {
+    secret: String,
+    verkey: String,
+}
+
+/// Result of opening DIDComm v2 encrypted message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnpackedMessage {
+    pub message: Vec<u8>,
+    pub content_type: Option<String>,
+    /// Verkey of the pairwise DID the used key agreement key belongs to.
+    pub recipient_verkey: String,
+    /// Key id of the sender for authcrypted messages.
+    pub sender_kid: Option<String>,
+}
+
+/// Generates X25519 key agreement key for the pairwise `verkey` and stores it in the wallet.
+/// Returns the public part of the key in base58.
+pub fn create_key_agreement_key(verkey: &str) -> VcxResult<String> {
+    trace!("didcomm_v2::create_key_agreement_key >>> verkey: {:?}", secret!(verkey));
+
+    let mut secret = vec![0u8; KEY_SIZE];
+    rand_bytes(&mut secret).map_err(_crypto_error)?;
+
+    let public_key = _raw_public_key(&_private_key(&secret)?)?.to_base58();
+
+    let record = KeyAgreementRecord { secret: secret.to_base58(), verkey: verkey.to_string() };
+    wallet::add_record(KEY_AGREEMENT_RECORD_TYPE, &public_key, &json!(record).to_string(), None)?;
+
+    trace!("didcomm_v2::create_key_agreement_key <<< key: {:?}", public_key);
+    Ok(public_key)
+}
+
+/// Builds `did:key` key identifier for X25519 public key in base58.
+pub fn key_agreement_kid(key: &str) -> VcxResult<String> {
+    let key = _decode_key(key)?;
+
+    let mut multicodec = X25519_MULTICODEC_PREFIX.to_vec();
+    multicodec.extend(key);
+    let multibase = format!("z{}", multicodec.to_base58());
+
+    Ok(format!("did:key:{}#{}", multibase, multibase))
+}
+
+/// Resolves X25519 public key (base58) from `did:key` key identifier.
+/// Ed25519 keys are converted into their X25519 form.
+pub fn resolve_kid(kid: &str) -> VcxResult<String> {
+    let (did, fragment) = match kid.find('#') {
+        Some(pos) => (&kid[..pos], Some(&kid[pos + 1..])),
+        None => (kid, None)
+    };
+
+    let multibase = fragment
+        .filter(|fragment| fragment.starts_with('z'))
+        .or(did.split(':').nth(2))
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Unsupported key identifier: {:?}", kid)))?;
+
+    if !multibase.starts_with('z') {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Unsupported multibase encoding of key identifier: {:?}", kid)));
+    }
+
+    let decoded = multibase[1..].from_base58()
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot decode key identifier: {:?}. Err: {:?}", kid, err)))?;
+
+    if decoded.len() != KEY_SIZE + 2 {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Invalid key length in key identifier: {:?}", kid)));
+    }
+
+    match [decoded[0], decoded[1]] {
+        X25519_MULTICODEC_PREFIX => Ok(decoded[2..].to_base58()),
+        ED25519_MULTICODEC_PREFIX => Ok(_ed25519_to_x25519(&decoded[2..])?.to_base58()),
+        _ => Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Unsupported multicodec of key identifier: {:?}", kid)))
+    }
+}
+
+/// Converts Ed25519 verkey (base58) into X25519 public key (base58).
+pub fn convert_verkey(verkey: &str) -> VcxResult<String> {
+    let verkey = _decode_key(verkey)?;
+    Ok(_ed25519_to_x25519(&verkey)?.to_base58())
+}
+
+/// Resolves X25519 public key (base58) of a mediator routing key given either as `did:key` or as Ed25519 verkey (base58).
+pub fn resolve_routing_key(routing_key: &str) -> VcxResult<String> {
+    if routing_key.starts_with("did:key:") {
+        resolve_kid(routing_key)
+    } else {
+        convert_verkey(routing_key)
+    }
+}
+
+/// Checks whether `payload` is DIDComm v2 encrypted message.
+pub fn is_encrypted_message(payload: &[u8]) -> bool {
+    ::serde_json::from_slice::<Jwe>(payload)
+        .ok()
+        .and_then(|jwe| _decode_protected_header(&jwe.protected).ok())
+        .map(|header| header.typ == ENCRYPTED_MESSAGE_TYPE || header.alg.starts_with("ECDH-"))
+        .unwrap_or(false)
+}
+
+/// Encrypts `message` for X25519 `recipient_keys` (base58).
+/// The message is authcrypted if `sender_key` (X25519 key agreement key stored in the wallet) is passed
+/// and anoncrypted otherwise.
+pub fn pack_message(message: &[u8],
+                    content_type: Option<&str>,
+                    sender_key: Option<&str>,
+                    recipient_keys: &[String]) -> VcxResult<Vec<u8>> {
+    trace!("didcomm_v2::pack_message >>> content_type: {:?}, sender_key: {:?}, recipient_keys: {:?}", content_type, secret!(sender_key), secret!(recipient_keys));
+
+    if recipient_keys.is_empty() {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, "Cannot encrypt DIDComm v2 message: recipient keys are empty"));
+    }
+
+    let recipients = recipient_keys.iter()
+        .map(|key| Ok((key_agreement_kid(key)?, _public_key(&_decode_key(key)?)?)))
+        .collect::<VcxResult<Vec<(String, PKey<Public>)>>>()?;
+
+    let sender = match sender_key {
+        Some(key) => Some((key_agreement_kid(key)?, _private_key(&_get_key_agreement_record(key)?.0)?)),
+        None => None
+    };
+
+    let mut kids: Vec<&str> = recipients.iter().map(|(kid, _)| kid.as_str()).collect();
+    kids.sort();
+
+    let mut ephemeral_secret = vec![0u8; KEY_SIZE];
+    rand_bytes(&mut ephemeral_secret).map_err(_crypto_error)?;
+    let ephemeral_key = _private_key(&ephemeral_secret)?;
+
+    let header = ProtectedHeader {
+        typ: ENCRYPTED_MESSAGE_TYPE.to_string(),
+        cty: content_type.map(String::from),
+        alg: if sender.is_some() { ALG_AUTHCRYPT } else { ALG_ANONCRYPT }.to_string(),
+        enc: ENC.to_string(),
+        skid: sender.as_ref().map(|(kid, _)| kid.clone()),
+        apu: sender.as_ref().map(|(kid, _)| _encode(kid.as_bytes())),
+        apv: _encode(&sha256(kids.join(".").as_bytes())),
+        epk: EphemeralPublicKey {
+            kty: "OKP".to_string(),
+            crv: "X25519".to_string(),
+            x: _encode(&_raw_public_key(&ephemeral_key)?),
+        },
+    };
+    let protected = _encode(json!(header).to_string().as_bytes());
+
+    let mut cek = vec![0u8; CEK_SIZE];
+    rand_bytes(&mut cek).map_err(_crypto_error)?;
+    let mut iv = vec![0u8; IV_SIZE];
+    rand_bytes(&mut iv).map_err(_crypto_error)?;
+
+    let (ciphertext, tag) = _encrypt_content(&cek, &iv, protected.as_bytes(), message)?;
+
+    let recipients = recipients.iter()
+        .map(|(kid, recipient_key)| {
+            let mut z = _ecdh(&ephemeral_key, recipient_key)?;
+            if let Some((_, ref sender_key)) = sender {
+                z.extend(_ecdh(sender_key, recipient_key)?);
+            }
+
+            let kek = _concat_kdf(&z, &header, if sender.is_some() { Some(&tag) } else { None })?;
+
+            Ok(JweRecipient {
+                header: JweRecipientHeader { kid: kid.clone() },
+                encrypted_key: _encode(&_wrap_key(&kek, &cek)?),
+            })
+        })
+        .collect::<VcxResult<Vec<JweRecipient>>>()?;
+
+    let jwe = Jwe {
+        protected,
+        recipients,
+        iv: _encode(&iv),
+        ciphertext: _encode(&ciphertext),
+        tag: _encode(&tag),
+    };
+
+    Ok(json!(jwe).to_string().into_bytes())
+}
+
+/// Decrypts DIDComm v2 encrypted message using the first key agreement key found in the wallet for the recipients of the message.
+pub fn unpack_message(payload: &[u8]) -> VcxResult<UnpackedMessage> {
+    trace!("didcomm_v2::unpack_message >>>");
+
+    let jwe: Jwe = ::serde_json::from_slice(payload)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot deserialize DIDComm v2 encrypted message: {}", err)))?;
+
+    let header = _decode_protected_header(&jwe.protected)?;
+
+    if header.enc != ENC {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Unsupported content encryption algorithm: {:?}", header.enc)));
+    }
+
+    let authcrypted = match header.alg.as_str() {
+        ALG_ANONCRYPT => false,
+        ALG_AUTHCRYPT => true,
+        alg => return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Unsupported key agreement algorithm: {:?}", alg)))
+    };
+
+    let (recipient, recipient_secret, recipient_verkey) = jwe.recipients.iter()
+        .filter_map(|recipient| {
+            let key = resolve_kid(&recipient.header.kid).ok()?;
+            let (secret, verkey) = _get_key_agreement_record(&key).ok()?;
+            Some((recipient, secret, verkey))
+        })
+        .next()
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Cannot find key agreement key for any recipient of DIDComm v2 message"))?;
+
+    let recipient_key = _private_key(&recipient_secret)?;
+    let ephemeral_key = _public_key(&_decode(&header.epk.x)?)?;
+
+    let iv = _decode(&jwe.iv)?;
+    let ciphertext = _decode(&jwe.ciphertext)?;
+    let tag = _decode(&jwe.tag)?;
+
+    let mut z = _ecdh(&recipient_key, &ephemeral_key)?;
+    let sender_kid = if authcrypted {
+        let skid = header.skid.clone()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Authcrypted message does not contain `skid` header"))?;
+        // `apu` is bound into the key derivation, so it must identify the same sender as `skid`
+        let apu = match header.apu {
+            Some(ref apu) => _decode(apu)?,
+            None => return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Authcrypted message does not contain `apu` header"))
+        };
+        if apu != skid.as_bytes() {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Authcrypted message `apu` header does not match `skid` header"));
+        }
+        let sender_key = _public_key(&_decode_key(&resolve_kid(&skid)?)?)?;
+        z.extend(_ecdh(&recipient_key, &sender_key)?);
+        Some(skid)
+    } else {
+        None
+    };
+
+    let kek = _concat_kdf(&z, &header, if authcrypted { Some(&tag) } else { None })?;
+    let cek = _unwrap_key(&kek, &_decode(&recipient.encrypted_key)?)?;
+
+    let message = _decrypt_content(&cek, &iv, jwe.protected.as_bytes(), &ciphertext, &tag)?;
+
+    trace!("didcomm_v2::unpack_message <<< recipient_verkey: {:?}, sender_kid: {:?}", recipient_verkey, sender_kid);
+
+    Ok(UnpackedMessage {
+        message,
+        content_type: header.cty,
+        recipient_verkey,
+        sender_kid,
+    })
+}
+
+/// Wraps encrypted message into DIDComm v2 Forward message addressed to `next` and anoncrypts it for `routing_key` (X25519 base58).
+pub fn wrap_into_forward(message: &[u8], next: &str, routing_key: &str) -> VcxResult<Vec<u8>> {
+    trace!("didcomm_v2::wrap_into_forward >>> next: {:?}, routing_key: {:?}", secret!(next), secret!(routing_key));
+
+    let attachment: ::serde_json::Value = ::serde_json::from_slice(message)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Could not parse JSON from bytes. Err: {:?}", err)))?;
+
+    let forward = json!({
+        "id": uuid(),
+        "type": FORWARD_MESSAGE_TYPE,
+        "body": {
+            "next": next
+        },
+        "attachments": [{
+            "id": uuid(),
+            "data": {
+                "json": attachment
+            }
+        }]
+    });
+
+    pack_message(forward.to_string().as_bytes(), Some(PLAIN_MESSAGE_TYPE), None, &[routing_key.to_string()])
+}
+
+/// Parses DIDComm v2 Forward message. Returns the next recipient and the forwarded message.
+pub fn parse_forward(message: &[u8]) -> VcxResult<Option<(String, ::serde_json::Value)>> {
+    let message: ::serde_json::Value = ::serde_json::from_slice(message)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot deserialize DIDComm v2 message: {}", err)))?;
+
+    if message["type"].as_str() != Some(FORWARD_MESSAGE_TYPE) {
+        return Ok(None);
+    }
+
+    let next = message["body"]["next"].as_str()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Cannot find `next` field in Forward message"))?;
+
+    let forwarded = message["attachments"][0]["data"]["json"].clone();
+    if forwarded.is_null() {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Cannot find forwarded message in Forward message attachments"));
+    }
+
+    Ok(Some((next.to_string(), forwarded)))
+}
+
+fn _get_key_agreement_record(key: &str) -> VcxResult<(Vec<u8>, String)> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false});
+    let record = wallet::get_record(KEY_AGREEMENT_RECORD_TYPE, key, &options.to_string())?;
+
+    let record: KeyAgreementRecord = WalletRecord::from_str(&record)?.value
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Key agreement record does not contain value"))
+        .and_then(|value| ::serde_json::from_str(&value)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize key agreement record: {}", err))))?;
+
+    Ok((_decode_key(&record.secret)?, record.verkey))
+}
+
+fn _decode_protected_header(protected: &str) -> VcxResult<ProtectedHeader> {
+    ::serde_json::from_slice(&_decode(protected)?)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot deserialize JWE protected header: {}", err)))
+}
+
+// ConcatKDF (NIST SP 800-56A) as defined for ECDH-ES (RFC 7518 4.6.2) and ECDH-1PU (draft-madden-jose-ecdh-1pu-04 2.3).
+fn _concat_kdf(z: &[u8], header: &ProtectedHeader, tag: Option<&[u8]>) -> VcxResult<Vec<u8>> {
+    fn _with_length(data: &[u8]) -> Vec<u8> {
+        let mut result = (data.len() as u32).to_be_bytes().to_vec();
+        result.extend_from_slice(data);
+        result
+    }
+
+    let apu = match header.apu {
+        Some(ref apu) => _decode(apu)?,
+        None => Vec::new()
+    };
+    let apv = _decode(&header.apv)?;
+
+    let mut input = 1u32.to_be_bytes().to_vec();
+    input.extend_from_slice(z);
+    input.extend(_with_length(header.alg.as_bytes()));
+    input.extend(_with_length(&apu));
+    input.extend(_with_length(&apv));
+    input.extend(((KEY_SIZE * 8) as u32).to_be_bytes().iter());
+    if let Some(tag) = tag {
+        input.extend(_with_length(tag));
+    }
+
+    Ok(sha256(&input).to_vec())
+}
+
+// A256CBC-HS512 (RFC 7518 5.2.5)
+fn _encrypt_content(cek: &[u8], iv: &[u8], aad: &[u8], plaintext: &[u8]) -> VcxResult<(Vec<u8>, Vec<u8>)> {
+    let (mac_key, enc_key) = cek.split_at(CEK_SIZE / 2);
+
+    let ciphertext = symm::encrypt(Cipher::aes_256_cbc(), enc_key, Some(iv), plaintext)
+        .map_err(_crypto_error)?;
+    let tag = _content_tag(mac_key, iv, aad, &ciphertext)?;
+
+    Ok((ciphertext, tag))
+}
+
+fn _decrypt_content(cek: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> VcxResult<Vec<u8>> {
+    if cek.len() != CEK_SIZE || iv.len() != IV_SIZE || tag.len() != TAG_SIZE {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Invalid size of DIDComm v2 message components"));
+    }
+
+    let (mac_key, enc_key) = cek.split_at(CEK_SIZE / 2);
+
+    if !memcmp::eq(&_content_tag(mac_key, iv, aad, ciphertext)?, tag) {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "DIDComm v2 message authentication failed"));
+    }
+
+    symm::decrypt(Cipher::aes_256_cbc(), enc_key, Some(iv), ciphertext)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot decrypt DIDComm v2 message. Err: {}", err)))
+}
+
+fn _content_tag(mac_key: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8]) -> VcxResult<Vec<u8>> {
+    let key = PKey::hmac(mac_key).map_err(_crypto_error)?;
+    let mut signer = Signer::new(MessageDigest::sha512(), &key).map_err(_crypto_error)?;
+    signer.update(aad).map_err(_crypto_error)?;
+    signer.update(iv).map_err(_crypto_error)?;
+    signer.update(ciphertext).map_err(_crypto_error)?;
+    signer.update(&((aad.len() as u64) * 8).to_be_bytes()).map_err(_crypto_error)?;
+
+    let mut tag = signer.sign_to_vec().map_err(_crypto_error)?;
+    tag.truncate(TAG_SIZE);
+    Ok(tag)
+}
+
+// A256KW (RFC 3394)
+fn _wrap_key(kek: &[u8], cek: &[u8]) -> VcxResult<Vec<u8>> {
+    let kek = AesKey::new_encrypt(kek).map_err(_crypto_error)?;
+    let mut wrapped = vec![0u8; cek.len() + 8];
+    wrap_key(&kek, None, &mut wrapped, cek).map_err(_crypto_error)?;
+    Ok(wrapped)
+}
+
+fn _unwrap_key(kek: &[u8], wrapped: &[u8]) -> VcxResult<Vec<u8>> {
+    if wrapped.len() != CEK_SIZE + 8 {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Invalid size of DIDComm v2 encrypted key"));
+    }
+
+    let kek = AesKey::new_decrypt(kek).map_err(_crypto_error)?;
+    let mut cek = vec![0u8; CEK_SIZE];
+    unwrap_key(&kek, None, &mut cek, wrapped)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot unwrap DIDComm v2 content encryption key. Err: {:?}", err)))?;
+    Ok(cek)
+}
+
+fn _ecdh(private_key: &PKey<Private>, public_key: &PKey<Public>) -> VcxResult<Vec<u8>> {
+    let mut deriver = Deriver::new(private_key).map_err(_crypto_error)?;
+    deriver.set_peer(public_key).map_err(_crypto_error)?;
+    deriver.derive_to_vec().map_err(_crypto_error)
+}
+
+fn _private_key(secret: &[u8]) -> VcxResult<PKey<Private>> {
+    if secret.len() != KEY_SIZE {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Invalid size of X25519 private key"));
+    }
+
+    let mut der = X25519_PKCS8_PREFIX.to_vec();
+    der.extend_from_slice(secret);
+    PKey::private_key_from_pkcs8(&der).map_err(_crypto_error)
+}
+
+fn _public_key(key: &[u8]) -> VcxResult<PKey<Public>> {
+    if key.len() != KEY_SIZE {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Invalid size of X25519 public key"));
+    }
+
+    let mut der = X25519_SPKI_PREFIX.to_vec();
+    der.extend_from_slice(key);
+    PKey::public_key_from_der(&der).map_err(_crypto_error)
+}
+
+fn _raw_public_key(key: &PKey<Private>) -> VcxResult<Vec<u8>> {
+    let der = key.public_key_to_der().map_err(_crypto_error)?;
+    Ok(der[der.len() - KEY_SIZE..].to_vec())
+}
+
+// Birational map from Edwards curve to Montgomery curve: u = (1 + y) / (1 - y) mod p (RFC 7748 4.1)
+fn _ed25519_to_x25519(verkey: &[u8]) -> VcxResult<Vec<u8>> {
+    if verkey.len() != KEY_SIZE {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidVerkey, "Invalid size of Ed25519 public key"));
+    }
+
+    let mut y = verkey.to_vec();
+    y[KEY_SIZE - 1] &= 0x7f;
+    y.reverse();
+
+    let mut ctx = BigNumContext::new().map_err(_crypto_error)?;
+    let p = BigNum::from_dec_str(CURVE25519_PRIME).map_err(_crypto_error)?;
+    let one = BigNum::from_u32(1).map_err(_crypto_error)?;
+    let y = BigNum::from_slice(&y).map_err(_crypto_error)?;
+
+    let mut numerator = BigNum::new().map_err(_crypto_error)?;
+    numerator.mod_add(&one, &y, &p, &mut ctx).map_err(_crypto_error)?;
+    let mut denominator = BigNum::new().map_err(_crypto_error)?;
+    denominator.mod_sub(&one, &y, &p, &mut ctx).map_err(_crypto_error)?;
+    let mut inverse = BigNum::new().map_err(_crypto_error)?;
+    inverse.mod_inverse(&denominator, &p, &mut ctx)
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidVerkey, "Ed25519 public key cannot be converted into X25519 public key"))?;
+    let mut u = BigNum::new().map_err(_crypto_error)?;
+    u.mod_mul(&numerator, &inverse, &p, &mut ctx).map_err(_crypto_error)?;
+
+    let mut u = u.to_vec();
+    u.reverse();
+    u.resize(KEY_SIZE, 0);
+    Ok(u)
+}
+
+fn _decode_key(key: &str) -> VcxResult<Vec<u8>> {
+    let key = key.from_base58()
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidVerkey, format!("Cannot decode key from base58. Err: {:?}", err)))?;
+
+    if key.len() != KEY_SIZE {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidVerkey, format!("Invalid key length: {}", key.len())));
+    }
+
+    Ok(key)
+}
+
+fn _encode(data: &[u8]) -> String {
+    base64::encode_config(data, base64::URL_SAFE_NO_PAD)
+}
+
+fn _decode(data: &str) -> VcxResult<Vec<u8>> {
+    base64::decode_config(data.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot decode base64url value. Err: {:?}", err)))
+}
+
+fn _crypto_error<E: Debug>(err: E) -> VcxError {
+    VcxError::from_msg(VcxErrorKind::EncodeError, format!("DIDComm v2 cryptographic operation failed. Err: {:?}", err))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::utils::libindy::tests::test_setup;
+    use crate::utils::libindy::crypto::create_key;
+
+    fn _setup() {
+        crate::settings::set_config_value(crate::settings::CONFIG_ENABLE_TEST_MODE, "false");
+    }
+
+    const MESSAGE: &str = r#"{"@type":"https://didcomm.org/notification/1.0/ack","@id":"1"}"#;
+
+    #[test]
+    fn test_ed25519_to_x25519_conversion() {
+        // seed and public key from RFC 8032 7.1 TEST 1
+        let seed = hex::decode("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60").unwrap();
+        let verkey = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+
+        // X25519 secret is the clamped first half of SHA-512(seed)
+        let secret = openssl::sha::sha512(&seed)[..KEY_SIZE].to_vec();
+        let expected = _raw_public_key(&_private_key(&secret).unwrap()).unwrap();
+
+        assert_eq!(expected, _ed25519_to_x25519(&verkey).unwrap());
+        assert_eq!(expected.to_base58(), convert_verkey(&verkey.to_base58()).unwrap());
+    }
+
+    #[test]
+    fn test_key_agreement_kid() {
+        let key = [7u8; KEY_SIZE].to_base58();
+
+        let kid = key_agreement_kid(&key).unwrap();
+        assert!(kid.starts_with("did:key:z6LS"));
+        assert_eq!(key, resolve_kid(&kid).unwrap());
+        assert_eq!(key, resolve_kid(kid.split('#').next().unwrap()).unwrap());
+
+        let verkey = hex::decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a").unwrap();
+        let mut multicodec = ED25519_MULTICODEC_PREFIX.to_vec();
+        multicodec.extend_from_slice(&verkey);
+        let kid = format!("did:key:z{}", multicodec.to_base58());
+        assert!(kid.starts_with("did:key:z6Mk"));
+        assert_eq!(convert_verkey(&verkey.to_base58()).unwrap(), resolve_kid(&kid).unwrap());
+
+        assert_eq!(VcxErrorKind::InvalidAgencyResponse, resolve_kid("did:sov:123#1").unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_content_encryption_detects_tampering() {
+        let cek = [1u8; CEK_SIZE];
+        let iv = [2u8; IV_SIZE];
+
+        let (ciphertext, tag) = _encrypt_content(&cek, &iv, b"aad", MESSAGE.as_bytes()).unwrap();
+        assert_eq!(MESSAGE.as_bytes().to_vec(), _decrypt_content(&cek, &iv, b"aad", &ciphertext, &tag).unwrap());
+        assert_eq!(VcxErrorKind::InvalidAgencyResponse, _decrypt_content(&cek, &iv, b"other", &ciphertext, &tag).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_pack_unpack_message() {
+        _setup();
+        let _setup = test_setup::key();
+
+        let sender_verkey = create_key(None).unwrap();
+        let recipient_verkey = create_key(None).unwrap();
+        let sender_key = create_key_agreement_key(&sender_verkey).unwrap();
+        let recipient_key = create_key_agreement_key(&recipient_verkey).unwrap();
+
+        // authcrypt
+        let packed = pack_message(MESSAGE.as_bytes(), Some(ARIES_MESSAGE_CONTENT_TYPE), Some(&sender_key), &[recipient_key.clone()]).unwrap();
+        assert!(is_encrypted_message(&packed));
+
+        let unpacked = unpack_message(&packed).unwrap();
+        assert_eq!(MESSAGE.as_bytes().to_vec(), unpacked.message);
+        assert_eq!(Some(ARIES_MESSAGE_CONTENT_TYPE.to_string()), unpacked.content_type);
+        assert_eq!(recipient_verkey, unpacked.recipient_verkey);
+        assert_eq!(Some(key_agreement_kid(&sender_key).unwrap()), unpacked.sender_kid);
+
+        // anoncrypt
+        let packed = pack_message(MESSAGE.as_bytes(), None, None, &[recipient_key]).unwrap();
+        let unpacked = unpack_message(&packed).unwrap();
+        assert_eq!(MESSAGE.as_bytes().to_vec(), unpacked.message);
+        assert_eq!(None, unpacked.sender_kid);
+
+        assert!(!is_encrypted_message(MESSAGE.as_bytes()));
+    }
+
+    #[test]
+    fn test_unpack_message_fails_for_apu_not_matching_skid() {
+        _setup();
+        let _setup = test_setup::key();
+
+        let sender_key = create_key_agreement_key(&create_key(None).unwrap()).unwrap();
+        let recipient_key = create_key_agreement_key(&create_key(None).unwrap()).unwrap();
+        let other_key = create_key_agreement_key(&create_key(None).unwrap()).unwrap();
+
+        let packed = pack_message(MESSAGE.as_bytes(), None, Some(&sender_key), &[recipient_key]).unwrap();
+
+        let mut jwe: Jwe = ::serde_json::from_slice(&packed).unwrap();
+        let mut header = _decode_protected_header(&jwe.protected).unwrap();
+        header.apu = Some(_encode(key_agreement_kid(&other_key).unwrap().as_bytes()));
+        jwe.protected = _encode(json!(header).to_string().as_bytes());
+
+        let err = unpack_message(json!(jwe).to_string().as_bytes()).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidAgencyResponse, err.kind());
+        assert!(err.to_string().contains("`apu`"));
+    }
+
+    #[test]
+    fn test_resolve_routing_key() {
+        _setup();
+        let _setup = test_setup::key();
+
+        let verkey = create_key(None).unwrap();
+        let key_agreement_key = create_key_agreement_key(&verkey).unwrap();
+
+        assert_eq!(key_agreement_key, resolve_routing_key(&key_agreement_kid(&key_agreement_key).unwrap()).unwrap());
+        assert_eq!(convert_verkey(&verkey).unwrap(), resolve_routing_key(&verkey).unwrap());
+
+        let mut multicodec = ED25519_MULTICODEC_PREFIX.to_vec();
+        multicodec.extend(verkey.from_base58().unwrap());
+        let did_key = format!("did:key:z{}", multicodec.to_base58());
+        assert_eq!(convert_verkey(&verkey).unwrap(), resolve_routing_key(&did_key).unwrap());
+    }
+
+    #[test]
+    fn test_wrap_into_forward() {
+        _setup();
+        let _setup = test_setup::key();
+
+        let recipient_key = create_key_agreement_key(&create_key(None).unwrap()).unwrap();
+        let routing_key = create_key_agreement_key(&create_key(None).unwrap()).unwrap();
+
+        let packed = pack_message(MESSAGE.as_bytes(), None, None, &[recipient_key.clone()]).unwrap();
+        let forward = wrap_into_forward(&packed, &key_agreement_kid(&recipient_key).unwrap(), &routing_key).unwrap();
+
+        let unpacked = unpack_message(&forward).unwrap();
+        assert_eq!(Some(PLAIN_MESSAGE_TYPE.to_string()), unpacked.content_type);
+
+        let (next, message) = parse_forward(&unpacked.message).unwrap().unwrap();
+        assert_eq!(key_agreement_kid(&recipient_key).unwrap(), next);
+
+        let unpacked = unpack_message(message.to_string().as_bytes()).unwrap();
+        assert_eq!(MESSAGE.as_bytes().to_vec(), unpacked.message);
+        assert_eq!(None, parse_forward(&unpacked.message).unwrap());
+    }
+
+    #[test]
+    fn test_unpack_message_fails_for_unknown_recipient() {
+        _setup();
+        let _setup = test_setup::key();
+
+        let packed = pack_message(MESSAGE.as_bytes(), None, None, &[[9u8; KEY_SIZE].to_base58()]).unwrap();
+        assert_eq!(VcxErrorKind::WalletRecordNotFound, unpack_message(&packed).unwrap_err().kind());
+    }
+}
@@ -5,11 +5,65 @@ use crate::error::prelude::*;
 use crate::aries::messages::connection::did_doc::DidDoc;
 use crate::aries::messages::forward::Forward;
 use crate::aries::messages::a2a::A2AMessage;
+use crate::aries::utils::didcomm_v2;
+use crate::utils::httpclient;
+use crate::settings;
+use crate::settings::delivery::DeliveryPolicy;
 use serde::Serialize;
 
 #[derive(Debug)]
 pub struct EncryptionEnvelope(pub Vec<u8>);
 
+/// Keys of an opened envelope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvelopeKeys {
+    /// Verkey the envelope was encrypted for.
+    pub recipient_verkey: String,
+    /// Key of the sender of authcrypted envelope:
+    /// verkey for DIDComm v1 envelopes and `did:key` id of the key agreement key for DIDComm v2 envelopes.
+    pub sender_key: Option<String>,
+}
+
+/// Format of envelopes used for messages sent over a connection.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum EnvelopeVersion {
+    /// DIDComm v1 `pack_message` JWE wrapped into Aries `Forward` messages.
+    #[serde(rename = "v1")]
+    V1,
+    /// DIDComm v2 JWE (ECDH-1PU/ECDH-ES with X25519 key agreement keys) wrapped into DIDComm v2 Forward messages.
+    #[serde(rename = "v2")]
+    V2,
+}
+
+impl Default for EnvelopeVersion {
+    fn default() -> EnvelopeVersion {
+        EnvelopeVersion::V1
+    }
+}
+
+impl EnvelopeVersion {
+    pub fn from_str(version: &str) -> VcxResult<EnvelopeVersion> {
+        ::serde_json::from_value(json!(version))
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidConfiguration, format!("Unsupported envelope version: {:?}. Expected \"v1\" or \"v2\"", version)))
+    }
+
+    /// Content type of HTTP requests delivering envelopes of this version.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            EnvelopeVersion::V1 => httpclient::DIDCOMM_ENVELOPE_CONTENT_TYPE,
+            EnvelopeVersion::V2 => didcomm_v2::ENCRYPTED_MESSAGE_TYPE,
+        }
+    }
+
+    /// Envelope version set in the library config through `didcomm_envelope_version` option.
+    pub fn from_config() -> VcxResult<EnvelopeVersion> {
+        match settings::get_opt_config_value(settings::CONFIG_DIDCOMM_ENVELOPE_VERSION) {
+            Some(version) => EnvelopeVersion::from_str(&version),
+            None => Ok(EnvelopeVersion::default())
+        }
+    }
+}
+
 impl EncryptionEnvelope {
     pub fn create<T: Serialize + Debug>(message: &T,
                   pw_verkey: Option<&str>,
@@ -24,6 +78,42 @@ impl EncryptionEnvelope {
             .map(|message| EncryptionEnvelope(message))
     }
 
+    /// Creates DIDComm v2 envelope for the keyAgreement keys of `did_doc`.
+    /// The message is authcrypted if `key_agreement_key` (X25519 key stored in the wallet) is passed
+    /// and anoncrypted otherwise.
+    pub fn create_v2<T: Serialize + Debug>(message: &T,
+                     key_agreement_key: Option<&str>,
+                     did_doc: &DidDoc) -> VcxResult<EncryptionEnvelope> {
+        trace!("EncryptionEnvelope::create_v2 >>> message: {:?}, key_agreement_key: {:?}, did_doc: {:?}", secret!(message), secret!(key_agreement_key), secret!(did_doc));
+        debug!("EncryptionEnvelope: Creating DIDComm v2 encryption envelop");
+
+        if crate::settings::indy_mocks_enabled() { return Ok(EncryptionEnvelope(vec![])); }
+
+        let recipient_keys = did_doc.key_agreement_keys();
+        if recipient_keys.is_empty() {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDIDDoc, format!("Key Agreement Key not found in DIDDoc: {:?}", did_doc)));
+        }
+
+        let message = json!(message).to_string();
+        let message = didcomm_v2::pack_message(message.as_bytes(),
+                                               Some(didcomm_v2::ARIES_MESSAGE_CONTENT_TYPE),
+                                               key_agreement_key,
+                                               &recipient_keys)?;
+
+        EncryptionEnvelope::wrap_into_forward_messages_v2(message, &recipient_keys[0], did_doc)
+            .map(|message| EncryptionEnvelope(message))
+    }
+
+    /// Delivers the envelope of the given `version` to the DIDDoc endpoint through the transport registered for it.
+    /// HTTP delivery follows `policy` if it is passed or the one set in the library config.
+    /// Returns the synchronous reply if the transport received one.
+    pub fn send(&self, did_doc: &DidDoc, version: EnvelopeVersion, policy: Option<&DeliveryPolicy>) -> VcxResult<Option<Vec<u8>>> {
+        trace!("EncryptionEnvelope::send >>> did_doc: {:?}, version: {:?}, policy: {:?}", secret!(did_doc), version, policy);
+        debug!("EncryptionEnvelope: Sending envelope to the remote endpoint");
+
+        httpclient::send_message_with_policy(&self.0, &did_doc.get_endpoint(), version.content_type(), policy)
+    }
+
     fn encrypt_for_pairwise<T: Serialize + Debug>(message: &T,
                             pw_verkey: Option<&str>,
                             did_doc: &DidDoc) -> VcxResult<Vec<u8>> {
@@ -69,15 +159,75 @@ impl EncryptionEnvelope {
         crypto::pack_message(None, &receiver_keys, message.as_bytes())
     }
 
+    fn wrap_into_forward_messages_v2(mut message: Vec<u8>,
+                                     recipient_key: &str,
+                                     did_doc: &DidDoc) -> VcxResult<Vec<u8>> {
+        trace!("EncryptionEnvelope::wrap_into_forward_messages_v2 >>> did_doc: {:?}", secret!(did_doc));
+        debug!("EncryptionEnvelope: DIDComm v2 encryption for routing");
+
+        let mut next = didcomm_v2::key_agreement_kid(recipient_key)?;
+
+        // `did:key` routing keys are resolved as they are, key references are resolved through the DIDDoc
+        let routing_keys = did_doc.service.get(0)
+            .map(|service| service.routing_keys.clone())
+            .unwrap_or_default();
+
+        for routing_key in routing_keys.iter() {
+            let routing_key = if routing_key.starts_with("did:key:") {
+                routing_key.clone()
+            } else {
+                did_doc.key_for_reference(routing_key)
+            };
+            let routing_key = didcomm_v2::resolve_routing_key(&routing_key)?;
+            message = didcomm_v2::wrap_into_forward(&message, &next, &routing_key)?;
+            next = didcomm_v2::key_agreement_kid(&routing_key)?;
+        }
+
+        Ok(message)
+    }
+
     pub fn open(payload: Vec<u8>) -> VcxResult<A2AMessage> {
         trace!("EncryptionEnvelope::open >>>");
         debug!("EncryptionEnvelope: Opening");
 
+        let (message, _) = EncryptionEnvelope::open_with_recipient_key(payload)?;
+
+        trace!("EncryptionEnvelope::open <<< message: {:?}", secret!(message));
+
+        Ok(message)
+    }
+
+    /// Opens the envelope and returns the message together with the verkey the envelope was encrypted for.
+    /// Both DIDComm v1 and DIDComm v2 envelopes are accepted.
+    pub fn open_with_recipient_key(payload: Vec<u8>) -> VcxResult<(A2AMessage, String)> {
+        trace!("EncryptionEnvelope::open_with_recipient_key >>>");
+
+        let (message, keys) = EncryptionEnvelope::open_with_keys(payload)?;
+
+        trace!("EncryptionEnvelope::open_with_recipient_key <<< message: {:?}, recipient_verkey: {:?}", secret!(message), keys.recipient_verkey);
+
+        Ok((message, keys.recipient_verkey))
+    }
+
+    /// Opens the envelope and returns the message together with the keys of its recipient and sender.
+    /// Both DIDComm v1 and DIDComm v2 envelopes are accepted.
+    pub fn open_with_keys(payload: Vec<u8>) -> VcxResult<(A2AMessage, EnvelopeKeys)> {
+        trace!("EncryptionEnvelope::open_with_keys >>>");
+
+        if didcomm_v2::is_encrypted_message(&payload) {
+            return EncryptionEnvelope::open_v2(&payload);
+        }
+
         let unpacked_msg = crypto::unpack_message(&payload)?;
 
         let message: ::serde_json::Value = ::serde_json::from_slice(unpacked_msg.as_slice())
             .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot deserialize message: {}", err)))?;
 
+        let recipient_verkey = message["recipient_verkey"].as_str()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Cannot find `recipient_verkey` field"))?.to_string();
+
+        let sender_key = message["sender_verkey"].as_str().map(String::from);
+
         let message = message["message"].as_str()
             .ok_or(VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, "Cannot find `message` field"))?.to_string();
 
@@ -86,9 +236,35 @@ impl EncryptionEnvelope {
                 VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot deserialize A2A message: {}", err))
             })?;
 
-        trace!("EncryptionEnvelope::open <<< message: {:?}", secret!(message));
+        let keys = EnvelopeKeys { recipient_verkey, sender_key };
 
-        Ok(message)
+        trace!("EncryptionEnvelope::open_with_keys <<< message: {:?}, keys: {:?}", secret!(message), keys);
+
+        Ok((message, keys))
+    }
+
+    fn open_v2(payload: &[u8]) -> VcxResult<(A2AMessage, EnvelopeKeys)> {
+        trace!("EncryptionEnvelope::open_v2 >>>");
+        debug!("EncryptionEnvelope: Opening DIDComm v2 envelope");
+
+        let unpacked = didcomm_v2::unpack_message(payload)?;
+
+        let forward = match unpacked.content_type.as_ref().map(String::as_str) {
+            Some(didcomm_v2::PLAIN_MESSAGE_TYPE) => didcomm_v2::parse_forward(&unpacked.message)?,
+            _ => None
+        };
+
+        let message = match forward {
+            Some((to, msg)) => A2AMessage::Forward(Forward { to, msg, ..Forward::default() }),
+            None => ::serde_json::from_slice(&unpacked.message)
+                .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidAgencyResponse, format!("Cannot deserialize A2A message: {}", err)))?
+        };
+
+        let keys = EnvelopeKeys { recipient_verkey: unpacked.recipient_verkey, sender_key: unpacked.sender_kid };
+
+        trace!("EncryptionEnvelope::open_v2 <<< message: {:?}, keys: {:?}", secret!(message), keys);
+
+        Ok((message, keys))
     }
 }
 
@@ -124,6 +300,105 @@ pub mod tests {
         assert_eq!(A2AMessage::Ack(message), EncryptionEnvelope::open(envelope.0).unwrap());
     }
 
+    #[test]
+    fn test_encryption_envelope_open_with_recipient_key() {
+        _setup();
+        let setup = test_setup::key();
+
+        let message = _ack();
+        let did_doc = _did_doc_4();
+        let envelope = EncryptionEnvelope::create(&message, Some(&setup.key), &did_doc).unwrap();
+
+        let (opened, recipient_key) = EncryptionEnvelope::open_with_recipient_key(envelope.0).unwrap();
+        assert_eq!(A2AMessage::Ack(message), opened);
+        assert_eq!(did_doc.recipient_keys()[0], recipient_key);
+    }
+
+    #[test]
+    fn test_encryption_envelope_v2_works_for_recipient_only() {
+        _setup();
+        let setup = test_setup::key();
+
+        let key_agreement_key = didcomm_v2::create_key_agreement_key(&setup.key).unwrap();
+
+        let mut did_doc = _did_doc_4();
+        did_doc.set_key_agreement_keys(vec![key_agreement_key.clone()]);
+
+        let message = _ack();
+        let envelope = EncryptionEnvelope::create_v2(&message, Some(&key_agreement_key), &did_doc).unwrap();
+        assert!(didcomm_v2::is_encrypted_message(&envelope.0));
+
+        let (opened, recipient_key) = EncryptionEnvelope::open_with_recipient_key(envelope.0).unwrap();
+        assert_eq!(A2AMessage::Ack(message), opened);
+        assert_eq!(setup.key, recipient_key);
+    }
+
+    #[test]
+    fn test_encryption_envelope_open_with_keys_returns_sender() {
+        _setup();
+        let setup = test_setup::key();
+
+        let did_doc = _did_doc_4();
+        let envelope = EncryptionEnvelope::create(&_ack(), Some(&setup.key), &did_doc).unwrap();
+        let (_, keys) = EncryptionEnvelope::open_with_keys(envelope.0).unwrap();
+        assert_eq!(did_doc.recipient_keys()[0], keys.recipient_verkey);
+        assert_eq!(Some(setup.key.clone()), keys.sender_key);
+
+        let key_agreement_key = didcomm_v2::create_key_agreement_key(&setup.key).unwrap();
+        let mut did_doc = _did_doc_4();
+        did_doc.set_key_agreement_keys(vec![key_agreement_key.clone()]);
+
+        let envelope = EncryptionEnvelope::create_v2(&_ack(), Some(&key_agreement_key), &did_doc).unwrap();
+        let (_, keys) = EncryptionEnvelope::open_with_keys(envelope.0).unwrap();
+        assert_eq!(setup.key, keys.recipient_verkey);
+        assert_eq!(Some(didcomm_v2::key_agreement_kid(&key_agreement_key).unwrap()), keys.sender_key);
+    }
+
+    #[test]
+    fn test_encryption_envelope_v2_works_for_did_key_routing_keys() {
+        _setup();
+        let setup = test_setup::key();
+
+        let key_agreement_key = didcomm_v2::create_key_agreement_key(&setup.key).unwrap();
+        let routing_key = didcomm_v2::create_key_agreement_key(&create_key(None).unwrap()).unwrap();
+
+        let mut did_doc = DidDoc::default();
+        did_doc.set_service_endpoint(_service_endpoint());
+        did_doc.set_keys(vec![setup.key.clone()], vec![didcomm_v2::key_agreement_kid(&routing_key).unwrap()]);
+        did_doc.set_key_agreement_keys(vec![key_agreement_key.clone()]);
+
+        let envelope = EncryptionEnvelope::create_v2(&_ack(), None, &did_doc).unwrap();
+
+        match EncryptionEnvelope::open(envelope.0).unwrap() {
+            A2AMessage::Forward(forward) => assert_eq!(didcomm_v2::key_agreement_kid(&key_agreement_key).unwrap(), forward.to),
+            message => panic!("Unexpected message: {:?}", message)
+        }
+    }
+
+    #[test]
+    fn test_envelope_version_content_type() {
+        assert_eq!(httpclient::DIDCOMM_ENVELOPE_CONTENT_TYPE, EnvelopeVersion::V1.content_type());
+        assert_eq!(didcomm_v2::ENCRYPTED_MESSAGE_TYPE, EnvelopeVersion::V2.content_type());
+    }
+
+    #[test]
+    fn test_encryption_envelope_v2_fails_for_did_doc_without_key_agreement_keys() {
+        _setup();
+        let setup = test_setup::key();
+
+        let key_agreement_key = didcomm_v2::create_key_agreement_key(&setup.key).unwrap();
+
+        let res = EncryptionEnvelope::create_v2(&_ack(), Some(&key_agreement_key), &_did_doc_4());
+        assert_eq!(res.unwrap_err().kind(), VcxErrorKind::InvalidDIDDoc);
+    }
+
+    #[test]
+    fn test_envelope_version_from_str() {
+        assert_eq!(EnvelopeVersion::V1, EnvelopeVersion::from_str("v1").unwrap());
+        assert_eq!(EnvelopeVersion::V2, EnvelopeVersion::from_str("v2").unwrap());
+        assert_eq!(VcxErrorKind::InvalidConfiguration, EnvelopeVersion::from_str("v3").unwrap_err().kind());
+    }
+
     #[test]
     fn test_encryption_envelope_works_for_routing_keys() {
         _setup();
@@ -0,0 +1,423 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::utils::libindy::wallet;
+use crate::aries::messages::a2a::A2AMessage;
+use crate::aries::messages::a2a::message_type::parse_message_type;
+
+/*
+    History of the messages sent and received over pairwise connections.
+
+    Enabled by `message_history` config option. A record is stored in the wallet for every message
+    sent to the remote side, downloaded from the Agency (or received through the inbound endpoint) and
+    then handled by a state machine. Records keep the message metadata only, not the message content.
+*/
+
+const MESSAGE_HISTORY_RECORD_TYPE: &str = "vcx_message_history";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageDirection {
+    Sent,
+    Received,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageStatus {
+    /// sent message was delivered to the remote endpoint
+    Sent,
+    /// sent message could not be delivered
+    Failed,
+    /// received message is waiting to be handled
+    Received,
+    /// received message was handled by a state machine
+    Processed,
+}
+
+/// Record describing a sent or received message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryMessage {
+    /// identifier of the connection the message was exchanged over (see `AgentInfo::connection_id`), kept on DID rotation
+    pub connection: String,
+    pub thread_id: String,
+    pub message_id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// protocol family of the message type (e.g. `connections`, `issue-credential`)
+    pub protocol: String,
+    pub direction: MessageDirection,
+    pub status: MessageStatus,
+    /// UTC time the message was sent or received at (RFC 3339)
+    pub timestamp: String,
+}
+
+/// Filter of message history records. All set fields must match.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HistoryQuery {
+    /// identifier of the connection (see `AgentInfo::connection_id`)
+    #[serde(default)]
+    pub connection: Option<String>,
+    #[serde(default)]
+    pub thread_id: Option<String>,
+    #[serde(default)]
+    pub protocol: Option<String>,
+    #[serde(default)]
+    pub direction: Option<MessageDirection>,
+    #[serde(default)]
+    pub status: Option<MessageStatus>,
+    /// messages sent or received at or after this time (Unix timestamp in seconds)
+    #[serde(default)]
+    pub from: Option<i64>,
+    /// messages sent or received at or before this time (Unix timestamp in seconds)
+    #[serde(default)]
+    pub to: Option<i64>,
+    /// maximum number of records to return
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// number of the first matching records (ordered by time) to skip
+    #[serde(default)]
+    pub offset: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn from_opt_str(query: Option<&str>) -> VcxResult<HistoryQuery> {
+        match query.map(str::trim) {
+            Some(query) if !query.is_empty() => {
+                ::serde_json::from_str(query)
+                    .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot parse message history query: {}", err)))
+            }
+            _ => Ok(HistoryQuery::default())
+        }
+    }
+
+    pub fn set_connection(mut self, connection: String) -> HistoryQuery {
+        self.connection = Some(connection);
+        self
+    }
+
+    /// WQL query over record tags.
+    /// Time range is checked against unencrypted `~timestamp` tag as only those support comparison operators.
+    fn to_wql(&self) -> Value {
+        let mut conditions = Vec::new();
+
+        let mut tag = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                conditions.push(json!({ name: value }));
+            }
+        };
+
+        tag("connection", self.connection.clone());
+        tag("thread_id", self.thread_id.clone());
+        tag("protocol", self.protocol.clone());
+        tag("direction", self.direction.map(|direction| json!(direction).as_str().unwrap_or_default().to_string()));
+        tag("status", self.status.map(|status| json!(status).as_str().unwrap_or_default().to_string()));
+
+        if let Some(from) = self.from {
+            conditions.push(json!({"~timestamp": {"$gte": timestamp_tag(from)}}));
+        }
+        if let Some(to) = self.to {
+            conditions.push(json!({"~timestamp": {"$lte": timestamp_tag(to)}}));
+        }
+
+        match conditions.len() {
+            0 => json!({}),
+            1 => conditions.remove(0),
+            _ => json!({"$and": conditions})
+        }
+    }
+}
+
+impl HistoryMessage {
+    fn build(connection: &str, message: &Value, direction: MessageDirection, status: MessageStatus) -> Option<HistoryMessage> {
+        let message_id = match message["@id"].as_str() {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => return None
+        };
+
+        let thread_id = message["~thread"]["thid"].as_str()
+            .map(String::from)
+            .unwrap_or_else(|| message_id.clone());
+
+        let type_ = message["@type"].as_str().unwrap_or_default().to_string();
+
+        let protocol = parse_message_type(&type_)
+            .map(|(_, family, _, _)| family)
+            .unwrap_or_default();
+
+        Some(HistoryMessage {
+            connection: connection.to_string(),
+            thread_id,
+            message_id,
+            type_,
+            protocol,
+            direction,
+            status,
+            timestamp: Utc::now().to_rfc3339(),
+        })
+    }
+
+    fn record_id(&self) -> String {
+        format!("{}:{}:{}", self.connection, json!(self.direction).as_str().unwrap_or_default(), self.message_id)
+    }
+
+    fn unix_timestamp(&self) -> i64 {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .map(|timestamp| timestamp.timestamp())
+            .unwrap_or_default()
+    }
+
+    fn tags(&self) -> String {
+        json!({
+            "connection": self.connection,
+            "thread_id": self.thread_id,
+            "message_id": self.message_id,
+            "protocol": self.protocol,
+            "direction": self.direction,
+            "status": self.status,
+            "~timestamp": timestamp_tag(self.unix_timestamp()),
+        }).to_string()
+    }
+}
+
+/// Unix timestamp padded with zeros so string comparison of the tags matches the time order.
+fn timestamp_tag(timestamp: i64) -> String {
+    format!("{:012}", timestamp.max(0))
+}
+
+fn is_enabled() -> bool {
+    !settings::indy_mocks_enabled() &&
+        settings::get_config_value(settings::CONFIG_MESSAGE_HISTORY).map_or(false, |value| value == "true")
+}
+
+/// Records the message sent over the connection identified by `connection`.
+pub fn record_sent(connection: &str, message: &Value, delivered: bool) {
+    let status = if delivered { MessageStatus::Sent } else { MessageStatus::Failed };
+    _record(connection, message, MessageDirection::Sent, status, true)
+}
+
+/// Records the message received over the connection identified by `connection`.
+/// Messages recorded already (e.g. downloaded again on the next poll) are skipped.
+pub fn record_received(connection: &str, message: &A2AMessage) {
+    _record(connection, &json!(message), MessageDirection::Received, MessageStatus::Received, false)
+}
+
+/// Records that the message received over the connection identified by `connection` has been handled.
+pub fn record_processed(connection: &str, message: &A2AMessage) {
+    _record(connection, &json!(message), MessageDirection::Received, MessageStatus::Processed, true)
+}
+
+fn _record(connection: &str, message: &Value, direction: MessageDirection, status: MessageStatus, update_status: bool) {
+    if !is_enabled() { return; }
+
+    let history_message = match HistoryMessage::build(connection, message, direction, status) {
+        Some(history_message) => history_message,
+        None => {
+            warn!("Message without @id cannot be recorded in message history");
+            return;
+        }
+    };
+
+    if let Err(err) = _store(history_message, update_status) {
+        warn!("Unable to record message in message history. Err: {:?}", err);
+    }
+}
+
+fn _store(history_message: HistoryMessage, update_status: bool) -> VcxResult<()> {
+    trace!("message_history::_store >>> history_message: {:?}", secret!(history_message));
+
+    let record_id = history_message.record_id();
+
+    if !update_status && _exists(&record_id)? {
+        trace!("message_history::_store <<< message {:?} is recorded already", history_message.message_id);
+        return Ok(());
+    }
+
+    match wallet::add_record(MESSAGE_HISTORY_RECORD_TYPE, &record_id, &json!(history_message).to_string(), Some(&history_message.tags())) {
+        Ok(()) => Ok(()),
+        Err(ref err) if err.kind() == VcxErrorKind::DuplicationWalletRecord && update_status => {
+            // message timestamp is kept, only the status changes
+            let mut stored = _get(&record_id)?;
+            stored.status = history_message.status;
+
+            wallet::update_record_value(MESSAGE_HISTORY_RECORD_TYPE, &record_id, &json!(stored).to_string())?;
+            wallet::update_record_tags(MESSAGE_HISTORY_RECORD_TYPE, &record_id, &stored.tags())
+        }
+        Err(ref err) if err.kind() == VcxErrorKind::DuplicationWalletRecord => Ok(()),
+        Err(err) => Err(err)
+    }
+}
+
+fn _exists(record_id: &str) -> VcxResult<bool> {
+    match _get(record_id) {
+        Ok(_) => Ok(true),
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
+fn _get(record_id: &str) -> VcxResult<HistoryMessage> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    let record = wallet::get_record(MESSAGE_HISTORY_RECORD_TYPE, record_id, &options)?;
+    let record: Value = ::serde_json::from_str(&record)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize message history record: {}", err)))?;
+
+    _parse_record(&record)
+}
+
+fn _parse_record(record: &Value) -> VcxResult<HistoryMessage> {
+    let value = record["value"].as_str()
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Message history record does not contain value"))?;
+
+    ::serde_json::from_str(value)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize message history record: {}", err)))
+}
+
+/// Returns message history records matching the query ordered by time.
+/// `offset` and `limit` of the query select the page of the ordered records.
+pub fn query(query: &HistoryQuery) -> VcxResult<Vec<HistoryMessage>> {
+    trace!("message_history::query >>> query: {:?}", secret!(query));
+
+    if settings::indy_mocks_enabled() { return Ok(Vec::new()); }
+
+    let options = json!({"retrieveRecords": true, "retrieveTotalCount": false, "retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let records = wallet::search_records(MESSAGE_HISTORY_RECORD_TYPE, &query.to_wql().to_string(), &options)?;
+
+    let mut messages = records.iter()
+        .map(_parse_record)
+        .collect::<VcxResult<Vec<HistoryMessage>>>()?;
+
+    messages.sort_by_key(HistoryMessage::unix_timestamp);
+
+    let messages = messages.into_iter()
+        .skip(query.offset.unwrap_or(0))
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect::<Vec<HistoryMessage>>();
+
+    trace!("message_history::query <<< messages: {:?}", secret!(messages));
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+    use crate::aries::messages::ack::tests::_ack;
+    use crate::aries::messages::trust_ping::ping::tests::_ping;
+
+    const CONNECTION: &str = "5d53a8c9-6d49-4a3a-a6b2-b53e2b1b2b8a";
+
+    #[test]
+    fn test_history_query_from_opt_str() {
+        assert_eq!(HistoryQuery::default(), HistoryQuery::from_opt_str(None).unwrap());
+        assert_eq!(HistoryQuery::default(), HistoryQuery::from_opt_str(Some(" ")).unwrap());
+
+        let query = HistoryQuery::from_opt_str(Some(r#"{"thread_id": "123", "direction": "received", "from": 1600000000, "limit": 10, "offset": 20}"#)).unwrap();
+        assert_eq!(Some("123".to_string()), query.thread_id);
+        assert_eq!(Some(MessageDirection::Received), query.direction);
+        assert_eq!(Some(1600000000), query.from);
+        assert_eq!(Some(10), query.limit);
+        assert_eq!(Some(20), query.offset);
+
+        HistoryQuery::from_opt_str(Some(r#"{"direction": "other"}"#)).unwrap_err();
+    }
+
+    #[test]
+    fn test_history_query_to_wql() {
+        assert_eq!(json!({}), HistoryQuery::default().to_wql());
+
+        let query = HistoryQuery::default().set_connection(CONNECTION.to_string());
+        assert_eq!(json!({"connection": CONNECTION}), query.to_wql());
+
+        let query = HistoryQuery {
+            protocol: Some("trust_ping".to_string()),
+            direction: Some(MessageDirection::Sent),
+            from: Some(100),
+            ..HistoryQuery::default()
+        };
+        assert_eq!(json!({"$and": [
+            {"protocol": "trust_ping"},
+            {"direction": "sent"},
+            {"~timestamp": {"$gte": "000000000100"}}
+        ]}), query.to_wql());
+    }
+
+    #[test]
+    fn test_message_history_is_disabled_by_default() {
+        let _setup = SetupLibraryWallet::init();
+
+        record_processed(CONNECTION, &A2AMessage::Ack(_ack()));
+
+        assert!(query(&HistoryQuery::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_message_history_works() {
+        let _setup = SetupLibraryWallet::init();
+        settings::set_config_value(settings::CONFIG_MESSAGE_HISTORY, "true");
+
+        let start = Utc::now().timestamp();
+
+        let ack = A2AMessage::Ack(_ack());
+        record_received(CONNECTION, &ack);
+        record_processed(CONNECTION, &ack);
+        record_received(CONNECTION, &ack);
+
+        record_sent(CONNECTION, &json!(A2AMessage::Ping(_ping())), false);
+        record_sent("other-connection", &json!(A2AMessage::Ping(_ping())), true);
+
+        let messages = query(&HistoryQuery::default().set_connection(CONNECTION.to_string())).unwrap();
+        assert_eq!(2, messages.len());
+
+        let received = messages.iter().find(|message| message.direction == MessageDirection::Received).unwrap();
+        assert_eq!(MessageStatus::Processed, received.status);
+        assert_eq!(_ack().thread.thid.unwrap(), received.thread_id);
+        assert_eq!("notification", received.protocol);
+
+        let sent = messages.iter().find(|message| message.direction == MessageDirection::Sent).unwrap();
+        assert_eq!(MessageStatus::Failed, sent.status);
+        assert_eq!("trust_ping", sent.protocol);
+
+        let query_ = HistoryQuery { protocol: Some("trust_ping".to_string()), ..HistoryQuery::default() };
+        assert_eq!(2, query(&query_).unwrap().len());
+
+        let query_ = HistoryQuery { status: Some(MessageStatus::Sent), ..HistoryQuery::default() };
+        assert_eq!("other-connection", query(&query_).unwrap()[0].connection);
+
+        let query_ = HistoryQuery { from: Some(start), to: Some(Utc::now().timestamp()), ..HistoryQuery::default() };
+        assert_eq!(3, query(&query_).unwrap().len());
+
+        let query_ = HistoryQuery { to: Some(start - 1), ..HistoryQuery::default() };
+        assert!(query(&query_).unwrap().is_empty());
+
+        let all = query(&HistoryQuery::default()).unwrap();
+
+        let query_ = HistoryQuery { limit: Some(2), ..HistoryQuery::default() };
+        assert_eq!(all[..2].to_vec(), query(&query_).unwrap());
+
+        let query_ = HistoryQuery { offset: Some(1), limit: Some(1), ..HistoryQuery::default() };
+        assert_eq!(all[1..2].to_vec(), query(&query_).unwrap());
+
+        let query_ = HistoryQuery { offset: Some(3), ..HistoryQuery::default() };
+        assert!(query(&query_).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_message_history_skips_messages_received_again() {
+        let _setup = SetupLibraryWallet::init();
+        settings::set_config_value(settings::CONFIG_MESSAGE_HISTORY, "true");
+
+        let ack = A2AMessage::Ack(_ack());
+        record_received(CONNECTION, &ack);
+        let recorded = query(&HistoryQuery::default()).unwrap();
+
+        // the same pending message is downloaded on every poll
+        record_received(CONNECTION, &ack);
+        record_received(CONNECTION, &ack);
+
+        assert_eq!(recorded, query(&HistoryQuery::default()).unwrap());
+    }
+}
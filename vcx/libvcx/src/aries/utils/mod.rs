@@ -7,6 +7,11 @@ use reqwest::{Url, Client, ClientBuilder, RedirectPolicy};
 use reqwest::Response;
 
 pub mod encryption_envelope;
+pub mod didcomm_v2;
+pub mod processed_messages;
+pub mod message_history;
+pub mod connection_storage;
+pub mod revoked_credentials;
 
 // ensure service keys are naked keys
 pub(crate) fn normalize_service_keys(services: &mut Vec<Service>) -> VcxResult<()> {
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::utils::libindy::wallet;
+use crate::aries::messages::a2a::A2AMessage;
+
+/*
+    Ledger of the messages which have already been handled by state machines.
+
+    A record is stored in the wallet for every message (per connection and message id) before it is handled,
+    so a message downloaded or delivered again (e.g. when the status update failed) or handled concurrently
+    is skipped instead of being handled for the second time. The record is dropped if the handling fails.
+    Records are kept by the stable connection identifier (`AgentInfo::connection_id`), so they survive DID rotation.
+*/
+
+const PROCESSED_MESSAGE_RECORD_TYPE: &str = "vcx_processed_message";
+
+/// Record describing a handled message.
+/// Records can be queried by `connection`, `thread_id` and `message_id` tags.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProcessedMessage {
+    /// identifier of the connection the message was received over
+    pub connection: String,
+    pub thread_id: String,
+    pub message_id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// UTC time the message was handled at (RFC 3339)
+    pub processed_at: String,
+}
+
+impl ProcessedMessage {
+    fn build(connection: &str, message: &A2AMessage) -> Option<ProcessedMessage> {
+        let message = json!(message);
+
+        let message_id = match message["@id"].as_str() {
+            Some(id) if !id.is_empty() => id.to_string(),
+            _ => return None
+        };
+
+        let thread_id = message["~thread"]["thid"].as_str()
+            .map(String::from)
+            .unwrap_or_else(|| message_id.clone());
+
+        Some(ProcessedMessage {
+            connection: connection.to_string(),
+            thread_id,
+            message_id,
+            type_: message["@type"].as_str().unwrap_or_default().to_string(),
+            processed_at: Utc::now().to_rfc3339(),
+        })
+    }
+
+    fn record_id(connection: &str, message_id: &str) -> String {
+        format!("{}:{}", connection, message_id)
+    }
+
+    fn tags(&self) -> String {
+        json!({
+            "connection": self.connection,
+            "thread_id": self.thread_id,
+            "message_id": self.message_id,
+        }).to_string()
+    }
+}
+
+/// Records that the message received over the connection with `connection` identifier is being handled.
+/// Returns `false` if the message has already been recorded.
+pub fn mark_processed(connection: &str, message: &A2AMessage) -> VcxResult<bool> {
+    trace!("processed_messages::mark_processed >>> connection: {:?}, message: {:?}", connection, secret!(message));
+
+    if settings::indy_mocks_enabled() { return Ok(true); }
+
+    let processed_message = match ProcessedMessage::build(connection, message) {
+        Some(processed_message) => processed_message,
+        None => {
+            warn!("Message without @id cannot be recorded as processed");
+            return Ok(true);
+        }
+    };
+
+    let record_id = ProcessedMessage::record_id(connection, &processed_message.message_id);
+
+    // adding the record is atomic, so only one of the concurrent handlers of the same message succeeds
+    match wallet::add_record(PROCESSED_MESSAGE_RECORD_TYPE, &record_id, &json!(processed_message).to_string(), Some(&processed_message.tags())) {
+        Ok(()) => Ok(true),
+        Err(ref err) if err.kind() == VcxErrorKind::DuplicationWalletRecord => Ok(false),
+        Err(err) => Err(err)
+    }
+}
+
+/// Drops the record of the message which handling failed, so the message is handled when it is received again.
+pub fn unmark_processed(connection: &str, message: &A2AMessage) -> VcxResult<()> {
+    trace!("processed_messages::unmark_processed >>> connection: {:?}, message: {:?}", connection, secret!(message));
+
+    if settings::indy_mocks_enabled() { return Ok(()); }
+
+    let processed_message = match ProcessedMessage::build(connection, message) {
+        Some(processed_message) => processed_message,
+        None => return Ok(())
+    };
+
+    match wallet::delete_record(PROCESSED_MESSAGE_RECORD_TYPE, &ProcessedMessage::record_id(connection, &processed_message.message_id)) {
+        Ok(()) => Ok(()),
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => Ok(()),
+        Err(err) => Err(err)
+    }
+}
+
+/// Returns uids of `messages` received over the connection with `connection` identifier which have already been handled.
+/// All messages are checked by a single wallet search.
+pub fn filter_processed(connection: &str, messages: &HashMap<String, A2AMessage>) -> VcxResult<Vec<String>> {
+    trace!("processed_messages::filter_processed >>> connection: {:?}, messages: {:?}", connection, secret!(messages));
+
+    let message_ids: HashMap<String, String> = messages.iter()
+        .filter_map(|(uid, message)|
+            ProcessedMessage::build(connection, message).map(|processed_message| (processed_message.message_id, uid.clone())))
+        .collect();
+
+    if message_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let query = json!({
+        "connection": connection,
+        "message_id": {"$in": message_ids.keys().collect::<Vec<&String>>()}
+    }).to_string();
+
+    let processed_uids = self::query(&query)?
+        .into_iter()
+        .filter_map(|processed_message| message_ids.get(&processed_message.message_id).cloned())
+        .collect();
+
+    trace!("processed_messages::filter_processed <<< processed_uids: {:?}", processed_uids);
+    Ok(processed_uids)
+}
+
+/// Returns processed message records matching WQL `query` over `connection`, `thread_id` and `message_id` tags.
+pub fn query(query: &str) -> VcxResult<Vec<ProcessedMessage>> {
+    trace!("processed_messages::query >>> query: {:?}", secret!(query));
+
+    if settings::indy_mocks_enabled() { return Ok(Vec::new()); }
+
+    let options = json!({"retrieveRecords": true, "retrieveTotalCount": false, "retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+    let records = wallet::search_records(PROCESSED_MESSAGE_RECORD_TYPE, query, &options)?;
+
+    let mut processed_messages = Vec::new();
+    for record in records {
+        let value = record["value"].as_str()
+            .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Processed message record does not contain value"))?;
+
+        let processed_message: ProcessedMessage = ::serde_json::from_str(value)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize processed message record: {}", err)))?;
+
+        processed_messages.push(processed_message);
+    }
+
+    trace!("processed_messages::query <<< processed_messages: {:?}", secret!(processed_messages));
+    Ok(processed_messages)
+}
+
+/// Deletes processed message records matching WQL `query`.
+/// Messages of deleted records are handled again if they are received once more.
+/// Returns the number of deleted records.
+pub fn purge(query: &str) -> VcxResult<usize> {
+    trace!("processed_messages::purge >>> query: {:?}", secret!(query));
+
+    let processed_messages = self::query(query)?;
+
+    for processed_message in processed_messages.iter() {
+        wallet::delete_record(PROCESSED_MESSAGE_RECORD_TYPE,
+                              &ProcessedMessage::record_id(&processed_message.connection, &processed_message.message_id))?;
+    }
+
+    trace!("processed_messages::purge <<< deleted: {}", processed_messages.len());
+    Ok(processed_messages.len())
+}
+
+/// Deletes all processed message records of the connection with `connection` identifier.
+pub fn purge_connection(connection: &str) -> VcxResult<usize> {
+    purge(&json!({"connection": connection}).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+    use crate::aries::messages::ack::tests::_ack;
+
+    const CONNECTION: &str = "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL";
+
+    #[test]
+    fn test_processed_messages_ledger() {
+        let _setup = SetupLibraryWallet::init();
+
+        let message = A2AMessage::Ack(_ack());
+        let messages = map!(String::from("uid_1") => message.clone());
+        assert!(filter_processed(CONNECTION, &messages).unwrap().is_empty());
+
+        assert!(mark_processed(CONNECTION, &message).unwrap());
+        assert!(!mark_processed(CONNECTION, &message).unwrap());
+
+        assert_eq!(vec![String::from("uid_1")], filter_processed(CONNECTION, &messages).unwrap());
+        assert!(filter_processed("OtherConnection", &messages).unwrap().is_empty());
+
+        let processed_messages = query(&json!({"connection": CONNECTION}).to_string()).unwrap();
+        assert_eq!(1, processed_messages.len());
+        assert_eq!(_ack().thread.thid.unwrap(), processed_messages[0].thread_id);
+
+        assert_eq!(0, query(&json!({"thread_id": "other"}).to_string()).unwrap().len());
+
+        assert_eq!(1, purge_connection(CONNECTION).unwrap());
+        assert!(filter_processed(CONNECTION, &messages).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unmark_processed_allows_handling_message_again() {
+        let _setup = SetupLibraryWallet::init();
+
+        let message = A2AMessage::Ack(_ack());
+
+        assert!(mark_processed(CONNECTION, &message).unwrap());
+        unmark_processed(CONNECTION, &message).unwrap();
+        unmark_processed(CONNECTION, &message).unwrap();
+        assert!(mark_processed(CONNECTION, &message).unwrap());
+    }
+}
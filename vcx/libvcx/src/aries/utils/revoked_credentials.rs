@@ -0,0 +1,277 @@
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::aries::messages::revocation_notification::revoke::{RevocationNotification, RevokedCredential};
+use crate::utils::libindy::anoncreds::holder::Holder as IndyHolder;
+use crate::utils::libindy::anoncreds::types::CredentialInfo;
+use crate::utils::libindy::wallet::{self, WalletRecord};
+
+/*
+    Credentials the issuer notified the holder about revocation of (Revocation Notification protocol).
+
+    A record is kept per stored credential (record id is the credential id in the wallet).
+    Notifications are accepted only over the connection the credential was issued over,
+    so the holder keeps the connection of every credential received over a connection
+    (record id is the credential id, value is the connection id, tagged with `rev_reg_id` and `cred_rev_id`).
+    Notifications about credentials not stored in the wallet or issued over another connection are ignored.
+*/
+
+const REVOKED_CREDENTIAL_RECORD_TYPE: &str = "vcx_revoked_credential";
+const ISSUER_CONNECTION_RECORD_TYPE: &str = "vcx_credential_issuer_connection";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RevokedCredentialRecord {
+    pub cred_id: String,
+    pub rev_reg_id: Option<String>,
+    pub cred_rev_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// UTC time the notification was received (RFC 3339)
+    pub received: String,
+}
+
+impl RevokedCredentialRecord {
+    pub fn new(credential: &CredentialInfo, comment: Option<String>) -> RevokedCredentialRecord {
+        RevokedCredentialRecord {
+            cred_id: credential.referent.clone(),
+            rev_reg_id: credential.rev_reg_id.clone(),
+            cred_rev_id: credential.cred_rev_id.clone(),
+            comment,
+            received: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Marks the credential the notification received over the connection identified by `connection_id` refers to as revoked.
+/// Returns the id of the credential or `None` if it is not stored in the wallet or was issued over another connection.
+pub fn handle_notification(notification: &RevocationNotification, connection_id: &str) -> VcxResult<Option<String>> {
+    trace!("revoked_credentials::handle_notification >>> notification: {:?}, connection_id: {}", secret!(notification), connection_id);
+
+    let cred_id = match find_credential(&notification.revoked_credential()?, connection_id)? {
+        Some(cred_id) => cred_id,
+        None => return Ok(None)
+    };
+
+    // the credential could be deleted after it was issued
+    let credential = match IndyHolder::get_credential(&cred_id) {
+        Ok(credential) => credential,
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    let record = RevokedCredentialRecord::new(&credential, notification.comment().cloned());
+    store(&record)?;
+
+    trace!("revoked_credentials::handle_notification <<< cred_id: {}", record.cred_id);
+    Ok(Some(record.cred_id))
+}
+
+/// Finds the id of the stored credential issued over the connection by the identifier used in the notification.
+fn find_credential(revoked_credential: &RevokedCredential, connection_id: &str) -> VcxResult<Option<String>> {
+    if settings::indy_mocks_enabled() { return Ok(None); }
+
+    let issuer_connections: Vec<(String, String)> = match revoked_credential {
+        // Aries Holder stores credentials under the thread id of their issuance
+        RevokedCredential::Thread(thread_id) => {
+            _get_issuer_connection(thread_id)?
+                .map(|issuer_connection| (thread_id.to_string(), issuer_connection))
+                .into_iter()
+                .collect()
+        }
+        RevokedCredential::Revocation { rev_reg_id, cred_rev_id } => {
+            let query = json!({"rev_reg_id": rev_reg_id, "cred_rev_id": cred_rev_id}).to_string();
+            let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+            wallet::search_records(ISSUER_CONNECTION_RECORD_TYPE, &query, &options)?
+                .into_iter()
+                .filter_map(|record| Some((record["id"].as_str()?.to_string(), record["value"].as_str()?.to_string())))
+                .collect()
+        }
+    };
+
+    Ok(issuer_connections.into_iter()
+        .find(|(_, issuer_connection)| issuer_connection == connection_id)
+        .map(|(cred_id, _)| cred_id))
+}
+
+/// Remembers the connection the stored credential was issued over.
+pub fn store_issuer_connection(cred_id: &str, connection_id: &str) -> VcxResult<()> {
+    trace!("revoked_credentials::store_issuer_connection >>> cred_id: {}, connection_id: {}", cred_id, connection_id);
+
+    if settings::indy_mocks_enabled() { return Ok(()); }
+
+    let credential = IndyHolder::get_credential(cred_id)?;
+
+    let tags = match (credential.rev_reg_id, credential.cred_rev_id) {
+        (Some(rev_reg_id), Some(cred_rev_id)) => json!({"rev_reg_id": rev_reg_id, "cred_rev_id": cred_rev_id}),
+        _ => json!({})
+    };
+
+    match _get_issuer_connection(cred_id)? {
+        Some(_) => wallet::update_record_value(ISSUER_CONNECTION_RECORD_TYPE, cred_id, connection_id),
+        None => wallet::add_record(ISSUER_CONNECTION_RECORD_TYPE, cred_id, connection_id, Some(&tags.to_string()))
+    }
+}
+
+/// Forgets the connection the credential was issued over (the credential is deleted).
+pub fn delete_issuer_connection(cred_id: &str) -> VcxResult<()> {
+    trace!("revoked_credentials::delete_issuer_connection >>> cred_id: {}", cred_id);
+
+    if settings::indy_mocks_enabled() { return Ok(()); }
+
+    match wallet::delete_record(ISSUER_CONNECTION_RECORD_TYPE, cred_id) {
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => Ok(()),
+        result => result
+    }
+}
+
+fn _get_issuer_connection(cred_id: &str) -> VcxResult<Option<String>> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    let record = match wallet::get_record(ISSUER_CONNECTION_RECORD_TYPE, cred_id, &options) {
+        Ok(record) => record,
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    WalletRecord::from_str(&record)?.value
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Credential issuer connection record does not contain value"))
+        .map(Some)
+}
+
+/// Returns revocation record of the credential.
+pub fn get(cred_id: &str) -> VcxResult<Option<RevokedCredentialRecord>> {
+    trace!("revoked_credentials::get >>> cred_id: {}", cred_id);
+
+    if settings::indy_mocks_enabled() { return Ok(None); }
+
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    let record = match wallet::get_record(REVOKED_CREDENTIAL_RECORD_TYPE, cred_id, &options) {
+        Ok(record) => record,
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    let value = WalletRecord::from_str(&record)?.value
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Revoked credential record does not contain value"))?;
+
+    ::serde_json::from_str(&value)
+        .map(Some)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize revoked credential record: {}", err)))
+}
+
+/// Stores revocation record of the credential replacing the stored one.
+pub fn store(record: &RevokedCredentialRecord) -> VcxResult<()> {
+    trace!("revoked_credentials::store >>> record: {:?}", record);
+
+    let value = json!(record).to_string();
+
+    match get(&record.cred_id)? {
+        Some(_) => wallet::update_record_value(REVOKED_CREDENTIAL_RECORD_TYPE, &record.cred_id, &value),
+        None => wallet::add_record(REVOKED_CREDENTIAL_RECORD_TYPE, &record.cred_id, &value, None)
+    }
+}
+
+/// Extends credential info with `revoked` flag and `revocation` record if the issuer notified about revocation.
+pub fn credential_info_json(credential: &CredentialInfo) -> VcxResult<String> {
+    let mut info = json!(credential);
+
+    let revocation = get(&credential.referent)?;
+    info["revoked"] = Value::Bool(revocation.is_some());
+    if let Some(revocation) = revocation {
+        info["revocation"] = json!(revocation);
+    }
+
+    Ok(info.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aries::messages::revocation_notification::v10::tests::THREAD_ID;
+    use crate::aries::messages::revocation_notification::v20::tests::{REV_REG_ID, CRED_REV_ID};
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    const CONNECTION_ID: &str = "connection_id";
+
+    fn _credential_info() -> CredentialInfo {
+        CredentialInfo {
+            referent: THREAD_ID.to_string(),
+            attrs: map!(String::from("name") => String::from("Alice")),
+            schema_id: String::from("V4SGRU86Z58d6TV7PBUe6f:2:test-licence:4.4.4"),
+            cred_def_id: String::from("V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1"),
+            rev_reg_id: Some(REV_REG_ID.to_string()),
+            cred_rev_id: Some(CRED_REV_ID.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_revoked_credentials_storage_works() {
+        let _setup = SetupLibraryWallet::init();
+
+        assert_eq!(None, get(THREAD_ID).unwrap());
+
+        let record = RevokedCredentialRecord::new(&_credential_info(), None);
+        store(&record).unwrap();
+        assert_eq!(Some(record.clone()), get(THREAD_ID).unwrap());
+
+        let record = RevokedCredentialRecord { comment: Some(String::from("Expired")), ..record };
+        store(&record).unwrap();
+        assert_eq!(Some(record), get(THREAD_ID).unwrap());
+    }
+
+    #[test]
+    fn test_credential_info_json_contains_revocation() {
+        let _setup = SetupLibraryWallet::init();
+
+        let info: Value = ::serde_json::from_str(&credential_info_json(&_credential_info()).unwrap()).unwrap();
+        assert_eq!(json!(false), info["revoked"]);
+        assert!(info.get("revocation").is_none());
+
+        let record = RevokedCredentialRecord::new(&_credential_info(), Some(String::from("Expired")));
+        store(&record).unwrap();
+
+        let info: Value = ::serde_json::from_str(&credential_info_json(&_credential_info()).unwrap()).unwrap();
+        assert_eq!(json!(true), info["revoked"]);
+        assert_eq!(json!(record), info["revocation"]);
+        assert_eq!(json!("Alice"), info["attrs"]["name"]);
+    }
+
+    #[test]
+    fn test_handle_notification_ignores_unknown_credential() {
+        let _setup = SetupLibraryWallet::init();
+
+        let notification = RevocationNotification::create(None, THREAD_ID, REV_REG_ID, CRED_REV_ID, None).unwrap();
+        assert_eq!(None, handle_notification(&notification, CONNECTION_ID).unwrap());
+
+        let notification = RevocationNotification::create(Some("2.0"), THREAD_ID, REV_REG_ID, CRED_REV_ID, None).unwrap();
+        assert_eq!(None, handle_notification(&notification, CONNECTION_ID).unwrap());
+    }
+
+    #[test]
+    fn test_find_credential_checks_issuer_connection() {
+        let _setup = SetupLibraryWallet::init();
+
+        let tags = json!({"rev_reg_id": REV_REG_ID, "cred_rev_id": CRED_REV_ID}).to_string();
+        wallet::add_record(ISSUER_CONNECTION_RECORD_TYPE, THREAD_ID, CONNECTION_ID, Some(&tags)).unwrap();
+
+        let by_thread = RevokedCredential::Thread(THREAD_ID.to_string());
+        let by_revocation = RevokedCredential::Revocation { rev_reg_id: REV_REG_ID.to_string(), cred_rev_id: CRED_REV_ID.to_string() };
+        let by_other_revocation = RevokedCredential::Revocation { rev_reg_id: REV_REG_ID.to_string(), cred_rev_id: String::from("2") };
+
+        assert_eq!(Some(THREAD_ID.to_string()), find_credential(&by_thread, CONNECTION_ID).unwrap());
+        assert_eq!(Some(THREAD_ID.to_string()), find_credential(&by_revocation, CONNECTION_ID).unwrap());
+        assert_eq!(None, find_credential(&by_other_revocation, CONNECTION_ID).unwrap());
+
+        // credential issued over another connection
+        assert_eq!(None, find_credential(&by_thread, "other_connection_id").unwrap());
+        assert_eq!(None, find_credential(&by_revocation, "other_connection_id").unwrap());
+
+        delete_issuer_connection(THREAD_ID).unwrap();
+        delete_issuer_connection(THREAD_ID).unwrap();
+        assert_eq!(None, find_credential(&by_thread, CONNECTION_ID).unwrap());
+    }
+}
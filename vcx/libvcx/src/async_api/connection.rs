@@ -0,0 +1,173 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::connection::{self, Connections};
+use crate::utils::object_cache::Handle;
+use crate::aries::utils::message_history::{HistoryMessage, HistoryQuery};
+use crate::aries::utils::connection_storage::StoredConnection;
+
+/// Async version of `connection::create_connection`.
+pub fn create_connection(source_id: &str) -> VcxFuture<Handle<Connections>> {
+    let source_id = source_id.to_string();
+    blocking(move || connection::create_connection(&source_id))
+}
+
+/// Async version of `connection::create_outofband_connection`.
+pub fn create_outofband_connection(source_id: &str,
+                                   goal_code: Option<String>,
+                                   goal: Option<String>,
+                                   handshake: bool,
+                                   request_attach: Option<String>) -> VcxFuture<Handle<Connections>> {
+    let source_id = source_id.to_string();
+    blocking(move || connection::create_outofband_connection(&source_id, goal_code, goal, handshake, request_attach))
+}
+
+/// Async version of `connection::create_connection_with_invite`.
+pub fn create_connection_with_invite(source_id: &str, details: &str) -> VcxFuture<Handle<Connections>> {
+    let source_id = source_id.to_string();
+    let details = details.to_string();
+    blocking(move || connection::create_connection_with_invite(&source_id, &details))
+}
+
+/// Async version of `connection::create_connection_with_outofband_invite`.
+pub fn create_connection_with_outofband_invite(source_id: &str, invitation: &str) -> VcxFuture<Handle<Connections>> {
+    let source_id = source_id.to_string();
+    let invitation = invitation.to_string();
+    blocking(move || connection::create_connection_with_outofband_invite(&source_id, &invitation))
+}
+
+/// Async version of `connection::accept_connection_invite`.
+/// Resolves to the connection handle and its serialized state.
+pub fn accept_connection_invite(source_id: &str, details: &str, options: Option<String>) -> VcxFuture<(Handle<Connections>, String)> {
+    let source_id = source_id.to_string();
+    let details = details.to_string();
+    blocking(move || connection::accept_connection_invite(&source_id, &details, options))
+}
+
+/// Async version of `Handle<Connections>::connect`.
+pub fn connect(handle: Handle<Connections>, options: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.connect(options))
+}
+
+/// Async version of `Handle<Connections>::update_state`.
+/// Resolves to the new state of the connection.
+pub fn update_state(handle: Handle<Connections>, message: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state(message))
+}
+
+/// Async version of `Handle<Connections>::get_invite_details`.
+pub fn get_invite_details(handle: Handle<Connections>, abbreviated: bool) -> VcxFuture<String> {
+    blocking(move || handle.get_invite_details(abbreviated))
+}
+
+/// Async version of `Handle<Connections>::send_generic_message`.
+/// Resolves to the id of the sent message.
+pub fn send_message(handle: Handle<Connections>, message: &str, options: &str) -> VcxFuture<String> {
+    let message = message.to_string();
+    let options = options.to_string();
+    blocking(move || handle.send_generic_message(&message, &options))
+}
+
+/// Async version of `Handle<Connections>::send_ping`.
+pub fn send_ping(handle: Handle<Connections>, comment: Option<String>) -> VcxFuture<()> {
+    blocking(move || handle.send_ping(comment))
+}
+
+/// Async version of `Handle<Connections>::rotate_did`.
+pub fn rotate_did(handle: Handle<Connections>) -> VcxFuture<()> {
+    blocking(move || handle.rotate_did())
+}
+
+/// Async version of `Handle<Connections>::get_children`.
+/// Resolves to the handles of the Connections spawned by the multi-use invitation.
+pub fn get_children(handle: Handle<Connections>) -> VcxFuture<Vec<Handle<Connections>>> {
+    blocking(move || handle.get_children())
+}
+
+/// Async version of `Handle<Connections>::close_children`.
+pub fn close_children(handle: Handle<Connections>) -> VcxFuture<()> {
+    blocking(move || handle.close_children())
+}
+
+/// Async version of `Handle<Connections>::get_message_history`.
+pub fn get_message_history(handle: Handle<Connections>, query: HistoryQuery) -> VcxFuture<Vec<HistoryMessage>> {
+    blocking(move || handle.get_message_history(query))
+}
+
+/// Async version of `Handle<Connections>::store`.
+/// Resolves to the id of the wallet record.
+pub fn store(handle: Handle<Connections>, tags: Option<String>) -> VcxFuture<String> {
+    blocking(move || handle.store(tags))
+}
+
+/// Async version of `connection::load_stored_connection`.
+pub fn load_stored_connection(id: &str) -> VcxFuture<Handle<Connections>> {
+    let id = id.to_string();
+    blocking(move || connection::load_stored_connection(&id))
+}
+
+/// Async version of `connection::search_stored_connections`.
+pub fn search_stored_connections(query: Option<String>) -> VcxFuture<Vec<StoredConnection>> {
+    blocking(move || connection::search_stored_connections(query.as_deref()))
+}
+
+/// Async version of `connection::delete_stored_connection`.
+pub fn delete_stored_connection(id: &str) -> VcxFuture<()> {
+    let id = id.to_string();
+    blocking(move || connection::delete_stored_connection(&id))
+}
+
+/// Async version of `Handle<Connections>::send_discovery_features`.
+pub fn send_discovery_features(handle: Handle<Connections>, query: Option<String>, comment: Option<String>) -> VcxFuture<()> {
+    blocking(move || handle.send_discovery_features(query, comment))
+}
+
+/// Async version of `Handle<Connections>::send_reuse`.
+pub fn send_reuse(handle: Handle<Connections>, invitation: String) -> VcxFuture<()> {
+    blocking(move || handle.send_reuse(invitation))
+}
+
+/// Async version of `Handle<Connections>::send_answer`.
+pub fn send_answer(handle: Handle<Connections>, question: String, answer: String) -> VcxFuture<()> {
+    blocking(move || handle.send_answer(question, answer))
+}
+
+/// Async version of `Handle<Connections>::get_connection_info`.
+pub fn get_connection_info(handle: Handle<Connections>) -> VcxFuture<String> {
+    blocking(move || handle.get_connection_info())
+}
+
+/// Async version of `Handle<Connections>::redirect`.
+pub fn redirect(handle: Handle<Connections>, redirect_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.redirect(redirect_handle))
+}
+
+/// Async version of `Handle<Connections>::delete_connection`.
+pub fn delete_connection(handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.delete_connection())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::VcxStateType;
+    use crate::async_api::tests::block_on;
+    use crate::utils::constants::{GET_MESSAGES_INVITE_ACCEPTED_RESPONSE, DELETE_CONNECTION_RESPONSE};
+    use crate::utils::devsetup::SetupMocks;
+    use crate::utils::httpclient::AgencyMock;
+
+    #[test]
+    fn test_connection_async() {
+        let _setup = SetupMocks::init();
+
+        let handle = block_on(create_connection("test_connection_async")).unwrap();
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state());
+
+        block_on(connect(handle, Some("{}".to_string()))).unwrap();
+
+        AgencyMock::set_next_response(GET_MESSAGES_INVITE_ACCEPTED_RESPONSE);
+        assert_eq!(VcxStateType::VcxStateAccepted as u32, block_on(update_state(handle, None)).unwrap());
+
+        AgencyMock::set_next_response(DELETE_CONNECTION_RESPONSE);
+        assert_eq!(0, block_on(delete_connection(handle)).unwrap());
+        assert!(!handle.is_valid_handle());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::connection::Connections;
+use crate::credential::{self, Credentials};
+use crate::utils::object_cache::Handle;
+
+/// Async version of `credential::credential_create_with_offer`.
+pub fn create_with_offer(source_id: &str, offer: &str) -> VcxFuture<Handle<Credentials>> {
+    let source_id = source_id.to_string();
+    let offer = offer.to_string();
+    blocking(move || credential::credential_create_with_offer(&source_id, &offer))
+}
+
+/// Async version of `credential::credential_create_proposal`.
+pub fn create_proposal(source_id: &str, proposal: &str, comment: Option<String>) -> VcxFuture<Handle<Credentials>> {
+    let source_id = source_id.to_string();
+    let proposal = proposal.to_string();
+    blocking(move || credential::credential_create_proposal(&source_id, &proposal, comment))
+}
+
+/// Async version of `Handle<Credentials>::send_proposal`.
+pub fn send_proposal(handle: Handle<Credentials>, connection_handle: Handle<Connections>) -> VcxFuture<()> {
+    blocking(move || handle.send_proposal(connection_handle))
+}
+
+/// Async version of `credential::credential_create_with_msgid`.
+/// Resolves to the credential handle and the credential offer.
+pub fn create_with_msgid(source_id: &str, connection_handle: Handle<Connections>, msg_id: &str) -> VcxFuture<(Handle<Credentials>, String)> {
+    let source_id = source_id.to_string();
+    let msg_id = msg_id.to_string();
+    blocking(move || credential::credential_create_with_msgid(&source_id, connection_handle, &msg_id))
+}
+
+/// Async version of `credential::accept_credential_offer`.
+/// Resolves to the credential handle and its serialized state.
+pub fn accept_credential_offer(source_id: &str, offer: &str, connection_handle: Handle<Connections>) -> VcxFuture<(Handle<Credentials>, String)> {
+    let source_id = source_id.to_string();
+    let offer = offer.to_string();
+    blocking(move || credential::accept_credential_offer(&source_id, &offer, connection_handle))
+}
+
+/// Async version of `credential::get_credential_offer_messages`.
+pub fn get_credential_offer_messages(connection_handle: Handle<Connections>) -> VcxFuture<String> {
+    blocking(move || credential::get_credential_offer_messages(connection_handle))
+}
+
+/// Async version of `Handle<Credentials>::send_credential_request`.
+pub fn send_credential_request(handle: Handle<Credentials>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.send_credential_request(connection_handle))
+}
+
+/// Async version of `Handle<Credentials>::update_state`.
+/// Resolves to the new state of the credential.
+pub fn update_state(handle: Handle<Credentials>, message: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state(message))
+}
+
+/// Async version of `Handle<Credentials>::reject`.
+pub fn reject(handle: Handle<Credentials>, connection_handle: Handle<Connections>, comment: Option<String>) -> VcxFuture<()> {
+    blocking(move || handle.reject(connection_handle, comment))
+}
+
+/// Async version of `Handle<Credentials>::delete_credential`.
+pub fn delete_credential(handle: Handle<Credentials>) -> VcxFuture<()> {
+    blocking(move || handle.delete_credential())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::VcxStateType;
+    use crate::async_api::tests::block_on;
+    use crate::connection::tests::build_test_connection;
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_credential_async() {
+        let _setup = SetupMocks::init();
+
+        let proposal = json!({
+            "attributes": {"name": "Alice"},
+            "cred_def_id": "V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1"
+        }).to_string();
+
+        let handle = block_on(create_proposal("test_credential_async", &proposal, None)).unwrap();
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state().unwrap());
+
+        let offers = block_on(get_credential_offer_messages(build_test_connection())).unwrap();
+        let offers: serde_json::Value = serde_json::from_str(&offers).unwrap();
+        assert!(offers.as_array().map(|offers| !offers.is_empty()).unwrap_or(false));
+    }
+}
@@ -0,0 +1,57 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::credential_def::{self, CredentialDef};
+use crate::utils::object_cache::Handle;
+
+/// Async version of `credential_def::create_and_publish_credentialdef`.
+pub fn create_and_publish(source_id: String,
+                          name: String,
+                          issuer_did: String,
+                          schema_id: String,
+                          tag: String,
+                          revocation_details: String) -> VcxFuture<Handle<CredentialDef>> {
+    blocking(move || credential_def::create_and_publish_credentialdef(source_id, name, issuer_did, schema_id, tag, revocation_details))
+}
+
+/// Async version of `credential_def::create_credentialdef_from_id`.
+pub fn create_from_id(source_id: String, cred_def_id: String, issuer_did: String, revocation_config: Option<String>) -> VcxFuture<Handle<CredentialDef>> {
+    blocking(move || credential_def::create_credentialdef_from_id(source_id, cred_def_id, issuer_did, revocation_config))
+}
+
+/// Async version of `Handle<CredentialDef>::update_state`.
+pub fn update_state(handle: Handle<CredentialDef>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state())
+}
+
+/// Async version of `Handle<CredentialDef>::check_is_published`.
+pub fn check_is_published(handle: Handle<CredentialDef>) -> VcxFuture<bool> {
+    blocking(move || handle.check_is_published())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PublicEntityStateType;
+    use crate::async_api::tests::block_on;
+    use crate::utils::constants::{SCHEMA_ID, CRED_DEF_ID};
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_credential_def_async() {
+        let _setup = SetupMocks::init();
+
+        let handle = block_on(create_and_publish("test_credential_def_async".to_string(),
+                                                 "name".to_string(),
+                                                 "8XFh8yBzrpJQmNyZzgoTqB".to_string(),
+                                                 SCHEMA_ID.to_string(),
+                                                 "tag".to_string(),
+                                                 "{}".to_string())).unwrap();
+        assert_eq!(PublicEntityStateType::Published as u32, block_on(update_state(handle)).unwrap());
+        assert!(block_on(check_is_published(handle)).unwrap());
+
+        let handle = block_on(create_from_id("test_credential_def_async".to_string(),
+                                             CRED_DEF_ID.to_string(),
+                                             "8XFh8yBzrpJQmNyZzgoTqB".to_string(),
+                                             None)).unwrap();
+        assert_eq!(CRED_DEF_ID, handle.get_cred_def_id().unwrap());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::connection::Connections;
+use crate::disclosed_proof::{self, DisclosedProofs};
+use crate::utils::object_cache::Handle;
+
+/// Async version of `disclosed_proof::create_proof`.
+pub fn create(source_id: &str, proof_req: &str) -> VcxFuture<Handle<DisclosedProofs>> {
+    let source_id = source_id.to_string();
+    let proof_req = proof_req.to_string();
+    blocking(move || disclosed_proof::create_proof(&source_id, &proof_req))
+}
+
+/// Async version of `disclosed_proof::create_proof_with_msgid`.
+/// Resolves to the proof handle and the proof request.
+pub fn create_with_msgid(source_id: &str, connection_handle: Handle<Connections>, msg_id: &str) -> VcxFuture<(Handle<DisclosedProofs>, String)> {
+    let source_id = source_id.to_string();
+    let msg_id = msg_id.to_string();
+    blocking(move || disclosed_proof::create_proof_with_msgid(&source_id, connection_handle, &msg_id))
+}
+
+/// Async version of `disclosed_proof::create_proposal`.
+pub fn create_proposal(source_id: &str, proposal: String, comment: String) -> VcxFuture<Handle<DisclosedProofs>> {
+    let source_id = source_id.to_string();
+    blocking(move || disclosed_proof::create_proposal(&source_id, proposal, comment))
+}
+
+/// Async version of `disclosed_proof::get_proof_request_messages`.
+pub fn get_proof_request_messages(connection_handle: Handle<Connections>, match_name: Option<String>) -> VcxFuture<String> {
+    blocking(move || disclosed_proof::get_proof_request_messages(connection_handle, match_name.as_deref()))
+}
+
+/// Async version of `Handle<DisclosedProofs>::retrieve_credentials`.
+pub fn retrieve_credentials(handle: Handle<DisclosedProofs>) -> VcxFuture<String> {
+    blocking(move || handle.retrieve_credentials())
+}
+
+/// Async version of `Handle<DisclosedProofs>::generate_proof`.
+pub fn generate_proof(handle: Handle<DisclosedProofs>, credentials: String, self_attested_attrs: String) -> VcxFuture<u32> {
+    blocking(move || handle.generate_proof(credentials, self_attested_attrs))
+}
+
+/// Async version of `Handle<DisclosedProofs>::send_proof`.
+pub fn send_proof(handle: Handle<DisclosedProofs>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.send_proof(connection_handle))
+}
+
+/// Async version of `Handle<DisclosedProofs>::send_proposal`.
+pub fn send_proposal(handle: Handle<DisclosedProofs>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.send_proposal(connection_handle))
+}
+
+/// Async version of `Handle<DisclosedProofs>::reject_proof`.
+pub fn reject_proof(handle: Handle<DisclosedProofs>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.reject_proof(connection_handle))
+}
+
+/// Async version of `Handle<DisclosedProofs>::decline_presentation_request`.
+pub fn decline_presentation_request(handle: Handle<DisclosedProofs>,
+                                    connection_handle: Handle<Connections>,
+                                    reason: Option<String>,
+                                    proposal: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.decline_presentation_request(connection_handle, reason, proposal))
+}
+
+/// Async version of `Handle<DisclosedProofs>::update_state`.
+/// Resolves to the new state of the proof.
+pub fn update_state(handle: Handle<DisclosedProofs>, message: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::VcxStateType;
+    use crate::async_api::tests::block_on;
+    use crate::connection::tests::build_test_connection;
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_disclosed_proof_async() {
+        let _setup = SetupMocks::init();
+
+        let connection_handle = build_test_connection();
+
+        let requests = block_on(get_proof_request_messages(connection_handle, None)).unwrap();
+        let requests: serde_json::Value = serde_json::from_str(&requests).unwrap();
+
+        let handle = block_on(create("test_disclosed_proof_async", &requests[0].to_string())).unwrap();
+        assert_eq!(VcxStateType::VcxStateRequestReceived as u32, handle.get_state().unwrap());
+
+        assert_eq!(0, block_on(send_proof(handle, connection_handle)).unwrap());
+        assert_eq!(VcxStateType::VcxStateAccepted as u32, handle.get_state().unwrap());
+    }
+}
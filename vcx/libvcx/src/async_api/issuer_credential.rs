@@ -0,0 +1,101 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::connection::Connections;
+use crate::credential_def::CredentialDef;
+use crate::issuer_credential::{self, IssuerCredentials};
+use crate::utils::object_cache::Handle;
+
+/// Async version of `issuer_credential::issuer_credential_create`.
+pub fn create(cred_def_handle: Handle<CredentialDef>,
+              source_id: String,
+              issuer_did: String,
+              credential_name: String,
+              credential_data: String,
+              price: u64) -> VcxFuture<Handle<IssuerCredentials>> {
+    blocking(move || issuer_credential::issuer_credential_create(cred_def_handle, source_id, issuer_did, credential_name, credential_data, price))
+}
+
+/// Async version of `issuer_credential::issuer_credential_create_from_proposal`.
+pub fn create_from_proposal(cred_def_handle: Handle<CredentialDef>,
+                            source_id: String,
+                            proposal: String,
+                            credential_data: Option<String>,
+                            credential_name: String) -> VcxFuture<Handle<IssuerCredentials>> {
+    blocking(move || issuer_credential::issuer_credential_create_from_proposal(cred_def_handle, source_id, &proposal, credential_data, credential_name))
+}
+
+/// Async version of `Handle<IssuerCredentials>::send_credential_offer`.
+pub fn send_credential_offer(handle: Handle<IssuerCredentials>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.send_credential_offer(connection_handle))
+}
+
+/// Async version of `Handle<IssuerCredentials>::update_state`.
+/// Resolves to the new state of the credential.
+pub fn update_state(handle: Handle<IssuerCredentials>, message: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state(message))
+}
+
+/// Async version of `Handle<IssuerCredentials>::send_credential`.
+pub fn send_credential(handle: Handle<IssuerCredentials>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.send_credential(connection_handle))
+}
+
+/// Async version of `Handle<IssuerCredentials>::revoke_credential`.
+pub fn revoke_credential(handle: Handle<IssuerCredentials>) -> VcxFuture<()> {
+    blocking(move || handle.revoke_credential())
+}
+
+/// Async version of `Handle<IssuerCredentials>::revoke_credential_local`.
+pub fn revoke_credential_local(handle: Handle<IssuerCredentials>) -> VcxFuture<()> {
+    blocking(move || handle.revoke_credential_local())
+}
+
+/// Async version of `Handle<IssuerCredentials>::is_revoked`.
+pub fn is_revoked(handle: Handle<IssuerCredentials>) -> VcxFuture<bool> {
+    blocking(move || handle.is_revoked())
+}
+
+/// Async version of `Handle<IssuerCredentials>::send_revocation_notification`.
+pub fn send_revocation_notification(handle: Handle<IssuerCredentials>, options: Option<String>) -> VcxFuture<()> {
+    blocking(move || handle.send_revocation_notification(options.as_deref()))
+}
+
+/// Async version of `issuer_credential::get_pending_revocations`.
+pub fn get_pending_revocations() -> VcxFuture<String> {
+    blocking(issuer_credential::get_pending_revocations)
+}
+
+/// Async version of `issuer_credential::publish_pending_revocations`.
+/// Resolves to JSON array of ids of the registries which revocations were published.
+pub fn publish_pending_revocations(rev_reg_id: Option<String>) -> VcxFuture<String> {
+    blocking(move || issuer_credential::publish_pending_revocations(rev_reg_id.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::VcxStateType;
+    use crate::async_api::tests::block_on;
+    use crate::connection::tests::build_test_connection;
+    use crate::credential_def::tests::create_cred_def_fake;
+    use crate::utils::constants::CREDENTIAL_REQ_RESPONSE_STR;
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_issuer_credential_async() {
+        let _setup = SetupMocks::init();
+
+        let handle = block_on(create(create_cred_def_fake(),
+                                     "test_issuer_credential_async".to_string(),
+                                     "8XFh8yBzrpJQmNyZzgoTqB".to_string(),
+                                     "credential_name".to_string(),
+                                     "{\"attr\":\"value\"}".to_string(),
+                                     1)).unwrap();
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state().unwrap());
+
+        assert_eq!(0, block_on(send_credential_offer(handle, build_test_connection())).unwrap());
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, handle.get_state().unwrap());
+
+        assert_eq!(VcxStateType::VcxStateRequestReceived as u32,
+                   block_on(update_state(handle, Some(CREDENTIAL_REQ_RESPONSE_STR.to_string()))).unwrap());
+    }
+}
@@ -0,0 +1,201 @@
+pub mod connection;
+pub mod issuer_credential;
+pub mod credential;
+pub mod proof;
+pub mod disclosed_proof;
+pub mod schema;
+pub mod credential_def;
+pub mod wallet;
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::error::prelude::*;
+
+/*
+    Async Rust API over the handle-based objects.
+
+    Every function returns `VcxFuture` resolving to the result of the matching blocking function.
+    The blocking work (Agency requests, ledger and wallet calls) is executed by the dedicated pool of
+    `EXECUTOR_THREADS` threads (independent of `threadpool_size` config option), so the futures can be awaited
+    from any executor (e.g. tokio) without blocking its threads. Operations exceeding the pool wait in the queue.
+    Functions which only read the object state (`get_state`, `get_source_id`, `to_string` and others) do not block
+    and should be called on the handles directly.
+*/
+
+const EXECUTOR_THREADS: usize = 8;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+lazy_static! {
+    static ref EXECUTOR: Mutex<Sender<Job>> = Mutex::new(start_executor());
+}
+
+fn start_executor() -> Sender<Job> {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    for index in 0..EXECUTOR_THREADS {
+        let receiver = receiver.clone();
+
+        let spawned = thread::Builder::new()
+            .name(format!("vcx-async-{}", index))
+            .spawn(move || loop {
+                let job = match receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return
+                };
+
+                match job {
+                    Ok(job) => job(),
+                    Err(_) => return
+                }
+            });
+
+        if let Err(err) = spawned {
+            error!("Cannot start async API executor thread. Err: {:?}", err);
+        }
+    }
+
+    sender
+}
+
+/// Future resolving to the result of the operation executed by the async API executor.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct VcxFuture<T> {
+    state: Arc<Mutex<FutureState<T>>>,
+}
+
+struct FutureState<T> {
+    result: Option<VcxResult<T>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Future for VcxFuture<T> {
+    type Output = VcxResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<VcxResult<T>> {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return Poll::Ready(Err(VcxError::from_msg(VcxErrorKind::InternalError, "Cannot get result of async operation: lock is poisoned")))
+        };
+
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Runs blocking `operation` by the async API executor and returns the future resolving to its result.
+pub(crate) fn blocking<T, F>(operation: F) -> VcxFuture<T>
+    where T: Send + 'static,
+          F: FnOnce() -> VcxResult<T> + Send + 'static {
+    let state = Arc::new(Mutex::new(FutureState { result: None, waker: None }));
+
+    let future = VcxFuture { state: state.clone() };
+
+    let job: Job = Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(operation))
+            .unwrap_or_else(|_| Err(VcxError::from_msg(VcxErrorKind::InternalError, "Async operation panicked")));
+
+        complete(&state, result);
+    });
+
+    let sent = EXECUTOR.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot get async API executor: lock is poisoned"))
+        .and_then(|executor| executor.send(job)
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Async API executor is stopped")));
+
+    if let Err(err) = sent {
+        complete(&future.state, Err(err));
+    }
+
+    future
+}
+
+fn complete<T>(state: &Mutex<FutureState<T>>, result: VcxResult<T>) {
+    let waker = match state.lock() {
+        Ok(mut state) => {
+            state.result = Some(result);
+            state.waker.take()
+        }
+        Err(_) => {
+            error!("Cannot set result of async operation: lock is poisoned");
+            None
+        }
+    };
+
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::thread::Thread;
+    use std::task::Wake;
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Minimal executor driving a single future on the current thread.
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park()
+            }
+        }
+    }
+
+    #[test]
+    fn test_blocking_resolves_to_operation_result() {
+        assert_eq!(42, block_on(blocking(|| Ok(42))).unwrap());
+
+        let err = block_on(blocking(|| -> VcxResult<()> { Err(VcxError::from(VcxErrorKind::InvalidHandle)) })).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidHandle, err.kind());
+    }
+
+    #[test]
+    fn test_blocking_reports_panic_as_error() {
+        let err = block_on(blocking(|| -> VcxResult<()> { panic!("operation failed") })).unwrap_err();
+        assert_eq!(VcxErrorKind::InternalError, err.kind());
+
+        // the executor thread survives the panic
+        assert_eq!(42, block_on(blocking(|| Ok(42))).unwrap());
+    }
+
+    #[test]
+    fn test_blocking_runs_on_bounded_executor() {
+        let futures: Vec<VcxFuture<String>> = (0..EXECUTOR_THREADS * 4)
+            .map(|_| blocking(|| Ok(thread::current().name().unwrap_or_default().to_string())))
+            .collect();
+
+        let threads: HashSet<String> = futures.into_iter()
+            .map(|future| block_on(future).unwrap())
+            .collect();
+
+        assert!(threads.len() <= EXECUTOR_THREADS);
+        assert!(threads.iter().all(|name| name.starts_with("vcx-async-")));
+    }
+}
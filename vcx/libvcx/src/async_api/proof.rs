@@ -0,0 +1,69 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::connection::Connections;
+use crate::proof::{self, Proofs};
+use crate::utils::object_cache::Handle;
+
+/// Async version of `proof::create_proof`.
+pub fn create(source_id: String,
+              requested_attrs: String,
+              requested_predicates: String,
+              revocation_details: String,
+              name: String) -> VcxFuture<Handle<Proofs>> {
+    blocking(move || proof::create_proof(source_id, requested_attrs, requested_predicates, revocation_details, name))
+}
+
+/// Async version of `proof::create_proof_with_proposal`.
+pub fn create_with_proposal(source_id: String, name: String, presentation_proposal: String) -> VcxFuture<Handle<Proofs>> {
+    blocking(move || proof::create_proof_with_proposal(source_id, name, presentation_proposal))
+}
+
+/// Async version of `Handle<Proofs>::send_proof_request`.
+pub fn send_proof_request(handle: Handle<Proofs>, connection_handle: Handle<Connections>) -> VcxFuture<u32> {
+    blocking(move || handle.send_proof_request(connection_handle))
+}
+
+/// Async version of `Handle<Proofs>::request_proof`.
+pub fn request_proof(handle: Handle<Proofs>,
+                     connection_handle: Handle<Connections>,
+                     requested_attrs: String,
+                     requested_predicates: String,
+                     revocation_details: String,
+                     name: String) -> VcxFuture<u32> {
+    blocking(move || handle.request_proof(connection_handle, requested_attrs, requested_predicates, revocation_details, name))
+}
+
+/// Async version of `Handle<Proofs>::update_state`.
+/// Resolves to the new state of the proof.
+pub fn update_state(handle: Handle<Proofs>, message: Option<String>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::VcxStateType;
+    use crate::async_api::tests::block_on;
+    use crate::connection::tests::build_test_connection;
+    use crate::utils::constants::{DID, VERKEY, REQUESTED_ATTRS, REQUESTED_PREDICATES};
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_proof_async() {
+        let _setup = SetupMocks::init();
+
+        let connection_handle = build_test_connection();
+        connection_handle.set_agent_verkey(VERKEY).unwrap();
+        connection_handle.set_agent_did(DID).unwrap();
+        connection_handle.set_their_pw_verkey(VERKEY).unwrap();
+
+        let handle = block_on(create("test_proof_async".to_string(),
+                                     REQUESTED_ATTRS.to_string(),
+                                     REQUESTED_PREDICATES.to_string(),
+                                     r#"{"support_revocation":false}"#.to_string(),
+                                     "Optional".to_string())).unwrap();
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state().unwrap());
+
+        assert_eq!(0, block_on(send_proof_request(handle, connection_handle)).unwrap());
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, handle.get_state().unwrap());
+    }
+}
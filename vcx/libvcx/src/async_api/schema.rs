@@ -0,0 +1,40 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::schema::{self, CreateSchema};
+use crate::utils::object_cache::Handle;
+
+/// Async version of `schema::create_and_publish_schema`.
+pub fn create_and_publish(source_id: &str, issuer_did: String, name: String, version: String, data: String) -> VcxFuture<Handle<CreateSchema>> {
+    let source_id = source_id.to_string();
+    blocking(move || schema::create_and_publish_schema(&source_id, issuer_did, name, version, data))
+}
+
+/// Async version of `schema::get_schema_attrs`.
+/// Resolves to the schema handle and the schema JSON.
+pub fn get_schema_attrs(source_id: String, schema_id: String) -> VcxFuture<(Handle<CreateSchema>, String)> {
+    blocking(move || schema::get_schema_attrs(source_id, schema_id))
+}
+
+/// Async version of `Handle<CreateSchema>::update_state`.
+pub fn update_state(handle: Handle<CreateSchema>) -> VcxFuture<u32> {
+    blocking(move || handle.update_state())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::PublicEntityStateType;
+    use crate::async_api::tests::block_on;
+    use crate::schema::tests::prepare_schema_data;
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_schema_async() {
+        let _setup = SetupMocks::init();
+
+        let (did, name, version, data) = prepare_schema_data();
+        let handle = block_on(create_and_publish("test_schema_async", did, name, version, data)).unwrap();
+        assert!(handle.get_schema_id().is_ok());
+
+        assert_eq!(PublicEntityStateType::Published as u32, block_on(update_state(handle)).unwrap());
+    }
+}
@@ -0,0 +1,94 @@
+use crate::async_api::{blocking, VcxFuture};
+use crate::utils::libindy::wallet;
+
+/// Async version of `wallet::add_record`.
+pub fn add_record(type_: &str, id: &str, value: &str, tags: Option<&str>) -> VcxFuture<()> {
+    let (type_, id, value, tags) = (type_.to_string(), id.to_string(), value.to_string(), tags.map(String::from));
+    blocking(move || wallet::add_record(&type_, &id, &value, tags.as_deref()))
+}
+
+/// Async version of `wallet::get_record`.
+pub fn get_record(type_: &str, id: &str, options: &str) -> VcxFuture<String> {
+    let (type_, id, options) = (type_.to_string(), id.to_string(), options.to_string());
+    blocking(move || wallet::get_record(&type_, &id, &options))
+}
+
+/// Async version of `wallet::update_record_value`.
+pub fn update_record_value(type_: &str, id: &str, value: &str) -> VcxFuture<()> {
+    let (type_, id, value) = (type_.to_string(), id.to_string(), value.to_string());
+    blocking(move || wallet::update_record_value(&type_, &id, &value))
+}
+
+/// Async version of `wallet::delete_record`.
+pub fn delete_record(type_: &str, id: &str) -> VcxFuture<()> {
+    let (type_, id) = (type_.to_string(), id.to_string());
+    blocking(move || wallet::delete_record(&type_, &id))
+}
+
+/// Async version of `wallet::add_record_tags`.
+pub fn add_record_tags(type_: &str, id: &str, tags: &str) -> VcxFuture<()> {
+    let (type_, id, tags) = (type_.to_string(), id.to_string(), tags.to_string());
+    blocking(move || wallet::add_record_tags(&type_, &id, &tags))
+}
+
+/// Async version of `wallet::update_record_tags`.
+pub fn update_record_tags(type_: &str, id: &str, tags: &str) -> VcxFuture<()> {
+    let (type_, id, tags) = (type_.to_string(), id.to_string(), tags.to_string());
+    blocking(move || wallet::update_record_tags(&type_, &id, &tags))
+}
+
+/// Async version of `wallet::delete_record_tags`.
+pub fn delete_record_tags(type_: &str, id: &str, tags: &str) -> VcxFuture<()> {
+    let (type_, id, tags) = (type_.to_string(), id.to_string(), tags.to_string());
+    blocking(move || wallet::delete_record_tags(&type_, &id, &tags))
+}
+
+/// Async version of `wallet::open_search`.
+pub fn open_search(type_: &str, query: &str, options: &str) -> VcxFuture<i32> {
+    let (type_, query, options) = (type_.to_string(), query.to_string(), options.to_string());
+    blocking(move || wallet::open_search(&type_, &query, &options))
+}
+
+/// Async version of `wallet::search_next_records`.
+pub fn search_next_records(search_handle: i32, count: usize) -> VcxFuture<String> {
+    blocking(move || wallet::search_next_records(search_handle, count))
+}
+
+/// Async version of `wallet::close_search`.
+pub fn close_search(search_handle: i32) -> VcxFuture<()> {
+    blocking(move || wallet::close_search(search_handle))
+}
+
+/// Async version of `wallet::export` for the opened wallet.
+pub fn export(path: &str, backup_key: &str) -> VcxFuture<()> {
+    let (path, backup_key) = (path.to_string(), backup_key.to_string());
+    blocking(move || wallet::export(wallet::get_wallet_handle(), &path, &backup_key))
+}
+
+/// Async version of `wallet::import`.
+pub fn import(config: &str) -> VcxFuture<()> {
+    let config = config.to_string();
+    blocking(move || wallet::import(&config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_api::tests::block_on;
+    use crate::error::VcxErrorKind;
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    #[test]
+    fn test_wallet_records_async() {
+        let _setup = SetupLibraryWallet::init();
+
+        block_on(add_record("async_type", "async_id", "value", None)).unwrap();
+        block_on(update_record_value("async_type", "async_id", "new value")).unwrap();
+
+        let record = block_on(get_record("async_type", "async_id", "{}")).unwrap();
+        assert!(record.contains("new value"));
+
+        block_on(delete_record("async_type", "async_id")).unwrap();
+        assert_eq!(VcxErrorKind::WalletRecordNotFound, block_on(get_record("async_type", "async_id", "{}")).unwrap_err().kind());
+    }
+}
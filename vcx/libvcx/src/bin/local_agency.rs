@@ -0,0 +1,52 @@
+//! Local agency serving the agency message set with in-memory storage.
+//! Used to run `agency` tests without the remote Agency:
+//!
+//!     cargo run --features local_agency --bin local_agency -- [address] [seed]
+//!     cargo test --features "agency pool_tests local_agency"
+//!
+//! `address` defaults to `127.0.0.1:8080`, `seed` to `local_agency::DEFAULT_AGENCY_SEED`.
+
+use std::process;
+use std::thread;
+
+use vcx::agent::local_agency::{self, server};
+use vcx::error::VcxResult;
+use vcx::settings;
+use vcx::utils::libindy::wallet;
+use vcx::utils::logger::LibvcxDefaultLogger;
+
+const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+const WALLET_NAME: &str = "local_agency_wallet";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let seed = args.next().unwrap_or_else(|| local_agency::DEFAULT_AGENCY_SEED.to_string());
+
+    if let Err(err) = run(&address, &seed) {
+        eprintln!("Cannot start local agency: {}", err);
+        process::exit(1);
+    }
+
+    loop {
+        thread::park();
+    }
+}
+
+fn run(address: &str, seed: &str) -> VcxResult<()> {
+    LibvcxDefaultLogger::init(Some(String::from("info")))?;
+    settings::set_defaults();
+
+    // the agency state is not persisted so keys left from the previous run are useless
+    wallet::delete_wallet(WALLET_NAME, None, None, None).ok();
+    wallet::init_wallet(WALLET_NAME, None, None, None)?;
+
+    let (did, verkey) = local_agency::init(Some(seed))?;
+    let address = server::start(address)?;
+
+    println!("Local agency is listening on http://{}", address);
+    println!("Agency DID: {}", did);
+    println!("Agency verkey: {}", verkey);
+
+    Ok(())
+}
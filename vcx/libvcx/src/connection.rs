@@ -19,11 +19,19 @@ use crate::utils::libindy::crypto::create_and_store_my_did;
 use crate::utils::libindy::crypto;
 use crate::utils::json::mapped_key_rewrite;
 use crate::settings::protocol::ProtocolTypes;
+use crate::settings::delivery::DeliveryPolicy;
 use crate::aries::handlers::connection::Connection as ConnectionV3;
 use crate::aries::handlers::connection::agent::AgentInfo;
+use crate::aries::utils::encryption_envelope::EnvelopeVersion;
+use crate::aries::messages::transport::ReturnRoute;
+use crate::aries::messages::outofband::HandshakeProtocol;
+use crate::aries::messages::connection::did_peer::PeerDidNumalgo;
 use crate::aries::messages::connection::invite::Invitation as InvitationV3;
+use crate::aries::messages::connection::did_doc::DidDoc;
 use crate::aries::messages::a2a::A2AMessage;
 use crate::aries::handlers::connection::types::CompletedConnection;
+use crate::aries::utils::message_history::{self, HistoryMessage, HistoryQuery};
+use crate::aries::utils::connection_storage::{self, StoredConnection};
 use crate::aries::messages::invite_action::invite::{Invite as InviteForAction, InviteActionData};
 use crate::aries::messages::committedanswer::question::{QuestionResponse, Question};
 use crate::aries::messages::committedanswer::answer::Answer;
@@ -33,7 +41,7 @@ lazy_static! {
     static ref CONNECTION_MAP: ObjectCache<Connections> = Default::default();
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "version")]
 pub enum Connections {
     #[serde(rename = "1.0")]
@@ -51,6 +59,24 @@ pub struct ConnectionOptions {
     pub use_public_did: Option<bool>,
     pub update_agent_info: Option<bool>,
     pub pairwise_agent_info: Option<AgentInfo>,
+    #[serde(default)]
+    pub envelope_version: Option<EnvelopeVersion>,
+    #[serde(default)]
+    pub return_route: Option<ReturnRoute>,
+    #[serde(default)]
+    pub handshake_protocols: Option<Vec<HandshakeProtocol>>,
+    #[serde(default)]
+    pub peer_did: Option<PeerDidNumalgo>,
+    #[serde(default)]
+    pub public_did_service: Option<bool>,
+    #[serde(default)]
+    pub multi_use: Option<bool>,
+    #[serde(default)]
+    pub invitation_ttl: Option<u64>,
+    #[serde(default)]
+    pub handshake_timeout: Option<u64>,
+    #[serde(default)]
+    pub delivery_policy: Option<DeliveryPolicy>,
 }
 
 impl Default for ConnectionOptions {
@@ -61,6 +87,15 @@ impl Default for ConnectionOptions {
             use_public_did: None,
             update_agent_info: Some(true),
             pairwise_agent_info: None,
+            envelope_version: None,
+            return_route: None,
+            handshake_protocols: None,
+            peer_did: None,
+            public_did_service: None,
+            multi_use: None,
+            invitation_ttl: None,
+            handshake_timeout: None,
+            delivery_policy: None,
         }
     }
 }
@@ -72,12 +107,62 @@ impl ConnectionOptions {
                 None => ConnectionOptions::default(),
                 Some("") => ConnectionOptions::default(),
                 Some(opt) => {
-                    serde_json::from_str(opt)
-                        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize ConnectionOptions: {}", err)))?
+                    let options: ConnectionOptions = serde_json::from_str(opt)
+                        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize ConnectionOptions: {}", err)))?;
+                    if let Some(ref delivery_policy) = options.delivery_policy {
+                        delivery_policy.validate()?;
+                    }
+                    options
                 }
             }
         )
     }
+
+    /// Envelope version requested for the connection or the one set in the library config.
+    pub fn envelope_version(&self) -> VcxResult<EnvelopeVersion> {
+        match self.envelope_version {
+            Some(version) => Ok(version),
+            None => EnvelopeVersion::from_config()
+        }
+    }
+
+    /// Handshake protocols to offer in Out-of-Band Invitation in order of preference (`connections` by default).
+    pub fn handshake_protocols(&self) -> Vec<HandshakeProtocol> {
+        self.handshake_protocols.clone()
+            .filter(|protocols| !protocols.is_empty())
+            .unwrap_or(vec![HandshakeProtocol::Connections])
+    }
+
+    /// Whether the invitation can be accepted by many invitees (single-use by default).
+    pub fn multi_use(&self) -> bool {
+        self.multi_use.unwrap_or(false)
+    }
+}
+
+/// Options of a message sent over an Aries connection with `send_generic_message`.
+/// Fields of proprietary `SendMessageOptions` are ignored.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GenericMessageOptions {
+    /// HTTP delivery policy used for this message instead of the connection or library one.
+    #[serde(default)]
+    pub delivery_policy: Option<DeliveryPolicy>,
+}
+
+impl GenericMessageOptions {
+    pub fn from_str(options: &str) -> VcxResult<GenericMessageOptions> {
+        if options.trim().is_empty() {
+            return Ok(GenericMessageOptions::default());
+        }
+
+        let options: GenericMessageOptions = serde_json::from_str(options)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize message options: {}", err)))?;
+
+        if let Some(ref delivery_policy) = options.delivery_policy {
+            delivery_policy.validate()?;
+        }
+
+        Ok(options)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -604,6 +689,81 @@ impl Connection {
     }
 }
 
+/// Returns the identifier of the Aries Connection using the pairwise key `recipient_vk` (see `AgentInfo::connection_id`).
+pub fn connection_id_by_recipient_key(recipient_vk: &str) -> VcxResult<Option<String>> {
+    let mut connection_id = None;
+
+    CONNECTION_MAP.filter(|connection| {
+        if let Connections::V3(connection) = connection {
+            if let Some(agent_info) = connection.agent_for_recipient_key(recipient_vk) {
+                connection_id = Some(agent_info.connection_id().to_string());
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    })?;
+
+    Ok(connection_id)
+}
+
+/// Returns the current state of the completed Aries Connection identified by `connection_id` (see `AgentInfo::connection_id`).
+/// Protocols use it to refresh their copies of the Connection after the DID rotation.
+/// Returns `None` if the Connection is not loaded or is being updated at the moment (it can be the caller).
+pub fn completed_connection_by_id(connection_id: &str) -> VcxResult<Option<CompletedConnection>> {
+    if connection_id.is_empty() { return Ok(None); }
+
+    let mut completed_connection = None;
+
+    CONNECTION_MAP.filter_unlocked(|connection| {
+        if let Connections::V3(connection) = connection {
+            if connection.agent_info().connection_id() == connection_id {
+                if let Ok(connection) = connection.get_completed_connection() {
+                    completed_connection = Some(connection);
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    })?;
+
+    Ok(completed_connection)
+}
+
+/// Passes a message received through the inbound endpoint to the Aries Connection objects.
+/// Returns the handle of the object that consumed the message.
+pub fn handle_inbound_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<Handle<Connections>>> {
+    let mut child = None;
+    let mut prev = None;
+
+    let handle = CONNECTION_MAP.find_mut(
+        |connection| match connection {
+            Connections::V1(_) => false,
+            Connections::V3(connection) => connection.accepts_inbound_message(recipient_vk, message)
+        },
+        |connection| match connection {
+            Connections::V1(_) => Ok(()),
+            Connections::V3(connection) if connection.is_multi_use() => {
+                prev = Some(storage_snapshot(connection));
+                child = connection.accept_inbound_request(recipient_vk, message)?;
+                Ok(())
+            }
+            Connections::V3(connection) => {
+                prev = Some(storage_snapshot(connection));
+                connection.handle_inbound_message(recipient_vk, message).map(|_| ())
+            }
+        },
+    )?;
+
+    if let Some(handle) = handle {
+        handle.refresh_stored(prev);
+    }
+
+    match child {
+        Some(child) => store_connection(Connections::V3(child)).map(Some),
+        None => Ok(handle)
+    }
+}
+
 fn handle_err(err: VcxError) -> VcxError {
     if err.kind() == VcxErrorKind::InvalidHandle {
         VcxError::from(VcxErrorKind::InvalidConnectionHandle)
@@ -835,7 +995,9 @@ impl Handle<Connections> {
     }
 
     pub fn update_state_with_message(self, message: String) -> VcxResult<u32> {
-        CONNECTION_MAP.get_mut(self, |connection| {
+        let prev = self.storage_snapshot();
+
+        let (state, children) = CONNECTION_MAP.get_mut(self, |connection| {
             match connection {
                 Connections::V1(connection) => {
                     let message: Message = ::serde_json::from_str(&message)
@@ -844,21 +1006,31 @@ impl Handle<Connections> {
 
                     if message.status_code == MessageStatusCode::Redirected && message.msg_type == RemoteMessageType::ConnReqRedirect {
                         connection.process_redirect_message(&message)?;
-                        Ok(connection.get_state())
                     } else {
                         connection.process_acceptance_message(&message)?;
-                        Ok(connection.get_state())
                     }
+                    Ok((connection.get_state(), Vec::new()))
+                }
+                Connections::V3(connection) if connection.is_multi_use() => {
+                    let children = connection.accept_requests(Some(&message))?;
+                    Ok((connection.state(), children))
                 }
                 Connections::V3(connection) => {
-                    connection.update_state(Some(&message))
+                    Ok((connection.update_state(Some(&message))?, Vec::new()))
                 }
             }
-        }).map_err(handle_err)
+        }).map_err(handle_err)?;
+
+        store_children(children)?;
+        release_retired_agents(self);
+        self.refresh_stored(prev);
+        Ok(state)
     }
 
     pub fn delete_connection(self) -> VcxResult<u32> {
-        CONNECTION_MAP.get_mut(self, |connection| {
+        let stored = self.storage_snapshot();
+
+        let rc = CONNECTION_MAP.get_mut(self, |connection| {
             match connection {
                 Connections::V1(connection) => {
                     connection.delete_connection()
@@ -872,11 +1044,22 @@ impl Handle<Connections> {
             .map(|_| error::SUCCESS.code_num)
             .map_err(handle_err)
             .and(self.release())
-            .and_then(|_| Ok(error::SUCCESS.code_num))
+            .and_then(|_| Ok(error::SUCCESS.code_num))?;
+
+        // the record of the deleted Connection cannot be used anymore
+        if let Some((_, agent_info, _)) = stored {
+            if let Err(err) = connection_storage::delete_if_stored(agent_info.connection_id()) {
+                warn!("Unable to delete stored Connection. Err: {:?}", err);
+            }
+        }
+
+        Ok(rc)
     }
 
     pub fn connect(self, options: Option<String>) -> VcxResult<u32> {
-        CONNECTION_MAP.get_mut(self, |connection| {
+        let prev = self.storage_snapshot();
+
+        let rc = CONNECTION_MAP.get_mut(self, |connection| {
             match connection {
                 Connections::V1(connection) => {
                     let options_obj: ConnectionOptions = ConnectionOptions::from_opt_str(options.as_ref())?;
@@ -894,7 +1077,10 @@ impl Handle<Connections> {
                     Ok(error::SUCCESS.code_num)
                 }
             }
-        }).map_err(handle_err)
+        }).map_err(handle_err)?;
+
+        self.refresh_stored(prev);
+        Ok(rc)
     }
 
     pub fn redirect(self, redirect_handle: Self) -> VcxResult<u32> {
@@ -977,16 +1163,120 @@ impl Handle<Connections> {
     }
 
     pub fn update_state(self, message: Option<String>) -> VcxResult<u32> {
-        CONNECTION_MAP.get_mut(self, |connection| {
+        let prev = self.storage_snapshot();
+
+        let (state, children) = CONNECTION_MAP.get_mut(self, |connection| {
             match connection {
                 Connections::V1(connection) => {
-                    connection.update_state(message.clone())
+                    Ok((connection.update_state(message.clone())?, Vec::new()))
+                }
+                Connections::V3(connection) if connection.is_multi_use() => {
+                    let children = connection.accept_requests(message.as_ref().map(String::as_str))?;
+                    Ok((connection.state(), children))
                 }
                 Connections::V3(connection) => {
-                    connection.update_state(message.as_ref().map(String::as_str))
+                    Ok((connection.update_state(message.as_ref().map(String::as_str))?, Vec::new()))
                 }
             }
-        }).map_err(handle_err)
+        }).map_err(handle_err)?;
+
+        store_children(children)?;
+        release_retired_agents(self);
+        self.refresh_stored(prev);
+        Ok(state)
+    }
+
+    /// Connections spawned by the multi-use invitation for the received requests.
+    pub fn get_children(self) -> VcxResult<Vec<Handle<Connections>>> {
+        let invitation_id = CONNECTION_MAP.get(self, |connection| {
+            match connection {
+                Connections::V1(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Connection type doesn't support this action: `get_children`.")),
+                Connections::V3(connection) if connection.is_multi_use() => {
+                    connection.get_invitation()
+                        .map(|invitation| invitation.id())
+                        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "Multi-use Connection does not contain invitation"))
+                }
+                Connections::V3(_) => Err(VcxError::from_msg(VcxErrorKind::InvalidState, "Connection is not an inviter side of multi-use invitation"))
+            }
+        }).map_err(handle_err)?;
+
+        CONNECTION_MAP.filter(|connection| {
+            match connection {
+                Connections::V1(_) => Ok(false),
+                Connections::V3(connection) => Ok(connection.is_spawned_by(&invitation_id))
+            }
+        })
+    }
+
+    /// Deletes all Connections spawned by the multi-use invitation.
+    /// The invitation itself keeps accepting new requests until it is deleted.
+    pub fn close_children(self) -> VcxResult<()> {
+        for child in self.get_children()? {
+            child.delete_connection()?;
+        }
+        Ok(())
+    }
+
+    /// Stores the Connection in the wallet (see `connection_storage`) or updates its record if it is already stored.
+    /// `tags` - JSON object of user-defined string tags, stored user-defined tags are kept if not set.
+    /// Returns id of the record.
+    pub fn store(self, tags: Option<String>) -> VcxResult<String> {
+        let user_tags = connection_storage::parse_user_tags(tags.as_deref())?;
+
+        let (connection_id, storage_tags) = CONNECTION_MAP.get(self, |connection| {
+            match connection {
+                Connections::V1(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Connection type doesn't support this action: `store`.")),
+                Connections::V3(connection) => Ok((connection.agent_info().connection_id().to_string(), connection.storage_tags())),
+            }
+        }).map_err(handle_err)?;
+
+        connection_storage::store(&connection_id, &self.to_string()?, storage_tags, user_tags)
+    }
+
+    /// State, pairwise agent and remote DIDDoc of Aries Connection the wallet record is kept in sync with.
+    fn storage_snapshot(self) -> Option<StorageSnapshot> {
+        CONNECTION_MAP.get(self, |connection| {
+            match connection {
+                Connections::V1(_) => Ok(None),
+                Connections::V3(connection) => Ok(Some(storage_snapshot(connection)))
+            }
+        }).unwrap_or(None)
+    }
+
+    /// Updates the wallet record of the stored Connection if its state, pairwise agent or remote DIDDoc changed since `prev` snapshot.
+    fn refresh_stored(self, prev: Option<StorageSnapshot>) {
+        let connection_id = match (prev, self.storage_snapshot()) {
+            (Some(prev), Some(current)) if prev != current => current.1.connection_id().to_string(),
+            _ => return
+        };
+
+        let result = CONNECTION_MAP.get(self, |connection| {
+            match connection {
+                Connections::V1(_) => Ok(None),
+                Connections::V3(connection) => Ok(Some(connection.storage_tags()))
+            }
+        }).and_then(|storage_tags| {
+            match storage_tags {
+                Some(storage_tags) => connection_storage::refresh(&connection_id, &self.to_string()?, storage_tags).map(|_| ()),
+                None => Ok(())
+            }
+        });
+
+        if let Err(err) = result {
+            warn!("Unable to update stored Connection. Err: {:?}", err);
+        }
+    }
+
+    /// Message history records of the Connection (see `message_history` setting).
+    pub fn get_message_history(self, query: HistoryQuery) -> VcxResult<Vec<HistoryMessage>> {
+        let query = CONNECTION_MAP.get(self, |connection| {
+            match connection {
+                Connections::V1(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Connection type doesn't support this action: `get_message_history`.")),
+                Connections::V3(connection) => Ok(query.set_connection(connection.agent_info().connection_id().to_string())),
+            }
+        }).map_err(handle_err)?;
+
+        message_history::query(&query)
     }
 
     pub fn get_messages(self) -> VcxResult<HashMap<String, A2AMessage>> {
@@ -1025,6 +1315,43 @@ impl Handle<Connections> {
         }).map_err(handle_err)
     }
 
+    pub fn request_mediation(self) -> VcxResult<()> {
+        CONNECTION_MAP.get_mut(self, |connection| {
+            match connection {
+                Connections::V1(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Connection type doesn't support this action: `request_mediation`.")),
+                Connections::V3(connection) => connection.request_mediation()
+            }
+        }).map_err(handle_err)
+    }
+
+    pub fn rotate_did(self) -> VcxResult<()> {
+        let prev = self.storage_snapshot();
+
+        CONNECTION_MAP.get_mut(self, |connection| {
+            match connection {
+                Connections::V1(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Connection type doesn't support this action: `rotate_did`.")),
+                Connections::V3(connection) => connection.rotate_did()
+            }
+        }).map_err(handle_err)?;
+
+        self.refresh_stored(prev);
+        Ok(())
+    }
+
+    pub fn update_service(self) -> VcxResult<()> {
+        let prev = self.storage_snapshot();
+
+        CONNECTION_MAP.get_mut(self, |connection| {
+            match connection {
+                Connections::V1(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Connection type doesn't support this action: `update_service`.")),
+                Connections::V3(connection) => connection.update_service()
+            }
+        }).map_err(handle_err)?;
+
+        self.refresh_stored(prev);
+        Ok(())
+    }
+
     pub fn send_discovery_features(self, query: Option<String>, comment: Option<String>) -> VcxResult<()> {
         CONNECTION_MAP.get_mut(self, |connection| {
             match connection {
@@ -1142,10 +1469,80 @@ pub fn from_string(connection_data: &str) -> VcxResult<Handle<Connections>> {
     Ok(handle)
 }
 
+/// Loads the Connection stored in the wallet (see `Handle<Connections>::store`).
+pub fn load_stored_connection(id: &str) -> VcxResult<Handle<Connections>> {
+    from_string(&connection_storage::get(id)?)
+}
+
+/// Searches the Connections stored in the wallet by WQL `query` over the record tags.
+pub fn search_stored_connections(query: Option<&str>) -> VcxResult<Vec<StoredConnection>> {
+    connection_storage::search(query)
+}
+
+/// Deletes the Connection record from the wallet. Loaded Connection objects are not affected.
+pub fn delete_stored_connection(id: &str) -> VcxResult<()> {
+    connection_storage::delete(id)
+}
+
+type StorageSnapshot = (u32, AgentInfo, Option<DidDoc>);
+
+fn storage_snapshot(connection: &ConnectionV3) -> StorageSnapshot {
+    (connection.state(), connection.agent_info().clone(), connection.remote_did_doc())
+}
+
 fn store_connection(connection: Connections) -> VcxResult<Handle<Connections>> {
     CONNECTION_MAP.add(connection).map_err(|_| VcxError::from(VcxErrorKind::CreateConnection))
 }
 
+// Agents of the rotated DIDs are kept until the protocols started before the rotation are finished.
+// Protocol objects are checked outside of the Connection lock.
+fn release_retired_agents(handle: Handle<Connections>) {
+    let connection_id = CONNECTION_MAP.get(handle, |connection| {
+        match connection {
+            Connections::V3(connection) if !connection.retired_agents().is_empty() =>
+                Ok(Some(connection.agent_info().connection_id().to_string())),
+            _ => Ok(None)
+        }
+    });
+
+    let connection_id = match connection_id {
+        Ok(Some(connection_id)) => connection_id,
+        _ => return
+    };
+
+    let in_progress = crate::issuer_credential::is_in_progress_over(&connection_id)
+        .and_then(|in_progress| Ok(in_progress || crate::credential::is_in_progress_over(&connection_id)?))
+        .and_then(|in_progress| Ok(in_progress || crate::proof::is_in_progress_over(&connection_id)?))
+        .and_then(|in_progress| Ok(in_progress || crate::disclosed_proof::is_in_progress_over(&connection_id)?));
+
+    match in_progress {
+        Ok(false) => {
+            let result = CONNECTION_MAP.get_mut(handle, |connection| {
+                if let Connections::V3(connection) = connection {
+                    connection.release_retired_agents();
+                }
+                Ok(())
+            });
+
+            if let Err(err) = result {
+                warn!("Unable to release agents of the rotated DIDs. Err: {:?}", err);
+            }
+        }
+        Ok(true) => debug!("Connection {:?}: agents of the rotated DIDs are kept until protocols in progress are finished", handle),
+        Err(err) => warn!("Unable to check protocols in progress. Err: {:?}", err)
+    }
+}
+
+fn store_children(children: Vec<ConnectionV3>) -> VcxResult<Vec<Handle<Connections>>> {
+    children.into_iter()
+        .map(|child| {
+            let handle = store_connection(Connections::V3(child))?;
+            debug!("stored Connection spawned by multi-use invitation, handle: {:?}", handle);
+            Ok(handle)
+        })
+        .collect()
+}
+
 fn create_connection_v1(source_id: &str) -> VcxResult<Connection> {
     trace!("create_connection_v1 >>> source_id: {:?}", source_id);
 
@@ -1391,6 +1788,42 @@ pub fn release_all() {
     CONNECTION_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<Connections>> {
+    CONNECTION_MAP.handles()
+}
+
+/// Object returned by `vcx_connection_update_service_all`
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+pub struct ServiceUpdateResult {
+    pub updated: Vec<u32>,
+    pub failed: Vec<u32>,
+}
+
+/// Sends the current service endpoint and routing keys to the remote side of every completed Aries Connection.
+/// Failure to update one Connection does not stop the update of the others.
+pub fn update_service_all() -> ServiceUpdateResult {
+    trace!("update_service_all >>>");
+
+    let mut result = ServiceUpdateResult::default();
+
+    for handle in all_handles() {
+        if !handle.is_aries_connection().unwrap_or(false) || handle.get_state() != VcxStateType::VcxStateAccepted as u32 {
+            continue;
+        }
+
+        match handle.update_service() {
+            Ok(()) => result.updated.push(handle.into()),
+            Err(err) => {
+                warn!("Unable to update service of Connection {}. Err: {:?}", handle, err);
+                result.failed.push(handle.into());
+            }
+        }
+    }
+
+    trace!("update_service_all <<< result: {:?}", result);
+    result
+}
+
 fn abbrv_event_detail(val: Value) -> Value {
     mapped_key_rewrite(val, |key: &str, _parent: Option<&str>| {
         let new_key = match key {
@@ -1553,6 +1986,25 @@ pub mod tests {
         assert!(handle.connect(Some(connection_options.to_string())).is_ok());
     }
 
+    #[test]
+    fn test_delivery_policy_options() {
+        let _setup = SetupDefaults::init();
+
+        let options = ConnectionOptions::from_opt_str(Some(&json!({"delivery_policy": {"max_attempts": 5}}).to_string())).unwrap();
+        assert_eq!(5, options.delivery_policy.unwrap().max_attempts);
+
+        let err = ConnectionOptions::from_opt_str(Some(&json!({"delivery_policy": {"max_attempts": 0}}).to_string())).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidConfiguration, err.kind());
+
+        assert!(GenericMessageOptions::from_str("").unwrap().delivery_policy.is_none());
+
+        let options = json!({"msg_type": "type", "msg_title": "title", "delivery_policy": {"retry_unconfirmed": true}}).to_string();
+        assert!(GenericMessageOptions::from_str(&options).unwrap().delivery_policy.unwrap().retry_unconfirmed);
+
+        let err = GenericMessageOptions::from_str(&json!({"delivery_policy": {"read_timeout_secs": 0}}).to_string()).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidConfiguration, err.kind());
+    }
+
     #[test]
     fn test_create_drop_create() {
         let _setup = SetupMocks::init();
@@ -2053,4 +2505,105 @@ pub mod tests {
         let expted_serialized = r#"{"data":{"agent_did":"5NR8Wmmpmu6QChiLCLgDU1","agent_vk":"3P7yrKuon8BSuUqxPUfjxuwJaDMYKpM1MsdsN66aqtU4","endpoint":"https://eas.com","invite_detail":{"connReqId":"","senderAgencyDetail":{"DID":"3rjc89idWZQzbHcUrHG2UomJn2h6nJ8exWmNuidyVv12","endpoint":"https://eas.com","verKey":"6F5i6Gc1X3tm6y7WHJAL2q"},"senderDetail":{"DID":"5kGK21ByLeD5mECcLbM55B","agentKeyDlgProof":{"agentDID":"","agentDelegatedKey":"","signature":""},"logoUrl":"https://s3.us-east-2.amazonaws.com/public-demo-artifacts/demo-icons/cbFaber.png","name":"Faber","verKey":"3b2dwi1ns6KrZnikuXGvZ1Q137kWcgqwxsNFBQL2DXBU"},"statusCode":"MS-101","statusMsg":"message created","targetName":"there","threadId":null,"version":"1.0"},"invite_url":null,"public_did":null,"pw_did":"M1MRZJ6yD9u9W3qX2qP1qr","pw_verkey":"BuS24QAxPU3xuqssvNYDZAiDVMx1GRhEyrkd1fev96Hm","redirect_detail":null,"source_id":"faber","state":4,"their_public_did":null,"their_pw_did":"5kGK21ByLeD5mECcLbM55B","their_pw_verkey":"3b2dwi1ns6KrZnikuXGvZ1Q137kWcgqwxsNFBQL2DXBU","uuid":"","version":"1.0"},"version":"1.0"}"#;
         assert_eq!(serialized, expted_serialized);
     }
+
+    #[test]
+    fn test_multi_use_connection_spawns_children() {
+        let _setup = crate::aries::test::setup::AgencyModeSetup::init();
+
+        let handle = create_connection("multi_use").unwrap();
+        handle.connect(Some(json!({"multi_use": true}).to_string())).unwrap();
+        assert!(handle.get_children().unwrap().is_empty());
+
+        let request = json!(A2AMessage::ConnectionRequest(crate::aries::messages::connection::request::tests::_request())).to_string();
+        handle.update_state(Some(request.clone())).unwrap();
+        handle.update_state_with_message(request).unwrap();
+
+        assert_eq!(VcxStateType::VcxStateOfferSent as u32, handle.get_state());
+
+        let children = handle.get_children().unwrap();
+        assert_eq!(2, children.len());
+        for child in children {
+            assert_eq!(VcxStateType::VcxStateRequestReceived as u32, child.get_state());
+            child.get_children().unwrap_err();
+            child.release().unwrap();
+        }
+        assert!(handle.get_children().unwrap().is_empty());
+
+        create_connection("single_use").unwrap().get_children().unwrap_err();
+    }
+
+    #[test]
+    fn test_stored_connection_works() {
+        let _setup = crate::aries::test::setup::AgencyModeSetup::init();
+
+        let handle = create_connection("stored").unwrap();
+        handle.connect(None).unwrap();
+
+        handle.store(Some(json!({"state": "4"}).to_string())).unwrap_err();
+        let id = handle.store(Some(json!({"group": "partners"}).to_string())).unwrap();
+        assert_eq!(handle.get_pw_did().unwrap(), id);
+
+        let query = json!({"group": "partners", "state": (VcxStateType::VcxStateOfferSent as u32).to_string()}).to_string();
+        let stored = search_stored_connections(Some(&query)).unwrap();
+        assert_eq!(1, stored.len());
+        assert_eq!(id, stored[0].id);
+        assert_eq!("stored", stored[0].tags["source_id"]);
+
+        // the record follows the state of the Connection
+        let request = json!(A2AMessage::ConnectionRequest(crate::aries::messages::connection::request::tests::_request())).to_string();
+        handle.update_state_with_message(request).unwrap();
+
+        let query = json!({"state": (VcxStateType::VcxStateRequestReceived as u32).to_string()}).to_string();
+        let stored = search_stored_connections(Some(&query)).unwrap();
+        assert_eq!(1, stored.len());
+        assert_eq!("partners", stored[0].tags["group"]);
+        assert_eq!(handle.get_their_pw_did().unwrap(), stored[0].tags["their_did"]);
+
+        let loaded = load_stored_connection(&id).unwrap();
+        assert_eq!(handle.to_string().unwrap(), loaded.to_string().unwrap());
+
+        delete_stored_connection(&id).unwrap();
+        assert!(search_stored_connections(None).unwrap().is_empty());
+        load_stored_connection(&id).unwrap_err();
+    }
+
+    #[test]
+    fn test_did_rotation_with_credential_issuance_in_progress() {
+        let _setup = crate::aries::test::setup::AgencyModeSetup::init();
+
+        let connection_handle = crate::aries::handlers::connection::tests::mock_connection();
+        let prev_agent = connection_handle.get_completed_connection().unwrap().agent;
+
+        let credential_handle = crate::issuer_credential::issuer_credential_create(crate::credential_def::tests::create_cred_def_fake(),
+                                                                                  "1".to_string(),
+                                                                                  String::new(),
+                                                                                  "credential_name".to_string(),
+                                                                                  json!({"attr": "value"}).to_string(),
+                                                                                  0).unwrap();
+        credential_handle.send_credential_offer(connection_handle).unwrap();
+        assert!(crate::issuer_credential::is_in_progress_over(prev_agent.connection_id()).unwrap());
+
+        connection_handle.rotate_did().unwrap();
+
+        let rotate_id = connection_handle.get_completed_connection().unwrap().data.did_rotation.unwrap().rotate_id;
+        let ack = crate::aries::messages::ack::Ack::create().set_thread_id(&rotate_id);
+        connection_handle.update_state_with_message(json!(A2AMessage::DidRotateAck(ack)).to_string()).unwrap();
+
+        // the issuance started before the rotation goes on with the new DID and still receives messages for the old one
+        let connection = completed_connection_by_id(prev_agent.connection_id()).unwrap().unwrap();
+        assert_ne!(prev_agent.pw_vk, connection.agent.pw_vk);
+        assert_eq!(vec![&prev_agent], connection.data.retired_agents.iter().collect::<Vec<&AgentInfo>>());
+        assert!(connection.agents().contains(&&prev_agent));
+        assert!(connection.is_addressed_to(&prev_agent.pw_vk));
+
+        // the old agent is deleted once the issuance is not in progress anymore
+        credential_handle.release().unwrap();
+
+        let ack = crate::aries::messages::ack::Ack::create().set_thread_id("other");
+        connection_handle.update_state_with_message(json!(A2AMessage::DidRotateAck(ack)).to_string()).unwrap();
+
+        let connection = connection_handle.get_completed_connection().unwrap();
+        assert!(connection.data.retired_agents.is_empty());
+        assert!(!connection.agents().contains(&&prev_agent));
+    }
 }
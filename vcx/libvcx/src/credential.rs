@@ -28,8 +28,11 @@ use crate::utils::libindy::payments::{PaymentTxn};
 use crate::utils::{error, constants};
 use crate::utils::httpclient::AgencyMock;
 use crate::aries::{
+    messages::a2a::A2AMessage,
     messages::issuance::credential_offer::CredentialOffer as CredentialOfferV3,
+    messages::issuance::credential_proposal::{CredentialProposal, CredentialProposalData},
     handlers::issuance::holder::Holder,
+    utils::revoked_credentials,
 };
 use crate::agent::agent_info::{get_agent_info, get_agent_attr, MyAgentInfo};
 use crate::legacy::messages::issuance::credential_offer::{set_cred_offer_ref_message, parse_json_offer, CredentialOffer};
@@ -485,7 +488,7 @@ impl Credential {
             .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, format!("Invalid {} Credential object state: `cred_id` not found", self.source_id)))?;
 
         let credential_info = IndyHolder::get_credential(&cred_id)?;
-        let credential_info_json = json!(credential_info).to_string();
+        let credential_info_json = revoked_credentials::credential_info_json(&credential_info)?;
 
         trace!("Credential::get_info <<< credential_json: {:?}", credential_info_json);
         Ok(credential_info_json)
@@ -495,6 +498,31 @@ impl Credential {
 //********************************************
 //         HANDLE FUNCTIONS
 //********************************************
+/// Passes a message received through the inbound endpoint to the Aries Credential objects.
+/// Returns the handle of the object that consumed the message.
+pub fn handle_inbound_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<Handle<Credentials>>> {
+    HANDLE_MAP.find_mut(
+        |credential| match credential {
+            Credentials::Pending(_) |
+            Credentials::V1(_) => false,
+            Credentials::V3(credential) => credential.accepts_inbound_message(recipient_vk, message)
+        },
+        |credential| match credential {
+            Credentials::Pending(_) |
+            Credentials::V1(_) => Ok(()),
+            Credentials::V3(credential) => credential.handle_inbound_message(recipient_vk, message).map(|_| ())
+        },
+    )
+}
+
+/// Whether any Aries Credential object started over the Connection `connection_id` (see `AgentInfo::connection_id`) is in progress.
+pub fn is_in_progress_over(connection_id: &str) -> VcxResult<bool> {
+    HANDLE_MAP.filter(|credential| match credential {
+        Credentials::V3(credential) => Ok(credential.is_in_progress_over(connection_id)),
+        _ => Ok(false)
+    }).map(|handles| !handles.is_empty())
+}
+
 fn handle_err(err: VcxError) -> VcxError {
     if err.kind() == VcxErrorKind::InvalidHandle {
         VcxError::from(VcxErrorKind::InvalidCredentialHandle)
@@ -575,6 +603,24 @@ pub fn credential_create_with_offer(source_id: &str, offer: &str) -> VcxResult<H
     Ok(handle)
 }
 
+/// Creates Aries Credential object which starts the issuance by sending a credential proposal.
+pub fn credential_create_proposal(source_id: &str, proposal: &str, comment: Option<String>) -> VcxResult<Handle<Credentials>> {
+    trace!("credential_create_proposal >>> source_id: {}, proposal: {}, comment: {:?}", source_id, secret!(&proposal), secret!(comment));
+    debug!("creating credential {} with proposal", source_id);
+
+    let proposal_data: CredentialProposalData = ::serde_json::from_str(proposal)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson,
+                                          format!("Cannot parse Credential Proposal from `proposal` JSON string. Err: {:?}", err)))?;
+
+    let proposal = CredentialProposal::create_from_data(&proposal_data, comment)?;
+    let holder = Holder::create_proposal(source_id, proposal)?;
+
+    let handle = HANDLE_MAP.add(Credentials::V3(holder))?;
+
+    trace!("credential_create_proposal <<< handle: {}", handle);
+    Ok(handle)
+}
+
 pub fn accept_credential_offer(source_id: &str, offer: &str, connection_handle: Handle<Connections>) -> VcxResult<(Handle<Credentials>, String)> {
     trace!("accept_credential_offer >>> source_id: {}, offer: {}, connection_handle: {}", source_id, secret!(&offer), connection_handle);
     debug!("creating credential {}", source_id);
@@ -825,6 +871,28 @@ impl Handle<Credentials> {
         }).map_err(handle_err)
     }
 
+    pub fn send_proposal(self, connection_handle: Handle<Connections>) -> VcxResult<()> {
+        HANDLE_MAP.get_mut(self, |credential| {
+            match credential {
+                Credentials::Pending(_) | Credentials::V1(_) => {
+                    Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Credential type doesn't support this action: `send_proposal`."))
+                }
+                Credentials::V3(obj) => obj.send_proposal(connection_handle)
+            }
+        }).map_err(handle_err)
+    }
+
+    pub fn get_credential_proposal(self) -> VcxResult<String> {
+        HANDLE_MAP.get(self, |credential| {
+            match credential {
+                Credentials::Pending(_) | Credentials::V1(_) => {
+                    Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary Credential type doesn't support this action: `get_credential_proposal`."))
+                }
+                Credentials::V3(obj) => Ok(json!(obj.get_credential_proposal()?).to_string())
+            }
+        }).map_err(handle_err)
+    }
+
     pub fn reject(self, connection_handle: Handle<Connections>, comment: Option<String>) -> VcxResult<()> {
         HANDLE_MAP.get_mut(self, move |credential| {
             let new_credential = match credential {
@@ -904,6 +972,10 @@ pub fn release_all() {
     HANDLE_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<Credentials>> {
+    HANDLE_MAP.handles()
+}
+
 
 fn get_credential_offer_msg(connection_handle: Handle<Connections>, msg_id: &str) -> VcxResult<String> {
     trace!("get_credential_offer_msg >>> connection_handle: {}, msg_id: {}", connection_handle, msg_id);
@@ -1057,6 +1129,26 @@ pub mod tests {
         assert_eq!(err.kind(), VcxErrorKind::InvalidCredentialOffer);
     }
 
+    #[test]
+    fn test_credential_create_proposal() {
+        let _setup = SetupMocks::init();
+
+        let proposal = json!({
+            "attributes": {"name": "Alice"},
+            "cred_def_id": "V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1"
+        }).to_string();
+
+        let handle = credential_create_proposal("test_credential_create_proposal", &proposal, Some("comment".to_string())).unwrap();
+        assert_eq!(VcxStateType::VcxStateInitialized as u32, handle.get_state().unwrap());
+
+        let proposal: serde_json::Value = serde_json::from_str(&handle.get_credential_proposal().unwrap()).unwrap();
+        assert_eq!(json!("V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1"), proposal["cred_def_id"]);
+        assert_eq!(json!("comment"), proposal["comment"]);
+
+        assert_eq!(VcxErrorKind::InvalidJson, credential_create_proposal("test", "invalid", None).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidOption, credential_create_proposal("test", r#"{"protocol_version":"3.0"}"#, None).unwrap_err().kind());
+    }
+
     #[test]
     fn test_credential_serialize_deserialize() {
         let _setup = SetupDefaults::init();
@@ -9,11 +9,18 @@ use crate::utils::libindy::payments::PaymentTxn;
 use crate::utils::libindy::ledger;
 use crate::utils::libindy::ledger::request::Request;
 use crate::utils::libindy::anoncreds::issuer::Issuer as IndyIssuer;
+use crate::utils::libindy::anoncreds::issued_credentials;
 use crate::utils::libindy::ledger::query::Query;
 use std::convert::AsRef;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use crate::settings;
 
 lazy_static! {
     static ref CREDENTIALDEF_MAP: ObjectCache<CredentialDef> = Default::default();
+    // serializes selection of the revocation registry and creation of the credential per Credential Definition
+    static ref ISSUANCE_LOCKS: Mutex<HashMap<String, Arc<Mutex<()>>>> = Default::default();
 }
 
 #[derive(Clone, Deserialize, Debug, Serialize, PartialEq)]
@@ -23,6 +30,9 @@ pub struct CredentialDef {
     name: String,
     source_id: String,
     issuer_did: Option<String>,
+    // not known for credential definitions created from id
+    #[serde(default)]
+    schema_id: Option<String>,
     cred_def_payment_txn: Option<PaymentTxn>,
     rev_reg_def_payment_txn: Option<PaymentTxn>,
     rev_reg_delta_payment_txn: Option<PaymentTxn>,
@@ -32,6 +42,14 @@ pub struct CredentialDef {
     tails_file: Option<String>,
     #[serde(default)]
     state: PublicEntityStateType,
+    // revocation registries in the order of creation, `rev_reg_id` / `rev_reg_def` / `tails_file` refer to the active one
+    #[serde(default)]
+    rev_regs: Vec<RevocationRegistry>,
+    #[serde(default)]
+    rotation_threshold: u32,
+    // the next revocation registry is being created
+    #[serde(skip)]
+    rotating: bool,
 }
 
 #[derive(Deserialize, Debug, Serialize)]
@@ -39,6 +57,72 @@ pub struct RevocationDetails {
     pub support_revocation: Option<bool>,
     pub tails_file: Option<String>,
     pub max_creds: Option<u32>,
+    pub rotation_threshold: Option<u32>,
+}
+
+impl RevocationDetails {
+    // by default the next registry is created when 10% of the active one is left
+    fn rotation_threshold(&self) -> u32 {
+        self.rotation_threshold.unwrap_or_else(|| self.max_creds.unwrap_or(0) / 10)
+    }
+}
+
+/// Revocation registry of the credential definition.
+#[derive(Clone, Deserialize, Debug, Serialize, PartialEq)]
+pub struct RevocationRegistry {
+    pub rev_reg_id: String,
+    pub rev_reg_def: String,
+    pub tails_file: String,
+    pub max_creds: u32,
+    /// number of credentials created against the registry as counted by the previous version,
+    /// the numbers are kept in the wallet now (see `issued_credentials`)
+    #[serde(default, skip_serializing)]
+    pub issued: u32,
+}
+
+impl RevocationRegistry {
+    fn remaining(&self, issued: u32) -> u32 { self.max_creds.saturating_sub(issued) }
+}
+
+/// Revocation registry to be created next to the existing ones of the credential definition.
+#[derive(Debug)]
+struct NextRevocationRegistry {
+    issuer_did: String,
+    cred_def_id: String,
+    tails_file: String,
+    max_creds: u32,
+    tag: String,
+}
+
+impl NextRevocationRegistry {
+    // creates the registry and publishes it on the Ledger
+    fn create(self) -> VcxResult<RevocationRegistry> {
+        debug!("Creating Revocation Registry {} of CredentialDef {}", self.tag, secret!(self.cred_def_id));
+
+        // `tails_file` is the directory tails files of the registries are written into
+        if !Path::new(&self.tails_file).is_dir() {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails,
+                                          format!("Cannot create Revocation Registry: tails location {} is not a directory", self.tails_file)));
+        }
+
+        let (rev_reg_id, rev_reg_def, rev_reg_entry) =
+            IndyIssuer::create_and_store_revoc_reg(&self.issuer_did, &self.cred_def_id, &self.tails_file, self.max_creds, &self.tag)
+                .map_err(|err| err.map(VcxErrorKind::CreateRevRegDef, "Cannot create Revocation Registry"))?;
+
+        ledger::utils::publish_rev_reg_def(&self.issuer_did, &rev_reg_def)
+            .map_err(|err| err.map(VcxErrorKind::CreateRevRegDef, "Cannot publish Revocation Registry"))?;
+
+        ledger::utils::publish_rev_reg_delta(&self.issuer_did, &rev_reg_id, &rev_reg_entry)
+            .map_err(|err| err.map(VcxErrorKind::InvalidRevocationEntry, "Cannot post RevocationEntry"))?;
+
+        Ok(RevocationRegistry {
+            rev_reg_id,
+            rev_reg_def,
+            tails_file: self.tails_file,
+            max_creds: self.max_creds,
+            issued: 0,
+        })
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug, Default)]
@@ -90,6 +174,8 @@ impl CredentialDef {
 
     pub fn get_cred_def_id(&self) -> &String { &self.id }
 
+    pub fn get_schema_id(&self) -> Option<&String> { self.schema_id.as_ref() }
+
     pub fn set_name(&mut self, name: String) { self.name = name.clone(); }
 
     pub fn set_source_id(&mut self, source_id: String) { self.source_id = source_id.clone(); }
@@ -120,6 +206,94 @@ impl CredentialDef {
     }
 
     fn get_state(&self) -> u32 { self.state as u32 }
+
+    /// Selects the revocation registry to issue the next credential with,
+    /// `issued` - numbers of credentials issued against the registries (in the order of `rev_regs`).
+    ///
+    /// Returns the first registry which is not full (`None` if all are full) and the next registry to create
+    /// when `rotation_threshold` credentials are left in the last one or all registries are full.
+    /// The next registry is not requested again until `complete_rotation` is called.
+    fn select_rev_reg(&mut self, issued: &[u32]) -> VcxResult<(Option<RevocationRegistry>, Option<NextRevocationRegistry>)> {
+        trace!("CredentialDef::select_rev_reg >>> source_id: {}, issued: {:?}", self.source_id, issued);
+
+        // registries added after the numbers were read have no credentials yet
+        let issued = |index: usize| issued.get(index).cloned().unwrap_or(0);
+
+        let active = (0..self.rev_regs.len())
+            .find(|&index| self.rev_regs[index].remaining(issued(index)) > 0);
+
+        let rotate = match active {
+            // the credential being issued is taken into account
+            Some(active) => active == self.rev_regs.len() - 1 &&
+                self.rev_regs[active].remaining(issued(active) + 1) <= self.rotation_threshold,
+            None => true
+        };
+
+        let next = if rotate && !self.rotating {
+            let next = self.next_rev_reg()?;
+            self.rotating = true;
+            Some(next)
+        } else {
+            None
+        };
+
+        let rev_reg = active.map(|active| self.rev_regs[active].clone());
+
+        if let Some(ref rev_reg) = rev_reg {
+            self.rev_reg_id = Some(rev_reg.rev_reg_id.clone());
+            self.rev_reg_def = Some(rev_reg.rev_reg_def.clone());
+            self.tails_file = Some(rev_reg.tails_file.clone());
+        }
+
+        trace!("CredentialDef::select_rev_reg <<< rev_reg_id: {:?}, next: {:?}",
+               secret!(rev_reg.as_ref().map(|rev_reg| &rev_reg.rev_reg_id)), next.as_ref().map(|next| &next.tag));
+
+        Ok((rev_reg, next))
+    }
+
+    // describes the next revocation registry of the same size and tails location as the last one
+    fn next_rev_reg(&self) -> VcxResult<NextRevocationRegistry> {
+        let last = self.rev_regs.last()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "CredentialDef does not support revocation"))?;
+
+        let issuer_did = self.issuer_did.clone()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "CredentialDef does not contain issuer DID"))?;
+
+        Ok(NextRevocationRegistry {
+            issuer_did,
+            cred_def_id: self.id.clone(),
+            tails_file: last.tails_file.clone(),
+            max_creds: last.max_creds,
+            tag: _rev_reg_tag(self.rev_regs.len()),
+        })
+    }
+
+    // adds the registry requested by `select_rev_reg` once it is created
+    fn complete_rotation(&mut self, rev_reg: VcxResult<RevocationRegistry>) -> VcxResult<()> {
+        self.rotating = false;
+        self.rev_regs.push(rev_reg?);
+        Ok(())
+    }
+
+    fn get_rev_regs(&self) -> &Vec<RevocationRegistry> { &self.rev_regs }
+}
+
+// tag of the n-th (starting from 0) revocation registry of the Credential Definition
+fn _rev_reg_tag(index: usize) -> String {
+    format!("tag{}", index + 1)
+}
+
+fn _initial_rev_regs(rev_reg_id: &Option<String>, rev_reg_def: &Option<String>, revocation_details: &RevocationDetails) -> Vec<RevocationRegistry> {
+    match (rev_reg_id, rev_reg_def, &revocation_details.tails_file, revocation_details.max_creds) {
+        (Some(rev_reg_id), Some(rev_reg_def), Some(tails_file), Some(max_creds)) => vec![RevocationRegistry {
+            rev_reg_id: rev_reg_id.to_string(),
+            rev_reg_def: rev_reg_def.to_string(),
+            tails_file: tails_file.to_string(),
+            max_creds,
+            issued: 0,
+        }],
+        _ => Vec::new()
+    }
 }
 
 fn handle_err(err: VcxError) -> VcxError {
@@ -155,6 +329,7 @@ pub fn create_credentialdef_from_id(_source_id: String,
         tag: _tag,
         id: cred_def_id,
         issuer_did: Some(issuer_did),
+        schema_id: None,
         name: String::new(),
         cred_def_payment_txn: None,
         rev_reg_def_payment_txn: None,
@@ -164,6 +339,9 @@ pub fn create_credentialdef_from_id(_source_id: String,
         rev_reg_entry: rev_config.as_ref().rev_reg_entry.to_owned(),
         tails_file: rev_config.as_ref().tails_file.to_owned(),
         state: PublicEntityStateType::Built,
+        rev_regs: Vec::new(),
+        rotation_threshold: 0,
+        rotating: false,
     };
 
     let handle = CREDENTIALDEF_MAP.add(cred_def).or(Err(VcxError::from(VcxErrorKind::CreateCredDef)))?;
@@ -203,7 +381,7 @@ fn _create_credentialdef(issuer_did: &str,
                 .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, "Invalid RevocationDetails: `max_creds` field not found"))?;
 
             let (rev_reg_id, rev_reg_def, rev_reg_entry) =
-                IndyIssuer::create_and_store_revoc_reg(&issuer_did, &cred_def_id, &tails_file, max_creds)
+                IndyIssuer::create_and_store_revoc_reg(&issuer_did, &cred_def_id, &tails_file, max_creds, &_rev_reg_tag(0))
                     .map_err(|err| err.map(VcxErrorKind::CreateRevRegDef, "Cannot create Revocation Registry"))?;
 
             (Some(rev_reg_id), Some(rev_reg_def), Some(rev_reg_entry))
@@ -261,9 +439,13 @@ pub fn prepare_credentialdef_for_endorser(source_id: String,
         tag,
         id: cred_def_id,
         issuer_did: Some(issuer_did),
+        schema_id: Some(schema_id),
         cred_def_payment_txn: None,
         rev_reg_def_payment_txn: None,
         rev_reg_delta_payment_txn: None,
+        rev_regs: _initial_rev_regs(&rev_reg_id, &rev_reg_def, &revocation_details),
+        rotation_threshold: revocation_details.rotation_threshold(),
+        rotating: false,
         rev_reg_id,
         rev_reg_def,
         rev_reg_entry,
@@ -315,9 +497,13 @@ pub fn create_and_publish_credentialdef(source_id: String,
         tag,
         id: cred_def_id,
         issuer_did: Some(issuer_did),
+        schema_id: Some(schema_id),
         cred_def_payment_txn: None,
         rev_reg_def_payment_txn: None,
         rev_reg_delta_payment_txn: None,
+        rev_regs: _initial_rev_regs(&rev_reg_id, &rev_reg_def, &revocation_details),
+        rotation_threshold: revocation_details.rotation_threshold(),
+        rotating: false,
         rev_reg_id,
         rev_reg_def,
         rev_reg_entry,
@@ -332,8 +518,45 @@ pub fn create_and_publish_credentialdef(source_id: String,
     Ok(handle)
 }
 
+// numbers of credentials issued against the registries, the ones counted by the previous version are used until the wallet has them
+fn _issued_credentials(rev_regs: &[RevocationRegistry]) -> VcxResult<Vec<u32>> {
+    rev_regs.iter()
+        .map(|rev_reg| issued_credentials::get(&rev_reg.rev_reg_id).map(|issued| issued.unwrap_or(rev_reg.issued)))
+        .collect()
+}
+
+/// Creates a credential of the Credential Definition with `create`, see `Handle<CredentialDef>::issue_credential`.
+/// `selected` registry (`rev_reg_id`, `rev_reg_def` and `tails_file` selected when the issuer was created)
+/// is used if the Credential Definition is not loaded.
+pub fn issue_credential<F, T>(cred_def_id: &str, selected: (Option<String>, Option<String>, Option<String>), create: F) -> VcxResult<T>
+    where F: FnOnce(Option<&str>, Option<&str>, Option<&str>) -> VcxResult<T> {
+    if settings::indy_mocks_enabled() {
+        return create(selected.0.as_deref(), selected.1.as_deref(), selected.2.as_deref());
+    }
+
+    let handle = CREDENTIALDEF_MAP.filter(|c| Ok(c.get_cred_def_id() == cred_def_id))?
+        .into_iter()
+        .next();
+
+    match handle {
+        Some(handle) => handle.issue_credential(create),
+        None => {
+            warn!("CredentialDef {} is not loaded, the credential is issued with the Revocation Registry selected before", cred_def_id);
+            create(selected.0.as_deref(), selected.1.as_deref(), selected.2.as_deref())
+        }
+    }
+}
+
 pub fn from_string(data: &str) -> VcxResult<Handle<CredentialDef>> {
     let cred_def: CredentialDef = CredentialDef::from_str(data)?;
+
+    // the previous version kept the numbers of issued credentials in the serialized object
+    for rev_reg in cred_def.rev_regs.iter().filter(|rev_reg| rev_reg.issued > 0) {
+        if let Err(err) = issued_credentials::init(&rev_reg.rev_reg_id, rev_reg.issued) {
+            warn!("CredentialDef {}: Cannot move number of issued credentials to the wallet. Err: {}", cred_def.source_id, err);
+        }
+    }
+
     CREDENTIALDEF_MAP.add(cred_def)
 }
 
@@ -366,6 +589,12 @@ impl Handle<CredentialDef> {
         }).map_err(handle_err)
     }
 
+    pub fn get_schema_id(self) -> VcxResult<Option<String>> {
+        CREDENTIALDEF_MAP.get(self, |c| {
+            Ok(c.get_schema_id().cloned())
+        }).map_err(handle_err)
+    }
+
     pub fn get_rev_reg_id(self) -> VcxResult<Option<String>> {
         CREDENTIALDEF_MAP.get(self, |c| {
             Ok(c.get_rev_reg_id().cloned())
@@ -384,6 +613,99 @@ impl Handle<CredentialDef> {
         }).map_err(handle_err)
     }
 
+    /// Selects the revocation registry to issue the next credential with, see `CredentialDef::select_rev_reg`.
+    /// Returns `rev_reg_id`, `rev_reg_def` and `tails_file` of the registry.
+    ///
+    /// Credentials are counted when they are created (see `issued_credentials`), so an offer does not use up the registry.
+    /// The next registry is created and published when `rotation_threshold` credentials are left in the last one,
+    /// issuance switches to it once the active registry is full.
+    pub fn select_rev_reg(self) -> VcxResult<(Option<String>, Option<String>, Option<String>)> {
+        trace!("Handle<CredentialDef>::select_rev_reg >>> handle: {}", self);
+
+        let rev_regs = CREDENTIALDEF_MAP.get(self, |c| {
+            Ok(c.get_rev_regs().clone())
+        }).map_err(handle_err)?;
+
+        // revocation is not supported or the registries are not tracked (Credential Definition created from id)
+        if rev_regs.is_empty() {
+            return CREDENTIALDEF_MAP.get(self, |c| {
+                Ok((c.get_rev_reg_id().cloned(), c.get_rev_reg_def().cloned(), c.get_tails_file().cloned()))
+            }).map_err(handle_err);
+        }
+
+        let issued = _issued_credentials(&rev_regs)?;
+
+        let (rev_reg, next) = CREDENTIALDEF_MAP.get_mut(self, |c| {
+            c.select_rev_reg(&issued)
+        }).map_err(handle_err)?;
+
+        if let Some(next) = next {
+            // the registry is created and published without holding the Credential Definition
+            let created = next.create();
+
+            let rotated = CREDENTIALDEF_MAP.get_mut(self, |c| {
+                Ok(c.complete_rotation(created))
+            }).map_err(handle_err)?;
+
+            match (rev_reg.is_some(), rotated) {
+                (true, Err(err)) => warn!("CredentialDef {}: Cannot create next Revocation Registry. Err: {}", self, err),
+                (false, Err(err)) => return Err(err),
+                // all registries were full, the credential is issued with the created one
+                (false, Ok(())) => return self.select_rev_reg(),
+                (true, Ok(())) => {}
+            }
+        }
+
+        let rev_reg = rev_reg
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState, "All Revocation Registries of CredentialDef are full, the next one is being created"))?;
+
+        trace!("Handle<CredentialDef>::select_rev_reg <<< rev_reg_id: {}", secret!(rev_reg.rev_reg_id));
+
+        Ok((Some(rev_reg.rev_reg_id), Some(rev_reg.rev_reg_def), Some(rev_reg.tails_file)))
+    }
+
+    /// Creates a credential with `create` taking `rev_reg_id`, `rev_reg_def` and `tails_file` of the revocation registry selected for it.
+    ///
+    /// The registry is selected right before the credential is created, and the credentials of the Credential Definition
+    /// are created one by one, so credentials offered before a registry is full do not use up the same registry.
+    pub fn issue_credential<F, T>(self, create: F) -> VcxResult<T>
+        where F: FnOnce(Option<&str>, Option<&str>, Option<&str>) -> VcxResult<T> {
+        trace!("Handle<CredentialDef>::issue_credential >>> handle: {}", self);
+
+        let cred_def_id = self.get_cred_def_id()?;
+
+        let lock = ISSUANCE_LOCKS.lock()
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot lock CredentialDef: lock is poisoned"))?
+            .entry(cred_def_id)
+            .or_default()
+            .clone();
+
+        let _guard = lock.lock()
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot lock CredentialDef: lock is poisoned"))?;
+
+        let (rev_reg_id, rev_reg_def, tails_file) = self.select_rev_reg()?;
+
+        create(rev_reg_id.as_deref(), rev_reg_def.as_deref(), tails_file.as_deref())
+    }
+
+    pub fn get_rev_regs(self) -> VcxResult<String> {
+        let rev_regs = CREDENTIALDEF_MAP.get(self, |c| {
+            Ok(c.get_rev_regs().clone())
+        }).map_err(handle_err)?;
+
+        let issued = _issued_credentials(&rev_regs)?;
+
+        let rev_regs: Vec<serde_json::Value> = rev_regs.iter().zip(issued)
+            .map(|(rev_reg, issued)| {
+                let mut rev_reg = json!(rev_reg);
+                rev_reg["issued"] = json!(issued);
+                rev_reg
+            })
+            .collect();
+
+        Ok(json!(rev_regs).to_string())
+    }
+
     pub fn get_rev_reg_def_payment_txn(self) -> VcxResult<Option<PaymentTxn>> {
         CREDENTIALDEF_MAP.get(self, |c| {
             Ok(c.get_rev_reg_def_payment_txn())
@@ -424,6 +746,10 @@ pub fn release_all() {
     CREDENTIALDEF_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<CredentialDef>> {
+    CREDENTIALDEF_MAP.handles()
+}
+
 #[cfg(test)]
 pub mod tests {
     use crate::utils::{
@@ -435,7 +761,7 @@ pub mod tests {
     use super::*;
     use crate::settings;
     use std::{
-        thread::sleep,
+        thread::{self, sleep},
         time::Duration,
     };
     use crate::utils::devsetup::*;
@@ -490,6 +816,165 @@ pub mod tests {
         create_cred_def_real(false);
     }
 
+    fn _issued(handle: Handle<CredentialDef>) -> Vec<u32> {
+        let rev_regs: Vec<RevocationRegistry> = serde_json::from_str(&handle.get_rev_regs().unwrap()).unwrap();
+        rev_regs.iter().map(|rev_reg| rev_reg.issued).collect()
+    }
+
+    fn _create_cred_def_with_rotation(tails_file: &str, max_creds: u32, rotation_threshold: u32) -> Handle<CredentialDef> {
+        let revocation_details = json!({
+            "support_revocation": true,
+            "tails_file": tails_file,
+            "max_creds": max_creds,
+            "rotation_threshold": rotation_threshold
+        });
+
+        create_and_publish_credentialdef("SourceId".to_string(),
+                                         CREDENTIAL_DEF_NAME.to_string(),
+                                         ISSUER_DID.to_string(),
+                                         SCHEMA_ID.to_string(),
+                                         "tag".to_string(),
+                                         revocation_details.to_string()).unwrap()
+    }
+
+    fn _tails_dir() -> String {
+        let tails_dir = get_temp_dir_path("tails_rotation");
+        ::std::fs::create_dir_all(&tails_dir).unwrap();
+        tails_dir.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_select_rev_reg_rotates_registries() {
+        let _setup = SetupMocks::init();
+
+        let handle = _create_cred_def_with_rotation(&_tails_dir(), 2, 1);
+        let rev_regs: Vec<RevocationRegistry> = serde_json::from_str(&handle.get_rev_regs().unwrap()).unwrap();
+
+        let select = |issued: &[u32]| CREDENTIALDEF_MAP.get_mut(handle, |c| c.select_rev_reg(issued)).unwrap();
+        let complete = |rev_reg: VcxResult<RevocationRegistry>| CREDENTIALDEF_MAP.get_mut(handle, |c| Ok(c.complete_rotation(rev_reg))).unwrap();
+
+        // next registry is requested when the threshold is reached
+        let (rev_reg, next) = select(&[0]);
+        assert_eq!(rev_regs[0], rev_reg.unwrap());
+        let next = next.unwrap();
+        assert_eq!("tag2", next.tag);
+
+        // registry being created is not requested again
+        let (rev_reg, next_) = select(&[1]);
+        assert_eq!(rev_regs[0], rev_reg.unwrap());
+        assert!(next_.is_none());
+
+        // mocked registries have the same id
+        let mut created = next.create().unwrap();
+        created.rev_reg_id = "rev_reg_id_2".to_string();
+        complete(Ok(created.clone())).unwrap();
+        assert_eq!(vec![0, 0], _issued(handle));
+
+        // full registry is skipped
+        let (rev_reg, next) = select(&[2, 0]);
+        assert_eq!(created, rev_reg.unwrap());
+        assert_eq!(Some(created.rev_reg_id), handle.get_rev_reg_id().unwrap());
+        assert_eq!("tag3", next.unwrap().tag);
+
+        // failed creation is requested again
+        complete(Err(VcxError::from(VcxErrorKind::CreateRevRegDef))).unwrap_err();
+        let (_, next) = select(&[2, 1]);
+        assert_eq!("tag3", next.unwrap().tag);
+
+        // all registries are full and the next one is being created
+        let (rev_reg, next) = select(&[2, 2]);
+        assert!(rev_reg.is_none());
+        assert!(next.is_none());
+    }
+
+    #[test]
+    fn test_select_rev_reg_creates_next_registry() {
+        let _setup = SetupMocks::init();
+
+        let tails_dir = _tails_dir();
+        let handle = _create_cred_def_with_rotation(&tails_dir, 2, 1);
+        assert_eq!(vec![0], _issued(handle));
+
+        let (rev_reg_id, rev_reg_def, tails_file) = handle.select_rev_reg().unwrap();
+        assert!(rev_reg_id.is_some());
+        assert!(rev_reg_def.is_some());
+        assert_eq!(Some(tails_dir), tails_file);
+
+        // credentials are counted when they are created, not when the registry is selected
+        assert_eq!(vec![0, 0], _issued(handle));
+
+        let restored = from_string(&handle.to_string().unwrap()).unwrap();
+        assert_eq!(vec![0, 0], _issued(restored));
+    }
+
+    #[test]
+    fn test_select_rev_reg_requires_tails_directory() {
+        let _setup = SetupMocks::init();
+
+        let handle = _create_cred_def_with_rotation(get_temp_dir_path("not_a_directory.txt").to_str().unwrap(), 1, 0);
+
+        let (_, next) = CREDENTIALDEF_MAP.get_mut(handle, |c| c.select_rev_reg(&[0])).unwrap();
+        assert_eq!(VcxErrorKind::InvalidRevocationDetails, next.unwrap().create().unwrap_err().kind());
+
+        // registry which is not full is used when the next one cannot be created
+        let handle = _create_cred_def_with_rotation(get_temp_dir_path("not_a_directory.txt").to_str().unwrap(), 1, 0);
+        handle.select_rev_reg().unwrap();
+        assert_eq!(vec![0], _issued(handle));
+    }
+
+    #[test]
+    fn test_issue_credential_selects_rev_reg_on_creation() {
+        let _setup = SetupLibraryWallet::init();
+
+        // registries are created with mocked Ledger
+        settings::set_config_value(settings::CONFIG_ENABLE_TEST_MODE, "true");
+        let handle = _create_cred_def_with_rotation(&_tails_dir(), 2, 0);
+        let mut created = CREDENTIALDEF_MAP.get(handle, |c| c.next_rev_reg()).unwrap().create().unwrap();
+        created.rev_reg_id = "rev_reg_id_2".to_string();
+        CREDENTIALDEF_MAP.get_mut(handle, |c| {
+            c.complete_rotation(Ok(created.clone()))?;
+            // no more registries are created
+            c.rotating = true;
+            Ok(())
+        }).unwrap();
+        settings::set_config_value(settings::CONFIG_ENABLE_TEST_MODE, "false");
+
+        // more issuers are open than registries have free slots, they all selected the first registry when created
+        let (selected, _, _) = handle.select_rev_reg().unwrap();
+        issued_credentials::increment(selected.as_deref().unwrap()).unwrap();
+
+        let issuers: Vec<_> = (0..3)
+            .map(|_| thread::spawn(move || {
+                handle.issue_credential(|rev_reg_id, _, _| {
+                    // counted as IndyIssuer::create_credential does
+                    issued_credentials::increment(rev_reg_id.unwrap())?;
+                    Ok(rev_reg_id.unwrap().to_string())
+                }).unwrap()
+            }))
+            .collect();
+
+        let mut used: Vec<String> = issuers.into_iter().map(|issuer| issuer.join().unwrap()).collect();
+        used.sort();
+
+        let mut expected = vec![selected.unwrap(), created.rev_reg_id.clone(), created.rev_reg_id.clone()];
+        expected.sort();
+
+        assert_eq!(expected, used);
+        assert_eq!(vec![2, 2], _issued(handle));
+
+        // all registries are full
+        assert_eq!(VcxErrorKind::InvalidState, handle.issue_credential(|_, _, _| Ok(())).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_select_rev_reg_without_revocation() {
+        let _setup = SetupMocks::init();
+
+        let handle = create_cred_def_fake();
+        assert_eq!((None, None, None), handle.select_rev_reg().unwrap());
+        assert_eq!("[]", handle.get_rev_regs().unwrap());
+    }
+
     #[cfg(feature = "pool_tests")]
     mod pool_tests {
         use super::*;
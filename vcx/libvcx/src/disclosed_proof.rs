@@ -21,6 +21,7 @@ use crate::utils::error;
 use crate::utils::constants::*;
 use crate::utils::libindy::anoncreds::holder::Holder as IndyHolder;
 use crate::aries::{
+    messages::a2a::A2AMessage,
     messages::proof_presentation::presentation_request::PresentationRequest,
     handlers::proof_presentation::prover::Prover,
 };
@@ -35,7 +36,7 @@ lazy_static! {
     static ref HANDLE_MAP: ObjectCache<DisclosedProofs>  = Default::default();
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "version", content = "data")]
 pub enum DisclosedProofs {
     #[serde(rename = "3.0")]
@@ -295,6 +296,31 @@ impl DisclosedProof {
 //********************************************
 //         HANDLE FUNCTIONS
 //********************************************
+/// Passes a message received through the inbound endpoint to the Aries DisclosedProof objects.
+/// Returns the handle of the object that consumed the message.
+pub fn handle_inbound_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<Handle<DisclosedProofs>>> {
+    HANDLE_MAP.find_mut(
+        |proof| match proof {
+            DisclosedProofs::Pending(_) |
+            DisclosedProofs::V1(_) => false,
+            DisclosedProofs::V3(proof) => proof.accepts_inbound_message(recipient_vk, message)
+        },
+        |proof| match proof {
+            DisclosedProofs::Pending(_) |
+            DisclosedProofs::V1(_) => Ok(()),
+            DisclosedProofs::V3(proof) => proof.handle_inbound_message(recipient_vk, message).map(|_| ())
+        },
+    )
+}
+
+/// Whether any Aries Disclosed Proof object started over the Connection `connection_id` (see `AgentInfo::connection_id`) is in progress.
+pub fn is_in_progress_over(connection_id: &str) -> VcxResult<bool> {
+    HANDLE_MAP.filter(|proof| match proof {
+        DisclosedProofs::V3(proof) => Ok(proof.is_in_progress_over(connection_id)),
+        _ => Ok(false)
+    }).map(|handles| !handles.is_empty())
+}
+
 fn handle_err(err: VcxError) -> VcxError {
     if err.kind() == VcxErrorKind::InvalidHandle {
         VcxError::from(VcxErrorKind::InvalidDisclosedProofHandle)
@@ -684,6 +710,10 @@ pub fn release_all() {
     HANDLE_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<DisclosedProofs>> {
+    HANDLE_MAP.handles()
+}
+
 
 pub fn from_string(proof_data: &str) -> VcxResult<Handle<DisclosedProofs>> {
     let proof: DisclosedProofs = serde_json::from_str(proof_data)
@@ -2,7 +2,7 @@ use std::mem::take;
 use std::collections::HashMap;
 use std::convert::TryInto;
 
-use crate::credential_def::CredentialDef;
+use crate::credential_def::{self, CredentialDef};
 use crate::utils::object_cache::Handle;
 use crate::connection::Connections;
 use crate::api::VcxStateType;
@@ -21,6 +21,8 @@ use crate::utils::qualifier;
 use crate::utils::object_cache::ObjectCache;
 use crate::error::prelude::*;
 use crate::aries::handlers::issuance::issuer::Issuer;
+use crate::aries::messages::a2a::A2AMessage;
+use crate::aries::messages::issuance::credential_proposal::CredentialProposal;
 use crate::agent::agent_info::{get_agent_info, MyAgentInfo, get_agent_attr};
 use crate::legacy::messages::issuance::credential_offer::CredentialOffer;
 use crate::legacy::messages::issuance::credential::CredentialMessage;
@@ -31,7 +33,7 @@ lazy_static! {
     static ref ISSUER_CREDENTIAL_MAP: ObjectCache<IssuerCredentials> = Default::default();
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "version", content = "data")]
 pub enum IssuerCredentials {
     #[serde(rename = "3.0")]
@@ -104,6 +106,28 @@ impl PaymentInfo {
     }
 }
 
+/// Options of the notification about credential revocation sent to the holder.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RevocationNotificationOptions {
+    /// version of Revocation Notification protocol: `1.0` (default) or `2.0`
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+impl RevocationNotificationOptions {
+    pub fn from_opt_str(options: Option<&str>) -> VcxResult<RevocationNotificationOptions> {
+        match options.map(|opt| opt.trim()) {
+            None | Some("") => Ok(RevocationNotificationOptions::default()),
+            Some(opt) => {
+                serde_json::from_str(opt)
+                    .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize RevocationNotificationOptions: {}", err)))
+            }
+        }
+    }
+}
+
 impl IssuerCredential {
     pub fn create(cred_def_handle: Handle<CredentialDef>,
                   source_id: String,
@@ -116,9 +140,8 @@ impl IssuerCredential {
         debug!("IssuerCredential {}: Creating state object", source_id);
 
         let cred_def_id = cred_def_handle.get_cred_def_id()?;
-        let rev_reg_id = cred_def_handle.get_rev_reg_id()?;
-        let tails_file = cred_def_handle.get_tails_file()?;
-        let rev_reg_def_json = cred_def_handle.get_rev_reg_def()?;
+        // selected again right before the credential is created
+        let (rev_reg_id, rev_reg_def_json, tails_file) = cred_def_handle.select_rev_reg()?;
 
         let mut issuer_credential = IssuerCredential {
             credential_id: source_id.to_string(),
@@ -393,13 +416,25 @@ impl IssuerCredential {
             .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState,
                                       format!("Invalid {} Issuer Credential object state: `credential_request` not found", self.source_id)))?;
 
-        let (cred, cred_revoc_id, revoc_reg_delta_json) =
-            LibindyIssuer::create_credential(&indy_cred_offer.libindy_offer,
-                                             &indy_cred_req.libindy_cred_req,
-                                             &credential_data,
-                                             self.rev_reg_id.as_deref(),
-                                             self.tails_file.as_deref())?;
-
+        // the revocation registry is selected right before the credential is created
+        let (cred, cred_revoc_id, revoc_reg_delta_json, (rev_reg_id, rev_reg_def_json, tails_file)) =
+            credential_def::issue_credential(
+                &self.cred_def_id,
+                (self.rev_reg_id.clone(), self.rev_reg_def_json.clone(), self.tails_file.clone()),
+                |rev_reg_id, rev_reg_def_json, tails_file| {
+                    let (cred, cred_revoc_id, revoc_reg_delta_json) =
+                        LibindyIssuer::create_credential(&indy_cred_offer.libindy_offer,
+                                                         &indy_cred_req.libindy_cred_req,
+                                                         &credential_data,
+                                                         rev_reg_id,
+                                                         tails_file)?;
+                    Ok((cred, cred_revoc_id, revoc_reg_delta_json,
+                        (rev_reg_id.map(String::from), rev_reg_def_json.map(String::from), tails_file.map(String::from))))
+                })?;
+
+        self.rev_reg_id = rev_reg_id;
+        self.rev_reg_def_json = rev_reg_def_json;
+        self.tails_file = tails_file;
         self.cred_rev_id = cred_revoc_id.clone();
 
         let their_pw_did = get_agent_attr(&self.their_did).unwrap_or_default();
@@ -464,8 +499,8 @@ impl IssuerCredential {
         Ok(credential_offer)
     }
 
-    fn revoke_cred(&mut self) -> VcxResult<()> {
-        trace!("IssuerCredential::revoke_cred >>>");
+    fn revoke_cred(&mut self, publish: bool) -> VcxResult<()> {
+        trace!("IssuerCredential::revoke_cred >>> publish: {}", publish);
         debug!("IssuerCredential {}: Revoking credential", self.source_id);
 
         let tails_file = self.tails_file
@@ -480,7 +515,11 @@ impl IssuerCredential {
             .as_ref()
             .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, "Invalid RevocationInfo: `cred_rev_id` not found"))?;
 
-        LibindyIssuer::revoke_credential(tails_file, rev_reg_id, cred_rev_id)?;
+        if publish {
+            LibindyIssuer::revoke_credential(tails_file, rev_reg_id, cred_rev_id)?;
+        } else {
+            LibindyIssuer::revoke_credential_local(tails_file, rev_reg_id, cred_rev_id)?;
+        }
 
         trace!("IssuerCredential::revoke_cred <<<");
 
@@ -525,6 +564,31 @@ impl IssuerCredential {
     }
 }
 
+/// Passes a message received through the inbound endpoint to the Aries IssuerCredential objects.
+/// Returns the handle of the object that consumed the message.
+pub fn handle_inbound_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<Handle<IssuerCredentials>>> {
+    ISSUER_CREDENTIAL_MAP.find_mut(
+        |credential| match credential {
+            IssuerCredentials::Pending(_) |
+            IssuerCredentials::V1(_) => false,
+            IssuerCredentials::V3(credential) => credential.accepts_inbound_message(recipient_vk, message)
+        },
+        |credential| match credential {
+            IssuerCredentials::Pending(_) |
+            IssuerCredentials::V1(_) => Ok(()),
+            IssuerCredentials::V3(credential) => credential.handle_inbound_message(recipient_vk, message).map(|_| ())
+        },
+    )
+}
+
+/// Whether any Aries Issuer Credential object started over the Connection `connection_id` (see `AgentInfo::connection_id`) is in progress.
+pub fn is_in_progress_over(connection_id: &str) -> VcxResult<bool> {
+    ISSUER_CREDENTIAL_MAP.filter(|credential| match credential {
+        IssuerCredentials::V3(credential) => Ok(credential.is_in_progress_over(connection_id)),
+        _ => Ok(false)
+    }).map(|handles| !handles.is_empty())
+}
+
 fn handle_err(err: VcxError) -> VcxError {
     if err.kind() == VcxErrorKind::InvalidHandle {
         VcxError::from(VcxErrorKind::InvalidIssuerCredentialHandle)
@@ -642,6 +706,31 @@ pub fn issuer_credential_create(cred_def_handle: Handle<CredentialDef>,
     Ok(handle)
 }
 
+/// Creates Aries Issuer Credential answering the credential proposal received from the holder.
+/// Proposed attribute values are offered if `credential_data` is not set.
+pub fn issuer_credential_create_from_proposal(cred_def_handle: Handle<CredentialDef>,
+                                              source_id: String,
+                                              proposal: &str,
+                                              credential_data: Option<String>,
+                                              credential_name: String) -> VcxResult<Handle<IssuerCredentials>> {
+    cred_def_handle.check_is_published()?;
+
+    trace!("issuer_credential_create_from_proposal >>> cred_def_handle: {}, source_id: {}, proposal: {}, credential_data: {:?}, credential_name: {}",
+           cred_def_handle, source_id, secret!(proposal), secret!(credential_data), secret!(credential_name));
+    debug!("creating issuer credential {} state object from proposal", source_id);
+
+    let proposal: CredentialProposal = ::serde_json::from_str(proposal)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson,
+                                          format!("Cannot parse Credential Proposal from JSON string. Err: {:?}", err)))?;
+
+    let issuer = Issuer::create_from_proposal(cred_def_handle, proposal, credential_data.as_deref(), &source_id, &credential_name)?;
+
+    let handle = ISSUER_CREDENTIAL_MAP.add(IssuerCredentials::V3(issuer))?;
+    trace!("issuer_credential_create_from_proposal <<< handle: {:?}", handle);
+
+    Ok(handle)
+}
+
 impl Handle<IssuerCredentials> {
     pub fn get_encoded_attributes(self) -> VcxResult<String> {
         ISSUER_CREDENTIAL_MAP.get(self, |obj| {
@@ -805,12 +894,51 @@ impl Handle<IssuerCredentials> {
         }).map_err(handle_err)
     }
 
+    /// Revokes the credential and publishes the revocation to the Ledger.
     pub fn revoke_credential(self) -> VcxResult<()> {
+        self._revoke_credential(true)
+    }
+
+    /// Revokes the credential in the wallet only.
+    /// The revocation is published to the Ledger with the next published revocation of the same revocation registry.
+    pub fn revoke_credential_local(self) -> VcxResult<()> {
+        self._revoke_credential(false)
+    }
+
+    fn _revoke_credential(self, publish: bool) -> VcxResult<()> {
         ISSUER_CREDENTIAL_MAP.get_mut(self, |obj| {
             match obj {
-                IssuerCredentials::Pending(obj) => obj.revoke_cred(),
-                IssuerCredentials::V1(obj) => obj.revoke_cred(),
-                IssuerCredentials::V3(_) => Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Aries IssuerCredential type doesn't support this action: `revoke_credential`.")), // TODO: implement
+                IssuerCredentials::Pending(obj) => obj.revoke_cred(publish),
+                IssuerCredentials::V1(obj) => obj.revoke_cred(publish),
+                IssuerCredentials::V3(obj) => obj.revoke_credential(publish),
+            }
+        }).map_err(handle_err)
+    }
+
+    /// Whether the credential has been revoked by `revoke_credential` or `revoke_credential_local`.
+    pub fn is_revoked(self) -> VcxResult<bool> {
+        ISSUER_CREDENTIAL_MAP.get(self, |obj| {
+            match obj {
+                IssuerCredentials::Pending(_) | IssuerCredentials::V1(_) => {
+                    Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary IssuerCredential type doesn't support this action: `is_revoked`."))
+                }
+                IssuerCredentials::V3(obj) => Ok(obj.is_revoked())
+            }
+        }).map_err(handle_err)
+    }
+
+    /// Notifies the holder about revocation of the credential over the connection the credential was issued over.
+    pub fn send_revocation_notification(self, options: Option<&str>) -> VcxResult<()> {
+        let options = RevocationNotificationOptions::from_opt_str(options)?;
+
+        ISSUER_CREDENTIAL_MAP.get(self, |obj| {
+            match obj {
+                IssuerCredentials::Pending(_) | IssuerCredentials::V1(_) => {
+                    Err(VcxError::from_msg(VcxErrorKind::ActionNotSupported, "Proprietary IssuerCredential type doesn't support this action: `send_revocation_notification`."))
+                }
+                IssuerCredentials::V3(obj) => {
+                    obj.send_revocation_notification(options.version.as_deref(), options.comment.clone())
+                }
             }
         }).map_err(handle_err)
     }
@@ -849,10 +977,41 @@ impl Handle<IssuerCredentials> {
     }
 }
 
+/// Returns pending revocations of all revocation registries as JSON array.
+pub fn get_pending_revocations() -> VcxResult<String> {
+    trace!("get_pending_revocations >>>");
+
+    let pending = LibindyIssuer::get_pending_revocations()?;
+
+    Ok(json!(pending).to_string())
+}
+
+/// Publishes pending revocations of the revocation registry (or of all registries if `rev_reg_id` is not set).
+/// Returns JSON array of ids of the registries which revocations were published.
+pub fn publish_pending_revocations(rev_reg_id: Option<&str>) -> VcxResult<String> {
+    trace!("publish_pending_revocations >>> rev_reg_id: {:?}", rev_reg_id);
+
+    let published = match rev_reg_id {
+        Some(rev_reg_id) => {
+            LibindyIssuer::publish_pending_revocations(rev_reg_id)?
+                .map(|_| vec![rev_reg_id.to_string()])
+                .unwrap_or_default()
+        }
+        None => LibindyIssuer::publish_all_pending_revocations()?
+    };
+
+    trace!("publish_pending_revocations <<< published: {:?}", published);
+    Ok(json!(published).to_string())
+}
+
 pub fn release_all() {
     ISSUER_CREDENTIAL_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<IssuerCredentials>> {
+    ISSUER_CREDENTIAL_MAP.handles()
+}
+
 pub fn convert_to_map(s: &str) -> VcxResult<serde_json::Map<String, serde_json::Value>> {
     serde_json::from_str(s)
         .map_err(|_| {
@@ -1100,6 +1259,36 @@ pub mod tests {
         assert_eq!(handle.get_offer_uid().unwrap(), "ntc2ytb");
     }
 
+    #[test]
+    fn test_issuer_credential_create_from_proposal() {
+        let _setup = SetupAriesMocks::init();
+        use crate::aries::messages::issuance::credential_proposal::tests::_credential_proposal_for_cred_def;
+
+        let proposal = json!(_credential_proposal_for_cred_def(CRED_DEF_ID, SCHEMA_ID)).to_string();
+
+        let handle = issuer_credential_create_from_proposal(create_cred_def_fake(),
+                                                            "1".to_string(),
+                                                            &proposal,
+                                                            None,
+                                                            "credential_name".to_string()).unwrap();
+        assert_eq!(handle.get_state().unwrap(), VcxStateType::VcxStateInitialized as u32);
+
+        let proposal = json!(_credential_proposal_for_cred_def("other:id", SCHEMA_ID)).to_string();
+        let err = issuer_credential_create_from_proposal(create_cred_def_fake(),
+                                                         "1".to_string(),
+                                                         &proposal,
+                                                         None,
+                                                         "credential_name".to_string()).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidCredDefHandle, err.kind());
+
+        let err = issuer_credential_create_from_proposal(create_cred_def_fake(),
+                                                         "1".to_string(),
+                                                         "{}",
+                                                         None,
+                                                         "credential_name".to_string()).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidJson, err.kind());
+    }
+
     #[cfg(feature = "pool_tests")]
     #[test]
     fn test_generate_cred_offer() {
@@ -1236,21 +1425,34 @@ pub mod tests {
         credential.tails_file = Some(get_temp_dir_path(TEST_TAILS_FILE).to_str().unwrap().to_string());
         credential.cred_rev_id = None;
         credential.rev_reg_id = None;
-        assert_eq!(credential.revoke_cred().unwrap_err().kind(), VcxErrorKind::InvalidRevocationDetails);
+        assert_eq!(credential.revoke_cred(true).unwrap_err().kind(), VcxErrorKind::InvalidRevocationDetails);
         credential.tails_file = None;
         credential.cred_rev_id = Some(CRED_REV_ID.to_string());
         credential.rev_reg_id = None;
-        assert_eq!(credential.revoke_cred().unwrap_err().kind(), VcxErrorKind::InvalidRevocationDetails);
+        assert_eq!(credential.revoke_cred(true).unwrap_err().kind(), VcxErrorKind::InvalidRevocationDetails);
         credential.tails_file = None;
         credential.cred_rev_id = None;
         credential.rev_reg_id = Some(REV_REG_ID.to_string());
-        assert_eq!(credential.revoke_cred().unwrap_err().kind(), VcxErrorKind::InvalidRevocationDetails);
+        assert_eq!(credential.revoke_cred(true).unwrap_err().kind(), VcxErrorKind::InvalidRevocationDetails);
 
         credential.tails_file = Some(get_temp_dir_path(TEST_TAILS_FILE).to_str().unwrap().to_string());
         credential.cred_rev_id = Some(CRED_REV_ID.to_string());
         credential.rev_reg_id = Some(REV_REG_ID.to_string());
 
-        credential.revoke_cred().unwrap();
+        credential.revoke_cred(true).unwrap();
+        credential.revoke_cred(false).unwrap();
+    }
+
+    #[test]
+    fn test_revocation_notification_options_from_opt_str() {
+        assert_eq!(RevocationNotificationOptions::default(), RevocationNotificationOptions::from_opt_str(None).unwrap());
+        assert_eq!(RevocationNotificationOptions::default(), RevocationNotificationOptions::from_opt_str(Some(" ")).unwrap());
+
+        let options = RevocationNotificationOptions::from_opt_str(Some(r#"{"version": "2.0", "comment": "Expired"}"#)).unwrap();
+        assert_eq!(Some(String::from("2.0")), options.version);
+        assert_eq!(Some(String::from("Expired")), options.comment);
+
+        assert_eq!(VcxErrorKind::InvalidJson, RevocationNotificationOptions::from_opt_str(Some("invalid")).unwrap_err().kind());
     }
 
 
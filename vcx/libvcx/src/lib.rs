@@ -24,6 +24,7 @@ pub mod settings;
 pub mod agent;
 
 pub mod api;
+pub mod async_api;
 pub mod connection;
 pub mod issuer_credential;
 pub mod proof;
@@ -33,6 +34,7 @@ pub mod error;
 pub mod credential;
 pub mod disclosed_proof;
 pub mod wallet_backup;
+pub mod session;
 pub mod aries;
 pub mod legacy;
 
@@ -25,6 +25,7 @@ use crate::utils::qualifier;
 use crate::legacy::messages::proof_presentation::proof_message::get_credential_info;
 
 use crate::aries::handlers::proof_presentation::verifier::Verifier;
+use crate::aries::messages::a2a::A2AMessage;
 use crate::agent::agent_info::{get_agent_info, MyAgentInfo, get_agent_attr};
 use crate::aries::messages::proof_presentation::presentation_proposal::PresentationProposal;
 use crate::utils::libindy::ledger::query::Query;
@@ -35,7 +36,7 @@ lazy_static! {
     static ref PROOF_MAP: ObjectCache<Proofs> = Default::default();
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "version", content = "data")]
 pub enum Proofs {
     #[serde(rename = "3.0")]
@@ -534,6 +535,31 @@ impl Proof {
     }
 }
 
+/// Passes a message received through the inbound endpoint to the Aries Proof objects.
+/// Returns the handle of the object that consumed the message.
+pub fn handle_inbound_message(recipient_vk: &str, message: &A2AMessage) -> VcxResult<Option<Handle<Proofs>>> {
+    PROOF_MAP.find_mut(
+        |proof| match proof {
+            Proofs::Pending(_) |
+            Proofs::V1(_) => false,
+            Proofs::V3(proof) => proof.accepts_inbound_message(recipient_vk, message)
+        },
+        |proof| match proof {
+            Proofs::Pending(_) |
+            Proofs::V1(_) => Ok(()),
+            Proofs::V3(proof) => proof.handle_inbound_message(recipient_vk, message).map(|_| ())
+        },
+    )
+}
+
+/// Whether any Aries Proof object started over the Connection `connection_id` (see `AgentInfo::connection_id`) is in progress.
+pub fn is_in_progress_over(connection_id: &str) -> VcxResult<bool> {
+    PROOF_MAP.filter(|proof| match proof {
+        Proofs::V3(proof) => Ok(proof.is_in_progress_over(connection_id)),
+        _ => Ok(false)
+    }).map(|handles| !handles.is_empty())
+}
+
 fn handle_err(err: VcxError) -> VcxError {
     if err.kind() == VcxErrorKind::InvalidHandle {
         VcxError::from(VcxErrorKind::InvalidProofHandle)
@@ -614,6 +640,10 @@ pub fn release_all() {
     PROOF_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<Proofs>> {
+    PROOF_MAP.handles()
+}
+
 pub fn from_string(proof_data: &str) -> VcxResult<Handle<Proofs>> {
     let proof: Proofs = serde_json::from_str(proof_data)
         .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot parse Proofs object from JSON string. Err: {:?}", err)))?;
@@ -220,6 +220,10 @@ pub fn release_all() {
     SCHEMA_MAP.drain().ok();
 }
 
+pub fn all_handles() -> Vec<Handle<CreateSchema>> {
+    SCHEMA_MAP.handles()
+}
+
 
 #[cfg(test)]
 pub mod tests {
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::utils::object_cache::Handle;
+use crate::connection::{self, Connections};
+use crate::issuer_credential::{self, IssuerCredentials};
+use crate::credential::{self, Credentials};
+use crate::proof::{self, Proofs};
+use crate::disclosed_proof::{self, DisclosedProofs};
+use crate::schema::{self, CreateSchema};
+use crate::credential_def::{self, CredentialDef};
+use crate::wallet_backup::{self, WalletBackup};
+
+/*
+    Snapshot of all live objects (connections, issuer credentials, credentials, proofs, disclosed proofs,
+    schemas, credential definitions and wallet backups) as a single versioned document.
+
+    Every object is stored in the same format as its `serialize` function returns, next to the handle it had.
+    Restored objects get new handles, so restore returns the mapping of old handles to new ones per object type.
+    Handles referring to other objects (credential definition of proprietary issuer credentials) are remapped as well.
+*/
+
+pub const SESSION_SNAPSHOT_VERSION: &str = "1.0";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionSnapshot {
+    pub version: String,
+    #[serde(default)]
+    pub connections: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub issuer_credentials: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub credentials: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub proofs: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub disclosed_proofs: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub schemas: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub credential_defs: Vec<SnapshotEntry>,
+    #[serde(default)]
+    pub wallet_backups: Vec<SnapshotEntry>,
+}
+
+/// Serialized object with the handle it had when the snapshot was taken.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnapshotEntry {
+    pub handle: u32,
+    pub data: Value,
+}
+
+/// Handles of restored objects: old handle -> new handle.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HandleMapping {
+    pub connections: HashMap<u32, u32>,
+    pub issuer_credentials: HashMap<u32, u32>,
+    pub credentials: HashMap<u32, u32>,
+    pub proofs: HashMap<u32, u32>,
+    pub disclosed_proofs: HashMap<u32, u32>,
+    pub schemas: HashMap<u32, u32>,
+    pub credential_defs: HashMap<u32, u32>,
+    pub wallet_backups: HashMap<u32, u32>,
+}
+
+/// Serializes all live objects into a single snapshot document.
+pub fn snapshot() -> VcxResult<String> {
+    trace!("session::snapshot >>>");
+
+    let snapshot = SessionSnapshot {
+        version: SESSION_SNAPSHOT_VERSION.to_string(),
+        connections: _snapshot(connection::all_handles(), Handle::<Connections>::to_string)?,
+        issuer_credentials: _snapshot(issuer_credential::all_handles(), Handle::<IssuerCredentials>::to_string)?,
+        credentials: _snapshot(credential::all_handles(), Handle::<Credentials>::to_string)?,
+        proofs: _snapshot(proof::all_handles(), Handle::<Proofs>::to_string)?,
+        disclosed_proofs: _snapshot(disclosed_proof::all_handles(), Handle::<DisclosedProofs>::to_string)?,
+        schemas: _snapshot(schema::all_handles(), Handle::<CreateSchema>::to_string)?,
+        credential_defs: _snapshot(credential_def::all_handles(), Handle::<CredentialDef>::to_string)?,
+        wallet_backups: _snapshot(wallet_backup::all_handles(), Handle::<WalletBackup>::to_string)?,
+    };
+
+    debug!("session::snapshot: taken snapshot of {} connections", snapshot.connections.len());
+
+    ::serde_json::to_string(&snapshot)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::SerializationError, format!("Cannot serialize session snapshot: {}", err)))
+}
+
+/// Restores all objects of the snapshot.
+/// Either all objects are restored or none of them (objects restored before a failure are released).
+pub fn restore(snapshot: &str) -> VcxResult<HandleMapping> {
+    trace!("session::restore >>>");
+
+    let snapshot: SessionSnapshot = ::serde_json::from_str(snapshot)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot parse session snapshot: {}", err)))?;
+
+    if snapshot.version != SESSION_SNAPSHOT_VERSION {
+        return Err(VcxError::from_msg(VcxErrorKind::InvalidJson,
+                                      format!("Unsupported session snapshot version: {:?}. Expected: {:?}", snapshot.version, SESSION_SNAPSHOT_VERSION)));
+    }
+
+    let mut restore = Restore::default();
+
+    match restore.restore_all(&snapshot) {
+        Ok(mapping) => {
+            trace!("session::restore <<< mapping: {:?}", mapping);
+            Ok(mapping)
+        }
+        Err(err) => {
+            warn!("session::restore: Cannot restore session snapshot, releasing restored objects. Err: {}", err);
+            restore.rollback();
+            Err(err)
+        }
+    }
+}
+
+fn _snapshot<T>(handles: Vec<Handle<T>>, to_string: fn(Handle<T>) -> VcxResult<String>) -> VcxResult<Vec<SnapshotEntry>> {
+    handles.into_iter()
+        .map(|handle| {
+            let data = ::serde_json::from_str(&to_string(handle)?)
+                .map_err(|err| VcxError::from_msg(VcxErrorKind::SerializationError, format!("Cannot add object {} to session snapshot: {}", handle, err)))?;
+            Ok(SnapshotEntry { handle: handle.into(), data })
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Restore {
+    release: Vec<Box<dyn Fn()>>,
+}
+
+impl Restore {
+    fn restore_all(&mut self, snapshot: &SessionSnapshot) -> VcxResult<HandleMapping> {
+        // objects referred by other objects go first
+        let schemas = self.restore(&snapshot.schemas, schema::from_string, Handle::<CreateSchema>::release)?;
+        let credential_defs = self.restore(&snapshot.credential_defs, credential_def::from_string, Handle::<CredentialDef>::release)?;
+
+        let issuer_credentials = snapshot.issuer_credentials.iter()
+            .map(|entry| _remap_cred_def_handle(entry, &credential_defs))
+            .collect::<Vec<SnapshotEntry>>();
+
+        Ok(HandleMapping {
+            connections: self.restore(&snapshot.connections, connection::from_string, Handle::<Connections>::release)?,
+            issuer_credentials: self.restore(&issuer_credentials, issuer_credential::from_string, Handle::<IssuerCredentials>::release)?,
+            credentials: self.restore(&snapshot.credentials, credential::from_string, Handle::<Credentials>::release)?,
+            proofs: self.restore(&snapshot.proofs, proof::from_string, Handle::<Proofs>::release)?,
+            disclosed_proofs: self.restore(&snapshot.disclosed_proofs, disclosed_proof::from_string, Handle::<DisclosedProofs>::release)?,
+            wallet_backups: self.restore(&snapshot.wallet_backups, wallet_backup::from_string, Handle::<WalletBackup>::release)?,
+            schemas,
+            credential_defs,
+        })
+    }
+
+    fn restore<T: 'static>(&mut self,
+                           entries: &[SnapshotEntry],
+                           from_string: fn(&str) -> VcxResult<Handle<T>>,
+                           release: fn(Handle<T>) -> VcxResult<()>) -> VcxResult<HashMap<u32, u32>> {
+        let mut handles = HashMap::new();
+
+        for entry in entries {
+            let handle = from_string(&entry.data.to_string())
+                .map_err(|err| VcxError::from_msg(err.kind(), format!("Cannot restore object {} of session snapshot: {}", entry.handle, err)))?;
+
+            self.release.push(Box::new(move || { release(handle).ok(); }));
+            handles.insert(entry.handle, handle.into());
+        }
+
+        Ok(handles)
+    }
+
+    fn rollback(self) {
+        for release in self.release {
+            release();
+        }
+    }
+}
+
+// proprietary issuer credentials keep the handle of their credential definition
+fn _remap_cred_def_handle(entry: &SnapshotEntry, credential_defs: &HashMap<u32, u32>) -> SnapshotEntry {
+    let mut entry = entry.clone();
+
+    let new_handle = entry.data["data"]["cred_def_handle"].as_u64()
+        .and_then(|handle| credential_defs.get(&(handle as u32)));
+
+    if let Some(new_handle) = new_handle {
+        entry.data["data"]["cred_def_handle"] = json!(new_handle);
+    }
+
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupMocks;
+
+    #[test]
+    fn test_session_snapshot_and_restore_works() {
+        let _setup = SetupMocks::init();
+
+        let connection_handle = connection::create_connection("session_snapshot").unwrap();
+        let connection = connection_handle.to_string().unwrap();
+
+        let snapshot = snapshot().unwrap();
+
+        connection::release_all();
+
+        let mapping = restore(&snapshot).unwrap();
+
+        let restored_handle = mapping.connections[&u32::from(connection_handle)];
+        let restored = connection::all_handles().into_iter().find(|handle| *handle == restored_handle).unwrap();
+        assert_eq!(connection, restored.to_string().unwrap());
+    }
+
+    #[test]
+    fn test_session_restore_is_atomic() {
+        let _setup = SetupMocks::init();
+
+        connection::create_connection("session_restore").unwrap();
+
+        let mut snapshot: SessionSnapshot = ::serde_json::from_str(&snapshot().unwrap()).unwrap();
+        snapshot.proofs.push(SnapshotEntry { handle: 1, data: json!({"version": "1.0", "data": "invalid"}) });
+
+        connection::release_all();
+
+        restore(&json!(snapshot).to_string()).unwrap_err();
+        assert!(connection::all_handles().is_empty());
+    }
+
+    #[test]
+    fn test_session_restore_remaps_cred_def_handle() {
+        let entry = SnapshotEntry { handle: 1, data: json!({"version": "1.0", "data": {"cred_def_handle": 10}}) };
+
+        let entry = _remap_cred_def_handle(&entry, &map!(10 => 20));
+        assert_eq!(json!(20), entry.data["data"]["cred_def_handle"]);
+    }
+
+    #[test]
+    fn test_session_restore_rejects_unknown_version() {
+        let _setup = SetupMocks::init();
+
+        let err = restore(&json!({"version": "2.0"}).to_string()).unwrap_err();
+        assert_eq!(VcxErrorKind::InvalidJson, err.kind());
+    }
+}
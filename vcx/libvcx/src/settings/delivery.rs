@@ -0,0 +1,183 @@
+use std::time::Duration;
+use rand::Rng;
+
+use crate::settings;
+use crate::error::prelude::*;
+
+pub static DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 20;
+pub static DEFAULT_READ_TIMEOUT_SECS: u64 = 50;
+pub static DEFAULT_MAX_ATTEMPTS: u32 = 3;
+pub static DEFAULT_INITIAL_BACKOFF_MS: u64 = 500;
+pub static DEFAULT_MAX_BACKOFF_MS: u64 = 10_000;
+// statuses meaning the remote side did not accept the request, so a retry cannot deliver the message twice
+pub static DEFAULT_RETRYABLE_STATUSES: [u16; 4] = [408, 429, 502, 503];
+
+/// Policy applied to outbound HTTP deliveries (agency requests and messages sent to remote endpoints).
+///
+/// Can be set globally through the `http_delivery_policy` config option, e.g.:
+/// {"http_delivery_policy": {"max_attempts": 5, "initial_backoff_ms": 1000, "retryable_statuses": [503]}}
+/// Omitted fields take their default values.
+///
+/// A POST failing after the connection was established (e.g. read timeout) may already be accepted by the remote side,
+/// so it is retried only if `retry_unconfirmed` is set. Connect errors and `retryable_statuses` are always retried.
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq)]
+#[serde(default)]
+pub struct DeliveryPolicy {
+    pub connect_timeout_secs: u64,
+    pub read_timeout_secs: u64,
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub jitter: bool,
+    pub retryable_statuses: Vec<u16>,
+    pub respect_retry_after: bool,
+    pub retry_unconfirmed: bool,
+}
+
+impl Default for DeliveryPolicy {
+    fn default() -> DeliveryPolicy {
+        DeliveryPolicy {
+            connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
+            read_timeout_secs: DEFAULT_READ_TIMEOUT_SECS,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff_ms: DEFAULT_INITIAL_BACKOFF_MS,
+            max_backoff_ms: DEFAULT_MAX_BACKOFF_MS,
+            jitter: true,
+            retryable_statuses: DEFAULT_RETRYABLE_STATUSES.to_vec(),
+            respect_retry_after: true,
+            retry_unconfirmed: false,
+        }
+    }
+}
+
+impl DeliveryPolicy {
+    pub fn from_str(policy: &str) -> VcxResult<DeliveryPolicy> {
+        let policy: DeliveryPolicy = ::serde_json::from_str(policy)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidConfiguration,
+                                              format!("Cannot parse HTTP delivery policy from JSON. Err: {:?}", err)))?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn validate(&self) -> VcxResult<()> {
+        if self.max_attempts == 0 {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidConfiguration, "HTTP delivery policy: `max_attempts` must be greater than 0"));
+        }
+        if self.read_timeout_secs == 0 || self.connect_timeout_secs == 0 {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidConfiguration, "HTTP delivery policy: timeouts must be greater than 0"));
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidConfiguration, "HTTP delivery policy: `initial_backoff_ms` must not exceed `max_backoff_ms`"));
+        }
+        Ok(())
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn read_timeout(&self) -> Duration {
+        Duration::from_secs(self.read_timeout_secs)
+    }
+
+    pub fn is_retryable_status(&self, status: u16) -> bool {
+        self.retryable_statuses.contains(&status)
+    }
+
+    /// Delay to wait after the failed attempt number `attempt` (starting from 1).
+    /// The delay doubles with every attempt up to `max_backoff_ms`.
+    /// With `jitter` enabled a random delay between the half and the full value is taken.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(32);
+        let delay = self.initial_backoff_ms
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_backoff_ms);
+
+        let delay = if self.jitter && delay > 1 {
+            delay / 2 + rand::thread_rng().gen_range(0, delay / 2 + 1)
+        } else {
+            delay
+        };
+
+        Duration::from_millis(delay)
+    }
+
+    /// Delay requested by the remote side through the `Retry-After` header, limited by `max_backoff_ms`.
+    pub fn retry_after(&self, retry_after: Option<&str>) -> Option<Duration> {
+        if !self.respect_retry_after {
+            return None;
+        }
+
+        retry_after
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(|secs| Duration::from_millis(secs.saturating_mul(1000).min(self.max_backoff_ms)))
+    }
+}
+
+pub fn get_delivery_policy() -> VcxResult<DeliveryPolicy> {
+    match settings::get_opt_config_value(settings::CONFIG_HTTP_DELIVERY_POLICY) {
+        Some(policy) => DeliveryPolicy::from_str(&policy),
+        None => Ok(DeliveryPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupDefaults;
+
+    #[test]
+    fn test_delivery_policy_defaults_for_missing_fields() {
+        let _setup = SetupDefaults::init();
+
+        let policy = DeliveryPolicy::from_str(r#"{"max_attempts": 5, "retryable_statuses": [503]}"#).unwrap();
+        assert_eq!(5, policy.max_attempts);
+        assert_eq!(DEFAULT_READ_TIMEOUT_SECS, policy.read_timeout_secs);
+        assert!(policy.is_retryable_status(503));
+        assert!(!policy.is_retryable_status(502));
+        assert!(!policy.retry_unconfirmed);
+
+        assert_eq!(DeliveryPolicy::default(), get_delivery_policy().unwrap());
+    }
+
+    #[test]
+    fn test_delivery_policy_validation() {
+        let _setup = SetupDefaults::init();
+
+        assert_eq!(VcxErrorKind::InvalidConfiguration, DeliveryPolicy::from_str(r#"{"max_attempts": 0}"#).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidConfiguration, DeliveryPolicy::from_str(r#"{"initial_backoff_ms": 100, "max_backoff_ms": 10}"#).unwrap_err().kind());
+        assert_eq!(VcxErrorKind::InvalidConfiguration, DeliveryPolicy::from_str(r#"{"max_attempts": "many"}"#).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_delivery_policy_backoff() {
+        let _setup = SetupDefaults::init();
+
+        let policy = DeliveryPolicy { initial_backoff_ms: 100, max_backoff_ms: 1000, jitter: false, ..DeliveryPolicy::default() };
+        assert_eq!(Duration::from_millis(100), policy.backoff(1));
+        assert_eq!(Duration::from_millis(200), policy.backoff(2));
+        assert_eq!(Duration::from_millis(800), policy.backoff(4));
+        assert_eq!(Duration::from_millis(1000), policy.backoff(5));
+        assert_eq!(Duration::from_millis(1000), policy.backoff(100));
+
+        let policy = DeliveryPolicy { jitter: true, ..policy };
+        for _ in 0..20 {
+            let delay = policy.backoff(3);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+        }
+    }
+
+    #[test]
+    fn test_delivery_policy_retry_after() {
+        let _setup = SetupDefaults::init();
+
+        let policy = DeliveryPolicy { max_backoff_ms: 5000, ..DeliveryPolicy::default() };
+        assert_eq!(Some(Duration::from_secs(2)), policy.retry_after(Some("2")));
+        assert_eq!(Some(Duration::from_secs(5)), policy.retry_after(Some("120")));
+        assert_eq!(None, policy.retry_after(Some("Wed, 21 Oct 2015 07:28:00 GMT")));
+        assert_eq!(None, policy.retry_after(None));
+
+        let policy = DeliveryPolicy { respect_retry_after: false, ..policy };
+        assert_eq!(None, policy.retry_after(Some("2")));
+    }
+}
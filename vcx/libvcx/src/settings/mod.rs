@@ -3,6 +3,7 @@ pub mod pool;
 pub mod environment;
 pub mod wallet;
 pub mod protocol;
+pub mod delivery;
 
 use std::collections::HashMap;
 use std::sync::RwLock;
@@ -17,6 +18,7 @@ use crate::error::prelude::*;
 use crate::utils::file::read_file;
 use crate::aries::messages::a2a::protocol_registry::Actors;
 use crate::settings::protocol::ProtocolTypes;
+use crate::aries::utils::encryption_envelope::EnvelopeVersion;
 
 pub static CONFIG_POOL_NAME: &str = "pool_name";
 pub static CONFIG_PROTOCOL_TYPE: &str = "protocol_type";
@@ -52,6 +54,11 @@ pub static CONFIG_ACTORS: &str = "actors"; // inviter, invitee, issuer, holder,
 pub static CONFIG_POOL_NETWORKS: &str = "pool_networks";
 pub static CONFIG_USE_LATEST_PROTOCOLS: &'static str = "use_latest_protocols";
 pub static CONFIG_INDY_POOL_NETWORKS: &str = "indy_pool_networks";
+pub static CONFIG_HTTP_DELIVERY_POLICY: &str = "http_delivery_policy";
+pub static CONFIG_DIDCOMM_ENVELOPE_VERSION: &str = "didcomm_envelope_version"; // v1, v2
+pub static CONFIG_DID_RESOLVER_CACHE_TTL: &str = "did_resolver_cache_ttl"; // seconds, 0 disables caching
+pub static CONFIG_MESSAGE_HISTORY: &str = "message_history"; // true, false
+pub static CONFIG_REVOCATION_PUBLISH_INTERVAL: &str = "revocation_publish_interval"; // seconds
 
 pub static UNINITIALIZED_WALLET_KEY: &str = "<KEY_IS_NOT_SET>";
 pub static DEFAULT_GENESIS_PATH: &str = "genesis.txn";
@@ -157,6 +164,11 @@ pub fn validate_config(config: &HashMap<String, String>) -> VcxResult<u32> {
     validate_optional_config_val(config.get(CONFIG_INSTITUTION_LOGO_URL), VcxErrorKind::InvalidUrl, Url::parse)?;
 
     validate_optional_config_val(config.get(CONFIG_ACTORS), VcxErrorKind::InvalidConfiguration, validation::validate_actors)?;
+    validate_optional_config_val(config.get(CONFIG_HTTP_DELIVERY_POLICY), VcxErrorKind::InvalidConfiguration, delivery::DeliveryPolicy::from_str)?;
+    validate_optional_config_val(config.get(CONFIG_DIDCOMM_ENVELOPE_VERSION), VcxErrorKind::InvalidConfiguration, EnvelopeVersion::from_str)?;
+    validate_optional_config_val(config.get(CONFIG_DID_RESOLVER_CACHE_TTL), VcxErrorKind::InvalidConfiguration, |ttl: &str| ttl.parse::<u64>())?;
+    validate_optional_config_val(config.get(CONFIG_MESSAGE_HISTORY), VcxErrorKind::InvalidConfiguration, |enabled: &str| enabled.parse::<bool>())?;
+    validate_optional_config_val(config.get(CONFIG_REVOCATION_PUBLISH_INTERVAL), VcxErrorKind::InvalidConfiguration, |interval: &str| interval.parse::<u64>())?;
 
     trace!("validate_config <<<");
 
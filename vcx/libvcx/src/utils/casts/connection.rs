@@ -56,6 +56,12 @@ impl From<(ConnectionV1, ActorDidExchangeState)> for ConnectionV3 {
             pw_vk: connection.pw_verkey.clone(),
             agent_did: connection.agent_did.clone(),
             agent_vk: connection.agent_vk.clone(),
+            key_agreement_key: None,
+            return_route: None,
+            mediator: None,
+            peer_did: None,
+            delivery_policy: None,
+            connection_id: String::new(),
         };
 
         ConnectionV3::from_parts(connection.source_id.clone(), agent_info, state)
@@ -83,6 +89,12 @@ impl From<(&ConnectionV1, &InviteDetail, ConnectionUpgradeInfo)> for ConnectionV
                     pw_vk: connection.pw_verkey.clone(),
                     agent_did: connection.agent_did.clone(),
                     agent_vk: connection.agent_vk.clone(),
+                    key_agreement_key: None,
+                    return_route: None,
+                    mediator: None,
+                    peer_did: None,
+                    delivery_policy: None,
+                    connection_id: String::new(),
                 },
                 state: ActorDidExchangeState::Invitee(DidExchangeState::Completed(CompleteState {
                     invitation: Some(Invitations::ConnectionInvitation(InvitationV3 {
@@ -98,6 +110,8 @@ impl From<(&ConnectionV1, &InviteDetail, ConnectionUpgradeInfo)> for ConnectionV
                     did_doc,
                     protocols: None,
                     thread: Thread::default(),
+                    did_rotation: None,
+                    retired_agents: Vec::new(),
                 })),
             }
         }
@@ -169,6 +183,8 @@ impl From<(&ConnectionV3, ConnectionUpgradeInfo)> for ConnectionV3 {
                     did_doc,
                     protocols: None,
                     thread: connection.connection_sm.thread().cloned().unwrap_or_default(),
+                    did_rotation: None,
+                    retired_agents: Vec::new(),
                 })),
             }
         }
@@ -398,14 +398,35 @@ pub const C_AGENCY_VERKEY: &'static str = "FKGV9jKvorzKPtPJPNLZkYPkLhiS1VbxdvBgd
 //pub const C_AGENCY_VERKEY: &'static str = "BjpTLofEbVYJ8xxXQxScbmubHsgpHY5uvScfXqW9B1vB";
 
 /* DEV RC */
+#[cfg(not(feature = "local_agency"))]
 pub const AGENCY_ENDPOINT: &'static str = "https://eas.pdev.evernym.com";
+#[cfg(not(feature = "local_agency"))]
 pub const AGENCY_DID: &'static str = "LTjTWsezEmV4wJYD5Ufxvk";
+#[cfg(not(feature = "local_agency"))]
 pub const AGENCY_VERKEY: &'static str = "BcCSmgdfChLqmtBkkA26YotWVFBNnyY45WCnQziF4cqN";
 
+#[cfg(not(feature = "local_agency"))]
 pub const C_AGENCY_ENDPOINT: &'static str = "https://agency.pdev.evernym.com";
+#[cfg(not(feature = "local_agency"))]
 pub const C_AGENCY_DID: &'static str = "LiLBGgFarh954ZtTByLM1C";
+#[cfg(not(feature = "local_agency"))]
 pub const C_AGENCY_VERKEY: &'static str = "Bk9wFrud3rz8v3nAFKGib6sQs8zHWzZxfst7Wh3Mbc9W";
 
+/* local agency -- `cargo run --features local_agency --bin local_agency` (both sides use the same agency) */
+#[cfg(feature = "local_agency")]
+pub const AGENCY_ENDPOINT: &'static str = "http://127.0.0.1:8080";
+#[cfg(feature = "local_agency")]
+pub const AGENCY_DID: &'static str = "BjGbYpN8tkNWPcK4yc2QHk";
+#[cfg(feature = "local_agency")]
+pub const AGENCY_VERKEY: &'static str = "6rA9VPXN5PHCrZnn3iNU8S6od4qDVn5a5yZdcp66byqR";
+
+#[cfg(feature = "local_agency")]
+pub const C_AGENCY_ENDPOINT: &'static str = AGENCY_ENDPOINT;
+#[cfg(feature = "local_agency")]
+pub const C_AGENCY_DID: &'static str = AGENCY_DID;
+#[cfg(feature = "local_agency")]
+pub const C_AGENCY_VERKEY: &'static str = AGENCY_VERKEY;
+
 /* DEV Team 1 */
 // pub const AGENCY_ENDPOINT: &'static str = "https://eas-team1.pdev.evernym.com";
 // pub const AGENCY_DID: &'static str = "CV65RFpeCtPu82hNF9i61G";
@@ -1,14 +1,98 @@
 use crate::settings;
-use std::io::Read;
-use std::sync::Mutex;
-use reqwest::header::CONTENT_TYPE;
+use std::io::{ErrorKind, Read};
+use std::sync::{Arc, Mutex, RwLock};
+use std::collections::HashMap;
+use reqwest::header::{CONTENT_TYPE, RETRY_AFTER};
 use std::env;
 use crate::error::prelude::*;
 use reqwest::ClientBuilder;
 use crate::error::agency_error::AgencyError;
+use crate::settings::delivery::{DeliveryPolicy, get_delivery_policy};
+
+pub static DIDCOMM_ENVELOPE_CONTENT_TYPE: &str = "application/didcomm-envelope-enc";
 
 lazy_static! {
     static ref AGENCY_MOCK: Mutex<AgencyMock> = Mutex::new(AgencyMock::default());
+    static ref TRANSPORTS: RwLock<HashMap<String, Arc<dyn Transport>>> = RwLock::new(HashMap::new());
+}
+
+/// Delivers packed outbound messages to a remote endpoint.
+///
+/// Every outbound message (agency requests and Aries messages sent to a remote DIDDoc endpoint)
+/// goes through a `Transport` selected by the scheme of the destination endpoint.
+/// `HttpTransport` is used for every scheme which has no transport registered.
+pub trait Transport: Send + Sync {
+    /// Sends `body` to `endpoint`.
+    /// Returns the synchronous reply if the transport received one.
+    fn send(&self, body: &[u8], endpoint: &str) -> VcxResult<Option<Vec<u8>>>;
+}
+
+impl<F> Transport for F where F: Fn(&[u8], &str) -> VcxResult<Option<Vec<u8>>> + Send + Sync {
+    fn send(&self, body: &[u8], endpoint: &str) -> VcxResult<Option<Vec<u8>>> {
+        self(body, endpoint)
+    }
+}
+
+/// Default transport sending messages as HTTP POST requests.
+/// Timeouts and retries are driven by its `DeliveryPolicy`.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    pub policy: DeliveryPolicy,
+    pub content_type: String,
+}
+
+impl HttpTransport {
+    pub fn new(policy: DeliveryPolicy) -> HttpTransport {
+        HttpTransport {
+            policy,
+            content_type: DIDCOMM_ENVELOPE_CONTENT_TYPE.to_string(),
+        }
+    }
+}
+
+impl Default for HttpTransport {
+    fn default() -> HttpTransport {
+        HttpTransport::new(DeliveryPolicy::default())
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, body: &[u8], endpoint: &str) -> VcxResult<Option<Vec<u8>>> {
+        let response = send_http_message(body, endpoint, RequestType::POST, self)?;
+        Ok(if response.is_empty() { None } else { Some(response) })
+    }
+}
+
+/// Registers `transport` to be used for all endpoints with the given `scheme` (e.g. `ws`, `unix`, `mem`).
+/// Registering a transport for `http` or `https` replaces the default `HttpTransport` for those endpoints.
+pub fn register_transport(scheme: &str, transport: Arc<dyn Transport>) -> VcxResult<()> {
+    trace!("register_transport >>> scheme: {}", scheme);
+
+    TRANSPORTS.write()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot register transport: lock is poisoned"))?
+        .insert(scheme.to_lowercase(), transport);
+
+    Ok(())
+}
+
+/// Removes the transport registered for the given `scheme`.
+pub fn unregister_transport(scheme: &str) -> VcxResult<()> {
+    trace!("unregister_transport >>> scheme: {}", scheme);
+
+    TRANSPORTS.write()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot unregister transport: lock is poisoned"))?
+        .remove(&scheme.to_lowercase());
+
+    Ok(())
+}
+
+fn endpoint_scheme(endpoint: &str) -> String {
+    endpoint.splitn(2, ':').next().unwrap_or_default().to_lowercase()
+}
+
+fn get_registered_transport(endpoint: &str) -> Option<Arc<dyn Transport>> {
+    TRANSPORTS.read().ok()
+        .and_then(|transports| transports.get(&endpoint_scheme(endpoint)).cloned())
 }
 
 #[derive(Default)]
@@ -45,62 +129,147 @@ pub fn get_status() -> VcxResult<Vec<u8>> {
 }
 
 pub fn post_message(body_content: &[u8], url: &str) -> VcxResult<Vec<u8>> {
-    send_http_message(body_content, url, RequestType::POST)
+    send_message(body_content, url)
+        .map(Option::unwrap_or_default)
 }
 
-pub fn get_message(url: &str) -> VcxResult<Vec<u8>> {
-    send_http_message(&[], url, RequestType::GET)
+/// Sends `body_content` to `url` using the transport registered for the endpoint scheme.
+/// Returns the synchronous reply if there was one.
+/// HTTP deliveries follow the `DeliveryPolicy` set in the library config.
+pub fn send_message(body_content: &[u8], url: &str) -> VcxResult<Option<Vec<u8>>> {
+    send_message_with_policy(body_content, url, DIDCOMM_ENVELOPE_CONTENT_TYPE, None)
+}
+
+/// Same as `send_message` but HTTP requests carry the given `Content-Type` header.
+pub fn send_message_with_content_type(body_content: &[u8], url: &str, content_type: &str) -> VcxResult<Option<Vec<u8>>> {
+    send_message_with_policy(body_content, url, content_type, None)
+}
+
+/// Same as `send_message_with_content_type` but HTTP delivery follows the given `policy` instead of the configured one.
+/// The policy is not applied to custom transports registered for the endpoint scheme.
+pub fn send_message_with_policy(body_content: &[u8], url: &str, content_type: &str, policy: Option<&DeliveryPolicy>) -> VcxResult<Option<Vec<u8>>> {
+    if settings::agency_mocks_enabled() {
+        return AgencyMock::get_response().map(|s| Some(s.to_vec()));
+    }
+
+    if let Some(transport) = get_registered_transport(url) {
+        return transport.send(body_content, url);
+    }
+
+    let policy = match policy {
+        Some(policy) => {
+            policy.validate()?;
+            policy.clone()
+        }
+        None => get_delivery_policy()?
+    };
+
+    HttpTransport { content_type: content_type.to_string(), ..HttpTransport::new(policy) }.send(body_content, url)
 }
 
-fn send_http_message(body_content: &[u8], url: &str, request_type: RequestType) -> VcxResult<Vec<u8>> {
+pub fn get_message(url: &str) -> VcxResult<Vec<u8>> {
     if settings::agency_mocks_enabled() {
         return AgencyMock::get_response().map(|s| s.to_vec());
     }
 
+    send_http_message(&[], url, RequestType::GET, &HttpTransport::new(get_delivery_policy()?))
+}
+
+fn send_http_message(body_content: &[u8], url: &str, request_type: RequestType, transport: &HttpTransport) -> VcxResult<Vec<u8>> {
     //Setting SSL Certs location. This is needed on android platform. Or openssl will fail to verify the certs
     if cfg!(target_os = "android") {
         info!("::Android code");
         set_ssl_cert_location();
     }
+    let policy = &transport.policy;
+
     let client =
         ClientBuilder::new()
-            .timeout(std::time::Duration::from_secs(50))
+            .connect_timeout(policy.connect_timeout())
+            .timeout(policy.read_timeout())
             .build()
             .map_err(|err| VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Could not prepare HTTP client. Err: {:?}", err)))?;
 
-    info!("Posting encrypted bundle to: \"{}\"", secret!(url));
-
-    let mut response = match request_type {
-        RequestType::POST => client.post(url)
-            .body(body_content.to_owned())
-            .header(CONTENT_TYPE, "application/didcomm-envelope-enc"),
-        RequestType::GET => client.get(url)
-    }.send()
-     .map_err(|err| {
-         error!("error: {}", err);
-         VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Could send HTTP message. Error: {:?}", err))
-     })?;
-
-    info!("Response received: url {:?}", secret!(url));
-
-    trace!("Response Header: {:?}", response);
-    if !response.status().is_success() {
-        let mut content = String::new();
-        match response.read_to_string(&mut content) {
-            Ok(_) => info!("Request failed: {}", content),
-            Err(_) => info!("Could not read response"),
-        };
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let is_last_attempt = attempt >= policy.max_attempts;
 
-        return match AgencyError::from_response(&content) {
-            Some(agency_error) => Err(agency_error.to_vcx_error()),
-            None => Err(VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Sending POST HTTP request failed with: {}", content)))
+        info!("Posting encrypted bundle to: \"{}\", attempt: {}/{}", secret!(url), attempt, policy.max_attempts);
+
+        let response = match request_type {
+            RequestType::POST => client.post(url)
+                .body(body_content.to_owned())
+                .header(CONTENT_TYPE, transport.content_type.as_str()),
+            RequestType::GET => client.get(url)
+        }.send();
+
+        let mut response = match response {
+            Ok(response) => response,
+            Err(err) => {
+                error!("error: {}", err);
+                // the POST could reach the remote side unless the connection was not established
+                let is_retryable = match request_type {
+                    RequestType::GET => true,
+                    RequestType::POST => policy.retry_unconfirmed || is_connect_error(&err)
+                };
+                if is_last_attempt || !is_retryable {
+                    return Err(VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Could send HTTP message. Error: {:?}", err)));
+                }
+                let delay = policy.backoff(attempt);
+                warn!("Sending HTTP message failed, retrying in {:?}", delay);
+                std::thread::sleep(delay);
+                continue;
+            }
         };
+
+        info!("Response received: url {:?}", secret!(url));
+
+        trace!("Response Header: {:?}", response);
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            if !is_last_attempt && policy.is_retryable_status(status) {
+                let retry_after = response.headers().get(RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok());
+                let delay = policy.retry_after(retry_after)
+                    .unwrap_or_else(|| policy.backoff(attempt));
+                warn!("HTTP request failed with status {}, retrying in {:?}", status, delay);
+                std::thread::sleep(delay);
+                continue;
+            }
+
+            let mut content = String::new();
+            match response.read_to_string(&mut content) {
+                Ok(_) => info!("Request failed: {}", content),
+                Err(_) => info!("Could not read response"),
+            };
+
+            return match AgencyError::from_response(&content) {
+                Some(agency_error) => Err(agency_error.to_vcx_error()),
+                None => Err(VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Sending POST HTTP request failed with: {}", content)))
+            };
+        }
+        let mut content = Vec::new();
+        response.read_to_end(&mut content)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Could not read HTTP response. Err: {:?}", err)))?;
+
+        return Ok(content);
     }
-    let mut content = Vec::new();
-    response.read_to_end(&mut content)
-        .map_err(|err| VcxError::from_msg(VcxErrorKind::PostMessageFailed, format!("Could not read HTTP response. Err: {:?}", err)))?;
+}
 
-    Ok(content)
+/// Whether the request failed before the connection to the remote side was established.
+fn is_connect_error(err: &reqwest::Error) -> bool {
+    let mut cause: Option<&(dyn std::error::Error + 'static)> = err.get_ref().map(|err| err as &(dyn std::error::Error + 'static));
+    while let Some(err) = cause {
+        if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+            return match io_err.kind() {
+                ErrorKind::ConnectionRefused | ErrorKind::AddrNotAvailable | ErrorKind::NotConnected => true,
+                _ => false
+            };
+        }
+        cause = err.source();
+    }
+    false
 }
 
 fn set_ssl_cert_location() {
@@ -113,3 +282,63 @@ fn set_ssl_cert_location() {
     }
     info!("::SSL_CERT_FILE has been set");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupDefaults;
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_endpoint_scheme() {
+        assert_eq!("https", endpoint_scheme("https://agency.com/agency/msg"));
+        assert_eq!("unix", endpoint_scheme("unix:/tmp/agent.sock"));
+        assert_eq!("mem", endpoint_scheme("MEM://alice"));
+    }
+
+    #[test]
+    fn test_send_message_uses_registered_transport() {
+        let _setup = SetupDefaults::init();
+
+        let transport = |body: &[u8], endpoint: &str| -> VcxResult<Option<Vec<u8>>> {
+            assert_eq!("mem://faber", endpoint);
+            Ok(Some(body.iter().rev().cloned().collect()))
+        };
+        register_transport("mem", Arc::new(transport)).unwrap();
+
+        assert_eq!(Some(vec![3, 2, 1]), send_message(&[1, 2, 3], "mem://faber").unwrap());
+        assert_eq!(vec![3, 2, 1], post_message(&[1, 2, 3], "mem://faber").unwrap());
+
+        unregister_transport("mem").unwrap();
+    }
+
+    #[test]
+    fn test_post_is_not_retried_after_read_timeout() {
+        let _setup = SetupDefaults::init();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        listener.set_nonblocking(true).unwrap();
+
+        // accepts connections but never responds
+        let server = thread::spawn(move || {
+            let mut streams = Vec::new();
+            let deadline = Instant::now() + Duration::from_secs(4);
+            while Instant::now() < deadline {
+                if let Ok((stream, _)) = listener.accept() {
+                    streams.push(stream);
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            streams.len()
+        });
+
+        let policy = DeliveryPolicy { read_timeout_secs: 1, max_attempts: 3, initial_backoff_ms: 10, jitter: false, ..DeliveryPolicy::default() };
+        let err = send_message_with_policy(&[1, 2, 3], &url, DIDCOMM_ENVELOPE_CONTENT_TYPE, Some(&policy)).unwrap_err();
+        assert_eq!(VcxErrorKind::PostMessageFailed, err.kind());
+
+        assert_eq!(1, server.join().unwrap());
+    }
+}
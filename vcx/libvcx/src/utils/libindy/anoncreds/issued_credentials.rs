@@ -0,0 +1,112 @@
+use std::sync::Mutex;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::utils::libindy::wallet::{self, WalletRecord};
+
+/*
+    Numbers of credentials issued against revocation registries.
+
+    A record is kept per revocation registry (record id is the registry id, value is the number).
+    The number is incremented when a credential is created for a received credential request,
+    so credentials which were offered but never issued are not counted.
+*/
+
+const ISSUED_CREDENTIALS_RECORD_TYPE: &str = "vcx_rev_reg_issued";
+
+lazy_static! {
+    // serializes read-modify-write of the numbers
+    static ref ISSUED_CREDENTIALS_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Returns the number of credentials issued against the registry (`None` if not counted yet).
+pub fn get(rev_reg_id: &str) -> VcxResult<Option<u32>> {
+    trace!("issued_credentials::get >>> rev_reg_id: {}", rev_reg_id);
+
+    if settings::indy_mocks_enabled() { return Ok(None); }
+
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    let record = match wallet::get_record(ISSUED_CREDENTIALS_RECORD_TYPE, rev_reg_id, &options) {
+        Ok(record) => record,
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    WalletRecord::from_str(&record)?.value
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Issued credentials record does not contain value"))?
+        .parse::<u32>()
+        .map(Some)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot parse number of issued credentials: {}", err)))
+}
+
+/// Sets the number of credentials issued against the registry if it is not counted yet.
+pub fn init(rev_reg_id: &str, issued: u32) -> VcxResult<()> {
+    trace!("issued_credentials::init >>> rev_reg_id: {}, issued: {}", rev_reg_id, issued);
+
+    if settings::indy_mocks_enabled() { return Ok(()); }
+
+    let _lock = ISSUED_CREDENTIALS_LOCK.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot count issued credentials: lock is poisoned"))?;
+
+    match get(rev_reg_id)? {
+        Some(_) => Ok(()),
+        None => wallet::add_record(ISSUED_CREDENTIALS_RECORD_TYPE, rev_reg_id, &issued.to_string(), None)
+    }
+}
+
+/// Counts the credential issued against the registry. Returns the new number.
+pub fn increment(rev_reg_id: &str) -> VcxResult<u32> {
+    trace!("issued_credentials::increment >>> rev_reg_id: {}", rev_reg_id);
+
+    if settings::indy_mocks_enabled() { return Ok(0); }
+
+    let _lock = ISSUED_CREDENTIALS_LOCK.lock()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot count issued credentials: lock is poisoned"))?;
+
+    let issued = match get(rev_reg_id)? {
+        Some(issued) => {
+            wallet::update_record_value(ISSUED_CREDENTIALS_RECORD_TYPE, rev_reg_id, &(issued + 1).to_string())?;
+            issued + 1
+        }
+        None => {
+            wallet::add_record(ISSUED_CREDENTIALS_RECORD_TYPE, rev_reg_id, "1", None)?;
+            1
+        }
+    };
+
+    trace!("issued_credentials::increment <<< issued: {}", issued);
+    Ok(issued)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    const REV_REG_ID: &str = "V4SGRU86Z58d6TV7PBUe6f:4:V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1:CL_ACCUM:tag1";
+
+    #[test]
+    fn test_issued_credentials_counting_works() {
+        let _setup = SetupLibraryWallet::init();
+
+        assert_eq!(None, get(REV_REG_ID).unwrap());
+
+        assert_eq!(1, increment(REV_REG_ID).unwrap());
+        assert_eq!(2, increment(REV_REG_ID).unwrap());
+        assert_eq!(Some(2), get(REV_REG_ID).unwrap());
+
+        // counted number is not replaced
+        init(REV_REG_ID, 5).unwrap();
+        assert_eq!(Some(2), get(REV_REG_ID).unwrap());
+    }
+
+    #[test]
+    fn test_issued_credentials_init_works() {
+        let _setup = SetupLibraryWallet::init();
+
+        init(REV_REG_ID, 5).unwrap();
+        assert_eq!(Some(5), get(REV_REG_ID).unwrap());
+        assert_eq!(6, increment(REV_REG_ID).unwrap());
+    }
+}
@@ -1,3 +1,9 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration as StdDuration;
+
 use futures::Future;
 use crate::indy::anoncreds;
 use crate::settings;
@@ -5,10 +11,24 @@ use crate::error::prelude::*;
 use crate::utils::constants::*;
 use crate::utils::libindy::{
     anoncreds::blob_storage::BlobStorage,
-    ledger::utils::publish_rev_reg_delta,
-    wallet::get_wallet_handle, LibindyMock,
+    anoncreds::issued_credentials,
+    anoncreds::pending_revocations::{self, PendingRevocations},
+    ledger::{query::Query, utils::publish_rev_reg_delta},
+    wallet::get_wallet_handle,
+    LibindyMock,
 };
 
+// indy error of revoking a credential which is revoked already
+const ANONCREDS_INVALID_USER_REVOC_ID: u32 = 402;
+
+// whether the background publisher of due revocations is running
+static DUE_REVOCATIONS_PUBLISHER: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    // serializes revocations and publishing of pending revocations per revocation registry
+    static ref REV_REG_LOCKS: Mutex<HashMap<String, Arc<Mutex<()>>>> = Default::default();
+}
+
 pub struct Issuer {}
 
 impl Issuer {
@@ -58,8 +78,9 @@ impl Issuer {
     pub fn create_and_store_revoc_reg(issuer_did: &str,
                                       cred_def_id: &str,
                                       tails_path: &str,
-                                      max_creds: u32) -> VcxResult<(String, String, String)> {
-        trace!("creating revocation registry: {}, {}, {}", secret!(cred_def_id), secret!(tails_path), secret!(max_creds));
+                                      max_creds: u32,
+                                      tag: &str) -> VcxResult<(String, String, String)> {
+        trace!("creating revocation registry: {}, {}, {}, {}", secret!(cred_def_id), secret!(tails_path), secret!(max_creds), tag);
 
         if settings::indy_mocks_enabled() { return Ok((REV_REG_ID.to_string(), rev_def_json(), "".to_string())); }
 
@@ -75,7 +96,7 @@ impl Issuer {
         anoncreds::issuer_create_and_store_revoc_reg(wallet_handle,
                                                      issuer_did,
                                                      None,
-                                                     "tag1",
+                                                     tag,
                                                      cred_def_id,
                                                      &revoc_config,
                                                      writer)
@@ -113,35 +134,274 @@ impl Issuer {
             Some(tails_file_) => BlobStorage::open_reader(&tails_file_)?,
             None => -1,
         };
-        anoncreds::issuer_create_credential(wallet_handle,
-                                            cred_offer_json,
-                                            cred_req_json,
-                                            cred_values_json,
-                                            rev_reg_id,
-                                            blob_handle)
+        let credential = anoncreds::issuer_create_credential(wallet_handle,
+                                                             cred_offer_json,
+                                                             cred_req_json,
+                                                             cred_values_json,
+                                                             rev_reg_id,
+                                                             blob_handle)
             .wait()
-            .map_err(VcxError::from)
+            .map_err(VcxError::from)?;
+
+        // the credential is counted once it is created for the received request, so unanswered offers do not use up the registry
+        if let Some(rev_reg_id) = rev_reg_id {
+            if let Err(err) = issued_credentials::increment(rev_reg_id) {
+                warn!("Cannot count credential issued against Revocation Registry {}. Err: {}", rev_reg_id, err);
+            }
+        }
+
+        Ok(credential)
     }
 
+    /// Revokes the credential and publishes the delta of the revocation registry to the Ledger
+    /// together with all pending revocations of the registry.
     pub fn revoke_credential(tails_file: &str, rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<String> {
         if settings::indy_mocks_enabled() {
             return Ok(REV_REG_DELTA_JSON.to_string());
         }
 
-        let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+        Self::_revoke_credential_local(tails_file, rev_reg_id, cred_rev_id)?;
+
+        let delta = Self::publish_pending_revocations(rev_reg_id)?
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, "Revocation registry delta not found"))?;
+
+        Self::_publish_due_revocations();
+
+        Ok(delta)
+    }
+
+    /// Revokes the credential in the wallet without publishing to the Ledger.
+    /// The revocation is added to the pending revocations of the registry.
+    /// Pending revocations older than `revocation_publish_interval` (if set) are published now or in background once they are due.
+    pub fn revoke_credential_local(tails_file: &str, rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<()> {
+        trace!("Issuer::revoke_credential_local >>> tails_file: {:?}, rev_reg_id: {:?}, cred_rev_id: {:?}", tails_file, rev_reg_id, cred_rev_id);
+
+        if settings::indy_mocks_enabled() { return Ok(()); }
+
+        Self::_revoke_credential_local(tails_file, rev_reg_id, cred_rev_id)?;
+
+        Self::_publish_due_revocations();
+        Self::_schedule_due_revocations();
+
+        trace!("Issuer::revoke_credential_local <<<");
+        Ok(())
+    }
+
+    /// Returns pending revocations of all registries.
+    pub fn get_pending_revocations() -> VcxResult<Vec<PendingRevocations>> {
+        pending_revocations::list()
+    }
+
+    /// Publishes the merged delta of pending revocations of the registry to the Ledger.
+    /// Returns the published delta or `None` if there are no pending revocations.
+    pub fn publish_pending_revocations(rev_reg_id: &str) -> VcxResult<Option<String>> {
+        trace!("Issuer::publish_pending_revocations >>> rev_reg_id: {:?}", rev_reg_id);
+
+        if settings::indy_mocks_enabled() { return Ok(Some(REV_REG_DELTA_JSON.to_string())); }
+
+        let delta = Self::_with_rev_reg_lock(rev_reg_id, || {
+            let pending = match pending_revocations::get(rev_reg_id)? {
+                Some(pending) => Self::_complete_interrupted_revocations(pending)?,
+                None => return Ok(None)
+            };
+
+            let ledger_accum = if pending.without_delta.is_empty() { None } else { Some(Self::_ledger_accum(rev_reg_id)?) };
+            let delta = pending.delta_to_publish(ledger_accum.as_deref())?;
+
+            let submitter_did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID)?;
+
+            publish_rev_reg_delta(&submitter_did, rev_reg_id, &delta)?;
+
+            // only the published revocations are removed
+            if let Some(current) = pending_revocations::get(rev_reg_id)? {
+                pending_revocations::store(&current.remove_published(&pending))?;
+            }
+
+            Ok(Some(delta))
+        })?;
+
+        trace!("Issuer::publish_pending_revocations <<< delta: {:?}", delta);
+        Ok(delta)
+    }
+
+    /// Whether the revocation of the credential is recorded in the wallet but not published to the Ledger yet.
+    pub fn is_revocation_pending(rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<bool> {
+        Ok(pending_revocations::get(rev_reg_id)?
+            .map(|pending| pending.contains(cred_rev_id))
+            .unwrap_or(false))
+    }
+
+    /// Publishes pending revocations of all registries to the Ledger.
+    /// Returns ids of the registries which deltas were published.
+    pub fn publish_all_pending_revocations() -> VcxResult<Vec<String>> {
+        trace!("Issuer::publish_all_pending_revocations >>>");
+
+        let mut published = Vec::new();
+
+        // revocations which deltas were lost wait for the next revocation of the registry
+        for pending in pending_revocations::list()?.into_iter().filter(PendingRevocations::can_publish) {
+            if Self::publish_pending_revocations(&pending.rev_reg_id)?.is_some() {
+                published.push(pending.rev_reg_id);
+            }
+        }
+
+        trace!("Issuer::publish_all_pending_revocations <<< published: {:?}", published);
+        Ok(published)
+    }
 
+    fn _revoke_credential_local(tails_file: &str, rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<()> {
+        Self::_with_rev_reg_lock(rev_reg_id, || {
+            let pending = match pending_revocations::get(rev_reg_id)? {
+                Some(pending) => PendingRevocations {
+                    tails_file: Some(tails_file.to_string()),
+                    ..Self::_complete_interrupted_revocations(pending)?
+                },
+                None => PendingRevocations::new(rev_reg_id, Some(tails_file))
+            };
+
+            // the revocation which publishing failed before is only published again
+            if pending.contains(cred_rev_id) {
+                debug!("Issuer: Revocation {} of revocation registry {} is pending already", cred_rev_id, rev_reg_id);
+                return Ok(());
+            }
+
+            // the revocation is recorded before the wallet revokes the credential,
+            // so it is completed later if the process stops in between
+            let pending = pending.start(cred_rev_id)?;
+            pending_revocations::store(&pending)?;
+
+            match Self::_revoke_in_wallet(tails_file, rev_reg_id, cred_rev_id) {
+                Ok(delta) => Self::_merge(pending, cred_rev_id, &delta).map(|_| ()),
+                Err(err) => {
+                    pending_revocations::store(&pending.discard(cred_rev_id))?;
+                    Err(err)
+                }
+            }
+        })
+    }
+
+    fn _with_rev_reg_lock<F, T>(rev_reg_id: &str, f: F) -> VcxResult<T> where F: FnOnce() -> VcxResult<T> {
+        let lock = REV_REG_LOCKS.lock()
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot lock revocation registry: lock is poisoned"))?
+            .entry(rev_reg_id.to_string())
+            .or_default()
+            .clone();
+
+        let _guard = lock.lock()
+            .map_err(|_| VcxError::from_msg(VcxErrorKind::InternalError, "Cannot lock revocation registry: lock is poisoned"))?;
+
+        f()
+    }
+
+    // revocations recorded before the process stopped: the wallet either has not revoked the credential yet
+    // or has revoked it, but the delta is lost
+    fn _complete_interrupted_revocations(mut pending: PendingRevocations) -> VcxResult<PendingRevocations> {
+        for cred_rev_id in pending.unmerged.clone() {
+            debug!("Issuer: Completing interrupted revocation {} of revocation registry {}", cred_rev_id, pending.rev_reg_id);
+
+            let tails_file = pending.tails_file.clone()
+                .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, "Tails file of pending revocations not found"))?;
+
+            pending = match Self::_revoke_in_wallet(&tails_file, &pending.rev_reg_id, &cred_rev_id) {
+                Ok(delta) => Self::_merge(pending, &cred_rev_id, &delta)?,
+                Err(ref err) if err.kind() == VcxErrorKind::LibndyError(ANONCREDS_INVALID_USER_REVOC_ID) => {
+                    warn!("Issuer: Delta of revocation {} of revocation registry {} is lost, it is published with the next revocation of the registry",
+                          cred_rev_id, pending.rev_reg_id);
+                    let pending = pending.mark_without_delta(&cred_rev_id)?;
+                    pending_revocations::store(&pending)?;
+                    pending
+                }
+                Err(err) => return Err(err)
+            };
+        }
+        Ok(pending)
+    }
+
+    fn _revoke_in_wallet(tails_file: &str, rev_reg_id: &str, cred_rev_id: &str) -> VcxResult<String> {
         let blob_handle = BlobStorage::open_reader(tails_file)?;
         let wallet_handle = get_wallet_handle();
 
-        let delta = anoncreds::issuer_revoke_credential(wallet_handle,
-                                                        blob_handle,
-                                                        rev_reg_id,
-                                                        cred_rev_id)
+        anoncreds::issuer_revoke_credential(wallet_handle,
+                                            blob_handle,
+                                            rev_reg_id,
+                                            cred_rev_id)
             .wait()
-            .map_err(VcxError::from)?;
+            .map_err(VcxError::from)
+    }
 
-        publish_rev_reg_delta(&submitter_did, rev_reg_id, &delta)?;
+    fn _merge(pending: PendingRevocations, cred_rev_id: &str, delta: &str) -> VcxResult<PendingRevocations> {
+        let delta = match pending.delta {
+            Some(ref merged) => anoncreds::issuer_merge_revocation_registry_deltas(merged, delta)
+                .wait()
+                .map_err(VcxError::from)?,
+            None => delta.to_string()
+        };
 
-        Ok(delta)
+        let pending = pending.merge(cred_rev_id, &delta);
+        pending_revocations::store(&pending)?;
+        Ok(pending)
+    }
+
+    fn _ledger_accum(rev_reg_id: &str) -> VcxResult<String> {
+        let (_, rev_reg, _) = Query::get_rev_reg(rev_reg_id, ::time::get_time().sec as u64)?;
+
+        let rev_reg: ::serde_json::Value = ::serde_json::from_str(&rev_reg)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize revocation registry: {}", err)))?;
+
+        rev_reg["value"]["accum"].as_str()
+            .map(String::from)
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, "Revocation registry does not contain accumulator"))
+    }
+
+    // the revocation is already recorded, so failed publishing is only reported
+    fn _publish_due_revocations() {
+        let interval = match pending_revocations::publish_interval() {
+            Some(interval) => interval,
+            None => return
+        };
+
+        let pending = match pending_revocations::list() {
+            Ok(pending) => pending,
+            Err(err) => {
+                warn!("Issuer: Cannot get pending revocations: {}", err);
+                return;
+            }
+        };
+
+        for pending in pending.into_iter().filter(|pending| pending.can_publish() && pending.is_due(interval)) {
+            if let Err(err) = Self::publish_pending_revocations(&pending.rev_reg_id) {
+                warn!("Issuer: Cannot publish pending revocations of revocation registry {}: {}", pending.rev_reg_id, err);
+            }
+        }
+    }
+
+    // pending revocations are published once they are due even if no other revocation is made
+    fn _schedule_due_revocations() {
+        if pending_revocations::publish_interval().is_none() || DUE_REVOCATIONS_PUBLISHER.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        thread::spawn(|| {
+            while let Some(interval) = pending_revocations::publish_interval() {
+                thread::sleep(StdDuration::from_secs(interval.max(1)));
+
+                Self::_publish_due_revocations();
+
+                if Self::_has_publishable_revocations() { continue; }
+
+                DUE_REVOCATIONS_PUBLISHER.store(false, Ordering::SeqCst);
+
+                // a revocation made meanwhile could see the publisher running
+                if !Self::_has_publishable_revocations() || DUE_REVOCATIONS_PUBLISHER.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+            }
+            DUE_REVOCATIONS_PUBLISHER.store(false, Ordering::SeqCst);
+        });
+    }
+
+    // revocations which deltas were lost are published with the next revocation of the registry, which schedules the publisher again
+    fn _has_publishable_revocations() -> bool {
+        pending_revocations::list().map(|pending| pending.iter().any(PendingRevocations::can_publish)).unwrap_or(false)
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,6 @@
 pub mod issuer;
+pub mod pending_revocations;
+pub mod issued_credentials;
 pub mod holder;
 pub mod verifier;
 pub mod blob_storage;
@@ -310,6 +312,7 @@ pub mod tests {
         use super::*;
         use crate::utils::constants::TEST_TAILS_FILE;
         use crate::utils::libindy::anoncreds::verifier::Verifier;
+        use crate::utils::libindy::anoncreds::pending_revocations;
         use crate::error::VcxErrorKind;
 
         #[test]
@@ -426,7 +429,7 @@ pub mod tests {
             // revoc_reg_def will fail in libindy because cred_Def doesn't have revocation keys
             let (_, _, cred_def_id, _, _, _) = create_and_store_credential_def(crate::utils::constants::DEFAULT_SCHEMA_ATTRS, false);
             let did = settings::get_config_value(settings::CONFIG_INSTITUTION_DID).unwrap();
-            let rc = Issuer::create_and_store_revoc_reg(&did, &cred_def_id, get_temp_dir_path("path.txt").to_str().unwrap(), 2);
+            let rc = Issuer::create_and_store_revoc_reg(&did, &cred_def_id, get_temp_dir_path("path.txt").to_str().unwrap(), 2, "tag1");
 
             assert_eq!(rc.unwrap_err().kind(), VcxErrorKind::LibindyInvalidStructure);
         }
@@ -441,7 +444,7 @@ pub mod tests {
 
             let (cred_def_id, cred_def_json) = Issuer::create_and_store_credential_def(&did, &schema_json, "tag_1", None, Some(true)).unwrap();
             ledger::utils::publish_cred_def(&cred_def_json).unwrap();
-            let (rev_reg_def_id, rev_reg_def_json, rev_reg_entry_json) = Issuer::create_and_store_revoc_reg(&did, &cred_def_id, "tails.txt", 2).unwrap();
+            let (rev_reg_def_id, rev_reg_def_json, rev_reg_entry_json) = Issuer::create_and_store_revoc_reg(&did, &cred_def_id, "tails.txt", 2, "tag1").unwrap();
             ledger::utils::publish_rev_reg_def(&did, &rev_reg_def_json).unwrap();
             ledger::utils::publish_rev_reg_delta(&did, &rev_reg_def_id, &rev_reg_entry_json).unwrap();
         }
@@ -517,6 +520,58 @@ pub mod tests {
             assert_ne!(first_rev_reg_delta, second_rev_reg_delta);
         }
 
+        #[test]
+        fn test_revoke_credential_local() {
+            let _setup = SetupLibraryWalletPool::init();
+
+            let (_, _, _, _, _, _, _, _, rev_reg_id, cred_rev_id)
+                = create_and_store_credential(crate::utils::constants::DEFAULT_SCHEMA_ATTRS, true);
+
+            let rev_reg_id = rev_reg_id.unwrap();
+            let (_, first_rev_reg_delta, first_timestamp) = Query::get_rev_reg_delta(&rev_reg_id, None, None).unwrap();
+
+            assert_eq!(None, Issuer::publish_pending_revocations(&rev_reg_id).unwrap());
+
+            Issuer::revoke_credential_local(get_temp_dir_path(TEST_TAILS_FILE).to_str().unwrap(), &rev_reg_id, cred_rev_id.unwrap().as_str()).unwrap();
+
+            let pending = Issuer::get_pending_revocations().unwrap();
+            assert_eq!(1, pending.len());
+            assert_eq!(rev_reg_id, pending[0].rev_reg_id);
+
+            // Delta should not change until pending revocations are published
+            let (_, not_published_rev_reg_delta, _) = Query::get_rev_reg_delta(&rev_reg_id, None, None).unwrap();
+            assert_eq!(first_rev_reg_delta, not_published_rev_reg_delta);
+
+            assert!(Issuer::publish_pending_revocations(&rev_reg_id).unwrap().is_some());
+            assert_eq!(None, Issuer::publish_pending_revocations(&rev_reg_id).unwrap());
+
+            let (_, second_rev_reg_delta, _) = Query::get_rev_reg_delta(&rev_reg_id, Some(first_timestamp + 1), None).unwrap();
+            assert_ne!(first_rev_reg_delta, second_rev_reg_delta);
+        }
+
+        #[test]
+        fn test_publish_pending_revocations_completes_interrupted_revocation() {
+            let _setup = SetupLibraryWalletPool::init();
+
+            let (_, _, _, _, _, _, _, _, rev_reg_id, cred_rev_id)
+                = create_and_store_credential(crate::utils::constants::DEFAULT_SCHEMA_ATTRS, true);
+
+            let rev_reg_id = rev_reg_id.unwrap();
+            let cred_rev_id = cred_rev_id.unwrap();
+            let (_, first_rev_reg_delta, first_timestamp) = Query::get_rev_reg_delta(&rev_reg_id, None, None).unwrap();
+
+            // the process stopped after recording the revocation
+            let pending = pending_revocations::PendingRevocations::new(&rev_reg_id, get_temp_dir_path(TEST_TAILS_FILE).to_str())
+                .start(&cred_rev_id).unwrap();
+            pending_revocations::store(&pending).unwrap();
+
+            assert!(Issuer::publish_pending_revocations(&rev_reg_id).unwrap().is_some());
+            assert!(Issuer::get_pending_revocations().unwrap().is_empty());
+
+            let (_, second_rev_reg_delta, _) = Query::get_rev_reg_delta(&rev_reg_id, Some(first_timestamp + 1), None).unwrap();
+            assert_ne!(first_rev_reg_delta, second_rev_reg_delta);
+        }
+
         #[test]
         fn test_fetch_public_entities() {
             let _setup = SetupLibraryWalletPool::init();
@@ -0,0 +1,423 @@
+use chrono::{DateTime, Duration, Utc};
+use serde_json::Value;
+
+use crate::error::prelude::*;
+use crate::settings;
+use crate::utils::libindy::wallet::{self, WalletRecord};
+
+/*
+    Revocations recorded in the wallet but not published to the Ledger yet.
+
+    A record is kept per revocation registry (record id is the registry id). It holds ids of the revoked
+    credentials and the registry delta merged from the deltas of all these revocations, so a single
+    `REVOC_REG_ENTRY` transaction publishes them at once. The published revocations are removed from the record
+    (the record is deleted if nothing is left). Changes of the record are serialized by the issuer per registry.
+
+    A revocation is recorded as `unmerged` before the wallet revokes the credential and is moved to the
+    merged delta after that, so a revocation interrupted in between is completed on the next access to the
+    record instead of being lost. If the wallet has revoked the credential already, the delta of the interrupted
+    revocation cannot be restored: its id is listed explicitly in the published delta, which accumulator is taken
+    from the next revocation of the registry and the previous accumulator - from the Ledger.
+
+    With `revocation_publish_interval` config option set, pending revocations older than the interval
+    are published in background.
+
+    Deltas stored by the previous versions of the library (`vcx_rev_reg_delta` records) are moved to
+    pending revocations on the first access.
+*/
+
+const PENDING_REVOCATIONS_RECORD_TYPE: &str = "vcx_pending_revocations";
+const LEGACY_REV_REG_DELTA_RECORD_TYPE: &str = "vcx_rev_reg_delta";
+
+/// Revocations of the registry not published to the Ledger yet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PendingRevocations {
+    pub rev_reg_id: String,
+    pub cred_rev_ids: Vec<String>,
+    /// UTC time of the first revocation not published yet (RFC 3339)
+    pub since: String,
+    /// delta of the revocation registry merged from the deltas of all pending revocations
+    pub delta: Option<String>,
+    /// revocations recorded before the wallet revoked the credentials, their deltas are not merged yet
+    #[serde(default)]
+    pub unmerged: Vec<String>,
+    /// revocations made by the wallet which deltas were lost by interrupted revocations
+    #[serde(default)]
+    pub without_delta: Vec<String>,
+    /// tails file of the registry used to complete unmerged revocations
+    #[serde(default)]
+    pub tails_file: Option<String>,
+}
+
+impl PendingRevocations {
+    pub fn new(rev_reg_id: &str, tails_file: Option<&str>) -> PendingRevocations {
+        PendingRevocations {
+            rev_reg_id: rev_reg_id.to_string(),
+            cred_rev_ids: Vec::new(),
+            since: Utc::now().to_rfc3339(),
+            delta: None,
+            unmerged: Vec::new(),
+            without_delta: Vec::new(),
+            tails_file: tails_file.map(String::from),
+        }
+    }
+
+    /// Records the revocation before the wallet revokes the credential.
+    pub fn start(mut self, cred_rev_id: &str) -> VcxResult<PendingRevocations> {
+        if self.contains(cred_rev_id) {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidState,
+                                          format!("Credential {} of revocation registry {} is already revoked", cred_rev_id, self.rev_reg_id)));
+        }
+        if self.cred_rev_ids.is_empty() {
+            self.since = Utc::now().to_rfc3339();
+        }
+        self.cred_rev_ids.push(cred_rev_id.to_string());
+        self.unmerged.push(cred_rev_id.to_string());
+        Ok(self)
+    }
+
+    /// Completes the revocation. `delta` is the merged delta of the registry including this revocation.
+    pub fn merge(mut self, cred_rev_id: &str, delta: &str) -> PendingRevocations {
+        self.unmerged.retain(|id| id != cred_rev_id);
+        self.delta = Some(delta.to_string());
+        self
+    }
+
+    /// Records the interrupted revocation which the wallet has made already, so its delta is lost.
+    /// The merged delta cannot be used as is anymore: revocations merged into it are listed explicitly
+    /// in the delta published after the next revocation.
+    pub fn mark_without_delta(mut self, cred_rev_id: &str) -> VcxResult<PendingRevocations> {
+        if let Some(ref delta) = self.delta {
+            for revoked in _revoked(&_parse_delta(delta)?) {
+                if !self.without_delta.contains(&revoked) { self.without_delta.push(revoked); }
+            }
+        }
+        self.unmerged.retain(|id| id != cred_rev_id);
+        self.without_delta.push(cred_rev_id.to_string());
+        self.delta = None;
+        Ok(self)
+    }
+
+    /// Returns the delta to publish to the Ledger.
+    /// `ledger_accum` - the current accumulator of the registry on the Ledger, required if some deltas were lost.
+    pub fn delta_to_publish(&self, ledger_accum: Option<&str>) -> VcxResult<String> {
+        let delta = self.delta.as_ref()
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidState,
+                                      format!("Revocations {:?} of revocation registry {} can be published only with the next revocation of the registry",
+                                              self.without_delta, self.rev_reg_id)))?;
+
+        if self.without_delta.is_empty() {
+            return Ok(delta.to_string());
+        }
+
+        let ledger_accum = ledger_accum
+            .ok_or(VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, "Ledger accumulator is required to publish revocations without delta"))?;
+
+        let mut delta = _parse_delta(delta)?;
+
+        let mut revoked = _revoked(&delta);
+        for id in self.without_delta.iter() {
+            if !revoked.contains(id) { revoked.push(id.clone()); }
+        }
+        let revoked = revoked.iter()
+            .map(|id| id.parse::<u32>()
+                .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidRevocationDetails, format!("Invalid credential revocation id: {}", id))))
+            .collect::<VcxResult<Vec<u32>>>()?;
+
+        delta["value"]["prevAccum"] = json!(ledger_accum);
+        delta["value"]["revoked"] = json!(revoked);
+
+        Ok(delta.to_string())
+    }
+
+    /// Drops the revocations published with the delta of `published`.
+    pub fn remove_published(mut self, published: &PendingRevocations) -> PendingRevocations {
+        self.cred_rev_ids.retain(|id| !published.cred_rev_ids.contains(id));
+        self.without_delta.retain(|id| !published.without_delta.contains(id) && !published.cred_rev_ids.contains(id));
+        if self.delta == published.delta {
+            self.delta = None;
+        }
+        self
+    }
+
+    /// Whether the revocations can be published now.
+    /// Revocations which deltas were lost can be published only with the next revocation of the registry.
+    pub fn can_publish(&self) -> bool {
+        self.delta.is_some() || !self.unmerged.is_empty()
+    }
+
+    /// Whether the revocation of the credential is recorded but not published yet.
+    pub fn contains(&self, cred_rev_id: &str) -> bool {
+        self.cred_rev_ids.iter().any(|id| id == cred_rev_id)
+    }
+
+    /// Forgets the revocation the wallet failed to make.
+    pub fn discard(mut self, cred_rev_id: &str) -> PendingRevocations {
+        self.unmerged.retain(|id| id != cred_rev_id);
+        self.cred_rev_ids.retain(|id| id != cred_rev_id);
+        self
+    }
+
+    /// Whether the first pending revocation is older than `interval` seconds.
+    /// Revocations with `since` in unknown format are considered as due.
+    pub fn is_due(&self, interval: u64) -> bool {
+        DateTime::parse_from_rfc3339(&self.since)
+            .map(|since| since.with_timezone(&Utc) + Duration::seconds(interval as i64) <= Utc::now())
+            .unwrap_or(true)
+    }
+}
+
+/// Interval (in seconds) after which pending revocations are published automatically.
+pub fn publish_interval() -> Option<u64> {
+    settings::get_opt_config_value(settings::CONFIG_REVOCATION_PUBLISH_INTERVAL)
+        .and_then(|interval| interval.parse::<u64>().ok())
+}
+
+/// Returns pending revocations of the registry.
+pub fn get(rev_reg_id: &str) -> VcxResult<Option<PendingRevocations>> {
+    trace!("pending_revocations::get >>> rev_reg_id: {}", rev_reg_id);
+
+    if settings::indy_mocks_enabled() { return Ok(None); }
+
+    match _get_value(PENDING_REVOCATIONS_RECORD_TYPE, rev_reg_id)? {
+        Some(value) => _parse(&value).map(Some),
+        None => _migrate_legacy(rev_reg_id)
+    }
+}
+
+/// Stores pending revocations of the registry replacing the stored ones.
+/// The record is deleted if there are no revocations left.
+pub fn store(pending: &PendingRevocations) -> VcxResult<()> {
+    trace!("pending_revocations::store >>> pending: {:?}", pending);
+
+    let exists = _get_value(PENDING_REVOCATIONS_RECORD_TYPE, &pending.rev_reg_id)?.is_some();
+
+    if pending.cred_rev_ids.is_empty() && pending.delta.is_none() {
+        return if exists { delete(&pending.rev_reg_id) } else { Ok(()) };
+    }
+
+    let value = json!(pending).to_string();
+
+    if exists {
+        wallet::update_record_value(PENDING_REVOCATIONS_RECORD_TYPE, &pending.rev_reg_id, &value)
+    } else {
+        wallet::add_record(PENDING_REVOCATIONS_RECORD_TYPE, &pending.rev_reg_id, &value, None)
+    }
+}
+
+/// Deletes pending revocations of the registry.
+pub fn delete(rev_reg_id: &str) -> VcxResult<()> {
+    trace!("pending_revocations::delete >>> rev_reg_id: {}", rev_reg_id);
+
+    wallet::delete_record(PENDING_REVOCATIONS_RECORD_TYPE, rev_reg_id)
+}
+
+/// Returns pending revocations of all registries.
+pub fn list() -> VcxResult<Vec<PendingRevocations>> {
+    trace!("pending_revocations::list >>>");
+
+    if settings::indy_mocks_enabled() { return Ok(Vec::new()); }
+
+    let options = json!({"retrieveRecords": true, "retrieveTotalCount": false, "retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    for record in wallet::search_records(LEGACY_REV_REG_DELTA_RECORD_TYPE, "{}", &options)? {
+        if let Some(rev_reg_id) = record["id"].as_str() {
+            _migrate_legacy(rev_reg_id)?;
+        }
+    }
+
+    let records = wallet::search_records(PENDING_REVOCATIONS_RECORD_TYPE, "{}", &options)?;
+
+    let pending = records.iter()
+        .map(|record| {
+            let value = record["value"].as_str()
+                .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, "Pending revocations record does not contain value"))?;
+            _parse(value)
+        })
+        .collect::<VcxResult<Vec<PendingRevocations>>>()?;
+
+    trace!("pending_revocations::list <<< pending: {:?}", pending);
+    Ok(pending)
+}
+
+fn _get_value(xtype: &str, id: &str) -> VcxResult<Option<String>> {
+    let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": false}).to_string();
+
+    let record = match wallet::get_record(xtype, id, &options) {
+        Ok(record) => record,
+        Err(ref err) if err.kind() == VcxErrorKind::WalletRecordNotFound => return Ok(None),
+        Err(err) => return Err(err)
+    };
+
+    WalletRecord::from_str(&record)?.value
+        .ok_or(VcxError::from_msg(VcxErrorKind::WalletRecordNotFound, format!("{} record does not contain value", xtype)))
+        .map(Some)
+}
+
+// revoked credential ids were not stored with the legacy delta
+fn _migrate_legacy(rev_reg_id: &str) -> VcxResult<Option<PendingRevocations>> {
+    let delta = match _get_value(LEGACY_REV_REG_DELTA_RECORD_TYPE, rev_reg_id)? {
+        Some(delta) => delta,
+        None => return Ok(None)
+    };
+
+    debug!("pending_revocations: moving stored delta of revocation registry {} to pending revocations", rev_reg_id);
+
+    let pending = PendingRevocations {
+        delta: Some(delta),
+        ..PendingRevocations::new(rev_reg_id, None)
+    };
+
+    store(&pending)?;
+    wallet::delete_record(LEGACY_REV_REG_DELTA_RECORD_TYPE, rev_reg_id)?;
+
+    Ok(Some(pending))
+}
+
+fn _parse_delta(delta: &str) -> VcxResult<Value> {
+    ::serde_json::from_str(delta)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize revocation registry delta: {}", err)))
+}
+
+// ids of the credentials revoked by the delta
+fn _revoked(delta: &Value) -> Vec<String> {
+    delta["value"]["revoked"].as_array()
+        .map(|revoked| revoked.iter().map(|id| id.to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn _parse(value: &str) -> VcxResult<PendingRevocations> {
+    ::serde_json::from_str(value)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize pending revocations record: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::devsetup::SetupLibraryWallet;
+
+    const REV_REG_ID: &str = "V4SGRU86Z58d6TV7PBUe6f:4:V4SGRU86Z58d6TV7PBUe6f:3:CL:1281:tag1:CL_ACCUM:tag1";
+
+    #[test]
+    fn test_pending_revocations_is_due() {
+        let pending = PendingRevocations::new(REV_REG_ID, None);
+        assert!(pending.is_due(0));
+        assert!(!pending.is_due(60));
+
+        let pending = PendingRevocations { since: String::from("2018-12-13T17:29:06+00:00"), ..pending };
+        assert!(pending.is_due(60));
+
+        let pending = PendingRevocations { since: String::from("yesterday"), ..pending };
+        assert!(pending.is_due(60));
+    }
+
+    #[test]
+    fn test_pending_revocations_start_merge_discard() {
+        let pending = PendingRevocations::new(REV_REG_ID, Some("tails"))
+            .start("1").unwrap()
+            .start("2").unwrap();
+
+        assert_eq!(vec!["1", "2"], pending.cred_rev_ids);
+        assert_eq!(vec!["1", "2"], pending.unmerged);
+        assert_eq!(VcxErrorKind::InvalidState, pending.clone().start("2").unwrap_err().kind());
+
+        let pending = pending.merge("1", "delta_1").discard("2");
+        assert_eq!(vec!["1"], pending.cred_rev_ids);
+        assert!(pending.unmerged.is_empty());
+        assert_eq!(Some(String::from("delta_1")), pending.delta);
+    }
+
+    #[test]
+    fn test_pending_revocations_delta_to_publish() {
+        let delta = json!({"ver": "1.0", "value": {"prevAccum": "1", "accum": "2", "revoked": [1]}}).to_string();
+
+        let pending = PendingRevocations::new(REV_REG_ID, Some("tails"))
+            .start("1").unwrap()
+            .merge("1", &delta);
+        assert_eq!(delta, pending.delta_to_publish(None).unwrap());
+
+        let pending = pending.start("2").unwrap()
+            .mark_without_delta("2").unwrap();
+        assert_eq!(vec!["1", "2"], pending.without_delta);
+        assert_eq!(VcxErrorKind::InvalidState, pending.delta_to_publish(Some("0")).unwrap_err().kind());
+
+        let delta = json!({"ver": "1.0", "value": {"prevAccum": "3", "accum": "4", "revoked": [3]}}).to_string();
+        let pending = pending.start("3").unwrap()
+            .merge("3", &delta);
+        assert_eq!(VcxErrorKind::InvalidRevocationDetails, pending.delta_to_publish(None).unwrap_err().kind());
+
+        let delta: Value = ::serde_json::from_str(&pending.delta_to_publish(Some("0")).unwrap()).unwrap();
+        assert_eq!(json!({"ver": "1.0", "value": {"prevAccum": "0", "accum": "4", "revoked": [3, 1, 2]}}), delta);
+    }
+
+    #[test]
+    fn test_pending_revocations_remove_published() {
+        let pending = PendingRevocations::new(REV_REG_ID, Some("tails"))
+            .start("1").unwrap()
+            .merge("1", "delta_1");
+        assert!(pending.can_publish());
+        assert!(pending.contains("1"));
+
+        // revocation recorded after the delta was published stays pending
+        let published = pending.clone();
+        let pending = pending.start("2").unwrap()
+            .merge("2", "delta_2")
+            .remove_published(&published);
+        assert_eq!(vec!["2"], pending.cred_rev_ids);
+        assert_eq!(Some(String::from("delta_2")), pending.delta);
+
+        let pending = pending.clone().remove_published(&pending);
+        assert!(pending.cred_rev_ids.is_empty());
+        assert_eq!(None, pending.delta);
+        assert!(!pending.can_publish());
+
+        let pending = pending.start("3").unwrap()
+            .mark_without_delta("3").unwrap();
+        assert!(!pending.can_publish());
+    }
+
+    #[test]
+    fn test_pending_revocations_storage_works() {
+        let _setup = SetupLibraryWallet::init();
+
+        assert_eq!(None, get(REV_REG_ID).unwrap());
+        assert!(list().unwrap().is_empty());
+
+        let pending = PendingRevocations::new(REV_REG_ID, None).start("1").unwrap();
+        store(&pending).unwrap();
+        assert_eq!(Some(pending.clone()), get(REV_REG_ID).unwrap());
+
+        let pending = pending.merge("1", "delta_1").start("2").unwrap();
+        store(&pending).unwrap();
+        assert_eq!(vec![pending.clone()], list().unwrap());
+
+        delete(REV_REG_ID).unwrap();
+        assert_eq!(None, get(REV_REG_ID).unwrap());
+    }
+
+    #[test]
+    fn test_pending_revocations_store_deletes_empty_record() {
+        let _setup = SetupLibraryWallet::init();
+
+        let pending = PendingRevocations::new(REV_REG_ID, None).start("1").unwrap();
+        store(&pending).unwrap();
+
+        store(&pending.discard("1")).unwrap();
+        assert_eq!(None, get(REV_REG_ID).unwrap());
+    }
+
+    #[test]
+    fn test_pending_revocations_migrate_legacy_delta() {
+        let _setup = SetupLibraryWallet::init();
+
+        wallet::add_record(LEGACY_REV_REG_DELTA_RECORD_TYPE, REV_REG_ID, "legacy_delta", None).unwrap();
+
+        let pending = list().unwrap();
+        assert_eq!(1, pending.len());
+        assert_eq!(Some(String::from("legacy_delta")), pending[0].delta);
+        assert!(pending[0].cred_rev_ids.is_empty());
+
+        assert_eq!(None, _get_value(LEGACY_REV_REG_DELTA_RECORD_TYPE, REV_REG_ID).unwrap());
+        assert_eq!(Some(pending[0].clone()), get(REV_REG_ID).unwrap());
+    }
+}
@@ -48,6 +48,20 @@ impl Request {
             .map_err(VcxError::from)
     }
 
+    /// Submits the request to the Ledger registered for `namespace` (see `namespace_list` of pool networks).
+    pub fn submit_to(namespace: &str, request_json: &str) -> VcxResult<String> {
+        let vdr = get_vdr()?;
+
+        if !vdr.namespace_list.iter().any(|namespace_| namespace_ == namespace) {
+            return Err(VcxError::from_msg(VcxErrorKind::InvalidDid,
+                                          format!("Ledger for namespace `{}` is not configured. Configured namespaces: {:?}", namespace, vdr.namespace_list)));
+        }
+
+        vdr::submit_raw_txn(&vdr.vdr, namespace, &request_json.as_bytes())
+            .wait()
+            .map_err(VcxError::from)
+    }
+
     pub fn append_txn_author_agreement(request_json: &str) -> VcxResult<String> {
         if let Some(author_agreement) = crate::utils::author_agreement::get_txn_author_agreement()? {
             ledger::append_txn_author_agreement_acceptance_to_request(request_json,
@@ -86,6 +100,12 @@ impl Request {
             .map_err(VcxError::from)
     }
 
+    pub fn get_attrib(submitter_did: Option<&str>, did: &str, raw: &str) -> VcxResult<String> {
+        ledger::build_get_attrib_request(submitter_did, did, Some(raw), None, None)
+            .wait()
+            .map_err(VcxError::from)
+    }
+
     pub fn get_revoc_reg_def(submitter_did: &str,
                              rev_reg_id: &str) -> VcxResult<String> {
         ledger::build_get_revoc_reg_def_request(Some(submitter_did), rev_reg_id)
@@ -59,4 +59,14 @@ pub struct TxnAuthorAgreement {
     pub taa_digest: Option<String>,
     pub acc_mech_type: String,
     pub time: u64,
-}
\ No newline at end of file
+}
+/// Value of `endpoint` ATTRIB written for a public DID.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct EndpointAttrib {
+    pub endpoint: String,
+    #[serde(default)]
+    #[serde(rename = "routingKeys")]
+    pub routing_keys: Vec<String>,
+    #[serde(default)]
+    pub types: Vec<String>,
+}
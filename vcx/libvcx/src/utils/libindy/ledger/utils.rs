@@ -1,4 +1,5 @@
 use serde_json;
+use rust_base58::{FromBase58, ToBase58};
 use futures::Future;
 
 use super::request::Request;
@@ -11,8 +12,11 @@ use crate::error::prelude::*;
 use crate::utils::libindy::vdr::VDRInfo;
 use crate::utils::libindy::ledger::types::Transaction;
 use crate::utils::libindy::ledger::types::Response as TransactionResponse;
+use crate::utils::libindy::ledger::types::EndpointAttrib;
 use crate::utils::qualifier;
 
+const ENDPOINT_ATTRIB: &str = "endpoint";
+
 pub fn publish_cred_def(cred_def_json: &str) -> VcxResult<String> {
     if settings::indy_mocks_enabled() { return Ok(String::new()); }
 
@@ -147,6 +151,73 @@ pub fn get_role(did: &str) -> VcxResult<String> {
     Ok(role)
 }
 
+/// Resolves the verkey written for `did` on the Ledger of `namespace` (the default one if not set).
+/// Abbreviated verkey (`~` prefixed) is expanded into the full one.
+pub fn get_verkey(namespace: Option<&str>, did: &str) -> VcxResult<String> {
+    if settings::indy_mocks_enabled() { return Ok(settings::DEFAULT_VERKEY.to_string()); }
+
+    let get_nym_req = Request::get_nym(None, &did)?;
+    let get_nym_resp = _submit(namespace, &get_nym_req)?;
+
+    let get_nym_resp: serde_json::Value = serde_json::from_str(&get_nym_resp)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                          format!("Could not parse Ledger response for GET_NYM. Err: {:?}", err)))?;
+
+    let data: serde_json::Value = serde_json::from_str(&get_nym_resp["result"]["data"].as_str().unwrap_or("{}"))
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                          format!("Could not parse Ledger response for GET_NYM. Err: {:?}", err)))?;
+
+    let verkey = data["verkey"].as_str()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                  format!("Verkey is not found on the Ledger for DID: {}", did)))?;
+
+    if !verkey.starts_with('~') {
+        return Ok(verkey.to_string());
+    }
+
+    let unqualified_did = did.rsplit(':').next().unwrap_or(did);
+
+    let mut full_verkey = unqualified_did.from_base58()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidDid, format!("DID is not base58 encoded: {}", did)))?;
+    full_verkey.extend(verkey[1..].from_base58()
+        .map_err(|_| VcxError::from_msg(VcxErrorKind::InvalidVerkey, format!("Verkey is not base58 encoded: {}", verkey)))?);
+
+    Ok(full_verkey.to_base58())
+}
+
+/// Resolves the `endpoint` ATTRIB written for `did` on the Ledger of `namespace` (the default one if not set).
+pub fn get_endpoint(namespace: Option<&str>, did: &str) -> VcxResult<EndpointAttrib> {
+    if settings::indy_mocks_enabled() {
+        return Ok(EndpointAttrib { endpoint: settings::DEFAULT_URL.to_string(), ..EndpointAttrib::default() });
+    }
+
+    let get_attrib_req = Request::get_attrib(None, did, ENDPOINT_ATTRIB)?;
+    let get_attrib_resp = _submit(namespace, &get_attrib_req)?;
+
+    let get_attrib_resp: serde_json::Value = serde_json::from_str(&get_attrib_resp)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                          format!("Could not parse Ledger response for GET_ATTRIB. Err: {:?}", err)))?;
+
+    let data = get_attrib_resp["result"]["data"].as_str()
+        .ok_or(VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                  format!("Endpoint is not found on the Ledger for DID: {}", did)))?;
+
+    let mut data: serde_json::Value = serde_json::from_str(data)
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                          format!("Could not parse Ledger response for GET_ATTRIB. Err: {:?}", err)))?;
+
+    serde_json::from_value(data[ENDPOINT_ATTRIB].take())
+        .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidLedgerResponse,
+                                          format!("Could not parse endpoint written on the Ledger for DID: {}. Err: {:?}", did, err)))
+}
+
+fn _submit(namespace: Option<&str>, request_json: &str) -> VcxResult<String> {
+    match namespace {
+        Some(namespace) => Request::submit_to(namespace, request_json),
+        None => Request::submit(request_json)
+    }
+}
+
 pub fn endorse_transaction(transaction_json: &str) -> VcxResult<()> {
     debug!("Ledger endorsing transaction");
 
@@ -1,4 +1,5 @@
 use futures::Future;
+use serde_json::Value;
 use crate::indy::{wallet, ErrorCode};
 use crate::settings;
 use crate::error::prelude::*;
@@ -50,6 +51,8 @@ impl RestoreWalletConfigs {
     }
 }
 
+const SEARCH_BATCH_SIZE: usize = 100;
+
 pub static mut WALLET_HANDLE: WalletHandle = INVALID_WALLET_HANDLE;
 
 pub fn set_wallet_handle(handle: WalletHandle) -> WalletHandle {
@@ -278,6 +281,36 @@ pub fn close_search(search_handle: i32) -> VcxResult<()> {
         .map_err(VcxError::from)
 }
 
+/// Returns all records of `xtype` type matching WQL `query`.
+/// The records are fetched by batches of `SEARCH_BATCH_SIZE`, the search is closed afterwards.
+pub fn search_records(xtype: &str, query: &str, options: &str) -> VcxResult<Vec<Value>> {
+    trace!("search_records >>> xtype: {}, query: {}, options: {}", secret!(&xtype), secret!(&query), secret!(&options));
+
+    let search_handle = open_search(xtype, query, options)?;
+
+    let result = _fetch_all(search_handle);
+    close_search(search_handle)?;
+    result
+}
+
+fn _fetch_all(search_handle: i32) -> VcxResult<Vec<Value>> {
+    let mut records = Vec::new();
+
+    loop {
+        let batch = search_next_records(search_handle, SEARCH_BATCH_SIZE)?;
+
+        let batch: Value = ::serde_json::from_str(&batch)
+            .map_err(|err| VcxError::from_msg(VcxErrorKind::InvalidJson, format!("Cannot deserialize wallet search result: {}", err)))?;
+
+        match batch["records"].as_array() {
+            Some(batch) if !batch.is_empty() => records.extend(batch.iter().cloned()),
+            _ => break
+        }
+    }
+
+    Ok(records)
+}
+
 pub fn export(wallet_handle: WalletHandle, path: &str, backup_key: &str) -> VcxResult<()> {
     trace!("export >>> wallet_handle: {:?}, path: {:?}, backup_key: {}", wallet_handle, secret!(path), secret!(backup_key));
 
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::marker::PhantomData;
 use std::cmp;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
 
 // NOTE: we manually implement standard traits to get around
 // limitations of derive macros (the bounds on T are too strict)
@@ -82,8 +83,10 @@ impl<T> From<u32> for Handle<T> {
     }
 }
 
+/// Objects are locked one by one: the cache itself is not locked while a closure is running,
+/// so closures can use the cache (e.g. add objects), and updates of the same object are serialized.
 pub struct ObjectCache<T> {
-    store: DashMap<u32, T>,
+    store: DashMap<u32, Arc<RwLock<T>>>,
 }
 
 impl<T> Default for ObjectCache<T> {
@@ -94,39 +97,53 @@ impl<T> Default for ObjectCache<T> {
     }
 }
 
+// a closure panicked while holding the object lock: the object is used as it is, as the cache did before
+fn _read<T>(obj: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    obj.read().unwrap_or_else(|err| err.into_inner())
+}
+
+fn _write<T>(obj: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    obj.write().unwrap_or_else(|err| err.into_inner())
+}
+
 impl<T> ObjectCache<T> {
     pub fn has_handle(&self, handle: Handle<T>) -> bool {
         self.store.contains_key(&handle.into())
     }
 
+    fn _get(&self, handle: Handle<T>) -> VcxResult<Arc<RwLock<T>>> {
+        self.store
+            .get(&handle.into())
+            .map(|entry| entry.value().clone())
+            .ok_or_else(|| VcxError::from_msg(
+                VcxErrorKind::InvalidHandle,
+                format!("Object not found for handle: {}", handle),
+            ))
+    }
+
+    // copies the stored entries, so the cache is not locked while the objects are accessed
+    fn _entries(&self) -> Vec<(u32, Arc<RwLock<T>>)> {
+        self.store.iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect()
+    }
+
     pub fn get<F, R>(&self, handle: Handle<T>, closure: F) -> VcxResult<R>
     where
         F: FnOnce(&T) -> VcxResult<R>,
     {
-        closure(
-            self.store
-                .get(&handle.into())
-                .ok_or_else(|| VcxError::from_msg(
-                    VcxErrorKind::InvalidHandle,
-                    format!("Object not found for handle: {}", handle),
-                ))?
-                .value(),
-        )
+        let obj = self._get(handle)?;
+        let obj = _read(&obj);
+        closure(&obj)
     }
 
     pub fn get_mut<F, R>(&self, handle: Handle<T>, closure: F) -> VcxResult<R>
     where
         F: FnOnce(&mut T) -> VcxResult<R>,
     {
-        closure(
-            self.store
-                .get_mut(&handle.into())
-                .ok_or_else(|| VcxError::from_msg(
-                    VcxErrorKind::InvalidHandle,
-                    format!("Object not found for handle: {}", handle),
-                ))?
-                .value_mut(),
-        )
+        let obj = self._get(handle)?;
+        let mut obj = _write(&obj);
+        closure(&mut obj)
     }
 
     pub fn add(&self, obj: T) -> VcxResult<Handle<T>> {
@@ -135,7 +152,7 @@ impl<T> ObjectCache<T> {
             // use the Entry API to avoid calculating the hash twice
             if let Entry::Vacant(v) = self.store.entry(rng.gen()) {
                 let handle = Handle(*v.key(), PhantomData);
-                v.insert(obj);
+                v.insert(Arc::new(RwLock::new(obj)));
                 // TODO: decide if this needs to return Result since this is infallible
                 // TODO: is it okay for a handle to be zero?
                 return Ok(handle);
@@ -146,7 +163,7 @@ impl<T> ObjectCache<T> {
     pub fn insert(&self, handle: Handle<T>, obj: T) -> VcxResult<()> {
         // TODO: decide if we should keep returning an error since
         // DashMap doesn't return a Result (infalliable)
-        self.store.insert(handle.into(), obj);
+        self.store.insert(handle.into(), Arc::new(RwLock::new(obj)));
         Ok(())
     }
 
@@ -161,6 +178,71 @@ impl<T> ObjectCache<T> {
         }
     }
 
+    /// Passes stored objects to `predicate` one by one until it returns `true`
+    /// and updates the accepted object with `closure`.
+    /// The object is locked from the check until the update is done, so concurrent `get_mut` calls are not lost.
+    /// Returns the handle of the accepted object.
+    pub fn find_mut<P, F>(&self, mut predicate: P, closure: F) -> VcxResult<Option<Handle<T>>>
+    where
+        P: FnMut(&T) -> bool,
+        F: FnOnce(&mut T) -> VcxResult<()>,
+    {
+        for (key, obj) in self._entries() {
+            let mut obj = _write(&obj);
+
+            if predicate(&obj) {
+                closure(&mut obj)?;
+                return Ok(Some(Handle(key, PhantomData)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns handles of all stored objects accepted by `closure`.
+    pub fn filter<F>(&self, mut closure: F) -> VcxResult<Vec<Handle<T>>>
+    where
+        F: FnMut(&T) -> VcxResult<bool>,
+    {
+        let mut handles = Vec::new();
+
+        for (key, obj) in self._entries() {
+            if closure(&_read(&obj))? {
+                handles.push(Handle(key, PhantomData));
+            }
+        }
+        Ok(handles)
+    }
+
+    /// Like `filter`, but skips the objects which are being updated at the moment instead of waiting for them,
+    /// so it can be called while the caller holds an object of the same cache.
+    pub fn filter_unlocked<F>(&self, mut closure: F) -> VcxResult<Vec<Handle<T>>>
+    where
+        F: FnMut(&T) -> VcxResult<bool>,
+    {
+        let mut handles = Vec::new();
+
+        for (key, obj) in self._entries() {
+            let obj = match obj.try_read() {
+                Ok(obj) => obj,
+                Err(TryLockError::Poisoned(err)) => err.into_inner(),
+                Err(TryLockError::WouldBlock) => continue
+            };
+
+            if closure(&obj)? {
+                handles.push(Handle(key, PhantomData));
+            }
+        }
+        Ok(handles)
+    }
+
+    /// Returns handles of all stored objects.
+    pub fn handles(&self) -> Vec<Handle<T>> {
+        self.store.iter()
+            .map(|entry| Handle(*entry.key(), PhantomData))
+            .collect()
+    }
+
     pub fn drain(&self) -> VcxResult<()> {
         // TODO: decide if we should keep returning an error since
         // DashMap doesn't return a Result (infalliable)
@@ -225,6 +307,103 @@ mod tests {
         assert_eq!("TEST", string);
     }
 
+    #[test]
+    fn find_mut_test() {
+        let _setup = SetupDefaults::init();
+
+        let test: ObjectCache<String> = Default::default();
+        test.add(String::from("first")).unwrap();
+        let handle = test.add(String::from("second")).unwrap();
+
+        let found = test.find_mut(|obj| obj.as_str() == "second", |obj| {
+            obj.push_str("_found");
+            Ok(())
+        }).unwrap();
+
+        assert_eq!(Some(handle), found);
+        assert_eq!("second_found", test.get(handle, |obj| Ok(obj.clone())).unwrap());
+        assert_eq!(None, test.find_mut(|obj| obj.as_str() == "third", |_| Ok(())).unwrap());
+
+        // the cache is not locked while the object is updated
+        let found = test.find_mut(|obj| obj.as_str() == "first", |obj| {
+            test.add(String::from("third")).unwrap();
+            obj.push_str("_found");
+            Ok(())
+        }).unwrap();
+        assert!(found.is_some());
+        assert!(test.find_mut(|obj| obj.as_str() == "first_found", |_| Ok(())).unwrap().is_some());
+    }
+
+    #[test]
+    fn find_mut_does_not_lose_concurrent_updates() {
+        let _setup = SetupDefaults::init();
+
+        for i in 0..200 {
+            let test_str = format!("TEST_FIND_{}", i);
+            let handle = TEST_CACHE.add(test_str.clone()).unwrap();
+
+            let expected = test_str.clone();
+            let t1 = thread::spawn(move || {
+                TEST_CACHE
+                    .find_mut(|s| s == &expected, |s| {
+                        thread::yield_now();
+                        s.insert_str(0, "FIND_");
+                        Ok(())
+                    })
+                    .unwrap()
+            });
+            let t2 = thread::spawn(move || {
+                TEST_CACHE
+                    .get_mut(handle, |s| {
+                        s.push_str("_GET");
+                        Ok(())
+                    })
+                    .unwrap()
+            });
+            let found = t1.join().unwrap();
+            t2.join().unwrap();
+
+            let value = TEST_CACHE.get(handle, |s| Ok(s.clone())).unwrap();
+            assert!(value.ends_with("_GET"), "update of get_mut is lost: {}", value);
+            // `find_mut` is either done before `get_mut` or finds the object no more
+            assert_eq!(found.is_some(), value.starts_with("FIND_"));
+        }
+    }
+
+    #[test]
+    fn filter_test() {
+        let _setup = SetupDefaults::init();
+
+        let test: ObjectCache<String> = Default::default();
+        let first = test.add(String::from("child_1")).unwrap();
+        let second = test.add(String::from("child_2")).unwrap();
+        test.add(String::from("parent")).unwrap();
+
+        let mut found = test.filter(|obj| Ok(obj.starts_with("child"))).unwrap();
+        found.sort_by_key(|handle| u32::from(*handle));
+
+        let mut expected = vec![first, second];
+        expected.sort_by_key(|handle| u32::from(*handle));
+
+        assert_eq!(expected, found);
+        assert!(test.filter(|obj| Ok(obj.as_str() == "other")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn filter_unlocked_skips_updated_objects() {
+        let _setup = SetupDefaults::init();
+
+        let test: ObjectCache<String> = Default::default();
+        let first = test.add(String::from("child_1")).unwrap();
+        let second = test.add(String::from("child_2")).unwrap();
+
+        let found = test.get_mut(first, |_| {
+            test.filter_unlocked(|obj| Ok(obj.starts_with("child")))
+        }).unwrap();
+
+        assert_eq!(vec![second], found);
+    }
+
     #[test]
     fn multi_thread_get() {
         for i in 0..2000 {
@@ -407,6 +407,10 @@ pub fn recover_dead_drop(vk: &str) -> VcxResult<CloudAddress> {
 impl Handle<WalletBackup> {
     pub fn is_valid_handle(self) -> bool { WALLET_BACKUP_MAP.has_handle(self) }
 
+    pub fn release(self) -> VcxResult<()> {
+        WALLET_BACKUP_MAP.release(self)
+    }
+
     pub fn get_state(self) -> u32 {
         WALLET_BACKUP_MAP.get(self, |wb| {
             debug!("get state for wallet_backup {}", wb.get_source_id());
@@ -468,6 +472,11 @@ pub fn from_string(wallet_backup_data: &str) -> VcxResult<Handle<WalletBackup>>
 
     Ok(new_handle)
 }
+
+pub fn all_handles() -> Vec<Handle<WalletBackup>> {
+    WALLET_BACKUP_MAP.handles()
+}
+
 #[cfg(all(test, feature = "wallet_backup"))]
 pub mod tests {
     use super::*;